@@ -65,7 +65,8 @@ pub trait CodeStorage {
     fn exists(code_id: CodeId) -> bool {
         Self::InstrumentedCodeStorage::contains_key(&code_id)
     }
-    /// Returns true if the code associated with given id was removed.
+    /// Returns true if the code associated with given id was removed
+    /// along with its original code and metadata.
     ///
     /// If there is no code for the given id then false is returned.
     fn remove_code(code_id: CodeId) -> bool {
@@ -74,6 +75,9 @@ pub trait CodeStorage {
                 return false;
             }
 
+            Self::OriginalCodeStorage::remove(code_id);
+            Self::MetadataStorage::remove(code_id);
+
             *maybe = None;
             true
         })
@@ -30,6 +30,8 @@ pub use code_storage::{CodeStorage, Error as CodeStorageError};
 
 pub mod gas_provider;
 
+pub mod migration;
+
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking;
 
@@ -41,8 +43,10 @@ use frame_support::{
     weights::{IdentityFee, Weight, WeightToFee},
 };
 use gear_core::{
-    ids::{CodeId, MessageId, ProgramId},
+    counters::CounterKey,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Error as MemoryError, PageBuf, PageNumber, WasmPageNumber},
+    message::DispatchKind,
 };
 use primitive_types::H256;
 use scale_info::TypeInfo;
@@ -60,6 +64,15 @@ pub use gas_provider::{Provider as GasProvider, Tree as GasTree};
 pub const STORAGE_PROGRAM_PREFIX: &[u8] = b"g::prog::";
 pub const STORAGE_PROGRAM_PAGES_PREFIX: &[u8] = b"g::pages::";
 pub const STORAGE_PROGRAM_STATE_WAIT_PREFIX: &[u8] = b"g::prog_wait::";
+pub const STORAGE_PROGRAM_DELAYED_PREFIX: &[u8] = b"g::prog_delayed::";
+pub const STORAGE_PROGRAM_VALUE_RESERVATIONS_PREFIX: &[u8] = b"g::prog_reservations::";
+pub const STORAGE_PROGRAM_CHILDREN_COUNT_PREFIX: &[u8] = b"g::prog_children::";
+pub const STORAGE_PROGRAM_CHILDREN_GAS_PREFIX: &[u8] = b"g::prog_children_gas::";
+pub const STORAGE_PROGRAM_COUNTERS_PREFIX: &[u8] = b"g::prog_counters::";
+pub const STORAGE_PROGRAM_ENTRY_BUDGETS_PREFIX: &[u8] = b"g::prog_budgets::";
+pub const STORAGE_PROGRAM_CHARGE_ALLOWANCE_PREFIX: &[u8] = b"g::prog_charge_allowance::";
+pub const STORAGE_PROGRAM_REPLY_WAKERS_PREFIX: &[u8] = b"g::prog_reply_wakers::";
+pub const STORAGE_PROGRAM_REPLY_WAITING_PREFIX: &[u8] = b"g::prog_reply_waiting::";
 
 pub type ExitCode = i32;
 
@@ -397,6 +410,21 @@ pub fn get_program_data_for_pages_optional(
     Ok(pages_data)
 }
 
+/// Iterates over the ids of all the programs in storage.
+pub fn program_ids() -> impl Iterator<Item = H256> {
+    let mut previous = STORAGE_PROGRAM_PREFIX.to_vec();
+
+    core::iter::from_fn(move || {
+        let key = sp_io::storage::next_key(&previous)
+            .filter(|key| key.starts_with(STORAGE_PROGRAM_PREFIX))?;
+        let id = H256::decode(&mut &key[STORAGE_PROGRAM_PREFIX.len()..])
+            .expect("keys encoded correctly");
+        previous = key;
+
+        Some(id)
+    })
+}
+
 pub fn set_program(id: H256, program: ActiveProgram) {
     log::trace!("set program with id = {}", id);
     sp_io::storage::set(&program_key(id), &Program::Active(program).encode());
@@ -471,9 +499,300 @@ pub fn waiting_init_take_messages(dest_prog_id: ProgramId) -> Vec<MessageId> {
     messages.unwrap_or_default()
 }
 
+pub fn delayed_dispatches_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_DELAYED_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+pub fn delayed_dispatch_key(prog_id: ProgramId, message_id: MessageId) -> Vec<u8> {
+    let mut key = delayed_dispatches_key(prog_id);
+    message_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns block number the dispatch delayed by the program is scheduled for.
+pub fn get_delayed_dispatch(prog_id: ProgramId, message_id: MessageId) -> Option<u32> {
+    let key = delayed_dispatch_key(prog_id, message_id);
+
+    sp_io::storage::get(&key).and_then(|v| u32::decode(&mut &v[..]).ok())
+}
+
+pub fn delayed_dispatches_insert(prog_id: ProgramId, message_id: MessageId, block_number: u32) {
+    let key = delayed_dispatch_key(prog_id, message_id);
+
+    sp_io::storage::set(&key, &block_number.encode());
+}
+
+pub fn delayed_dispatches_remove(prog_id: ProgramId, message_id: MessageId) {
+    sp_io::storage::clear(&delayed_dispatch_key(prog_id, message_id));
+}
+
+/// Forgets all the dispatches delayed by the program.
+pub fn clear_delayed_dispatches(prog_id: ProgramId) {
+    sp_io::storage::clear_prefix(&delayed_dispatches_key(prog_id), None);
+}
+
+pub fn reply_wakers_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_REPLY_WAKERS_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+pub fn reply_waker_key(prog_id: ProgramId, reply_to: MessageId) -> Vec<u8> {
+    let mut key = reply_wakers_key(prog_id);
+    reply_to.encode_to(&mut key);
+
+    key
+}
+
+/// Returns message of the program waiting for the reply to `reply_to`.
+pub fn get_reply_waker(prog_id: ProgramId, reply_to: MessageId) -> Option<MessageId> {
+    let key = reply_waker_key(prog_id, reply_to);
+
+    sp_io::storage::get(&key).and_then(|v| MessageId::decode(&mut &v[..]).ok())
+}
+
+pub fn reply_waiting_key(prog_id: ProgramId, waiting: MessageId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_REPLY_WAITING_PREFIX);
+    prog_id.encode_to(&mut key);
+    waiting.encode_to(&mut key);
+
+    key
+}
+
+/// Sets or removes message of the program waiting for the reply to `reply_to`.
+///
+/// The reply the message waits for is kept as well, so the entry
+/// is removed once the message leaves the waitlist.
+pub fn set_reply_waker(prog_id: ProgramId, reply_to: MessageId, waiting: Option<MessageId>) {
+    let key = reply_waker_key(prog_id, reply_to);
+
+    match waiting {
+        Some(waiting) => {
+            sp_io::storage::set(&key, &waiting.encode());
+            sp_io::storage::set(&reply_waiting_key(prog_id, waiting), &reply_to.encode());
+        }
+        None => {
+            if let Some(waiting) = get_reply_waker(prog_id, reply_to) {
+                sp_io::storage::clear(&reply_waiting_key(prog_id, waiting));
+            }
+
+            sp_io::storage::clear(&key);
+        }
+    }
+}
+
+/// Forgets the reply the message of the program waits for, if any.
+pub fn clear_reply_waker_of(prog_id: ProgramId, waiting: MessageId) {
+    let key = reply_waiting_key(prog_id, waiting);
+
+    let reply_to = sp_io::storage::get(&key).and_then(|v| MessageId::decode(&mut &v[..]).ok());
+
+    if let Some(reply_to) = reply_to {
+        sp_io::storage::clear(&key);
+
+        if get_reply_waker(prog_id, reply_to) == Some(waiting) {
+            sp_io::storage::clear(&reply_waker_key(prog_id, reply_to));
+        }
+    }
+}
+
+/// Forgets all the messages of the program waiting for replies.
+pub fn clear_reply_wakers(prog_id: ProgramId) {
+    sp_io::storage::clear_prefix(&reply_wakers_key(prog_id), None);
+
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_REPLY_WAITING_PREFIX);
+    prog_id.encode_to(&mut key);
+    sp_io::storage::clear_prefix(&key, None);
+}
+
+pub fn value_reservations_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_VALUE_RESERVATIONS_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns value reservations of the program mapped to reserved values.
+pub fn get_value_reservations(prog_id: ProgramId) -> BTreeMap<ReservationId, u128> {
+    let key = value_reservations_key(prog_id);
+
+    sp_io::storage::get(&key)
+        .and_then(|v| BTreeMap::<ReservationId, u128>::decode(&mut &v[..]).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_value_reservations(prog_id: ProgramId, reservations: BTreeMap<ReservationId, u128>) {
+    let key = value_reservations_key(prog_id);
+
+    if reservations.is_empty() {
+        sp_io::storage::clear(&key);
+    } else {
+        sp_io::storage::set(&key, &reservations.encode());
+    }
+}
+
+pub fn children_count_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_CHILDREN_COUNT_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns number of programs created by the program over its lifetime.
+pub fn get_children_count(prog_id: ProgramId) -> u64 {
+    let key = children_count_key(prog_id);
+
+    sp_io::storage::get(&key)
+        .and_then(|v| u64::decode(&mut &v[..]).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_children_count(prog_id: ProgramId, children_count: u64) {
+    let key = children_count_key(prog_id);
+
+    if children_count == 0 {
+        sp_io::storage::clear(&key);
+    } else {
+        sp_io::storage::set(&key, &children_count.encode());
+    }
+}
+
+pub fn children_gas_burned_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_CHILDREN_GAS_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns gas burned by initializations of programs created by the program.
+pub fn get_children_gas_burned(prog_id: ProgramId) -> u64 {
+    let key = children_gas_burned_key(prog_id);
+
+    sp_io::storage::get(&key)
+        .and_then(|v| u64::decode(&mut &v[..]).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_children_gas_burned(prog_id: ProgramId, gas_burned: u64) {
+    let key = children_gas_burned_key(prog_id);
+
+    if gas_burned == 0 {
+        sp_io::storage::clear(&key);
+    } else {
+        sp_io::storage::set(&key, &gas_burned.encode());
+    }
+}
+
+pub fn counters_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_COUNTERS_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns persistent counters of the program mapped to their values.
+pub fn get_counters(prog_id: ProgramId) -> BTreeMap<CounterKey, u64> {
+    let key = counters_key(prog_id);
+
+    sp_io::storage::get(&key)
+        .and_then(|v| BTreeMap::<CounterKey, u64>::decode(&mut &v[..]).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_counters(prog_id: ProgramId, counters: BTreeMap<CounterKey, u64>) {
+    let key = counters_key(prog_id);
+
+    if counters.is_empty() {
+        sp_io::storage::clear(&key);
+    } else {
+        sp_io::storage::set(&key, &counters.encode());
+    }
+}
+
+pub fn entry_budgets_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_ENTRY_BUDGETS_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns gas budgets of the program's entry points.
+pub fn get_entry_budgets(prog_id: ProgramId) -> BTreeMap<DispatchKind, u64> {
+    let key = entry_budgets_key(prog_id);
+
+    sp_io::storage::get(&key)
+        .and_then(|v| BTreeMap::<DispatchKind, u64>::decode(&mut &v[..]).ok())
+        .unwrap_or_default()
+}
+
+/// Returns gas budget of the program's entry point.
+pub fn get_entry_budget(prog_id: ProgramId, kind: DispatchKind) -> Option<u64> {
+    get_entry_budgets(prog_id).get(&kind).copied()
+}
+
+pub fn set_entry_budgets(prog_id: ProgramId, entry_budgets: BTreeMap<DispatchKind, u64>) {
+    let key = entry_budgets_key(prog_id);
+
+    if entry_budgets.is_empty() {
+        sp_io::storage::clear(&key);
+    } else {
+        sp_io::storage::set(&key, &entry_budgets.encode());
+    }
+}
+
+pub fn charge_allowance_key(prog_id: ProgramId) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend(STORAGE_PROGRAM_CHARGE_ALLOWANCE_PREFIX);
+    prog_id.encode_to(&mut key);
+
+    key
+}
+
+/// Returns gas the program still agrees to pay for messages sent to it.
+pub fn get_charge_allowance(prog_id: ProgramId) -> u64 {
+    let key = charge_allowance_key(prog_id);
+
+    sp_io::storage::get(&key)
+        .and_then(|v| u64::decode(&mut &v[..]).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_charge_allowance(prog_id: ProgramId, allowance: u64) {
+    let key = charge_allowance_key(prog_id);
+
+    if allowance == 0 {
+        sp_io::storage::clear(&key);
+    } else {
+        sp_io::storage::set(&key, &allowance.encode());
+    }
+}
+
 pub fn reset_storage() {
     sp_io::storage::clear_prefix(STORAGE_PROGRAM_PREFIX, None);
     sp_io::storage::clear_prefix(STORAGE_PROGRAM_PAGES_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_DELAYED_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_VALUE_RESERVATIONS_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_CHILDREN_COUNT_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_CHILDREN_GAS_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_COUNTERS_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_ENTRY_BUDGETS_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_CHARGE_ALLOWANCE_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_REPLY_WAKERS_PREFIX, None);
+    sp_io::storage::clear_prefix(STORAGE_PROGRAM_REPLY_WAITING_PREFIX, None);
 
     // TODO: Remove this legacy after next runtime upgrade.
     sp_io::storage::clear_prefix(b"g::wait::", None);
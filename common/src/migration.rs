@@ -0,0 +1,101 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Layouts of the stored types from previous storage versions.
+//!
+//! Shared by pallets' migrations, which read entries stored before
+//! the layout change and translate them into the current types.
+
+/// Layouts, which messages were stored with before metadata
+/// and reply destination were added.
+pub mod v1 {
+    use codec::{Decode, Encode};
+    use gear_core::{
+        ids::{MessageId, ProgramId},
+        message::{
+            ContextStore as CurrentContextStore, DispatchKind, Payload, ReplyDetails,
+            StoredDispatch as CurrentStoredDispatch, StoredMessage as CurrentStoredMessage, Value,
+        },
+    };
+    use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+
+    /// Stored message without metadata and reply destination.
+    #[derive(Clone, Debug, Decode, Encode)]
+    pub struct StoredMessage {
+        pub id: MessageId,
+        pub source: ProgramId,
+        pub destination: ProgramId,
+        pub payload: Payload,
+        #[codec(compact)]
+        pub value: Value,
+        pub reply: Option<ReplyDetails>,
+    }
+
+    impl From<StoredMessage> for CurrentStoredMessage {
+        fn from(message: StoredMessage) -> Self {
+            CurrentStoredMessage::new(
+                message.id,
+                message.source,
+                message.destination,
+                message.payload,
+                message.value,
+                message.reply,
+            )
+        }
+    }
+
+    /// Context store without handles counter and reservation nonce.
+    #[derive(Clone, Debug, Decode, Encode)]
+    pub struct ContextStore {
+        pub outgoing: BTreeMap<u32, Option<Payload>>,
+        pub reply: Option<Payload>,
+        pub initialized: BTreeSet<ProgramId>,
+        pub awaken: BTreeSet<MessageId>,
+        pub reply_sent: bool,
+    }
+
+    impl From<ContextStore> for CurrentContextStore {
+        fn from(context: ContextStore) -> Self {
+            // Handles counter and reservation nonce are the last fields of
+            // the current layout, so the old encoding followed by zeroes is
+            // the new one.
+            let encoded = (context, 0u32, 0u64).encode();
+
+            CurrentContextStore::decode(&mut encoded.as_ref())
+                .expect("Current layout only appends handles counter and reservation nonce; qed")
+        }
+    }
+
+    /// Stored dispatch of the message without metadata and reply destination.
+    #[derive(Clone, Debug, Decode, Encode)]
+    pub struct StoredDispatch {
+        pub kind: DispatchKind,
+        pub message: StoredMessage,
+        pub context: Option<ContextStore>,
+    }
+
+    impl From<StoredDispatch> for CurrentStoredDispatch {
+        fn from(dispatch: StoredDispatch) -> Self {
+            CurrentStoredDispatch::new(
+                dispatch.kind,
+                dispatch.message.into(),
+                dispatch.context.map(Into::into),
+            )
+        }
+    }
+}
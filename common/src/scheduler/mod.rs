@@ -69,6 +69,12 @@ pub trait Scheduler {
         > + CountedByKey<Key = Self::BlockNumber, Length = usize>
         + KeyIterableByKeyMap<Key1 = Self::BlockNumber, Key2 = Self::Task>;
 
+    /// Tells whether stored tasks are migrated over several blocks.
+    ///
+    /// Tasks can't be read until the migration is over,
+    /// so they shouldn't be processed.
+    fn migrating() -> bool;
+
     /// Resets all related to messenger storages.
     ///
     /// It's a temporary production solution to avoid DB migrations
@@ -50,6 +50,10 @@ pub enum ScheduledTask<AccountId> {
 
     /// Delayed message sending.
     SendDispatch(StoredDispatch),
+
+    /// Exit of the program scheduled by itself, with the inheritor
+    /// of its value.
+    ExitProgram(ProgramId, ProgramId),
 }
 
 impl<AccountId> ScheduledTask<AccountId> {
@@ -68,6 +72,7 @@ impl<AccountId> ScheduledTask<AccountId> {
             RemovePausedProgram(program_id) => handler.remove_paused_program(program_id),
             WakeMessage(program_id, message_id) => handler.wake_message(program_id, message_id),
             SendDispatch(dispatch) => handler.send_dispatch(dispatch),
+            ExitProgram(program_id, inheritor) => handler.exit_program(program_id, inheritor),
         }
     }
 }
@@ -94,4 +99,7 @@ pub trait TaskHandler<AccountId> {
 
     // Send delayed message action.
     fn send_dispatch(&mut self, dispatch: StoredDispatch);
+
+    /// Scheduled exit of the program action.
+    fn exit_program(&mut self, program_id: ProgramId, inheritor: ProgramId);
 }
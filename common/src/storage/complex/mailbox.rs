@@ -22,8 +22,8 @@
 //! addressed to users.
 
 use crate::storage::{
-    Callback, CountedByKey, DoubleMapStorage, GetCallback, Interval, IterableByKeyMap, IterableMap,
-    KeyFor,
+    Callback, CountedByKey, DoubleMapStorage, GetCallback, Interval, IterableByKeyFromMap,
+    IterableByKeyMap, IterableMap, KeyFor,
 };
 use core::marker::PhantomData;
 
@@ -49,6 +49,9 @@ pub trait Mailbox {
     /// Returns bool, defining does first key's mailbox contain second key.
     fn contains(key1: &Self::Key1, key2: &Self::Key2) -> bool;
 
+    /// Returns value from mailbox by given keys, if present.
+    fn peek(key1: &Self::Key1, key2: &Self::Key2) -> Option<Self::Value>;
+
     /// Inserts given value in mailbox.
     fn insert(value: Self::Value, bn: Self::BlockNumber) -> Result<(), Self::OutputError>;
 
@@ -132,6 +135,10 @@ where
         T::contains_keys(user_id, message_id)
     }
 
+    fn peek(user_id: &Self::Key1, message_id: &Self::Key2) -> Option<Self::Value> {
+        T::get(user_id, message_id).map(|(message, _)| message)
+    }
+
     fn insert(
         message: Self::Value,
         scheduled_at: Self::BlockNumber,
@@ -240,3 +247,24 @@ where
         T::iter()
     }
 }
+
+// Implementation of `IterableByKeyFromMap` trait for `MailboxImpl` in case,
+// when inner `DoubleMapStorage` implements `IterableByKeyFromMap`.
+impl<T, Value, BlockNumber, Error, OutputError, Callbacks, KeyGen> IterableByKeyFromMap<T::Value>
+    for MailboxImpl<T, Value, BlockNumber, Error, OutputError, Callbacks, KeyGen>
+where
+    T: DoubleMapStorage<Value = ValueWithInterval<Value, BlockNumber>>
+        + IterableByKeyFromMap<T::Value, Key1 = T::Key1, Key2 = T::Key2>,
+    Error: MailboxError,
+    OutputError: From<Error>,
+    Callbacks: MailboxCallbacks<OutputError, Value = Value, BlockNumber = BlockNumber>,
+    KeyGen: KeyFor<Key = (T::Key1, T::Key2), Value = Value>,
+{
+    type Key1 = T::Key1;
+    type Key2 = T::Key2;
+    type Iter = T::Iter;
+
+    fn iter_key_from(key1: Self::Key1, key2: Option<Self::Key2>) -> Self::Iter {
+        T::iter_key_from(key1, key2)
+    }
+}
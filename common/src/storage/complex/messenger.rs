@@ -21,8 +21,8 @@
 //! Messenger provides API for all available gear message storing.
 
 use crate::storage::{
-    Counted, CountedByKey, Counter, DequeueError, Interval, IterableByKeyMap, IterableMap, Mailbox,
-    MailboxError, Queue, Toggler, Waitlist, WaitlistError,
+    Counted, CountedByKey, Counter, DequeueError, Interval, IterableByKeyFromMap, IterableByKeyMap,
+    IterableFromMap, IterableMap, Mailbox, MailboxError, Queue, Toggler, Waitlist, WaitlistError,
 };
 use core::fmt::Debug;
 
@@ -55,6 +55,10 @@ pub trait Messenger {
     ///
     /// Present to clarify compiler behavior over associated types.
     type MailboxedMessage;
+    /// Key of the queue storage.
+    ///
+    /// Present to clarify compiler behavior over associated types.
+    type QueueKey;
     /// Stored values type for `Self::Queue`.
     ///
     /// Present to clarify compiler behavior over associated types.
@@ -99,7 +103,8 @@ pub trait Messenger {
     /// function, except case of runtime upgrade - then processing skipped.
     type Queue: Queue<Value = Self::QueuedDispatch, Error = Self::Error, OutputError = Self::OutputError>
         + Counted<Length = Self::Capacity>
-        + IterableMap<Result<Self::QueuedDispatch, Self::OutputError>>;
+        + IterableMap<Result<Self::QueuedDispatch, Self::OutputError>>
+        + IterableFromMap<Result<Self::QueuedDispatch, Self::OutputError>, Key = Self::QueueKey>;
 
     /// Gear mailbox.
     ///
@@ -121,6 +126,10 @@ pub trait Messenger {
         + IterableByKeyMap<
             (Self::MailboxedMessage, Interval<Self::BlockNumber>),
             Key = Self::MailboxFirstKey,
+        > + IterableByKeyFromMap<
+            (Self::MailboxedMessage, Interval<Self::BlockNumber>),
+            Key1 = Self::MailboxFirstKey,
+            Key2 = Self::MailboxSecondKey,
         >;
 
     /// Gear waitlist.
@@ -161,6 +170,12 @@ pub trait Messenger {
             Key = Self::WaitlistFirstKey,
         >;
 
+    /// Tells whether stored messages are migrated over several blocks.
+    ///
+    /// Messages can't be read until the migration is over,
+    /// so neither messages nor tasks should be processed.
+    fn migrating() -> bool;
+
     /// Resets all related to messenger storages.
     ///
     /// It's a temporary production solution to avoid DB migrations
@@ -21,8 +21,8 @@
 //! Message queue provides functionality of storing messages,
 //! addressed to programs.
 
-use crate::storage::{Counted, Dequeue, DequeueError, IterableMap, KeyFor};
-use core::marker::PhantomData;
+use crate::storage::{Counted, Dequeue, DequeueError, IterableFromMap, IterableMap, KeyFor};
+use core::{iter::Map, marker::PhantomData};
 
 /// Represents message queue managing logic.
 pub trait Queue {
@@ -172,3 +172,24 @@ where
         QueueIter(T::iter(), PhantomData::<OutputError>)
     }
 }
+
+// `IterableFromMap` implementation for `QueueImpl` in case,
+// when inner `Dequeue` implements `IterableFromMap`.
+impl<T, OutputError, KeyGen> IterableFromMap<Result<T::Value, OutputError>>
+    for QueueImpl<T, OutputError, KeyGen>
+where
+    T: Dequeue + IterableFromMap<Result<T::Value, T::Error>, Key = T::Key>,
+    OutputError: From<T::Error>,
+    KeyGen: KeyFor<Key = T::Key, Value = T::Value>,
+{
+    type Key = T::Key;
+    type Iter = Map<
+        <T as IterableFromMap<Result<T::Value, T::Error>>>::Iter,
+        fn(Result<T::Value, T::Error>) -> Result<T::Value, OutputError>,
+    >;
+
+    fn iter_from(key: Option<Self::Key>) -> Self::Iter {
+        let map_err: fn(_) -> _ = |res: Result<T::Value, T::Error>| res.map_err(Into::into);
+        T::iter_from(key).map(map_err)
+    }
+}
@@ -28,7 +28,9 @@
 //! - Inner map should contain values under keys, set in head and tail,
 //! if they present.
 
-use crate::storage::{Callback, Counted, EmptyCallback, IterableMap, MapStorage, ValueStorage};
+use crate::storage::{
+    Callback, Counted, EmptyCallback, IterableFromMap, IterableMap, MapStorage, ValueStorage,
+};
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
 use scale_info::TypeInfo;
@@ -427,3 +429,31 @@ where
         DequeueIter(HVS::get(), PhantomData::<(Error, HVS, TVS, MS)>)
     }
 }
+
+// `IterableFromMap` implementation for `DequeueImpl`, returning
+// iterator, presented with `DequeueIter`.
+//
+// If the given key isn't in the dequeue anymore, all the values
+// preceding it were removed as well, so iteration starts from the head.
+impl<Key, Value, Error, HVS, TVS, MS, Callbacks> IterableFromMap<Result<Value, Error>>
+    for DequeueImpl<Key, Value, Error, HVS, TVS, MS, Callbacks>
+where
+    Key: Clone + PartialEq,
+    Error: DequeueError,
+    HVS: ValueStorage<Value = Key>,
+    TVS: ValueStorage<Value = Key>,
+    MS: MapStorage<Key = Key, Value = LinkedNode<Key, Value>>,
+    Callbacks: DequeueCallbacks<Value = Value>,
+{
+    type Key = Key;
+    type Iter = DequeueIter<Key, Value, Error, HVS, TVS, MS>;
+
+    fn iter_from(key: Option<Self::Key>) -> Self::Iter {
+        let start = match key.and_then(|key| MS::get(&key)) {
+            Some(node) => node.next,
+            None => HVS::get(),
+        };
+
+        DequeueIter(start, PhantomData::<(Error, HVS, TVS, MS)>)
+    }
+}
@@ -84,6 +84,39 @@ pub trait KeyIterableByKeyMap {
     fn iter_prefix_keys(key: Self::Key1) -> Self::Iter;
 }
 
+/// Represents iterable logic for single key maps
+/// (Key -> Value), starting after the given key.
+///
+/// Items are returned in the same order as `IterableMap` does.
+pub trait IterableFromMap<Item> {
+    /// Map's key type.
+    type Key;
+    /// Getting iterator type.
+    type Iter: Iterator<Item = Item>;
+
+    /// Creates the getting iterator over map Items,
+    /// which keys follow the given one, or over all Items.
+    fn iter_from(key: Option<Self::Key>) -> Self::Iter;
+}
+
+/// Represents iterable logic for double key maps
+/// (Key1 -> Key2 -> Value), starting after the given second key.
+///
+/// Items are returned in the storage order of second keys,
+/// which is the order of keys for `Identity` hasher.
+pub trait IterableByKeyFromMap<Item> {
+    /// Map's first key type.
+    type Key1;
+    /// Map's second key type.
+    type Key2;
+    /// Getting iterator type.
+    type Iter: Iterator<Item = Item>;
+
+    /// Creates the getting iterator over double map Items of the first key,
+    /// which second keys follow the given one, or over all its Items.
+    fn iter_key_from(key1: Self::Key1, key2: Option<Self::Key2>) -> Self::Iter;
+}
+
 /// Transpose callback for getting first element of tuple.
 pub struct GetFirstPos;
 
@@ -36,8 +36,8 @@ pub use callback::{Callback, EmptyCallback, FallibleCallback, GetCallback, Trans
 pub use counted::{Counted, CountedByKey};
 pub use double_map::DoubleMapStorage;
 pub use iterable::{
-    GetFirstPos, GetSecondPos, GetThirdPos, IterableByKeyMap, IterableMap, IteratorWrap,
-    KeyIterableByKeyMap,
+    GetFirstPos, GetSecondPos, GetThirdPos, IterableByKeyFromMap, IterableByKeyMap,
+    IterableFromMap, IterableMap, IteratorWrap, KeyIterableByKeyMap,
 };
 pub use key::{KeyFor, MailboxKeyGen, QueueKeyGen, WaitlistKeyGen};
 pub use map::MapStorage;
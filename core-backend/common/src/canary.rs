@@ -0,0 +1,215 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Canaries placed around program's static data to detect wild writes.
+//!
+//! Rust programs keep static data right below `__heap_base`, while the
+//! allocator takes memory for the heap from pages allocated after static ones.
+//! So the rest of the last static page is never used by a well-behaved
+//! program, and canaries are placed at its boundaries: right after
+//! the static data and right before the heap start.
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use core::convert::TryFrom;
+use gear_core::memory::{Memory, WasmPageNumber};
+use scale_info::TypeInfo;
+
+// '__heap_base' export is emitted by the linker and kept by wasm-builder.
+pub const HEAP_BASE_EXPORT_NAME: &str = "__heap_base";
+
+/// Size of a single canary in bytes.
+pub const CANARY_SIZE: usize = 8;
+
+const CANARY: [u8; CANARY_SIZE] = 0x6361_6e61_7279_4745u64.to_le_bytes();
+
+/// Memory region guarded by a canary.
+#[derive(
+    Decode,
+    Encode,
+    TypeInfo,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    derive_more::Display,
+)]
+pub enum MemoryRegion {
+    /// End of the static data.
+    #[display(fmt = "static data")]
+    StaticData,
+    /// Start of the allocator's heap.
+    #[display(fmt = "heap start")]
+    HeapStart,
+}
+
+/// Canaries placed in program's memory.
+#[derive(Debug, Default)]
+pub struct MemoryCanaries {
+    // Guarded region, canary offset and memory data replaced by canary.
+    canaries: Vec<(MemoryRegion, usize, [u8; CANARY_SIZE])>,
+}
+
+impl MemoryCanaries {
+    /// Place canaries around static data ending at `heap_base`.
+    ///
+    /// Nothing is placed if `heap_base` is unknown or there is no room
+    /// for canaries in the last static page.
+    pub fn place(memory: &mut impl Memory, heap_base: Option<i32>) -> Self {
+        let mut canaries = Self::default();
+
+        let heap_base = match heap_base.and_then(|base| usize::try_from(base).ok()) {
+            Some(heap_base) => heap_base,
+            None => return canaries,
+        };
+
+        let page_size = WasmPageNumber::size();
+        let heap_start = (heap_base + page_size - 1) / page_size * page_size;
+
+        if heap_start.saturating_sub(heap_base) < 2 * CANARY_SIZE {
+            return canaries;
+        }
+
+        for (region, offset) in [
+            (MemoryRegion::StaticData, heap_base),
+            (MemoryRegion::HeapStart, heap_start - CANARY_SIZE),
+        ] {
+            let mut data = [0; CANARY_SIZE];
+
+            if memory.read(offset, &mut data).is_err() || memory.write(offset, &CANARY).is_err() {
+                log::debug!("Unable to place canary for {} at {:#x}", region, offset);
+                continue;
+            }
+
+            canaries.canaries.push((region, offset, data));
+        }
+
+        canaries
+    }
+
+    /// Check all canaries are intact, returning the first damaged region otherwise.
+    pub fn check(&self, memory: &impl Memory) -> Result<(), MemoryRegion> {
+        let mut data = [0; CANARY_SIZE];
+
+        for &(region, offset, _) in &self.canaries {
+            if memory.read(offset, &mut data).is_err() || data != CANARY {
+                return Err(region);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restore memory data replaced by canaries.
+    pub fn remove(self, memory: &mut impl Memory) {
+        for (region, offset, data) in self.canaries {
+            if memory.write(offset, &data).is_err() {
+                log::debug!("Unable to remove canary for {} at {:#x}", region, offset);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use gear_core::memory::{Error, HostPointer, PageNumber};
+
+    struct TestMemory(Vec<u8>);
+
+    impl Memory for TestMemory {
+        fn grow(&mut self, _pages: WasmPageNumber) -> Result<PageNumber, Error> {
+            unimplemented!()
+        }
+
+        fn size(&self) -> WasmPageNumber {
+            WasmPageNumber::new_from_addr(self.0.len())
+        }
+
+        fn write(&mut self, offset: usize, buffer: &[u8]) -> Result<(), Error> {
+            self.0
+                .get_mut(offset..offset + buffer.len())
+                .ok_or(Error::OutOfBounds)?
+                .copy_from_slice(buffer);
+            Ok(())
+        }
+
+        fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), Error> {
+            buffer.copy_from_slice(
+                self.0
+                    .get(offset..offset + buffer.len())
+                    .ok_or(Error::OutOfBounds)?,
+            );
+            Ok(())
+        }
+
+        fn data_size(&self) -> usize {
+            self.0.len()
+        }
+
+        unsafe fn get_buffer_host_addr_unsafe(&self) -> HostPointer {
+            unimplemented!()
+        }
+    }
+
+    fn new_memory() -> TestMemory {
+        TestMemory(vec![0xaa; WasmPageNumber::size()])
+    }
+
+    #[test]
+    fn canaries_detect_writes() {
+        let mut memory = new_memory();
+        let heap_base = 0x100;
+        let canaries = MemoryCanaries::place(&mut memory, Some(heap_base));
+        assert_eq!(canaries.check(&memory), Ok(()));
+
+        memory.write(heap_base as usize + 1, &[0]).unwrap();
+        assert_eq!(canaries.check(&memory), Err(MemoryRegion::StaticData));
+
+        let mut memory = new_memory();
+        let canaries = MemoryCanaries::place(&mut memory, Some(heap_base));
+        memory.write(WasmPageNumber::size() - 1, &[0]).unwrap();
+        assert_eq!(canaries.check(&memory), Err(MemoryRegion::HeapStart));
+    }
+
+    #[test]
+    fn canaries_removed() {
+        let mut memory = new_memory();
+        let canaries = MemoryCanaries::place(&mut memory, Some(0x100));
+        assert!(memory.0.iter().any(|&byte| byte != 0xaa));
+
+        canaries.remove(&mut memory);
+        assert!(memory.0.iter().all(|&byte| byte == 0xaa));
+    }
+
+    #[test]
+    fn no_room_for_canaries() {
+        let mut memory = new_memory();
+        let heap_base = WasmPageNumber::size() - CANARY_SIZE;
+        let canaries = MemoryCanaries::place(&mut memory, Some(heap_base as i32));
+        memory.write(heap_base, &[0]).unwrap();
+        assert_eq!(canaries.check(&memory), Ok(()));
+
+        let canaries = MemoryCanaries::place(&mut memory, None);
+        assert_eq!(canaries.check(&memory), Ok(()));
+    }
+}
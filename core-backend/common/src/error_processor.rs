@@ -16,8 +16,50 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use core::num::NonZeroU32;
 use gear_core_errors::ExtError;
 
+/// Length of the encoded error returned by fallible syscalls.
+///
+/// Zero means the call succeeded, any other value is the size of the
+/// [`ExtError`] which can be read afterwards with `gr_error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLen(u32);
+
+impl ErrorLen {
+    /// Length reported on success.
+    pub const fn ok() -> Self {
+        Self(0)
+    }
+
+    /// Length reported on failure.
+    pub const fn err(len: NonZeroU32) -> Self {
+        Self(len.get())
+    }
+
+    fn from_ext_error(err: &ExtError) -> Self {
+        let len = NonZeroU32::new(err.encoded_size() as u32)
+            .expect("encoded `ExtError` always has at least one byte of enum index");
+        Self::err(len)
+    }
+
+    /// Whether the length reports success.
+    pub fn is_ok(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Raw length, zero on success.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<ErrorLen> for i32 {
+    fn from(len: ErrorLen) -> Self {
+        len.0 as i32
+    }
+}
+
 pub struct ExtErrorProcessor<T> {
     inner: Result<T, ExtError>,
 }
@@ -36,26 +78,26 @@ impl<T> ExtErrorProcessor<T> {
         }
     }
 
-    pub fn error_len_on_success<F, E>(self, f: F) -> Result<u32, E>
+    pub fn error_len_on_success<F, E>(self, f: F) -> Result<ErrorLen, E>
     where
         F: FnOnce(T) -> Result<(), E>,
     {
         match self.inner {
             Ok(t) => {
                 f(t)?;
-                Ok(0)
+                Ok(ErrorLen::ok())
             }
-            Err(err) => Ok(err.encoded_size() as u32),
+            Err(err) => Ok(ErrorLen::from_ext_error(&err)),
         }
     }
 }
 
 impl ExtErrorProcessor<()> {
-    pub fn error_len(self) -> u32 {
+    pub fn error_len(self) -> ErrorLen {
         self.inner
             .err()
-            .map(|err| err.encoded_size() as u32)
-            .unwrap_or(0)
+            .map(|err| ErrorLen::from_ext_error(&err))
+            .unwrap_or_else(ErrorLen::ok)
     }
 }
 
@@ -72,3 +114,72 @@ impl<T, E: IntoExtError> ProcessError<T, E> for Result<T, E> {
 pub trait IntoExtError: Sized {
     fn into_ext_error(self) -> Result<ExtError, Self>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gear_core_errors::MessageError;
+
+    #[derive(Debug, PartialEq)]
+    enum TestError {
+        Ext(ExtError),
+        Trap,
+    }
+
+    impl IntoExtError for TestError {
+        fn into_ext_error(self) -> Result<ExtError, Self> {
+            match self {
+                Self::Ext(err) => Ok(err),
+                err => Err(err),
+            }
+        }
+    }
+
+    #[test]
+    fn error_len_is_zero_only_on_success() {
+        let res: Result<(), TestError> = Ok(());
+        let len = res.process_error().unwrap().error_len();
+        assert!(len.is_ok());
+        assert_eq!(len.get(), 0);
+
+        let err = ExtError::Message(MessageError::NotEnoughGas);
+        let res: Result<(), TestError> = Err(TestError::Ext(err.clone()));
+        let len = res.process_error().unwrap().error_len();
+        assert!(!len.is_ok());
+        assert_eq!(len.get(), err.encoded_size() as u32);
+    }
+
+    #[test]
+    fn error_len_on_success_skips_output_on_error() {
+        let res: Result<u32, TestError> = Ok(42);
+        let mut written = None;
+        let len = res
+            .process_error()
+            .unwrap()
+            .error_len_on_success(|v| {
+                written = Some(v);
+                Ok::<_, TestError>(())
+            })
+            .unwrap();
+        assert_eq!(len, ErrorLen::ok());
+        assert_eq!(written, Some(42));
+
+        // Failing after the ext call partially succeeded must still report
+        // a nonzero length, without touching the output.
+        let err = ExtError::Message(MessageError::DuplicateReply);
+        let res: Result<u32, TestError> = Err(TestError::Ext(err.clone()));
+        let len = res
+            .process_error()
+            .unwrap()
+            .error_len_on_success(|_| -> Result<(), TestError> {
+                unreachable!("output must not be written on error")
+            })
+            .unwrap();
+        assert!(!len.is_ok());
+        assert_eq!(len.get(), err.encoded_size() as u32);
+
+        // Errors that can't be turned into `ExtError` are propagated as is.
+        let res: Result<u32, TestError> = Err(TestError::Trap);
+        assert_eq!(res.process_error().err(), Some(TestError::Trap));
+    }
+}
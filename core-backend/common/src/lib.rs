@@ -22,12 +22,16 @@
 
 extern crate alloc;
 
+pub mod canary;
 pub mod error_processor;
+pub mod read;
+pub mod records;
+pub mod ring;
 
 mod utils;
 pub use utils::calc_stack_end;
 
-#[cfg(feature = "mock")]
+#[cfg(any(feature = "mock", test))]
 pub mod mock;
 
 use alloc::{
@@ -35,6 +39,7 @@ use alloc::{
     string::String,
     vec::Vec,
 };
+use canary::MemoryRegion;
 use codec::{Decode, Encode, MaxEncodedLen};
 use core::{
     fmt::{self, Display},
@@ -42,28 +47,53 @@ use core::{
 };
 use gear_core::{
     buffer::RuntimeBufferSizeError,
+    counters::CounterKey,
     env::Ext,
     gas::GasAmount,
-    ids::{CodeId, MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Memory, PageBuf, PageNumber, WasmPageNumber},
     message::{ContextStore, Dispatch, DispatchKind},
 };
 use gear_core_errors::{ExtError, MemoryError};
 use scale_info::TypeInfo;
 
+pub use gear_core::syscalls::{
+    check_syscall_signatures, syscall_signature, SysCallSignature, SysCallSignatureError,
+    ValueType, SYSCALLS,
+};
+
 // Max amount of bytes allowed to be thrown as string explanation of the error.
 pub const TRIMMED_MAX_LEN: usize = 1024;
 
 /// Wrapped string to fit `core-backend::TRIMMED_MAX_LEN` amount of bytes.
-#[derive(
-    Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, derive_more::Display,
-)]
-pub struct TrimmedString(String);
+///
+/// Longer strings are cut at a char boundary and marked as truncated,
+/// which is kept in the encoding and shown as trailing `...` on display.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TrimmedString {
+    string: String,
+    truncated: bool,
+}
 
 impl TrimmedString {
     pub(crate) fn new(mut string: String) -> Self {
-        utils::smart_truncate(&mut string, TRIMMED_MAX_LEN);
-        Self(string)
+        let truncated = utils::smart_truncate(&mut string, TRIMMED_MAX_LEN);
+        Self { string, truncated }
+    }
+
+    /// Whether the original string exceeded `TRIMMED_MAX_LEN` bytes.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl fmt::Display for TrimmedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.string)?;
+        if self.truncated {
+            f.write_str("...")?;
+        }
+        Ok(())
     }
 }
 
@@ -77,7 +107,7 @@ impl Deref for TrimmedString {
     type Target = String;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.string
     }
 }
 
@@ -101,6 +131,8 @@ pub enum TrapExplanation {
     Other(TrimmedString),
     #[display(fmt = "Reason is unknown. Possibly `unreachable` instruction is occurred")]
     Unknown,
+    #[display(fmt = "Memory corruption detected in {}", region)]
+    MemoryCorruptionDetected { region: MemoryRegion },
 }
 
 #[derive(Debug)]
@@ -110,8 +142,15 @@ pub struct ExtInfo {
     pub pages_data: BTreeMap<PageNumber, PageBuf>,
     pub generated_dispatches: Vec<(Dispatch, u32)>,
     pub awakening: Vec<(MessageId, u32)>,
+    pub recipient_charged: BTreeSet<MessageId>,
     pub program_candidates_data: BTreeMap<CodeId, Vec<(ProgramId, MessageId)>>,
     pub context_store: ContextStore,
+    pub value_reservations: Option<BTreeMap<ReservationId, u128>>,
+    pub counters: Option<BTreeMap<CounterKey, u64>>,
+    pub scheduled_exit: Option<(u32, ProgramId)>,
+    pub entry_budgets: BTreeMap<DispatchKind, u64>,
+    pub charge_allowance: Option<u64>,
+    pub reply_waker: Option<MessageId>,
 }
 
 pub trait IntoExtInfo {
@@ -159,6 +198,9 @@ pub trait RuntimeCtx<E: Ext> {
     /// Write the given buffer and its length to the designated locations in memory.
     //
     /// `out_ptr` is the location in memory where `buf` should be written to.
+    ///
+    /// Fixed-width integers must be written as their little-endian bytes
+    /// (`to_le_bytes`), not SCALE-encoded: that's the layout programs decode.
     fn write_output(&mut self, out_ptr: u32, buf: &[u8]) -> Result<(), RuntimeCtxError<E::Error>>;
 }
 
@@ -175,6 +217,40 @@ pub enum StackEndError {
     IsNotAligned(i32),
 }
 
+/// Context of the program's debug message.
+///
+/// Backends built with the `debug-context` feature log it along with
+/// every debug message, so logs tell which message printed what.
+/// Capturing the message id and available gas charges gas as the
+/// corresponding syscalls do. The kind is read without charging and
+/// never sets the last error, e.g. when the entry point isn't `handle_signal`.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugContext {
+    pub kind: DispatchKind,
+    pub message_id: MessageId,
+    pub gas_available: u64,
+}
+
+impl DebugContext {
+    pub fn capture<E: Ext>(ext: &mut E) -> Result<Self, E::Error> {
+        Ok(Self {
+            kind: ext.dispatch_kind(),
+            message_id: ext.message_id()?,
+            gas_available: ext.gas_available()?,
+        })
+    }
+}
+
+impl Display for DebugContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} of message {}, {} gas available",
+            self.kind, self.message_id, self.gas_available
+        )
+    }
+}
+
 // '__gear_stack_end' export is inserted in wasm-proc or wasm-builder
 pub const STACK_END_EXPORT_NAME: &str = "__gear_stack_end";
 
@@ -201,8 +277,73 @@ pub trait Environment<E: Ext + IntoExtInfo + 'static>: Sized {
     where
         F: FnOnce(&mut Self::Memory, Option<WasmPageNumber>) -> Result<(), T>,
         T: fmt::Display;
+
+    /// Return memory of the finished execution, so it may be reused by the
+    /// next executions of the same code.
+    ///
+    /// `pages_data` is the data of pages already read from the memory after
+    /// the execution. Environments without memory pooling just drop it.
+    fn release_memory(_memory: Self::Memory, _pages_data: &BTreeMap<PageNumber, PageBuf>) {}
 }
 
 pub trait AsTerminationReason {
     fn as_termination_reason(&self) -> Option<&TerminationReason>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn trimmed_string_marks_truncation() {
+        let short = TrimmedString::from("short error");
+        assert!(!short.is_truncated());
+        assert_eq!(short.to_string(), "short error");
+
+        let long = TrimmedString::from("e".repeat(10 * 1024));
+        assert!(long.is_truncated());
+        assert_eq!(long.len(), TRIMMED_MAX_LEN);
+        assert_eq!(long.to_string(), "e".repeat(TRIMMED_MAX_LEN) + "...");
+
+        // Cut at char boundary, never in the middle of a char.
+        let long = TrimmedString::from("ё".repeat(10 * 1024));
+        assert!(long.is_truncated());
+        assert_eq!(long.len(), TRIMMED_MAX_LEN);
+
+        let long = TrimmedString::from("e".to_string() + &"ё".repeat(10 * 1024));
+        assert!(long.is_truncated());
+        assert_eq!(long.len(), TRIMMED_MAX_LEN - 1);
+    }
+
+    #[test]
+    fn debug_context_captures_dispatch_kind() {
+        // Signal details are always available in the mock, so the kind
+        // must not be guessed from them.
+        for kind in [
+            DispatchKind::Init,
+            DispatchKind::Handle,
+            DispatchKind::Reply,
+            DispatchKind::Signal,
+        ] {
+            let mut ext = mock::MockExt::with_dispatch_kind(kind);
+            let context = DebugContext::capture(&mut ext).unwrap();
+            assert_eq!(context.kind, kind);
+        }
+    }
+
+    #[test]
+    fn trap_explanation_encoding_bounded() {
+        let explanation = TrapExplanation::Other("e".repeat(10 * 1024).into());
+        let encoded = explanation.encode();
+        // Enum index, compact length, string bytes and truncation flag.
+        assert_eq!(encoded.len(), 1 + 2 + TRIMMED_MAX_LEN + 1);
+
+        let decoded = TrapExplanation::decode(&mut encoded.as_slice()).unwrap();
+        assert_eq!(decoded, explanation);
+        match decoded {
+            TrapExplanation::Other(string) => assert!(string.is_truncated()),
+            _ => unreachable!(),
+        }
+    }
+}
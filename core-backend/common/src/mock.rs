@@ -20,20 +20,29 @@ use crate::{
     error_processor::IntoExtError, AsTerminationReason, ExtInfo, IntoExtInfo, TerminationReason,
 };
 use alloc::collections::BTreeSet;
-use core::fmt;
+use core::{fmt, num::NonZeroU32};
 use gear_core::{
     costs::RuntimeCosts,
+    counters::CounterKey,
     env::Ext,
     gas::{GasAmount, GasCounter},
-    ids::{MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Memory, WasmPageNumber},
-    message::{ExitCode, HandlePacket, InitPacket, ReplyPacket},
+    message::{
+        ContextStoreLimits, DispatchKind, ExitCode, HandlePacket, InitPacket, ReplyPacket,
+        SignalCode,
+    },
 };
 use gear_core_errors::{CoreError, ExtError, MemoryError};
 
 /// Mock error
 #[derive(Debug)]
-pub struct Error;
+pub enum Error {
+    /// The call isn't supported by the mock.
+    Unsupported,
+    /// The call failed with the error reported to the program.
+    Ext(ExtError),
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
@@ -55,13 +64,45 @@ impl AsTerminationReason for Error {
 
 impl IntoExtError for Error {
     fn into_ext_error(self) -> Result<ExtError, Self> {
-        todo!()
+        match self {
+            Self::Ext(err) => Ok(err),
+            err => Err(err),
+        }
     }
 }
 
 /// Mock ext
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
-pub struct MockExt(BTreeSet<&'static str>);
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MockExt {
+    forbidden_funcs: BTreeSet<&'static str>,
+    dispatch_kind: DispatchKind,
+    send_init_error: Option<ExtError>,
+    last_error: Option<ExtError>,
+}
+
+impl Default for MockExt {
+    fn default() -> Self {
+        Self::with_dispatch_kind(DispatchKind::Handle)
+    }
+}
+
+impl MockExt {
+    /// Mock ext processing the dispatch of the given kind.
+    pub fn with_dispatch_kind(dispatch_kind: DispatchKind) -> Self {
+        Self {
+            forbidden_funcs: Default::default(),
+            dispatch_kind,
+            send_init_error: None,
+            last_error: None,
+        }
+    }
+
+    /// Mock ext failing `send_init` with the given error.
+    pub fn with_send_init_error(mut self, err: ExtError) -> Self {
+        self.send_init_error = Some(err);
+        self
+    }
+}
 
 impl Ext for MockExt {
     type Error = Error;
@@ -71,7 +112,7 @@ impl Ext for MockExt {
         _pages: WasmPageNumber,
         _mem: &mut impl Memory,
     ) -> Result<WasmPageNumber, Self::Error> {
-        Err(Error)
+        Err(Error::Unsupported)
     }
     fn block_height(&mut self) -> Result<u32, Self::Error> {
         Ok(0)
@@ -79,16 +120,56 @@ impl Ext for MockExt {
     fn block_timestamp(&mut self) -> Result<u64, Self::Error> {
         Ok(0)
     }
+    fn block_duration(&mut self) -> Result<u64, Self::Error> {
+        Ok(0)
+    }
+    fn block_hash(&mut self, _height: u32) -> Result<[u8; 32], Self::Error> {
+        Err(Error::Unsupported)
+    }
+    fn tx_hash(&mut self) -> Result<[u8; 32], Self::Error> {
+        Err(Error::Unsupported)
+    }
+    fn set_default_gas_limit(&mut self, _gas_limit: u64) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_charge_allowance(&mut self, _allowance: u64) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn stack_limit(&mut self) -> Result<Option<u32>, Self::Error> {
+        Ok(None)
+    }
+    fn children_count(&mut self) -> Result<u64, Self::Error> {
+        Ok(0)
+    }
+    fn children_gas_burned(&mut self) -> Result<u64, Self::Error> {
+        Ok(0)
+    }
+    fn codes_count(&mut self) -> Result<u64, Self::Error> {
+        Ok(0)
+    }
+    fn context_limits(&mut self) -> Result<ContextStoreLimits, Self::Error> {
+        Ok(Default::default())
+    }
     fn origin(&mut self) -> Result<ProgramId, Self::Error> {
         Ok(ProgramId::from(0))
     }
     fn send_init(&mut self) -> Result<usize, Self::Error> {
-        Ok(0)
+        match self.send_init_error.clone() {
+            Some(err) => {
+                self.last_error = Some(err.clone());
+                Err(Error::Ext(err))
+            }
+            None => Ok(0),
+        }
     }
     fn send_push(&mut self, _handle: usize, _buffer: &[u8]) -> Result<(), Self::Error> {
         Ok(())
     }
-    fn reply_commit(&mut self, _msg: ReplyPacket, _delay: u32) -> Result<MessageId, Self::Error> {
+    fn reply_commit(
+        &mut self,
+        _msg: ReplyPacket,
+        _delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
         Ok(MessageId::default())
     }
     fn reply_push(&mut self, _buffer: &[u8]) -> Result<(), Self::Error> {
@@ -98,7 +179,39 @@ impl Ext for MockExt {
         &mut self,
         _handle: usize,
         _msg: HandlePacket,
-        _delay: u32,
+        _delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        Ok(MessageId::default())
+    }
+    fn send_commit_charge_recipient(
+        &mut self,
+        _handle: usize,
+        _msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error> {
+        Ok(MessageId::default())
+    }
+    fn reservation_send_commit(
+        &mut self,
+        _id: ReservationId,
+        _handle: usize,
+        _msg: HandlePacket,
+        _delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        Ok(MessageId::default())
+    }
+    fn reserve_and_send(
+        &mut self,
+        _amount: u128,
+        _msg: HandlePacket,
+        _delay: Option<NonZeroU32>,
+    ) -> Result<(ReservationId, MessageId), Self::Error> {
+        Ok((ReservationId::default(), MessageId::default()))
+    }
+    fn forward(
+        &mut self,
+        _destination: ProgramId,
+        _value: u128,
+        _delay: Option<NonZeroU32>,
     ) -> Result<MessageId, Self::Error> {
         Ok(MessageId::default())
     }
@@ -108,12 +221,27 @@ impl Ext for MockExt {
     fn source(&mut self) -> Result<ProgramId, Self::Error> {
         Ok(ProgramId::from(0))
     }
+    fn source_code_id(&mut self) -> Result<CodeId, Self::Error> {
+        Err(Error::Unsupported)
+    }
     fn exit(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
+    fn schedule_exit(&mut self, _block: u32, _inheritor: ProgramId) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_entry_budget(&mut self, _entry: u32, _gas: u64) -> Result<(), Self::Error> {
+        Ok(())
+    }
     fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error> {
         Ok(None)
     }
+    fn signal_details(&mut self) -> Result<(MessageId, SignalCode), Self::Error> {
+        Ok((MessageId::default(), SignalCode::RemovedFromWaitlist))
+    }
+    fn execution_id(&mut self) -> Result<u64, Self::Error> {
+        Ok(0)
+    }
     fn message_id(&mut self) -> Result<MessageId, Self::Error> {
         Ok(0.into())
     }
@@ -126,12 +254,34 @@ impl Ext for MockExt {
     fn debug(&mut self, _data: &str) -> Result<(), Self::Error> {
         Ok(())
     }
+    fn trace(&mut self, _level: u32, _data: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
     fn read(&mut self) -> Result<&[u8], Self::Error> {
         Ok(&[])
     }
+    fn read_part(&mut self, _len: usize) -> Result<&[u8], Self::Error> {
+        Ok(&[])
+    }
     fn size(&mut self) -> Result<usize, Self::Error> {
         Ok(0)
     }
+    fn message_meta(&mut self) -> Result<&[u8], Self::Error> {
+        Ok(&[])
+    }
+    fn mailbox_contains(
+        &mut self,
+        _user_id: ProgramId,
+        _message_id: MessageId,
+    ) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+    fn record_not_found(&mut self, _index: u32, _count: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn max_payload_size(&mut self) -> Result<u32, Self::Error> {
+        Ok(0)
+    }
     fn gas(&mut self, _amount: u32) -> Result<(), Self::Error> {
         Ok(())
     }
@@ -147,36 +297,86 @@ impl Ext for MockExt {
     fn gas_available(&mut self) -> Result<u64, Self::Error> {
         Ok(1_000_000)
     }
+    fn gas_allowance(&mut self) -> Result<u64, Self::Error> {
+        Ok(1_000_000)
+    }
+    fn is_init(&mut self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+    fn resumed_from_wait(&mut self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
     fn value(&mut self) -> Result<u128, Self::Error> {
         Ok(0)
     }
     fn value_available(&mut self) -> Result<u128, Self::Error> {
         Ok(1_000_000)
     }
+    fn spendable_value(&mut self) -> Result<u128, Self::Error> {
+        Ok(1_000_000)
+    }
     fn leave(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
     fn wait(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
+    fn wait_for_reply(&mut self, _message_id: MessageId) -> Result<(), Self::Error> {
+        Ok(())
+    }
     fn wait_for(&mut self, _duration: u32) -> Result<(), Self::Error> {
         Ok(())
     }
     fn wait_up_to(&mut self, _duration: u32) -> Result<(), Self::Error> {
         Ok(())
     }
-    fn wake(&mut self, _waker_id: MessageId, _delay: u32) -> Result<(), Self::Error> {
+    fn wait_budget_remaining(&mut self) -> Result<u32, Self::Error> {
+        Ok(0)
+    }
+    fn wait_if(&mut self, _condition: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn wake(
+        &mut self,
+        _waker_id: MessageId,
+        _delay: Option<NonZeroU32>,
+    ) -> Result<(), Self::Error> {
         Ok(())
     }
     fn create_program(
         &mut self,
         _packet: InitPacket,
-        _delay: u32,
-    ) -> Result<ProgramId, Self::Error> {
+        _delay: Option<NonZeroU32>,
+    ) -> Result<(MessageId, ProgramId), Self::Error> {
         Ok(Default::default())
     }
+    fn new_handle(&mut self) -> Result<u32, Self::Error> {
+        Ok(0)
+    }
+    fn delayed_eta(&mut self, _message_id: MessageId) -> Result<u32, Self::Error> {
+        Ok(0)
+    }
+    fn reserve_value(&mut self, _value: u128) -> Result<ReservationId, Self::Error> {
+        Ok(ReservationId::default())
+    }
+    fn unreserve_value(&mut self, _id: ReservationId) -> Result<u128, Self::Error> {
+        Ok(0)
+    }
+    fn split_value_reservation(
+        &mut self,
+        _id: ReservationId,
+        _value: u128,
+    ) -> Result<ReservationId, Self::Error> {
+        Ok(ReservationId::default())
+    }
+    fn bump_counter(&mut self, _key: CounterKey) -> Result<u64, Self::Error> {
+        Ok(0)
+    }
     fn forbidden_funcs(&self) -> &BTreeSet<&'static str> {
-        &self.0
+        &self.forbidden_funcs
+    }
+    fn dispatch_kind(&self) -> DispatchKind {
+        self.dispatch_kind
     }
 }
 
@@ -188,8 +388,15 @@ impl IntoExtInfo for MockExt {
             pages_data: Default::default(),
             generated_dispatches: Default::default(),
             awakening: Default::default(),
+            recipient_charged: Default::default(),
             program_candidates_data: Default::default(),
             context_store: Default::default(),
+            value_reservations: Default::default(),
+            counters: Default::default(),
+            scheduled_exit: Default::default(),
+            entry_budgets: Default::default(),
+            charge_allowance: Default::default(),
+            reply_waker: Default::default(),
         })
     }
 
@@ -198,7 +405,7 @@ impl IntoExtInfo for MockExt {
     }
 
     fn last_error(&self) -> Option<&gear_core_errors::ExtError> {
-        None
+        self.last_error.as_ref()
     }
 
     fn trap_explanation(&self) -> Option<crate::TrapExplanation> {
@@ -0,0 +1,154 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Context of payload reads failed on the range out of the payload.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+/// Max amount of payload bytes around the failed read kept for debugging.
+pub const EXCERPT_MAX_LEN: usize = 32;
+
+/// Origin of the range read from the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum ReadOrigin {
+    /// Range requested by the program in the syscall arguments.
+    #[display(fmt = "requested by the program")]
+    Requested,
+    /// Range found by slicing the payload internally, e.g. into records.
+    #[display(fmt = "sliced from the payload")]
+    Sliced,
+}
+
+/// Bytes of the payload around the failed read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadExcerpt {
+    /// Offset of the excerpt in the payload.
+    pub offset: usize,
+    /// Bytes of the excerpt.
+    pub bytes: Vec<u8>,
+}
+
+impl PayloadExcerpt {
+    /// Take up to [`EXCERPT_MAX_LEN`] bytes of the `payload` around `at`,
+    /// or its last bytes if `at` is out of the payload.
+    pub fn around(payload: &[u8], at: usize) -> Self {
+        let offset = at.min(payload.len()).saturating_sub(EXCERPT_MAX_LEN / 2);
+        let end = offset.saturating_add(EXCERPT_MAX_LEN).min(payload.len());
+
+        Self {
+            offset,
+            bytes: payload[offset..end].to_vec(),
+        }
+    }
+}
+
+impl Display for PayloadExcerpt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "payload from {}: 0x", self.offset)?;
+        self.bytes
+            .iter()
+            .try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+/// Context of the read failed on the range out of the payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadContext {
+    /// Syscall reading the payload.
+    pub syscall: &'static str,
+    /// Origin of the range read.
+    pub origin: ReadOrigin,
+    /// Payload around the read.
+    ///
+    /// Captured only by backends built with the `debug-context` feature,
+    /// so never by the runtime.
+    pub excerpt: Option<PayloadExcerpt>,
+}
+
+impl ReadContext {
+    /// Create the context without the payload excerpt.
+    pub fn new(syscall: &'static str, origin: ReadOrigin) -> Self {
+        Self {
+            syscall,
+            origin,
+            excerpt: None,
+        }
+    }
+
+    /// Keep the `payload` excerpt around `at`.
+    pub fn with_excerpt(self, payload: &[u8], at: usize) -> Self {
+        Self {
+            excerpt: Some(PayloadExcerpt::around(payload, at)),
+            ..self
+        }
+    }
+}
+
+impl Display for ReadContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in `{}`, {}", self.syscall, self.origin)?;
+
+        match &self.excerpt {
+            Some(excerpt) => write!(f, ", {}", excerpt),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn excerpt_taken_around_read() {
+        let payload: Vec<u8> = (0..64).collect();
+
+        let excerpt = PayloadExcerpt::around(&payload, 20);
+        assert_eq!(excerpt.offset, 4);
+        assert_eq!(excerpt.bytes, (4..36).collect::<Vec<u8>>());
+
+        let excerpt = PayloadExcerpt::around(&payload, 3);
+        assert_eq!(excerpt.offset, 0);
+        assert_eq!(excerpt.bytes.len(), EXCERPT_MAX_LEN);
+
+        // Reads past the end show the tail of the payload.
+        let excerpt = PayloadExcerpt::around(&payload, 100);
+        assert_eq!(excerpt.offset, 48);
+        assert_eq!(excerpt.bytes, (48..64).collect::<Vec<u8>>());
+
+        assert!(PayloadExcerpt::around(&[], 10).bytes.is_empty());
+    }
+
+    #[test]
+    fn context_displayed_with_excerpt() {
+        let context = ReadContext::new("gr_read", ReadOrigin::Requested);
+        assert_eq!(
+            context.to_string(),
+            "in `gr_read`, requested by the program"
+        );
+
+        let context =
+            ReadContext::new("gr_read_records", ReadOrigin::Sliced).with_excerpt(&[0xab, 0x01], 1);
+        assert_eq!(
+            context.to_string(),
+            "in `gr_read_records`, sliced from the payload, payload from 0: 0xab01"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Payload consisting of length-prefixed records for `gr_read_records`.
+//!
+//! Each record is prefixed with its length encoded as little-endian `u32`.
+//! Records follow each other up to the end of the payload.
+
+use core::{convert::TryInto, ops::Range};
+
+/// Size of the record length prefix in bytes.
+pub const RECORD_PREFIX_LEN: usize = 4;
+
+/// Error of walking the records, caused by a prefix pointing out of the payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordsError {
+    /// Range of the prefix or the record is out of the payload of given size.
+    WrongRange(Range<usize>, usize),
+    /// Record length overflows when added to the record offset.
+    LenOverflow(usize, usize),
+}
+
+/// Result of looking up the record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordLookup {
+    /// Range of the record in the payload.
+    Found(Range<usize>),
+    /// The payload has no record with requested index, but `count` records.
+    NotFound { count: u32 },
+}
+
+/// Find the record with given `index` in the `payload`.
+///
+/// Prefixes are validated against the remaining payload while walking,
+/// so records past the found one aren't checked.
+pub fn find_record(payload: &[u8], index: u32) -> Result<RecordLookup, RecordsError> {
+    let mut at = 0;
+    let mut count = 0;
+
+    while at < payload.len() {
+        let start = at
+            .checked_add(RECORD_PREFIX_LEN)
+            .ok_or(RecordsError::LenOverflow(at, RECORD_PREFIX_LEN))?;
+
+        if start > payload.len() {
+            return Err(RecordsError::WrongRange(at..start, payload.len()));
+        }
+
+        let len = u32::from_le_bytes(
+            payload[at..start]
+                .try_into()
+                .expect("prefix has exactly `RECORD_PREFIX_LEN` bytes"),
+        ) as usize;
+
+        let end = start
+            .checked_add(len)
+            .ok_or(RecordsError::LenOverflow(start, len))?;
+
+        if end > payload.len() {
+            return Err(RecordsError::WrongRange(start..end, payload.len()));
+        }
+
+        if count == index {
+            return Ok(RecordLookup::Found(start..end));
+        }
+
+        count += 1;
+        at = end;
+    }
+
+    Ok(RecordLookup::NotFound { count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn records(records: &[&[u8]]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for record in records {
+            payload.extend_from_slice(&(record.len() as u32).to_le_bytes());
+            payload.extend_from_slice(record);
+        }
+        payload
+    }
+
+    #[test]
+    fn records_found() {
+        let payload = records(&[b"abc", b"", b"de"]);
+
+        assert_eq!(find_record(&payload, 0), Ok(RecordLookup::Found(4..7)));
+        assert_eq!(find_record(&payload, 1), Ok(RecordLookup::Found(11..11)));
+        assert_eq!(find_record(&payload, 2), Ok(RecordLookup::Found(15..17)));
+    }
+
+    #[test]
+    fn index_out_of_records() {
+        let payload = records(&[b"abc", b"de"]);
+
+        assert_eq!(
+            find_record(&payload, 2),
+            Ok(RecordLookup::NotFound { count: 2 })
+        );
+        assert_eq!(find_record(&[], 0), Ok(RecordLookup::NotFound { count: 0 }));
+    }
+
+    #[test]
+    fn malformed_records() {
+        // Prefix is cut.
+        let mut payload = records(&[b"abc"]);
+        payload.extend_from_slice(&[1, 0]);
+        assert_eq!(find_record(&payload, 0), Ok(RecordLookup::Found(4..7)));
+        assert_eq!(
+            find_record(&payload, 1),
+            Err(RecordsError::WrongRange(7..11, 9))
+        );
+
+        // Record is longer than the rest of the payload.
+        let mut payload = records(&[b"abc"]);
+        payload.truncate(6);
+        assert_eq!(
+            find_record(&payload, 0),
+            Err(RecordsError::WrongRange(4..7, 6))
+        );
+    }
+}
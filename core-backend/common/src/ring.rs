@@ -0,0 +1,181 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Ring buffer designated by program in its memory for `gr_read_ring`.
+//!
+//! Data is written starting at the current write offset and wraps around
+//! to the start of the buffer when it reaches the buffer's end, so a single
+//! write results in at most two contiguous chunks of memory.
+
+/// Error of writing into the ring buffer.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum RingBufferError {
+    #[display(fmt = "Write offset {} is out of ring buffer with capacity {}", _0, _1)]
+    WrongOffset(u32, u32),
+    #[display(
+        fmt = "Cannot write {} bytes into ring buffer with capacity {}",
+        _0,
+        _1
+    )]
+    LenExceedsCapacity(u32, u32),
+    #[display(
+        fmt = "Ring buffer at {:#x} with capacity {} overflows address space",
+        _0,
+        _1
+    )]
+    AddressOverflow(u32, u32),
+}
+
+/// Ring buffer placed in program's memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingBuffer {
+    /// Address of the buffer start.
+    pub ptr: u32,
+    /// Size of the buffer in bytes.
+    pub capacity: u32,
+    /// Offset from the buffer start the next write begins at.
+    pub offset: u32,
+}
+
+/// Contiguous part of the write into the ring buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingChunk {
+    /// Address in memory the part is written at.
+    pub ptr: u32,
+    /// Length of the part in bytes.
+    pub len: u32,
+}
+
+impl RingBuffer {
+    /// Split write of `len` bytes into chunks of memory.
+    ///
+    /// Returns the chunk up to the buffer's end, the chunk wrapped around
+    /// to the buffer start (empty if there is no wraparound) and
+    /// the write offset following the written data.
+    pub fn split(&self, len: u32) -> Result<([RingChunk; 2], u32), RingBufferError> {
+        let Self {
+            ptr,
+            capacity,
+            offset,
+        } = *self;
+
+        if offset >= capacity {
+            return Err(RingBufferError::WrongOffset(offset, capacity));
+        }
+
+        if len > capacity {
+            return Err(RingBufferError::LenExceedsCapacity(len, capacity));
+        }
+
+        if ptr.checked_add(capacity).is_none() {
+            return Err(RingBufferError::AddressOverflow(ptr, capacity));
+        }
+
+        let tail = capacity - offset;
+        let first = len.min(tail);
+        let second = len - first;
+
+        let next_offset = if first < tail { offset + first } else { second };
+
+        Ok((
+            [
+                RingChunk {
+                    ptr: ptr + offset,
+                    len: first,
+                },
+                RingChunk { ptr, len: second },
+            ],
+            next_offset,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(offset: u32) -> RingBuffer {
+        RingBuffer {
+            ptr: 0x100,
+            capacity: 8,
+            offset,
+        }
+    }
+
+    fn chunk(ptr: u32, len: u32) -> RingChunk {
+        RingChunk { ptr, len }
+    }
+
+    #[test]
+    fn no_wraparound() {
+        assert_eq!(
+            ring(0).split(3),
+            Ok(([chunk(0x100, 3), chunk(0x100, 0)], 3))
+        );
+        assert_eq!(
+            ring(2).split(0),
+            Ok(([chunk(0x102, 0), chunk(0x100, 0)], 2))
+        );
+        assert_eq!(
+            ring(2).split(5),
+            Ok(([chunk(0x102, 5), chunk(0x100, 0)], 7))
+        );
+    }
+
+    #[test]
+    fn wraparound() {
+        assert_eq!(
+            ring(2).split(6),
+            Ok(([chunk(0x102, 6), chunk(0x100, 0)], 0))
+        );
+        assert_eq!(
+            ring(5).split(6),
+            Ok(([chunk(0x105, 3), chunk(0x100, 3)], 3))
+        );
+        assert_eq!(
+            ring(7).split(8),
+            Ok(([chunk(0x107, 1), chunk(0x100, 7)], 7))
+        );
+    }
+
+    #[test]
+    fn invalid_rings() {
+        assert_eq!(ring(8).split(1), Err(RingBufferError::WrongOffset(8, 8)));
+        assert_eq!(
+            ring(0).split(9),
+            Err(RingBufferError::LenExceedsCapacity(9, 8))
+        );
+
+        let empty = RingBuffer {
+            ptr: 0,
+            capacity: 0,
+            offset: 0,
+        };
+        assert_eq!(empty.split(0), Err(RingBufferError::WrongOffset(0, 0)));
+
+        let overflowing = RingBuffer {
+            ptr: u32::MAX - 4,
+            capacity: 8,
+            offset: 0,
+        };
+        assert_eq!(
+            overflowing.split(1),
+            Err(RingBufferError::AddressOverflow(u32::MAX - 4, 8))
+        );
+    }
+}
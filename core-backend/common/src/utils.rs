@@ -23,7 +23,10 @@ macro_rules! assert_err {
     };
 }
 
-pub(crate) fn smart_truncate(s: &mut String, max_bytes: usize) {
+/// Truncates `s` to at most `max_bytes` at a char boundary.
+///
+/// Returns whether anything was cut off.
+pub(crate) fn smart_truncate(s: &mut String, max_bytes: usize) -> bool {
     let mut last_byte = max_bytes;
 
     if s.len() > last_byte {
@@ -32,7 +35,10 @@ pub(crate) fn smart_truncate(s: &mut String, max_bytes: usize) {
         }
 
         s.truncate(last_byte);
+        return true;
     }
+
+    false
 }
 pub fn calc_stack_end(stack_end: Option<i32>) -> Result<Option<WasmPageNumber>, StackEndError> {
     use StackEndError::*;
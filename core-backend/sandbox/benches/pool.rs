@@ -0,0 +1,92 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Compares creating a fresh memory for each execution with reusing
+//! a pooled one, for memories with a few pages written by the execution.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gear_backend_sandbox::{memory::MemoryWrap, pool::MemoryPool};
+use gear_core::{
+    ids::CodeId,
+    memory::{Memory, PageBuf, PageNumber},
+};
+use sp_sandbox::{default_executor::Memory as DefaultExecutorMemory, SandboxMemory};
+use std::collections::BTreeMap;
+
+/// Wasm pages of the memory.
+const SIZES: [u32; 3] = [1, 16, 256];
+/// Gear pages written by the execution.
+const DIRTY_PAGES: u32 = 4;
+
+/// Writes the pages as an execution would, returning their data.
+fn execute(memory: &mut MemoryWrap) -> BTreeMap<PageNumber, PageBuf> {
+    let mut pages_data = BTreeMap::new();
+
+    for page in (0..DIRTY_PAGES).map(PageNumber) {
+        let mut data = PageBuf::new_zeroed();
+        data.fill(1);
+
+        memory
+            .write(page.offset(), &data)
+            .expect("Page is in memory");
+        pages_data.insert(page, data);
+    }
+
+    pages_data
+}
+
+fn new_memory(size: u32) -> DefaultExecutorMemory {
+    SandboxMemory::new(size, None).expect("Memory creation failed")
+}
+
+fn memory_pool(c: &mut Criterion) {
+    let code_id = CodeId::generate(b"binary");
+    let mut group = c.benchmark_group("memory");
+
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::new("fresh", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut memory = MemoryWrap::new(new_memory(size));
+                execute(&mut memory)
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("pooled", size), &size, |b, &size| {
+            let mut pool = MemoryPool::new(1);
+            pool.release(
+                code_id,
+                MemoryWrap::new(new_memory(size)),
+                &Default::default(),
+            );
+
+            b.iter(|| {
+                let memory = pool
+                    .acquire(code_id, size.into())
+                    .expect("Memory is pooled");
+                let mut memory = MemoryWrap::new(memory);
+                let pages_data = execute(&mut memory);
+                pool.release(code_id, memory, &pages_data);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, memory_pool);
+criterion_main!(benches);
@@ -21,18 +21,26 @@
 use crate::{
     funcs::{FuncError, FuncsHandler as Funcs},
     memory::MemoryWrap,
+    pool,
     runtime::Runtime,
 };
 use alloc::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     string::{String, ToString},
 };
 use core::fmt;
+#[cfg(feature = "memory-canaries")]
+use gear_backend_common::canary::{MemoryCanaries, HEAP_BASE_EXPORT_NAME};
 use gear_backend_common::{
     calc_stack_end, error_processor::IntoExtError, AsTerminationReason, BackendReport, Environment,
     IntoExtInfo, StackEndError, TerminationReason, TrapExplanation, STACK_END_EXPORT_NAME,
 };
-use gear_core::{env::Ext, memory::WasmPageNumber, message::DispatchKind};
+use gear_core::{
+    env::Ext,
+    ids::CodeId,
+    memory::{PageBuf, PageNumber, WasmPageNumber},
+    message::DispatchKind,
+};
 use sp_sandbox::{
     default_executor::{EnvironmentDefinitionBuilder, Instance, Memory as DefaultExecutorMemory},
     HostFuncType, ReturnValue, SandboxEnvironmentBuilder, SandboxInstance, SandboxMemory,
@@ -59,18 +67,49 @@ pub enum SandboxEnvironmentError {
 /// Environment to run one module at a time providing Ext.
 pub struct SandboxEnvironment;
 
+/// Wraps syscall to check memory canaries after it's called.
+#[cfg(feature = "memory-canaries")]
+macro_rules! syscall {
+    ($func:ident) => {{
+        fn checked<E>(
+            ctx: &mut Runtime<E>,
+            args: &[sp_sandbox::Value],
+        ) -> crate::funcs::SyscallOutput
+        where
+            E: Ext + IntoExtInfo + 'static,
+            E::Error: AsTerminationReason + IntoExtError,
+        {
+            let res = Funcs::$func(ctx, args);
+            ctx.check_canaries()?;
+            res
+        }
+
+        checked::<E>
+    }};
+}
+
+#[cfg(not(feature = "memory-canaries"))]
+macro_rules! syscall {
+    ($func:ident) => {
+        Funcs::$func
+    };
+}
+
 // A helping wrapper for `EnvironmentDefinitionBuilder` and `forbidden_funcs`.
 // It makes adding functions to `EnvironmentDefinitionBuilder` shorter.
 struct EnvBuilder<'a, E: Ext> {
     env_def_builder: EnvironmentDefinitionBuilder<Runtime<'a, E>>,
     forbidden_funcs: &'a BTreeSet<&'static str>,
+    funcs: BTreeSet<&'static str>,
 }
 
 impl<'a, E: Ext + IntoExtInfo + 'static> EnvBuilder<'a, E> {
-    fn add_func(&mut self, name: &str, f: HostFuncType<Runtime<'a, E>>)
+    fn add_func(&mut self, name: &'static str, f: HostFuncType<Runtime<'a, E>>)
     where
         E::Error: AsTerminationReason + IntoExtError,
     {
+        self.funcs.insert(name);
+
         if self.forbidden_funcs.contains(name) {
             self.env_def_builder
                 .add_host_func("env", name, Funcs::forbidden);
@@ -86,6 +125,100 @@ impl<'a, E: Ext> From<EnvBuilder<'a, E>> for EnvironmentDefinitionBuilder<Runtim
     }
 }
 
+// Registers all the syscalls in the environment.
+fn add_syscalls<E>(builder: &mut EnvBuilder<E>)
+where
+    E: Ext + IntoExtInfo + 'static,
+    E::Error: AsTerminationReason + IntoExtError,
+{
+    builder.add_func("gr_block_hash", syscall!(block_hash));
+    builder.add_func("gr_tx_hash", syscall!(tx_hash));
+    builder.add_func("gr_block_height", syscall!(block_height));
+    builder.add_func("gr_block_timestamp", syscall!(block_timestamp));
+    builder.add_func("gr_block_duration", syscall!(block_duration));
+    builder.add_func("gr_bump_counter", syscall!(bump_counter));
+    builder.add_func("gr_children_count", syscall!(children_count));
+    builder.add_func("gr_children_gas_burned", syscall!(children_gas_burned));
+    builder.add_func("gr_codes_count", syscall!(codes_count));
+    builder.add_func("gr_context_limits", syscall!(context_limits));
+    builder.add_func("gr_create_program", syscall!(create_program));
+    builder.add_func("gr_create_program_wgas", syscall!(create_program_wgas));
+    builder.add_func(
+        "gr_create_program_for_reply",
+        syscall!(create_program_for_reply),
+    );
+    builder.add_func(
+        "gr_create_program_wgas_for_reply",
+        syscall!(create_program_wgas_for_reply),
+    );
+    builder.add_func("gr_debug", syscall!(debug));
+    builder.add_func("gr_trace", syscall!(trace));
+    builder.add_func("gr_delayed_eta", syscall!(delayed_eta));
+    builder.add_func("gr_reserve_value", syscall!(reserve_value));
+    builder.add_func("gr_unreserve_value", syscall!(unreserve_value));
+    builder.add_func(
+        "gr_split_value_reservation",
+        syscall!(split_value_reservation),
+    );
+    builder.add_func("gr_reservation_send", syscall!(reservation_send));
+    builder.add_func("gr_reserve_and_send", syscall!(reserve_and_send));
+    builder.add_func("gr_error", syscall!(error));
+    builder.add_func("gr_execution_id", syscall!(execution_id));
+    builder.add_func("gr_exit", syscall!(exit));
+    builder.add_func("gr_exit_code", syscall!(exit_code));
+    builder.add_func("gr_schedule_exit", syscall!(schedule_exit));
+    builder.add_func("gr_set_entry_budget", syscall!(set_entry_budget));
+    builder.add_func("gr_forward", syscall!(forward));
+    builder.add_func("gr_gas_available", syscall!(gas_available));
+    builder.add_func("gr_gas_allowance", syscall!(gas_allowance));
+    builder.add_func("gr_is_init", syscall!(is_init));
+    builder.add_func("gr_resumed_from_wait", syscall!(resumed_from_wait));
+    builder.add_func("gr_leave", syscall!(leave));
+    builder.add_func("gr_mailbox_contains", syscall!(mailbox_contains));
+    builder.add_func("gr_max_payload_size", syscall!(max_payload_size));
+    builder.add_func("gr_message_meta", syscall!(message_meta));
+    builder.add_func("gr_msg_id", syscall!(msg_id));
+    builder.add_func("gr_new_handle", syscall!(new_handle));
+    builder.add_func("gr_origin", syscall!(origin));
+    builder.add_func("gr_program_id", syscall!(program_id));
+    builder.add_func("gr_read", syscall!(read));
+    builder.add_func("gr_read_records", syscall!(read_records));
+    builder.add_func("gr_read_ring", syscall!(read_ring));
+    builder.add_func("gr_reply", syscall!(reply));
+    builder.add_func("gr_reply_with_code", syscall!(reply_with_code));
+    builder.add_func("gr_set_default_gas_limit", syscall!(set_default_gas_limit));
+    builder.add_func("gr_set_charge_allowance", syscall!(set_charge_allowance));
+    builder.add_func("gr_reply_commit", syscall!(reply_commit));
+    builder.add_func("gr_reply_commit_wgas", syscall!(reply_commit_wgas));
+    builder.add_func("gr_reply_push", syscall!(reply_push));
+    builder.add_func("gr_reply_to", syscall!(reply_to));
+    builder.add_func("gr_reply_wgas", syscall!(reply_wgas));
+    builder.add_func("gr_send", syscall!(send));
+    builder.add_func("gr_send_and_wait", syscall!(send_and_wait));
+    builder.add_func("gr_send_charge_recipient", syscall!(send_charge_recipient));
+    builder.add_func("gr_send_commit", syscall!(send_commit));
+    builder.add_func("gr_send_commit_wgas", syscall!(send_commit_wgas));
+    builder.add_func("gr_send_init", syscall!(send_init));
+    builder.add_func("gr_send_push", syscall!(send_push));
+    builder.add_func("gr_send_wgas", syscall!(send_wgas));
+    builder.add_func("gr_send_with_meta", syscall!(send_with_meta));
+    builder.add_func("gr_send_with_reply_to", syscall!(send_with_reply_to));
+    builder.add_func("gr_signal_details", syscall!(signal_details));
+    builder.add_func("gr_size", syscall!(size));
+    builder.add_func("gr_source", syscall!(source));
+    builder.add_func("gr_source_code_id", syscall!(source_code_id));
+    builder.add_func("gr_stack_limit", syscall!(stack_limit));
+    builder.add_func("gr_value", syscall!(value));
+    builder.add_func("gr_value_available", syscall!(value_available));
+    builder.add_func("gr_spendable_value", syscall!(spendable_value));
+    builder.add_func("gr_wait", syscall!(wait));
+    builder.add_func("gr_wait_for", syscall!(wait_for));
+    builder.add_func("gr_wait_budget_remaining", syscall!(wait_budget_remaining));
+    builder.add_func("gr_wait_if", syscall!(wait_if));
+    builder.add_func("gr_wait_up_to", syscall!(wait_up_to));
+    builder.add_func("gr_wake", syscall!(wake));
+}
+
 impl<E> Environment<E> for SandboxEnvironment
 where
     E: Ext + IntoExtInfo + 'static,
@@ -111,47 +244,19 @@ where
         let mut builder = EnvBuilder::<E> {
             env_def_builder: EnvironmentDefinitionBuilder::new(),
             forbidden_funcs: &ext.forbidden_funcs().clone(),
+            funcs: Default::default(),
         };
 
-        builder.add_func("gr_block_height", Funcs::block_height);
-        builder.add_func("gr_block_timestamp", Funcs::block_timestamp);
-        builder.add_func("gr_create_program", Funcs::create_program);
-        builder.add_func("gr_create_program_wgas", Funcs::create_program_wgas);
-        builder.add_func("gr_debug", Funcs::debug);
-        builder.add_func("gr_error", Funcs::error);
-        builder.add_func("gr_exit", Funcs::exit);
-        builder.add_func("gr_exit_code", Funcs::exit_code);
-        builder.add_func("gr_gas_available", Funcs::gas_available);
-        builder.add_func("gr_leave", Funcs::leave);
-        builder.add_func("gr_msg_id", Funcs::msg_id);
-        builder.add_func("gr_origin", Funcs::origin);
-        builder.add_func("gr_program_id", Funcs::program_id);
-        builder.add_func("gr_read", Funcs::read);
-        builder.add_func("gr_reply", Funcs::reply);
-        builder.add_func("gr_reply_commit", Funcs::reply_commit);
-        builder.add_func("gr_reply_commit_wgas", Funcs::reply_commit_wgas);
-        builder.add_func("gr_reply_push", Funcs::reply_push);
-        builder.add_func("gr_reply_to", Funcs::reply_to);
-        builder.add_func("gr_reply_wgas", Funcs::reply_wgas);
-        builder.add_func("gr_send", Funcs::send);
-        builder.add_func("gr_send_commit", Funcs::send_commit);
-        builder.add_func("gr_send_commit_wgas", Funcs::send_commit_wgas);
-        builder.add_func("gr_send_init", Funcs::send_init);
-        builder.add_func("gr_send_push", Funcs::send_push);
-        builder.add_func("gr_send_wgas", Funcs::send_wgas);
-        builder.add_func("gr_size", Funcs::size);
-        builder.add_func("gr_source", Funcs::source);
-        builder.add_func("gr_value", Funcs::value);
-        builder.add_func("gr_value_available", Funcs::value_available);
-        builder.add_func("gr_wait", Funcs::wait);
-        builder.add_func("gr_wait_for", Funcs::wait_for);
-        builder.add_func("gr_wait_up_to", Funcs::wait_up_to);
-        builder.add_func("gr_wake", Funcs::wake);
+        add_syscalls(&mut builder);
+
         let mut env_builder: EnvironmentDefinitionBuilder<_> = builder.into();
 
-        let mem: DefaultExecutorMemory = match SandboxMemory::new(mem_size.0, None) {
-            Ok(mem) => mem,
-            Err(e) => return Err(CreateEnvMemory(e)),
+        // Code id is only computed while memories are pooled, and it's kept
+        // with the memory, so it isn't computed again once it's released.
+        let code_id = pool::is_enabled().then(|| CodeId::generate(binary));
+        let mem: DefaultExecutorMemory = match code_id.and_then(|id| pool::acquire(id, mem_size)) {
+            Some(mem) => mem,
+            None => SandboxMemory::new(mem_size.0, None).map_err(CreateEnvMemory)?,
         };
 
         env_builder.add_memory("env", "memory", mem.clone());
@@ -159,12 +264,13 @@ where
         env_builder.add_host_func("env", "free", Funcs::free);
         env_builder.add_host_func("env", "gas", Funcs::gas);
 
-        let mut memory_wrap = MemoryWrap::new(mem.clone());
+        let mut memory_wrap = MemoryWrap::new(mem.clone()).with_code_id(code_id);
         let mut runtime = Runtime {
             ext,
             memory: &mem,
             memory_wrap: &mut memory_wrap,
             err: FuncError::Terminated(TerminationReason::Success),
+            canaries: Default::default(),
         };
 
         let mut instance = match Instance::new(binary, &env_builder, &mut runtime) {
@@ -180,13 +286,26 @@ where
         pre_execution_handler(runtime.memory_wrap, stack_end_page)
             .map_err(|e| PreExecutionHandler(e.to_string()))?;
 
+        #[cfg(feature = "memory-canaries")]
+        {
+            let heap_base = instance
+                .get_global_val(HEAP_BASE_EXPORT_NAME)
+                .and_then(|global| global.as_i32());
+            runtime.canaries = MemoryCanaries::place(runtime.memory_wrap, heap_base);
+        }
+
         let res = if entries.contains(entry_point) {
             instance.invoke(entry_point.into_entry(), &[], &mut runtime)
         } else {
             Ok(ReturnValue::Unit)
         };
 
-        let Runtime { ext, err: trap, .. } = runtime;
+        let Runtime {
+            ext,
+            err: trap,
+            canaries,
+            ..
+        } = runtime;
 
         log::debug!("SandboxEnvironment::execute res = {res:?}");
 
@@ -207,10 +326,45 @@ where
             TerminationReason::Success
         };
 
+        // Corruption may cause any other termination, so it's reported first.
+        let termination = match canaries.check(&memory_wrap) {
+            Ok(()) => termination,
+            Err(region) => {
+                TerminationReason::Trap(TrapExplanation::MemoryCorruptionDetected { region })
+            }
+        };
+        canaries.remove(&mut memory_wrap);
+
         drop(instance);
         Ok(BackendReport {
             termination_reason: termination,
             memory_wrap,
         })
     }
+
+    fn release_memory(memory: Self::Memory, pages_data: &BTreeMap<PageNumber, PageBuf>) {
+        pool::release(memory, pages_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gear_backend_common::{mock::MockExt, SYSCALLS};
+
+    #[test]
+    fn syscalls_match_common_list() {
+        let mut builder = EnvBuilder::<MockExt> {
+            env_def_builder: EnvironmentDefinitionBuilder::new(),
+            forbidden_funcs: &Default::default(),
+            funcs: Default::default(),
+        };
+
+        add_syscalls(&mut builder);
+
+        assert_eq!(
+            builder.funcs,
+            SYSCALLS.iter().map(|(name, _)| *name).collect()
+        );
+    }
 }
@@ -28,19 +28,26 @@ use core::{
     convert::{TryFrom, TryInto},
     fmt::{self, Display},
     marker::PhantomData,
+    num::NonZeroU32,
     ops::Range,
     slice::Iter,
 };
+#[cfg(feature = "debug-context")]
+use gear_backend_common::DebugContext;
 use gear_backend_common::{
-    error_processor::{IntoExtError, ProcessError},
+    error_processor::{ErrorLen, IntoExtError, ProcessError},
+    read::{ReadContext, ReadOrigin},
+    records::{find_record, RecordLookup, RecordsError},
+    ring::{RingBuffer, RingBufferError},
     AsTerminationReason, IntoExtInfo, RuntimeCtx, RuntimeCtxError, TerminationReason,
     TrapExplanation,
 };
 use gear_core::{
     buffer::{RuntimeBuffer, RuntimeBufferSizeError},
+    counters::CounterKey,
     env::Ext,
-    ids::{MessageId, ProgramId},
-    message::{HandlePacket, InitPacket, PayloadSizeError, ReplyPacket},
+    ids::{MessageId, ProgramId, ReservationId},
+    message::{HandlePacket, InitPacket, MetadataSizeError, PayloadSizeError, ReplyPacket},
 };
 use gear_core_errors::{CoreError, MemoryError};
 use sp_sandbox::{HostError, ReturnValue, Value};
@@ -74,6 +81,10 @@ pub(crate) fn return_i64<T: TryInto<i64>>(val: T) -> SyscallOutput {
         .map_err(|_| HostError)
 }
 
+pub(crate) fn return_error_len(len: ErrorLen) -> ReturnValue {
+    Value::I32(len.into()).into()
+}
+
 #[derive(Debug, derive_more::Display, derive_more::From)]
 pub enum FuncError<E: Display> {
     #[display(fmt = "{}", _0)]
@@ -89,6 +100,9 @@ pub enum FuncError<E: Display> {
     PayloadSize(PayloadSizeError),
     #[from]
     #[display(fmt = "{}", _0)]
+    MetadataSize(MetadataSizeError),
+    #[from]
+    #[display(fmt = "{}", _0)]
     RuntimeBufferSize(RuntimeBufferSizeError),
     #[display(fmt = "Cannot set u128: {}", _0)]
     SetU128(MemoryError),
@@ -103,19 +117,52 @@ pub enum FuncError<E: Display> {
     #[display(fmt = "Terminated: {:?}", _0)]
     Terminated(TerminationReason),
     #[display(
-        fmt = "Cannot take data by indexes {:?} from message with size {}",
+        fmt = "Cannot take data by indexes {:?} from message with size {} {}",
         _0,
-        _1
+        _1,
+        _2
     )]
-    ReadWrongRange(Range<usize>, usize),
-    #[display(fmt = "Overflow at {} + len {} in `gr_read`", _0, _1)]
-    ReadLenOverflow(usize, usize),
+    ReadWrongRange(Range<usize>, usize, ReadContext),
+    #[display(fmt = "Overflow at {} + len {} {}", _0, _1, _2)]
+    ReadLenOverflow(usize, usize, ReadContext),
+    #[from]
+    #[display(fmt = "{}", _0)]
+    RingBuffer(RingBufferError),
+}
+
+/// Context of the failed read of the `payload` at `at`, keeping the payload
+/// excerpt only if the backend is built for debugging.
+fn read_context(
+    syscall: &'static str,
+    origin: ReadOrigin,
+    payload: &[u8],
+    at: usize,
+) -> ReadContext {
+    let context = ReadContext::new(syscall, origin);
+
+    if cfg!(feature = "debug-context") {
+        context.with_excerpt(payload, at)
+    } else {
+        context
+    }
 }
 
 impl<E> FuncError<E>
 where
     E: fmt::Display,
 {
+    fn read_records(err: RecordsError, payload: &[u8]) -> Self {
+        let context = |at| read_context("gr_read_records", ReadOrigin::Sliced, payload, at);
+
+        match err {
+            RecordsError::WrongRange(range, size) => {
+                let context = context(range.start);
+                Self::ReadWrongRange(range, size, context)
+            }
+            RecordsError::LenOverflow(at, len) => Self::ReadLenOverflow(at, len, context(at)),
+        }
+    }
+
     fn as_core(&self) -> Option<&E> {
         match self {
             Self::Core(err) => Some(err),
@@ -179,7 +226,7 @@ where
             let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
             let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -192,11 +239,161 @@ where
             Ok(error_len)
         };
 
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn send_with_meta(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "send_with_meta, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let metadata_ptr = pop_i32(&mut args)?;
+        let metadata_len = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let metadata = ctx.read_memory(metadata_ptr, metadata_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let packet = HandlePacket::new(dest, payload, value).with_metadata(metadata);
+            let error_len = ctx
+                .ext
+                .send(packet, delay)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn send_with_reply_to(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "send_with_reply_to, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let reply_destination_ptr = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let reply_destination: ProgramId = ctx.read_memory_as(reply_destination_ptr)?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let packet =
+                HandlePacket::new(dest, payload, value).with_reply_destination(reply_destination);
+            let error_len = ctx
+                .ext
+                .send(packet, delay)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn send_and_wait(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "send_and_wait, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+
+            let mut sent = None;
+            let error_len = ctx
+                .ext
+                .send(HandlePacket::new(dest, payload, value), None)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    sent = Some(message_id);
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+
+            // Message isn't sent, so there is nothing to wait for.
+            let message_id = match sent {
+                Some(message_id) => message_id,
+                None => return Ok(error_len),
+            };
+
+            ctx.ext
+                .wait_for_reply(message_id)
+                .map_err(FuncError::Core)?;
+            Err(FuncError::Terminated(TerminationReason::Wait(None)))
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn forward(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "forward, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let program_id_ptr = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let error_len = ctx
+                .ext
+                .forward(dest, value, delay)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn send_wgas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -215,7 +412,7 @@ where
             let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
             let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -230,11 +427,42 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn send_charge_recipient(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "send_charge_recipient, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let gas_limit = pop_i64(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+
+            let error_len = ctx
+                .ext
+                .send_charge_recipient(HandlePacket::new_with_gas(dest, payload, gas_limit, value))
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn send_commit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -250,7 +478,7 @@ where
         let mut f = || {
             let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -266,11 +494,10 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn send_commit_wgas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -287,7 +514,7 @@ where
         let mut f = || {
             let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -303,11 +530,10 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn send_init(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -327,11 +553,10 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn send_push(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -352,11 +577,10 @@ where
                 .error_len();
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn read(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -368,14 +592,20 @@ where
         let dest = pop_i32(&mut args)?;
 
         ctx.write_validated_output(dest, |ext| {
-            let msg = ext.read().map_err(FuncError::Core)?;
+            let msg = ext.read_part(len).map_err(FuncError::Core)?;
+
+            let context = || read_context("gr_read", ReadOrigin::Requested, msg, at);
 
             let last_idx = at
                 .checked_add(len)
-                .ok_or(FuncError::ReadLenOverflow(at, len))?;
+                .ok_or_else(|| FuncError::ReadLenOverflow(at, len, context()))?;
 
             if last_idx > msg.len() {
-                return Err(FuncError::ReadWrongRange(at..last_idx, msg.len()));
+                return Err(FuncError::ReadWrongRange(
+                    at..last_idx,
+                    msg.len(),
+                    context(),
+                ));
             }
 
             Ok(&msg[at..last_idx])
@@ -387,6 +617,96 @@ where
         })
     }
 
+    pub fn read_ring(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "read_ring, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let at: usize = pop_i32(&mut args)?;
+        let len: usize = pop_i32(&mut args)?;
+        let ring = RingBuffer {
+            ptr: pop_i32(&mut args)?,
+            capacity: pop_i32(&mut args)?,
+            offset: pop_i32(&mut args)?,
+        };
+
+        let res = ctx.write_validated_ring_output(ring, |ext| {
+            let msg = ext.read_part(len).map_err(FuncError::Core)?;
+
+            let context = || read_context("gr_read_ring", ReadOrigin::Requested, msg, at);
+
+            let last_idx = at
+                .checked_add(len)
+                .ok_or_else(|| FuncError::ReadLenOverflow(at, len, context()))?;
+
+            if last_idx > msg.len() {
+                return Err(FuncError::ReadWrongRange(
+                    at..last_idx,
+                    msg.len(),
+                    context(),
+                ));
+            }
+
+            Ok(&msg[at..last_idx])
+        });
+
+        match res {
+            Ok(next_offset) => return_i32(next_offset),
+            Err(err) => {
+                ctx.err = err;
+                Err(HostError)
+            }
+        }
+    }
+
+    pub fn read_records(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "read_records, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let index: u32 = pop_i32(&mut args)?;
+        let buffer_ptr = pop_i32(&mut args)?;
+        let buffer_len: usize = pop_i32(&mut args)?;
+        let record_len_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let mut lookup = None;
+            ctx.write_validated_output(buffer_ptr, |ext| {
+                let msg = ext.read().map_err(FuncError::Core)?;
+                let found =
+                    find_record(msg, index).map_err(|err| FuncError::read_records(err, msg))?;
+
+                // Record is truncated to the buffer, its full length is written
+                // separately for the caller to detect truncation.
+                let record: &[u8] = match &found {
+                    RecordLookup::Found(range) => {
+                        &msg[range.start..range.end.min(range.start.saturating_add(buffer_len))]
+                    }
+                    RecordLookup::NotFound { .. } => &[],
+                };
+
+                lookup = Some(found);
+                Ok(record)
+            })?;
+
+            let error_len = match lookup.expect("set once the payload is read") {
+                RecordLookup::Found(range) => {
+                    ctx.write_output(record_len_ptr, &(range.len() as u32).to_le_bytes())?;
+                    ErrorLen::ok()
+                }
+                RecordLookup::NotFound { count } => ctx
+                    .ext
+                    .record_not_found(index, count)
+                    .process_error()
+                    .map_err(FuncError::Core)?
+                    .error_len(),
+            };
+            Ok(error_len)
+        };
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn size(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "size");
         let size = ctx.ext.size().map_err(FuncError::Core);
@@ -400,23 +720,129 @@ where
         }
     }
 
-    pub fn exit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
-        let value_dest_ptr = pop_i32(&mut args.iter())?;
-        sys_trace!(target: "syscall::gear", "exit, value_dest_ptr = {:#x}", value_dest_ptr);
+    pub fn message_meta(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "message_meta, args = {}", args_to_str(args));
+        let mut args = args.iter();
 
-        let mut res = || -> Result<(), _> {
-            let value_dest: ProgramId = ctx.read_memory_as(value_dest_ptr)?;
-            ctx.ext.exit().map_err(FuncError::Core)?;
-            Err(FuncError::Terminated(TerminationReason::Exit(value_dest)))
-        };
-        if let Err(err) = res() {
-            ctx.err = err;
-        }
+        let metadata_ptr = pop_i32(&mut args)?;
 
-        Err(HostError)
+        let mut len = 0;
+        let res = ctx.write_validated_output(metadata_ptr, |ext| {
+            let metadata = ext.message_meta().map_err(FuncError::Core)?;
+            len = metadata.len();
+            Ok(metadata)
+        });
+
+        match res {
+            Ok(()) => return_i32(len),
+            Err(err) => {
+                ctx.err = err;
+                Err(HostError)
+            }
+        }
     }
 
-    pub fn exit_code(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+    pub fn mailbox_contains(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "mailbox_contains, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let user_id_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let user_id: ProgramId = ctx.read_memory_as(user_id_ptr)?;
+            let message_id: MessageId = ctx.read_memory_as(message_id_ptr)?;
+            ctx.ext
+                .mailbox_contains(user_id, message_id)
+                .map_err(FuncError::Core)
+        };
+
+        match f() {
+            Ok(contains) => return_i32(contains),
+            Err(err) => {
+                ctx.err = err;
+                Err(HostError)
+            }
+        }
+    }
+
+    pub fn max_payload_size(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "max_payload_size");
+        let max_payload_size = ctx.ext.max_payload_size().map_err(FuncError::Core);
+
+        match max_payload_size {
+            Ok(max_payload_size) => return_i32(max_payload_size),
+            Err(err) => {
+                ctx.err = err;
+                Err(HostError)
+            }
+        }
+    }
+
+    pub fn exit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        let value_dest_ptr = pop_i32(&mut args.iter())?;
+        sys_trace!(target: "syscall::gear", "exit, value_dest_ptr = {:#x}", value_dest_ptr);
+
+        let mut res = || -> Result<(), _> {
+            let value_dest: ProgramId = ctx.read_memory_as(value_dest_ptr)?;
+            ctx.ext.exit().map_err(FuncError::Core)?;
+            Err(FuncError::Terminated(TerminationReason::Exit(value_dest)))
+        };
+        if let Err(err) = res() {
+            ctx.err = err;
+        }
+
+        Err(HostError)
+    }
+
+    pub fn schedule_exit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "schedule_exit, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let block = pop_i32(&mut args)?;
+        let inheritor_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let inheritor: ProgramId = ctx.read_memory_as(inheritor_ptr)?;
+            let error_len = ctx
+                .ext
+                .schedule_exit(block, inheritor)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn set_entry_budget(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "set_entry_budget, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let entry = pop_i32(&mut args)?;
+        let gas = pop_i64(&mut args)?;
+
+        let mut f = || {
+            let error_len = ctx
+                .ext
+                .set_entry_budget(entry, gas)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len();
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn exit_code(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "exit_code");
         let exit_code = ctx.ext.exit_code().map_err(FuncError::Core).map_err(|e| {
             ctx.err = e;
@@ -431,6 +857,32 @@ where
         }
     }
 
+    pub fn signal_details(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "signal_details, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let message_id_ptr = pop_i32(&mut args)?;
+        let code_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let error_len = ctx
+                .ext
+                .signal_details()
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|(message_id, code)| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())?;
+                    ctx.write_output(code_ptr, &code.to_u32().to_le_bytes())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn gas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "gas::gear", "gas, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -499,6 +951,62 @@ where
         return_i32(block_height)
     }
 
+    pub fn stack_limit(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "stack_limit");
+        let stack_limit = ctx
+            .ext
+            .stack_limit()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i32(stack_limit.unwrap_or(0))
+    }
+
+    pub fn children_count(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "children_count");
+        let children_count = ctx
+            .ext
+            .children_count()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i64(children_count)
+    }
+
+    pub fn children_gas_burned(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "children_gas_burned");
+        let children_gas_burned = ctx
+            .ext
+            .children_gas_burned()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i64(children_gas_burned)
+    }
+
+    pub fn codes_count(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "codes_count");
+        let codes_count = ctx
+            .ext
+            .codes_count()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i64(codes_count)
+    }
+
     pub fn block_timestamp(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "block_timestamp");
         let block_timestamp =
@@ -513,6 +1021,88 @@ where
         return_i64(block_timestamp)
     }
 
+    pub fn block_duration(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "block_duration");
+        let block_duration = ctx
+            .ext
+            .block_duration()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i64(block_duration)
+    }
+
+    pub fn block_hash(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "block_hash, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let height = pop_i32(&mut args)?;
+        let hash_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let error_len = ctx
+                .ext
+                .block_hash(height)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|hash| ctx.write_output(hash_ptr, &hash))?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn tx_hash(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "tx_hash, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let hash_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let error_len = ctx
+                .ext
+                .tx_hash()
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|hash| ctx.write_output(hash_ptr, &hash))?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn context_limits(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "context_limits, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let limits_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let limits = ctx.ext.context_limits().map_err(FuncError::Core)?;
+
+            let mut buf = [0u8; 12];
+            buf[..4].copy_from_slice(&limits.handles.to_le_bytes());
+            buf[4..8].copy_from_slice(&limits.ids.to_le_bytes());
+            buf[8..].copy_from_slice(&limits.payload_bytes.to_le_bytes());
+
+            ctx.write_output(limits_ptr, &buf).map_err(Into::into)
+        };
+        f().map(|()| ReturnValue::Unit)
+            .map_err(|err: FuncError<_>| {
+                ctx.err = err;
+                HostError
+            })
+    }
+
     pub fn origin(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "origin, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -544,7 +1134,7 @@ where
         let mut f = || {
             let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -556,11 +1146,45 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn reply_with_code(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "reply_with_code, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let exit_code = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let error_len = ctx
+                .ext
+                .reply(
+                    ReplyPacket::new_with_exit_code(payload, exit_code, value),
+                    delay,
+                )
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn reply_wgas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -577,7 +1201,7 @@ where
         let mut f = || {
             let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -589,11 +1213,10 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn reply_commit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -606,7 +1229,7 @@ where
 
         let mut f = || {
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -618,11 +1241,10 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn reply_commit_wgas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -636,7 +1258,7 @@ where
 
         let mut f = || {
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -651,26 +1273,55 @@ where
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn set_default_gas_limit(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "set_default_gas_limit, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let gas_limit = pop_i64(&mut args)?;
+
+        ctx.ext
+            .set_default_gas_limit(gas_limit)
+            .map(|()| ReturnValue::Unit)
             .map_err(|err| {
-                ctx.err = err;
+                ctx.err = FuncError::Core(err);
                 HostError
             })
     }
 
-    pub fn reply_to(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
-        sys_trace!(target: "syscall::gear", "reply_to, args = {}", args_to_str(args));
+    pub fn set_charge_allowance(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "set_charge_allowance, args = {}", args_to_str(args));
         let mut args = args.iter();
 
-        let dest = pop_i32(&mut args)?;
-
-        let message_id = ctx.ext.reply_to().map_err(FuncError::Core).map_err(|err| {
-            ctx.err = err;
-            HostError
-        })?;
+        let allowance = pop_i64(&mut args)?;
 
-        if let Some(id) = message_id {
-            ctx.write_output(dest, id.as_ref()).map_err(|err| {
+        ctx.ext
+            .set_charge_allowance(allowance)
+            .map(|()| ReturnValue::Unit)
+            .map_err(|err| {
+                ctx.err = FuncError::Core(err);
+                HostError
+            })
+    }
+
+    pub fn reply_to(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "reply_to, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let dest = pop_i32(&mut args)?;
+
+        let message_id = ctx.ext.reply_to().map_err(FuncError::Core).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })?;
+
+        if let Some(id) = message_id {
+            ctx.write_output(dest, id.as_ref()).map_err(|err| {
                 ctx.err = err.into();
                 HostError
             })?;
@@ -699,11 +1350,10 @@ where
                 .error_len();
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn debug(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -717,6 +1367,13 @@ where
             let mut data = RuntimeBuffer::try_new_default(str_len)?;
             ctx.read_memory_into_buf(str_ptr, data.get_mut())?;
             let s = String::from_utf8(data.into_vec()).map_err(FuncError::DebugString)?;
+
+            #[cfg(feature = "debug-context")]
+            {
+                let context = DebugContext::capture(ctx.ext).map_err(FuncError::Core)?;
+                log::debug!(target: "gwasm", "DEBUG CONTEXT: {}", context);
+            }
+
             ctx.ext.debug(&s).map_err(FuncError::Core)?;
             Ok(())
         };
@@ -726,6 +1383,29 @@ where
         })
     }
 
+    pub fn trace(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "trace, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let level = pop_i32(&mut args)?;
+        let data_ptr = pop_i32(&mut args)?;
+        let data_len: usize = pop_i32(&mut args)?;
+
+        let mut f = || {
+            // The data is read whether the node logs the level or not,
+            // so the execution doesn't depend on the node settings.
+            let mut data = RuntimeBuffer::try_new_default(data_len)?;
+            ctx.read_memory_into_buf(data_ptr, data.get_mut())?;
+            let s = String::from_utf8(data.into_vec()).map_err(FuncError::DebugString)?;
+
+            ctx.ext.trace(level, &s).map_err(FuncError::Core)
+        };
+        f().map(|()| ReturnValue::Unit).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn gas_available(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "gas_available");
         let gas_available = ctx
@@ -737,6 +1417,44 @@ where
         return_i64(gas_available)
     }
 
+    pub fn gas_allowance(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "gas_allowance");
+        let gas_allowance = ctx
+            .ext
+            .gas_allowance()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i64(gas_allowance)
+    }
+
+    pub fn is_init(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "is_init");
+        let is_init = ctx.ext.is_init().map_err(FuncError::Core).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })?;
+
+        return_i32(is_init)
+    }
+
+    pub fn resumed_from_wait(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "resumed_from_wait");
+        let resumed = ctx
+            .ext
+            .resumed_from_wait()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i32(resumed)
+    }
+
     pub fn msg_id(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "msg_id, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -754,6 +1472,20 @@ where
         })
     }
 
+    pub fn execution_id(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "execution_id");
+        let execution_id = ctx
+            .ext
+            .execution_id()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        return_i64(execution_id)
+    }
+
     pub fn program_id(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "program_id, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -793,6 +1525,28 @@ where
         res
     }
 
+    pub fn source_code_id(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "source_code_id, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let code_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let error_len = ctx
+                .ext
+                .source_code_id()
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|code_id| ctx.write_output(code_id_ptr, code_id.as_ref()))?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
     pub fn value(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "value, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -801,7 +1555,7 @@ where
 
         let mut f = || -> Result<(), FuncError<_>> {
             let value = ctx.ext.value().map_err(FuncError::Core)?;
-            ctx.write_output(value_ptr, &value.encode())
+            ctx.write_output(value_ptr, &value.to_le_bytes())
                 .map_err(Into::into)
         };
         f().map(|()| ReturnValue::Unit).map_err(|err| {
@@ -818,7 +1572,24 @@ where
 
         let mut f = || {
             let value_available = ctx.ext.value_available().map_err(FuncError::Core)?;
-            ctx.write_output(value_ptr, &value_available.encode())
+            ctx.write_output(value_ptr, &value_available.to_le_bytes())
+                .map_err(Into::into)
+        };
+        f().map(|()| ReturnValue::Unit).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn spendable_value(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "spendable_value, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let value_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let spendable_value = ctx.ext.spendable_value().map_err(FuncError::Core)?;
+            ctx.write_output(value_ptr, &spendable_value.to_le_bytes())
                 .map_err(Into::into)
         };
         f().map(|()| ReturnValue::Unit).map_err(|err| {
@@ -851,6 +1622,20 @@ where
         Err(HostError)
     }
 
+    pub fn wait_if(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "wait_if, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let condition = pop_i32::<i32>(&mut args)? != 0;
+
+        ctx.err = match ctx.ext.wait_if(condition).map_err(FuncError::Core) {
+            Ok(()) if !condition => return Ok(ReturnValue::Unit),
+            Ok(()) => FuncError::Terminated(TerminationReason::Wait(None)),
+            Err(e) => e,
+        };
+        Err(HostError)
+    }
+
     pub fn wait_for(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "wait_for, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -889,6 +1674,21 @@ where
         Err(HostError)
     }
 
+    pub fn wait_budget_remaining(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "wait_budget_remaining");
+        let blocks = ctx
+            .ext
+            .wait_budget_remaining()
+            .map_err(FuncError::Core)
+            .map_err(|err| {
+                ctx.err = err;
+                HostError
+            })?;
+
+        // Reinterpreted as `u32` by the program.
+        return_i32(blocks as i32)
+    }
+
     pub fn wake(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
         sys_trace!(target: "syscall::gear", "wake, args = {}", args_to_str(args));
         let mut args = args.iter();
@@ -898,7 +1698,7 @@ where
 
         let mut f = || {
             let waker_id: MessageId = ctx.read_memory_as(waker_id_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             ctx.ext.wake(waker_id, delay).map_err(FuncError::Core)
         };
@@ -927,7 +1727,7 @@ where
             let salt = ctx.read_memory(salt_ptr, salt_len)?;
             let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -937,16 +1737,15 @@ where
                 )
                 .process_error()
                 .map_err(FuncError::Core)?
-                .error_len_on_success(|new_actor_id| {
+                .error_len_on_success(|(_, new_actor_id)| {
                     ctx.write_output(program_id_ptr, new_actor_id.as_ref())
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
-            .map_err(|err| {
-                ctx.err = err;
-                HostError
-            })
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn create_program_wgas(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
@@ -968,7 +1767,89 @@ where
             let salt = ctx.read_memory(salt_ptr, salt_len)?;
             let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
             let value: u128 = ctx.read_memory_as(value_ptr)?;
-            let delay: u32 = ctx.read_memory_as(delay_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let error_len = ctx
+                .ext
+                .create_program(
+                    InitPacket::new_with_gas(code_hash.into(), salt, payload, gas_limit, value),
+                    delay,
+                )
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|(_, new_actor_id)| {
+                    ctx.write_output(program_id_ptr, new_actor_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn create_program_for_reply(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "create_program_for_reply, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let code_hash_ptr = pop_i32(&mut args)?;
+        let salt_ptr = pop_i32(&mut args)?;
+        let salt_len = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let program_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let code_hash: [u8; 32] = ctx.read_memory_as(code_hash_ptr)?;
+            let salt = ctx.read_memory(salt_ptr, salt_len)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let error_len = ctx
+                .ext
+                .create_program(
+                    InitPacket::new(code_hash.into(), salt, payload, value),
+                    delay,
+                )
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|(init_message_id, new_actor_id)| {
+                    ctx.write_output(message_id_ptr, init_message_id.as_ref())?;
+                    ctx.write_output(program_id_ptr, new_actor_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn create_program_wgas_for_reply(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "create_program_wgas_for_reply, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let code_hash_ptr = pop_i32(&mut args)?;
+        let salt_ptr = pop_i32(&mut args)?;
+        let salt_len = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let gas_limit = pop_i64(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let program_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let code_hash: [u8; 32] = ctx.read_memory_as(code_hash_ptr)?;
+            let salt = ctx.read_memory(salt_ptr, salt_len)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
 
             let error_len = ctx
                 .ext
@@ -978,16 +1859,231 @@ where
                 )
                 .process_error()
                 .map_err(FuncError::Core)?
-                .error_len_on_success(|new_actor_id| {
+                .error_len_on_success(|(init_message_id, new_actor_id)| {
+                    ctx.write_output(message_id_ptr, init_message_id.as_ref())?;
                     ctx.write_output(program_id_ptr, new_actor_id.as_ref())
                 })?;
             Ok(error_len)
         };
-        f().map(|code| Value::I32(code as i32).into())
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn new_handle(ctx: &mut Runtime<E>, _args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "new_handle");
+        let handle = ctx
+            .ext
+            .new_handle()
+            .map_err(FuncError::Core)
             .map_err(|err| {
                 ctx.err = err;
                 HostError
-            })
+            })?;
+
+        return_i32(handle)
+    }
+
+    pub fn delayed_eta(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "delayed_eta, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let message_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let message_id: MessageId = ctx.read_memory_as(message_id_ptr)?;
+            ctx.ext.delayed_eta(message_id).map_err(FuncError::Core)
+        };
+
+        let eta = f().map_err(|err| {
+            ctx.err = err;
+            HostError
+        })?;
+
+        return_i32(eta)
+    }
+
+    pub fn bump_counter(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "bump_counter, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let key_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let key: CounterKey = ctx.read_memory_as(key_ptr)?;
+            ctx.ext.bump_counter(key).map_err(FuncError::Core)
+        };
+
+        let counter = f().map_err(|err| {
+            ctx.err = err;
+            HostError
+        })?;
+
+        return_i64(counter)
+    }
+
+    pub fn reserve_value(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "reserve_value, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let value_ptr = pop_i32(&mut args)?;
+        let reservation_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+
+            let error_len = ctx
+                .ext
+                .reserve_value(value)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|reservation_id| {
+                    ctx.write_output(reservation_id_ptr, reservation_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn unreserve_value(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "unreserve_value, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let reservation_id_ptr = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let reservation_id: ReservationId = ctx.read_memory_as(reservation_id_ptr)?;
+
+            let error_len = ctx
+                .ext
+                .unreserve_value(reservation_id)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|value| ctx.write_output(value_ptr, &value.to_le_bytes()))?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn split_value_reservation(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(
+            target: "syscall::gear",
+            "split_value_reservation, args = {}",
+            args_to_str(args)
+        );
+        let mut args = args.iter();
+
+        let reservation_id_ptr = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let split_id_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let reservation_id: ReservationId = ctx.read_memory_as(reservation_id_ptr)?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+
+            let error_len = ctx
+                .ext
+                .split_value_reservation(reservation_id, value)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|split_id| {
+                    ctx.write_output(split_id_ptr, split_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn reservation_send(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "reservation_send, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let reservation_id_ptr = pop_i32(&mut args)?;
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let reservation_id: ReservationId = ctx.read_memory_as(reservation_id_ptr)?;
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let error_len = ctx
+                .ext
+                .reservation_send(
+                    reservation_id,
+                    HandlePacket::new(dest, payload, value),
+                    delay,
+                )
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|message_id| {
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
+    }
+
+    pub fn reserve_and_send(ctx: &mut Runtime<E>, args: &[Value]) -> SyscallOutput {
+        sys_trace!(target: "syscall::gear", "reserve_and_send, args = {}", args_to_str(args));
+        let mut args = args.iter();
+
+        let amount_ptr = pop_i32(&mut args)?;
+        let program_id_ptr = pop_i32(&mut args)?;
+        let payload_ptr = pop_i32(&mut args)?;
+        let payload_len = pop_i32(&mut args)?;
+        let value_ptr = pop_i32(&mut args)?;
+        let reservation_id_ptr = pop_i32(&mut args)?;
+        let message_id_ptr = pop_i32(&mut args)?;
+        let delay_ptr = pop_i32(&mut args)?;
+
+        let mut f = || {
+            let amount: u128 = ctx.read_memory_as(amount_ptr)?;
+            let dest: ProgramId = ctx.read_memory_as(program_id_ptr)?;
+            let payload = ctx.read_memory(payload_ptr, payload_len)?.try_into()?;
+            let value: u128 = ctx.read_memory_as(value_ptr)?;
+            let delay = NonZeroU32::new(ctx.read_memory_as(delay_ptr)?);
+
+            let error_len = ctx
+                .ext
+                .reserve_and_send(amount, HandlePacket::new(dest, payload, value), delay)
+                .process_error()
+                .map_err(FuncError::Core)?
+                .error_len_on_success(|(reservation_id, message_id)| {
+                    ctx.write_output(reservation_id_ptr, reservation_id.as_ref())?;
+                    ctx.write_output(message_id_ptr, message_id.as_ref())
+                })?;
+            Ok(error_len)
+        };
+
+        f().map(return_error_len).map_err(|err| {
+            ctx.err = err;
+            HostError
+        })
     }
 
     pub fn error(ctx: &mut Runtime<E>, args: &[Value]) -> Result<ReturnValue, HostError> {
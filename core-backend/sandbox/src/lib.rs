@@ -25,6 +25,7 @@ extern crate alloc;
 pub mod env;
 pub mod funcs;
 pub mod memory;
+pub mod pool;
 pub mod runtime;
 
 pub use env::SandboxEnvironment;
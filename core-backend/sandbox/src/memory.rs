@@ -18,50 +18,69 @@
 
 //! sp-sandbox extensions for memory.
 
-use gear_core::memory::{Error, HostPointer, Memory, PageNumber, WasmPageNumber};
+use gear_core::{
+    ids::CodeId,
+    memory::{Error, HostPointer, Memory, PageNumber, WasmPageNumber},
+};
 use sp_sandbox::SandboxMemory;
 
 /// Wrapper for sp_sandbox::Memory.
-pub struct MemoryWrap(sp_sandbox::default_executor::Memory);
+pub struct MemoryWrap {
+    mem: sp_sandbox::default_executor::Memory,
+    /// Code the memory was created for, if the memory may be pooled.
+    code_id: Option<CodeId>,
+}
 
 impl MemoryWrap {
     /// Wrap sp_sandbox::Memory for Memory trait.
     pub fn new(mem: sp_sandbox::default_executor::Memory) -> Self {
-        MemoryWrap(mem)
+        MemoryWrap { mem, code_id: None }
+    }
+
+    pub(crate) fn with_code_id(self, code_id: Option<CodeId>) -> Self {
+        MemoryWrap { code_id, ..self }
+    }
+
+    pub(crate) fn code_id(&self) -> Option<CodeId> {
+        self.code_id
+    }
+
+    pub(crate) fn into_parts(self) -> (sp_sandbox::default_executor::Memory, Option<CodeId>) {
+        (self.mem, self.code_id)
     }
 }
 
 /// Memory interface for the allocator.
 impl Memory for MemoryWrap {
     fn grow(&mut self, pages: WasmPageNumber) -> Result<PageNumber, Error> {
-        self.0
+        self.mem
             .grow(pages.0)
             .map(|prev| prev.into())
             .map_err(|_| Error::OutOfBounds)
     }
 
     fn size(&self) -> WasmPageNumber {
-        self.0.size().into()
+        self.mem.size().into()
     }
 
     fn write(&mut self, offset: usize, buffer: &[u8]) -> Result<(), Error> {
-        self.0
+        self.mem
             .set(offset as u32, buffer)
             .map_err(|_| Error::MemoryAccessError)
     }
 
     fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), Error> {
-        self.0
+        self.mem
             .get(offset as u32, buffer)
             .map_err(|_| Error::MemoryAccessError)
     }
 
     fn data_size(&self) -> usize {
-        self.0.size() as usize * WasmPageNumber::size()
+        self.mem.size() as usize * WasmPageNumber::size()
     }
 
     unsafe fn get_buffer_host_addr_unsafe(&self) -> HostPointer {
-        self.0.get_buff()
+        self.mem.get_buff()
     }
 }
 
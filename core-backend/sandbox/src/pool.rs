@@ -0,0 +1,274 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Pool of linear memories reused across executions.
+//!
+//! Creating a fresh memory for each message is a noticeable part of
+//! processing tiny executions. Memory released after an execution is
+//! restored to the state of a fresh one by zeroing only the pages the
+//! execution left non-zero, and is handed out to the next execution of
+//! the same code with the same memory size.
+//!
+//! Pages already read after the execution are checked with the data at
+//! hand, only the rest of the pages are read from the memory. Pages are
+//! compared by content, so the pool must only get memories which are fully
+//! readable: memories protected by lazy pages are never released into it.
+//!
+//! Id of the code is computed once per execution while pooling is enabled,
+//! and is kept with the memory until it's released.
+
+use crate::memory::MemoryWrap;
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use gear_core::{
+    ids::CodeId,
+    memory::{Memory, PageBuf, PageNumber, WasmPageNumber},
+};
+use sp_sandbox::default_executor::Memory as DefaultExecutorMemory;
+
+type PoolKey = (CodeId, WasmPageNumber);
+
+/// Memories released by finished executions.
+pub struct MemoryPool {
+    capacity: usize,
+    len: usize,
+    memories: BTreeMap<PoolKey, Vec<DefaultExecutorMemory>>,
+}
+
+impl MemoryPool {
+    /// New pool keeping at most `capacity` memories.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            len: 0,
+            memories: Default::default(),
+        }
+    }
+
+    /// Amount of memories kept by the pool.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the pool keeps no memories.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Take memory of `size` pages released after execution of `code_id`.
+    pub fn acquire(
+        &mut self,
+        code_id: CodeId,
+        size: WasmPageNumber,
+    ) -> Option<DefaultExecutorMemory> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let memory = self.memories.get_mut(&(code_id, size))?.pop()?;
+        self.len -= 1;
+
+        Some(memory)
+    }
+
+    /// Put memory released after execution of `code_id` into the pool.
+    ///
+    /// `pages_data` is the data of pages read from the memory after the
+    /// execution, so these pages aren't read again.
+    ///
+    /// Memory is dropped if the pool is full or it can't be restored.
+    pub fn release(
+        &mut self,
+        code_id: CodeId,
+        memory: MemoryWrap,
+        pages_data: &BTreeMap<PageNumber, PageBuf>,
+    ) {
+        if self.len >= self.capacity {
+            return;
+        }
+
+        let size = memory.size();
+
+        if let Some(memory) = restore(memory, pages_data) {
+            self.memories
+                .entry((code_id, size))
+                .or_default()
+                .push(memory);
+            self.len += 1;
+        }
+    }
+}
+
+/// Zero all the pages left non-zero, returning memory equal to a fresh one.
+fn restore(
+    mut memory: MemoryWrap,
+    pages_data: &BTreeMap<PageNumber, PageBuf>,
+) -> Option<DefaultExecutorMemory> {
+    let mut buffer = vec![0; PageNumber::size()];
+    let pages = memory.size().to_gear_page().0;
+
+    for page in (0..pages).map(PageNumber) {
+        let dirty = match pages_data.get(&page) {
+            Some(data) => data.iter().any(|byte| *byte != 0),
+            None => {
+                memory.read(page.offset(), &mut buffer).ok()?;
+                buffer.iter().any(|byte| *byte != 0)
+            }
+        };
+
+        if dirty {
+            buffer.fill(0);
+            memory.write(page.offset(), &buffer).ok()?;
+        }
+    }
+
+    Some(memory.into_parts().0)
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static POOL: RefCell<Option<MemoryPool>> = RefCell::new(None);
+}
+
+/// Enable pooling of memories for executions on the current thread,
+/// keeping at most `capacity` of them.
+///
+/// Must only be enabled if memories aren't protected by lazy pages.
+#[cfg(feature = "std")]
+pub fn enable(capacity: usize) {
+    POOL.with(|pool| *pool.borrow_mut() = Some(MemoryPool::new(capacity)));
+}
+
+/// Disable pooling of memories on the current thread, dropping pooled ones.
+#[cfg(feature = "std")]
+pub fn disable() {
+    POOL.with(|pool| *pool.borrow_mut() = None);
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn is_enabled() -> bool {
+    POOL.with(|pool| pool.borrow().is_some())
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn is_enabled() -> bool {
+    false
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn acquire(code_id: CodeId, size: WasmPageNumber) -> Option<DefaultExecutorMemory> {
+    POOL.with(|pool| {
+        pool.borrow_mut()
+            .as_mut()
+            .and_then(|pool| pool.acquire(code_id, size))
+    })
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn acquire(_code_id: CodeId, _size: WasmPageNumber) -> Option<DefaultExecutorMemory> {
+    None
+}
+
+/// Release memory into the pool if it was created for a pooled execution.
+#[cfg(feature = "std")]
+pub(crate) fn release(memory: MemoryWrap, pages_data: &BTreeMap<PageNumber, PageBuf>) {
+    let code_id = match memory.code_id() {
+        Some(code_id) => code_id,
+        None => return,
+    };
+
+    POOL.with(|pool| {
+        if let Some(pool) = pool.borrow_mut().as_mut() {
+            pool.release(code_id, memory, pages_data)
+        }
+    })
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn release(_memory: MemoryWrap, _pages_data: &BTreeMap<PageNumber, PageBuf>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_sandbox::SandboxMemory;
+
+    fn code_id() -> CodeId {
+        CodeId::generate(b"binary")
+    }
+
+    fn new_memory(pages: u32) -> MemoryWrap {
+        MemoryWrap::new(SandboxMemory::new(pages, None).expect("Memory creation failed"))
+    }
+
+    fn assert_zeroed(memory: DefaultExecutorMemory) {
+        let memory = MemoryWrap::new(memory);
+        let mut buffer = vec![1; memory.data_size()];
+        memory.read(0, &mut buffer).unwrap();
+        assert!(buffer.iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn released_memory_restored() {
+        let mut pool = MemoryPool::new(1);
+
+        let mut memory = new_memory(2);
+        memory.write(0x10, &[1; 8]).unwrap();
+        memory.write(0x1_8000, &[2; 8]).unwrap();
+
+        pool.release(code_id(), memory, &Default::default());
+        assert_eq!(pool.len(), 1);
+
+        // Another code or size doesn't get the memory.
+        assert!(pool.acquire(CodeId::generate(b"other"), 2.into()).is_none());
+        assert!(pool.acquire(code_id(), 1.into()).is_none());
+
+        assert_zeroed(pool.acquire(code_id(), 2.into()).expect("Pooled"));
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn known_pages_data_used() {
+        let mut pool = MemoryPool::new(1);
+
+        let mut memory = new_memory(1);
+        memory.write(0x10, &[1; 8]).unwrap();
+        memory.write(0x8000, &[2; 8]).unwrap();
+
+        // Data of the first page is known, the rest of pages are read.
+        let mut data = PageBuf::new_zeroed();
+        data[0x10] = 1;
+        let mut pages_data = BTreeMap::new();
+        pages_data.insert(PageNumber(0), data);
+
+        pool.release(code_id(), memory, &pages_data);
+        assert_zeroed(pool.acquire(code_id(), 1.into()).expect("Pooled"));
+    }
+
+    #[test]
+    fn capacity_respected() {
+        let mut pool = MemoryPool::new(1);
+
+        pool.release(code_id(), new_memory(1), &Default::default());
+        pool.release(code_id(), new_memory(1), &Default::default());
+        assert_eq!(pool.len(), 1);
+
+        assert!(pool.acquire(code_id(), 1.into()).is_some());
+        assert!(pool.acquire(code_id(), 1.into()).is_none());
+    }
+}
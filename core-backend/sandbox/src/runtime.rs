@@ -1,6 +1,6 @@
 use alloc::vec::Vec;
 use codec::{Decode, DecodeAll, MaxEncodedLen};
-use gear_backend_common::{RuntimeCtx, RuntimeCtxError};
+use gear_backend_common::{canary::MemoryCanaries, ring::RingBuffer, RuntimeCtx, RuntimeCtxError};
 use gear_core::{buffer::RuntimeBuffer, env::Ext, memory::WasmPageNumber};
 
 use gear_core_errors::MemoryError;
@@ -13,6 +13,7 @@ pub(crate) struct Runtime<'a, E: Ext> {
     pub memory: &'a DefaultExecutorMemory,
     pub memory_wrap: &'a mut MemoryWrap,
     pub err: FuncError<E::Error>,
+    pub canaries: MemoryCanaries,
 }
 
 impl<'a, E: Ext> Runtime<'a, E> {
@@ -29,6 +30,44 @@ impl<'a, E: Ext> Runtime<'a, E> {
 
         Ok(())
     }
+
+    /// Write the output into the ring buffer, wrapping around at its end.
+    ///
+    /// Returns the write offset following the written data.
+    pub(crate) fn write_validated_ring_output(
+        &mut self,
+        ring: RingBuffer,
+        f: impl FnOnce(&mut E) -> Result<&[u8], FuncError<E::Error>>,
+    ) -> Result<u32, FuncError<E::Error>> {
+        let buf = f(self.ext)?;
+        let (chunks, next_offset) = ring.split(buf.len() as u32)?;
+        let (first, second) = buf.split_at(chunks[0].len as usize);
+
+        for (chunk, data) in chunks.iter().zip([first, second]) {
+            self.memory
+                .set(chunk.ptr, data)
+                .map_err(|_| MemoryError::OutOfBounds)?;
+        }
+
+        Ok(next_offset)
+    }
+
+    /// Check canaries placed in memory are intact, terminating execution otherwise.
+    #[cfg(feature = "memory-canaries")]
+    pub(crate) fn check_canaries(&mut self) -> Result<(), HostError> {
+        use gear_backend_common::{TerminationReason, TrapExplanation};
+        use sp_sandbox::HostError;
+
+        if let Err(region) = self.canaries.check(&*self.memory_wrap) {
+            self.err = FuncError::Terminated(TerminationReason::Trap(
+                TrapExplanation::MemoryCorruptionDetected { region },
+            ));
+
+            return Err(HostError);
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, E: Ext> RuntimeCtx<E> for Runtime<'a, E> {
@@ -28,6 +28,8 @@ use alloc::{
     string::{String, ToString},
 };
 use core::fmt::{self, Display};
+#[cfg(feature = "memory-canaries")]
+use gear_backend_common::canary::{MemoryCanaries, HEAP_BASE_EXPORT_NAME};
 use gear_backend_common::{
     calc_stack_end, error_processor::IntoExtError, AsTerminationReason, BackendReport, Environment,
     IntoExtInfo, StackEndError, TerminationReason, TrapExplanation, STACK_END_EXPORT_NAME,
@@ -60,6 +62,34 @@ pub enum WasmiEnvironmentError {
 /// Environment to run one module at a time providing Ext.
 pub struct WasmiEnvironment;
 
+/// Wraps syscall to check memory canaries after it's called.
+#[cfg(feature = "memory-canaries")]
+macro_rules! syscall {
+    ($func:ident) => {{
+        fn checked<E>(
+            ctx: &mut Runtime<E>,
+            args: &[RuntimeValue],
+        ) -> crate::funcs::SyscallOutput<E::Error>
+        where
+            E: Ext + IntoExtInfo + 'static,
+            E::Error: AsTerminationReason + IntoExtError,
+        {
+            let res = Funcs::$func(ctx, args);
+            ctx.check_canaries()?;
+            res
+        }
+
+        checked::<E>
+    }};
+}
+
+#[cfg(not(feature = "memory-canaries"))]
+macro_rules! syscall {
+    ($func:ident) => {
+        Funcs::$func
+    };
+}
+
 struct HostFuncIndex(usize);
 
 /// Function pointer for specifying functions by the
@@ -289,6 +319,127 @@ impl<T, E: Display> ImportResolver for EnvironmentDefinitionBuilder<T, E> {
     }
 }
 
+// Registers all the syscalls in the environment.
+fn add_syscalls<E>(builder: &mut EnvironmentDefinitionBuilder<Runtime<E>, E::Error>)
+where
+    E: Ext + IntoExtInfo + 'static,
+    E::Error: AsTerminationReason + IntoExtError,
+{
+    builder.add_host_func("env", "gr_block_hash", syscall!(block_hash));
+    builder.add_host_func("env", "gr_tx_hash", syscall!(tx_hash));
+    builder.add_host_func("env", "gr_block_height", syscall!(block_height));
+    builder.add_host_func("env", "gr_block_timestamp", syscall!(block_timestamp));
+    builder.add_host_func("env", "gr_block_duration", syscall!(block_duration));
+    builder.add_host_func("env", "gr_bump_counter", syscall!(bump_counter));
+    builder.add_host_func("env", "gr_children_count", syscall!(children_count));
+    builder.add_host_func(
+        "env",
+        "gr_children_gas_burned",
+        syscall!(children_gas_burned),
+    );
+    builder.add_host_func("env", "gr_codes_count", syscall!(codes_count));
+    builder.add_host_func("env", "gr_context_limits", syscall!(context_limits));
+    builder.add_host_func("env", "gr_create_program", syscall!(create_program));
+    builder.add_host_func(
+        "env",
+        "gr_create_program_wgas",
+        syscall!(create_program_wgas),
+    );
+    builder.add_host_func(
+        "env",
+        "gr_create_program_for_reply",
+        syscall!(create_program_for_reply),
+    );
+    builder.add_host_func(
+        "env",
+        "gr_create_program_wgas_for_reply",
+        syscall!(create_program_wgas_for_reply),
+    );
+    builder.add_host_func("env", "gr_debug", syscall!(debug));
+    builder.add_host_func("env", "gr_trace", syscall!(trace));
+    builder.add_host_func("env", "gr_delayed_eta", syscall!(delayed_eta));
+    builder.add_host_func("env", "gr_reserve_value", syscall!(reserve_value));
+    builder.add_host_func("env", "gr_unreserve_value", syscall!(unreserve_value));
+    builder.add_host_func(
+        "env",
+        "gr_split_value_reservation",
+        syscall!(split_value_reservation),
+    );
+    builder.add_host_func("env", "gr_reservation_send", syscall!(reservation_send));
+    builder.add_host_func("env", "gr_reserve_and_send", syscall!(reserve_and_send));
+    builder.add_host_func("env", "gr_error", syscall!(error));
+    builder.add_host_func("env", "gr_execution_id", syscall!(execution_id));
+    builder.add_host_func("env", "gr_exit", syscall!(exit));
+    builder.add_host_func("env", "gr_exit_code", syscall!(exit_code));
+    builder.add_host_func("env", "gr_schedule_exit", syscall!(schedule_exit));
+    builder.add_host_func("env", "gr_set_entry_budget", syscall!(set_entry_budget));
+    builder.add_host_func("env", "gr_forward", syscall!(forward));
+    builder.add_host_func("env", "gr_gas_available", syscall!(gas_available));
+    builder.add_host_func("env", "gr_gas_allowance", syscall!(gas_allowance));
+    builder.add_host_func("env", "gr_is_init", syscall!(is_init));
+    builder.add_host_func("env", "gr_resumed_from_wait", syscall!(resumed_from_wait));
+    builder.add_host_func("env", "gr_leave", syscall!(leave));
+    builder.add_host_func("env", "gr_mailbox_contains", syscall!(mailbox_contains));
+    builder.add_host_func("env", "gr_max_payload_size", syscall!(max_payload_size));
+    builder.add_host_func("env", "gr_message_meta", syscall!(message_meta));
+    builder.add_host_func("env", "gr_msg_id", syscall!(msg_id));
+    builder.add_host_func("env", "gr_new_handle", syscall!(new_handle));
+    builder.add_host_func("env", "gr_origin", syscall!(origin));
+    builder.add_host_func("env", "gr_program_id", syscall!(program_id));
+    builder.add_host_func("env", "gr_read", syscall!(read));
+    builder.add_host_func("env", "gr_read_records", syscall!(read_records));
+    builder.add_host_func("env", "gr_read_ring", syscall!(read_ring));
+    builder.add_host_func("env", "gr_reply", syscall!(reply));
+    builder.add_host_func("env", "gr_reply_with_code", syscall!(reply_with_code));
+    builder.add_host_func(
+        "env",
+        "gr_set_default_gas_limit",
+        syscall!(set_default_gas_limit),
+    );
+    builder.add_host_func("env", "gr_reply_commit", syscall!(reply_commit));
+    builder.add_host_func("env", "gr_reply_commit_wgas", syscall!(reply_commit_wgas));
+    builder.add_host_func("env", "gr_reply_push", syscall!(reply_push));
+    builder.add_host_func("env", "gr_reply_to", syscall!(reply_to));
+    builder.add_host_func("env", "gr_reply_wgas", syscall!(reply_wgas));
+    builder.add_host_func("env", "gr_send", syscall!(send));
+    builder.add_host_func("env", "gr_send_and_wait", syscall!(send_and_wait));
+    builder.add_host_func(
+        "env",
+        "gr_send_charge_recipient",
+        syscall!(send_charge_recipient),
+    );
+    builder.add_host_func(
+        "env",
+        "gr_set_charge_allowance",
+        syscall!(set_charge_allowance),
+    );
+    builder.add_host_func("env", "gr_send_commit", syscall!(send_commit));
+    builder.add_host_func("env", "gr_send_commit_wgas", syscall!(send_commit_wgas));
+    builder.add_host_func("env", "gr_send_init", syscall!(send_init));
+    builder.add_host_func("env", "gr_send_push", syscall!(send_push));
+    builder.add_host_func("env", "gr_send_wgas", syscall!(send_wgas));
+    builder.add_host_func("env", "gr_send_with_meta", syscall!(send_with_meta));
+    builder.add_host_func("env", "gr_send_with_reply_to", syscall!(send_with_reply_to));
+    builder.add_host_func("env", "gr_signal_details", syscall!(signal_details));
+    builder.add_host_func("env", "gr_size", syscall!(size));
+    builder.add_host_func("env", "gr_source", syscall!(source));
+    builder.add_host_func("env", "gr_source_code_id", syscall!(source_code_id));
+    builder.add_host_func("env", "gr_stack_limit", syscall!(stack_limit));
+    builder.add_host_func("env", "gr_value", syscall!(value));
+    builder.add_host_func("env", "gr_value_available", syscall!(value_available));
+    builder.add_host_func("env", "gr_spendable_value", syscall!(spendable_value));
+    builder.add_host_func("env", "gr_wait", syscall!(wait));
+    builder.add_host_func("env", "gr_wait_for", syscall!(wait_for));
+    builder.add_host_func(
+        "env",
+        "gr_wait_budget_remaining",
+        syscall!(wait_budget_remaining),
+    );
+    builder.add_host_func("env", "gr_wait_if", syscall!(wait_if));
+    builder.add_host_func("env", "gr_wait_up_to", syscall!(wait_up_to));
+    builder.add_host_func("env", "gr_wake", syscall!(wake));
+}
+
 impl<E> Environment<E> for WasmiEnvironment
 where
     E: Ext + IntoExtInfo + 'static,
@@ -320,40 +471,7 @@ where
         );
 
         builder.add_host_func("env", "forbidden", Funcs::forbidden);
-        builder.add_host_func("env", "gr_block_height", Funcs::block_height);
-        builder.add_host_func("env", "gr_block_timestamp", Funcs::block_timestamp);
-        builder.add_host_func("env", "gr_create_program", Funcs::create_program);
-        builder.add_host_func("env", "gr_create_program_wgas", Funcs::create_program_wgas);
-        builder.add_host_func("env", "gr_debug", Funcs::debug);
-        builder.add_host_func("env", "gr_error", Funcs::error);
-        builder.add_host_func("env", "gr_exit", Funcs::exit);
-        builder.add_host_func("env", "gr_exit_code", Funcs::exit_code);
-        builder.add_host_func("env", "gr_gas_available", Funcs::gas_available);
-        builder.add_host_func("env", "gr_leave", Funcs::leave);
-        builder.add_host_func("env", "gr_msg_id", Funcs::msg_id);
-        builder.add_host_func("env", "gr_origin", Funcs::origin);
-        builder.add_host_func("env", "gr_program_id", Funcs::program_id);
-        builder.add_host_func("env", "gr_read", Funcs::read);
-        builder.add_host_func("env", "gr_reply", Funcs::reply);
-        builder.add_host_func("env", "gr_reply_commit", Funcs::reply_commit);
-        builder.add_host_func("env", "gr_reply_commit_wgas", Funcs::reply_commit_wgas);
-        builder.add_host_func("env", "gr_reply_push", Funcs::reply_push);
-        builder.add_host_func("env", "gr_reply_to", Funcs::reply_to);
-        builder.add_host_func("env", "gr_reply_wgas", Funcs::reply_wgas);
-        builder.add_host_func("env", "gr_send", Funcs::send);
-        builder.add_host_func("env", "gr_send_commit", Funcs::send_commit);
-        builder.add_host_func("env", "gr_send_commit_wgas", Funcs::send_commit_wgas);
-        builder.add_host_func("env", "gr_send_init", Funcs::send_init);
-        builder.add_host_func("env", "gr_send_push", Funcs::send_push);
-        builder.add_host_func("env", "gr_send_wgas", Funcs::send_wgas);
-        builder.add_host_func("env", "gr_size", Funcs::size);
-        builder.add_host_func("env", "gr_source", Funcs::source);
-        builder.add_host_func("env", "gr_value", Funcs::value);
-        builder.add_host_func("env", "gr_value_available", Funcs::value_available);
-        builder.add_host_func("env", "gr_wait", Funcs::wait);
-        builder.add_host_func("env", "gr_wait_for", Funcs::wait_for);
-        builder.add_host_func("env", "gr_wait_up_to", Funcs::wait_up_to);
-        builder.add_host_func("env", "gr_wake", Funcs::wake);
+        add_syscalls(&mut builder);
 
         let mem: MemoryRef = match MemoryInstance::alloc(Pages(mem_size.0 as usize), None) {
             Ok(mem) => mem,
@@ -371,6 +489,7 @@ where
             memory: &mem,
             memory_wrap: &mut memory_wrap,
             err: FuncError::Terminated(TerminationReason::Success),
+            canaries: Default::default(),
         };
 
         let defined_host_functions = builder.defined_host_functions.clone();
@@ -392,6 +511,18 @@ where
         pre_execution_handler(runtime.memory_wrap, stack_end_page)
             .map_err(|e| PreExecutionHandler(e.to_string()))?;
 
+        #[cfg(feature = "memory-canaries")]
+        {
+            let heap_base = instance
+                .export_by_name(HEAP_BASE_EXPORT_NAME)
+                .and_then(|export| {
+                    export
+                        .as_global()
+                        .and_then(|global| global.get().try_into::<i32>())
+                });
+            runtime.canaries = MemoryCanaries::place(runtime.memory_wrap, heap_base);
+        }
+
         let res = if entries.contains(entry_point) {
             let mut externals = GuestExternals {
                 state: &mut runtime,
@@ -404,7 +535,12 @@ where
             Ok(())
         };
 
-        let Runtime { ext, err: trap, .. } = runtime;
+        let Runtime {
+            ext,
+            err: trap,
+            canaries,
+            ..
+        } = runtime;
 
         log::debug!("WasmiEnvironment::execute result = {res:?}");
 
@@ -425,6 +561,15 @@ where
             TerminationReason::Success
         };
 
+        // Corruption may cause any other termination, so it's reported first.
+        let termination = match canaries.check(&memory_wrap) {
+            Ok(()) => termination,
+            Err(region) => {
+                TerminationReason::Trap(TrapExplanation::MemoryCorruptionDetected { region })
+            }
+        };
+        canaries.remove(&mut memory_wrap);
+
         drop(instance);
         Ok(BackendReport {
             termination_reason: termination,
@@ -432,3 +577,74 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use codec::{Decode, Encode};
+    use gear_backend_common::{mock::MockExt, SYSCALLS};
+    use gear_core::memory::Memory;
+    use gear_core_errors::{ExtError, MessageError};
+
+    #[test]
+    fn syscalls_match_common_list() {
+        let mut builder =
+            EnvironmentDefinitionBuilder::<Runtime<MockExt>, _>::new(Default::default());
+
+        add_syscalls(&mut builder);
+
+        let funcs: BTreeSet<_> = builder
+            .map
+            .keys()
+            .map(|(_, name)| String::from_utf8_lossy(name).into_owned())
+            .collect();
+
+        assert_eq!(
+            funcs,
+            SYSCALLS.iter().map(|(name, _)| name.to_string()).collect()
+        );
+    }
+
+    #[test]
+    fn failed_syscall_error_read_by_gr_error() {
+        let wat = r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "gr_send_init" (func $send_init (param i32) (result i32)))
+                (import "env" "gr_error" (func $error (param i32)))
+                (export "handle" (func $handle))
+                (func $handle
+                    (i32.store (i32.const 0x100) (call $send_init (i32.const 0)))
+                    (call $error (i32.const 0x200))
+                )
+            )"#;
+        let binary = wat::parse_str(wat).expect("valid wat");
+
+        let err = ExtError::Message(MessageError::LimitExceeded);
+        let mut ext = MockExt::default().with_send_init_error(err.clone());
+
+        let report = <WasmiEnvironment as Environment<MockExt>>::execute(
+            &mut ext,
+            &binary,
+            [DispatchKind::Handle].into(),
+            WasmPageNumber(1),
+            &DispatchKind::Handle,
+            |_, _| Ok::<_, String>(()),
+        )
+        .expect("execution succeeds");
+        assert_eq!(report.termination_reason, TerminationReason::Success);
+
+        // The failed call returns the length of the encoded error...
+        let mut len = [0u8; 4];
+        report.memory_wrap.read(0x100, &mut len).unwrap();
+        let len = u32::from_le_bytes(len) as usize;
+        assert_eq!(len, err.encoded_size());
+
+        // ...which is stored and written by `gr_error`.
+        let mut encoded = vec![0; len];
+        report.memory_wrap.read(0x200, &mut encoded).unwrap();
+        let stored = ExtError::decode(&mut encoded.as_slice()).expect("valid encoded error");
+        assert_eq!(stored, err);
+    }
+}
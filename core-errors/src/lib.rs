@@ -121,6 +121,54 @@ pub enum MessageError {
     /// The error occurs when program receives too big payload.
     #[display(fmt = "Received message with abnormal payload size")]
     IncomingPayloadTooBig,
+
+    /// The error occurs in attempt to query delayed message, which wasn't
+    /// scheduled by the program or was already dispatched.
+    #[display(fmt = "Delayed message with given id is not found")]
+    DelayedNotFound,
+
+    /// The error occurs when program creates another one with zero value,
+    /// but can't afford the existential deposit the new program's account
+    /// is topped up with.
+    #[display(
+        fmt = "Existing value {} is not enough to top up the created program with existential deposit {}",
+        value_left,
+        existential_deposit
+    )]
+    NotEnoughValueForExistentialDeposit {
+        /// Existential deposit the program is topped up with.
+        existential_deposit: u128,
+        /// Amount of available value.
+        value_left: u128,
+    },
+
+    /// The error occurs when a program replies with an exit code
+    /// reserved for the replies generated by the system.
+    #[display(fmt = "Exit code {} is reserved for system replies", _0)]
+    ReservedExitCode(i32),
+
+    /// The error occurs in attempt to read a record with index out of
+    /// the records the payload consists of.
+    #[display(fmt = "Record {} is out of {} records of the payload", index, count)]
+    RecordNotFound {
+        /// Index of the record.
+        index: u32,
+        /// Amount of the records in the payload.
+        count: u32,
+    },
+
+    /// The error occurs when a program creates another one with the code
+    /// and salt of an existing program.
+    #[display(fmt = "Program with the same code and salt already exists")]
+    ProgramAlreadyExists {
+        /// Id of the existing program.
+        id: [u8; 32],
+    },
+
+    /// The error occurs when a program redirects replies on the message
+    /// it sends to an id of no existing program.
+    #[display(fmt = "Reply destination is not an existing program")]
+    InvalidReplyDestination,
 }
 
 /// Error using waiting syscalls.
@@ -133,6 +181,60 @@ pub enum WaitError {
     /// An error occurs in attempt to wait duration greater than could be payed.
     #[display(fmt = "Provided incorrect argument for wait (zero case)")]
     InvalidArgument,
+    /// An error occurs in attempt to wait with the context exceeding limits of stored one.
+    #[display(fmt = "Message context is too large to be stored while waiting")]
+    ContextTooLarge,
+}
+
+/// Error using value reservations.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, derive_more::Display)]
+#[cfg_attr(feature = "codec", derive(Encode, Decode, TypeInfo))]
+pub enum ReservationError {
+    /// The error occurs when program's balance is less than value it tries to reserve.
+    #[display(
+        fmt = "Existing value {} is not enough to reserve value {}",
+        value_left,
+        value
+    )]
+    NotEnoughValue {
+        /// Value to reserve.
+        value: u128,
+        /// Amount of available value.
+        value_left: u128,
+    },
+
+    /// The error occurs in attempt to use reservation, which wasn't made
+    /// by the program or was already spent or unreserved.
+    #[display(fmt = "Value reservation with given id is not found")]
+    NotFound,
+
+    /// The error occurs in attempt to send a message with value greater
+    /// than reserved one.
+    #[display(
+        fmt = "Reserved value {} is not enough to send a message with value {}",
+        reserved,
+        message_value
+    )]
+    ValueExceeded {
+        /// Message's value.
+        message_value: u128,
+        /// Amount of reserved value.
+        reserved: u128,
+    },
+
+    /// The error occurs in attempt to split from the reservation
+    /// value greater than reserved one.
+    #[display(
+        fmt = "Reserved value {} is not enough to split value {} from it",
+        reserved,
+        value
+    )]
+    SplitExceeded {
+        /// Value to split.
+        value: u128,
+        /// Amount of reserved value.
+        reserved: u128,
+    },
 }
 
 /// Memory error.
@@ -172,6 +274,155 @@ pub enum ExecutionError {
     /// An error occurs in attempt to call forbidden sys-call.
     #[display(fmt = "Unable to call a forbidden function")]
     ForbiddenFunction,
+    /// An error occurs in attempt to get signal details outside the signal entry point.
+    #[display(fmt = "Not running in signal entry point")]
+    NoSignalContext,
+    /// An error occurs in attempt to get hash of the block older than
+    /// the available recent blocks.
+    #[display(fmt = "Block is too old to get its hash")]
+    BlockHashTooOld,
+    /// An error occurs in attempt to get hash of the current or future block,
+    /// which isn't known yet.
+    #[display(fmt = "Block hash is not known yet")]
+    BlockHashUnknown,
+    /// An error occurs in attempt to get hash of the originating transaction
+    /// when the message chain wasn't initiated by an external transaction.
+    #[display(fmt = "Message chain wasn't initiated by an external transaction")]
+    NoTxHash,
+    /// An error occurs in attempt to get code of the message source
+    /// when the message was sent by a user or by a program, which
+    /// isn't active anymore.
+    #[display(fmt = "Source of the message has no code")]
+    NoSourceCode,
+    /// An error occurs in attempt to schedule exit of the program
+    /// at the current or a past block.
+    #[display(fmt = "Exit can be scheduled only at a future block")]
+    ExitNotInFuture,
+    /// An error occurs in attempt to schedule exit of the program
+    /// without enough gas to pay for keeping it in the scheduler.
+    #[display(fmt = "Not enough gas to keep the exit scheduled")]
+    NotEnoughGasForExit,
+    /// An error occurs in attempt to set gas budget of an unknown entry point
+    /// or of the one, which can't be limited.
+    #[display(fmt = "Gas budget can't be set for the entry point")]
+    InvalidEntryPoint,
+    /// An error occurs in attempt to get details of the signal, which code
+    /// is unknown to the program, e.g. added by a newer runtime.
+    #[display(fmt = "Signal code is unknown")]
+    UnknownSignalCode,
+}
+
+/// Reason of the signal sent to the program.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, derive_more::Display)]
+#[cfg_attr(feature = "codec", derive(Encode, Decode, TypeInfo))]
+pub enum SignalCode {
+    /// Waiting message was removed from the waitlist once the requested
+    /// waiting duration expired.
+    #[display(fmt = "Message was removed from the waitlist")]
+    RemovedFromWaitlist,
+    /// Waiting message was removed from the waitlist because its gas was
+    /// completely spent on the waitlist rent.
+    #[display(fmt = "Message ran out of gas in the waitlist")]
+    OutOfGasInWaitlist,
+    /// Execution of the message resumed after waiting failed. The value
+    /// of the message was transferred to the program once it started
+    /// waiting, so it isn't returned to the sender with the error reply.
+    #[display(fmt = "Message failed to execute after waiting")]
+    ExecutionFailed,
+}
+
+impl SignalCode {
+    /// Code as it's passed to the program.
+    ///
+    /// Codes are non-zero, so the signal is never confused with a successful reply.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            Self::RemovedFromWaitlist => 1,
+            Self::OutOfGasInWaitlist => 2,
+            Self::ExecutionFailed => 3,
+        }
+    }
+
+    /// Code from its representation passed to the program.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(Self::RemovedFromWaitlist),
+            2 => Some(Self::OutOfGasInWaitlist),
+            3 => Some(Self::ExecutionFailed),
+            _ => None,
+        }
+    }
+}
+
+/// Exit code of a reply.
+///
+/// Codes `1..=255` are reserved for the replies generated by the system,
+/// e.g. once the replied message traps or runs out of gas. The rest of
+/// non-zero codes are left to programs for their own conventions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, derive_more::Display)]
+#[cfg_attr(feature = "codec", derive(Encode, Decode, TypeInfo))]
+#[display(fmt = "{}", _0)]
+pub struct ReplyCode(i32);
+
+impl ReplyCode {
+    /// Successful reply.
+    pub const SUCCESS: Self = Self(0);
+    /// The replied message failed to execute: trapped, ran out of gas, etc.
+    pub const EXECUTION_ERROR: Self = Self(1);
+    /// Destination of the replied message isn't available:
+    /// it's terminated, exited or was never initialized.
+    pub const UNAVAILABLE_DESTINATION: Self = Self(2);
+    /// The replied message tried to initialize an already initialized program.
+    pub const REINIT: Self = Self(3);
+    /// The reply was dropped since handling it exceeded the gas budget
+    /// of `handle_reply` declared by the program.
+    pub const ENTRY_BUDGET_EXCEEDED: Self = Self(4);
+    /// The replied init message failed to execute. The program is terminated
+    /// and its id can't be used to create a program again.
+    pub const INIT_FAILURE: Self = Self(5);
+    /// The program exited while handling the replied init message. The program
+    /// is exited and its value is inherited as the reply payload states.
+    pub const INIT_EXITED: Self = Self(6);
+
+    /// The greatest code reserved for the system.
+    const SYSTEM_MAX: i32 = 255;
+
+    /// Code from its representation in the reply.
+    pub const fn from_raw(raw: i32) -> Self {
+        Self(raw)
+    }
+
+    /// Code as it's stored in the reply.
+    pub const fn to_raw(self) -> i32 {
+        self.0
+    }
+
+    /// Whether the reply is a successful one.
+    pub const fn is_success(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether the reply was generated by the system on an error.
+    pub const fn is_system_error(self) -> bool {
+        self.0 > 0 && self.0 <= Self::SYSTEM_MAX
+    }
+
+    /// Whether the reply was sent by a program with a non-zero code.
+    pub const fn is_user_error(self) -> bool {
+        !self.is_success() && !self.is_system_error()
+    }
+}
+
+impl From<i32> for ReplyCode {
+    fn from(raw: i32) -> Self {
+        Self::from_raw(raw)
+    }
+}
+
+impl From<ReplyCode> for i32 {
+    fn from(code: ReplyCode) -> Self {
+        code.to_raw()
+    }
 }
 
 /// An error occurred in API.
@@ -195,6 +446,9 @@ pub enum ExtError {
     /// Execution error.
     #[display(fmt = "Execution error: {}", _0)]
     Execution(ExecutionError),
+    /// Value reservation error.
+    #[display(fmt = "Reservation error: {}", _0)]
+    Reservation(ReservationError),
 }
 
 impl ExtError {
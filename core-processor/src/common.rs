@@ -24,12 +24,14 @@ use alloc::{
     vec::Vec,
 };
 use codec::{Decode, Encode};
+use core::num::NonZeroU32;
 use gear_backend_common::TrapExplanation;
 use gear_core::{
+    counters::CounterKey,
     gas::{GasAllowanceCounter, GasAmount, GasCounter},
-    ids::{CodeId, MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
-    message::{ContextStore, Dispatch, IncomingDispatch, StoredDispatch},
+    message::{ContextStore, Dispatch, DispatchKind, IncomingDispatch, StoredDispatch},
     program::Program,
 };
 use gear_core_errors::MemoryError;
@@ -61,9 +63,25 @@ pub struct DispatchResult {
     /// Context store after execution.
     pub context_store: ContextStore,
     /// List of generated messages.
-    pub generated_dispatches: Vec<(Dispatch, u32)>,
+    pub generated_dispatches: Vec<(Dispatch, Option<NonZeroU32>)>,
     /// List of messages that should be woken.
-    pub awakening: Vec<(MessageId, u32)>,
+    pub awakening: Vec<(MessageId, Option<NonZeroU32>)>,
+    /// Generated messages, which gas should be paid by their recipients.
+    pub recipient_charged: BTreeSet<MessageId>,
+    /// New value reservations of the program, if they were changed.
+    pub value_reservations: Option<BTreeMap<ReservationId, u128>>,
+    /// New number of programs created by the program, if it was changed.
+    pub children_count: Option<u64>,
+    /// New persistent counters of the program, if they were changed.
+    pub counters: Option<BTreeMap<CounterKey, u64>>,
+    /// Block and inheritor of the exit scheduled by the program.
+    pub scheduled_exit: Option<(u32, ProgramId)>,
+    /// Gas budgets of entry points set by the program.
+    pub entry_budgets: BTreeMap<DispatchKind, u64>,
+    /// Gas allowance for recipient charged messages set by the program.
+    pub charge_allowance: Option<u64>,
+    /// Message the reply to which wakes the current one.
+    pub reply_waker: Option<MessageId>,
     /// New programs to be created with additional data (corresponding code hash and init message id).
     pub program_candidates: BTreeMap<CodeId, Vec<(ProgramId, MessageId)>>,
     /// Gas amount after execution.
@@ -109,6 +127,14 @@ impl DispatchResult {
             context_store: Default::default(),
             generated_dispatches: Default::default(),
             awakening: Default::default(),
+            recipient_charged: Default::default(),
+            value_reservations: Default::default(),
+            children_count: Default::default(),
+            counters: Default::default(),
+            scheduled_exit: Default::default(),
+            entry_budgets: Default::default(),
+            charge_allowance: Default::default(),
+            reply_waker: Default::default(),
             program_candidates: Default::default(),
             gas_amount,
             page_update: Default::default(),
@@ -118,7 +144,10 @@ impl DispatchResult {
 }
 
 /// Dispatch outcome of the specific message.
-#[derive(Clone, Debug)]
+///
+/// Explanations of failures aren't encoded, because they may differ between
+/// nodes, which executed the message the same way, e.g. with other backends.
+#[derive(Clone, Debug, Encode)]
 pub enum DispatchOutcome {
     /// Message was a exit.
     Exit {
@@ -137,6 +166,7 @@ pub enum DispatchOutcome {
         /// Source of the init message. Funds inheritor.
         origin: ProgramId,
         /// Reason of the fail.
+        #[codec(skip)]
         reason: String,
     },
     /// Message was a trap.
@@ -144,6 +174,7 @@ pub enum DispatchOutcome {
         /// Program that was failed initializing.
         program_id: ProgramId,
         /// Reason of the fail.
+        #[codec(skip)]
         trap: String,
     },
     /// Message was a success.
@@ -153,7 +184,9 @@ pub enum DispatchOutcome {
 }
 
 /// Journal record for the state update.
-#[derive(Clone, Debug)]
+///
+/// Encoded in the [canonical form](Journal::canonical_encode).
+#[derive(Clone, Debug, Encode)]
 pub enum JournalNote {
     /// Message was successfully dispatched.
     MessageDispatched {
@@ -190,7 +223,16 @@ pub enum JournalNote {
         /// New message with entry point that was generated.
         dispatch: Dispatch,
         /// Amount of blocks to wait before sending.
-        delay: u32,
+        delay: Option<NonZeroU32>,
+    },
+    /// Message was generated, and its gas should be paid by the recipient.
+    SendDispatchChargeRecipient {
+        /// Message id of the message that generated this message.
+        message_id: MessageId,
+        /// New message with entry point that was generated.
+        dispatch: Dispatch,
+        /// Amount of blocks to wait before sending.
+        delay: Option<NonZeroU32>,
     },
     /// Put this dispatch in the wait list.
     WaitDispatch {
@@ -208,7 +250,7 @@ pub enum JournalNote {
         /// Message that should be woken.
         awakening_id: MessageId,
         /// Amount of blocks to wait before waking.
-        delay: u32,
+        delay: Option<NonZeroU32>,
     },
     /// Update page.
     UpdatePage {
@@ -227,6 +269,67 @@ pub enum JournalNote {
         /// New allocations set for the program.
         allocations: BTreeSet<WasmPageNumber>,
     },
+    /// Update value reservations of the program.
+    UpdateValueReservations {
+        /// Program id.
+        program_id: ProgramId,
+        /// New value reservations of the program.
+        reservations: BTreeMap<ReservationId, u128>,
+    },
+    /// Update number of programs created by the program.
+    UpdateChildrenCount {
+        /// Program id.
+        program_id: ProgramId,
+        /// New number of created programs.
+        children_count: u64,
+    },
+    /// Gas was burned by initialization of the program created by another one.
+    ChildGasBurned {
+        /// Id of the program, which created the initialized one.
+        program_id: ProgramId,
+        /// Amount of gas burned.
+        amount: u64,
+    },
+    /// Update persistent counters of the program.
+    UpdateCounters {
+        /// Program id.
+        program_id: ProgramId,
+        /// New counters of the program.
+        counters: BTreeMap<CounterKey, u64>,
+    },
+    /// Update gas budgets of the program's entry points.
+    UpdateEntryBudgets {
+        /// Program id.
+        program_id: ProgramId,
+        /// Budgets set by the program, replacing the previous ones
+        /// of the same entry points.
+        entry_budgets: BTreeMap<DispatchKind, u64>,
+    },
+    /// Update gas the program agrees to pay for recipient charged messages.
+    UpdateChargeAllowance {
+        /// Program id.
+        program_id: ProgramId,
+        /// New allowance, replacing the previous one.
+        allowance: u64,
+    },
+    /// Update message of the program waiting for the reply.
+    UpdateReplyWaker {
+        /// Program id.
+        program_id: ProgramId,
+        /// Message the reply is awaited to.
+        reply_to: MessageId,
+        /// Message woken by the reply, `None` to forget the waiting one.
+        waiting: Option<MessageId>,
+    },
+    /// Schedule exit of the program at the future block.
+    ScheduleExit {
+        /// Program id.
+        program_id: ProgramId,
+        /// Block the program exits at.
+        block: u32,
+        /// Receiver of the program's value.
+        inheritor: ProgramId,
+    },
     /// Send value
     SendValue {
         /// Value sender
@@ -252,6 +355,81 @@ pub enum JournalNote {
     },
 }
 
+/// Journal of the messages' execution, e.g. within a block.
+///
+/// Notes are kept in order of generation, so journals of nodes executing
+/// the same messages are equal, unless the execution diverged.
+#[derive(Clone, Debug, Default)]
+pub struct Journal(pub Vec<JournalNote>);
+
+impl Journal {
+    /// Create an empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the `notes` of the next message execution.
+    pub fn extend(&mut self, notes: impl IntoIterator<Item = JournalNote>) {
+        self.0.extend(notes);
+    }
+
+    /// Canonical SCALE encoding of the journal, which is the same for any node
+    /// executing the same messages.
+    ///
+    /// Notes are encoded one after another in order of generation, each one
+    /// with fields in order of declaration. Data which isn't part of consensus,
+    /// e.g. [`DispatchOutcome`] failure explanations, is skipped.
+    pub fn canonical_encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        self.0.iter().for_each(|note| note.encode_to(&mut encoded));
+        encoded
+    }
+
+    /// Blake2b-256 hash of the [canonical encoding](Self::canonical_encode).
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        let mut hasher = JournalHasher::new();
+        self.0.iter().for_each(|note| hasher.update(note));
+        hasher.finalize()
+    }
+}
+
+/// Hasher of the journal's [canonical encoding](Journal::canonical_encode).
+///
+/// Takes notes one by one as they're generated, so the notes don't have
+/// to be kept until the hash is computed.
+pub struct JournalHasher(blake2_rfc::blake2b::Blake2b);
+
+impl JournalHasher {
+    /// Create the hasher of an empty journal.
+    pub fn new() -> Self {
+        Self(blake2_rfc::blake2b::Blake2b::new(32))
+    }
+
+    /// Append the next note.
+    pub fn update(&mut self, note: &JournalNote) {
+        note.using_encoded(|bytes| self.0.update(bytes));
+    }
+
+    /// Hash of the notes appended.
+    pub fn finalize(self) -> [u8; 32] {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(self.0.finalize().as_bytes());
+        hash
+    }
+}
+
+impl Default for JournalHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Vec<JournalNote>> for Journal {
+    fn from(notes: Vec<JournalNote>) -> Self {
+        Self(notes)
+    }
+}
+
 /// Journal handler.
 ///
 /// Something that can update state.
@@ -270,7 +448,19 @@ pub trait JournalHandler {
     /// Process message consumed.
     fn message_consumed(&mut self, message_id: MessageId);
     /// Process send dispatch.
-    fn send_dispatch(&mut self, message_id: MessageId, dispatch: Dispatch, delay: u32);
+    fn send_dispatch(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+    );
+    /// Process send dispatch, which gas is paid by the recipient.
+    fn send_dispatch_charge_recipient(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+    );
     /// Process send message.
     fn wait_dispatch(&mut self, dispatch: StoredDispatch, duration: Option<u32>);
     /// Process send message.
@@ -279,7 +469,7 @@ pub trait JournalHandler {
         message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     );
     /// Process page update.
     fn update_pages_data(
@@ -289,6 +479,35 @@ pub trait JournalHandler {
     );
     /// Process [JournalNote::UpdateAllocations].
     fn update_allocations(&mut self, program_id: ProgramId, allocations: BTreeSet<WasmPageNumber>);
+    /// Process [JournalNote::UpdateValueReservations].
+    fn update_value_reservations(
+        &mut self,
+        program_id: ProgramId,
+        reservations: BTreeMap<ReservationId, u128>,
+    );
+    /// Process [JournalNote::UpdateChildrenCount].
+    fn update_children_count(&mut self, program_id: ProgramId, children_count: u64);
+    /// Process [JournalNote::ChildGasBurned].
+    fn child_gas_burned(&mut self, program_id: ProgramId, amount: u64);
+    /// Process [JournalNote::UpdateCounters].
+    fn update_counters(&mut self, program_id: ProgramId, counters: BTreeMap<CounterKey, u64>);
+    /// Process [JournalNote::UpdateEntryBudgets].
+    fn update_entry_budgets(
+        &mut self,
+        program_id: ProgramId,
+        entry_budgets: BTreeMap<DispatchKind, u64>,
+    );
+    /// Process [JournalNote::UpdateChargeAllowance].
+    fn update_charge_allowance(&mut self, program_id: ProgramId, allowance: u64);
+    /// Process [JournalNote::UpdateReplyWaker].
+    fn update_reply_waker(
+        &mut self,
+        program_id: ProgramId,
+        reply_to: MessageId,
+        waiting: Option<MessageId>,
+    );
+    /// Process [JournalNote::ScheduleExit].
+    fn schedule_exit(&mut self, program_id: ProgramId, block: u32, inheritor: ProgramId);
     /// Send value.
     fn send_value(&mut self, from: ProgramId, to: Option<ProgramId>, value: u128);
     /// Store new programs in storage.
@@ -301,6 +520,71 @@ pub trait JournalHandler {
     fn stop_processing(&mut self, dispatch: StoredDispatch, gas_burned: u64);
 }
 
+/// Outcome of the dispatch execution, which isn't committed yet.
+#[derive(Clone, Debug)]
+pub struct SimulationOutcome {
+    /// Journal of the execution to be handled by the caller.
+    pub journal: Vec<JournalNote>,
+    /// Gas burned by the dispatch.
+    pub gas: u64,
+    /// Memory pages updated by the execution.
+    pub pages: BTreeSet<PageNumber>,
+    /// Memory of the program after the execution: the snapshot the dispatch
+    /// was executed over with the updated pages applied.
+    pub memory: BTreeMap<PageNumber, PageBuf>,
+    /// Allocations of the program after the execution, if they were changed.
+    pub allocations: Option<BTreeSet<WasmPageNumber>>,
+}
+
+impl SimulationOutcome {
+    /// Create the outcome from the `journal` of the dispatch with `message_id`
+    /// executed by the program with `program_id` over its `memory` snapshot.
+    pub fn new(
+        message_id: MessageId,
+        program_id: ProgramId,
+        mut memory: BTreeMap<PageNumber, PageBuf>,
+        journal: Vec<JournalNote>,
+    ) -> Self {
+        let mut gas = 0u64;
+        let mut pages = BTreeSet::new();
+        let mut allocations = None;
+
+        for note in &journal {
+            match note {
+                JournalNote::GasBurned {
+                    message_id: id,
+                    amount,
+                } if *id == message_id => gas = gas.saturating_add(*amount),
+                JournalNote::UpdatePage {
+                    program_id: id,
+                    page_number,
+                    data,
+                } if *id == program_id => {
+                    pages.insert(*page_number);
+                    memory.insert(*page_number, data.clone());
+                }
+                JournalNote::UpdateAllocations {
+                    program_id: id,
+                    allocations: new_allocations,
+                } if *id == program_id => {
+                    // Pages beyond the allocations are released.
+                    memory.retain(|page, _| new_allocations.contains(&page.to_wasm_page()));
+                    allocations = Some(new_allocations.clone());
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            journal,
+            gas,
+            pages,
+            memory,
+            allocations,
+        }
+    }
+}
+
 /// Execution error.
 #[derive(Debug)]
 pub struct ExecutionError {
@@ -366,6 +650,12 @@ pub enum ExecutionErrorReason {
     /// Cannot write initial data to wasm memory.
     #[display(fmt = "Cannot write initial data for {:?}: {}", _0, _1)]
     InitialDataWriteFailed(PageNumber, MemoryError),
+    /// Program called `exit` during initialization.
+    #[display(fmt = "Program exited in init, value inherited by {:?}", inheritor)]
+    InitExited {
+        /// Program which inherits value of the exited one.
+        inheritor: ProgramId,
+    },
     /// Message killed from storage as out of rent.
     #[display(fmt = "Out of rent")]
     OutOfRent,
@@ -382,6 +672,35 @@ pub enum ExecutionErrorReason {
     /// It's not allowed to set initial data for stack memory pages, if they are specified in WASM code.
     #[display(fmt = "Set initial data for stack pages is restricted")]
     StackPagesHaveInitialData,
+    /// Recipient can't pay for the gas of the message sent to it.
+    #[display(fmt = "Recipient can't pay for message gas")]
+    RecipientCannotPayGas,
+    /// Execution changed more pages than could be persisted at once.
+    #[display(fmt = "Too many pages changed: {}, while max is {}", dirtied, max)]
+    TooManyPageWrites {
+        /// Amount of the changed pages.
+        dirtied: u32,
+        /// Max amount of pages persisted after a single execution.
+        max: u32,
+    },
+    /// Not enough gas to persist changed pages
+    #[display(fmt = "Not enough gas to persist changed pages")]
+    PageWritesGasExceeded,
+    /// Execution ran out of the gas budget of its entry point.
+    #[display(fmt = "Gas budget {} of the entry point exceeded", _0)]
+    EntryBudgetExceeded(u64),
+    /// Not enough gas to read the gas budget of the entry point
+    #[display(fmt = "Not enough gas to read the gas budget of the entry point")]
+    EntryBudgetReadGasExceeded,
+    /// Not enough gas in block to read the gas budget of the entry point
+    #[display(fmt = "Not enough gas in block to read the gas budget of the entry point")]
+    EntryBudgetReadBlockGasExceeded,
+    /// Not enough gas to read the message waiting for the reply
+    #[display(fmt = "Not enough gas to read the message waiting for the reply")]
+    ReplyWakerReadGasExceeded,
+    /// Not enough gas in block to read the message waiting for the reply
+    #[display(fmt = "Not enough gas in block to read the message waiting for the reply")]
+    ReplyWakerReadBlockGasExceeded,
 }
 
 /// Actor.
@@ -402,12 +721,20 @@ pub struct ExecutableActorData {
     pub program: Program,
     /// Numbers of allocated memory pages that have non-default data.
     pub pages_with_data: BTreeSet<PageNumber>,
+    /// Value reservations of the program.
+    pub value_reservations: BTreeMap<ReservationId, u128>,
+    /// Number of programs created by the program over its lifetime.
+    pub children_count: u64,
 }
 
 /// Execution context.
 pub struct WasmExecutionContext {
     /// Original user.
     pub origin: ProgramId,
+    /// Hash of the originating external transaction.
+    pub tx_hash: Option<[u8; 32]>,
+    /// Identifier of the execution.
+    pub execution_id: u64,
     /// A counter for gas.
     pub gas_counter: GasCounter,
     /// A counter for gas allowance.
@@ -418,4 +745,8 @@ pub struct WasmExecutionContext {
     pub pages_initial_data: BTreeMap<PageNumber, PageBuf>,
     /// Size of the memory block.
     pub memory_size: WasmPageNumber,
+    /// Value reservations of the program.
+    pub value_reservations: BTreeMap<ReservationId, u128>,
+    /// Number of programs created by the program over its lifetime.
+    pub children_count: u64,
 }
@@ -19,16 +19,24 @@
 //! Configurations.
 
 use crate::common::Actor;
-use alloc::collections::BTreeSet;
+use alloc::collections::{BTreeMap, BTreeSet};
 use codec::{Decode, Encode};
 use gear_core::{
-    code, costs::HostFnWeights, ids::ProgramId, memory::WasmPageNumber, message::IncomingDispatch,
+    code,
+    costs::HostFnWeights,
+    counters::CounterKey,
+    ids::{CodeId, MessageId, ProgramId},
+    memory::WasmPageNumber,
+    message::{DispatchKind, IncomingDispatch},
 };
+use scale_info::TypeInfo;
 
 const INIT_COST: u64 = 5000;
 const ALLOC_COST: u64 = 10000;
 const MEM_GROW_COST: u64 = 10000;
 const LOAD_PAGE_COST: u64 = 3000;
+const WRITE_PAGE_COST: u64 = 10000;
+const MAX_PAGES_PERSISTED: u32 = 4096;
 
 /// Contextual block information.
 #[derive(Clone, Copy, Debug, Encode, Decode, Default)]
@@ -52,6 +60,10 @@ pub struct AllocationsConfig {
     pub mem_grow_cost: u64,
     /// Load page cost.
     pub load_page_cost: u64,
+    /// Cost of persisting a changed page.
+    pub write_page_cost: u64,
+    /// Max amount of changed pages persisted after a single execution.
+    pub max_pages_persisted: u32,
 }
 
 impl Default for AllocationsConfig {
@@ -62,18 +74,39 @@ impl Default for AllocationsConfig {
             alloc_cost: ALLOC_COST,
             mem_grow_cost: MEM_GROW_COST,
             load_page_cost: LOAD_PAGE_COST,
+            write_page_cost: WRITE_PAGE_COST,
+            max_pages_persisted: MAX_PAGES_PERSISTED,
         }
     }
 }
 
+/// Policy of providing existential deposit for accounts of the programs
+/// created by other programs with zero value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum ExistentialDepositPolicy {
+    /// Program is created with zero value. Its account is kept by a provider
+    /// reference instead, so the program is able to receive value.
+    #[default]
+    Exempt,
+    /// Init message of the program carries existential deposit instead of
+    /// zero value, which is taken from the creator's balance.
+    ///
+    /// Creation fails if the creator can't afford it.
+    TopUp,
+}
+
 /// Execution settings for handling messages.
 pub struct ExecutionSettings {
     /// Contextual block information.
     pub block_info: BlockInfo,
+    /// Duration of a block in milliseconds.
+    pub block_duration: u64,
     /// Allocation config.
     pub allocations_config: AllocationsConfig,
     /// Minimal amount of existence for account.
     pub existential_deposit: u128,
+    /// Policy of providing existential deposit for created programs.
+    pub existential_deposit_policy: ExistentialDepositPolicy,
     /// Weights of host functions.
     pub host_fn_weights: HostFnWeights,
     /// Functions forbidden to be called.
@@ -82,8 +115,29 @@ pub struct ExecutionSettings {
     pub mailbox_threshold: u64,
     /// Cost for single block waitlist holding.
     pub waitlist_cost: u64,
+    /// Cost of keeping the entry of the message waiting for a reply.
+    pub reply_waker_cost: u64,
     /// Reserve for parameter of scheduling.
     pub reserve_for: u32,
+    /// Amount of blocks preceding the current one with hashes available.
+    pub block_hash_depth: u32,
+    /// Hash of the block at the given height, read on request.
+    pub block_hash: fn(u32) -> [u8; 32],
+    /// Whether the program with given id exists.
+    pub program_exists: fn(ProgramId) -> bool,
+    /// Whether the message with given id, sent by the program, is in
+    /// the mailbox of the user.
+    pub mailbox_contains: fn(ProgramId, ProgramId, MessageId) -> bool,
+    /// Number of distinct codes uploaded to the chain.
+    pub codes_count: fn() -> u64,
+    /// Block number the dispatch delayed by the program is scheduled for.
+    pub delayed_dispatch: fn(ProgramId, MessageId) -> Option<u32>,
+    /// Persistent counters of the program, loaded on the first bump.
+    pub program_counters: fn(ProgramId) -> BTreeMap<CounterKey, u64>,
+    /// Gas burned by initializations of programs created by the program, read on request.
+    pub children_gas_burned: fn(ProgramId) -> u64,
+    /// Code of the active program, read on request.
+    pub code_id_of: fn(ProgramId) -> Option<CodeId>,
 }
 
 impl ExecutionSettings {
@@ -98,10 +152,14 @@ impl ExecutionSettings {
 pub struct BlockConfig {
     /// Block info.
     pub block_info: BlockInfo,
+    /// Duration of a block in milliseconds.
+    pub block_duration: u64,
     /// Allocations config.
     pub allocations_config: AllocationsConfig,
     /// Existential deposit.
     pub existential_deposit: u128,
+    /// Policy of providing existential deposit for created programs.
+    pub existential_deposit_policy: ExistentialDepositPolicy,
     /// Outgoing limit.
     pub outgoing_limit: u32,
     /// Host function weights.
@@ -112,8 +170,35 @@ pub struct BlockConfig {
     pub mailbox_threshold: u64,
     /// Cost for single block waitlist holding.
     pub waitlist_cost: u64,
+    /// Cost of keeping the entry of the message waiting for a reply.
+    pub reply_waker_cost: u64,
     /// Reserve for parameter of scheduling.
     pub reserve_for: u32,
+    /// Amount of blocks preceding the current one with hashes available.
+    pub block_hash_depth: u32,
+    /// Hash of the block at the given height, read on request.
+    pub block_hash: fn(u32) -> [u8; 32],
+    /// Whether the program with given id exists.
+    pub program_exists: fn(ProgramId) -> bool,
+    /// Whether the message with given id, sent by the program, is in
+    /// the mailbox of the user.
+    pub mailbox_contains: fn(ProgramId, ProgramId, MessageId) -> bool,
+    /// Number of distinct codes uploaded to the chain.
+    pub codes_count: fn() -> u64,
+    /// Block number the dispatch delayed by the program is scheduled for.
+    pub delayed_dispatch: fn(ProgramId, MessageId) -> Option<u32>,
+    /// Persistent counters of the program, loaded on the first bump.
+    pub program_counters: fn(ProgramId) -> BTreeMap<CounterKey, u64>,
+    /// Gas burned by initializations of programs created by the program, read on request.
+    pub children_gas_burned: fn(ProgramId) -> u64,
+    /// Code of the active program, read on request.
+    pub code_id_of: fn(ProgramId) -> Option<CodeId>,
+    /// Message of the program waiting for the reply to the given message.
+    pub reply_waker: fn(ProgramId, MessageId) -> Option<MessageId>,
+    /// Gas budget of the program's entry point, read before the execution.
+    pub entry_budget: fn(ProgramId, DispatchKind) -> Option<u64>,
+    /// Cost of reading the gas budget of the entry point.
+    pub entry_budget_read_cost: u64,
 }
 
 /// Unstable parameters for message execution across processing runs.
@@ -125,6 +210,11 @@ pub struct MessageExecutionContext {
     pub dispatch: IncomingDispatch,
     /// The ID of the user who started interaction with programs.
     pub origin: ProgramId,
+    /// Hash of the external transaction the message chain originates from.
+    pub tx_hash: Option<[u8; 32]>,
+    /// Identifier of the execution assigned by the runtime, increasing
+    /// with every execution.
+    pub execution_id: u64,
     /// Gas allowance.
     pub gas_allowance: u64,
     /// The program is being executed the second or next time in the block.
@@ -31,10 +31,11 @@ use alloc::{
 use gear_backend_common::{BackendReport, Environment, IntoExtInfo, TerminationReason};
 use gear_core::{
     env::Ext as EnvExt,
-    gas::{ChargeResult, GasAllowanceCounter, GasCounter, ValueCounter},
+    gas::{ChargeResult, GasAllowanceCounter, GasAmount, GasCounter, ValueCounter},
     ids::ProgramId,
     memory::{AllocationsContext, Memory, PageBuf, PageNumber, WasmPageNumber},
     message::{ContextSettings, IncomingDispatch, MessageContext},
+    reservation::ValueReservations,
 };
 
 /// Make checks that everything with memory goes well.
@@ -183,6 +184,36 @@ fn prepare_memory<A: ProcessorExt, M: Memory>(
     Ok(())
 }
 
+/// Charge gas for persisting of the changed pages, checking their amount
+/// doesn't exceed the limit, in which case none of them is persisted.
+fn charge_gas_for_page_writes(
+    settings: &AllocationsConfig,
+    gas_amount: GasAmount,
+    dirtied: usize,
+) -> Result<GasAmount, (ExecutionErrorReason, GasAmount)> {
+    let max = settings.max_pages_persisted;
+
+    if dirtied > max as usize {
+        let dirtied = dirtied as u32;
+        return Err((
+            ExecutionErrorReason::TooManyPageWrites { dirtied, max },
+            gas_amount,
+        ));
+    }
+
+    let mut gas_counter = GasCounter::from(gas_amount);
+
+    let amount = settings.write_page_cost * dirtied as u64;
+    if gas_counter.charge(amount) != ChargeResult::Enough {
+        return Err((
+            ExecutionErrorReason::PageWritesGasExceeded,
+            gas_counter.into(),
+        ));
+    }
+
+    Ok(gas_counter.into())
+}
+
 /// Returns pages and their new data, which must be updated or uploaded to storage.
 fn get_pages_to_be_updated<A: ProcessorExt>(
     mut old_pages_data: BTreeMap<PageNumber, PageBuf>,
@@ -237,9 +268,13 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         gas_counter,
         gas_allowance_counter,
         origin,
+        tx_hash,
+        execution_id,
         program,
         mut pages_initial_data,
         memory_size,
+        value_reservations,
+        children_count,
     } = context;
 
     let program_id = program.id();
@@ -276,8 +311,12 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         msg_ctx_settings,
     );
 
-    // Creating value counter.
-    let value_counter = ValueCounter::new(balance + dispatch.value());
+    // Creating value reservations context.
+    let value_reservations = ValueReservations::new(value_reservations);
+
+    // Creating value counter, reserved value isn't available for spending.
+    let value_counter =
+        ValueCounter::new((balance + dispatch.value()).saturating_sub(value_reservations.total()));
 
     let context = ProcessorContext {
         gas_counter,
@@ -285,17 +324,41 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         value_counter,
         allocations_context,
         message_context,
+        dispatch_kind: kind,
         block_info: settings.block_info,
-        config: settings.allocations_config,
+        block_duration: settings.block_duration,
+        config: settings.allocations_config.clone(),
         existential_deposit: settings.existential_deposit,
+        existential_deposit_policy: settings.existential_deposit_policy,
         origin,
+        tx_hash,
+        execution_id,
         program_id,
         program_candidates_data: Default::default(),
         host_fn_weights: settings.host_fn_weights,
         forbidden_funcs: settings.forbidden_funcs,
         mailbox_threshold: settings.mailbox_threshold,
         waitlist_cost: settings.waitlist_cost,
+        reply_waker_cost: settings.reply_waker_cost,
         reserve_for: settings.reserve_for,
+        block_hash_depth: settings.block_hash_depth,
+        block_hash: settings.block_hash,
+        stack_height: program.code().stack_height(),
+        program_exists: settings.program_exists,
+        mailbox_contains: settings.mailbox_contains,
+        codes_count: settings.codes_count,
+        delayed_dispatch: settings.delayed_dispatch,
+        delayed_dispatches: Default::default(),
+        value_reservations,
+        children_count,
+        program_counters: settings.program_counters,
+        children_gas_burned: settings.children_gas_burned,
+        code_id_of: settings.code_id_of,
+        counters: None,
+        scheduled_exit: None,
+        entry_budgets: Default::default(),
+        charge_allowance: None,
+        reply_waker: None,
     };
 
     // Creating externalities.
@@ -354,6 +417,12 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
             reason: ExecutionErrorReason::Backend(err.to_string()),
         })?;
 
+    // Memory protected by lazy pages can't be checked for changes,
+    // so it's never reused.
+    if !A::LAZY_PAGES_ENABLED {
+        E::release_memory(memory, &info.pages_data);
+    }
+
     if A::LAZY_PAGES_ENABLED && !pages_initial_data.is_empty() {
         return Err(ExecutionError {
             program_id,
@@ -382,9 +451,28 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
     let page_update =
         get_pages_to_be_updated::<A>(pages_initial_data, info.pages_data, static_pages);
 
+    // Pages of the failed or interrupted executions aren't persisted.
+    let gas_amount = match kind {
+        DispatchResultKind::Trap(_) | DispatchResultKind::GasAllowanceExceed => info.gas_amount,
+        _ => charge_gas_for_page_writes(
+            &settings.allocations_config,
+            info.gas_amount,
+            page_update.len(),
+        )
+        .map_err(|(reason, gas_amount)| ExecutionError {
+            program_id,
+            gas_amount,
+            reason,
+        })?,
+    };
+
     // Getting new programs that are scheduled to be initialized (respected messages are in `generated_dispatches` collection)
     let program_candidates = info.program_candidates_data;
 
+    // Updating number of created programs, if new ones were requested.
+    let created = program_candidates.values().map(Vec::len).sum::<usize>() as u64;
+    let children_count = (created != 0).then(|| children_count.saturating_add(created));
+
     // Output
     Ok(DispatchResult {
         kind,
@@ -393,8 +481,16 @@ pub fn execute_wasm<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environ
         context_store: info.context_store,
         generated_dispatches: info.generated_dispatches,
         awakening: info.awakening,
+        recipient_charged: info.recipient_charged,
+        value_reservations: info.value_reservations,
+        children_count,
+        counters: info.counters,
+        scheduled_exit: info.scheduled_exit,
+        entry_budgets: info.entry_budgets,
+        charge_allowance: info.charge_allowance,
+        reply_waker: info.reply_waker,
         program_candidates,
-        gas_amount: info.gas_amount,
+        gas_amount,
         page_update,
         allocations: info.allocations,
     })
@@ -16,13 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::configs::{AllocationsConfig, BlockInfo};
+use crate::configs::{AllocationsConfig, BlockInfo, ExistentialDepositPolicy};
 use alloc::{
     collections::{BTreeMap, BTreeSet},
     string::{String, ToString},
     vec::Vec,
 };
-use core::fmt;
+use core::{fmt, num::NonZeroU32};
 use gear_backend_common::{
     error_processor::IntoExtError, AsTerminationReason, ExtInfo, IntoExtInfo, TerminationReason,
     TrapExplanation,
@@ -30,13 +30,43 @@ use gear_backend_common::{
 use gear_core::{
     charge_gas_token,
     costs::{HostFnWeights, RuntimeCosts},
+    counters::{CounterKey, ProgramCounters},
     env::Ext as EnvExt,
     gas::{ChargeResult, GasAllowanceCounter, GasAmount, GasCounter, ValueCounter},
-    ids::{CodeId, MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{AllocationsContext, Memory, PageBuf, WasmPageNumber},
-    message::{ExitCode, GasLimit, HandlePacket, InitPacket, MessageContext, Packet, ReplyPacket},
+    message::{
+        ContextStoreLimits, DispatchKind, ExitCode, GasLimit, HandlePacket, InitPacket,
+        MessageContext, Packet, ReplyPacket, SignalCode, MAX_PAYLOAD_SIZE,
+    },
+    reservation::ValueReservations,
+};
+use gear_core_errors::{
+    CoreError, ExecutionError, ExtError, MemoryError, MessageError, ReplyCode, ReservationError,
+    WaitError,
 };
-use gear_core_errors::{CoreError, ExecutionError, ExtError, MemoryError, MessageError, WaitError};
+
+/// Converts level of the program trace into the log level.
+fn trace_level(level: u32) -> Option<log::Level> {
+    match level {
+        1 => Some(log::Level::Error),
+        2 => Some(log::Level::Warn),
+        3 => Some(log::Level::Info),
+        4 => Some(log::Level::Debug),
+        5 => Some(log::Level::Trace),
+        _ => None,
+    }
+}
+
+/// Converts entry point number into the kind of dispatches it handles.
+fn budgeted_entry(entry: u32) -> Option<DispatchKind> {
+    match entry {
+        1 => Some(DispatchKind::Handle),
+        2 => Some(DispatchKind::Reply),
+        3 => Some(DispatchKind::Signal),
+        _ => None,
+    }
+}
 
 /// Processor context.
 pub struct ProcessorContext {
@@ -50,14 +80,24 @@ pub struct ProcessorContext {
     pub allocations_context: AllocationsContext,
     /// Message context.
     pub message_context: MessageContext,
+    /// Kind of the executed dispatch.
+    pub dispatch_kind: DispatchKind,
     /// Block info.
     pub block_info: BlockInfo,
+    /// Duration of a block in milliseconds.
+    pub block_duration: u64,
     /// Allocations config.
     pub config: AllocationsConfig,
     /// Account existential deposit
     pub existential_deposit: u128,
+    /// Policy of providing existential deposit for created programs
+    pub existential_deposit_policy: ExistentialDepositPolicy,
     /// Communication origin
     pub origin: ProgramId,
+    /// Hash of the originating external transaction
+    pub tx_hash: Option<[u8; 32]>,
+    /// Identifier of the current execution, unique across resumptions
+    pub execution_id: u64,
     /// Current program id
     pub program_id: ProgramId,
     /// Map of code hashes to program ids of future programs, which are planned to be
@@ -71,8 +111,48 @@ pub struct ProcessorContext {
     pub mailbox_threshold: u64,
     /// Cost for single block waitlist holding.
     pub waitlist_cost: u64,
+    /// Cost of keeping the entry of the message waiting for a reply.
+    pub reply_waker_cost: u64,
     /// Reserve for parameter of scheduling.
     pub reserve_for: u32,
+    /// Amount of blocks preceding the current one with hashes available.
+    pub block_hash_depth: u32,
+    /// Hash of the block at the given height, read on request.
+    pub block_hash: fn(u32) -> [u8; 32],
+    /// Stack height limit the code of the program is instrumented with.
+    pub stack_height: Option<u32>,
+    /// Whether the program with given id exists.
+    pub program_exists: fn(ProgramId) -> bool,
+    /// Whether the message with given id, sent by the program, is in
+    /// the mailbox of the user.
+    pub mailbox_contains: fn(ProgramId, ProgramId, MessageId) -> bool,
+    /// Number of distinct codes uploaded to the chain.
+    pub codes_count: fn() -> u64,
+    /// Block number the dispatch delayed by the program is scheduled for.
+    pub delayed_dispatch: fn(ProgramId, MessageId) -> Option<u32>,
+    /// Messages delayed during the execution mapped to block numbers
+    /// they are scheduled for.
+    pub delayed_dispatches: BTreeMap<MessageId, u32>,
+    /// Value reservations of the program.
+    pub value_reservations: ValueReservations,
+    /// Number of programs created by the program before the current execution.
+    pub children_count: u64,
+    /// Gas burned by initializations of programs created by the program, read on request.
+    pub children_gas_burned: fn(ProgramId) -> u64,
+    /// Code of the active program, read on request.
+    pub code_id_of: fn(ProgramId) -> Option<CodeId>,
+    /// Persistent counters of the program, `None` until the first bump.
+    pub counters: Option<ProgramCounters>,
+    /// Persistent counters of the program stored after previous executions.
+    pub program_counters: fn(ProgramId) -> BTreeMap<CounterKey, u64>,
+    /// Block and inheritor of the exit scheduled during the execution.
+    pub scheduled_exit: Option<(u32, ProgramId)>,
+    /// Gas budgets of entry points set during the execution.
+    pub entry_budgets: BTreeMap<DispatchKind, u64>,
+    /// Gas allowance for recipient charged messages set during the execution.
+    pub charge_allowance: Option<u64>,
+    /// Message the reply to which wakes the current one.
+    pub reply_waker: Option<MessageId>,
 }
 
 /// Trait to which ext must have to work in processor wasm executor.
@@ -154,6 +234,12 @@ impl From<ExecutionError> for ProcessorError {
     }
 }
 
+impl From<ReservationError> for ProcessorError {
+    fn from(err: ReservationError) -> Self {
+        Self::Core(ExtError::Reservation(err))
+    }
+}
+
 impl CoreError for ProcessorError {
     fn forbidden_function() -> Self {
         Self::Core(ExtError::forbidden_function())
@@ -184,6 +270,8 @@ pub struct Ext {
     pub context: ProcessorContext,
     /// Any guest code panic explanation, if available.
     pub error_explanation: Option<ProcessorError>,
+    /// Gas limit of the sent messages without a specified one.
+    pub default_gas_limit: Option<GasLimit>,
 }
 
 /// Empty implementation for non-substrate (and non-lazy-pages) using
@@ -195,6 +283,7 @@ impl ProcessorExt for Ext {
         Self {
             context,
             error_explanation: None,
+            default_gas_limit: None,
         }
     }
 
@@ -218,6 +307,12 @@ impl IntoExtInfo for Ext {
             message_context,
             gas_counter,
             program_candidates_data,
+            value_reservations,
+            counters,
+            scheduled_exit,
+            entry_budgets,
+            charge_allowance,
+            reply_waker,
             ..
         } = self.context;
 
@@ -237,7 +332,7 @@ impl IntoExtInfo for Ext {
         }
 
         let (outcome, context_store) = message_context.drain();
-        let (generated_dispatches, awakening) = outcome.drain();
+        let (generated_dispatches, awakening, recipient_charged) = outcome.drain();
 
         let info = ExtInfo {
             gas_amount: gas_counter.into(),
@@ -245,8 +340,15 @@ impl IntoExtInfo for Ext {
             pages_data,
             generated_dispatches,
             awakening,
+            recipient_charged,
             context_store,
             program_candidates_data,
+            value_reservations: value_reservations.into_changed(),
+            counters: counters.and_then(ProgramCounters::into_changed),
+            scheduled_exit,
+            entry_budgets,
+            charge_allowance,
+            reply_waker,
         };
         Ok(info)
     }
@@ -280,6 +382,17 @@ impl Ext {
         })
     }
 
+    fn remember_delayed(&mut self, message_id: MessageId, delay: Option<NonZeroU32>) -> MessageId {
+        if let Some(delay) = delay {
+            let block_number = self.context.block_info.height.saturating_add(delay.get());
+            self.context
+                .delayed_dispatches
+                .insert(message_id, block_number);
+        }
+
+        message_id
+    }
+
     fn check_message_value(&mut self, message_value: u128) -> Result<(), ProcessorError> {
         let existential_deposit = self.context.existential_deposit;
         // Sending value should apply the range {0} ∪ [existential_deposit; +inf)
@@ -293,7 +406,7 @@ impl Ext {
         }
     }
 
-    fn charge_message_gas(&mut self, gas_limit: Option<GasLimit>) -> Result<(), ProcessorError> {
+    fn check_message_gas(&mut self, gas_limit: Option<GasLimit>) -> Result<(), ProcessorError> {
         let mailbox_threshold = self.context.mailbox_threshold;
         let gas_limit = gas_limit.unwrap_or(0);
 
@@ -302,13 +415,22 @@ impl Ext {
                 message_gas_limit: gas_limit,
                 mailbox_threshold,
             }))
-        } else if self.context.gas_counter.reduce(gas_limit) != ChargeResult::Enough {
+        } else if self.context.gas_counter.left() < gas_limit {
             self.return_and_store_err(Err(MessageError::NotEnoughGas))
         } else {
             Ok(())
         }
     }
 
+    fn charge_message_gas(&mut self, gas_limit: Option<GasLimit>) -> Result<(), ProcessorError> {
+        self.check_message_gas(gas_limit)?;
+
+        // Gas limit is checked to be left, so it can't fail.
+        self.context.gas_counter.reduce(gas_limit.unwrap_or(0));
+
+        Ok(())
+    }
+
     fn charge_message_value(&mut self, message_value: u128) -> Result<(), ProcessorError> {
         if self.context.value_counter.reduce(message_value) != ChargeResult::Enough {
             self.return_and_store_err(Err(MessageError::NotEnoughValue {
@@ -320,6 +442,30 @@ impl Ext {
         }
     }
 
+    fn top_up_existential_deposit(
+        &mut self,
+        packet: InitPacket,
+    ) -> Result<InitPacket, ProcessorError> {
+        let existential_deposit = self.context.existential_deposit;
+
+        if self.context.existential_deposit_policy != ExistentialDepositPolicy::TopUp
+            || packet.value() != 0
+            || existential_deposit == 0
+        {
+            return Ok(packet);
+        }
+
+        let value_left = self.context.value_counter.left();
+        if value_left < existential_deposit {
+            self.return_and_store_err(Err(MessageError::NotEnoughValueForExistentialDeposit {
+                existential_deposit,
+                value_left,
+            }))
+        } else {
+            Ok(packet.with_value(existential_deposit))
+        }
+    }
+
     fn charge_expiring_resources<T: Packet>(&mut self, packet: &T) -> Result<(), ProcessorError> {
         self.check_message_value(packet.value())?;
         // Charge for using expiring resources. Charge for calling sys-call was done earlier.
@@ -335,6 +481,23 @@ impl Ext {
             Ok(())
         }
     }
+
+    fn check_reply_destination(&mut self, id: Option<ProgramId>) -> Result<(), ProcessorError> {
+        match id {
+            Some(id) => {
+                self.check_forbidden_call(id)?;
+
+                // Replies can only be redirected to programs: users would get
+                // replies on messages they never sent into their mailboxes.
+                if id != self.context.program_id && !(self.context.program_exists)(id) {
+                    self.return_and_store_err(Err(MessageError::InvalidReplyDestination))
+                } else {
+                    Ok(())
+                }
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl EnvExt for Ext {
@@ -400,6 +563,70 @@ impl EnvExt for Ext {
         Ok(self.context.block_info.timestamp)
     }
 
+    fn block_duration(&mut self) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::BlockDuration)?;
+        Ok(self.context.block_duration)
+    }
+
+    fn block_hash(&mut self, height: u32) -> Result<[u8; 32], Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::BlockHash)?;
+
+        // Hash is read from the storage only for the requested block,
+        // the read is included into the weight of the syscall.
+        let current = self.context.block_info.height;
+        let hash = if height >= current {
+            Err(ExecutionError::BlockHashUnknown)
+        } else if current - height > self.context.block_hash_depth {
+            Err(ExecutionError::BlockHashTooOld)
+        } else {
+            Ok((self.context.block_hash)(height))
+        };
+
+        self.return_and_store_err(hash)
+    }
+
+    fn tx_hash(&mut self) -> Result<[u8; 32], Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::TxHash)?;
+
+        let hash = self.context.tx_hash.ok_or(ExecutionError::NoTxHash);
+        self.return_and_store_err(hash)
+    }
+
+    fn stack_limit(&mut self) -> Result<Option<u32>, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::StackLimit)?;
+        Ok(self.context.stack_height)
+    }
+
+    fn children_count(&mut self) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ChildrenCount)?;
+
+        let created = self
+            .context
+            .program_candidates_data
+            .values()
+            .map(Vec::len)
+            .sum::<usize>() as u64;
+
+        Ok(self.context.children_count.saturating_add(created))
+    }
+
+    fn children_gas_burned(&mut self) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ChildrenGasBurned)?;
+
+        // The read is included into the weight of the syscall.
+        Ok((self.context.children_gas_burned)(self.context.program_id))
+    }
+
+    fn codes_count(&mut self) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::CodesCount)?;
+        Ok((self.context.codes_count)())
+    }
+
+    fn context_limits(&mut self) -> Result<ContextStoreLimits, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ContextLimits)?;
+        Ok(self.context.message_context.store_limits())
+    }
+
     fn origin(&mut self) -> Result<gear_core::ids::ProgramId, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Origin)?;
         Ok(self.context.origin)
@@ -433,28 +660,224 @@ impl EnvExt for Ext {
         &mut self,
         handle: usize,
         msg: HandlePacket,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     ) -> Result<MessageId, Self::Error> {
-        self.charge_gas_runtime(RuntimeCosts::SendCommit(msg.payload().len() as u32))?;
+        // Metadata is charged the same way as the payload.
+        let len = msg.payload().len() + msg.metadata().len();
+        self.charge_gas_runtime(RuntimeCosts::SendCommit(len as u32))?;
+
+        let msg = match self.default_gas_limit {
+            Some(gas_limit) => msg.with_default_gas_limit(gas_limit),
+            None => msg,
+        };
 
         self.check_forbidden_call(msg.destination())?;
+        self.check_reply_destination(msg.reply_destination())?;
         self.charge_expiring_resources(&msg)?;
 
         let result = self
             .context
             .message_context
-            .send_commit(handle as u32, msg, delay);
+            .send_commit(handle as u32, msg, delay)
+            .map(|message_id| self.remember_delayed(message_id, delay));
+
+        self.return_and_store_err(result)
+    }
+
+    fn send_commit_charge_recipient(
+        &mut self,
+        handle: usize,
+        msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SendCommit(msg.payload().len() as u32))?;
+
+        let msg = match self.default_gas_limit {
+            Some(gas_limit) => msg.with_default_gas_limit(gas_limit),
+            None => msg,
+        };
+
+        self.check_forbidden_call(msg.destination())?;
+        self.check_message_value(msg.value())?;
+
+        // Gas limit is bought by the recipient, so it isn't taken from
+        // the gas counter, but should still be enough for the mailbox.
+        let mailbox_threshold = self.context.mailbox_threshold;
+        let gas_limit = msg.gas_limit().unwrap_or(0);
+
+        if gas_limit < mailbox_threshold {
+            return self.return_and_store_err(Err(MessageError::InsufficientGasLimit {
+                message_gas_limit: gas_limit,
+                mailbox_threshold,
+            }));
+        }
+
+        self.charge_message_value(msg.value())?;
+
+        let result = self
+            .context
+            .message_context
+            .send_commit_charge_recipient(handle as u32, msg);
+
+        self.return_and_store_err(result)
+    }
+
+    fn reservation_send_commit(
+        &mut self,
+        id: ReservationId,
+        handle: usize,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SendCommit(msg.payload().len() as u32))?;
+
+        let msg = match self.default_gas_limit {
+            Some(gas_limit) => msg.with_default_gas_limit(gas_limit),
+            None => msg,
+        };
+
+        self.check_forbidden_call(msg.destination())?;
+        self.check_message_value(msg.value())?;
+
+        // Value was already taken from the value counter on reservation.
+        let value = msg.value();
+        let result = self.context.value_reservations.check(&id, value);
+        self.return_and_store_err(result)?;
+
+        let gas_limit = msg.gas_limit();
+        self.check_message_gas(gas_limit)?;
+
+        let result = self
+            .context
+            .message_context
+            .send_commit(handle as u32, msg, delay)
+            .map(|message_id| self.remember_delayed(message_id, delay));
+        let message_id = self.return_and_store_err(result)?;
+
+        // Gas and reservation are drawn only once the message is sent,
+        // so a failed send doesn't consume them.
+        self.charge_message_gas(gas_limit)
+            .unwrap_or_else(|_| unreachable!("Gas was checked before sending"));
+        self.context
+            .value_reservations
+            .draw(id, value)
+            .unwrap_or_else(|_| unreachable!("Reservation was checked before sending"));
+
+        Ok(message_id)
+    }
+
+    fn reserve_and_send(
+        &mut self,
+        amount: u128,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<(ReservationId, MessageId), Self::Error> {
+        // Reserving first, so a failed reservation leaves no handle behind.
+        let id = self.reserve_value(amount)?;
+
+        let result = self.send_init().and_then(|handle| {
+            self.reservation_send_commit(id, handle, msg, delay)
+                .map_err(|err| {
+                    // The handle of the message which wasn't sent is dropped.
+                    self.context
+                        .message_context
+                        .send_rollback(handle as u32)
+                        .unwrap_or_else(|_| unreachable!("Handle was just provided"));
+                    err
+                })
+        });
+
+        match result {
+            Ok(message_id) => Ok((id, message_id)),
+            Err(err) => {
+                // Rolling back the reservation without charging for it,
+                // so the error of the send is the one returned.
+                let value = self
+                    .context
+                    .value_reservations
+                    .unreserve(id)
+                    .unwrap_or_else(|_| unreachable!("Reservation was just made"));
+                self.context.value_counter.increase(value);
+
+                Err(err)
+            }
+        }
+    }
+
+    fn forward(
+        &mut self,
+        destination: ProgramId,
+        value: u128,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        // Charged before copying, so the payload isn't copied for nothing.
+        let len = self.context.message_context.current().payload().len();
+        self.charge_gas_runtime(RuntimeCosts::Forward(len as u32))?;
+
+        // Incoming payload is already limited, so conversion can't fail.
+        let payload = self
+            .context
+            .message_context
+            .current()
+            .payload()
+            .to_vec()
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("Payload of incoming message exceeds the limit"));
+        let msg = match self.default_gas_limit {
+            Some(gas_limit) => HandlePacket::new_with_gas(destination, payload, gas_limit, value),
+            None => HandlePacket::new(destination, payload, value),
+        };
+
+        self.check_forbidden_call(msg.destination())?;
+        self.charge_expiring_resources(&msg)?;
+
+        let result = self.context.message_context.send_init().and_then(|handle| {
+            self.context
+                .message_context
+                .send_commit(handle, msg, delay)
+                .map(|message_id| self.remember_delayed(message_id, delay))
+        });
 
         self.return_and_store_err(result)
     }
 
-    fn reply_commit(&mut self, msg: ReplyPacket, delay: u32) -> Result<MessageId, Self::Error> {
+    fn set_default_gas_limit(&mut self, gas_limit: u64) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SetDefaultGasLimit)?;
+
+        self.default_gas_limit = Some(gas_limit);
+        Ok(())
+    }
+
+    fn set_charge_allowance(&mut self, allowance: u64) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SetChargeAllowance)?;
+
+        self.context.charge_allowance = Some(allowance);
+        Ok(())
+    }
+
+    fn reply_commit(
+        &mut self,
+        msg: ReplyPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::ReplyCommit(msg.payload().len() as u32))?;
 
+        if ReplyCode::from(msg.exit_code()).is_system_error() {
+            return self.return_and_store_err(Err(MessageError::ReservedExitCode(msg.exit_code())));
+        }
+
+        let msg = match self.default_gas_limit {
+            Some(gas_limit) => msg.with_default_gas_limit(gas_limit),
+            None => msg,
+        };
+
         self.check_forbidden_call(self.context.message_context.reply_destination())?;
         self.charge_expiring_resources(&msg)?;
 
-        let result = self.context.message_context.reply_commit(msg, delay);
+        let result = self
+            .context
+            .message_context
+            .reply_commit(msg, delay)
+            .map(|message_id| self.remember_delayed(message_id, delay));
 
         self.return_and_store_err(result)
     }
@@ -474,11 +897,59 @@ impl EnvExt for Ext {
         Ok(self.context.message_context.current().source())
     }
 
+    fn source_code_id(&mut self) -> Result<CodeId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SourceCodeId)?;
+
+        let source = self.context.message_context.current().source();
+        // The read is included into the weight of the syscall.
+        let code_id = (self.context.code_id_of)(source).ok_or(ExecutionError::NoSourceCode);
+        self.return_and_store_err(code_id)
+    }
+
     fn exit(&mut self) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Exit)?;
         Ok(())
     }
 
+    fn schedule_exit(&mut self, block: u32, inheritor: ProgramId) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ScheduleExit)?;
+
+        let height = self.context.block_info.height;
+        if block <= height {
+            return self.return_and_store_err(Err(ExecutionError::ExitNotInFuture));
+        }
+
+        // The task is kept in the scheduler for the same rent per block as
+        // a waiting message. Nothing holds gas for the task, so the rent is
+        // paid upfront, including the blocks the scheduler may miss.
+        let rent = u64::from(self.context.reserve_for.saturating_add(block - height))
+            .saturating_mul(self.context.waitlist_cost);
+
+        if self.context.gas_counter.left() < rent {
+            return self.return_and_store_err(Err(ExecutionError::NotEnoughGasForExit));
+        }
+
+        self.charge_gas(rent)?;
+
+        // The exit scheduled before is replaced.
+        self.context.scheduled_exit = Some((block, inheritor));
+
+        Ok(())
+    }
+
+    fn set_entry_budget(&mut self, entry: u32, gas: u64) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SetEntryBudget)?;
+
+        let kind = match budgeted_entry(entry) {
+            Some(kind) => kind,
+            None => return self.return_and_store_err(Err(ExecutionError::InvalidEntryPoint)),
+        };
+
+        self.context.entry_budgets.insert(kind, gas);
+
+        Ok(())
+    }
+
     fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::ExitCode)?;
         Ok(self
@@ -489,6 +960,27 @@ impl EnvExt for Ext {
             .map(|d| d.into_exit_code()))
     }
 
+    fn signal_details(&mut self) -> Result<(MessageId, SignalCode), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SignalDetails)?;
+
+        let details = match self.context.dispatch_kind {
+            DispatchKind::Signal => self.context.message_context.current().reply(),
+            _ => None,
+        }
+        .and_then(|d| {
+            let (reply_to, exit_code) = d.into_parts();
+            SignalCode::from_u32(exit_code as u32).map(|code| (reply_to, code))
+        })
+        .ok_or(ExecutionError::NoSignalContext);
+
+        self.return_and_store_err(details)
+    }
+
+    fn execution_id(&mut self) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ExecutionId)?;
+        Ok(self.context.execution_id)
+    }
+
     fn message_id(&mut self) -> Result<MessageId, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::MsgId)?;
         Ok(self.context.message_context.current().id())
@@ -521,6 +1013,28 @@ impl EnvExt for Ext {
         Ok(())
     }
 
+    fn trace(&mut self, level: u32, data: &str) -> Result<(), Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::Trace(data.len() as u32))?;
+
+        // `log_enabled!` needs the target itself, so the maximum level
+        // enabled is checked instead, not to build the target needlessly.
+        if let Some(level) = trace_level(level).filter(|&level| level <= log::max_level()) {
+            // Programs are distinguished by targets, so nodes
+            // are able to configure trace levels per program.
+            let target = alloc::format!("gwasm::trace::{}", self.context.program_id);
+
+            log::log!(
+                target: &target,
+                level,
+                "[{}] {}",
+                self.context.message_context.current().id(),
+                data
+            );
+        }
+
+        Ok(())
+    }
+
     fn read(&mut self) -> Result<&[u8], Self::Error> {
         let size = self
             .size()?
@@ -532,12 +1046,57 @@ impl EnvExt for Ext {
         Ok(self.context.message_context.current().payload())
     }
 
+    fn read_part(&mut self, len: usize) -> Result<&[u8], Self::Error> {
+        // Charged up to the payload size, so reading out of the payload
+        // fails with the range error rather than running out of gas.
+        let size = self
+            .size()?
+            .min(len)
+            .try_into()
+            .map_err(|_| MessageError::IncomingPayloadTooBig)?;
+
+        self.charge_gas_runtime(RuntimeCosts::Read(size))?;
+
+        Ok(self.context.message_context.current().payload())
+    }
+
     fn size(&mut self) -> Result<usize, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Size)?;
 
         Ok(self.context.message_context.current().payload().len())
     }
 
+    fn message_meta(&mut self) -> Result<&[u8], Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::MessageMeta)?;
+
+        Ok(self.context.message_context.current().metadata())
+    }
+
+    fn mailbox_contains(
+        &mut self,
+        user_id: ProgramId,
+        message_id: MessageId,
+    ) -> Result<bool, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::MailboxContains)?;
+
+        // The read is included into the weight of the syscall.
+        Ok((self.context.mailbox_contains)(
+            self.context.program_id,
+            user_id,
+            message_id,
+        ))
+    }
+
+    fn record_not_found(&mut self, index: u32, count: u32) -> Result<(), Self::Error> {
+        self.return_and_store_err(Err(MessageError::RecordNotFound { index, count }))
+    }
+
+    fn max_payload_size(&mut self) -> Result<u32, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::MaxPayloadSize)?;
+
+        Ok(MAX_PAYLOAD_SIZE as u32)
+    }
+
     fn gas(&mut self, val: u32) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::MeteringBlock(val))
     }
@@ -584,6 +1143,21 @@ impl EnvExt for Ext {
         Ok(self.context.gas_counter.left())
     }
 
+    fn gas_allowance(&mut self) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::GasAllowance)?;
+        Ok(self.context.gas_allowance_counter.left())
+    }
+
+    fn is_init(&mut self) -> Result<bool, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::IsInit)?;
+        Ok(self.context.dispatch_kind == DispatchKind::Init)
+    }
+
+    fn resumed_from_wait(&mut self) -> Result<bool, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ResumedFromWait)?;
+        Ok(self.context.message_context.resumed())
+    }
+
     fn value(&mut self) -> Result<u128, Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Value)?;
         Ok(self.context.message_context.current().value())
@@ -594,6 +1168,19 @@ impl EnvExt for Ext {
         Ok(self.context.value_counter.left())
     }
 
+    fn spendable_value(&mut self) -> Result<u128, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SpendableValue)?;
+
+        let value_left = self.context.value_counter.left();
+
+        // Sending value should apply the range {0} ∪ [existential_deposit; +inf)
+        Ok(if value_left < self.context.existential_deposit {
+            0
+        } else {
+            value_left
+        })
+    }
+
     fn leave(&mut self) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Leave)?;
         Ok(())
@@ -602,6 +1189,9 @@ impl EnvExt for Ext {
     fn wait(&mut self) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Wait)?;
 
+        let result = self.context.message_context.check_store_limits();
+        self.return_and_store_err(result)?;
+
         let reserve = u64::from(self.context.reserve_for.saturating_add(1))
             .saturating_mul(self.context.waitlist_cost);
 
@@ -612,6 +1202,21 @@ impl EnvExt for Ext {
         Ok(())
     }
 
+    fn wait_for_reply(&mut self, message_id: MessageId) -> Result<(), Self::Error> {
+        self.wait()?;
+
+        // Entry of the waker is kept apart from the waitlisted message
+        // and removed along with it, so it's charged once here.
+        let cost = self.context.reply_waker_cost;
+
+        if self.context.gas_counter.reduce(cost) != ChargeResult::Enough {
+            return self.return_and_store_err(Err(WaitError::NotEnoughGas));
+        }
+
+        self.context.reply_waker = Some(message_id);
+        Ok(())
+    }
+
     fn wait_for(&mut self, duration: u32) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::WaitFor)?;
 
@@ -619,6 +1224,9 @@ impl EnvExt for Ext {
             return self.return_and_store_err(Err(WaitError::InvalidArgument));
         }
 
+        let result = self.context.message_context.check_store_limits();
+        self.return_and_store_err(result)?;
+
         let reserve = u64::from(self.context.reserve_for.saturating_add(duration))
             .saturating_mul(self.context.waitlist_cost);
 
@@ -636,6 +1244,9 @@ impl EnvExt for Ext {
             return self.return_and_store_err(Err(WaitError::InvalidArgument));
         }
 
+        let result = self.context.message_context.check_store_limits();
+        self.return_and_store_err(result)?;
+
         let reserve = u64::from(self.context.reserve_for.saturating_add(1))
             .saturating_mul(self.context.waitlist_cost);
 
@@ -646,16 +1257,63 @@ impl EnvExt for Ext {
         Ok(())
     }
 
-    fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Self::Error> {
+    fn wait_budget_remaining(&mut self) -> Result<u32, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::WaitBudgetRemaining)?;
+
+        // Matches the maximal hold bound the message gets on `wait`.
+        let blocks = self
+            .context
+            .gas_counter
+            .left()
+            .checked_div(self.context.waitlist_cost)
+            .unwrap_or(u64::MAX)
+            .saturating_sub(self.context.reserve_for.into());
+
+        Ok(blocks.try_into().unwrap_or(u32::MAX))
+    }
+
+    fn wait_if(&mut self, condition: bool) -> Result<(), Self::Error> {
+        if condition {
+            return self.wait();
+        }
+
+        self.charge_gas_runtime(RuntimeCosts::WaitIf)
+    }
+
+    fn wake(&mut self, waker_id: MessageId, delay: Option<NonZeroU32>) -> Result<(), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::Wake)?;
         let result = self.context.message_context.wake(waker_id, delay);
 
         self.return_and_store_err(result)
     }
 
-    fn create_program(&mut self, packet: InitPacket, delay: u32) -> Result<ProgramId, Self::Error> {
+    fn create_program(
+        &mut self,
+        packet: InitPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<(MessageId, ProgramId), Self::Error> {
         self.charge_gas_runtime(RuntimeCosts::CreateProgram(packet.payload().len() as u32))?;
 
+        // Program id is derived from the code id and salt, so the collision
+        // is detected before any gas is reserved for the init message.
+        // Programs created within the current execution aren't stored yet,
+        // so they are looked up among the candidates. The storage read is
+        // included into the weight of the syscall.
+        let new_prog_id = packet.destination();
+        let created = self
+            .context
+            .program_candidates_data
+            .get(&packet.code_id())
+            .into_iter()
+            .flatten()
+            .any(|&(id, _)| id == new_prog_id);
+        if created || (self.context.program_exists)(new_prog_id) {
+            return self.return_and_store_err(Err(MessageError::ProgramAlreadyExists {
+                id: new_prog_id.into(),
+            }));
+        }
+
+        let packet = self.top_up_existential_deposit(packet)?;
         self.charge_expiring_resources(&packet)?;
 
         let code_hash = packet.code_id();
@@ -674,13 +1332,102 @@ impl EnvExt for Ext {
                     .or_default();
                 entry.push((new_prog_id, init_msg_id));
 
-                new_prog_id
+                (init_msg_id, new_prog_id)
             });
 
         self.return_and_store_err(result)
     }
 
+    fn new_handle(&mut self) -> Result<u32, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::NewHandle)?;
+        let result = self.context.message_context.new_handle();
+
+        self.return_and_store_err(result)
+    }
+
+    fn delayed_eta(&mut self, message_id: MessageId) -> Result<u32, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::DelayedEta)?;
+
+        // Messages delayed by earlier executions are looked up in the
+        // storage, which is covered by the syscall weight.
+        let height = self.context.block_info.height;
+        let result = self
+            .context
+            .delayed_dispatches
+            .get(&message_id)
+            .copied()
+            .or_else(|| (self.context.delayed_dispatch)(self.context.program_id, message_id))
+            .map(|block_number| block_number.saturating_sub(height))
+            .ok_or(MessageError::DelayedNotFound);
+
+        self.return_and_store_err(result)
+    }
+
+    fn reserve_value(&mut self, value: u128) -> Result<ReservationId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::ReserveValue)?;
+
+        let result = self.context.message_context.reservation_id();
+        let id = self.return_and_store_err(result)?;
+
+        if self.context.value_counter.reduce(value) != ChargeResult::Enough {
+            return self.return_and_store_err(Err(ReservationError::NotEnoughValue {
+                value,
+                value_left: self.context.value_counter.left(),
+            }));
+        }
+
+        self.context.value_reservations.reserve(id, value);
+
+        Ok(id)
+    }
+
+    fn unreserve_value(&mut self, id: ReservationId) -> Result<u128, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::UnreserveValue)?;
+
+        let result = self.context.value_reservations.unreserve(id);
+        let value = self.return_and_store_err(result)?;
+
+        self.context.value_counter.increase(value);
+
+        Ok(value)
+    }
+
+    fn split_value_reservation(
+        &mut self,
+        id: ReservationId,
+        value: u128,
+    ) -> Result<ReservationId, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::SplitValueReservation)?;
+
+        let result = self.context.message_context.reservation_id();
+        let new_id = self.return_and_store_err(result)?;
+
+        let result = self.context.value_reservations.split(id, new_id, value);
+        self.return_and_store_err(result)?;
+
+        Ok(new_id)
+    }
+
+    fn bump_counter(&mut self, key: CounterKey) -> Result<u64, Self::Error> {
+        self.charge_gas_runtime(RuntimeCosts::BumpCounter)?;
+
+        // Counters are read from the storage on the first bump only,
+        // the read is included into the weight of the syscall.
+        let program_id = self.context.program_id;
+        let program_counters = self.context.program_counters;
+        let counters = self
+            .context
+            .counters
+            .get_or_insert_with(|| ProgramCounters::new(program_counters(program_id)));
+
+        Ok(counters.bump(key))
+    }
+
     fn forbidden_funcs(&self) -> &BTreeSet<&'static str> {
         &self.context.forbidden_funcs
     }
+
+    fn dispatch_kind(&self) -> DispatchKind {
+        self.context.dispatch_kind
+    }
 }
@@ -46,6 +46,11 @@ pub fn handle_journal(
                 dispatch,
                 delay,
             } => handler.send_dispatch(message_id, dispatch, delay),
+            JournalNote::SendDispatchChargeRecipient {
+                message_id,
+                dispatch,
+                delay,
+            } => handler.send_dispatch_charge_recipient(message_id, dispatch, delay),
             JournalNote::WaitDispatch { dispatch, duration } => {
                 handler.wait_dispatch(dispatch, duration)
             }
@@ -69,6 +74,39 @@ pub fn handle_journal(
             } => {
                 allocations_update.insert(program_id, allocations);
             }
+            JournalNote::UpdateValueReservations {
+                program_id,
+                reservations,
+            } => handler.update_value_reservations(program_id, reservations),
+            JournalNote::UpdateChildrenCount {
+                program_id,
+                children_count,
+            } => handler.update_children_count(program_id, children_count),
+            JournalNote::ChildGasBurned { program_id, amount } => {
+                handler.child_gas_burned(program_id, amount)
+            }
+            JournalNote::UpdateCounters {
+                program_id,
+                counters,
+            } => handler.update_counters(program_id, counters),
+            JournalNote::UpdateEntryBudgets {
+                program_id,
+                entry_budgets,
+            } => handler.update_entry_budgets(program_id, entry_budgets),
+            JournalNote::UpdateChargeAllowance {
+                program_id,
+                allowance,
+            } => handler.update_charge_allowance(program_id, allowance),
+            JournalNote::UpdateReplyWaker {
+                program_id,
+                reply_to,
+                waiting,
+            } => handler.update_reply_waker(program_id, reply_to, waiting),
+            JournalNote::ScheduleExit {
+                program_id,
+                block,
+                inheritor,
+            } => handler.schedule_exit(program_id, block, inheritor),
             JournalNote::SendValue { from, to, value } => handler.send_value(from, to, value),
             JournalNote::StoreNewPrograms {
                 code_hash,
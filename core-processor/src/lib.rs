@@ -26,6 +26,7 @@
 extern crate alloc;
 
 use gear_core::message::ExitCode;
+use gear_core_errors::ReplyCode;
 
 pub mod common;
 pub mod configs;
@@ -34,8 +35,11 @@ mod ext;
 mod handler;
 mod processor;
 
+// Exit codes of the replies generated by the processor lie in the range
+// reserved by `ReplyCode`, so they never clash with ones chosen by programs.
+
 /// Error exit code.
-pub const ERR_EXIT_CODE: ExitCode = 1;
+pub const ERR_EXIT_CODE: ExitCode = ReplyCode::EXECUTION_ERROR.to_raw();
 
 /// Destination isn't available for the message.
 ///
@@ -45,12 +49,33 @@ pub const ERR_EXIT_CODE: ExitCode = 1;
 /// 2. Program tries to init terminated program.
 /// If the message is `handle` or `handle_reply` it means, that destination
 /// was terminated while the message was in the queue.
-pub const UNAVAILABLE_DEST_EXIT_CODE: ExitCode = 2;
+pub const UNAVAILABLE_DEST_EXIT_CODE: ExitCode = ReplyCode::UNAVAILABLE_DESTINATION.to_raw();
 
 /// A try to init again initialized, existing program.
-pub const RE_INIT_EXIT_CODE: ExitCode = 3;
+pub const RE_INIT_EXIT_CODE: ExitCode = ReplyCode::REINIT.to_raw();
+
+/// Initialization of the program failed, so the program is terminated.
+///
+/// It's the exit code of the error reply to the failed `init` message, which
+/// lets the creator tell the dead program id apart from other errors.
+pub const INIT_FAILURE_EXIT_CODE: ExitCode = ReplyCode::INIT_FAILURE.to_raw();
+
+/// The program exited during its initialization without replying.
+///
+/// It's the exit code of the reply to the `init` message, which payload is
+/// the encoded [`ExecutionErrorReason::InitExited`](common::ExecutionErrorReason::InitExited)
+/// with the inheritor of the program value.
+pub const INIT_EXITED_EXIT_CODE: ExitCode = ReplyCode::INIT_EXITED.to_raw();
+
+/// Handling of the reply exceeded the gas budget of `handle_reply`.
+///
+/// The reply with this code is delivered to the program instead of the
+/// dropped one, so the flow waiting for the reply isn't stuck.
+pub const ENTRY_BUDGET_EXCEEDED_EXIT_CODE: ExitCode = ReplyCode::ENTRY_BUDGET_EXCEEDED.to_raw();
 
 pub use executor::execute_wasm;
 pub use ext::{Ext, ProcessorContext, ProcessorError, ProcessorExt};
 pub use handler::handle_journal;
-pub use processor::{prepare, process, PrepareResult, PreparedMessageExecutionContext};
+pub use processor::{
+    prepare, process, simulate_dispatch, PrepareResult, PreparedMessageExecutionContext,
+};
@@ -19,7 +19,7 @@
 use crate::{
     common::{
         Actor, DispatchOutcome, DispatchResult, DispatchResultKind, ExecutableActorData,
-        ExecutionErrorReason, JournalNote, WasmExecutionContext,
+        ExecutionErrorReason, JournalNote, SimulationOutcome, WasmExecutionContext,
     },
     configs::{BlockConfig, ExecutionSettings, MessageExecutionContext},
     executor,
@@ -32,15 +32,19 @@ use alloc::{
     vec::Vec,
 };
 use codec::Encode;
-use gear_backend_common::{Environment, IntoExtInfo};
+use gear_backend_common::{Environment, IntoExtInfo, TrapExplanation};
 use gear_core::{
     env::Ext as EnvExt,
-    gas::{GasAllowanceCounter, GasCounter},
-    ids::ProgramId,
+    gas::{ChargeResult, GasAllowanceCounter, GasCounter},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
-    message::{DispatchKind, ExitCode, IncomingDispatch, ReplyMessage, StoredDispatch},
+    message::{
+        DispatchKind, ExitCode, IncomingDispatch, ReplyMessage, SignalCode, SignalMessage,
+        StoredDispatch,
+    },
     program::Program,
 };
+use gear_core_errors::{ExecutionError, ExtError};
 
 #[derive(Debug)]
 enum SuccessfulDispatchResultKind {
@@ -55,9 +59,15 @@ pub struct PreparedMessageExecutionContext {
     gas_allowance_counter: GasAllowanceCounter,
     dispatch: IncomingDispatch,
     origin: ProgramId,
+    tx_hash: Option<[u8; 32]>,
+    execution_id: u64,
     balance: u128,
     program: Program,
     memory_size: WasmPageNumber,
+    value_reservations: BTreeMap<ReservationId, u128>,
+    children_count: u64,
+    entry_budget: Option<u64>,
+    reply_wake: Vec<JournalNote>,
 }
 
 impl PreparedMessageExecutionContext {
@@ -94,6 +104,8 @@ pub fn prepare(
         actor,
         dispatch,
         origin,
+        tx_hash,
+        execution_id,
         gas_allowance,
         subsequent_execution,
     } = execution_context;
@@ -103,30 +115,91 @@ pub fn prepare(
         executable_data,
     } = actor;
 
-    let (program, pages_with_data) = match check_is_executable(executable_data, &dispatch) {
-        Err(exit_code) => {
-            return PrepareResult::Error(process_non_executable(
-                dispatch,
-                destination_program,
-                exit_code,
-            ))
-        }
-        Ok(ExecutableActorData {
-            program,
-            pages_with_data,
-        }) => (program, pages_with_data),
-    };
+    let (program, pages_with_data, value_reservations, children_count) =
+        match check_is_executable(executable_data, &dispatch) {
+            Err(exit_code) => {
+                return PrepareResult::Error(process_non_executable(
+                    dispatch,
+                    destination_program,
+                    exit_code,
+                ))
+            }
+            Ok(ExecutableActorData {
+                program,
+                pages_with_data,
+                value_reservations,
+                children_count,
+            }) => (program, pages_with_data, value_reservations, children_count),
+        };
 
     let program_id = program.id();
+    let exports_signal = program.code().exports().contains(&DispatchKind::Signal);
+
     let mut gas_counter = GasCounter::new(dispatch.gas_limit());
+    let mut gas_allowance_counter = GasAllowanceCounter::new(gas_allowance);
+    let reply_wake = match charge_and_process_reply_wake(
+        block_config,
+        &dispatch,
+        program_id,
+        &mut gas_counter,
+        &mut gas_allowance_counter,
+    ) {
+        Ok(reply_wake) => reply_wake,
+        Err(reason) => {
+            log::debug!("Failed to read reply waker: {reason:?}");
+            return PrepareResult::Error(match reason {
+                ExecutionErrorReason::ReplyWakerReadBlockGasExceeded => {
+                    process_allowance_exceed(dispatch, program_id, gas_counter.burned())
+                }
+                _ => process_error(
+                    dispatch,
+                    program_id,
+                    exports_signal,
+                    gas_counter.burned(),
+                    reason,
+                ),
+            });
+        }
+    };
+
     if !program.code().exports().contains(&dispatch.kind()) {
-        return PrepareResult::WontExecute(process_success(
-            SuccessfulDispatchResultKind::Success,
-            DispatchResult::success(dispatch, program_id, gas_counter.into()),
+        return PrepareResult::WontExecute(with_reply_wake(
+            reply_wake,
+            process_success(
+                SuccessfulDispatchResultKind::Success,
+                DispatchResult::success(dispatch, program_id, gas_counter.into()),
+            ),
         ));
     }
 
-    let mut gas_allowance_counter = GasAllowanceCounter::new(gas_allowance);
+    let entry_budget = match charge_and_read_entry_budget(
+        block_config,
+        &dispatch,
+        program_id,
+        &mut gas_counter,
+        &mut gas_allowance_counter,
+    ) {
+        Ok(entry_budget) => entry_budget,
+        Err(reason) => {
+            log::debug!("Failed to read entry budget: {reason:?}");
+            return PrepareResult::Error(with_reply_wake(
+                reply_wake,
+                match reason {
+                    ExecutionErrorReason::EntryBudgetReadBlockGasExceeded => {
+                        process_allowance_exceed(dispatch, program_id, gas_counter.burned())
+                    }
+                    _ => process_error(
+                        dispatch,
+                        program_id,
+                        exports_signal,
+                        gas_counter.burned(),
+                        reason,
+                    ),
+                },
+            ));
+        }
+    };
+
     let memory_size = match executor::charge_gas_for_pages(
         &block_config.allocations_config,
         &mut gas_counter,
@@ -142,14 +215,23 @@ pub fn prepare(
         }
         Err(reason) => {
             log::debug!("Failed to charge for memory pages: {reason:?}");
-            return PrepareResult::Error(match reason {
-                ExecutionErrorReason::InitialMemoryBlockGasExceeded
-                | ExecutionErrorReason::GrowMemoryBlockGasExceeded
-                | ExecutionErrorReason::LoadMemoryBlockGasExceeded => {
-                    process_allowance_exceed(dispatch, program_id, gas_counter.burned())
-                }
-                _ => process_error(dispatch, program_id, gas_counter.burned(), reason),
-            });
+            return PrepareResult::Error(with_reply_wake(
+                reply_wake,
+                match reason {
+                    ExecutionErrorReason::InitialMemoryBlockGasExceeded
+                    | ExecutionErrorReason::GrowMemoryBlockGasExceeded
+                    | ExecutionErrorReason::LoadMemoryBlockGasExceeded => {
+                        process_allowance_exceed(dispatch, program_id, gas_counter.burned())
+                    }
+                    _ => process_error(
+                        dispatch,
+                        program_id,
+                        exports_signal,
+                        gas_counter.burned(),
+                        check_entry_budget(reason, entry_budget),
+                    ),
+                },
+            ));
         }
     };
 
@@ -159,9 +241,15 @@ pub fn prepare(
             gas_allowance_counter,
             dispatch,
             origin,
+            tx_hash,
+            execution_id,
             balance,
             program,
             memory_size,
+            value_reservations,
+            children_count,
+            entry_budget,
+            reply_wake,
         }),
         pages_with_data,
     }
@@ -177,37 +265,75 @@ pub fn process<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environment<
 
     let BlockConfig {
         block_info,
+        block_duration,
         allocations_config,
         existential_deposit,
+        existential_deposit_policy,
         outgoing_limit,
         host_fn_weights,
         forbidden_funcs,
         mailbox_threshold,
         waitlist_cost,
+        reply_waker_cost,
         reserve_for,
+        block_hash_depth,
+        block_hash,
+        program_exists,
+        mailbox_contains,
+        codes_count,
+        delayed_dispatch,
+        program_counters,
+        children_gas_burned,
+        code_id_of,
+        reply_waker: _,
+        entry_budget: _,
+        entry_budget_read_cost: _,
     } = block_config.clone();
 
     let execution_settings = ExecutionSettings {
         block_info,
+        block_duration,
         existential_deposit,
+        existential_deposit_policy,
         allocations_config,
         host_fn_weights,
         forbidden_funcs,
         mailbox_threshold,
         waitlist_cost,
+        reply_waker_cost,
         reserve_for,
+        block_hash_depth,
+        block_hash,
+        program_exists,
+        mailbox_contains,
+        codes_count,
+        delayed_dispatch,
+        program_counters,
+        children_gas_burned,
+        code_id_of,
     };
 
+    let reply_wake = execution_context.reply_wake;
     let dispatch = execution_context.dispatch;
     let balance = execution_context.balance;
+    let entry_budget = execution_context.entry_budget;
     let program_id = execution_context.program.id();
+    let exports_signal = execution_context
+        .program
+        .code()
+        .exports()
+        .contains(&DispatchKind::Signal);
     let execution_context = WasmExecutionContext {
         origin: execution_context.origin,
+        tx_hash: execution_context.tx_hash,
+        execution_id: execution_context.execution_id,
         gas_counter: execution_context.gas_counter,
         gas_allowance_counter: execution_context.gas_allowance_counter,
         program: execution_context.program,
         pages_initial_data: memory_pages,
         memory_size: execution_context.memory_size,
+        value_reservations: execution_context.value_reservations,
+        children_count: execution_context.children_count,
     };
     let msg_ctx_settings = gear_core::message::ContextSettings::new(0, outgoing_limit);
 
@@ -223,13 +349,17 @@ pub fn process<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environment<
         err
     });
 
-    match exec_result {
+    let kind = dispatch.kind();
+    let source = dispatch.source();
+
+    let journal = match exec_result {
         Ok(res) => match res.kind {
             DispatchResultKind::Trap(reason) => process_error(
                 res.dispatch,
                 program_id,
+                exports_signal,
                 res.gas_amount.burned(),
-                ExecutionErrorReason::Ext(reason),
+                check_entry_budget(ExecutionErrorReason::Ext(reason), entry_budget),
             ),
             DispatchResultKind::Success => process_success(Success, res),
             DispatchResultKind::Wait(duration) => process_success(Wait(duration), res),
@@ -246,9 +376,122 @@ pub fn process<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environment<
             | ExecutionErrorReason::LoadMemoryBlockGasExceeded => {
                 process_allowance_exceed(dispatch, program_id, e.gas_amount.burned())
             }
-            _ => process_error(dispatch, program_id, e.gas_amount.burned(), e.reason),
+            _ => process_error(
+                dispatch,
+                program_id,
+                exports_signal,
+                e.gas_amount.burned(),
+                check_entry_budget(e.reason, entry_budget),
+            ),
         },
+    };
+    let mut journal = with_reply_wake(reply_wake, journal);
+
+    // Gas burned by initialization is also accounted to the creator
+    // of the program. Notes for users as creators are ignored by handlers.
+    if matches!(kind, DispatchKind::Init) {
+        let amount = journal
+            .iter()
+            .filter_map(|note| match note {
+                JournalNote::GasBurned { amount, .. } => Some(*amount),
+                _ => None,
+            })
+            .fold(0u64, |total, amount| total.saturating_add(amount));
+
+        if amount != 0 {
+            journal.push(JournalNote::ChildGasBurned {
+                program_id: source,
+                amount,
+            });
+        }
     }
+
+    journal
+}
+
+/// Execute the prepared dispatch without committing its results.
+///
+/// The program state is taken from the `execution_context` and `memory_pages`
+/// snapshot, so the journal of the outcome is the same as the one of
+/// [`process`] over that state. The memory and allocations of the program
+/// are kept by the outcome with the changes of the execution applied, while
+/// handling the rest of the journal is up to the caller.
+pub fn simulate_dispatch<A: ProcessorExt + EnvExt + IntoExtInfo + 'static, E: Environment<A>>(
+    block_config: &BlockConfig,
+    execution_context: Box<PreparedMessageExecutionContext>,
+    memory_pages: BTreeMap<PageNumber, PageBuf>,
+) -> SimulationOutcome {
+    let message_id = execution_context.dispatch.id();
+    let program_id = execution_context.program.id();
+    let journal = process::<A, E>(block_config, execution_context, memory_pages.clone());
+
+    SimulationOutcome::new(message_id, program_id, memory_pages, journal)
+}
+
+/// Wakes the message waiting for the reply sent by
+/// [`gr_send_and_wait`](gear_core::env::Ext::wait_for_reply),
+/// if the dispatch is that reply.
+///
+/// Reading and removing the entry of the waiting message is charged
+/// with `reply_waker_cost` before the read.
+fn charge_and_process_reply_wake(
+    block_config: &BlockConfig,
+    dispatch: &IncomingDispatch,
+    program_id: ProgramId,
+    gas_counter: &mut GasCounter,
+    gas_allowance_counter: &mut GasAllowanceCounter,
+) -> Result<Vec<JournalNote>, ExecutionErrorReason> {
+    let reply_to = match dispatch.reply() {
+        Some(reply) => reply.reply_to(),
+        None => return Ok(Vec::new()),
+    };
+
+    let amount = block_config.reply_waker_cost;
+    if gas_allowance_counter.charge(amount) != ChargeResult::Enough {
+        return Err(ExecutionErrorReason::ReplyWakerReadBlockGasExceeded);
+    }
+
+    if gas_counter.charge(amount) != ChargeResult::Enough {
+        return Err(ExecutionErrorReason::ReplyWakerReadGasExceeded);
+    }
+
+    let awakening_id = match (block_config.reply_waker)(program_id, reply_to) {
+        Some(waiting) => waiting,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(alloc::vec![
+        JournalNote::WakeMessage {
+            message_id: dispatch.id(),
+            program_id,
+            awakening_id,
+            delay: None,
+        },
+        JournalNote::UpdateReplyWaker {
+            program_id,
+            reply_to,
+            waiting: None,
+        },
+    ])
+}
+
+/// Prepends notes waking the message waiting for the reply to the journal.
+///
+/// Dispatch stopped by the gas allowance is processed again,
+/// so the message is woken on that run.
+fn with_reply_wake(
+    mut reply_wake: Vec<JournalNote>,
+    journal: Vec<JournalNote>,
+) -> Vec<JournalNote> {
+    if journal
+        .iter()
+        .any(|note| matches!(note, JournalNote::StopProcessing { .. }))
+    {
+        return journal;
+    }
+
+    reply_wake.extend(journal);
+    reply_wake
 }
 
 fn check_is_executable(
@@ -266,10 +509,71 @@ fn check_is_executable(
         .unwrap_or(Err(crate::UNAVAILABLE_DEST_EXIT_CODE))
 }
 
+/// Charges for reading the gas budget of the entry point and reads it.
+///
+/// Budget caps gas available to the execution, while the rest of the
+/// message's gas is left intact. Init has no budget, so nothing is read.
+fn charge_and_read_entry_budget(
+    block_config: &BlockConfig,
+    dispatch: &IncomingDispatch,
+    program_id: ProgramId,
+    gas_counter: &mut GasCounter,
+    gas_allowance_counter: &mut GasAllowanceCounter,
+) -> Result<Option<u64>, ExecutionErrorReason> {
+    if matches!(dispatch.kind(), DispatchKind::Init) {
+        return Ok(None);
+    }
+
+    let amount = block_config.entry_budget_read_cost;
+    if gas_allowance_counter.charge(amount) != ChargeResult::Enough {
+        return Err(ExecutionErrorReason::EntryBudgetReadBlockGasExceeded);
+    }
+
+    if gas_counter.charge(amount) != ChargeResult::Enough {
+        return Err(ExecutionErrorReason::EntryBudgetReadGasExceeded);
+    }
+
+    let entry_budget = (block_config.entry_budget)(program_id, dispatch.kind())
+        .filter(|&budget| budget < gas_counter.left());
+
+    if let Some(budget) = entry_budget {
+        // Reducing rather than charging, so the cut gas isn't burned.
+        gas_counter.reduce(gas_counter.left() - budget);
+    }
+
+    Ok(entry_budget)
+}
+
+/// Replaces running out of gas with exceeding the entry point's budget,
+/// if the execution was limited by the budget.
+fn check_entry_budget(
+    reason: ExecutionErrorReason,
+    entry_budget: Option<u64>,
+) -> ExecutionErrorReason {
+    let budget = match entry_budget {
+        Some(budget) => budget,
+        None => return reason,
+    };
+
+    match reason {
+        ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Execution(
+            ExecutionError::GasLimitExceeded,
+        )))
+        | ExecutionErrorReason::LoadMemoryGasExceeded
+        | ExecutionErrorReason::GrowMemoryGasExceeded
+        | ExecutionErrorReason::InitialMemoryGasExceeded
+        | ExecutionErrorReason::PageWritesGasExceeded => {
+            ExecutionErrorReason::EntryBudgetExceeded(budget)
+        }
+        reason => reason,
+    }
+}
+
 /// Helper function for journal creation in trap/error case
 fn process_error(
     dispatch: IncomingDispatch,
     program_id: ProgramId,
+    exports_signal: bool,
     gas_burned: u64,
     err: ExecutionErrorReason,
 ) -> Vec<JournalNote> {
@@ -300,7 +604,36 @@ fn process_error(
         });
     }
 
-    if !dispatch.is_error_reply() {
+    if matches!(err, ExecutionErrorReason::EntryBudgetExceeded(_))
+        && matches!(dispatch.kind(), DispatchKind::Reply)
+    {
+        // The reply is replaced with the system one carrying the error, so
+        // the flow waiting for the reply isn't stuck. The replier gets no
+        // error reply, so it can't spend the gas left any further.
+        if let Some(reply_to) = dispatch
+            .reply_to()
+            .filter(|_| dispatch.exit_code() != Some(crate::ENTRY_BUDGET_EXCEEDED_EXIT_CODE))
+        {
+            // This expect panic is unreachable, unless error message is too large or max payload size is too small.
+            let err_payload = err.encode().try_into().expect("Error message is too large");
+            // # Safety
+            //
+            // The replaced reply is the only one to `reply_to` handled by the
+            // program, so the id of the system reply will not be duplicated.
+            let dispatch = ReplyMessage::system(
+                reply_to,
+                err_payload,
+                crate::ENTRY_BUDGET_EXCEEDED_EXIT_CODE,
+            )
+            .into_dispatch(dispatch.source(), program_id, reply_to);
+
+            journal.push(JournalNote::SendDispatch {
+                message_id,
+                dispatch,
+                delay: None,
+            });
+        }
+    } else if !dispatch.is_error_reply() {
         // This expect panic is unreachable, unless error message is too large or max payload size is too small.
         let err_payload = err.encode().try_into().expect("Error message is too large");
         // # Safety
@@ -309,13 +642,37 @@ fn process_error(
         // 2. This reply message is generated by our system
         //
         // So, the message id of this reply message will not be duplicated.
-        let dispatch = ReplyMessage::system(dispatch.id(), err_payload, crate::ERR_EXIT_CODE)
-            .into_dispatch(program_id, dispatch.source(), dispatch.id());
+        let exit_code = match dispatch.kind() {
+            DispatchKind::Init => crate::INIT_FAILURE_EXIT_CODE,
+            _ => crate::ERR_EXIT_CODE,
+        };
+        let dispatch = ReplyMessage::system(dispatch.id(), err_payload, exit_code).into_dispatch(
+            program_id,
+            dispatch.reply_destination(),
+            dispatch.id(),
+        );
 
         journal.push(JournalNote::SendDispatch {
             message_id,
             dispatch,
-            delay: 0,
+            delay: None,
+        });
+    }
+
+    // Value of the message resumed after waiting was transferred to the
+    // program, so the program is signaled to clean up after the message,
+    // e.g. to refund the value it held. The signal is paid with the gas left.
+    if exports_signal
+        && dispatch.context().is_some()
+        && matches!(dispatch.kind(), DispatchKind::Handle)
+    {
+        let signal = SignalMessage::with_code(message_id, SignalCode::ExecutionFailed)
+            .into_dispatch(program_id);
+
+        journal.push(JournalNote::SendDispatch {
+            message_id,
+            dispatch: signal,
+            delay: None,
         });
     }
 
@@ -352,6 +709,14 @@ fn process_success(
         dispatch,
         generated_dispatches,
         awakening,
+        recipient_charged,
+        value_reservations,
+        children_count,
+        counters,
+        scheduled_exit,
+        entry_budgets,
+        charge_allowance,
+        reply_waker,
         program_candidates,
         gas_amount,
         page_update,
@@ -388,6 +753,12 @@ fn process_success(
         });
     }
 
+    // Checked before generated dispatches are consumed: program exited
+    // during initialization replies with error only if it hasn't replied.
+    let replied = generated_dispatches
+        .iter()
+        .any(|(dispatch, _)| dispatch.reply().is_some());
+
     // Must be handled before handling generated dispatches.
     for (code_hash, candidates) in program_candidates {
         journal.push(JournalNote::StoreNewPrograms {
@@ -397,11 +768,19 @@ fn process_success(
     }
 
     for (dispatch, delay) in generated_dispatches {
-        journal.push(JournalNote::SendDispatch {
-            message_id,
-            dispatch,
-            delay,
-        });
+        if recipient_charged.contains(&dispatch.id()) {
+            journal.push(JournalNote::SendDispatchChargeRecipient {
+                message_id,
+                dispatch,
+                delay,
+            });
+        } else {
+            journal.push(JournalNote::SendDispatch {
+                message_id,
+                dispatch,
+                delay,
+            });
+        }
     }
 
     for (awakening_id, delay) in awakening {
@@ -428,6 +807,57 @@ fn process_success(
         });
     }
 
+    if let Some(reservations) = value_reservations {
+        journal.push(JournalNote::UpdateValueReservations {
+            program_id,
+            reservations,
+        });
+    }
+
+    if let Some(children_count) = children_count {
+        journal.push(JournalNote::UpdateChildrenCount {
+            program_id,
+            children_count,
+        });
+    }
+
+    if let Some(counters) = counters {
+        journal.push(JournalNote::UpdateCounters {
+            program_id,
+            counters,
+        });
+    }
+
+    if !entry_budgets.is_empty() {
+        journal.push(JournalNote::UpdateEntryBudgets {
+            program_id,
+            entry_budgets,
+        });
+    }
+
+    if let Some(allowance) = charge_allowance {
+        journal.push(JournalNote::UpdateChargeAllowance {
+            program_id,
+            allowance,
+        });
+    }
+
+    if let Some(reply_to) = reply_waker {
+        journal.push(JournalNote::UpdateReplyWaker {
+            program_id,
+            reply_to,
+            waiting: Some(message_id),
+        });
+    }
+
+    if let Some((block, inheritor)) = scheduled_exit {
+        journal.push(JournalNote::ScheduleExit {
+            program_id,
+            block,
+            inheritor,
+        });
+    }
+
     let outcome = match kind {
         Wait(duration) => {
             journal.push(JournalNote::WaitDispatch {
@@ -442,6 +872,31 @@ fn process_success(
             _ => DispatchOutcome::Success,
         },
         Exit(value_destination) => {
+            // Creator of the program exited during its initialization
+            // is notified, unless the program has already replied.
+            if dispatch.kind() == DispatchKind::Init && !replied {
+                let err = ExecutionErrorReason::InitExited {
+                    inheritor: value_destination,
+                };
+                // This expect panic is unreachable, unless error message is too large or max payload size is too small.
+                let err_payload = err.encode().try_into().expect("Error message is too large");
+                // # Safety
+                //
+                // 1. The dispatch.id() has already been checked
+                // 2. This reply message is generated by our system
+                //
+                // So, the message id of this reply message will not be duplicated.
+                let dispatch =
+                    ReplyMessage::system(message_id, err_payload, crate::INIT_EXITED_EXIT_CODE)
+                        .into_dispatch(program_id, origin, message_id);
+
+                journal.push(JournalNote::SendDispatch {
+                    message_id,
+                    dispatch,
+                    delay: None,
+                });
+            }
+
             journal.push(JournalNote::ExitDispatch {
                 id_exited: program_id,
                 value_destination,
@@ -516,14 +971,14 @@ fn process_non_executable(
         // So, the message id of this reply message will not be duplicated.
         let dispatch = ReplyMessage::system(dispatch.id(), err_payload, exit_code).into_dispatch(
             program_id,
-            dispatch.source(),
+            dispatch.reply_destination(),
             dispatch.id(),
         );
 
         journal.push(JournalNote::SendDispatch {
             message_id,
             dispatch,
-            delay: 0,
+            delay: None,
         });
     }
 
@@ -18,7 +18,12 @@
 
 //! Module for checked code.
 
-use crate::{ids::CodeId, memory::WasmPageNumber, message::DispatchKind};
+use crate::{
+    ids::CodeId,
+    memory::WasmPageNumber,
+    message::DispatchKind,
+    syscalls::{self, SysCallSignatureError},
+};
 use alloc::{collections::BTreeSet, vec::Vec};
 use codec::{Decode, Encode};
 use parity_wasm::elements::{Internal, Module};
@@ -81,6 +86,8 @@ pub enum CodeError {
     /// This might be due to program contained unsupported/non-deterministic instructions
     /// (floats, manual memory grow, etc.).
     GasInjection,
+    /// Error occurred during stack height instrumentation.
+    StackLimitInjection,
     /// Error occurred during encoding instrumented program.
     ///
     /// The only possible reason for that might be OOM.
@@ -89,6 +96,8 @@ pub enum CodeError {
     StartSectionExists,
     /// The provided code has invalid count of static pages.
     InvalidStaticPageCount,
+    /// The provided code imports a syscall with a wrong signature.
+    SyscallSignature(SysCallSignatureError),
 }
 
 /// Contains instrumented binary code of a program and initial memory size from memory import.
@@ -103,14 +112,20 @@ pub struct Code {
     static_pages: WasmPageNumber,
     #[codec(compact)]
     instruction_weights_version: u32,
+    /// Stack height limit the code is instrumented with.
+    stack_height: Option<u32>,
 }
 
 impl Code {
     /// Create the code by checking and instrumenting `original_code`.
+    ///
+    /// If `stack_height` is set, the code traps once the height of its stack
+    /// exceeds the limit.
     pub fn try_new<R, GetRulesFn>(
         raw_code: Vec<u8>,
         version: u32,
         mut get_gas_rules: GetRulesFn,
+        stack_height: Option<u32>,
     ) -> Result<Self, CodeError>
     where
         R: Rules,
@@ -144,14 +159,22 @@ impl Code {
             return Err(CodeError::InvalidStaticPageCount);
         }
 
+        syscalls::check_syscall_signatures(&module).map_err(CodeError::SyscallSignature)?;
+
         let exports = get_exports(&module, true)?;
 
         if exports.contains(&DispatchKind::Init) || exports.contains(&DispatchKind::Handle) {
             let gas_rules = get_gas_rules(&module);
-            let instrumented_module =
+            let mut instrumented_module =
                 wasm_instrument::gas_metering::inject(module, &gas_rules, "env")
                     .map_err(|_| CodeError::GasInjection)?;
 
+            if let Some(stack_height) = stack_height {
+                instrumented_module =
+                    wasm_instrument::inject_stack_limiter(instrumented_module, stack_height)
+                        .map_err(|_| CodeError::StackLimitInjection)?;
+            }
+
             let instrumented =
                 wasm_instrument::parity_wasm::elements::serialize(instrumented_module)
                     .map_err(|_| CodeError::Encode)?;
@@ -162,6 +185,7 @@ impl Code {
                 exports,
                 static_pages,
                 instruction_weights_version: version,
+                stack_height,
             })
         } else {
             Err(CodeError::RequiredExportFnNotFound)
@@ -217,6 +241,7 @@ impl Code {
                     exports,
                     static_pages,
                     instruction_weights_version: version,
+                    stack_height: None,
                 })
             } else {
                 Ok(Self {
@@ -225,6 +250,7 @@ impl Code {
                     exports,
                     static_pages,
                     instruction_weights_version: version,
+                    stack_height: None,
                 })
             }
         } else {
@@ -257,6 +283,11 @@ impl Code {
         self.static_pages
     }
 
+    /// Returns stack height limit the code is instrumented with.
+    pub fn stack_height(&self) -> Option<u32> {
+        self.stack_height
+    }
+
     /// Consumes this instance and returns the instrumented and raw binary codes.
     pub fn into_parts(self) -> (InstrumentedCode, Vec<u8>) {
         (
@@ -265,6 +296,7 @@ impl Code {
                 exports: self.exports,
                 static_pages: self.static_pages,
                 version: self.instruction_weights_version,
+                stack_height: self.stack_height,
             },
             self.raw_code,
         )
@@ -314,6 +346,7 @@ pub struct InstrumentedCode {
     exports: BTreeSet<DispatchKind>,
     static_pages: WasmPageNumber,
     version: u32,
+    stack_height: Option<u32>,
 }
 
 impl InstrumentedCode {
@@ -337,6 +370,11 @@ impl InstrumentedCode {
         self.static_pages
     }
 
+    /// Returns stack height limit the code is instrumented with.
+    pub fn stack_height(&self) -> Option<u32> {
+        self.stack_height
+    }
+
     /// Consumes the instance and returns the instrumented code.
     pub fn into_code(self) -> Vec<u8> {
         self.code
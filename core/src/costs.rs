@@ -124,6 +124,108 @@ pub struct HostFnWeights {
     /// Weight per payload byte by `gr_create_program_wgas`.
     pub gr_create_program_wgas_per_byte: u64,
 
+    /// Weight of calling `gr_new_handle`.
+    pub gr_new_handle: u64,
+
+    /// Weight of calling `gr_delayed_eta`.
+    pub gr_delayed_eta: u64,
+
+    /// Weight of calling `gr_reserve_value`.
+    pub gr_reserve_value: u64,
+
+    /// Weight of calling `gr_unreserve_value`.
+    pub gr_unreserve_value: u64,
+
+    /// Weight of calling `gr_context_limits`.
+    pub gr_context_limits: u64,
+
+    /// Weight of calling `gr_children_count`.
+    pub gr_children_count: u64,
+
+    /// Weight of calling `gr_bump_counter`.
+    pub gr_bump_counter: u64,
+
+    /// Weight of calling `gr_is_init`.
+    pub gr_is_init: u64,
+
+    /// Weight of calling `gr_signal_details`.
+    pub gr_signal_details: u64,
+
+    /// Weight of calling `gr_forward`.
+    pub gr_forward: u64,
+
+    /// Weight per payload byte by `gr_forward`.
+    pub gr_forward_per_byte: u64,
+
+    /// Weight of calling `gr_block_hash`.
+    pub gr_block_hash: u64,
+
+    /// Weight of calling `gr_stack_limit`.
+    pub gr_stack_limit: u64,
+
+    /// Weight of calling `gr_split_value_reservation`.
+    pub gr_split_value_reservation: u64,
+
+    /// Weight of calling `gr_tx_hash`.
+    pub gr_tx_hash: u64,
+
+    /// Weight of calling `gr_set_default_gas_limit`.
+    pub gr_set_default_gas_limit: u64,
+
+    /// Weight of calling `gr_max_payload_size`.
+    pub gr_max_payload_size: u64,
+
+    /// Weight of calling `gr_resumed_from_wait`.
+    pub gr_resumed_from_wait: u64,
+
+    /// Weight of calling `gr_children_gas_burned`.
+    pub gr_children_gas_burned: u64,
+
+    /// Weight of calling `gr_wait_if`.
+    pub gr_wait_if: u64,
+
+    /// Weight of calling `gr_source_code_id`.
+    pub gr_source_code_id: u64,
+
+    /// Weight of calling `gr_wait_budget_remaining`.
+    pub gr_wait_budget_remaining: u64,
+
+    /// Weight of calling `gr_spendable_value`.
+    pub gr_spendable_value: u64,
+
+    /// Weight of calling `gr_block_duration`.
+    pub gr_block_duration: u64,
+
+    /// Weight of calling `gr_schedule_exit`.
+    pub gr_schedule_exit: u64,
+
+    /// Weight of calling `gr_message_meta`.
+    pub gr_message_meta: u64,
+
+    /// Weight of calling `gr_gas_allowance`.
+    pub gr_gas_allowance: u64,
+
+    /// Weight of calling `gr_mailbox_contains`.
+    pub gr_mailbox_contains: u64,
+
+    /// Weight of calling `gr_codes_count`.
+    pub gr_codes_count: u64,
+
+    /// Weight of calling `gr_trace`.
+    pub gr_trace: u64,
+
+    /// Weight per data byte by `gr_trace`.
+    pub gr_trace_per_byte: u64,
+
+    /// Weight of calling `gr_execution_id`.
+    pub gr_execution_id: u64,
+
+    /// Weight of calling `gr_set_entry_budget`.
+    pub gr_set_entry_budget: u64,
+
+    /// Weight of calling `gr_set_charge_allowance`.
+    pub gr_set_charge_allowance: u64,
+
     /// Weight of calling `gas`.
     pub gas: u64,
 }
@@ -213,6 +315,70 @@ pub enum RuntimeCosts {
     Wake,
     /// Weight of calling `gr_create_program_wgas`.
     CreateProgram(u32),
+    /// Weight of calling `gr_new_handle`.
+    NewHandle,
+    /// Weight of calling `gr_delayed_eta`.
+    DelayedEta,
+    /// Weight of calling `gr_reserve_value`.
+    ReserveValue,
+    /// Weight of calling `gr_unreserve_value`.
+    UnreserveValue,
+    /// Weight of calling `gr_context_limits`.
+    ContextLimits,
+    /// Weight of calling `gr_children_count`.
+    ChildrenCount,
+    /// Weight of calling `gr_bump_counter`.
+    BumpCounter,
+    /// Weight of calling `gr_is_init`.
+    IsInit,
+    /// Weight of calling `gr_signal_details`.
+    SignalDetails,
+    /// Weight of calling `gr_forward`, taking in account payload length.
+    Forward(u32),
+    /// Weight of calling `gr_block_hash`.
+    BlockHash,
+    /// Weight of calling `gr_stack_limit`.
+    StackLimit,
+    /// Weight of calling `gr_split_value_reservation`.
+    SplitValueReservation,
+    /// Weight of calling `gr_tx_hash`.
+    TxHash,
+    /// Weight of calling `gr_set_default_gas_limit`.
+    SetDefaultGasLimit,
+    /// Weight of calling `gr_max_payload_size`.
+    MaxPayloadSize,
+    /// Weight of calling `gr_resumed_from_wait`.
+    ResumedFromWait,
+    /// Weight of calling `gr_children_gas_burned`.
+    ChildrenGasBurned,
+    /// Weight of calling `gr_wait_if`.
+    WaitIf,
+    /// Weight of calling `gr_source_code_id`.
+    SourceCodeId,
+    /// Weight of calling `gr_wait_budget_remaining`.
+    WaitBudgetRemaining,
+    /// Weight of calling `gr_spendable_value`.
+    SpendableValue,
+    /// Weight of calling `gr_block_duration`.
+    BlockDuration,
+    /// Weight of calling `gr_schedule_exit`.
+    ScheduleExit,
+    /// Weight of calling `gr_message_meta`.
+    MessageMeta,
+    /// Weight of calling `gr_gas_allowance`.
+    GasAllowance,
+    /// Weight of calling `gr_mailbox_contains`.
+    MailboxContains,
+    /// Weight of calling `gr_codes_count`.
+    CodesCount,
+    /// Weight of calling `gr_trace`, taking in account data length.
+    Trace(u32),
+    /// Weight of calling `gr_execution_id`.
+    ExecutionId,
+    /// Weight of calling `gr_set_entry_budget`.
+    SetEntryBudget,
+    /// Weight of calling `gr_set_charge_allowance`.
+    SetChargeAllowance,
 }
 
 impl RuntimeCosts {
@@ -260,6 +426,42 @@ impl RuntimeCosts {
             CreateProgram(len) => s
                 .gr_create_program_wgas
                 .saturating_add(s.gr_create_program_wgas_per_byte.saturating_mul(len.into())),
+            NewHandle => s.gr_new_handle,
+            DelayedEta => s.gr_delayed_eta,
+            ReserveValue => s.gr_reserve_value,
+            UnreserveValue => s.gr_unreserve_value,
+            ContextLimits => s.gr_context_limits,
+            ChildrenCount => s.gr_children_count,
+            BumpCounter => s.gr_bump_counter,
+            IsInit => s.gr_is_init,
+            SignalDetails => s.gr_signal_details,
+            Forward(len) => s
+                .gr_forward
+                .saturating_add(s.gr_forward_per_byte.saturating_mul(len.into())),
+            BlockHash => s.gr_block_hash,
+            StackLimit => s.gr_stack_limit,
+            SplitValueReservation => s.gr_split_value_reservation,
+            TxHash => s.gr_tx_hash,
+            SetDefaultGasLimit => s.gr_set_default_gas_limit,
+            MaxPayloadSize => s.gr_max_payload_size,
+            ResumedFromWait => s.gr_resumed_from_wait,
+            ChildrenGasBurned => s.gr_children_gas_burned,
+            WaitIf => s.gr_wait_if,
+            SourceCodeId => s.gr_source_code_id,
+            WaitBudgetRemaining => s.gr_wait_budget_remaining,
+            SpendableValue => s.gr_spendable_value,
+            BlockDuration => s.gr_block_duration,
+            ScheduleExit => s.gr_schedule_exit,
+            MessageMeta => s.gr_message_meta,
+            GasAllowance => s.gr_gas_allowance,
+            MailboxContains => s.gr_mailbox_contains,
+            CodesCount => s.gr_codes_count,
+            Trace(len) => s
+                .gr_trace
+                .saturating_add(s.gr_trace_per_byte.saturating_mul(len.into())),
+            ExecutionId => s.gr_execution_id,
+            SetEntryBudget => s.gr_set_entry_budget,
+            SetChargeAllowance => s.gr_set_charge_allowance,
         };
         RuntimeToken { weight }
     }
@@ -0,0 +1,83 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for program counters.
+
+use alloc::collections::BTreeMap;
+
+/// Key of the program counter.
+pub type CounterKey = [u8; 8];
+
+/// Persistent counters of the program.
+///
+/// Counters let programs generate unique ids for their own records without
+/// keeping a counter in memory. Changes are applied only if the execution
+/// succeeds, so ids of failed executions are reused.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramCounters {
+    initial: BTreeMap<CounterKey, u64>,
+    current: BTreeMap<CounterKey, u64>,
+}
+
+impl ProgramCounters {
+    /// New counters context from counters bumped in previous executions.
+    pub fn new(counters: BTreeMap<CounterKey, u64>) -> Self {
+        Self {
+            initial: counters.clone(),
+            current: counters,
+        }
+    }
+
+    /// Current value of the counter.
+    pub fn get(&self, key: &CounterKey) -> u64 {
+        self.current.get(key).copied().unwrap_or_default()
+    }
+
+    /// Increment the counter, returning its new value.
+    pub fn bump(&mut self, key: CounterKey) -> u64 {
+        let counter = self.current.entry(key).or_default();
+        *counter = counter.saturating_add(1);
+        *counter
+    }
+
+    /// Counters after execution, if they were changed.
+    pub fn into_changed(self) -> Option<BTreeMap<CounterKey, u64>> {
+        self.current.ne(&self.initial).then_some(self.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_monotonic() {
+        let mut counters = ProgramCounters::default();
+        assert_eq!(counters.get(b"orders\0\0"), 0);
+
+        assert_eq!(counters.bump(*b"orders\0\0"), 1);
+        assert_eq!(counters.bump(*b"orders\0\0"), 2);
+        assert_eq!(counters.bump(*b"tickets\0"), 1);
+        assert_eq!(counters.get(b"orders\0\0"), 2);
+
+        let previous = counters.into_changed().expect("Counters changed");
+        let mut counters = ProgramCounters::new(previous);
+        assert_eq!(counters.clone().into_changed(), None);
+        assert_eq!(counters.bump(*b"orders\0\0"), 3);
+    }
+}
@@ -20,12 +20,17 @@
 
 use crate::{
     costs::RuntimeCosts,
-    ids::{MessageId, ProgramId},
+    counters::CounterKey,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Memory, WasmPageNumber},
-    message::{ExitCode, HandlePacket, InitPacket, ReplyPacket},
+    message::{
+        ContextStoreLimits, DispatchKind, ExitCode, HandlePacket, InitPacket, ReplyPacket,
+        SignalCode,
+    },
 };
 use alloc::collections::BTreeSet;
 use codec::{Decode, Encode};
+use core::num::NonZeroU32;
 use gear_core_errors::CoreError;
 
 /// Page access rights.
@@ -59,6 +64,53 @@ pub trait Ext {
     /// Get the current block timestamp.
     fn block_timestamp(&mut self) -> Result<u64, Self::Error>;
 
+    /// Get the duration of a block in milliseconds.
+    fn block_duration(&mut self) -> Result<u64, Self::Error>;
+
+    /// Get hash of the block at the given height.
+    ///
+    /// Only a limited amount of blocks preceding the current one is
+    /// available. Hash of the current block isn't known during its
+    /// execution, so it's an error as well as a future height.
+    fn block_hash(&mut self, height: u32) -> Result<[u8; 32], Self::Error>;
+
+    /// Get hash of the external transaction the current message chain
+    /// originates from.
+    ///
+    /// It's an error if the chain wasn't initiated by a transaction,
+    /// e.g. during gas calculation.
+    fn tx_hash(&mut self) -> Result<[u8; 32], Self::Error>;
+
+    /// Get the maximum stack height the program is instrumented with.
+    ///
+    /// Execution traps once the height is exceeded. `None` means the stack
+    /// height isn't limited by instrumentation.
+    fn stack_limit(&mut self) -> Result<Option<u32>, Self::Error>;
+
+    /// Get the number of programs created by the program over its lifetime.
+    ///
+    /// Includes programs created during the current execution. Children
+    /// are counted once their creation is requested, regardless of whether
+    /// their initialization succeeds or they are terminated later.
+    fn children_count(&mut self) -> Result<u64, Self::Error>;
+
+    /// Get the total gas burned by initializations of programs
+    /// created by the program.
+    ///
+    /// Children are initialized after the execution that created them,
+    /// so only initializations finished before the current execution
+    /// are accounted.
+    fn children_gas_burned(&mut self) -> Result<u64, Self::Error>;
+
+    /// Get the number of distinct codes uploaded to the chain.
+    ///
+    /// The count is global: it covers codes uploaded by anyone, not only
+    /// codes of programs created by the current program.
+    fn codes_count(&mut self) -> Result<u64, Self::Error>;
+
+    /// Get limits of the message context stored while waiting.
+    fn context_limits(&mut self) -> Result<ContextStoreLimits, Self::Error>;
+
     /// Get the id of the user who initiated communication with blockchain,
     /// during which, currently processing message was created.
     fn origin(&mut self) -> Result<ProgramId, Self::Error>;
@@ -74,23 +126,108 @@ pub trait Ext {
         &mut self,
         handle: usize,
         msg: HandlePacket,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     ) -> Result<MessageId, Self::Error>;
 
     /// Send message to another program.
-    fn send(&mut self, msg: HandlePacket, delay: u32) -> Result<MessageId, Self::Error> {
+    fn send(
+        &mut self,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
         let handle = self.send_init()?;
         self.send_commit(handle, msg, delay)
     }
 
+    /// Complete message and send it to another program, charging
+    /// the recipient for the gas of its processing.
+    fn send_commit_charge_recipient(
+        &mut self,
+        handle: usize,
+        msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error>;
+
+    /// Send message to another program, which pays for its processing.
+    fn send_charge_recipient(&mut self, msg: HandlePacket) -> Result<MessageId, Self::Error> {
+        let handle = self.send_init()?;
+        self.send_commit_charge_recipient(handle, msg)
+    }
+
+    /// Set the total amount of gas the program agrees to pay for messages
+    /// sent to it with [`Ext::send_charge_recipient`].
+    ///
+    /// The allowance replaces the previous one and is decreased by the gas
+    /// limit of every message paid. Zero allowance refuses such messages.
+    fn set_charge_allowance(&mut self, allowance: u64) -> Result<(), Self::Error>;
+
+    /// Complete message and send it to another program, drawing
+    /// its value from the value reservation.
+    fn reservation_send_commit(
+        &mut self,
+        id: ReservationId,
+        handle: usize,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error>;
+
+    /// Send message to another program, drawing its value from the value reservation.
+    fn reservation_send(
+        &mut self,
+        id: ReservationId,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        let handle = self.send_init()?;
+        self.reservation_send_commit(id, handle, msg, delay)
+    }
+
+    /// Reserve `amount` of value and send message from the reservation at once.
+    ///
+    /// Nothing is reserved if the message can't be sent, so the program is
+    /// never left with a reservation it hasn't used, and the error of the
+    /// send is returned. The reservation is removed once the message spends
+    /// all of its value.
+    fn reserve_and_send(
+        &mut self,
+        amount: u128,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<(ReservationId, MessageId), Self::Error>;
+
+    /// Send the payload of the current message to another program as is,
+    /// without reading it into the program memory.
+    ///
+    /// The forwarded message is sent by the program itself, so replies to it
+    /// come back to the program rather than to the source of the current message.
+    fn forward(
+        &mut self,
+        destination: ProgramId,
+        value: u128,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error>;
+
+    /// Set the gas limit of messages sent and replied by the program
+    /// without a specified one.
+    ///
+    /// Only affects messages sent after the call within the current execution.
+    fn set_default_gas_limit(&mut self, gas_limit: u64) -> Result<(), Self::Error>;
+
     /// Push an extra buffer into reply message.
     fn reply_push(&mut self, buffer: &[u8]) -> Result<(), Self::Error>;
 
     /// Complete reply message and send it to source program.
-    fn reply_commit(&mut self, msg: ReplyPacket, delay: u32) -> Result<MessageId, Self::Error>;
+    fn reply_commit(
+        &mut self,
+        msg: ReplyPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error>;
 
     /// Produce reply to the current message.
-    fn reply(&mut self, msg: ReplyPacket, delay: u32) -> Result<MessageId, Self::Error> {
+    fn reply(
+        &mut self,
+        msg: ReplyPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
         self.reply_commit(msg, delay)
     }
 
@@ -100,15 +237,48 @@ pub trait Ext {
     /// Get the source of the message currently being handled.
     fn source(&mut self) -> Result<ProgramId, Self::Error>;
 
+    /// Get the code id of the program sent the message currently being handled.
+    ///
+    /// It's an error if the message was sent by a user.
+    fn source_code_id(&mut self) -> Result<CodeId, Self::Error>;
+
     /// Terminate the program and transfer all available value to the address.
     fn exit(&mut self) -> Result<(), Self::Error>;
 
+    /// Schedule termination of the program at the given future block,
+    /// transferring all its value to the `inheritor` then.
+    ///
+    /// The exit isn't a delayed message, so it's neither reported by
+    /// [`Ext::delayed_eta`] nor cancellable. The rent for keeping it till
+    /// the block is charged from the gas counter. Scheduling the exit again
+    /// replaces the previous one, whose rent isn't returned.
+    fn schedule_exit(&mut self, block: u32, inheritor: ProgramId) -> Result<(), Self::Error>;
+
+    /// Limit gas available to the executions of the given entry point.
+    ///
+    /// Entry points are numbered as 1 (`handle`), 2 (`handle_reply`) and
+    /// 3 (`handle_signal`), other ones can't be limited. The budget takes
+    /// effect for the next executions once the current one succeeds.
+    fn set_entry_budget(&mut self, entry: u32, gas: u64) -> Result<(), Self::Error>;
+
     /// Get the exit code of the message being processed.
     fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error>;
 
+    /// Get the id of the message the signal is sent about and the signal code.
+    ///
+    /// Fails unless the signal entry point is executed.
+    fn signal_details(&mut self) -> Result<(MessageId, SignalCode), Self::Error>;
+
     /// Get the id of the message currently being handled.
     fn message_id(&mut self) -> Result<MessageId, Self::Error>;
 
+    /// Get identifier of the current execution of the message.
+    ///
+    /// Identifiers are assigned by the runtime in increasing order, one per
+    /// execution. Unlike the message id, it changes each time the message
+    /// is executed again after being woken.
+    fn execution_id(&mut self) -> Result<u64, Self::Error>;
+
     /// Get the id of program itself
     fn program_id(&mut self) -> Result<ProgramId, Self::Error>;
 
@@ -123,15 +293,47 @@ pub trait Ext {
     /// This should be no-op in release builds.
     fn debug(&mut self, data: &str) -> Result<(), Self::Error>;
 
+    /// Log the trace of the given level along with ids of the program
+    /// and the message.
+    ///
+    /// Levels are numbered from 1 (error) to 5 (trace), other ones are
+    /// never logged. The trace is charged the same whether the node logs
+    /// it or not, since log settings differ between nodes.
+    fn trace(&mut self, level: u32, data: &str) -> Result<(), Self::Error>;
+
     /// Interrupt the program, saving it's state.
     fn leave(&mut self) -> Result<(), Self::Error>;
 
     /// Access currently handled message payload.
     fn read(&mut self) -> Result<&[u8], Self::Error>;
 
+    /// Same as [`Ext::read`], but charges only for `len` bytes of the
+    /// payload, which are copied by the caller.
+    fn read_part(&mut self, len: usize) -> Result<&[u8], Self::Error>;
+
     /// Size of currently handled message payload.
     fn size(&mut self) -> Result<usize, Self::Error>;
 
+    /// Access currently handled message metadata set by its sender.
+    fn message_meta(&mut self) -> Result<&[u8], Self::Error>;
+
+    /// Tell whether the message sent by the program is still in the mailbox
+    /// of the user, i.e. it wasn't claimed, replied or removed on timeout yet.
+    ///
+    /// Messages sent by other actors are reported as absent.
+    fn mailbox_contains(
+        &mut self,
+        user_id: ProgramId,
+        message_id: MessageId,
+    ) -> Result<bool, Self::Error>;
+
+    /// Fail reading the record with given `index`, since the payload
+    /// consists of `count` records only.
+    fn record_not_found(&mut self, index: u32, count: u32) -> Result<(), Self::Error>;
+
+    /// Max size of a message payload.
+    fn max_payload_size(&mut self) -> Result<u32, Self::Error>;
+
     /// Default gas host call.
     fn gas(&mut self, amount: u32) -> Result<(), Self::Error>;
 
@@ -147,15 +349,39 @@ pub trait Ext {
     /// Tell how much gas is left in running context.
     fn gas_available(&mut self) -> Result<u64, Self::Error>;
 
+    /// Tell how much gas is left in the block-level allowance.
+    ///
+    /// Unlike [`Ext::gas_available`] it's shared by all messages of the block,
+    /// so running out of it postpones the message instead of failing it.
+    fn gas_allowance(&mut self) -> Result<u64, Self::Error>;
+
+    /// Tell whether the program is being initialized, i.e. the `init`
+    /// entry point is executed.
+    fn is_init(&mut self) -> Result<bool, Self::Error>;
+
+    /// Tell whether the message is executed again after it was put
+    /// to wait.
+    fn resumed_from_wait(&mut self) -> Result<bool, Self::Error>;
+
     /// Value associated with message.
     fn value(&mut self) -> Result<u128, Self::Error>;
 
     /// Tell how much value is left in running context.
     fn value_available(&mut self) -> Result<u128, Self::Error>;
 
+    /// Tell how much value can still be attached to outgoing messages.
+    ///
+    /// Unlike [`Ext::value_available`] it's zero once the value left is
+    /// below the existential deposit, as no non-zero value can be sent then.
+    fn spendable_value(&mut self) -> Result<u128, Self::Error>;
+
     /// Interrupt the program and reschedule execution for maximum.
     fn wait(&mut self) -> Result<(), Self::Error>;
 
+    /// Same as [`Ext::wait`], but the message is woken by the reply
+    /// to the sent message with `message_id`.
+    fn wait_for_reply(&mut self, message_id: MessageId) -> Result<(), Self::Error>;
+
     /// Interrupt the program and reschedule execution in duration.
     fn wait_for(&mut self, duration: u32) -> Result<(), Self::Error>;
 
@@ -163,12 +389,68 @@ pub trait Ext {
     /// but not more than duration.
     fn wait_up_to(&mut self, duration: u32) -> Result<(), Self::Error>;
 
+    /// Get the number of blocks the message can still afford to wait.
+    ///
+    /// Waitlist rent is charged per block from the gas left, so the budget
+    /// shrinks as execution proceeds and after every wake.
+    fn wait_budget_remaining(&mut self) -> Result<u32, Self::Error>;
+
+    /// Interrupt the program and reschedule execution, if `condition` is true.
+    ///
+    /// Does nothing besides charging for the call otherwise.
+    fn wait_if(&mut self, condition: bool) -> Result<(), Self::Error>;
+
     /// Wake the waiting message and move it to the processing queue.
-    fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Self::Error>;
+    fn wake(&mut self, waker_id: MessageId, delay: Option<NonZeroU32>) -> Result<(), Self::Error>;
 
-    /// Send init message to create a new program
-    fn create_program(&mut self, packet: InitPacket, delay: u32) -> Result<ProgramId, Self::Error>;
+    /// Send init message to create a new program.
+    ///
+    /// Returns id of the init message along with id of the new program.
+    fn create_program(
+        &mut self,
+        packet: InitPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<(MessageId, ProgramId), Self::Error>;
+
+    /// Obtain a fresh handle for correlating program's own async operations.
+    ///
+    /// Handles are unique and monotonic within the processing of the current
+    /// message, including its resumptions after `wait`, and don't intersect
+    /// with send handles.
+    fn new_handle(&mut self) -> Result<u32, Self::Error>;
+
+    /// Get the number of blocks until the message, delayed
+    /// by the current program, will be dispatched.
+    fn delayed_eta(&mut self, message_id: MessageId) -> Result<u32, Self::Error>;
+
+    /// Reserve value of the program's balance, so it can't be spent
+    /// other than by the messages sent from the reservation.
+    fn reserve_value(&mut self, value: u128) -> Result<ReservationId, Self::Error>;
+
+    /// Cancel the value reservation, returning the value it kept.
+    fn unreserve_value(&mut self, id: ReservationId) -> Result<u128, Self::Error>;
+
+    /// Move `value` from the value reservation into the new one,
+    /// returning id of the latter.
+    fn split_value_reservation(
+        &mut self,
+        id: ReservationId,
+        value: u128,
+    ) -> Result<ReservationId, Self::Error>;
+
+    /// Increment the program's persistent counter with the given key,
+    /// returning its new value.
+    ///
+    /// Counters start from zero and keep their values between executions,
+    /// unless the execution fails.
+    fn bump_counter(&mut self, key: CounterKey) -> Result<u64, Self::Error>;
 
     /// Return the set of functions that are forbidden to be called.
     fn forbidden_funcs(&self) -> &BTreeSet<&'static str>;
+
+    /// Return the kind of the dispatch being processed.
+    ///
+    /// Unlike the syscalls, it's neither charged nor sets the last error,
+    /// so the backend may use it for its own needs.
+    fn dispatch_kind(&self) -> DispatchKind;
 }
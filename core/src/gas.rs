@@ -173,6 +173,15 @@ impl From<GasCounter> for GasAmount {
     }
 }
 
+impl From<GasAmount> for GasCounter {
+    /// Resume counting of the gas after execution.
+    fn from(gas_amount: GasAmount) -> Self {
+        let GasAmount { left, burned } = gas_amount;
+
+        Self { left, burned }
+    }
+}
+
 /// Value counter with some predefined maximum value.
 #[derive(Debug)]
 pub struct ValueCounter(u128);
@@ -198,6 +207,14 @@ impl ValueCounter {
         }
     }
 
+    /// Increase value by `amount`.
+    ///
+    /// Called when value reservation is cancelled, so the reserved value
+    /// becomes available again.
+    pub fn increase(&mut self, amount: u128) {
+        self.0 = self.0.saturating_add(amount);
+    }
+
     /// Report how much value is left.
     pub fn left(&self) -> u128 {
         self.0
@@ -258,6 +275,11 @@ impl GasAllowanceCounter {
 
         self.0 = new_value.unwrap_or(u128::MAX);
     }
+
+    /// Report how much gas is left, saturated to `u64::MAX`.
+    pub fn left(&self) -> u64 {
+        self.0.try_into().unwrap_or(u64::MAX)
+    }
 }
 
 #[cfg(test)]
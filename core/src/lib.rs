@@ -29,11 +29,14 @@ extern crate alloc;
 
 pub mod code;
 pub mod costs;
+pub mod counters;
 pub mod env;
 pub mod gas;
 pub mod ids;
 pub mod memory;
 pub mod message;
 pub mod program;
+pub mod reservation;
+pub mod syscalls;
 
 pub mod buffer;
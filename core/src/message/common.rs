@@ -18,7 +18,10 @@
 
 use crate::{
     ids::{MessageId, ProgramId},
-    message::{DispatchKind, ExitCode, GasLimit, Payload, StoredDispatch, StoredMessage, Value},
+    message::{
+        DispatchKind, ExitCode, GasLimit, Metadata, Payload, ReplyCode, StoredDispatch,
+        StoredMessage, Value,
+    },
 };
 use alloc::string::ToString;
 use codec::{Decode, Encode};
@@ -43,6 +46,10 @@ pub struct Message {
     value: Value,
     /// Message id replied on with exit code.
     reply: Option<ReplyDetails>,
+    /// Message metadata.
+    metadata: Metadata,
+    /// Program replies are delivered to instead of the source, if set.
+    reply_destination: Option<ProgramId>,
 }
 
 impl From<Message> for StoredMessage {
@@ -55,6 +62,8 @@ impl From<Message> for StoredMessage {
             message.value,
             message.reply,
         )
+        .with_metadata(message.metadata)
+        .with_reply_destination(message.reply_destination)
     }
 }
 
@@ -77,6 +86,21 @@ impl Message {
             gas_limit,
             value,
             reply,
+            metadata: Default::default(),
+            reply_destination: None,
+        }
+    }
+
+    /// Set metadata of the message.
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Set program replies are delivered to instead of the source.
+    pub fn with_reply_destination(self, reply_destination: Option<ProgramId>) -> Self {
+        Self {
+            reply_destination,
+            ..self
         }
     }
 
@@ -120,6 +144,18 @@ impl Message {
         self.reply
     }
 
+    /// Message metadata reference.
+    pub fn metadata(&self) -> &[u8] {
+        self.metadata.get()
+    }
+
+    /// Destination of replies on the message.
+    ///
+    /// The source, unless overridden by the sender.
+    pub fn reply_destination(&self) -> ProgramId {
+        self.reply_destination.unwrap_or(self.source)
+    }
+
     /// Check if this message is reply.
     pub fn is_reply(&self) -> bool {
         self.reply.is_some()
@@ -151,9 +187,9 @@ impl Message {
         }
     }
 
-    /// Returns bool defining if message is error reply.
+    /// Returns bool defining if message is error reply generated by the system.
     pub fn is_error_reply(&self) -> bool {
-        !matches!(self.exit_code(), Some(0) | None)
+        matches!(self.exit_code(), Some(code) if ReplyCode::from(code).is_system_error())
     }
 }
 
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    ids::{MessageId, ProgramId},
+    ids::{MessageId, ProgramId, ReservationId},
     message::{
         Dispatch, HandleMessage, HandlePacket, IncomingMessage, InitMessage, InitPacket, Payload,
         ReplyMessage, ReplyPacket,
@@ -28,11 +28,42 @@ use alloc::{
     vec::Vec,
 };
 use codec::{Decode, Encode};
-use gear_core_errors::MessageError as Error;
+use core::num::NonZeroU32;
+use gear_core_errors::{MessageError as Error, WaitError};
 use scale_info::TypeInfo;
 
 pub const OUTGOING_LIMIT: u32 = 1024;
 
+/// Default limit of outgoing handles stored while waiting.
+pub const STORED_HANDLES_LIMIT: u32 = 256;
+
+/// Default limit of program and message ids stored while waiting.
+pub const STORED_IDS_LIMIT: u32 = 1024;
+
+/// Default limit of payload bytes stored while waiting.
+pub const STORED_PAYLOAD_LIMIT: u32 = 64 * 1024;
+
+/// Limits of the context store kept with a waiting dispatch.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
+pub struct ContextStoreLimits {
+    /// Limit of outgoing handles.
+    pub handles: u32,
+    /// Limit of initialized program ids and awaken message ids.
+    pub ids: u32,
+    /// Limit of bytes in payloads of outgoing handles and reply.
+    pub payload_bytes: u32,
+}
+
+impl Default for ContextStoreLimits {
+    fn default() -> Self {
+        Self {
+            handles: STORED_HANDLES_LIMIT,
+            ids: STORED_IDS_LIMIT,
+            payload_bytes: STORED_PAYLOAD_LIMIT,
+        }
+    }
+}
+
 /// Context settings.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo)]
 pub struct ContextSettings {
@@ -40,6 +71,8 @@ pub struct ContextSettings {
     sending_fee: u64,
     /// Limit of outgoing messages that program can send during execution of current message.
     outgoing_limit: u32,
+    /// Limits of the context store kept while waiting.
+    store_limits: ContextStoreLimits,
 }
 
 impl ContextSettings {
@@ -48,8 +81,15 @@ impl ContextSettings {
         Self {
             sending_fee,
             outgoing_limit,
+            store_limits: Default::default(),
         }
     }
+
+    /// Set limits of the context store kept while waiting.
+    pub fn with_store_limits(mut self, store_limits: ContextStoreLimits) -> Self {
+        self.store_limits = store_limits;
+        self
+    }
 }
 
 impl Default for ContextSettings {
@@ -58,7 +98,7 @@ impl Default for ContextSettings {
     }
 }
 
-type WithDelay<T> = (T, u32);
+type WithDelay<T> = (T, Option<NonZeroU32>);
 
 /// Context outcome.
 ///
@@ -69,6 +109,7 @@ pub struct ContextOutcome {
     handle: Vec<WithDelay<HandleMessage>>,
     reply: Option<WithDelay<ReplyMessage>>,
     awakening: Vec<WithDelay<MessageId>>,
+    recipient_charged: BTreeSet<MessageId>,
     // Additional information section.
     program_id: ProgramId,
     source: ProgramId,
@@ -86,8 +127,15 @@ impl ContextOutcome {
         }
     }
 
-    /// Destructs outcome after execution and returns provided dispatches and awaken message ids.
-    pub fn drain(self) -> (Vec<WithDelay<Dispatch>>, Vec<WithDelay<MessageId>>) {
+    /// Destructs outcome after execution and returns provided dispatches, awaken message ids
+    /// and ids of dispatches, which gas should be paid by their recipients.
+    pub fn drain(
+        self,
+    ) -> (
+        Vec<WithDelay<Dispatch>>,
+        Vec<WithDelay<MessageId>>,
+        BTreeSet<MessageId>,
+    ) {
         let mut dispatches = Vec::new();
 
         for (msg, delay) in self.init.into_iter() {
@@ -105,7 +153,7 @@ impl ContextOutcome {
             ));
         };
 
-        (dispatches, self.awakening)
+        (dispatches, self.awakening, self.recipient_charged)
     }
 }
 
@@ -117,6 +165,8 @@ pub struct ContextStore {
     initialized: BTreeSet<ProgramId>,
     awaken: BTreeSet<MessageId>,
     reply_sent: bool,
+    handles_counter: u32,
+    reservation_nonce: u64,
 }
 
 /// Context of currently processing incoming message.
@@ -126,6 +176,7 @@ pub struct MessageContext {
     outcome: ContextOutcome,
     store: ContextStore,
     settings: ContextSettings,
+    resumed: bool,
 }
 
 impl MessageContext {
@@ -146,8 +197,9 @@ impl MessageContext {
         settings: ContextSettings,
     ) -> Self {
         Self {
-            outcome: ContextOutcome::new(program_id, message.source(), message.id()),
+            outcome: ContextOutcome::new(program_id, message.reply_destination(), message.id()),
             current: message,
+            resumed: store.is_some(),
             store: store.unwrap_or_default(),
             settings,
         }
@@ -160,7 +212,7 @@ impl MessageContext {
     pub fn init_program(
         &mut self,
         packet: InitPacket,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     ) -> Result<(ProgramId, MessageId), Error> {
         let program_id = packet.destination();
 
@@ -192,7 +244,7 @@ impl MessageContext {
         &mut self,
         handle: u32,
         packet: HandlePacket,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     ) -> Result<MessageId, Error> {
         if let Some(payload) = self.store.outgoing.get_mut(&handle) {
             if let Some(data) = payload.take() {
@@ -218,6 +270,23 @@ impl MessageContext {
         }
     }
 
+    /// Send a new message, which gas is paid by its recipient.
+    ///
+    /// Works the same as [`send_commit`](Self::send_commit), but marks
+    /// generated message to be charged from the recipient's balance.
+    /// Such messages can't be delayed and must have gas limit.
+    pub fn send_commit_charge_recipient(
+        &mut self,
+        handle: u32,
+        packet: HandlePacket,
+    ) -> Result<MessageId, Error> {
+        let message_id = self.send_commit(handle, packet, None)?;
+
+        self.outcome.recipient_charged.insert(message_id);
+
+        Ok(message_id)
+    }
+
     /// Provide space for storing payload for future message creation.
     ///
     /// Returns it's handle.
@@ -233,6 +302,30 @@ impl MessageContext {
         }
     }
 
+    /// Drops the handle provided by the last [`send_init`](Self::send_init)
+    /// of the execution, which message wasn't sent, so it doesn't count
+    /// toward the limit.
+    pub fn send_rollback(&mut self, handle: u32) -> Result<(), Error> {
+        if !self.store.outgoing.contains_key(&handle)
+            || handle + 1 != self.store.outgoing.len() as u32
+        {
+            return Err(Error::OutOfBounds);
+        }
+
+        let message_id = MessageId::generate_outgoing(self.current.id(), handle);
+        if self
+            .outcome
+            .handle
+            .iter()
+            .any(|(message, _)| message.id() == message_id)
+        {
+            return Err(Error::LateAccess);
+        }
+
+        self.store.outgoing.remove(&handle);
+        Ok(())
+    }
+
     /// Pushes payload into stored payload by handle.
     pub fn send_push(&mut self, handle: u32, buffer: &[u8]) -> Result<(), Error> {
         match self.store.outgoing.get_mut(&handle) {
@@ -250,7 +343,11 @@ impl MessageContext {
     ///
     /// Generates reply from provided data packet and stored reply payload.
     /// Returns message id.
-    pub fn reply_commit(&mut self, packet: ReplyPacket, delay: u32) -> Result<MessageId, Error> {
+    pub fn reply_commit(
+        &mut self,
+        packet: ReplyPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Error> {
         if !self.store.reply_sent {
             let data = self.store.reply.take().unwrap_or_default();
 
@@ -293,7 +390,7 @@ impl MessageContext {
     }
 
     /// Wake message by it's message id.
-    pub fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Error> {
+    pub fn wake(&mut self, waker_id: MessageId, delay: Option<NonZeroU32>) -> Result<(), Error> {
         if self.store.awaken.insert(waker_id) {
             self.outcome.awakening.push((waker_id, delay));
 
@@ -303,11 +400,74 @@ impl MessageContext {
         }
     }
 
+    /// Obtain a new handle for correlating program's async operations.
+    ///
+    /// Handles are monotonic within the current message, as the counter is
+    /// kept in the context store and survives waits.
+    pub fn new_handle(&mut self) -> Result<u32, Error> {
+        let handle = self.store.handles_counter;
+
+        self.store.handles_counter = handle.checked_add(1).ok_or(Error::LimitExceeded)?;
+
+        Ok(handle)
+    }
+
+    /// Obtain an id for the new value reservation.
+    ///
+    /// Ids are never repeated within the current message, as the nonce
+    /// is kept in the context store and survives waits.
+    pub fn reservation_id(&mut self) -> Result<ReservationId, Error> {
+        let nonce = self.store.reservation_nonce;
+
+        self.store.reservation_nonce = nonce.checked_add(1).ok_or(Error::LimitExceeded)?;
+
+        Ok(ReservationId::generate(self.current.id(), nonce))
+    }
+
+    /// Limits of the context store kept while waiting.
+    pub fn store_limits(&self) -> ContextStoreLimits {
+        self.settings.store_limits
+    }
+
+    /// Check the context store fits its limits to be kept while waiting.
+    pub fn check_store_limits(&self) -> Result<(), WaitError> {
+        let ContextStoreLimits {
+            handles,
+            ids,
+            payload_bytes,
+        } = self.settings.store_limits;
+        let store = &self.store;
+
+        let stored_ids = store.initialized.len().saturating_add(store.awaken.len());
+        let stored_bytes = store
+            .outgoing
+            .values()
+            .flatten()
+            .chain(store.reply.iter())
+            .fold(0usize, |bytes, payload| {
+                bytes.saturating_add(payload.get().len())
+            });
+
+        if store.outgoing.len() > handles as usize
+            || stored_ids > ids as usize
+            || stored_bytes > payload_bytes as usize
+        {
+            return Err(WaitError::ContextTooLarge);
+        }
+
+        Ok(())
+    }
+
     /// Current processing incoming message.
     pub fn current(&self) -> &IncomingMessage {
         &self.current
     }
 
+    /// Whether the message is executed again after waiting.
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+
     /// Current program's id.
     pub fn program_id(&self) -> ProgramId {
         self.outcome.program_id
@@ -362,11 +522,11 @@ mod tests {
         let mut message_context =
             MessageContext::new(Default::default(), Default::default(), Default::default());
         // first init to default ProgramId.
-        assert_ok!(message_context.init_program(Default::default(), 0));
+        assert_ok!(message_context.init_program(Default::default(), None));
 
         // second init to same default ProgramId should get error.
         assert_err!(
-            message_context.init_program(Default::default(), 0),
+            message_context.init_program(Default::default(), None),
             Error::DuplicateInit,
         );
     }
@@ -393,7 +553,7 @@ mod tests {
                     .send_push(handle, b"payload")
                     .expect("unreachable");
                 message_context
-                    .send_commit(handle, HandlePacket::default(), 0)
+                    .send_commit(handle, HandlePacket::default(), None)
                     .expect("unreachable");
             }
             // n + 1 should get first error.
@@ -401,7 +561,7 @@ mod tests {
             assert_eq!(limit_exceeded, Err(Error::LimitExceeded));
 
             // we can't send messages in this MessageContext.
-            let limit_exceeded = message_context.init_program(Default::default(), 0);
+            let limit_exceeded = message_context.init_program(Default::default(), None);
             assert_eq!(limit_exceeded, Err(Error::LimitExceeded));
         }
     }
@@ -412,7 +572,7 @@ mod tests {
             MessageContext::new(Default::default(), Default::default(), Default::default());
 
         // Use invalid handle 0.
-        let out_of_bounds = message_context.send_commit(0, Default::default(), 0);
+        let out_of_bounds = message_context.send_commit(0, Default::default(), None);
         assert_eq!(out_of_bounds, Err(Error::OutOfBounds));
 
         // make 0 valid.
@@ -420,26 +580,100 @@ mod tests {
         assert_eq!(valid_handle, 0);
 
         // Use valid handle 0.
-        assert_ok!(message_context.send_commit(0, Default::default(), 0));
+        assert_ok!(message_context.send_commit(0, Default::default(), None));
 
         // Use invalid handle 42.
         assert_err!(
-            message_context.send_commit(42, Default::default(), 0),
+            message_context.send_commit(42, Default::default(), None),
             Error::OutOfBounds,
         );
     }
 
+    #[test]
+    fn send_rollback_frees_handle() {
+        let settings = ContextSettings::new(0, 2);
+        let mut message_context = MessageContext::new_with_settings(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            settings,
+        );
+
+        assert_ok!(message_context.send_init(), 0);
+        assert_ok!(message_context.send_commit(0, Default::default(), None));
+
+        // Committed message can't be rolled back.
+        assert_err!(message_context.send_rollback(0), Error::LateAccess);
+
+        assert_ok!(message_context.send_init(), 1);
+        assert_ok!(message_context.send_rollback(1));
+        assert_err!(message_context.send_rollback(1), Error::OutOfBounds);
+
+        // The dropped handle doesn't count toward the limit.
+        assert_ok!(message_context.send_init(), 1);
+        assert_err!(message_context.send_init(), Error::LimitExceeded);
+    }
+
+    #[test]
+    fn new_handles_are_monotonic() {
+        let mut message_context =
+            MessageContext::new(Default::default(), Default::default(), Default::default());
+
+        assert_ok!(message_context.new_handle(), 0);
+        assert_ok!(message_context.new_handle(), 1);
+
+        // Send handles don't intersect with correlation handles.
+        assert_eq!(message_context.send_init(), Ok(0));
+
+        // Counter survives through the stored context.
+        let (_, store) = message_context.drain();
+        let mut message_context =
+            MessageContext::new(Default::default(), Default::default(), Some(store));
+
+        assert_ok!(message_context.new_handle(), 2);
+    }
+
+    #[test]
+    fn recipient_charged_marked_on_drain() {
+        let mut message_context =
+            MessageContext::new(Default::default(), Default::default(), Default::default());
+
+        let handle = message_context.send_init().expect("unreachable");
+        let common_id = message_context
+            .send_commit(handle, Default::default(), None)
+            .expect("unreachable");
+
+        let handle = message_context.send_init().expect("unreachable");
+        let charged_id = message_context
+            .send_commit_charge_recipient(handle, Default::default())
+            .expect("unreachable");
+
+        // Failed commit doesn't mark anything.
+        assert_err!(
+            message_context.send_commit_charge_recipient(handle, Default::default()),
+            Error::LateAccess,
+        );
+
+        let (outcome, _) = message_context.drain();
+        let (dispatches, _, recipient_charged) = outcome.drain();
+
+        assert_eq!(dispatches.len(), 2);
+        assert!(!recipient_charged.contains(&common_id));
+        assert!(recipient_charged.contains(&charged_id));
+        assert_eq!(recipient_charged.len(), 1);
+    }
+
     #[test]
     fn double_reply() {
         let mut message_context =
             MessageContext::new(Default::default(), Default::default(), Default::default());
 
         // First reply.
-        assert_ok!(message_context.reply_commit(Default::default(), 0));
+        assert_ok!(message_context.reply_commit(Default::default(), None));
 
         // Reply twice in one message is forbidden.
         assert_err!(
-            message_context.reply_commit(Default::default(), 0),
+            message_context.reply_commit(Default::default(), None),
             Error::DuplicateReply,
         );
     }
@@ -480,7 +714,7 @@ mod tests {
         assert_ok!(context.reply_push(&[1, 2, 3]));
 
         // Setting reply message and making sure the operation was successful
-        assert_ok!(context.reply_commit(reply_packet.clone(), 0));
+        assert_ok!(context.reply_commit(reply_packet.clone(), None));
 
         // Checking that the `ReplyMessage` matches the passed one
         assert_eq!(
@@ -496,7 +730,10 @@ mod tests {
         );
 
         // Checking that repeated call `reply_commit(...)` returns error and does not
-        assert_err!(context.reply_commit(reply_packet, 0), Error::DuplicateReply);
+        assert_err!(
+            context.reply_commit(reply_packet, None),
+            Error::DuplicateReply
+        );
 
         // Checking that at this point vector of outgoing messages is empty
         assert!(context.outcome.handle.is_empty());
@@ -527,7 +764,7 @@ mod tests {
         let commit_packet = HandlePacket::default();
 
         // Checking if commit is successful
-        assert_ok!(context.send_commit(expected_handle, commit_packet, 0));
+        assert_ok!(context.send_commit(expected_handle, commit_packet, None));
 
         // Checking that we are **NOT** able to push payload for the message or
         // commit it if we already committed it or directly pushed before
@@ -536,7 +773,7 @@ mod tests {
             Error::LateAccess,
         );
         assert_err!(
-            context.send_commit(expected_handle, HandlePacket::default(), 0),
+            context.send_commit(expected_handle, HandlePacket::default(), None),
             Error::LateAccess,
         );
 
@@ -547,7 +784,7 @@ mod tests {
         // to commit or send a non-existent message
         assert_err!(context.send_push(expected_handle, &[0]), Error::OutOfBounds);
         assert_err!(
-            context.send_commit(expected_handle, HandlePacket::default(), 0),
+            context.send_commit(expected_handle, HandlePacket::default(), None),
             Error::OutOfBounds,
         );
 
@@ -573,4 +810,104 @@ mod tests {
         assert_eq!(expected_result.handle.len(), 1);
         assert_eq!(expected_result.handle[0].0.payload(), vec![5, 7, 9]);
     }
+
+    fn limited_context(store: Option<ContextStore>) -> MessageContext {
+        let settings =
+            ContextSettings::new(0, OUTGOING_LIMIT).with_store_limits(ContextStoreLimits {
+                handles: 2,
+                ids: 2,
+                payload_bytes: 4,
+            });
+
+        MessageContext::new_with_settings(Default::default(), Default::default(), store, settings)
+    }
+
+    #[test]
+    fn store_handles_limit() {
+        let mut context = limited_context(None);
+
+        for _ in 0..2 {
+            context.send_init().expect("unreachable");
+        }
+        assert_ok!(context.check_store_limits());
+
+        context.send_init().expect("unreachable");
+        assert_err!(context.check_store_limits(), WaitError::ContextTooLarge);
+    }
+
+    #[test]
+    fn store_ids_limit() {
+        let mut context = limited_context(None);
+
+        context
+            .init_program(Default::default(), None)
+            .expect("unreachable");
+        context
+            .wake(ids::MessageId::from(1), None)
+            .expect("unreachable");
+        assert_ok!(context.check_store_limits());
+
+        context
+            .wake(ids::MessageId::from(2), None)
+            .expect("unreachable");
+        assert_err!(context.check_store_limits(), WaitError::ContextTooLarge);
+    }
+
+    #[test]
+    fn store_payload_limit() {
+        let mut context = limited_context(None);
+
+        let handle = context.send_init().expect("unreachable");
+        context.send_push(handle, &[1, 2]).expect("unreachable");
+        context.reply_push(&[3, 4]).expect("unreachable");
+        assert_ok!(context.check_store_limits());
+
+        context.reply_push(&[5]).expect("unreachable");
+        assert_err!(context.check_store_limits(), WaitError::ContextTooLarge);
+
+        // Committed payloads are no longer stored.
+        context
+            .send_commit(handle, HandlePacket::default(), None)
+            .expect("unreachable");
+        assert_ok!(context.check_store_limits());
+    }
+
+    #[test]
+    fn store_round_trip() {
+        let mut context = limited_context(None);
+
+        let handle = context.send_init().expect("unreachable");
+        context.send_push(handle, &[1, 2]).expect("unreachable");
+        context.reply_push(&[3]).expect("unreachable");
+        context
+            .wake(ids::MessageId::from(1), None)
+            .expect("unreachable");
+        assert_ok!(context.check_store_limits());
+
+        let (_, store) = context.drain();
+        let mut context = limited_context(Some(store.clone()));
+        assert_eq!(context.store, store);
+
+        let message_id = context
+            .send_commit(handle, HandlePacket::default(), None)
+            .expect("unreachable");
+        assert_eq!(context.outcome.handle[0].0.id(), message_id);
+        assert_eq!(context.outcome.handle[0].0.payload(), vec![1, 2]);
+        assert_err!(
+            context.wake(ids::MessageId::from(1), None),
+            Error::DuplicateWaking,
+        );
+    }
+
+    #[test]
+    fn reservation_ids_unique_across_waits() {
+        let mut context = limited_context(None);
+
+        let id = context.reservation_id().expect("unreachable");
+        assert_ne!(context.reservation_id().expect("unreachable"), id);
+
+        let (_, store) = context.drain();
+        let mut context = limited_context(Some(store));
+        assert_ne!(context.reservation_id().expect("unreachable"), id);
+    }
 }
@@ -19,8 +19,8 @@
 use crate::{
     ids::{MessageId, ProgramId},
     message::{
-        Dispatch, DispatchKind, GasLimit, Message, Packet, Payload, StoredDispatch, StoredMessage,
-        Value,
+        Dispatch, DispatchKind, GasLimit, Message, Metadata, Packet, Payload, StoredDispatch,
+        StoredMessage, Value,
     },
 };
 use codec::{Decode, Encode};
@@ -42,6 +42,10 @@ pub struct HandleMessage {
     gas_limit: Option<GasLimit>,
     /// Message value.
     value: Value,
+    /// Message metadata.
+    metadata: Metadata,
+    /// Program replies are delivered to instead of the source, if set.
+    reply_destination: Option<ProgramId>,
 }
 
 impl HandleMessage {
@@ -53,6 +57,8 @@ impl HandleMessage {
             payload: packet.payload,
             gas_limit: packet.gas_limit,
             value: packet.value,
+            metadata: packet.metadata,
+            reply_destination: packet.reply_destination,
         }
     }
 
@@ -67,6 +73,8 @@ impl HandleMessage {
             self.value,
             None,
         )
+        .with_metadata(self.metadata)
+        .with_reply_destination(self.reply_destination)
     }
 
     /// Convert HandleMessage into StoredMessage.
@@ -108,6 +116,16 @@ impl HandleMessage {
     pub fn value(&self) -> Value {
         self.value
     }
+
+    /// Message metadata reference.
+    pub fn metadata(&self) -> &[u8] {
+        self.metadata.get()
+    }
+
+    /// Program replies on the message are delivered to, if overridden.
+    pub fn reply_destination(&self) -> Option<ProgramId> {
+        self.reply_destination
+    }
 }
 
 /// Handle message packet.
@@ -123,6 +141,10 @@ pub struct HandlePacket {
     gas_limit: Option<GasLimit>,
     /// Packet value.
     value: Value,
+    /// Packet metadata.
+    metadata: Metadata,
+    /// Program replies are delivered to instead of the source, if set.
+    reply_destination: Option<ProgramId>,
 }
 
 impl HandlePacket {
@@ -133,6 +155,8 @@ impl HandlePacket {
             payload,
             gas_limit: None,
             value,
+            metadata: Default::default(),
+            reply_destination: None,
         }
     }
 
@@ -148,6 +172,30 @@ impl HandlePacket {
             payload,
             gas_limit: Some(gas_limit),
             value,
+            metadata: Default::default(),
+            reply_destination: None,
+        }
+    }
+
+    /// Set metadata of the packet.
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Set program replies on the message are delivered to instead of
+    /// the sender.
+    pub fn with_reply_destination(self, reply_destination: ProgramId) -> Self {
+        Self {
+            reply_destination: Some(reply_destination),
+            ..self
+        }
+    }
+
+    /// Set gas limit of the packet, unless it's already specified.
+    pub fn with_default_gas_limit(self, gas_limit: GasLimit) -> Self {
+        Self {
+            gas_limit: self.gas_limit.or(Some(gas_limit)),
+            ..self
         }
     }
 
@@ -160,6 +208,16 @@ impl HandlePacket {
     pub fn destination(&self) -> ProgramId {
         self.destination
     }
+
+    /// Packet metadata reference.
+    pub fn metadata(&self) -> &[u8] {
+        self.metadata.get()
+    }
+
+    /// Program replies on the packet are delivered to, if overridden.
+    pub fn reply_destination(&self) -> Option<ProgramId> {
+        self.reply_destination
+    }
 }
 
 impl Packet for HandlePacket {
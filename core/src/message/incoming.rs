@@ -20,8 +20,8 @@ use super::common::ReplyDetails;
 use crate::{
     ids::{MessageId, ProgramId},
     message::{
-        ContextStore, DispatchKind, ExitCode, GasLimit, Payload, StoredDispatch, StoredMessage,
-        Value,
+        ContextStore, DispatchKind, ExitCode, GasLimit, Metadata, Payload, ReplyCode,
+        StoredDispatch, StoredMessage, Value,
     },
 };
 use codec::{Decode, Encode};
@@ -45,6 +45,10 @@ pub struct IncomingMessage {
     value: Value,
     /// Message id replied on with exit code.
     reply: Option<ReplyDetails>,
+    /// Message metadata.
+    metadata: Metadata,
+    /// Program replies are delivered to instead of the source, if set.
+    reply_destination: Option<ProgramId>,
 }
 
 impl IncomingMessage {
@@ -64,8 +68,24 @@ impl IncomingMessage {
             gas_limit,
             value,
             reply,
+            metadata: Default::default(),
+            reply_destination: None,
         }
     }
+
+    /// Set metadata of the message.
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Set program replies are delivered to instead of the source.
+    pub fn with_reply_destination(self, reply_destination: Option<ProgramId>) -> Self {
+        Self {
+            reply_destination,
+            ..self
+        }
+    }
+
     /// Convert IncomingMessage into gasless StoredMessage.
     pub fn into_stored(self, destination: ProgramId) -> StoredMessage {
         StoredMessage::new(
@@ -76,6 +96,8 @@ impl IncomingMessage {
             self.value,
             self.reply,
         )
+        .with_metadata(self.metadata)
+        .with_reply_destination(self.reply_destination)
     }
 
     /// Message id.
@@ -108,6 +130,18 @@ impl IncomingMessage {
         self.reply
     }
 
+    /// Message metadata reference.
+    pub fn metadata(&self) -> &[u8] {
+        self.metadata.get()
+    }
+
+    /// Destination of replies on the message.
+    ///
+    /// The source, unless overridden by the sender.
+    pub fn reply_destination(&self) -> ProgramId {
+        self.reply_destination.unwrap_or(self.source)
+    }
+
     /// Check if this message is reply.
     pub fn is_reply(&self) -> bool {
         self.reply.is_some()
@@ -123,9 +157,9 @@ impl IncomingMessage {
         self.reply.map(|v| v.exit_code())
     }
 
-    /// Returns bool defining if message is error reply.
+    /// Returns bool defining if message is error reply generated by the system.
     pub fn is_error_reply(&self) -> bool {
-        !matches!(self.exit_code(), Some(0) | None)
+        matches!(self.exit_code(), Some(code) if ReplyCode::from(code).is_system_error())
     }
 }
 
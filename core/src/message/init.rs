@@ -172,6 +172,11 @@ impl InitPacket {
     pub fn salt(&self) -> &[u8] {
         self.salt.as_ref()
     }
+
+    /// Replace value of the packet.
+    pub fn with_value(self, value: Value) -> Self {
+        Self { value, ..self }
+    }
 }
 
 impl Packet for InitPacket {
@@ -32,7 +32,10 @@ mod signal;
 mod stored;
 
 pub use common::{Dispatch, Message, ReplyDetails};
-pub use context::{ContextOutcome, ContextSettings, ContextStore, MessageContext};
+pub use context::{
+    ContextOutcome, ContextSettings, ContextStore, ContextStoreLimits, MessageContext,
+};
+pub use gear_core_errors::{ReplyCode, SignalCode};
 pub use handle::{HandleMessage, HandlePacket};
 pub use incoming::{IncomingDispatch, IncomingMessage};
 pub use init::{InitMessage, InitPacket};
@@ -45,7 +48,7 @@ use core::fmt::Display;
 use super::buffer::LimitedVec;
 
 /// Max payload size which one message can have (8 MiB).
-const MAX_PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
+pub const MAX_PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
 
 /// Payload size exceed error
 #[derive(
@@ -68,6 +71,33 @@ impl Display for PayloadSizeError {
 /// Payload type for message.
 pub type Payload = LimitedVec<u8, PayloadSizeError, MAX_PAYLOAD_SIZE>;
 
+/// Max metadata size which one message can have (64 bytes).
+pub const MAX_METADATA_SIZE: usize = 64;
+
+/// Metadata size exceed error
+#[derive(
+    Clone, Copy, Default, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Decode, Encode, TypeInfo,
+)]
+pub struct MetadataSizeError;
+
+impl From<MetadataSizeError> for &str {
+    fn from(_: MetadataSizeError) -> Self {
+        "Metadata size limit exceeded"
+    }
+}
+
+impl Display for MetadataSizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str((*self).into())
+    }
+}
+
+/// Metadata type for message.
+///
+/// Opaque header attached to the message by its sender apart from
+/// the payload, e.g. routing or priority tags.
+pub type Metadata = LimitedVec<u8, MetadataSizeError, MAX_METADATA_SIZE>;
+
 /// Gas limit type for message.
 pub type GasLimit = u64;
 
@@ -75,6 +105,8 @@ pub type GasLimit = u64;
 pub type Value = u128;
 
 /// Exit code type for message replies.
+///
+/// See [`ReplyCode`] for the ranges of the codes.
 pub type ExitCode = i32;
 
 /// Salt type for init message.
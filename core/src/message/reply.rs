@@ -180,6 +180,24 @@ impl ReplyPacket {
         }
     }
 
+    /// Set gas limit of the packet, unless it's already specified.
+    pub fn with_default_gas_limit(self, gas_limit: GasLimit) -> Self {
+        Self {
+            gas_limit: self.gas_limit.or(Some(gas_limit)),
+            ..self
+        }
+    }
+
+    /// Create new ReplyPacket without gas, with the exit code set by program.
+    pub fn new_with_exit_code(payload: Payload, exit_code: ExitCode, value: Value) -> Self {
+        Self {
+            payload,
+            gas_limit: None,
+            value,
+            exit_code,
+        }
+    }
+
     // TODO: consider using here `impl CoreError` and/or provide `AsExitCode`
     // trait or append such functionality to `CoreError` (issue #1083).
     /// Create new system generated ReplyPacket.
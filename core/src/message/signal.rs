@@ -21,6 +21,7 @@ use crate::{
     message::{Dispatch, DispatchKind, ExitCode, Message, ReplyDetails},
 };
 use codec::{Decode, Encode};
+use gear_core_errors::SignalCode;
 use scale_info::TypeInfo;
 
 /// Message for signal entry point.
@@ -28,6 +29,8 @@ use scale_info::TypeInfo;
 pub struct SignalMessage {
     /// Message id.
     id: MessageId,
+    /// Id of the message the signal is sent about.
+    origin_msg_id: MessageId,
     /// Reply exit code.
     exit_code: ExitCode,
 }
@@ -37,7 +40,16 @@ impl SignalMessage {
     pub fn new(origin_msg_id: MessageId, exit_code: ExitCode) -> Self {
         let id = MessageId::generate_signal(origin_msg_id, exit_code);
 
-        Self { id, exit_code }
+        Self {
+            id,
+            origin_msg_id,
+            exit_code,
+        }
+    }
+
+    /// Creates a new [`SignalMessage`] about `origin_msg_id` with the given code.
+    pub fn with_code(origin_msg_id: MessageId, code: SignalCode) -> Self {
+        Self::new(origin_msg_id, code.to_u32() as ExitCode)
     }
 
     /// Convert [`SignalMessage`] into [`Message`].
@@ -49,7 +61,7 @@ impl SignalMessage {
             Default::default(),
             None,
             0,
-            Some(ReplyDetails::new(self.id, self.exit_code)),
+            Some(ReplyDetails::new(self.origin_msg_id, self.exit_code)),
         )
     }
 
@@ -63,6 +75,11 @@ impl SignalMessage {
         self.id
     }
 
+    /// Id of the message the signal is sent about.
+    pub fn origin_msg_id(&self) -> MessageId {
+        self.origin_msg_id
+    }
+
     /// Exit code of the reply message.
     pub fn exit_code(&self) -> ExitCode {
         self.exit_code
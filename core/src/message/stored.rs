@@ -20,13 +20,13 @@ use super::common::ReplyDetails;
 use crate::{
     ids::{MessageId, ProgramId},
     message::{
-        ContextStore, DispatchKind, ExitCode, GasLimit, IncomingDispatch, IncomingMessage, Payload,
-        Value,
+        ContextStore, DispatchKind, ExitCode, GasLimit, IncomingDispatch, IncomingMessage,
+        Metadata, Payload, ReplyCode, Value,
     },
 };
 use alloc::string::ToString;
 use codec::{Decode, Encode};
-use core::{convert::TryInto, ops::Deref};
+use core::{convert::TryInto, mem, ops::Deref};
 use scale_info::TypeInfo;
 
 /// Stored message.
@@ -47,6 +47,10 @@ pub struct StoredMessage {
     value: Value,
     /// Message id replied on with exit code.
     reply: Option<ReplyDetails>,
+    /// Message metadata.
+    metadata: Metadata,
+    /// Program replies are delivered to instead of the source, if set.
+    reply_destination: Option<ProgramId>,
 }
 
 impl StoredMessage {
@@ -66,6 +70,21 @@ impl StoredMessage {
             payload,
             value,
             reply,
+            metadata: Default::default(),
+            reply_destination: None,
+        }
+    }
+
+    /// Set metadata of the message.
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Set program replies are delivered to instead of the source.
+    pub fn with_reply_destination(self, reply_destination: Option<ProgramId>) -> Self {
+        Self {
+            reply_destination,
+            ..self
         }
     }
 
@@ -79,6 +98,8 @@ impl StoredMessage {
             self.value,
             self.reply,
         )
+        .with_metadata(self.metadata)
+        .with_reply_destination(self.reply_destination)
     }
 
     /// Message id.
@@ -101,6 +122,18 @@ impl StoredMessage {
         self.payload.get()
     }
 
+    /// Take message payload out, leaving empty payload in its place.
+    ///
+    /// Used for storing payload apart from the rest of the message.
+    pub fn take_payload(&mut self) -> Payload {
+        mem::take(&mut self.payload)
+    }
+
+    /// Set message payload, replacing the current one.
+    pub fn set_payload(&mut self, payload: Payload) {
+        self.payload = payload;
+    }
+
     /// Message value.
     pub fn value(&self) -> Value {
         self.value
@@ -111,6 +144,18 @@ impl StoredMessage {
         self.reply
     }
 
+    /// Message metadata reference.
+    pub fn metadata(&self) -> &[u8] {
+        self.metadata.get()
+    }
+
+    /// Destination of replies on the message.
+    ///
+    /// The source, unless overridden by the sender.
+    pub fn reply_destination(&self) -> ProgramId {
+        self.reply_destination.unwrap_or(self.source)
+    }
+
     /// Check if this message is reply.
     pub fn is_reply(&self) -> bool {
         self.reply.is_some()
@@ -142,9 +187,9 @@ impl StoredMessage {
         }
     }
 
-    /// Returns bool defining if message is error reply.
+    /// Returns bool defining if message is error reply generated by the system.
     pub fn is_error_reply(&self) -> bool {
-        !matches!(self.exit_code(), Some(0) | None)
+        matches!(self.exit_code(), Some(code) if ReplyCode::from(code).is_system_error())
     }
 }
 
@@ -203,6 +248,16 @@ impl StoredDispatch {
     pub fn context(&self) -> &Option<ContextStore> {
         &self.context
     }
+
+    /// Take dispatch message payload out, leaving empty payload in its place.
+    pub fn take_payload(&mut self) -> Payload {
+        self.message.take_payload()
+    }
+
+    /// Set dispatch message payload, replacing the current one.
+    pub fn set_payload(&mut self, payload: Payload) {
+        self.message.set_payload(payload)
+    }
 }
 
 impl Deref for StoredDispatch {
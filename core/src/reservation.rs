@@ -0,0 +1,220 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for value reservations.
+
+use crate::ids::ReservationId;
+use alloc::collections::BTreeMap;
+use gear_core_errors::ReservationError as Error;
+
+/// Value reservations of the program.
+///
+/// Reserved value stays on the program's balance, but can only be spent
+/// by sending messages from the reservation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValueReservations {
+    initial: BTreeMap<ReservationId, u128>,
+    current: BTreeMap<ReservationId, u128>,
+}
+
+impl ValueReservations {
+    /// New reservations context from reservations made in previous executions.
+    pub fn new(reservations: BTreeMap<ReservationId, u128>) -> Self {
+        Self {
+            initial: reservations.clone(),
+            current: reservations,
+        }
+    }
+
+    /// Total reserved value.
+    pub fn total(&self) -> u128 {
+        self.current
+            .values()
+            .fold(0, |total, value| total.saturating_add(*value))
+    }
+
+    /// Value kept by the reservation, if it exists.
+    pub fn get(&self, id: &ReservationId) -> Option<u128> {
+        self.current.get(id).copied()
+    }
+
+    /// Reserve `value` under the new reservation `id`.
+    pub fn reserve(&mut self, id: ReservationId, value: u128) {
+        self.current.insert(id, value);
+    }
+
+    /// Remove reservation, returning its value.
+    pub fn unreserve(&mut self, id: ReservationId) -> Result<u128, Error> {
+        self.current.remove(&id).ok_or(Error::NotFound)
+    }
+
+    /// Check that `value` can be drawn from the reservation.
+    pub fn check(&self, id: &ReservationId, value: u128) -> Result<(), Error> {
+        let reserved = self.get(id).ok_or(Error::NotFound)?;
+
+        if value > reserved {
+            return Err(Error::ValueExceeded {
+                message_value: value,
+                reserved,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Draw `value` from the reservation, which is removed once it's spent.
+    pub fn draw(&mut self, id: ReservationId, value: u128) -> Result<(), Error> {
+        let reserved = self.current.get_mut(&id).ok_or(Error::NotFound)?;
+
+        *reserved = reserved.checked_sub(value).ok_or(Error::ValueExceeded {
+            message_value: value,
+            reserved: *reserved,
+        })?;
+
+        if *reserved == 0 {
+            self.current.remove(&id);
+        }
+
+        Ok(())
+    }
+
+    /// Move `value` from the reservation into the new one with `new_id`.
+    ///
+    /// The source reservation is removed once all its value is moved.
+    pub fn split(
+        &mut self,
+        id: ReservationId,
+        new_id: ReservationId,
+        value: u128,
+    ) -> Result<(), Error> {
+        let reserved = self.get(&id).ok_or(Error::NotFound)?;
+
+        if value > reserved {
+            return Err(Error::SplitExceeded { value, reserved });
+        }
+
+        self.draw(id, value)?;
+        self.reserve(new_id, value);
+
+        Ok(())
+    }
+
+    /// Reservations after execution, if they were changed.
+    pub fn into_changed(self) -> Option<BTreeMap<ReservationId, u128>> {
+        self.current.ne(&self.initial).then_some(self.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::MessageContext;
+
+    #[test]
+    fn reserve_and_draw() {
+        let mut reservations = ValueReservations::default();
+
+        let id = ReservationId::from(1);
+        let other_id = ReservationId::from(2);
+        reservations.reserve(id, 100);
+        reservations.reserve(other_id, 50);
+        assert_eq!(reservations.total(), 150);
+
+        assert_eq!(
+            reservations.draw(id, 101),
+            Err(Error::ValueExceeded {
+                message_value: 101,
+                reserved: 100
+            })
+        );
+        assert_eq!(
+            reservations.check(&id, 101),
+            Err(Error::ValueExceeded {
+                message_value: 101,
+                reserved: 100
+            })
+        );
+        assert_eq!(reservations.check(&id, 100), Ok(()));
+        assert_eq!(reservations.get(&id), Some(100));
+
+        assert_eq!(reservations.draw(id, 60), Ok(()));
+        assert_eq!(reservations.get(&id), Some(40));
+
+        assert_eq!(reservations.draw(id, 40), Ok(()));
+        assert_eq!(reservations.get(&id), None);
+        assert_eq!(reservations.draw(id, 0), Err(Error::NotFound));
+
+        assert_eq!(reservations.unreserve(other_id), Ok(50));
+        assert_eq!(reservations.unreserve(other_id), Err(Error::NotFound));
+        assert_eq!(reservations.total(), 0);
+    }
+
+    #[test]
+    fn split() {
+        let mut reservations = ValueReservations::default();
+        let id = ReservationId::from(1);
+        reservations.reserve(id, 100);
+
+        let split_id = ReservationId::from(2);
+        assert_eq!(
+            reservations.split(id, split_id, 101),
+            Err(Error::SplitExceeded {
+                value: 101,
+                reserved: 100
+            })
+        );
+
+        assert_eq!(reservations.split(id, split_id, 60), Ok(()));
+        assert_eq!(reservations.get(&id), Some(40));
+        assert_eq!(reservations.get(&split_id), Some(60));
+        assert_eq!(reservations.total(), 100);
+
+        let last_id = ReservationId::from(3);
+        assert_eq!(reservations.split(id, last_id, 40), Ok(()));
+        assert_eq!(reservations.get(&id), None);
+        assert_eq!(reservations.get(&last_id), Some(40));
+        assert_eq!(
+            reservations.split(id, ReservationId::from(4), 0),
+            Err(Error::NotFound)
+        );
+        assert_eq!(reservations.total(), 100);
+    }
+
+    #[test]
+    fn ids_unique_after_unreserve() {
+        let mut context = MessageContext::new(Default::default(), Default::default(), None);
+        let mut reservations = ValueReservations::default();
+
+        let id = context.reservation_id().expect("unreachable");
+        reservations.reserve(id, 100);
+        assert_eq!(reservations.unreserve(id), Ok(100));
+
+        let other_id = context.reservation_id().expect("unreachable");
+        reservations.reserve(other_id, 100);
+        assert_ne!(other_id, id);
+        assert_eq!(reservations.unreserve(other_id), Ok(100));
+
+        // The message is executed again after waiting.
+        let (_, store) = context.drain();
+        let mut context = MessageContext::new(Default::default(), Default::default(), Some(store));
+
+        let next_id = context.reservation_id().expect("unreachable");
+        assert_ne!(next_id, id);
+        assert_ne!(next_id, other_id);
+    }
+}
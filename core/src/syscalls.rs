@@ -0,0 +1,351 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for syscall signatures.
+
+use alloc::string::String;
+use core::fmt::{self, Display};
+use parity_wasm::elements::{External, Module, Type};
+use ValueType::{I32, I64};
+
+/// Type of the syscall param or result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum ValueType {
+    /// 32-bit integer.
+    #[display(fmt = "i32")]
+    I32,
+    /// 64-bit integer.
+    #[display(fmt = "i64")]
+    I64,
+}
+
+impl PartialEq<parity_wasm::elements::ValueType> for ValueType {
+    fn eq(&self, other: &parity_wasm::elements::ValueType) -> bool {
+        use parity_wasm::elements::ValueType as Wasm;
+
+        matches!((self, other), (I32, Wasm::I32) | (I64, Wasm::I64))
+    }
+}
+
+/// Signature the syscall must be imported with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SysCallSignature {
+    /// Types of the params.
+    pub params: &'static [ValueType],
+    /// Type of the result, if any.
+    pub result: Option<ValueType>,
+}
+
+impl SysCallSignature {
+    /// Create a new signature.
+    pub const fn new(params: &'static [ValueType], result: Option<ValueType>) -> Self {
+        Self { params, result }
+    }
+}
+
+impl Display for SysCallSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", param)?;
+        }
+        write!(f, ")")?;
+
+        match self.result {
+            Some(result) => write!(f, " -> {}", result),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Syscall is imported with a signature different from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+#[display(fmt = "Syscall {} must be imported with signature {}", name, expected)]
+pub struct SysCallSignatureError {
+    /// Name of the syscall.
+    pub name: String,
+    /// Signature the syscall must be imported with.
+    pub expected: SysCallSignature,
+}
+
+/// Syscalls every backend must provide in the "env" module,
+/// along with signatures programs must import them with.
+///
+/// Backends check their host function tables against it in tests,
+/// so programs behave the same whichever backend executes them.
+pub const SYSCALLS: &[(&str, SysCallSignature)] = &[
+    ("gr_block_duration", SysCallSignature::new(&[], Some(I64))),
+    (
+        "gr_block_hash",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    ("gr_block_height", SysCallSignature::new(&[], Some(I32))),
+    ("gr_block_timestamp", SysCallSignature::new(&[], Some(I64))),
+    ("gr_bump_counter", SysCallSignature::new(&[I32], Some(I64))),
+    ("gr_children_count", SysCallSignature::new(&[], Some(I64))),
+    (
+        "gr_children_gas_burned",
+        SysCallSignature::new(&[], Some(I64)),
+    ),
+    ("gr_codes_count", SysCallSignature::new(&[], Some(I64))),
+    ("gr_context_limits", SysCallSignature::new(&[I32], None)),
+    (
+        "gr_create_program",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_create_program_for_reply",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_create_program_wgas",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I64, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_create_program_wgas_for_reply",
+        SysCallSignature::new(
+            &[I32, I32, I32, I32, I32, I64, I32, I32, I32, I32],
+            Some(I32),
+        ),
+    ),
+    ("gr_debug", SysCallSignature::new(&[I32, I32], None)),
+    ("gr_delayed_eta", SysCallSignature::new(&[I32], Some(I32))),
+    ("gr_error", SysCallSignature::new(&[I32], None)),
+    ("gr_execution_id", SysCallSignature::new(&[], Some(I64))),
+    ("gr_exit", SysCallSignature::new(&[I32], None)),
+    ("gr_exit_code", SysCallSignature::new(&[], Some(I32))),
+    (
+        "gr_forward",
+        SysCallSignature::new(&[I32, I32, I32, I32], Some(I32)),
+    ),
+    ("gr_gas_allowance", SysCallSignature::new(&[], Some(I64))),
+    ("gr_gas_available", SysCallSignature::new(&[], Some(I64))),
+    ("gr_is_init", SysCallSignature::new(&[], Some(I32))),
+    ("gr_leave", SysCallSignature::new(&[], None)),
+    (
+        "gr_mailbox_contains",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    ("gr_max_payload_size", SysCallSignature::new(&[], Some(I32))),
+    ("gr_message_meta", SysCallSignature::new(&[I32], Some(I32))),
+    ("gr_msg_id", SysCallSignature::new(&[I32], None)),
+    ("gr_new_handle", SysCallSignature::new(&[], Some(I32))),
+    ("gr_origin", SysCallSignature::new(&[I32], None)),
+    ("gr_program_id", SysCallSignature::new(&[I32], None)),
+    ("gr_read", SysCallSignature::new(&[I32, I32, I32], None)),
+    (
+        "gr_read_records",
+        SysCallSignature::new(&[I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_read_ring",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reply",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reply_commit",
+        SysCallSignature::new(&[I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reply_commit_wgas",
+        SysCallSignature::new(&[I64, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reply_push",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    ("gr_reply_to", SysCallSignature::new(&[I32], None)),
+    (
+        "gr_reply_wgas",
+        SysCallSignature::new(&[I32, I32, I64, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reply_with_code",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reservation_send",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reserve_and_send",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_reserve_value",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    (
+        "gr_resumed_from_wait",
+        SysCallSignature::new(&[], Some(I32)),
+    ),
+    (
+        "gr_schedule_exit",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_and_wait",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_charge_recipient",
+        SysCallSignature::new(&[I32, I32, I32, I64, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_commit",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_commit_wgas",
+        SysCallSignature::new(&[I32, I32, I32, I64, I32, I32], Some(I32)),
+    ),
+    ("gr_send_init", SysCallSignature::new(&[I32], Some(I32))),
+    (
+        "gr_send_push",
+        SysCallSignature::new(&[I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_wgas",
+        SysCallSignature::new(&[I32, I32, I32, I64, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_with_meta",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_send_with_reply_to",
+        SysCallSignature::new(&[I32, I32, I32, I32, I32, I32, I32], Some(I32)),
+    ),
+    (
+        "gr_set_charge_allowance",
+        SysCallSignature::new(&[I64], None),
+    ),
+    (
+        "gr_set_default_gas_limit",
+        SysCallSignature::new(&[I64], None),
+    ),
+    (
+        "gr_set_entry_budget",
+        SysCallSignature::new(&[I32, I64], Some(I32)),
+    ),
+    (
+        "gr_signal_details",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    ("gr_size", SysCallSignature::new(&[], Some(I32))),
+    ("gr_source", SysCallSignature::new(&[I32], None)),
+    (
+        "gr_source_code_id",
+        SysCallSignature::new(&[I32], Some(I32)),
+    ),
+    ("gr_spendable_value", SysCallSignature::new(&[I32], None)),
+    (
+        "gr_split_value_reservation",
+        SysCallSignature::new(&[I32, I32, I32], Some(I32)),
+    ),
+    ("gr_stack_limit", SysCallSignature::new(&[], Some(I32))),
+    (
+        "gr_trace",
+        SysCallSignature::new(&[I32, I32, I32], Some(I32)),
+    ),
+    ("gr_tx_hash", SysCallSignature::new(&[I32], Some(I32))),
+    (
+        "gr_unreserve_value",
+        SysCallSignature::new(&[I32, I32], Some(I32)),
+    ),
+    ("gr_value", SysCallSignature::new(&[I32], None)),
+    ("gr_value_available", SysCallSignature::new(&[I32], None)),
+    ("gr_wait", SysCallSignature::new(&[], None)),
+    (
+        "gr_wait_budget_remaining",
+        SysCallSignature::new(&[], Some(I32)),
+    ),
+    ("gr_wait_for", SysCallSignature::new(&[I32], None)),
+    ("gr_wait_if", SysCallSignature::new(&[I32], None)),
+    ("gr_wait_up_to", SysCallSignature::new(&[I32], None)),
+    ("gr_wake", SysCallSignature::new(&[I32, I32], None)),
+];
+
+/// Signature of the syscall, if there is one with the `name`.
+pub fn syscall_signature(name: &str) -> Option<SysCallSignature> {
+    SYSCALLS
+        .iter()
+        .find(|(syscall, _)| *syscall == name)
+        .map(|(_, signature)| *signature)
+}
+
+/// Check that syscalls are imported by the module with their signatures.
+///
+/// Otherwise host functions would read wrong arguments, or even differ
+/// between backends in how they treat them, so such modules are rejected
+/// once, when the code is uploaded. Imports unknown to [`SYSCALLS`] are left
+/// for the backend to resolve.
+pub fn check_syscall_signatures(module: &Module) -> Result<(), SysCallSignatureError> {
+    let imports = match module.import_section() {
+        Some(section) => section.entries(),
+        None => return Ok(()),
+    };
+    let types = module
+        .type_section()
+        .map(|section| section.types())
+        .unwrap_or_default();
+
+    for import in imports.iter().filter(|import| import.module() == "env") {
+        let name = import.field();
+        let (expected, type_idx) = match (syscall_signature(name), import.external()) {
+            (Some(expected), External::Function(type_idx)) => (expected, *type_idx),
+            _ => continue,
+        };
+
+        let matches = match types.get(type_idx as usize) {
+            Some(Type::Function(func_type)) => {
+                func_type.params().len() == expected.params.len()
+                    && expected
+                        .params
+                        .iter()
+                        .zip(func_type.params())
+                        .all(|(a, b)| a == b)
+                    && match (expected.result, func_type.results()) {
+                        (None, []) => true,
+                        (Some(expected), [result]) => expected == *result,
+                        _ => false,
+                    }
+            }
+            None => false,
+        };
+
+        if !matches {
+            return Err(SysCallSignatureError {
+                name: name.into(),
+                expected,
+            });
+        }
+    }
+
+    Ok(())
+}
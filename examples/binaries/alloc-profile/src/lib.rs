@@ -0,0 +1,219 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Program parsing JSON-like documents, which profiles its allocations.
+//!
+//! Every payload is parsed as a document, while payload `profile` is replied
+//! with the [`Report`](gstd::profiler::Report) of the allocations made so far.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+/// Payload requesting the profiling report.
+pub const PROFILE: &[u8] = b"profile";
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::PROFILE;
+    use gstd::{msg, prelude::*, profiler};
+
+    #[allow(unused)]
+    enum Value {
+        Null,
+        Num(i64),
+        Str(String),
+        Arr(Vec<Value>),
+        Obj(Vec<(String, Value)>),
+    }
+
+    struct Parser<'a> {
+        input: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn peek(&mut self) -> Option<u8> {
+            while let Some(b' ' | b'\n' | b'\t' | b'\r') = self.input.get(self.pos) {
+                self.pos += 1;
+            }
+
+            self.input.get(self.pos).copied()
+        }
+
+        fn expect(&mut self, byte: u8) -> Option<()> {
+            (self.peek()? == byte).then(|| self.pos += 1)
+        }
+
+        fn value(&mut self) -> Option<Value> {
+            match self.peek()? {
+                b'n' => {
+                    self.pos += 4;
+                    Some(Value::Null)
+                }
+                b'"' => self.string().map(Value::Str),
+                b'[' => self.array(),
+                b'{' => self.object(),
+                _ => self.number(),
+            }
+        }
+
+        fn number(&mut self) -> Option<Value> {
+            let start = self.pos;
+
+            while let Some(b'-' | b'0'..=b'9') = self.input.get(self.pos) {
+                self.pos += 1;
+            }
+
+            let digits = core::str::from_utf8(&self.input[start..self.pos]).ok()?;
+
+            digits.parse().ok().map(Value::Num)
+        }
+
+        fn string(&mut self) -> Option<String> {
+            let _tag = gstd::alloc_tag("json::string");
+
+            self.expect(b'"')?;
+
+            let mut string = String::new();
+
+            loop {
+                match *self.input.get(self.pos)? {
+                    b'"' => break,
+                    byte => string.push(byte as char),
+                }
+
+                self.pos += 1;
+            }
+
+            self.pos += 1;
+
+            Some(string)
+        }
+
+        fn array(&mut self) -> Option<Value> {
+            let _tag = gstd::alloc_tag("json::array");
+
+            self.expect(b'[')?;
+
+            let mut items = Vec::new();
+
+            while self.peek()? != b']' {
+                items.push(self.value()?);
+
+                if self.peek()? == b',' {
+                    self.pos += 1;
+                }
+            }
+
+            self.pos += 1;
+
+            Some(Value::Arr(items))
+        }
+
+        fn object(&mut self) -> Option<Value> {
+            let _tag = gstd::alloc_tag("json::object");
+
+            self.expect(b'{')?;
+
+            let mut fields = Vec::new();
+
+            while self.peek()? != b'}' {
+                let key = self.string()?;
+                self.expect(b':')?;
+                fields.push((key, self.value()?));
+
+                if self.peek()? == b',' {
+                    self.pos += 1;
+                }
+            }
+
+            self.pos += 1;
+
+            Some(Value::Obj(fields))
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        let payload = msg::load_bytes();
+
+        if payload == PROFILE {
+            profiler::dump();
+            msg::reply(profiler::report(), 0).unwrap();
+
+            return;
+        }
+
+        let parsed = Parser {
+            input: &payload,
+            pos: 0,
+        }
+        .value();
+
+        msg::reply(parsed.is_some(), 0).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::PROFILE;
+    use codec::Decode;
+    use gstd::profiler::Report;
+    use gtest::{Log, Program, System};
+
+    const USER: u64 = 42;
+
+    #[test]
+    fn top_sites_are_parser_scopes() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let _res = program.send_bytes(USER, b"init");
+
+        let document = br#"{"name": "gear", "tags": ["wasm", "actor", "async"],
+            "nested": {"values": [1, 2, 3, 4, 5, 6, 7, 8], "empty": null}}"#;
+
+        let res = program.send_bytes(USER, document.as_ref());
+        assert!(res.contains(&Log::builder().dest(USER).payload(true)));
+
+        let res = program.send_bytes(USER, PROFILE);
+        let log = res.log().last().expect("Report replied");
+        let report = Report::decode(&mut log.payload()).expect("Report decoded");
+
+        assert!(report.count > 0);
+        assert!(!report.largest.is_empty());
+        assert!(report
+            .largest
+            .windows(2)
+            .all(|sites| sites[0].size >= sites[1].size));
+        assert!(report
+            .largest
+            .iter()
+            .any(|site| site.tag.starts_with("json::")));
+    }
+}
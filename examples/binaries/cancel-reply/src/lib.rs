@@ -0,0 +1,211 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Requester cancelling the awaiting of replies with
+//! [`gstd::msg::MessageFuture::cancel`] and notifying about the replies
+//! received after cancellation.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gstd::Vec;
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+pub const PING: &[u8] = b"PING";
+
+#[derive(Debug, Encode, Decode)]
+pub enum Command {
+    /// Reply with the payload.
+    Echo(Vec<u8>),
+    /// Send [`PING`] to the echo program and cancel awaiting the reply
+    /// right away.
+    CancelAtOnce([u8; 32]),
+    /// Send [`PING`] to the echo program and cancel awaiting the reply with
+    /// the [`Command::Cancel`] sent to itself.
+    CancelQueued([u8; 32]),
+    /// Send [`PING`] to the echo program, keeping the reply awaited by
+    /// nobody until [`Command::Cancel`].
+    Keep([u8; 32]),
+    /// Cancel awaiting the reply to the kept request.
+    Cancel,
+    /// Enable or disable notifications on the replies received after
+    /// cancellation.
+    Notify(bool),
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+pub enum Event {
+    /// Awaiting the kept request's reply is cancelled.
+    Cancelled,
+    /// Reply to the cancelled request is received.
+    LateReply(Vec<u8>),
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::{Command, Event, PING};
+    use codec::Encode;
+    use gstd::{
+        msg::{self, MessageFuture},
+        ActorId, MessageId,
+    };
+
+    static mut ORIGIN: ActorId = ActorId::new([0; 32]);
+    static mut KEPT: Option<MessageFuture> = None;
+
+    fn notify(_reply_to: MessageId) {
+        msg::send(unsafe { ORIGIN }, Event::LateReply(msg::load_bytes()), 0)
+            .expect("Failed to notify");
+    }
+
+    fn ping(echo: [u8; 32]) -> MessageFuture {
+        unsafe { ORIGIN = msg::source() };
+
+        msg::send_bytes_for_reply(ActorId::new(echo), Command::Echo(PING.into()).encode(), 0)
+            .expect("Failed to send")
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn init() {
+        msg::set_late_reply_hook(Some(notify));
+    }
+
+    #[gstd::async_main]
+    async fn main() {
+        match msg::load().expect("Failed to decode command") {
+            Command::Echo(payload) => {
+                msg::reply_bytes(payload, 0).expect("Failed to reply");
+            }
+            Command::CancelAtOnce(echo) => {
+                ping(echo).cancel().expect("Reply isn't awaited");
+            }
+            Command::CancelQueued(echo) => {
+                unsafe { KEPT = Some(ping(echo)) };
+                msg::send(gstd::exec::program_id(), Command::Cancel, 0).expect("Failed to send");
+            }
+            Command::Keep(echo) => {
+                unsafe { KEPT = Some(ping(echo)) };
+            }
+            Command::Cancel => {
+                let kept = unsafe { KEPT.take() }.expect("No request kept");
+                kept.cancel().expect("Reply isn't awaited");
+                msg::send(unsafe { ORIGIN }, Event::Cancelled, 0).expect("Failed to send");
+            }
+            Command::Notify(enabled) => {
+                msg::set_late_reply_hook(enabled.then(|| notify as fn(MessageId)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{Command, Event, PING};
+    use gtest::{Log, Program, System};
+
+    const USER: u64 = 42;
+
+    fn programs(system: &System) -> (Program, Program, [u8; 32]) {
+        let echo = Program::current(system);
+        let requester = Program::current(system);
+
+        let _res = echo.send_bytes(USER, b"init");
+        let _res = requester.send_bytes(USER, b"init");
+
+        let mut echo_id = [0; 32];
+        echo_id.copy_from_slice(echo.id().as_ref());
+
+        (echo, requester, echo_id)
+    }
+
+    fn late_reply() -> Log {
+        Log::builder()
+            .dest(USER)
+            .payload(Event::LateReply(PING.into()))
+    }
+
+    #[test]
+    fn cancel_before_reply() {
+        let system = System::new();
+        system.init_logger();
+
+        let (_echo, requester, echo_id) = programs(&system);
+
+        let res = requester.send(USER, Command::CancelAtOnce(echo_id));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+        assert!(res.contains(&late_reply()));
+    }
+
+    #[test]
+    fn cancel_with_reply_queued() {
+        let system = System::new();
+        system.init_logger();
+
+        let (_echo, requester, echo_id) = programs(&system);
+
+        // The reply is queued before the `Cancel` sent to itself is handled.
+        let res = requester.send(USER, Command::CancelQueued(echo_id));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+        assert!(res.contains(&Log::builder().dest(USER).payload(Event::Cancelled)));
+        assert!(res.contains(&late_reply()));
+    }
+
+    #[test]
+    fn cancel_after_reply_drops_it() {
+        let system = System::new();
+        system.init_logger();
+
+        let (_echo, requester, echo_id) = programs(&system);
+
+        let res = requester.send(USER, Command::Keep(echo_id));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+
+        let res = requester.send(USER, Command::Cancel);
+        assert!(!res.main_failed());
+        assert!(res.contains(&Log::builder().dest(USER).payload(Event::Cancelled)));
+        assert!(!res.contains(&late_reply()));
+    }
+
+    #[test]
+    fn late_reply_ignored_without_hook() {
+        let system = System::new();
+        system.init_logger();
+
+        let (_echo, requester, echo_id) = programs(&system);
+
+        let res = requester.send(USER, Command::Notify(false));
+        assert!(!res.main_failed());
+
+        let res = requester.send(USER, Command::CancelAtOnce(echo_id));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+        assert!(!res.contains(&late_reply()));
+    }
+}
@@ -0,0 +1,115 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Counter dispatching its requests with [`gstd::dispatch!`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Debug, Encode, Decode)]
+pub enum Request {
+    /// Increase the counter, replying with the new value.
+    Inc(u32),
+    /// Reset the counter, replying with empty payload.
+    Reset,
+    /// Reply with the current value of the counter.
+    Get,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Request;
+
+    static mut COUNTER: u32 = 0;
+
+    fn inc(by: u32) -> u32 {
+        unsafe {
+            COUNTER = COUNTER.saturating_add(by);
+            COUNTER
+        }
+    }
+
+    fn reset() {
+        unsafe { COUNTER = 0 }
+    }
+
+    gstd::dispatch! {
+        Request {
+            Request::Inc(by) => inc(by),
+            Request::Reset => reset(),
+            Request::Get => unsafe { COUNTER },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Request;
+    use gtest::{Log, Program, System};
+
+    const USER: u64 = 42;
+
+    #[test]
+    fn requests_dispatched() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let res = program.send_bytes(USER, b"");
+        assert!(!res.main_failed());
+
+        let res = program.send(USER, Request::Inc(2));
+        assert!(res.contains(&Log::builder().dest(USER).payload(2u32)));
+
+        let res = program.send(USER, Request::Inc(3));
+        assert!(res.contains(&Log::builder().dest(USER).payload(5u32)));
+
+        let res = program.send(USER, Request::Get);
+        assert!(res.contains(&Log::builder().dest(USER).payload(5u32)));
+
+        let res = program.send(USER, Request::Reset);
+        assert!(res.contains(&Log::builder().dest(USER).payload_bytes(b"")));
+
+        let res = program.send(USER, Request::Get);
+        assert!(res.contains(&Log::builder().dest(USER).payload(0u32)));
+    }
+
+    #[test]
+    fn undecoded_request_fails() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let res = program.send_bytes(USER, b"");
+        assert!(!res.main_failed());
+
+        let res = program.send_bytes(USER, [42u8]);
+        assert!(res.main_failed());
+    }
+}
@@ -0,0 +1,169 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Counter bounded by [`LIMIT`], which reports the limit being exceeded
+//! with [`gstd::ensure!`] or rolls the change back with
+//! [`gstd::ensure_rollback!`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+/// Max value of the counter.
+pub const LIMIT: u32 = 10;
+
+/// Raw code of the reply telling the limit is exceeded.
+pub const LIMIT_EXCEEDED: i32 = 256;
+
+/// Payload sent to the source if the error reply can't be sent.
+pub const UNHANDLED: &[u8] = b"unhandled";
+
+#[derive(Debug, Encode, Decode)]
+pub enum Action {
+    /// Add to the counter, keeping the result even above the limit.
+    Add(u32),
+    /// Add to the counter, rolling back the result above the limit.
+    AddOrRollback(u32),
+    /// Reply and then fail, which can't reply once more.
+    ReplyAndFail,
+    /// Reply with the counter.
+    Get,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::{Action, LIMIT, LIMIT_EXCEEDED, UNHANDLED};
+    use gstd::{ensure, ensure_rollback, errors::Result, fail, msg, msg::ReplyCode};
+
+    const CODE: ReplyCode = ReplyCode::from_raw(LIMIT_EXCEEDED);
+
+    static mut COUNTER: u32 = 0;
+
+    unsafe fn process(action: Action) -> Result<()> {
+        match action {
+            Action::Add(value) => {
+                COUNTER = COUNTER.saturating_add(value);
+                ensure!(COUNTER <= LIMIT, CODE, "Limit exceeded");
+            }
+            Action::AddOrRollback(value) => {
+                COUNTER = COUNTER.saturating_add(value);
+                ensure_rollback!(COUNTER <= LIMIT, "Limit exceeded");
+            }
+            Action::ReplyAndFail => {
+                msg::reply(COUNTER, 0)?;
+                fail!(CODE, "Already replied");
+            }
+            Action::Get => {
+                msg::reply(COUNTER, 0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        let action = msg::load().expect("Failed to decode action");
+
+        if process(action).is_err() {
+            msg::send_bytes(msg::source(), UNHANDLED, 0).expect("Failed to send");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{Action, LIMIT_EXCEEDED, UNHANDLED};
+    use codec::{Decode, Encode};
+    use gtest::{Log, Program, RunResult, System};
+
+    const USER: u64 = 42;
+
+    fn counter(program: &Program) -> u32 {
+        let res = program.send(USER, Action::Get);
+        let log = res.log().last().expect("Counter replied");
+        u32::decode(&mut log.payload()).expect("Counter encoded")
+    }
+
+    fn limit_exceeded(res: &RunResult) -> bool {
+        res.log().iter().any(|log| {
+            log.exit_code() == Some(LIMIT_EXCEEDED) && log.payload() == "Limit exceeded".encode()
+        })
+    }
+
+    #[test]
+    fn ensure_replies_and_persists() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let _res = program.send_bytes(USER, b"init");
+
+        let res = program.send(USER, Action::Add(8));
+        assert!(!res.main_failed());
+        assert!(!limit_exceeded(&res));
+
+        let res = program.send(USER, Action::Add(5));
+        assert!(!res.main_failed());
+        assert!(limit_exceeded(&res));
+
+        // Changes made before the failed check are kept.
+        assert_eq!(counter(&program), 13);
+    }
+
+    #[test]
+    fn ensure_rollback_traps() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let _res = program.send_bytes(USER, b"init");
+
+        let res = program.send(USER, Action::AddOrRollback(8));
+        assert!(!res.main_failed());
+
+        let res = program.send(USER, Action::AddOrRollback(5));
+        assert!(res.main_failed());
+        assert!(!limit_exceeded(&res));
+
+        assert_eq!(counter(&program), 8);
+    }
+
+    #[test]
+    fn fail_after_reply_returns_error() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let _res = program.send_bytes(USER, b"init");
+
+        let res = program.send(USER, Action::ReplyAndFail);
+        assert!(!res.main_failed());
+        assert!(res.contains(&Log::builder().dest(USER).payload_bytes(UNHANDLED)));
+    }
+}
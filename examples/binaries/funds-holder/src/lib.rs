@@ -0,0 +1,229 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use codec::{Decode, Encode};
+use gstd::{errors::ContractError, exec, funds, msg, prelude::*, ActorId, MessageId};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Encode, Debug, Decode, PartialEq, Eq)]
+pub enum Request {
+    /// Hold message value and reply with the held amount once woken.
+    HoldAndWait,
+    /// Hold message value and panic.
+    HoldAndPanic,
+    /// Hold message value and panic once woken.
+    HoldWaitAndPanic,
+    /// Wake message holding value.
+    Wake(MessageId),
+    /// Release value held by the message to the actor.
+    Release(MessageId, ActorId),
+}
+
+static mut WOKEN: Option<BTreeSet<MessageId>> = None;
+
+fn process_request(request: Request) {
+    let woken = unsafe { WOKEN.get_or_insert_with(BTreeSet::new) };
+
+    match request {
+        Request::HoldAndWait => {
+            let hold = funds::hold(msg::value()).unwrap();
+
+            if !woken.remove(&msg::id()) {
+                exec::wait();
+            }
+
+            msg::reply(funds::held(hold), 0).unwrap();
+        }
+        Request::HoldAndPanic => {
+            funds::hold(msg::value()).unwrap();
+            panic!("Trap after hold");
+        }
+        Request::HoldWaitAndPanic => {
+            funds::hold(msg::value()).unwrap();
+
+            if !woken.remove(&msg::id()) {
+                exec::wait();
+            }
+
+            panic!("Trap after wait");
+        }
+        Request::Wake(id) => {
+            woken.insert(id);
+            exec::wake(id);
+        }
+        Request::Release(id, to) => {
+            let res = funds::release(id.into(), to)
+                .map(|_| ())
+                .map_err(|err| match err {
+                    ContractError::Funds(err) => err,
+                    err => panic!("{}", err),
+                });
+
+            msg::reply(res, 0).unwrap();
+        }
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn init() {
+    msg::reply((), 0).unwrap();
+}
+
+#[no_mangle]
+unsafe extern "C" fn handle() {
+    msg::load::<Request>().map(process_request).unwrap();
+}
+
+#[no_mangle]
+unsafe extern "C" fn handle_signal() {
+    funds::handle_signal().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Request;
+    use std::convert::TryInto;
+
+    use gstd::{funds::FundsError, MessageId};
+    use gtest::{Log, Program, System};
+
+    const USER: u64 = 42;
+    const RECEIVER: u64 = 43;
+    const VALUE: u128 = 1_000;
+
+    fn init_program(system: &System) -> Program {
+        system.init_logger();
+        system.mint_to(USER, 10_000);
+
+        let program = Program::current(system);
+        let _res = program.send_bytes(USER, b"init");
+
+        program
+    }
+
+    #[test]
+    fn hold_wait_release() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send_with_value(USER, Request::HoldAndWait, VALUE);
+        let hold_msg_id = MessageId::new(res.sent_message_id().as_ref().try_into().unwrap());
+        assert!(res.log().is_empty());
+        assert_eq!(program.balance(), VALUE);
+
+        // Resumed execution gets the same hold, so value isn't held twice.
+        let res = program.send(USER, Request::Wake(hold_msg_id));
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload(Some(VALUE));
+        assert!(res.contains(&log));
+
+        let res = program.send(USER, Request::Release(hold_msg_id, RECEIVER.into()));
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload(Ok::<(), FundsError>(()));
+        assert!(res.contains(&log));
+
+        system.claim_value_from_mailbox(RECEIVER);
+        assert_eq!(system.balance_of(RECEIVER), VALUE);
+        assert_eq!(program.balance(), 0);
+    }
+
+    #[test]
+    fn hold_and_trap_refunds() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send_with_value(USER, Request::HoldAndPanic, VALUE);
+        let hold_msg_id = MessageId::new(res.sent_message_id().as_ref().try_into().unwrap());
+        assert!(res.main_failed());
+
+        assert_eq!(system.balance_of(USER), 10_000);
+        assert_eq!(program.balance(), 0);
+
+        // Hold was reverted together with the program's memory.
+        let res = program.send(USER, Request::Release(hold_msg_id, RECEIVER.into()));
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload(Err::<(), _>(FundsError::HoldNotFound(hold_msg_id.into())));
+        assert!(res.contains(&log));
+    }
+
+    #[test]
+    fn hold_wait_and_trap_refunds() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send_with_value(USER, Request::HoldWaitAndPanic, VALUE);
+        let hold_msg_id = MessageId::new(res.sent_message_id().as_ref().try_into().unwrap());
+        assert_eq!(system.balance_of(USER), 10_000 - VALUE);
+        assert_eq!(program.balance(), VALUE);
+
+        // Value isn't returned with the error reply, since it was transferred
+        // to the program, but the hold is refunded on the signal.
+        let res = program.send(USER, Request::Wake(hold_msg_id));
+        assert!(res.others_failed());
+
+        system.claim_value_from_mailbox(USER);
+        assert_eq!(system.balance_of(USER), 10_000);
+        assert_eq!(program.balance(), 0);
+
+        let res = program.send(USER, Request::Release(hold_msg_id, RECEIVER.into()));
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload(Err::<(), _>(FundsError::HoldNotFound(hold_msg_id.into())));
+        assert!(res.contains(&log));
+    }
+
+    #[test]
+    fn double_release() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send_with_value(USER, Request::HoldAndWait, VALUE);
+        let hold_msg_id = MessageId::new(res.sent_message_id().as_ref().try_into().unwrap());
+        let _res = program.send(USER, Request::Wake(hold_msg_id));
+
+        let res = program.send(USER, Request::Release(hold_msg_id, RECEIVER.into()));
+        assert!(!res.main_failed());
+
+        let res = program.send(USER, Request::Release(hold_msg_id, RECEIVER.into()));
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload(Err::<(), _>(FundsError::HoldNotFound(hold_msg_id.into())));
+        assert!(res.contains(&log));
+    }
+}
@@ -0,0 +1,235 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Factory deploying children of its own code with
+//! [`gstd::prog::create_program_for_reply`] and waiting for the payload the
+//! child replies with from `init`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gstd::Vec;
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+/// Payload the child replies with from `init`.
+pub const CONFIG_DIGEST: &[u8] = b"config digest";
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub enum Init {
+    /// Initialize as a factory.
+    Factory,
+    /// Initialize as a child created by the factory.
+    Child(Behavior),
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub enum Behavior {
+    /// Reply with [`CONFIG_DIGEST`].
+    Reply,
+    /// Reply with [`CONFIG_DIGEST`] and then panic.
+    ReplyAndPanic,
+    /// Send [`Request::Notify`] to the factory, then reply with
+    /// [`CONFIG_DIGEST`].
+    NotifyAndReply,
+}
+
+#[derive(Debug, Encode, Decode)]
+pub enum Request {
+    /// Create a child from the given code with the given behavior.
+    Deploy([u8; 32], Behavior),
+    /// Notification sent to the factory by the child.
+    Notify,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+pub enum Outcome {
+    /// The child replied from `init`.
+    Deployed {
+        program: [u8; 32],
+        payload: Vec<u8>,
+        notifications: u32,
+    },
+    /// The child's init failed with the given exit code.
+    Failed(i32),
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::{Behavior, Init, Outcome, Request, CONFIG_DIGEST};
+    use codec::Encode;
+    use gstd::{errors::ContractError, msg, prog};
+
+    static mut NONCE: u64 = 0;
+    static mut NOTIFICATIONS: u32 = 0;
+
+    #[gstd::async_main]
+    async fn main() {
+        match msg::load().expect("Failed to decode request") {
+            Request::Deploy(code_hash, behavior) => {
+                let salt = unsafe {
+                    NONCE += 1;
+                    NONCE
+                };
+
+                let res = prog::create_program_for_reply(
+                    code_hash.into(),
+                    salt.to_le_bytes(),
+                    Init::Child(behavior).encode(),
+                    0,
+                )
+                .expect("Failed to create program")
+                .await;
+
+                let outcome = match res {
+                    Ok((program, payload)) => Outcome::Deployed {
+                        program: program.into(),
+                        payload,
+                        notifications: unsafe { NOTIFICATIONS },
+                    },
+                    Err(ContractError::ExitCode(code)) => Outcome::Failed(code),
+                    Err(err) => panic!("Unexpected error: {}", err),
+                };
+
+                msg::reply(outcome, 0).expect("Failed to reply");
+            }
+            Request::Notify => unsafe { NOTIFICATIONS += 1 },
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn init() {
+        match msg::load().expect("Failed to decode init") {
+            Init::Factory => {}
+            Init::Child(Behavior::Reply) => {
+                msg::reply_bytes(CONFIG_DIGEST, 0).expect("Failed to reply");
+            }
+            Init::Child(Behavior::ReplyAndPanic) => {
+                msg::reply_bytes(CONFIG_DIGEST, 0).expect("Failed to reply");
+                panic!("Failed after reply");
+            }
+            Init::Child(Behavior::NotifyAndReply) => {
+                msg::send(msg::source(), Request::Notify, 0).expect("Failed to notify");
+                msg::reply_bytes(CONFIG_DIGEST, 0).expect("Failed to reply");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{Behavior, Init, Outcome, Request, CONFIG_DIGEST, WASM_BINARY};
+    use gtest::{Program, RunResult, System};
+    use std::io::Write;
+
+    const USER: u64 = 42;
+
+    fn prepare(system: &System) -> (Program, [u8; 32]) {
+        let mut path = std::env::temp_dir();
+        path.push("demo_init_reply_factory.wasm");
+        std::fs::File::create(&path)
+            .and_then(|mut file| file.write_all(WASM_BINARY))
+            .expect("Failed to write code");
+
+        let mut code_hash = [0; 32];
+        code_hash.copy_from_slice(system.submit_code(&path).as_ref());
+
+        let factory = Program::current(system);
+        let res = factory.send(USER, Init::Factory);
+        assert!(!res.main_failed());
+
+        (factory, code_hash)
+    }
+
+    fn outcome(res: &RunResult) -> Outcome {
+        let log = res
+            .log()
+            .iter()
+            .find(|log| log.destination() == USER.into())
+            .expect("No reply to user");
+
+        codec::Decode::decode(&mut log.payload()).expect("Failed to decode outcome")
+    }
+
+    #[test]
+    fn factory_gets_init_reply_payload() {
+        let system = System::new();
+        system.init_logger();
+
+        let (factory, code_hash) = prepare(&system);
+
+        let res = factory.send(USER, Request::Deploy(code_hash, Behavior::Reply));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+
+        match outcome(&res) {
+            Outcome::Deployed {
+                program, payload, ..
+            } => {
+                assert!(system.is_active_program(program));
+                assert_eq!(payload, CONFIG_DIGEST);
+            }
+            outcome => panic!("Unexpected outcome: {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn failed_init_reply_is_error() {
+        let system = System::new();
+        system.init_logger();
+
+        let (factory, code_hash) = prepare(&system);
+
+        let res = factory.send(USER, Request::Deploy(code_hash, Behavior::ReplyAndPanic));
+        assert!(!res.main_failed());
+        assert!(res.others_failed());
+        assert!(matches!(outcome(&res), Outcome::Failed(code) if code != 0));
+    }
+
+    #[test]
+    fn init_message_and_reply_both_delivered() {
+        let system = System::new();
+        system.init_logger();
+
+        let (factory, code_hash) = prepare(&system);
+
+        let res = factory.send(USER, Request::Deploy(code_hash, Behavior::NotifyAndReply));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+
+        match outcome(&res) {
+            Outcome::Deployed {
+                payload,
+                notifications,
+                ..
+            } => {
+                assert_eq!(payload, CONFIG_DIGEST);
+                assert_eq!(notifications, 1);
+            }
+            outcome => panic!("Unexpected outcome: {:?}", outcome),
+        }
+    }
+}
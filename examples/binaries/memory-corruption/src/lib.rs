@@ -0,0 +1,106 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Encode, Debug, Decode, PartialEq, Eq)]
+pub enum Request {
+    /// Write to the last byte of the buffer.
+    WriteInBounds,
+    /// Write past the buffer, right after the end of the static data.
+    WritePastEnd,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Request;
+    use gstd::msg;
+
+    extern "C" {
+        static __heap_base: u8;
+    }
+
+    static mut BUFFER: [u8; 16] = [0; 16];
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        let buffer = BUFFER.as_mut_ptr();
+
+        let index = match msg::load().expect("Failed to load request") {
+            Request::WriteInBounds => BUFFER.len() - 1,
+            Request::WritePastEnd => &__heap_base as *const u8 as usize - buffer as usize,
+        };
+
+        buffer.add(index).write(0xff);
+
+        // Corruption is detected once the program calls any syscall.
+        msg::reply(b"written", 0).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Request;
+    use gtest::{Log, Program, System};
+
+    const USER: u64 = 42;
+
+    #[test]
+    fn write_in_bounds() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let _res = program.send_bytes(USER, b"init");
+
+        let res = program.send(USER, Request::WriteInBounds);
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload_bytes("written");
+        assert!(res.contains(&log));
+    }
+
+    #[test]
+    fn write_past_end_detected() {
+        let system = System::new();
+        system.init_logger();
+
+        let program = Program::current(&system);
+        let _res = program.send_bytes(USER, b"init");
+
+        let res = program.send(USER, Request::WritePastEnd);
+        assert!(res.main_failed());
+
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload_bytes("Memory corruption detected in static data");
+        assert!(res.contains(&log));
+    }
+}
@@ -0,0 +1,157 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Program relaying messages along a chain of hops and reporting who
+//! it sees as the source and the origin of each message.
+//!
+//! Every hop sends its [`Observed`] pair to the origin and forwards
+//! the message to the next hop, if any, given on init.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gstd::ActorId;
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+/// Source and origin of the message as seen by the hop.
+#[derive(Debug, Encode, Decode, PartialEq, Eq)]
+pub struct Observed {
+    pub hop: ActorId,
+    pub source: ActorId,
+    pub origin: ActorId,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Observed;
+    use gstd::{exec, msg, ActorId};
+
+    static mut NEXT: Option<ActorId> = None;
+
+    #[no_mangle]
+    unsafe extern "C" fn init() {
+        NEXT = msg::load().expect("Failed to decode next hop");
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        let observed = Observed {
+            hop: exec::program_id(),
+            source: msg::source(),
+            origin: exec::origin(),
+        };
+        msg::send(observed.origin, observed, 0).expect("Failed to report");
+
+        if let Some(next) = NEXT {
+            msg::send_bytes(next, [], 0).expect("Failed to forward");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Observed;
+    use codec::Decode;
+    use gstd::ActorId;
+    use gtest::{Program, RunResult, System};
+    use std::vec::Vec;
+
+    const USER: u64 = 10;
+    const RELAYER: u64 = 20;
+    const HOPS: [u64; 3] = [1, 2, 3];
+
+    /// Deploys the chain of hops, returning the first one.
+    fn init_chain(system: &System) -> Program {
+        system.init_logger();
+
+        let mut next: Option<ActorId> = None;
+        for &id in HOPS.iter().rev() {
+            let hop = Program::current_with_id(system, id);
+            let res = hop.send(USER, next);
+            assert!(!res.main_failed());
+            next = Some(id.into());
+        }
+
+        system.get_program(HOPS[0])
+    }
+
+    fn observed(res: &RunResult) -> Vec<Observed> {
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+
+        res.log()
+            .iter()
+            .map(|log| Observed::decode(&mut log.payload()).expect("Failed to decode report"))
+            .collect()
+    }
+
+    /// Pairs documented for the message sent by `source` on behalf of `origin`.
+    fn expected(source: u64, origin: u64) -> Vec<Observed> {
+        let sources = [source, HOPS[0], HOPS[1]];
+        HOPS.iter()
+            .zip(sources)
+            .map(|(&hop, source)| Observed {
+                hop: hop.into(),
+                source: source.into(),
+                origin: origin.into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn origin_is_source_for_direct_sends() {
+        let system = System::new();
+        let first = init_chain(&system);
+
+        let res = first.send_bytes(USER, []);
+        assert_eq!(observed(&res), expected(USER, USER));
+
+        // Same as sending on own behalf.
+        let res = first.send_as(USER, USER, (), 0);
+        assert_eq!(observed(&res), expected(USER, USER));
+    }
+
+    #[test]
+    fn origin_kept_across_hops_when_overridden() {
+        let system = System::new();
+        let first = init_chain(&system);
+
+        system.allow_origin_override(true);
+
+        let res = first.send_as(RELAYER, USER, (), 0);
+        assert_eq!(observed(&res), expected(RELAYER, USER));
+    }
+
+    #[test]
+    #[should_panic(expected = "origin override isn't allowed")]
+    fn origin_override_denied_by_default() {
+        let system = System::new();
+        let first = init_chain(&system);
+
+        first.send_as(RELAYER, USER, (), 0);
+    }
+}
@@ -0,0 +1,125 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Encode, Debug, Decode, PartialEq, Eq)]
+pub enum Request {
+    /// Handled only for the owner, replying with `b"withdrawn"`.
+    Withdraw,
+    /// Reply whether the message is sent by the program itself.
+    IsSelf,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Request;
+    use gstd::{msg, ownership, ActorId};
+
+    static mut OWNER: ActorId = ActorId::zero();
+
+    #[gstd::only_owner(unsafe { OWNER })]
+    fn withdraw() {
+        msg::reply_bytes(b"withdrawn", 0).unwrap();
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn init() {
+        OWNER = msg::source();
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        match msg::load().unwrap() {
+            Request::Withdraw => withdraw(),
+            Request::IsSelf => {
+                msg::reply(ownership::only_self().is_ok(), 0).unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Request;
+    use gstd::{msg::ERROR_REPLY_CODE, ownership::AccessDenied};
+    use gtest::{Log, Program, System};
+
+    const OWNER: u64 = 42;
+    const STRANGER: u64 = 43;
+
+    fn init_program(system: &System) -> Program {
+        system.init_logger();
+
+        let program = Program::current(system);
+        let _res = program.send_bytes(OWNER, b"init");
+
+        program
+    }
+
+    #[test]
+    fn owner_allowed() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send(OWNER, Request::Withdraw);
+        let log = Log::builder()
+            .source(program.id())
+            .dest(OWNER)
+            .payload_bytes(b"withdrawn");
+        assert!(res.contains(&log));
+    }
+
+    #[test]
+    fn stranger_denied() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send(STRANGER, Request::Withdraw);
+        assert!(!res.main_failed());
+
+        let log = Log::error_builder(ERROR_REPLY_CODE.to_raw())
+            .source(program.id())
+            .dest(STRANGER)
+            .payload(AccessDenied);
+        assert!(res.contains(&log));
+    }
+
+    #[test]
+    fn only_self() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let res = program.send(OWNER, Request::IsSelf);
+        let log = Log::builder()
+            .source(program.id())
+            .dest(OWNER)
+            .payload(false);
+        assert!(res.contains(&log));
+    }
+}
@@ -41,6 +41,16 @@ pub enum CreateProgram {
     Default,
     // code hash, salt, gas limit
     Custom(Vec<([u8; 32], Vec<u8>, u64)>),
+    // nonce
+    WithNonce(u64),
+    // code hash, salt, gas limit; replies with `Result<program id, existing program id>`
+    Checked([u8; 32], Vec<u8>, u64),
+    // salts; replies with `Result<program ids, index of the failed salt>`
+    Batch(Vec<Vec<u8>>),
+    // number of programs; replies with `(predicted id, created id)` of each
+    Predicted(u32),
+    // salt; replies with `(program id, init message id)`
+    WithInitId(Vec<u8>),
 }
 
 #[allow(unused)]
@@ -50,7 +60,13 @@ const CHILD_CODE_HASH: [u8; 32] =
 #[cfg(not(feature = "std"))]
 mod wasm {
     use super::{CreateProgram, CHILD_CODE_HASH};
-    use gstd::{debug, msg, prog, ActorId, CodeHash};
+    use gstd::{
+        debug,
+        errors::ContractError,
+        msg, prog,
+        prog::{ProgramBuilder, ProgramGenerator},
+        ActorId, CodeHash,
+    };
 
     static mut COUNTER: i32 = 0;
     static mut ORIGIN: Option<ActorId> = None;
@@ -86,6 +102,65 @@ mod wasm {
                     let msg_id = msg::send_bytes(new_program_id, [], 0).unwrap();
                 }
             }
+            CreateProgram::WithNonce(nonce) => {
+                let submitted_code = CHILD_CODE_HASH.into();
+                let new_program_id = ProgramGenerator::create_program_with_gas_and_nonce(
+                    submitted_code,
+                    nonce,
+                    [],
+                    10_000_000_000,
+                    0,
+                )
+                .unwrap();
+                msg::send_bytes(new_program_id, [], 0).unwrap();
+            }
+            CreateProgram::Checked(code_hash, salt, gas_limit) => {
+                let res: Result<[u8; 32], [u8; 32]> =
+                    match prog::create_program_with_gas(code_hash.into(), salt, [], gas_limit, 0) {
+                        Ok(new_program_id) => Ok(new_program_id.into()),
+                        Err(ContractError::ProgramAlreadyExists(id)) => Err(id.into()),
+                        Err(err) => panic!("Unexpected error: {}", err),
+                    };
+                msg::reply(res, 0).unwrap();
+            }
+            CreateProgram::Batch(salts) => {
+                let entries = salts.into_iter().map(|salt| (salt, b"", 0));
+                let res: Result<Vec<[u8; 32]>, u32> =
+                    match prog::create_program_batch(CHILD_CODE_HASH.into(), entries) {
+                        Ok(ids) => Ok(ids.into_iter().map(Into::into).collect()),
+                        Err(ContractError::BatchEntry(index, _)) => Err(index as u32),
+                        Err(err) => panic!("Unexpected error: {}", err),
+                    };
+                msg::reply(res, 0).unwrap();
+            }
+            CreateProgram::Predicted(count) => {
+                let ids: Vec<([u8; 32], [u8; 32])> = (0..count)
+                    .map(|_| {
+                        let predicted =
+                            ProgramGenerator::calculate_program_id(CHILD_CODE_HASH.into());
+                        let created = ProgramGenerator::create_program_with_gas(
+                            CHILD_CODE_HASH.into(),
+                            [],
+                            10_000_000_000,
+                            0,
+                        )
+                        .unwrap();
+
+                        (predicted.into(), created.into())
+                    })
+                    .collect();
+                msg::reply(ids, 0).unwrap();
+            }
+            CreateProgram::WithInitId(salt) => {
+                let created = ProgramBuilder::new()
+                    .salt(&salt)
+                    .with_gas(10_000_000_000)
+                    .create_with_init_id(CHILD_CODE_HASH.into())
+                    .unwrap();
+                let ids: ([u8; 32], [u8; 32]) =
+                    (created.program_id.into(), created.init_message_id.into());
+                msg::reply(ids, 0).unwrap();
+            }
         };
     }
 
@@ -101,9 +176,11 @@ mod wasm {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gtest::{calculate_program_id, Program, System};
+    use gtest::{calculate_program_id, Log, Program, System};
     use std::io::Write;
 
+    const FACTORY_ID: u64 = 100;
+
     // Creates a new factory and initializes it.
     fn prepare_factory(sys: &System) -> Program {
         // Store child
@@ -111,12 +188,12 @@ mod tests {
         assert_eq!(code_hash_stored, CHILD_CODE_HASH.into());
 
         // Instantiate factory
-        let factory = Program::current_with_id(sys, 100);
+        let factory = Program::current_with_id(sys, FACTORY_ID);
 
         // Send `init` msg to factory
         let res = factory.send_bytes(10001, "EMPTY");
         assert!(!res.main_failed());
-        assert!(sys.is_active_program(100));
+        assert!(sys.is_active_program(FACTORY_ID));
 
         factory
     }
@@ -148,6 +225,26 @@ mod tests {
         assert!(sys.is_active_program(child_id_expected));
     }
 
+    #[test]
+    fn test_nonce_reproducible() {
+        let nonce = 42;
+        let child_id_expected = calculate_program_id(
+            CHILD_CODE_HASH.into(),
+            &gstd::prog::ProgramGenerator::salt_from_nonce(FACTORY_ID.into(), nonce),
+        );
+
+        // Every run creates the child with the same id.
+        for _ in 0..2 {
+            let sys = System::new();
+            let factory = prepare_factory(&sys);
+
+            let res = factory.send_bytes(10001, CreateProgram::WithNonce(nonce).encode());
+            assert!(!res.main_failed());
+            assert!(!res.others_failed());
+            assert!(sys.is_active_program(child_id_expected));
+        }
+    }
+
     #[test]
     fn test_duplicate() {
         let sys = System::new();
@@ -164,13 +261,147 @@ mod tests {
         assert!(sys.is_active_program(child_id_expected));
 
         // Send `handle` msg to create a duplicate
+        let res = factory.send_bytes(10001, payload.encode());
+        // Creation fails right in the syscall, so neither init nor dispatch
+        // message is sent and the factory panics on unwrapping the error.
+        assert!(res.main_failed());
+        assert_eq!(res.total_processed(), 1); // the original message, initiated by user
+    }
+
+    #[test]
+    fn test_duplicate_within_execution() {
+        let sys = System::new();
+        let factory = prepare_factory(&sys);
+
+        let salt = 1i32.to_be_bytes();
+        let child_id_expected = calculate_program_id(CHILD_CODE_HASH.into(), &salt);
+        let child = (CHILD_CODE_HASH, salt.to_vec(), 100_000);
+        let payload = CreateProgram::Custom(vec![child.clone(), child]);
+
+        // The second child collides with the first one, which isn't stored
+        // yet, so the factory panics and no message is sent.
+        let res = factory.send_bytes(10001, payload.encode());
+        assert!(res.main_failed());
+        assert_eq!(res.total_processed(), 1);
+        assert!(!sys.is_active_program(child_id_expected));
+    }
+
+    #[test]
+    fn test_duplicate_reported() {
+        let sys = System::new();
+        let factory = prepare_factory(&sys);
+
+        let salt = b"intentional collision".to_vec();
+        let child_id_expected = calculate_program_id(CHILD_CODE_HASH.into(), &salt);
+        let payload = CreateProgram::Checked(CHILD_CODE_HASH, salt, 10_000_000_000);
+
         let res = factory.send_bytes(10001, payload.encode());
         assert!(!res.main_failed());
         assert!(!res.others_failed());
-        // Init message, which is not processed. Error reply for that init is generated.
-        // Dispatch message is processed, no error replies, because message is sent to
-        // the original program.
-        assert_eq!(res.total_processed(), 3 + 1); // +1 for the original message, initiated by user
+        assert!(res.contains(
+            &Log::builder()
+                .dest(10001)
+                .payload(Ok::<[u8; 32], [u8; 32]>(child_id_expected.into()))
+        ));
+
+        // Collision is reported with the id of the existing program.
+        let res = factory.send_bytes(10001, payload.encode());
+        assert!(!res.main_failed());
+        assert!(res.contains(
+            &Log::builder()
+                .dest(10001)
+                .payload(Err::<[u8; 32], [u8; 32]>(child_id_expected.into()))
+        ));
+
+        // No init message is sent for the duplicate.
+        assert_eq!(res.total_processed(), 1);
+    }
+
+    #[test]
+    fn test_batch() {
+        let sys = System::new();
+        let factory = prepare_factory(&sys);
+
+        let batch_reply = |salts: &[&[u8]], expected: Result<Vec<[u8; 32]>, u32>| {
+            let payload = CreateProgram::Batch(salts.iter().map(|salt| salt.to_vec()).collect());
+
+            let res = factory.send_bytes(10001, payload.encode());
+            assert!(!res.main_failed());
+            assert!(!res.others_failed());
+            assert!(res.contains(&Log::builder().dest(10001).payload(expected)));
+        };
+        let child_id = |salt: &[u8]| calculate_program_id(CHILD_CODE_HASH.into(), salt);
+
+        // Empty batch creates nothing.
+        batch_reply(&[], Ok(vec![]));
+
+        // Ids are derived from salts in the order of entries.
+        batch_reply(
+            &[b"first", b"second"],
+            Ok(vec![child_id(b"first").into(), child_id(b"second").into()]),
+        );
+        assert!(sys.is_active_program(child_id(b"first")));
+        assert!(sys.is_active_program(child_id(b"second")));
+
+        // Collision stops the batch, keeping programs of the preceding entries.
+        batch_reply(&[b"third", b"first", b"fourth"], Err(1));
+        assert!(sys.is_active_program(child_id(b"third")));
+        assert!(!sys.is_active_program(child_id(b"fourth")));
+    }
+
+    #[test]
+    fn test_predicted() {
+        let sys = System::new();
+        let factory = prepare_factory(&sys);
+
+        let res = factory.send_bytes(10001, CreateProgram::Predicted(3).encode());
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+
+        let reply = res
+            .log()
+            .iter()
+            .find(|log| log.destination() == 10001.into())
+            .expect("No reply from factory");
+        let ids = Vec::<([u8; 32], [u8; 32])>::decode(&mut reply.payload())
+            .expect("Failed to decode ids");
+        assert_eq!(ids.len(), 3);
+
+        // Prediction doesn't consume the salt, so it's used by the creation.
+        for (predicted, created) in &ids {
+            assert_eq!(predicted, created);
+            assert!(sys.is_active_program(*created));
+        }
+
+        let unique: std::collections::BTreeSet<_> = ids.iter().map(|(_, id)| id).collect();
+        assert_eq!(unique.len(), ids.len());
+    }
+
+    #[test]
+    fn test_init_message_id() {
+        let sys = System::new();
+        let factory = prepare_factory(&sys);
+
+        let salt = b"with init id".to_vec();
+        let res = factory.send_bytes(10001, CreateProgram::WithInitId(salt.clone()).encode());
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+
+        let reply = res
+            .log()
+            .iter()
+            .find(|log| log.destination() == 10001.into())
+            .expect("No reply from factory");
+        let (program_id, init_message_id) =
+            <([u8; 32], [u8; 32])>::decode(&mut reply.payload()).expect("Failed to decode ids");
+
+        assert_eq!(
+            program_id,
+            <[u8; 32]>::from(calculate_program_id(CHILD_CODE_HASH.into(), &salt))
+        );
+        assert!(sys.is_active_program(program_id));
+        assert_ne!(init_message_id, [0; 32]);
+        assert_ne!(init_message_id, program_id);
     }
 
     #[test]
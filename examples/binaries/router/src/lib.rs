@@ -0,0 +1,117 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Router dispatching on the first byte of the payload, which is either
+//! loaded with the whole payload or peeked with [`gstd::msg::discriminant`].
+//!
+//! Used to compare gas burned by both ways for a large payload. Loading
+//! copies the whole payload into the program's memory, while peeking reads
+//! only the leading bytes once per message.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+/// Way of getting the first byte of the payload, chosen on init.
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+pub enum Mode {
+    Load,
+    Peek,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Mode;
+    use gstd::msg;
+
+    static mut MODE: Mode = Mode::Load;
+
+    #[no_mangle]
+    unsafe extern "C" fn init() {
+        MODE = msg::load().expect("Failed to decode mode");
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        let route = match MODE {
+            Mode::Load => msg::load_bytes().first().copied(),
+            Mode::Peek => msg::discriminant(),
+        };
+
+        // Peeking again is served from the cache.
+        if let Mode::Peek = MODE {
+            assert_eq!(msg::discriminant(), route);
+        }
+
+        msg::reply(route, 0).expect("Failed to reply");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Mode;
+    use gtest::{Gas, Log, Program, System};
+    use std::vec;
+
+    const USER: u64 = 42;
+
+    fn route(system: &System, mode: Mode, payload: &[u8]) -> Gas {
+        let program = Program::current(system);
+        let res = program.send(USER, mode);
+        assert!(!res.main_failed());
+
+        let res = program.send_bytes(USER, payload);
+        assert!(!res.main_failed());
+        assert!(res.contains(&Log::builder().dest(USER).payload(payload.first().copied())));
+
+        res.main_gas_burned()
+    }
+
+    #[test]
+    fn peek_is_cheaper_for_large_payload() {
+        let system = System::new();
+        system.init_logger();
+
+        let mut payload = vec![0; 16 * 1024];
+        payload[0] = 7;
+
+        let loaded = route(&system, Mode::Load, &payload);
+        let peeked = route(&system, Mode::Peek, &payload);
+
+        assert!(peeked < loaded);
+    }
+
+    #[test]
+    fn empty_payload_has_no_route() {
+        let system = System::new();
+        system.init_logger();
+
+        route(&system, Mode::Load, &[]);
+        route(&system, Mode::Peek, &[]);
+    }
+}
@@ -0,0 +1,144 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gstd::MessageId;
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Encode, Debug, Decode, PartialEq, Eq)]
+pub enum Request {
+    /// Start the timer for given amount of blocks and reply
+    /// whether it fired once resolved.
+    Start(u32),
+    /// Cancel the last started timer, replying whether it was cancelled.
+    CancelLast,
+    /// Wake the message, as if its waiting timed out.
+    Wake(MessageId),
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Request;
+    use gstd::{
+        exec, msg,
+        timer::{self, Timer, TimerId},
+    };
+
+    static mut LAST_TIMER: Option<TimerId> = None;
+
+    #[gstd::async_main]
+    async fn main() {
+        match msg::load().unwrap() {
+            Request::Start(blocks) => {
+                let timer = Timer::new(blocks, ());
+                unsafe { LAST_TIMER = Some(timer.id()) };
+
+                msg::reply(timer.await.is_some(), 0).unwrap();
+            }
+            Request::CancelLast => {
+                let cancelled = unsafe { LAST_TIMER.take() }
+                    .map(timer::cancel)
+                    .unwrap_or_default();
+
+                msg::reply(cancelled, 0).unwrap();
+            }
+            Request::Wake(message_id) => exec::wake(message_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Request;
+    use std::convert::TryInto;
+
+    use gstd::MessageId;
+    use gtest::{Log, Program, RunResult, System};
+
+    const USER: u64 = 42;
+    const BLOCKS: u32 = 10;
+
+    fn init_program(system: &System) -> Program {
+        system.init_logger();
+
+        let program = Program::current(system);
+        let _res = program.send_bytes(USER, b"init");
+
+        program
+    }
+
+    fn start(program: &Program) -> MessageId {
+        let res = program.send(USER, Request::Start(BLOCKS));
+        assert!(res.log().is_empty());
+
+        MessageId::new(res.sent_message_id().as_ref().try_into().unwrap())
+    }
+
+    fn replied(program: &Program, res: &RunResult, fired: bool) -> bool {
+        let log = Log::builder()
+            .source(program.id())
+            .dest(USER)
+            .payload(fired);
+
+        res.contains(&log)
+    }
+
+    #[test]
+    fn fires_after_blocks() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let timer_msg_id = start(&program);
+
+        // Woken too early, so continues waiting.
+        let res = program.send(USER, Request::Wake(timer_msg_id));
+        assert!(res.log().is_empty());
+
+        system.spend_blocks(BLOCKS);
+
+        let res = program.send(USER, Request::Wake(timer_msg_id));
+        assert!(replied(&program, &res, true));
+    }
+
+    #[test]
+    fn cancelled() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let _timer_msg_id = start(&program);
+
+        // Both cancelling and timer messages reply `true` and `false` accordingly.
+        let res = program.send(USER, Request::CancelLast);
+        assert!(replied(&program, &res, true));
+        assert!(replied(&program, &res, false));
+
+        let res = program.send(USER, Request::CancelLast);
+        assert!(replied(&program, &res, false));
+    }
+}
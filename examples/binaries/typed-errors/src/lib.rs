@@ -0,0 +1,148 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Divider replying with a typed error, and a relay decoding the divider's
+//! reply with [`gstd::msg::CodecMessageFuture::with_errors`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Debug, Encode, Decode)]
+pub enum Request {
+    /// Divide the first number by the second one.
+    Divide(u32, u32),
+    /// Ask the divider program to divide and reply with the [`Outcome`].
+    Relay([u8; 32], u32, u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum DivisionError {
+    ByZero,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+pub enum Outcome {
+    Quotient(u32),
+    Failed(DivisionError),
+    System(i32),
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::{DivisionError, Outcome, Request};
+    use gstd::{errors::ProgramError, msg, ActorId};
+
+    #[gstd::async_main]
+    async fn main() {
+        match msg::load().expect("Failed to decode request") {
+            Request::Divide(_, 0) => {
+                msg::reply_err(DivisionError::ByZero, 0).expect("Failed to reply");
+            }
+            Request::Divide(a, b) => {
+                msg::reply(a / b, 0).expect("Failed to reply");
+            }
+            Request::Relay(divider, a, b) => {
+                let res = msg::send_for_reply_as::<_, u32>(
+                    ActorId::new(divider),
+                    Request::Divide(a, b),
+                    0,
+                )
+                .expect("Failed to send")
+                .with_errors::<DivisionError>()
+                .await;
+
+                let outcome = match res {
+                    Ok(quotient) => Outcome::Quotient(quotient),
+                    Err(ProgramError::Program(err)) => Outcome::Failed(err),
+                    Err(ProgramError::System(code)) => Outcome::System(code.to_raw()),
+                    Err(ProgramError::Contract(err)) => panic!("Failed to decode: {}", err),
+                };
+
+                msg::reply(outcome, 0).expect("Failed to reply");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{DivisionError, Outcome, Request};
+    use codec::Encode;
+    use gstd::msg::ERROR_REPLY_CODE;
+    use gtest::{Log, Program, System};
+
+    const USER: u64 = 42;
+
+    fn programs(system: &System) -> (Program, Program) {
+        let divider = Program::current(system);
+        let relay = Program::current(system);
+
+        let _res = divider.send_bytes(USER, b"init");
+        let _res = relay.send_bytes(USER, b"init");
+
+        (divider, relay)
+    }
+
+    #[test]
+    fn divider_replies_with_error_code() {
+        let system = System::new();
+        system.init_logger();
+
+        let (divider, _) = programs(&system);
+
+        let res = divider.send(USER, Request::Divide(7, 0));
+        assert!(!res.main_failed());
+        assert!(res.log().iter().any(|log| {
+            log.exit_code() == Some(ERROR_REPLY_CODE.to_raw())
+                && log.payload() == DivisionError::ByZero.encode()
+        }));
+    }
+
+    #[test]
+    fn relay_decodes_quotient_and_error() {
+        let system = System::new();
+        system.init_logger();
+
+        let (divider, relay) = programs(&system);
+        let mut divider_id = [0; 32];
+        divider_id.copy_from_slice(divider.id().as_ref());
+
+        let res = relay.send(USER, Request::Relay(divider_id, 7, 2));
+        assert!(!res.main_failed());
+        assert!(res.contains(&Log::builder().dest(USER).payload(Outcome::Quotient(3))));
+
+        let res = relay.send(USER, Request::Relay(divider_id, 7, 0));
+        assert!(!res.main_failed());
+        assert!(res.contains(
+            &Log::builder()
+                .dest(USER)
+                .payload(Outcome::Failed(DivisionError::ByZero))
+        ));
+    }
+}
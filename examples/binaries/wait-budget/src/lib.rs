@@ -0,0 +1,217 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Program parking a message in the waitlist for as long as its gas
+//! allows, so the waitlist rent can be observed.
+//!
+//! The parked message is sent by the program to itself with the gas given
+//! by the user. Being woken, it reports the wait budget left to the user.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use gstd::MessageId;
+
+#[cfg(feature = "std")]
+mod code {
+    include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+}
+
+#[cfg(feature = "std")]
+pub use code::WASM_BINARY_OPT as WASM_BINARY;
+
+#[derive(Debug, Encode, Decode)]
+pub enum Request {
+    /// Park the message with the given amount of gas.
+    Park(u64),
+    /// Wait while parked, report the budget left once woken.
+    Wait,
+    /// Wake the parked message.
+    Wake(MessageId),
+    /// Reply with the wait budget of the current message.
+    Budget,
+}
+
+#[cfg(not(feature = "std"))]
+mod wasm {
+    use super::Request;
+    use gstd::{exec, msg, ActorId};
+
+    static mut OWNER: Option<ActorId> = None;
+
+    #[no_mangle]
+    unsafe extern "C" fn init() {}
+
+    #[no_mangle]
+    unsafe extern "C" fn handle() {
+        match msg::load().expect("Failed to decode request") {
+            Request::Park(gas) => {
+                OWNER = Some(msg::source());
+                msg::send_with_gas(exec::program_id(), Request::Wait, gas, 0)
+                    .expect("Failed to park the message");
+            }
+            Request::Wait if exec::resumed_from_wait() => {
+                let owner = OWNER.expect("Owner is set on parking");
+                msg::send(owner, exec::wait_budget_remaining(), 0)
+                    .expect("Failed to report the budget");
+            }
+            Request::Wait => exec::wait(),
+            Request::Wake(message_id) => exec::wake(message_id),
+            Request::Budget => {
+                msg::reply(exec::wait_budget_remaining(), 0).expect("Failed to reply");
+            }
+        }
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn handle_signal() {}
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::Request;
+    use codec::Decode;
+    use gear_core::{
+        ids::MessageId,
+        message::{SignalCode, SignalMessage},
+    };
+    use gtest::{JournalEntry, Program, RunResult, System, RESERVE_FOR, WAITLIST_COST};
+    use std::convert::TryInto;
+
+    const USER: u64 = 42;
+    const GAS: u64 = 1_000_000_000;
+
+    /// Parks the message, returning its id and the budget it waits with.
+    fn park(program: &Program) -> (MessageId, u32) {
+        let res = program.send(USER, Request::Park(GAS));
+        assert!(!res.main_failed());
+
+        let parked_id = res
+            .journal()
+            .iter()
+            .find_map(|entry| match entry {
+                JournalEntry::Sent {
+                    message_id,
+                    destination,
+                    ..
+                } if *destination == program.id() => Some(*message_id),
+                _ => None,
+            })
+            .expect("Parked message is sent");
+
+        let gas_left = GAS - gas_burned(&res, parked_id);
+        let budget = (gas_left / WAITLIST_COST) as u32 - RESERVE_FOR;
+
+        (parked_id, budget)
+    }
+
+    fn wake(message_id: MessageId) -> Request {
+        Request::Wake(gstd::MessageId::new(
+            message_id.as_ref().try_into().unwrap(),
+        ))
+    }
+
+    fn gas_burned(res: &RunResult, id: MessageId) -> u64 {
+        res.journal()
+            .iter()
+            .map(|entry| match entry {
+                JournalEntry::GasBurned { message_id, amount } if *message_id == id => *amount,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn init_program(system: &System) -> Program {
+        system.init_logger();
+        system.record_journal(true);
+
+        let program = Program::current(system);
+        let res = program.send_bytes(USER, b"");
+        assert!(!res.main_failed());
+
+        program
+    }
+
+    #[test]
+    fn removed_once_budget_exhausted() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        let (parked_id, budget) = park(&program);
+
+        assert!(system.spend_blocks(budget - 1).is_empty());
+
+        let results = system.spend_blocks(1);
+        assert_eq!(results.len(), 1);
+
+        let res = &results[0];
+        assert_eq!(res.sent_message_id(), parked_id);
+
+        let signal_id = SignalMessage::with_code(parked_id, SignalCode::OutOfGasInWaitlist).id();
+        assert!(res.journal().iter().any(|entry| matches!(
+            entry,
+            JournalEntry::Dispatched { message_id, .. } if *message_id == signal_id
+        )));
+
+        // Nothing left to wake.
+        let res = program.send(USER, wake(parked_id));
+        assert!(res.log().is_empty());
+    }
+
+    #[test]
+    fn woken_message_reports_budget_left() {
+        const BLOCKS: u32 = 10;
+
+        let system = System::new();
+        let program = init_program(&system);
+
+        let (parked_id, budget) = park(&program);
+
+        assert!(system.spend_blocks(BLOCKS).is_empty());
+
+        let res = program.send(USER, wake(parked_id));
+        let report = res
+            .log()
+            .iter()
+            .find(|log| log.source() == program.id())
+            .expect("Budget is reported");
+        let reported = u32::decode(&mut report.payload()).expect("Failed to decode budget");
+
+        // Rent for the spent blocks is charged, as well as gas burned
+        // after being woken.
+        let burned_blocks: u32 = (gas_burned(&res, parked_id) / WAITLIST_COST)
+            .try_into()
+            .unwrap();
+        assert!(reported <= budget - BLOCKS);
+        assert!(reported >= budget - BLOCKS - burned_blocks - 1);
+    }
+
+    #[test]
+    fn user_message_budget_is_unbounded() {
+        let system = System::new();
+        let program = init_program(&system);
+
+        // Users send messages with the maximal gas limit.
+        let res = program.send(USER, Request::Budget);
+        let reply = res.log().first().expect("Reply is sent");
+        let budget = u32::decode(&mut reply.payload()).expect("Failed to decode budget");
+        assert_eq!(budget, u32::MAX);
+    }
+}
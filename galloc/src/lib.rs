@@ -21,8 +21,20 @@
 #![doc(html_logo_url = "https://docs.gear.rs/logo.svg")]
 
 // until https://github.com/alexcrichton/dlmalloc-rs/pull/26 is merged
-#[cfg(not(windows))]
+#[cfg(all(
+    not(windows),
+    any(not(feature = "profiler"), not(target_arch = "wasm32"))
+))]
 #[global_allocator]
 pub static ALLOC: dlmalloc::GlobalDlmalloc = dlmalloc::GlobalDlmalloc;
 
+// Native tests run in several threads, while the profiler keeps its
+// statistics in plain statics, so it's only installed for programs.
+#[cfg(all(target_arch = "wasm32", feature = "profiler"))]
+#[global_allocator]
+pub static ALLOC: profiler::Profiler<dlmalloc::GlobalDlmalloc> =
+    profiler::Profiler(dlmalloc::GlobalDlmalloc);
+
 pub mod prelude;
+#[cfg(feature = "profiler")]
+pub mod profiler;
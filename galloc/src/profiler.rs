@@ -0,0 +1,175 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Allocation profiler.
+//!
+//! Enabled with the `profiler` feature, which wraps the global allocator so
+//! that every allocation is recorded into a histogram of sizes and a fixed
+//! set of the largest allocations. Allocations are attributed to the scope
+//! tag set with [`set_tag`] at the moment they happen.
+//!
+//! Recording doesn't allocate itself, so it's safe to call from within the
+//! allocator. Programs are executed in a single thread, so the statistics
+//! are kept in plain statics. For the same reason allocations are only
+//! recorded on `wasm32`: on other targets the functions of the module do
+//! nothing and the statistics stay empty.
+
+#[cfg(target_arch = "wasm32")]
+use core::alloc::{GlobalAlloc, Layout};
+
+/// Amount of histogram buckets.
+///
+/// `i`-th bucket counts allocations of sizes in `[2^i, 2^(i + 1))`,
+/// while the last one also counts all the larger ones.
+pub const BUCKETS: usize = 20;
+
+/// Amount of the largest allocations kept.
+pub const LARGEST: usize = 16;
+
+/// Tag of allocations made outside of any scope.
+pub const UNTAGGED: &str = "untagged";
+
+/// Recorded allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    /// Size of the allocation in bytes.
+    pub size: usize,
+    /// Scope tag the allocation was made in.
+    pub tag: &'static str,
+}
+
+/// Statistics of allocations.
+#[derive(Clone, Copy, Debug)]
+pub struct Stats {
+    /// Total amount of allocations.
+    pub count: u64,
+    /// Total amount of allocated bytes.
+    pub bytes: u64,
+    /// Amounts of allocations by sizes, see [`BUCKETS`].
+    pub histogram: [u32; BUCKETS],
+    /// The largest allocations in no particular order.
+    pub largest: [Option<Allocation>; LARGEST],
+}
+
+impl Stats {
+    const fn new() -> Self {
+        Self {
+            count: 0,
+            bytes: 0,
+            histogram: [0; BUCKETS],
+            largest: [None; LARGEST],
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn record(&mut self, size: usize, tag: &'static str) {
+        self.count += 1;
+        self.bytes += size as u64;
+
+        let bucket = (usize::BITS - size.leading_zeros()).saturating_sub(1) as usize;
+        self.histogram[bucket.min(BUCKETS - 1)] += 1;
+
+        // Replacing the smallest of the kept allocations, free slots go first.
+        let (smallest, smallest_size) = self
+            .largest
+            .iter()
+            .enumerate()
+            .map(|(i, allocation)| (i, allocation.map(|a| a.size)))
+            .min_by_key(|&(_, size)| size)
+            .expect("LARGEST is non-zero");
+
+        if smallest_size.map_or(true, |smallest_size| size > smallest_size) {
+            self.largest[smallest] = Some(Allocation { size, tag });
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+static mut STATS: Stats = Stats::new();
+#[cfg(target_arch = "wasm32")]
+static mut TAG: &str = UNTAGGED;
+
+/// Set tag of the following allocations, returning the previous one.
+#[cfg(target_arch = "wasm32")]
+pub fn set_tag(tag: &'static str) -> &'static str {
+    unsafe { core::mem::replace(&mut TAG, tag) }
+}
+
+/// Set tag of the following allocations, returning the previous one.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_tag(_tag: &'static str) -> &'static str {
+    UNTAGGED
+}
+
+/// Get statistics of allocations made so far.
+#[cfg(target_arch = "wasm32")]
+pub fn stats() -> Stats {
+    unsafe { STATS }
+}
+
+/// Get statistics of allocations made so far.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn stats() -> Stats {
+    Stats::new()
+}
+
+/// Forget allocations made so far.
+#[cfg(target_arch = "wasm32")]
+pub fn reset() {
+    unsafe { STATS = Stats::new() }
+}
+
+/// Forget allocations made so far.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reset() {}
+
+/// Allocator, recording allocations made with the inner one.
+pub struct Profiler<A>(pub A);
+
+#[cfg(target_arch = "wasm32")]
+impl<A> Profiler<A> {
+    fn record(&self, size: usize) {
+        unsafe { STATS.record(size, TAG) }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Profiler<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.record(layout.size());
+        self.0.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.record(layout.size());
+        self.0.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // Only growth is accounted, as shrinking doesn't take new memory.
+        if new_size > layout.size() {
+            self.record(new_size);
+        }
+
+        self.0.realloc(ptr, layout, new_size)
+    }
+}
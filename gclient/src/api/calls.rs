@@ -445,19 +445,44 @@ impl GearApi {
         Err(Error::EventNotFound)
     }
 
+    /// `pallet_gear::upload_code`, skipped if the code is already in storage.
+    ///
+    /// Block hash is returned only if the code was uploaded. Code uploaded
+    /// by anyone else meanwhile is deduplicated by the pallet, so the call
+    /// doesn't fail in this case too.
+    pub async fn upload_code_if_absent(
+        &self,
+        code: impl AsRef<[u8]>,
+    ) -> Result<(CodeId, Option<H256>)> {
+        let code = code.as_ref();
+        let code_id = CodeId::generate(code);
+
+        if self.code_exists(code_id).await? {
+            return Ok((code_id, None));
+        }
+
+        let tx = self.0.upload_code(code.to_vec()).await?;
+        tx.wait_for_success().await?;
+
+        Ok((code_id, Some(tx.block_hash())))
+    }
+
     /// `pallet_gear::upload_code` batched.
     pub async fn upload_code_batch(
         &self,
         args: impl IntoIterator<Item = impl AsRef<[u8]>>,
     ) -> Result<(Vec<Result<CodeId>>, H256)> {
-        let calls: Vec<_> = args
+        let (code_ids, calls): (Vec<_>, Vec<_>) = args
             .into_iter()
             .map(|code| {
-                RuntimeCall::Gear(GearCall::upload_code {
-                    code: code.as_ref().to_vec(),
-                })
+                let code = code.as_ref().to_vec();
+
+                (
+                    CodeId::generate(&code),
+                    RuntimeCall::Gear(GearCall::upload_code { code }),
+                )
             })
-            .collect();
+            .unzip();
 
         let amount = calls.len();
 
@@ -466,13 +491,14 @@ impl GearApi {
 
         let mut res = Vec::with_capacity(amount);
 
+        // Codes stored before don't emit `CodeChanged`,
+        // so results are matched with the batch items.
         for event in tx.wait_for_success().await?.iter() {
             match event?.event {
-                Event::Gear(GearEvent::CodeChanged {
-                    id,
-                    change: CodeChangeKind::Active { .. },
-                }) => {
-                    res.push(Ok(id.into()));
+                Event::Utility(UtilityEvent::ItemCompleted) => {
+                    if let Some(&code_id) = code_ids.get(res.len()) {
+                        res.push(Ok(code_id));
+                    }
                 }
                 Event::Utility(UtilityEvent::ItemFailed { error }) => res.push(Err(
                     subxt::GenericError::Runtime(subxt::RuntimeError(error)).into(),
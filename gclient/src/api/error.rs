@@ -53,4 +53,10 @@ pub enum Error {
     MaxDepthReached,
     #[error("Event not found in pre-queried events")]
     EventNotFoundInIterator,
+    #[error("Code not found")]
+    CodeNotFound,
+    #[error(transparent)]
+    Wasm(#[from] parity_wasm::elements::Error),
+    #[error("Metadata hash section has wrong length")]
+    WrongMetahashLength(usize),
 }
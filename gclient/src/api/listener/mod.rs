@@ -24,7 +24,7 @@ pub use subscription::*;
 
 use crate::{Error, Result};
 use async_trait::async_trait;
-use gear_core::ids::MessageId;
+use gear_core::{ids::MessageId, message::ReplyCode};
 use gp::api::generated::api::{
     gear::Event as GearEvent,
     runtime_types::{
@@ -154,8 +154,7 @@ pub trait EventProcessor {
             }) = e
             {
                 reply_to.eq(&message_id).then(|| {
-                    let res = exit_code
-                        .eq(&0)
+                    let res = (!ReplyCode::from(exit_code).is_system_error())
                         .then_some(payload.0.clone())
                         .ok_or_else(|| String::from_utf8(payload.0).expect("Infallible"));
 
@@ -185,7 +184,7 @@ pub trait EventProcessor {
                     },
                 ..
             }) => {
-                if reply_to == message_id && exit_code != 0 {
+                if reply_to == message_id && ReplyCode::from(exit_code).is_system_error() {
                     Some(Some(String::from_utf8(payload.0).expect("Infallible")))
                 } else {
                     None
@@ -226,7 +225,9 @@ pub trait EventProcessor {
                             },
                         ..
                     }) => {
-                        if message_ids.contains(&reply_to) && exit_code != 0 {
+                        if message_ids.contains(&reply_to)
+                            && ReplyCode::from(exit_code).is_system_error()
+                        {
                             Some(vec![(
                                 reply_to.into(),
                                 Some(String::from_utf8(payload.0).expect("Infallible")),
@@ -19,11 +19,15 @@
 pub mod calls;
 pub mod error;
 pub mod listener;
+pub mod offline;
+pub mod rpc;
 pub mod storage;
 
 use crate::{node::ws::WSAddress, EventListener};
 use error::*;
+pub use gp::api::signer::{Finality, Strategy, Verification};
 use gp::api::{signer::Signer, Api};
+use subxt::sp_runtime::AccountId32;
 
 #[derive(Clone)]
 pub struct GearApi(Signer);
@@ -59,6 +63,18 @@ impl GearApi {
         ))
     }
 
+    // Extrinsics submitted by the returned api are processed
+    // according to the given finality strategy.
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        Self(self.0.with_strategy(strategy))
+    }
+
+    // Finalization verifications of extrinsics submitted with
+    // `Strategy::InBlockThenVerifyFinalized` since the previous call.
+    pub fn take_verifications(&self) -> Vec<Verification> {
+        self.0.take_verifications()
+    }
+
     pub async fn dev() -> Result<Self> {
         Self::init(WSAddress::dev()).await
     }
@@ -77,6 +93,10 @@ impl GearApi {
         Ok(EventListener(events))
     }
 
+    pub fn account_id(&self) -> &AccountId32 {
+        self.0.account_id()
+    }
+
     pub fn set_nonce(&mut self, nonce: u32) {
         self.0.signer.set_nonce(nonce)
     }
@@ -0,0 +1,304 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Construction of extrinsics without a node connection.
+//!
+//! Signing keys of valuable accounts are often kept on air-gapped
+//! machines. [`GearApi::build_unsigned`] encodes a call and its signing
+//! payload from the statically known metadata and explicitly provided
+//! [`OfflineParams`], so nothing is requested from the node. The payload
+//! is signed elsewhere and the signature is brought back to
+//! [`GearApi::submit_signed`], which broadcasts the extrinsic.
+
+use super::{GearApi, Result};
+use gear_core::ids::*;
+use gp::api::{
+    config::GearConfig,
+    generated::api::runtime_types::{
+        gear_runtime::RuntimeCall, pallet_gear::pallet::Call as GearCall,
+    },
+};
+use parity_scale_codec::{Compact, Encode};
+use subxt::{
+    extrinsic::{Era, ExtrinsicParams, PlainTip},
+    sp_core::{blake2_256, H256},
+    sp_runtime::{AccountId32, MultiAddress, MultiSignature},
+    Encoded, PolkadotExtrinsicParams, PolkadotExtrinsicParamsBuilder,
+};
+
+/// Version byte of signed extrinsics: signed bit set, format version 4.
+const SIGNED_EXTRINSIC_VERSION: u8 = 0b1000_0000 + 4;
+
+/// Signing payloads longer than this are hashed before signing.
+const MAX_UNHASHED_PAYLOAD_LEN: usize = 256;
+
+/// `pallet_gear` calls available for offline construction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OfflineCall {
+    /// `pallet_gear::upload_program`
+    UploadProgram {
+        code: Vec<u8>,
+        salt: Vec<u8>,
+        payload: Vec<u8>,
+        gas_limit: u64,
+        value: u128,
+    },
+    /// `pallet_gear::send_message`
+    SendMessage {
+        destination: ProgramId,
+        payload: Vec<u8>,
+        gas_limit: u64,
+        value: u128,
+    },
+    /// `pallet_gear::send_reply`
+    SendReply {
+        reply_to_id: MessageId,
+        payload: Vec<u8>,
+        gas_limit: u64,
+        value: u128,
+    },
+    /// `pallet_gear::claim_value`
+    ClaimValue { message_id: MessageId },
+}
+
+impl From<OfflineCall> for RuntimeCall {
+    fn from(call: OfflineCall) -> Self {
+        RuntimeCall::Gear(match call {
+            OfflineCall::UploadProgram {
+                code,
+                salt,
+                payload,
+                gas_limit,
+                value,
+            } => GearCall::upload_program {
+                code,
+                salt,
+                init_payload: payload,
+                gas_limit,
+                value,
+            },
+            OfflineCall::SendMessage {
+                destination,
+                payload,
+                gas_limit,
+                value,
+            } => GearCall::send_message {
+                destination: destination.into(),
+                payload,
+                gas_limit,
+                value,
+            },
+            OfflineCall::SendReply {
+                reply_to_id,
+                payload,
+                gas_limit,
+                value,
+            } => GearCall::send_reply {
+                reply_to_id: reply_to_id.into(),
+                payload,
+                gas_limit,
+                value,
+            },
+            OfflineCall::ClaimValue { message_id } => GearCall::claim_value {
+                message_id: message_id.into(),
+            },
+        })
+    }
+}
+
+/// Chain state an extrinsic commits to, normally queried from the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfflineParams {
+    /// Account the extrinsic will be signed by.
+    pub account_id: AccountId32,
+    /// Next nonce of the signing account.
+    pub nonce: u32,
+    /// `spec_version` of the runtime.
+    pub spec_version: u32,
+    /// `transaction_version` of the runtime.
+    pub transaction_version: u32,
+    /// Hash of the genesis block.
+    pub genesis_hash: H256,
+    /// Mortality of the extrinsic, immortal by default.
+    pub era: Era,
+    /// Block the mortal era starts from, genesis if not set.
+    pub checkpoint: Option<H256>,
+    /// Tip paid to the block author.
+    pub tip: u128,
+}
+
+impl OfflineParams {
+    /// Params of an immortal extrinsic without a tip.
+    pub fn new(
+        account_id: AccountId32,
+        nonce: u32,
+        spec_version: u32,
+        transaction_version: u32,
+        genesis_hash: H256,
+    ) -> Self {
+        Self {
+            account_id,
+            nonce,
+            spec_version,
+            transaction_version,
+            genesis_hash,
+            era: Era::Immortal,
+            checkpoint: None,
+            tip: 0,
+        }
+    }
+
+    /// Make the extrinsic mortal, starting from the `checkpoint` block.
+    pub fn mortal(self, era: Era, checkpoint: H256) -> Self {
+        Self {
+            era,
+            checkpoint: Some(checkpoint),
+            ..self
+        }
+    }
+
+    /// Set the tip paid to the block author.
+    pub fn tip(self, tip: u128) -> Self {
+        Self { tip, ..self }
+    }
+
+    fn extrinsic_params(&self) -> PolkadotExtrinsicParams<GearConfig> {
+        let mut builder =
+            PolkadotExtrinsicParamsBuilder::<GearConfig>::new().tip(PlainTip::new(self.tip));
+
+        if let Some(checkpoint) = self.checkpoint {
+            builder = builder.era(self.era, checkpoint);
+        }
+
+        PolkadotExtrinsicParams::<GearConfig>::new(
+            self.spec_version,
+            self.transaction_version,
+            self.nonce,
+            self.genesis_hash,
+            builder,
+        )
+    }
+}
+
+/// Extrinsic awaiting a signature produced elsewhere.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfflineExtrinsic {
+    account_id: AccountId32,
+    call: Vec<u8>,
+    extra: Vec<u8>,
+    additional: Vec<u8>,
+}
+
+impl OfflineExtrinsic {
+    /// Account the extrinsic is expected to be signed by.
+    pub fn account_id(&self) -> &AccountId32 {
+        &self.account_id
+    }
+
+    /// SCALE-encoded call.
+    pub fn call_data(&self) -> &[u8] {
+        &self.call
+    }
+
+    /// Bytes to be signed by the account.
+    ///
+    /// Payloads longer than 256 bytes are replaced with their
+    /// `blake2_256` hash, as the runtime expects.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let payload = [&self.call[..], &self.extra, &self.additional].concat();
+
+        if payload.len() > MAX_UNHASHED_PAYLOAD_LEN {
+            blake2_256(&payload).to_vec()
+        } else {
+            payload
+        }
+    }
+
+    /// Encode the extrinsic with the given signature of
+    /// [`signing_payload`](Self::signing_payload).
+    pub fn encode_signed(&self, signature: impl Into<MultiSignature>) -> Vec<u8> {
+        let mut inner = vec![SIGNED_EXTRINSIC_VERSION];
+        MultiAddress::<AccountId32, ()>::Id(self.account_id.clone()).encode_to(&mut inner);
+        signature.into().encode_to(&mut inner);
+        inner.extend_from_slice(&self.extra);
+        inner.extend_from_slice(&self.call);
+
+        let mut encoded = Compact(inner.len() as u32).encode();
+        encoded.extend(inner);
+
+        encoded
+    }
+}
+
+impl GearApi {
+    /// Build an extrinsic for offline signing.
+    ///
+    /// Doesn't touch the node: everything the signature commits to is
+    /// taken from `params`.
+    pub fn build_unsigned(call: OfflineCall, params: OfflineParams) -> OfflineExtrinsic {
+        let extrinsic_params = params.extrinsic_params();
+
+        let mut extra = vec![];
+        extrinsic_params.encode_extra_to(&mut extra);
+
+        let mut additional = vec![];
+        extrinsic_params.encode_additional_to(&mut additional);
+
+        OfflineExtrinsic {
+            account_id: params.account_id,
+            call: RuntimeCall::from(call).encode(),
+            extra,
+            additional,
+        }
+    }
+
+    /// Query [`OfflineParams`] of the next extrinsic of the account.
+    ///
+    /// Meant to be run on a connected machine before the payload
+    /// is carried to the signing one.
+    pub async fn offline_params(&self, account_id: AccountId32) -> Result<OfflineParams> {
+        let rpc = self.0.client.rpc();
+
+        let nonce = rpc.system_account_next_index(&account_id).await?;
+        let version = rpc.runtime_version(None).await?;
+        let genesis_hash = rpc.genesis_hash().await?;
+
+        Ok(OfflineParams::new(
+            account_id,
+            nonce,
+            version.spec_version,
+            version.transaction_version,
+            genesis_hash,
+        ))
+    }
+
+    /// Submit an offline-built extrinsic with a signature of its
+    /// signing payload, returning the extrinsic hash.
+    pub async fn submit_signed(
+        &self,
+        extrinsic: OfflineExtrinsic,
+        signature: impl Into<MultiSignature>,
+    ) -> Result<H256> {
+        self.0
+            .client
+            .rpc()
+            .submit_extrinsic(Encoded(extrinsic.encode_signed(signature)))
+            .await
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,193 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::{GearApi, Result};
+use crate::Error;
+use futures::stream::{self, Stream, TryStreamExt};
+use gear_core::ids::*;
+pub use gp::api::types::{CodeStats, GasInfo, MessagePreview, MessagesPage};
+use std::borrow::Borrow;
+use subxt::{sp_core::H256, sp_runtime::AccountId32};
+
+/// Amount of messages requested per page by paging streams.
+const PAGE_LEN: u32 = 100;
+
+impl GearApi {
+    /// `gear_calculateInitUploadGas` for the `upload_program` call.
+    pub async fn calculate_upload_gas(
+        &self,
+        code: impl AsRef<[u8]>,
+        payload: impl AsRef<[u8]>,
+        value: u128,
+        allow_other_panics: bool,
+    ) -> Result<GasInfo> {
+        self.0
+            .calculate_upload_gas(
+                code.as_ref().to_vec(),
+                payload.as_ref().to_vec(),
+                value,
+                allow_other_panics,
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    /// `gear_calculateInitCreateGas` for the `create_program` call.
+    pub async fn calculate_create_gas(
+        &self,
+        code_id: CodeId,
+        payload: impl AsRef<[u8]>,
+        value: u128,
+        allow_other_panics: bool,
+    ) -> Result<GasInfo> {
+        self.0
+            .calculate_create_gas(
+                code_id,
+                payload.as_ref().to_vec(),
+                value,
+                allow_other_panics,
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    /// `gear_calculateHandleGas` for the `send_message` call.
+    pub async fn calculate_handle_gas(
+        &self,
+        destination: ProgramId,
+        payload: impl AsRef<[u8]>,
+        value: u128,
+        allow_other_panics: bool,
+    ) -> Result<GasInfo> {
+        self.0
+            .calculate_handle_gas(
+                destination,
+                payload.as_ref().to_vec(),
+                value,
+                allow_other_panics,
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    /// `gear_mailboxOf` for the page of messages following `cursor`
+    /// in the mailbox of `account_id`.
+    pub async fn mailbox_of(
+        &self,
+        account_id: impl Borrow<AccountId32>,
+        cursor: Option<MessageId>,
+        limit: u32,
+    ) -> Result<MessagesPage> {
+        let account: &[u8; 32] = account_id.borrow().as_ref();
+
+        self.0
+            .mailbox_of(
+                H256(*account),
+                cursor.map(|id| H256(id.into())),
+                limit,
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    /// `gear_queueSnapshot` for the page of messages following `cursor`
+    /// in the message queue.
+    pub async fn queue_snapshot(
+        &self,
+        cursor: Option<MessageId>,
+        limit: u32,
+    ) -> Result<MessagesPage> {
+        self.0
+            .queue_snapshot(cursor.map(|id| H256(id.into())), limit, None)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// `gear_codeStats` for the codes, which burned the most gas within
+    /// the code stats `era`, or the current one if it isn't specified.
+    pub async fn code_stats(
+        &self,
+        era: Option<u32>,
+        limit: u32,
+    ) -> Result<Vec<(CodeId, CodeStats)>> {
+        let stats = self.0.code_stats(era, limit, None).await?;
+
+        Ok(stats
+            .into_iter()
+            .map(|(code_id, stats)| (CodeId::from(code_id.as_bytes()), stats))
+            .collect())
+    }
+
+    /// `gear_codeRefcount` for the amount of active programs
+    /// running the code with `code_id`.
+    pub async fn code_refcount(&self, code_id: CodeId) -> Result<u32> {
+        self.0
+            .code_refcount(H256(code_id.into()), None)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Stream of all messages in the mailbox of `account_id`,
+    /// requested page by page.
+    pub fn mailbox_messages(
+        &self,
+        account_id: AccountId32,
+    ) -> impl Stream<Item = Result<MessagePreview>> + '_ {
+        paged(move |cursor| {
+            let account_id = account_id.clone();
+            async move { self.mailbox_of(account_id, cursor, PAGE_LEN).await }
+        })
+    }
+
+    /// Stream of all messages in the message queue, requested page by page.
+    pub fn queue_messages(&self) -> impl Stream<Item = Result<MessagePreview>> + '_ {
+        paged(move |cursor| self.queue_snapshot(cursor, PAGE_LEN))
+    }
+}
+
+// Flatten pages requested one by one, following cursors.
+fn paged<F, Fut>(request: F) -> impl Stream<Item = Result<MessagePreview>>
+where
+    F: Fn(Option<MessageId>) -> Fut,
+    Fut: std::future::Future<Output = Result<MessagesPage>>,
+{
+    // `None` state stands for the exhausted stream,
+    // `Some(None)` for the first page.
+    stream::try_unfold(Some(None), move |cursor| {
+        let page = cursor.map(&request);
+
+        async move {
+            let page = match page {
+                Some(page) => page.await?,
+                None => return Ok(None),
+            };
+
+            let cursor = page
+                .next_cursor
+                .map(|id| Some(MessageId::from(id.as_bytes())));
+            let messages = page.messages.into_iter().map(Ok::<_, Error>);
+
+            Ok(Some((stream::iter(messages), cursor)))
+        }
+    })
+    .try_flatten()
+}
@@ -21,11 +21,14 @@ mod block;
 pub use block::*;
 
 use super::{GearApi, Result};
-use crate::Error;
+use crate::{utils, Error};
 use gear_core::{ids::*, message::StoredMessage};
-use gp::api::generated::api::runtime_types::gear_common::storage::primitives::Interval;
+use gp::{
+    api::generated::api::runtime_types::gear_common::storage::primitives::Interval,
+    result::Error as GPError,
+};
 use std::borrow::Borrow;
-use subxt::sp_runtime::AccountId32;
+use subxt::{sp_core::H256, sp_runtime::AccountId32};
 
 impl GearApi {
     pub async fn get_from_mailbox(
@@ -77,4 +80,36 @@ impl GearApi {
 
         Ok(data.free)
     }
+
+    // Terminated programs are considered as non-existing,
+    // since they can't receive messages anymore.
+    pub async fn program_exists(&self, program_id: ProgramId) -> Result<bool> {
+        match self.0.gprog(H256(program_id.into())).await {
+            Ok(_) => Ok(true),
+            Err(GPError::ProgramNotFound(_) | GPError::ProgramTerminated) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get the metadata hash of the program, stored in its code by
+    /// `gear-wasm-builder`.
+    ///
+    /// Returns `None` if the program was built without metadata.
+    pub async fn metahash(&self, program_id: ProgramId) -> Result<Option<[u8; 32]>> {
+        let program = self.0.gprog(H256(program_id.into())).await?;
+
+        let code = self
+            .0
+            .original_code_storage(program.code_hash.0)
+            .await?
+            .ok_or(Error::CodeNotFound)?;
+
+        utils::metahash_from_code(code)
+    }
+
+    pub async fn code_exists(&self, code_id: CodeId) -> Result<bool> {
+        let code = self.0.code_storage(code_id.into()).await?;
+
+        Ok(code.is_some())
+    }
 }
@@ -20,6 +20,9 @@ mod api;
 mod node;
 mod utils;
 
-pub use api::{calls::*, error::*, listener::*, storage::*, GearApi};
+pub use api::{
+    calls::*, error::*, listener::*, offline::*, rpc::*, storage::*, Finality, GearApi, Strategy,
+    Verification,
+};
 pub use node::ws::*;
 pub use utils::*;
@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{Error, Result};
+use parity_wasm::elements::Module;
 use std::{
     fs,
     path::PathBuf,
@@ -48,6 +49,28 @@ pub fn wasm_target(root_path: impl AsRef<str>, demo_name: impl AsRef<str>) -> St
     )
 }
 
+/// Name of the custom section that `gear-wasm-builder` stores the
+/// metadata hash of a program in.
+pub const METAHASH_SECTION: &str = "metahash";
+
+/// Read the metadata hash from the custom section of the program `code`.
+///
+/// Returns `None` if the program was built without metadata.
+pub fn metahash_from_code(code: impl AsRef<[u8]>) -> Result<Option<[u8; 32]>> {
+    let module: Module = parity_wasm::deserialize_buffer(code.as_ref())?;
+
+    module
+        .custom_sections()
+        .find(|section| section.name() == METAHASH_SECTION)
+        .map(|section| {
+            section
+                .payload()
+                .try_into()
+                .map_err(|_| Error::WrongMetahashLength(section.payload().len()))
+        })
+        .transpose()
+}
+
 pub fn code_from_os(path: impl Into<PathBuf>) -> Result<Vec<u8>> {
     let path = path.into();
 
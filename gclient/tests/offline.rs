@@ -0,0 +1,138 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for offline construction of extrinsics.
+
+use gclient::{GearApi, OfflineCall, OfflineParams, Result, WSAddress};
+use gear_core::ids::{MessageId, ProgramId};
+use gp::api::{config::GearConfig, Api};
+use subxt::{
+    sp_core::{blake2_256, ed25519, sr25519, Pair, H256},
+    sp_runtime::MultiSignature,
+    PairSigner,
+};
+
+async fn online_and_offline(api: &GearApi, online: &Api, call: OfflineCall) -> Result<()> {
+    // Ed25519 signatures are deterministic, so both paths must agree byte to byte.
+    let pair = ed25519::Pair::from_string("//Alice", None).expect("Infallible");
+    let signer = PairSigner::<GearConfig, _>::new(pair.clone());
+
+    let params = api.offline_params(pair.public().into()).await?;
+    let nonce = params.nonce;
+
+    let tx = online.tx().gear();
+    let ex = match call.clone() {
+        OfflineCall::UploadProgram {
+            code,
+            salt,
+            payload,
+            gas_limit,
+            value,
+        } => tx
+            .upload_program(code, salt, payload, gas_limit, value)?
+            .create_signed_with_nonce(&signer, nonce, Default::default())?,
+        OfflineCall::SendMessage {
+            destination,
+            payload,
+            gas_limit,
+            value,
+        } => tx
+            .send_message(destination.into(), payload, gas_limit, value)?
+            .create_signed_with_nonce(&signer, nonce, Default::default())?,
+        OfflineCall::SendReply {
+            reply_to_id,
+            payload,
+            gas_limit,
+            value,
+        } => tx
+            .send_reply(reply_to_id.into(), payload, gas_limit, value)?
+            .create_signed_with_nonce(&signer, nonce, Default::default())?,
+        OfflineCall::ClaimValue { message_id } => tx
+            .claim_value(message_id.into())?
+            .create_signed_with_nonce(&signer, nonce, Default::default())?,
+    };
+
+    let unsigned = GearApi::build_unsigned(call, params);
+    let signature = pair.sign(&unsigned.signing_payload());
+
+    assert_eq!(ex.0, unsigned.encode_signed(signature));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn offline_extrinsics_match_online() -> Result<()> {
+    let api = GearApi::dev().await?;
+    let online = Api::new(Some(&WSAddress::dev().url())).await?;
+
+    let calls = [
+        OfflineCall::UploadProgram {
+            code: gclient::code_from_os("../target/wat-examples/wrong_load.wasm")?,
+            salt: b"salt".to_vec(),
+            payload: b"init".to_vec(),
+            gas_limit: 1_000_000,
+            value: 0,
+        },
+        OfflineCall::SendMessage {
+            destination: ProgramId::from(42),
+            payload: vec![0; 1024],
+            gas_limit: 1_000_000,
+            value: 1_000,
+        },
+        OfflineCall::SendReply {
+            reply_to_id: MessageId::from(42),
+            payload: b"reply".to_vec(),
+            gas_limit: 1_000_000,
+            value: 0,
+        },
+        OfflineCall::ClaimValue {
+            message_id: MessageId::from(42),
+        },
+    ];
+
+    for call in calls {
+        online_and_offline(&api, &online, call).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn offline_signed_submitted() -> Result<()> {
+    let api = GearApi::dev().await?;
+    let pair = sr25519::Pair::from_string("//Alice", None).expect("Infallible");
+
+    // Queried on a connected machine.
+    let params: OfflineParams = api.offline_params(api.account_id().clone()).await?;
+
+    // Signed on an air-gapped one.
+    let unsigned = GearApi::build_unsigned(
+        OfflineCall::ClaimValue {
+            message_id: MessageId::from(42),
+        },
+        params,
+    );
+    let signature = MultiSignature::from(pair.sign(&unsigned.signing_payload()));
+    let encoded = unsigned.encode_signed(signature.clone());
+
+    let hash = api.submit_signed(unsigned, signature).await?;
+
+    assert_eq!(hash, H256(blake2_256(&encoded)));
+
+    Ok(())
+}
@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-pub use gear_core_errors::{ExtError, MemoryError, MessageError};
+pub use gear_core_errors::{ExecutionError, ExtError, MemoryError, MessageError};
 
 pub type Result<T, E = ExtError> = core::result::Result<T, E>;
 
@@ -20,21 +20,57 @@
 //!
 //! Provides API for low-level async implementation.
 
-use crate::{ActorId, MessageId};
+use crate::{
+    error::Result, utils, ActorId, ContextLimits, EntryPoint, MessageId, ReservationId, TraceLevel,
+};
+use core::num::NonZeroU32;
 
 mod sys {
+    use crate::error::SyscallError;
+
     extern "C" {
+        pub fn gr_block_hash(height: u32, hash_ptr: *mut u8) -> SyscallError;
         pub fn gr_block_height() -> u32;
         pub fn gr_block_timestamp() -> u64;
+        pub fn gr_block_duration() -> u64;
+        pub fn gr_bump_counter(key_ptr: *const u8) -> u64;
+        pub fn gr_children_count() -> u64;
+        pub fn gr_children_gas_burned() -> u64;
+        pub fn gr_codes_count() -> u64;
+        pub fn gr_context_limits(limits_ptr: *mut u8);
+        pub fn gr_delayed_eta(message_id_ptr: *const u8) -> u32;
+        pub fn gr_execution_id() -> u64;
         pub fn gr_exit(value_dest_ptr: *const u8) -> !;
+        pub fn gr_schedule_exit(block: u32, inheritor_ptr: *const u8) -> SyscallError;
+        pub fn gr_set_entry_budget(entry: u32, gas: u64) -> SyscallError;
+        pub fn gr_gas_allowance() -> u64;
         pub fn gr_gas_available() -> u64;
+        pub fn gr_is_init() -> i32;
         pub fn gr_program_id(val: *mut u8);
         pub fn gr_origin(origin_ptr: *mut u8);
         pub fn gr_leave() -> !;
+        pub fn gr_new_handle() -> u32;
+        pub fn gr_stack_limit() -> u32;
+        pub fn gr_tx_hash(hash_ptr: *mut u8) -> SyscallError;
+        pub fn gr_reserve_value(value_ptr: *const u8, reservation_id_ptr: *mut u8) -> SyscallError;
+        pub fn gr_resumed_from_wait() -> i32;
+        pub fn gr_unreserve_value(
+            reservation_id_ptr: *const u8,
+            value_ptr: *mut u8,
+        ) -> SyscallError;
+        pub fn gr_trace(level: u32, data_ptr: *const u8, data_len: u32);
+        pub fn gr_split_value_reservation(
+            reservation_id_ptr: *const u8,
+            value_ptr: *const u8,
+            split_id_ptr: *mut u8,
+        ) -> SyscallError;
         pub fn gr_value_available(val: *mut u8);
+        pub fn gr_spendable_value(val: *mut u8);
         pub fn gr_wait() -> !;
         pub fn gr_wait_up_to(duration: *const u8) -> !;
+        pub fn gr_wait_budget_remaining() -> u32;
         pub fn gr_wait_for(duration: *const u8) -> !;
+        pub fn gr_wait_if(condition: u32);
         pub fn gr_wake(waker_id_ptr: *const u8, delay_ptr: *const u8);
     }
 }
@@ -81,6 +117,245 @@ pub fn block_timestamp() -> u64 {
     unsafe { sys::gr_block_timestamp() }
 }
 
+/// Get the duration of a block in milliseconds.
+///
+/// Durations of waits and delays are given in blocks, so this allows
+/// to convert them from time intervals.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// // Wait for at least a minute
+/// unsafe extern "C" fn handle() {
+///     let blocks = 60_000 / exec::block_duration_millis() + 1;
+///     exec::wait_for(blocks as u32);
+/// }
+/// ```
+pub fn block_duration_millis() -> u64 {
+    unsafe { sys::gr_block_duration() }
+}
+
+/// Get hash of the block at the given `height`.
+///
+/// Only a limited amount of blocks preceding the current one is available,
+/// older blocks result in an error. Hash of the current block isn't known
+/// until the block is built, so the current and future heights result in
+/// an error as well.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// // Reply with hash of the previous block
+/// unsafe extern "C" fn handle() {
+///     let hash = exec::block_hash(exec::block_height() - 1).expect("Unable to get hash");
+///     msg::reply(&hash, 0).unwrap();
+/// }
+/// ```
+pub fn block_hash(height: u32) -> Result<[u8; 32]> {
+    unsafe {
+        let mut hash = [0u8; 32];
+        sys::gr_block_hash(height, hash.as_mut_ptr()).into_result()?;
+        Ok(hash)
+    }
+}
+
+/// Get hash of the external transaction the current message chain
+/// originates from.
+///
+/// All messages caused by the same transaction, including replies and
+/// messages of the created programs, share the hash. Delayed messages and
+/// messages sent by them don't get it. It's an error if the chain wasn't
+/// initiated by a transaction, e.g. during gas calculation.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// // Reply with hash of the transaction
+/// unsafe extern "C" fn handle() {
+///     if let Ok(hash) = exec::tx_hash() {
+///         msg::reply(&hash, 0).unwrap();
+///     }
+/// }
+/// ```
+pub fn tx_hash() -> Result<[u8; 32]> {
+    unsafe {
+        let mut hash = [0u8; 32];
+        sys::gr_tx_hash(hash.as_mut_ptr()).into_result()?;
+        Ok(hash)
+    }
+}
+
+/// Get the maximum height of the stack the program is allowed to reach.
+///
+/// Execution traps once the height is exceeded. The height is measured
+/// in values the functions on the stack keep, i.e. their locals and
+/// operands, so recursive algorithms may estimate their depth against it.
+///
+/// Returns `None` if the stack height isn't limited.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     // Each level of the recursion keeps about 16 values on the stack.
+///     let max_depth = exec::stack_limit().map(|limit| limit / 16);
+///     // ...
+/// }
+/// ```
+pub fn stack_limit() -> Option<u32> {
+    match unsafe { sys::gr_stack_limit() } {
+        0 => None,
+        limit => Some(limit),
+    }
+}
+
+/// Get the number of programs created by the program over its lifetime.
+///
+/// Programs created during the current execution are counted as well.
+/// A child is counted once its creation is requested by
+/// [`prog::create_program`](crate::prog::create_program), even if its
+/// initialization fails or it is terminated later.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let children = exec::children_count();
+///     msg::reply(&children.to_le_bytes(), 0).unwrap();
+/// }
+/// ```
+pub fn children_count() -> u64 {
+    unsafe { sys::gr_children_count() }
+}
+
+/// Get the total gas burned by initializations of programs created by the
+/// program.
+///
+/// Children are initialized after the execution that created them, so the
+/// gas of their initializations is available in the next executions only.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let gas_burned = exec::children_gas_burned();
+///     msg::reply(&gas_burned.to_le_bytes(), 0).unwrap();
+/// }
+/// ```
+pub fn children_gas_burned() -> u64 {
+    unsafe { sys::gr_children_gas_burned() }
+}
+
+/// Get the number of distinct codes uploaded to the chain.
+///
+/// The count is global: it includes codes uploaded by any user or
+/// program, not only the ones the program's children were created from.
+/// Codes uploaded during the current block before the execution are
+/// counted as well.
+///
+/// The call is charged with a fixed cost, which includes a storage read
+/// of the counter.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let codes = exec::codes_count();
+///     msg::reply(&codes.to_le_bytes(), 0).unwrap();
+/// }
+/// ```
+pub fn codes_count() -> u64 {
+    unsafe { sys::gr_codes_count() }
+}
+
+/// Get identifier of the current execution of the message.
+///
+/// The runtime assigns identifiers in increasing order, one per execution,
+/// so it's unique to this specific execution. Unlike
+/// [`msg::id`](crate::msg::id), which stays the same for the message, the
+/// identifier changes on each re-entry after the message is woken from
+/// the waiting queue. This allows telling resumptions of the same message
+/// apart, e.g. in logs.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let execution_id = exec::execution_id();
+///     msg::reply(&execution_id.to_le_bytes(), 0).unwrap();
+/// }
+/// ```
+pub fn execution_id() -> u64 {
+    unsafe { sys::gr_execution_id() }
+}
+
+/// Increment the program's persistent counter with the given `key`,
+/// returning its new value.
+///
+/// Counters start from zero and keep their values between messages without
+/// occupying program's memory, so they may serve as a source of unique ids
+/// for program's own records. Increments made by a failed execution
+/// are discarded.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let order_id = exec::bump_counter(*b"orders\0\0");
+///     msg::reply(&order_id.to_le_bytes(), 0).unwrap();
+/// }
+/// ```
+pub fn bump_counter(key: [u8; 8]) -> u64 {
+    unsafe { sys::gr_bump_counter(key.as_ptr()) }
+}
+
+/// Get limits of the message context stored while waiting.
+///
+/// Waiting with the context exceeding any of the limits fails, so a program
+/// forming many messages before waiting may check them in advance.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     if exec::context_limits().payload_bytes < 1024 {
+///         // Send the payload right away instead of keeping it while waiting.
+///     }
+/// }
+/// ```
+pub fn context_limits() -> ContextLimits {
+    let mut buf = [0u8; 12];
+    unsafe { sys::gr_context_limits(buf.as_mut_ptr()) }
+
+    let [h0, h1, h2, h3, i0, i1, i2, i3, p0, p1, p2, p3] = buf;
+
+    ContextLimits {
+        handles: u32::from_le_bytes([h0, h1, h2, h3]),
+        ids: u32::from_le_bytes([i0, i1, i2, i3]),
+        payload_bytes: u32::from_le_bytes([p0, p1, p2, p3]),
+    }
+}
+
 /// Terminate the execution of a program. The program and all corresponding data
 /// are removed from the storage. This is similar to
 /// `std::process::exit`. `value_destination` specifies the address where all
@@ -101,6 +376,65 @@ pub fn exit(value_destination: ActorId) -> ! {
     unsafe { sys::gr_exit(value_destination.as_slice().as_ptr()) }
 }
 
+/// Schedule termination of the program at the given future `block`.
+///
+/// Once the block comes, the program exits the same way as by [`exit`],
+/// transferring all its value to the `inheritor`. Scheduling at the current
+/// or a past block results in an error.
+///
+/// The exit isn't a delayed message: it isn't reported by [`delayed_eta`]
+/// and can't be cancelled. Keeping the exit till the block costs the same
+/// rent per block as waiting does, which is charged from the gas of the
+/// current message, so scheduling fails if there isn't enough gas.
+///
+/// A program has at most one scheduled exit: scheduling it again replaces
+/// the previous one, whose rent isn't returned.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// // Time-limited escrow returning the value to its creator
+/// unsafe extern "C" fn init() {
+///     exec::schedule_exit(exec::block_height() + 1000, msg::source())
+///         .expect("Unable to schedule exit");
+/// }
+/// ```
+pub fn schedule_exit(block: u32, inheritor: ActorId) -> Result<()> {
+    unsafe { sys::gr_schedule_exit(block, inheritor.as_slice().as_ptr()).into_result() }
+}
+
+/// Limit gas available to the executions of the given `entry` point.
+///
+/// An execution of the entry point gets the least of the message's gas
+/// limit and the `gas` budget. Running out of the budget fails the
+/// execution the same way as running out of gas, while the rest of the
+/// message's gas isn't spent.
+///
+/// It protects the program from counterparties spending all the gas
+/// attached to its requests. Once a reply exceeds the budget of
+/// `handle_reply`, the program gets a system reply with
+/// [`ReplyCode::ENTRY_BUDGET_EXCEEDED`](crate::msg::ReplyCode::ENTRY_BUDGET_EXCEEDED)
+/// instead, so the flow waiting for the reply isn't stuck.
+///
+/// The budget takes effect for the messages executed after the current
+/// one succeeds, replacing the previous budget of the entry point.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, EntryPoint};
+///
+/// unsafe extern "C" fn init() {
+///     exec::set_entry_budget(EntryPoint::Reply, 1_000_000_000)
+///         .expect("Unable to set budget");
+/// }
+/// ```
+pub fn set_entry_budget(entry: EntryPoint, gas: u64) -> Result<()> {
+    unsafe { sys::gr_set_entry_budget(entry as u32, gas).into_result() }
+}
+
 /// Get the current value of the gas available for execution.
 ///
 /// Each message processing consumes gas, both on instructions execution and
@@ -125,6 +459,75 @@ pub fn gas_available() -> u64 {
     unsafe { sys::gr_gas_available() }
 }
 
+/// Get the current amount of the block gas allowance left.
+///
+/// The allowance is shared by all messages processed within the block.
+/// Unlike running out of [`gas_available`], which fails the message,
+/// exceeding the allowance postpones the message to the next block and
+/// re-runs it from the start. Long-running messages may check it to
+/// [`wait`] in time and resume with the fresh allowance.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     while exec::gas_allowance() > 1_000_000 {
+///         // ...
+///     }
+///
+///     exec::wait();
+/// }
+/// ```
+pub fn gas_allowance() -> u64 {
+    unsafe { sys::gr_gas_allowance() }
+}
+
+/// Tell whether the program is being initialized.
+///
+/// Returns `true` only within the `init()` entry point, so programs with
+/// a single dispatcher may branch on it cheaply.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn dispatch() {
+///     if exec::is_init() {
+///         // ...
+///     }
+/// }
+/// ```
+pub fn is_init() -> bool {
+    unsafe { sys::gr_is_init() != 0 }
+}
+
+/// Tell whether the current message is executed again after [`wait`],
+/// [`wait_for`] or [`wait_up_to`] and being woken.
+///
+/// The message is executed from the beginning of the entry point again,
+/// so the program may skip the setup it has already done before waiting.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     if !exec::resumed_from_wait() {
+///         // Setup done once per message.
+///         // ...
+///         exec::wait();
+///     }
+///     // ...
+/// }
+/// ```
+pub fn resumed_from_wait() -> bool {
+    unsafe { sys::gr_resumed_from_wait() != 0 }
+}
+
 /// Terminate the current message handling.
 ///
 /// For cases when the message handling needs to be terminated with state
@@ -167,6 +570,36 @@ pub fn value_available() -> u128 {
     u128::from_le_bytes(value_data)
 }
 
+/// Get the value amount that can still be attached to outgoing messages.
+///
+/// It's the [`value_available`] amount, except that it's zero once this
+/// amount is below the existential deposit.
+///
+/// The value attached to a message must be either zero or not less than
+/// the existential deposit, so sending a part of the returned amount fails
+/// if the part is below the deposit.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg, ActorId};
+///
+/// // Forward all the value still attachable to the receiver
+/// unsafe extern "C" fn handle() {
+///     let value = exec::spendable_value();
+///     if value > 0 {
+///         msg::send(ActorId([1; 32]), b"PING", value).unwrap();
+///     }
+/// }
+/// ```
+pub fn spendable_value() -> u128 {
+    let mut value_data = [0u8; 16];
+    unsafe {
+        sys::gr_spendable_value(value_data.as_mut_ptr());
+    }
+    u128::from_le_bytes(value_data)
+}
+
 /// Pause the current message handling.
 ///
 /// If the message handling needs to be paused, i.e. to wait for another
@@ -206,6 +639,71 @@ pub fn wait_up_to(duration: u32) -> ! {
     unsafe { sys::gr_wait_up_to(duration.to_le_bytes().as_ptr()) }
 }
 
+/// Get the number of blocks the current message can still afford
+/// to wait for.
+///
+/// Waiting in the waitlist is paid per block from the gas left, so after
+/// being woken a message has less budget for subsequent waits. [`wait`]
+/// reschedules handling for exactly this amount of blocks.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     // Give up rather than be removed from the waitlist.
+///     if exec::wait_budget_remaining() < 10 {
+///         msg::reply(b"Can't wait any longer", 0).unwrap();
+///     } else {
+///         exec::wait_for(10);
+///     }
+/// }
+/// ```
+pub fn wait_budget_remaining() -> u32 {
+    unsafe { sys::gr_wait_budget_remaining() }
+}
+
+/// Same as [`wait`], but only if `condition` is true, otherwise does nothing.
+///
+/// Allows to wait for further input without branching around the call.
+/// As with [`wait`], the message is only resumed once woken with [`wake`]
+/// and is executed from the beginning of the entry point again.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg, MessageId};
+///
+/// static mut RECEIVED: u32 = 0;
+/// static mut FIRST: Option<MessageId> = None;
+///
+/// unsafe extern "C" fn handle() {
+///     if FIRST.is_none() {
+///         FIRST = Some(msg::id());
+///     }
+///
+///     // The woken message mustn't be counted twice.
+///     if !exec::resumed_from_wait() {
+///         RECEIVED += 1;
+///     }
+///
+///     let is_first = FIRST == Some(msg::id());
+///     if let (false, Some(first), 3) = (is_first, FIRST, RECEIVED) {
+///         exec::wake(first);
+///     }
+///
+///     // The first message waits until the third one is received.
+///     exec::wait_if(is_first && RECEIVED < 3);
+///
+///     let reply: &[u8] = if is_first { b"Complete" } else { b"Received" };
+///     msg::reply(reply, 0).unwrap();
+/// }
+/// ```
+pub fn wait_if(condition: bool) {
+    unsafe { sys::gr_wait_if(condition as u32) }
+}
+
 /// Resume previously paused message handling.
 ///
 /// If a message has been paused using the [`wait`] function, then it is
@@ -230,9 +728,128 @@ pub fn wake(waker_id: MessageId) {
 }
 
 /// Same as [`wake`], but wakes delayed.
-pub fn wake_delayed(waker_id: MessageId, delay: u32) {
+pub fn wake_delayed(waker_id: MessageId, delay: Option<NonZeroU32>) {
+    unsafe {
+        sys::gr_wake(
+            waker_id.as_slice().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
+        );
+    }
+}
+
+/// Get the number of blocks until the message delayed by the current program
+/// will be dispatched.
+///
+/// Execution traps if the message wasn't sent by the current program with a
+/// delay or it was already dispatched.
+///
+/// # Examples
+///
+/// ```
+/// use core::num::NonZeroU32;
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let message_id = msg::send_delayed(msg::source(), b"PING", 0, NonZeroU32::new(10)).unwrap();
+///     assert_eq!(exec::delayed_eta(message_id), 10);
+/// }
+/// ```
+pub fn delayed_eta(message_id: MessageId) -> u32 {
+    unsafe { sys::gr_delayed_eta(message_id.as_slice().as_ptr()) }
+}
+
+/// Reserve `value` of the program's balance for future messages.
+///
+/// Reserved value can't be sent by ordinary send functions; it's only spent
+/// by [`msg::reservation_send`](crate::msg::reservation_send) or returned to
+/// the available balance by [`unreserve_value`]. Reservation persists across
+/// executions until it's fully drawn or unreserved, or until the program
+/// exits.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let reservation_id = exec::reserve_value(1000).expect("Unable to reserve");
+///     // ...
+///     msg::reservation_send(reservation_id, msg::source(), b"PAYOUT", 1000)
+///         .expect("Unable to send");
+/// }
+/// ```
+pub fn reserve_value(value: u128) -> Result<ReservationId> {
     unsafe {
-        sys::gr_wake(waker_id.as_slice().as_ptr(), delay.to_le_bytes().as_ptr());
+        let mut reservation_id = ReservationId::default();
+        sys::gr_reserve_value(
+            value.to_le_bytes().as_ptr(),
+            reservation_id.as_mut_slice().as_mut_ptr(),
+        )
+        .into_result()?;
+        Ok(reservation_id)
+    }
+}
+
+/// Remove the value reservation, returning the value left in it to the
+/// program's available balance.
+///
+/// Returns the value that was left in the reservation.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     let reservation_id = exec::reserve_value(1000).expect("Unable to reserve");
+///     let value = exec::unreserve_value(reservation_id).expect("Unable to unreserve");
+///     assert_eq!(value, 1000);
+/// }
+/// ```
+pub fn unreserve_value(reservation_id: ReservationId) -> Result<u128> {
+    unsafe {
+        let mut value = [0u8; 16];
+        sys::gr_unreserve_value(reservation_id.as_slice().as_ptr(), value.as_mut_ptr())
+            .into_result()?;
+        Ok(u128::from_le_bytes(value))
+    }
+}
+
+/// Move `value` from the value reservation into the new one.
+///
+/// Returns id of the new reservation. The source reservation keeps the rest
+/// of its value, or is removed once all of it is moved. So a part of the
+/// reserved value can be handed over to [`unreserve_value`] or
+/// [`msg::reservation_send`](crate::msg::reservation_send) without spending
+/// the whole reservation.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     let reservation_id = exec::reserve_value(1000).expect("Unable to reserve");
+///     let split_id =
+///         exec::split_value_reservation(reservation_id, 400).expect("Unable to split");
+///     let value = exec::unreserve_value(split_id).expect("Unable to unreserve");
+///     assert_eq!(value, 400);
+/// }
+/// ```
+pub fn split_value_reservation(
+    reservation_id: ReservationId,
+    value: u128,
+) -> Result<ReservationId> {
+    unsafe {
+        let mut split_id = ReservationId::default();
+        sys::gr_split_value_reservation(
+            reservation_id.as_slice().as_ptr(),
+            value.to_le_bytes().as_ptr(),
+            split_id.as_mut_slice().as_mut_ptr(),
+        )
+        .into_result()?;
+        Ok(split_id)
     }
 }
 
@@ -272,3 +889,52 @@ pub fn origin() -> ActorId {
     unsafe { sys::gr_origin(actor_id.as_mut_slice().as_mut_ptr()) };
     actor_id
 }
+
+/// Obtain a fresh handle for correlating program's own async operations.
+///
+/// Handles are unique and monotonic within the processing of the current
+/// message, including its resumptions after [`wait`], so they may be used
+/// as stable correlation ids surviving wait/wake cycles. Combined with
+/// [`msg::id`](crate::msg::id) they are unique across the program.
+///
+/// These handles don't intersect with message handles obtained via
+/// [`msg::send_init`](crate::msg::send_init).
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let first = exec::new_handle();
+///     let second = exec::new_handle();
+///     assert!(first < second);
+/// }
+/// ```
+pub fn new_handle() -> u32 {
+    unsafe { sys::gr_new_handle() }
+}
+
+/// Log the trace of the given level, if such traces are logged by the node.
+///
+/// Traces are lightweight diagnostics available in production, unlike the
+/// `debug` mode. The node logs them along with ids of the program and the
+/// message being processed.
+///
+/// Nodes configure the minimum level of logged traces globally or per
+/// program, so the program can't tell whether the trace is logged. It's
+/// charged the same either way.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, TraceLevel};
+///
+/// unsafe extern "C" fn handle() {
+///     exec::trace(TraceLevel::Warn, "Low balance");
+/// }
+/// ```
+pub fn trace(level: TraceLevel, data: &str) {
+    unsafe { sys::gr_trace(level as u32, data.as_ptr(), data.len() as u32) }
+}
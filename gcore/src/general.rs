@@ -94,6 +94,116 @@ impl MessageId {
     }
 }
 
+/// Value reservation identifier.
+///
+/// Identifies a part of the program's balance earmarked by
+/// [`exec::reserve_value`](crate::exec::reserve_value) for future messages.
+/// Reservations outlive the execution they were made in, so the identifier is
+/// supposed to be stored in the program's state.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     let reservation_id = exec::reserve_value(1000).expect("Unable to reserve");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq)]
+pub struct ReservationId(pub [u8; 32]);
+
+impl ReservationId {
+    /// Get `ReservationId` represented as a slice of `u8`.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.0[..]
+    }
+}
+
+/// Limits of the message context stored while the message waits.
+///
+/// Waiting fails if the outgoing messages being formed, ids of initialized
+/// programs and woken messages or payloads not sent yet exceed the limits.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::exec;
+///
+/// unsafe extern "C" fn handle() {
+///     let limits = exec::context_limits();
+///     // Don't form more messages than could be kept while waiting.
+///     let max_handles = limits.handles;
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq)]
+pub struct ContextLimits {
+    /// Limit of outgoing message handles.
+    pub handles: u32,
+    /// Limit of initialized program ids and woken message ids.
+    pub ids: u32,
+    /// Limit of payload bytes of outgoing messages and reply.
+    pub payload_bytes: u32,
+}
+
+/// Entry point of the program, which gas budget can be limited.
+///
+/// See [`exec::set_entry_budget`](crate::exec::set_entry_budget).
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, EntryPoint};
+///
+/// unsafe extern "C" fn init() {
+///     exec::set_entry_budget(EntryPoint::Reply, 1_000_000_000)
+///         .expect("Unable to set budget");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Hash, Ord, PartialEq, PartialOrd, Eq)]
+#[repr(u32)]
+pub enum EntryPoint {
+    /// `handle` entry point.
+    Handle = 1,
+    /// `handle_reply` entry point.
+    Reply = 2,
+    /// `handle_signal` entry point.
+    Signal = 3,
+}
+
+/// Level of the program trace.
+///
+/// Nodes log traces of levels up to the configured one, see
+/// [`exec::trace`](crate::exec::trace).
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, TraceLevel};
+///
+/// unsafe extern "C" fn handle() {
+///     exec::trace(TraceLevel::Info, "Handling started");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Hash, Ord, PartialEq, PartialOrd, Eq)]
+#[repr(u32)]
+pub enum TraceLevel {
+    /// Errors of the program.
+    Error = 1,
+    /// Hazardous situations.
+    Warn = 2,
+    /// Useful information.
+    Info = 3,
+    /// Lower priority information.
+    Debug = 4,
+    /// Very low priority, often extremely verbose, information.
+    Trace = 5,
+}
+
 /// Program identifier.
 ///
 /// Gear allows users and programs to interact with other users and programs via
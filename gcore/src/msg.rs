@@ -24,17 +24,52 @@
 //! contains code for processing an incoming message. During a message
 //! processing a program can send messages to other programs and users including
 //! reply to the initial message.
+//!
+//! Delays of messages are given in blocks as `Option<NonZeroU32>`: `None`
+//! enqueues the message at once, while `Some(n)` schedules it `n` blocks
+//! later.
+
+use crate::{
+    error::{ExecutionError, ExtError, Result},
+    utils, ActorId, CodeHash, MessageHandle, MessageId, ReservationId,
+};
+use core::{mem::MaybeUninit, num::NonZeroU32};
+pub use gear_core_errors::{ReplyCode, SignalCode};
 
-use crate::{error::Result, ActorId, MessageHandle, MessageId};
-use core::mem::MaybeUninit;
+/// Max size of the message metadata (64 bytes).
+///
+/// See [`send_with_meta`] and [`message_meta`].
+pub const MAX_METADATA_SIZE: usize = 64;
 
 mod sys {
     use crate::error::SyscallError;
 
     extern "C" {
         pub fn gr_exit_code() -> i32;
+        pub fn gr_forward(
+            program: *const u8,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            delay_ptr: *const u8,
+        ) -> SyscallError;
+        pub fn gr_mailbox_contains(user_id_ptr: *const u8, message_id_ptr: *const u8) -> i32;
+        pub fn gr_max_payload_size() -> u32;
+        pub fn gr_message_meta(metadata_ptr: *mut u8) -> u32;
         pub fn gr_msg_id(val: *mut u8);
         pub fn gr_read(at: u32, len: u32, dest: *mut u8);
+        pub fn gr_read_records(
+            index: u32,
+            buffer_ptr: *mut u8,
+            buffer_len: u32,
+            record_len_ptr: *mut u32,
+        ) -> SyscallError;
+        pub fn gr_read_ring(
+            at: u32,
+            len: u32,
+            ring_ptr: *mut u8,
+            capacity: u32,
+            offset: u32,
+        ) -> u32;
         pub fn gr_reply(
             data_ptr: *const u8,
             data_len: u32,
@@ -42,6 +77,14 @@ mod sys {
             message_id_ptr: *mut u8,
             delay_ptr: *const u8,
         ) -> SyscallError;
+        pub fn gr_reply_with_code(
+            data_ptr: *const u8,
+            data_len: u32,
+            exit_code: i32,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            delay_ptr: *const u8,
+        ) -> SyscallError;
         pub fn gr_reply_wgas(
             data_ptr: *const u8,
             data_len: u32,
@@ -63,6 +106,25 @@ mod sys {
         ) -> SyscallError;
         pub fn gr_reply_push(data_ptr: *const u8, data_len: u32) -> SyscallError;
         pub fn gr_reply_to(dest: *mut u8);
+        pub fn gr_reservation_send(
+            reservation_id_ptr: *const u8,
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            delay_ptr: *const u8,
+        ) -> SyscallError;
+        pub fn gr_reserve_and_send(
+            amount_ptr: *const u8,
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            value_ptr: *const u8,
+            reservation_id_ptr: *mut u8,
+            message_id_ptr: *mut u8,
+            delay_ptr: *const u8,
+        ) -> SyscallError;
         pub fn gr_send(
             program: *const u8,
             data_ptr: *const u8,
@@ -71,6 +133,13 @@ mod sys {
             message_id_ptr: *mut u8,
             delay_ptr: *const u8,
         ) -> SyscallError;
+        pub fn gr_send_and_wait(
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+        ) -> SyscallError;
         pub fn gr_send_wgas(
             program: *const u8,
             data_ptr: *const u8,
@@ -80,6 +149,33 @@ mod sys {
             message_id_ptr: *mut u8,
             delay_ptr: *const u8,
         ) -> SyscallError;
+        pub fn gr_send_with_meta(
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            metadata_ptr: *const u8,
+            metadata_len: u32,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            delay_ptr: *const u8,
+        ) -> SyscallError;
+        pub fn gr_send_with_reply_to(
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            reply_to_ptr: *const u8,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            delay_ptr: *const u8,
+        ) -> SyscallError;
+        pub fn gr_send_charge_recipient(
+            program: *const u8,
+            data_ptr: *const u8,
+            data_len: u32,
+            gas_limit: u64,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+        ) -> SyscallError;
         pub fn gr_send_commit(
             handle: u32,
             message_id_ptr: *mut u8,
@@ -97,8 +193,12 @@ mod sys {
         ) -> SyscallError;
         pub fn gr_send_init(handle: *mut u32) -> SyscallError;
         pub fn gr_send_push(handle: u32, data_ptr: *const u8, data_len: u32) -> SyscallError;
+        pub fn gr_set_charge_allowance(allowance: u64);
+        pub fn gr_set_default_gas_limit(gas_limit: u64);
+        pub fn gr_signal_details(message_id_ptr: *mut u8, code_ptr: *mut u8) -> SyscallError;
         pub fn gr_size() -> u32;
         pub fn gr_source(program: *mut u8);
+        pub fn gr_source_code_id(code_id_ptr: *mut u8) -> SyscallError;
         pub fn gr_value(val: *mut u8);
     }
 }
@@ -122,6 +222,56 @@ pub fn exit_code() -> i32 {
     unsafe { sys::gr_exit_code() }
 }
 
+/// Get the exit code of the message being processed as a [`ReplyCode`].
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle_reply() {
+///     if msg::status_code().is_system_error() {
+///         // The replied message failed to be processed
+///     }
+/// }
+/// ```
+pub fn status_code() -> ReplyCode {
+    exit_code().into()
+}
+
+/// Get the id of the message the signal is sent about and the reason
+/// of the signal.
+///
+/// This function is used in signal handler to clean up after the message
+/// which failed to be processed.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle_signal() {
+///     let (message_id, code) = msg::signal_details().expect("Not in signal handler");
+///     // ...
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if called in a context other than `handle_signal()`,
+/// or if the signal code is unknown to the program.
+pub fn signal_details() -> Result<(MessageId, SignalCode)> {
+    let mut message_id = MessageId::default();
+    let mut code = [0u8; 4];
+    unsafe {
+        sys::gr_signal_details(message_id.0.as_mut_ptr(), code.as_mut_ptr()).into_result()?;
+    }
+
+    let code = SignalCode::from_u32(u32::from_le_bytes(code))
+        .ok_or(ExtError::Execution(ExecutionError::UnknownSignalCode))?;
+    Ok((message_id, code))
+}
+
 /// Obtain an identifier of the message currently being processed.
 ///
 /// Message identifiers can be obtained for the currently processed message,
@@ -171,6 +321,98 @@ pub fn load(buffer: &mut [u8]) {
     }
 }
 
+/// Copy the part of the payload starting at `at` into the `buffer`,
+/// filling the whole `buffer`.
+///
+/// Unlike [`load`], only the requested bytes are copied, e.g. to inspect
+/// the leading bytes of a large payload. Execution traps if the requested
+/// range is out of the payload.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     let mut selector = [0u8; 4];
+///     msg::read(0, &mut selector);
+/// }
+/// ```
+pub fn read(at: usize, buffer: &mut [u8]) {
+    if !buffer.is_empty() {
+        unsafe { sys::gr_read(at as _, buffer.len() as _, buffer.as_mut_ptr()) }
+    }
+}
+
+/// Copy `len` bytes of the payload starting at `at` into the `ring` buffer.
+///
+/// Bytes are written starting at the `offset` of the `ring` and wrap around
+/// to its start when reaching the end. Returns the offset following
+/// the written bytes, which is the offset for the next call.
+///
+/// Execution traps if the payload range is out of the message size,
+/// `offset` is out of the `ring` or `len` exceeds its length.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// static mut RING: [u8; 1024] = [0; 1024];
+/// static mut OFFSET: usize = 0;
+///
+/// unsafe extern "C" fn handle() {
+///     let len = msg::size().min(RING.len());
+///     OFFSET = msg::read_ring(0, len, &mut RING, OFFSET);
+/// }
+/// ```
+pub fn read_ring(at: usize, len: usize, ring: &mut [u8], offset: usize) -> usize {
+    unsafe {
+        sys::gr_read_ring(
+            at as _,
+            len as _,
+            ring.as_mut_ptr(),
+            ring.len() as _,
+            offset as _,
+        ) as _
+    }
+}
+
+/// Copy the record with given `index` of the payload into the `buffer`.
+///
+/// The payload is treated as a sequence of records, each prefixed with its
+/// length encoded as little-endian `u32`. Returns the full length of the
+/// record, which is copied only partially if it exceeds the `buffer`.
+///
+/// Fails with [`MessageError::RecordNotFound`](crate::error::MessageError::RecordNotFound)
+/// if the payload has no record with given `index`. Execution traps if
+/// a length prefix points out of the payload before the record is reached.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     let mut record = [0u8; 32];
+///     let len = msg::read_record(1, &mut record).expect("No second record");
+///     let second = &record[..len.min(record.len())];
+/// }
+/// ```
+pub fn read_record(index: u32, buffer: &mut [u8]) -> Result<usize> {
+    unsafe {
+        let mut record_len = 0u32;
+        sys::gr_read_records(
+            index,
+            buffer.as_mut_ptr(),
+            buffer.len() as _,
+            &mut record_len as *mut u32,
+        )
+        .into_result()?;
+        Ok(record_len as _)
+    }
+}
+
 /// Send a new message as a reply to the message currently being processed.
 ///
 /// Some programs can reply to other programs, i.e. check another program's
@@ -217,7 +459,7 @@ pub fn reply(payload: &[u8], value: u128) -> Result<MessageId> {
 }
 
 /// Same as [`reply`], but sends delayed.
-pub fn reply_delayed(payload: &[u8], value: u128, delay: u32) -> Result<MessageId> {
+pub fn reply_delayed(payload: &[u8], value: u128, delay: Option<NonZeroU32>) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
         sys::gr_reply(
@@ -225,7 +467,7 @@ pub fn reply_delayed(payload: &[u8], value: u128, delay: u32) -> Result<MessageI
             payload.len() as _,
             value.to_le_bytes().as_ptr(),
             message_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -264,12 +506,47 @@ pub fn reply_with_gas(payload: &[u8], gas_limit: u64, value: u128) -> Result<Mes
     }
 }
 
+/// Same as [`reply`], but with the exit code chosen by the program.
+///
+/// The code is available to the receiving program via [`status_code`].
+/// Codes reserved for the replies generated by the system can't be used:
+/// the reply is rejected with
+/// [`MessageError::ReservedExitCode`](crate::error::MessageError::ReservedExitCode).
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg::{self, ReplyCode};
+///
+/// const NOT_FOUND: ReplyCode = ReplyCode::from_raw(404);
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     msg::reply_with_code(NOT_FOUND, b"Unknown key", 0).unwrap();
+/// }
+/// ```
+pub fn reply_with_code(code: ReplyCode, payload: &[u8], value: u128) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_reply_with_code(
+            payload.as_ptr(),
+            payload.len() as _,
+            code.to_raw(),
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            0u32.to_le_bytes().as_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
 /// Same as [`reply_with_gas`], but sends delayed.
 pub fn reply_with_gas_delayed(
     payload: &[u8],
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
@@ -279,7 +556,7 @@ pub fn reply_with_gas_delayed(
             gas_limit,
             value.to_le_bytes().as_ptr(),
             message_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -330,13 +607,13 @@ pub fn reply_commit(value: u128) -> Result<MessageId> {
 }
 
 /// Same as [`reply_commit`], but sends delayed.
-pub fn reply_commit_delayed(value: u128, delay: u32) -> Result<MessageId> {
+pub fn reply_commit_delayed(value: u128, delay: Option<NonZeroU32>) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
         sys::gr_reply_commit(
             value.to_le_bytes().as_ptr(),
             message_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -378,14 +655,18 @@ pub fn reply_commit_with_gas(gas_limit: u64, value: u128) -> Result<MessageId> {
 }
 
 /// Same as [`reply_commit_with_gas`], but sends delayed.
-pub fn reply_commit_with_gas_delayed(gas_limit: u64, value: u128, delay: u32) -> Result<MessageId> {
+pub fn reply_commit_with_gas_delayed(
+    gas_limit: u64,
+    value: u128,
+    delay: Option<NonZeroU32>,
+) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
         sys::gr_reply_commit_wgas(
             gas_limit,
             value.to_le_bytes().as_ptr(),
             message_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -445,6 +726,31 @@ pub fn reply_to() -> MessageId {
     message_id
 }
 
+/// Set the gas limit of messages sent without a specified one.
+///
+/// The limit applies to [`send`], [`reply`] and other functions which don't
+/// take an explicit gas limit, including messages formed in parts. It only
+/// affects the messages sent after the call within the current execution,
+/// so it needs to be set again on every execution.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     msg::set_default_gas_limit(10_000_000);
+///
+///     // Sent with 10_000_000 of gas
+///     msg::send(msg::source(), b"PING", 0).unwrap();
+///     // Sent with 5_000_000 of gas
+///     msg::send_with_gas(msg::source(), b"PING", 5_000_000, 0).unwrap();
+/// }
+/// ```
+pub fn set_default_gas_limit(gas_limit: u64) {
+    unsafe { sys::gr_set_default_gas_limit(gas_limit) }
+}
+
 /// Send a new message to the program or user.
 ///
 /// Gear allows programs to communicate to each other and users via messages.
@@ -493,12 +799,248 @@ pub fn send(program: ActorId, payload: &[u8], value: u128) -> Result<MessageId>
     }
 }
 
+/// Same as [`send`], but attaches `metadata` to the message.
+///
+/// Metadata is an opaque header kept apart from the payload, e.g. routing
+/// or priority tags. The recipient reads it with [`message_meta`].
+///
+/// Execution traps if `metadata` exceeds [`MAX_METADATA_SIZE`].
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// const PRIORITY_HIGH: u8 = 1;
+///
+/// unsafe extern "C" fn handle() {
+///     msg::send_with_meta(msg::source(), b"HELLO", &[PRIORITY_HIGH], 0)
+///         .expect("Unable to send");
+/// }
+/// ```
+pub fn send_with_meta(
+    program: ActorId,
+    payload: &[u8],
+    metadata: &[u8],
+    value: u128,
+) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_send_with_meta(
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            metadata.as_ptr(),
+            metadata.len() as _,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            0u32.to_le_bytes().as_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
+/// Same as [`send`], but the reply on the message is delivered to the
+/// `reply_to` program instead of the current one.
+///
+/// Allows delegating the handling of the reply, e.g. a proxy forwards the
+/// request and lets the reply go straight to the program it acts for. The
+/// recipient sees the current program as the [`source`] of the message, but
+/// replies to `reply_to`. System error replies on the message are delivered
+/// to `reply_to` as well.
+///
+/// Fails with
+/// [`MessageError::InvalidReplyDestination`](crate::error::MessageError::InvalidReplyDestination)
+/// if `reply_to` isn't an existing program: replies can't be redirected to
+/// users.
+///
+/// # Security
+///
+/// The `reply_to` program receives a reply on a message it never sent, so
+/// the `reply_to` message id it gets in `handle_reply()` is unknown to it.
+/// Such program must not trust the reply just because of its [`reply_to`] id
+/// and should check the [`source`] of the reply against the programs it
+/// expects replies from. Conversely, the sender
+/// never sees the reply, so it can't wait for it.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{msg, ActorId};
+///
+/// static mut CLIENT: ActorId = ActorId([0; 32]);
+/// static mut SERVICE: ActorId = ActorId([0; 32]);
+///
+/// unsafe extern "C" fn handle() {
+///     // Forward the request to the service and let it reply to the client.
+///     let mut payload = vec![0; msg::size()];
+///     msg::read(&mut payload).expect("Unable to read");
+///     msg::send_with_reply_to(SERVICE, &payload, CLIENT, 0).expect("Unable to send");
+/// }
+/// ```
+pub fn send_with_reply_to(
+    program: ActorId,
+    payload: &[u8],
+    reply_to: ActorId,
+    value: u128,
+) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_send_with_reply_to(
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            reply_to.as_slice().as_ptr(),
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            0u32.to_le_bytes().as_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
+/// Send a new message to the program or user and wait for the reply.
+///
+/// Combines [`send`] and [`exec::wait`](crate::exec::wait) into a single
+/// call, so the current message can't be left running after sending.
+/// Once the message is sent, its id is written to `message_id` and the
+/// current message is put into the *waiting queue*, so `message_id` should
+/// point to memory surviving the execution, e.g. a static. The message is
+/// woken by the reply to the sent one, before the reply is handled by
+/// `handle_reply()`.
+///
+/// The execution is stopped once the message is sent, so the function
+/// returns only with the error of sending it.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{msg, MessageId};
+///
+/// static mut SENT_ID: MessageId = MessageId([0; 32]);
+/// static mut SENT: bool = false;
+///
+/// unsafe extern "C" fn handle() {
+///     if !SENT {
+///         SENT = true;
+///         msg::send_and_wait(msg::source(), b"PING", 0, &mut SENT_ID).expect("Unable to send");
+///     }
+///
+///     // Woken by the reply to `SENT_ID`.
+///     // ...
+/// }
+/// ```
+pub fn send_and_wait(
+    program: ActorId,
+    payload: &[u8],
+    value: u128,
+    message_id: &mut MessageId,
+) -> Result<()> {
+    unsafe {
+        sys::gr_send_and_wait(
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+        )
+        .into_result()
+    }
+}
+
+/// Same as [`send`], but the message value is drawn from the value reservation
+/// made by [`exec::reserve_value`](crate::exec::reserve_value).
+///
+/// The reservation is removed once all its value is drawn.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let reservation_id = exec::reserve_value(1000).expect("Unable to reserve");
+///     // ...
+///     msg::reservation_send(reservation_id, msg::source(), b"PAYOUT", 400)
+///         .expect("Unable to send");
+/// }
+/// ```
+pub fn reservation_send(
+    reservation_id: ReservationId,
+    program: ActorId,
+    payload: &[u8],
+    value: u128,
+) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_reservation_send(
+            reservation_id.as_slice().as_ptr(),
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            0u32.to_le_bytes().as_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
+/// Reserve `amount` of value and send a delayed message drawing its value
+/// from the reservation, returning ids of both.
+///
+/// Unlike separate [`exec::reserve_value`](crate::exec::reserve_value) and
+/// [`reservation_send`] calls, either both succeed or nothing is reserved.
+/// The rest of the reservation can be spent by later [`reservation_send`]
+/// calls; the reservation is removed if the message takes all its value.
+///
+/// # Examples
+///
+/// ```
+/// use core::num::NonZeroU32;
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let (reservation_id, message_id) =
+///         msg::reserve_and_send(1000, msg::source(), b"PAYOUT", 400, NonZeroU32::new(10))
+///             .expect("Unable to reserve and send");
+/// }
+/// ```
+pub fn reserve_and_send(
+    amount: u128,
+    program: ActorId,
+    payload: &[u8],
+    value: u128,
+    delay: Option<NonZeroU32>,
+) -> Result<(ReservationId, MessageId)> {
+    unsafe {
+        let mut reservation_id = ReservationId::default();
+        let mut message_id = MessageId::default();
+        sys::gr_reserve_and_send(
+            amount.to_le_bytes().as_ptr(),
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            value.to_le_bytes().as_ptr(),
+            reservation_id.as_mut_slice().as_mut_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
+        )
+        .into_result()?;
+        Ok((reservation_id, message_id))
+    }
+}
+
 /// Same as [`send`], but sends delayed.
 pub fn send_delayed(
     program: ActorId,
     payload: &[u8],
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
@@ -508,7 +1050,51 @@ pub fn send_delayed(
             payload.len() as _,
             value.to_le_bytes().as_ptr(),
             message_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
+/// Send the payload of the current message to the program or user as is.
+///
+/// Unlike [`send`] with the payload got by [`load`], the payload isn't
+/// read into the program memory, so proxies and routers don't pay for
+/// copying it back and forth.
+///
+/// The forwarded message is sent by the current program, so the reply to it
+/// comes to the current program rather than to [`source`] of the current
+/// message.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{msg, ActorId};
+///
+/// static mut TARGET: ActorId = ActorId([0; 32]);
+///
+/// unsafe extern "C" fn handle() {
+///     msg::forward(TARGET, msg::value()).expect("Unable to forward");
+/// }
+/// ```
+pub fn forward(program: ActorId, value: u128) -> Result<MessageId> {
+    forward_delayed(program, value, None)
+}
+
+/// Same as [`forward`], but sends delayed.
+pub fn forward_delayed(
+    program: ActorId,
+    value: u128,
+    delay: Option<NonZeroU32>,
+) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_forward(
+            program.as_slice().as_ptr(),
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -565,7 +1151,7 @@ pub fn send_with_gas_delayed(
     payload: &[u8],
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
@@ -576,13 +1162,81 @@ pub fn send_with_gas_delayed(
             gas_limit,
             value.to_le_bytes().as_ptr(),
             message_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
     }
 }
 
+/// Same as [`send_with_gas`], but the gas for message processing is paid by
+/// the recipient program from its balance instead of the current message.
+///
+/// `gas_limit` must be not less than the mailbox threshold. Value is still
+/// transferred from the current program account.
+///
+/// The recipient has to agree to pay by setting its allowance with
+/// [`set_charge_allowance`]. If the recipient is not an active program, its
+/// allowance is less than `gas_limit` or it can't afford `gas_limit`, the
+/// message is not delivered and the current program receives an error reply
+/// to the returned message id.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{msg, ActorId};
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let id = msg::source();
+///
+///     msg::send_charge_recipient(id, b"HELLO", 10_000_000, 0)
+///         .expect("Failed to send message");
+/// }
+/// ```
+pub fn send_charge_recipient(
+    program: ActorId,
+    payload: &[u8],
+    gas_limit: u64,
+    value: u128,
+) -> Result<MessageId> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        sys::gr_send_charge_recipient(
+            program.as_slice().as_ptr(),
+            payload.as_ptr(),
+            payload.len() as _,
+            gas_limit,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+        )
+        .into_result()?;
+        Ok(message_id)
+    }
+}
+
+/// Set the total amount of gas the program agrees to pay for messages sent
+/// to it with [`send_charge_recipient`].
+///
+/// The allowance is stored with the program and replaces the previous one.
+/// Each message paid decreases it by the message gas limit, and messages
+/// not fitting into the allowance left aren't delivered. Zero allowance,
+/// which programs have by default, refuses all such messages.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn init() {
+///     // Pay for up to 100 messages of 10_000_000 gas
+///     msg::set_charge_allowance(1_000_000_000);
+/// }
+/// ```
+pub fn set_charge_allowance(allowance: u64) {
+    unsafe { sys::gr_set_charge_allowance(allowance) }
+}
+
 /// Finalize and send message formed in parts.
 ///
 /// Gear allows programs to work with messages that consist of several parts.
@@ -637,7 +1291,7 @@ pub fn send_commit_delayed(
     handle: MessageHandle,
     program: ActorId,
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
@@ -646,7 +1300,7 @@ pub fn send_commit_delayed(
             message_id.as_mut_slice().as_mut_ptr(),
             program.as_slice().as_ptr(),
             value.to_le_bytes().as_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -701,7 +1355,7 @@ pub fn send_commit_with_gas_delayed(
     program: ActorId,
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<MessageId> {
     unsafe {
         let mut message_id = MessageId::default();
@@ -711,7 +1365,7 @@ pub fn send_commit_with_gas_delayed(
             program.as_slice().as_ptr(),
             gas_limit,
             value.to_le_bytes().as_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(message_id)
@@ -798,6 +1452,81 @@ pub fn size() -> usize {
     unsafe { sys::gr_size() as _ }
 }
 
+/// Get the maximum size of a message payload.
+///
+/// The limit may change across runtime upgrades, so it should be
+/// queried instead of being hardcoded, e.g. to split large data
+/// into messages.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let data = [0u8; 1024];
+///
+///     for chunk in data.chunks(msg::max_payload_size()) {
+///         msg::send(msg::source(), chunk, 0).expect("Unable to send");
+///     }
+/// }
+/// ```
+pub fn max_payload_size() -> usize {
+    unsafe { sys::gr_max_payload_size() as _ }
+}
+
+/// Tell whether the message sent to the `user` is still in the user's
+/// mailbox, i.e. it wasn't claimed, replied or removed on timeout yet.
+///
+/// Only messages sent by this program can be looked up: messages sent by
+/// other actors are reported as absent.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{msg, ActorId, MessageId};
+///
+/// static mut SENT: Option<(ActorId, MessageId)> = None;
+///
+/// unsafe extern "C" fn handle() {
+///     match SENT {
+///         Some((user, message_id)) if msg::mailbox_contains(user, message_id) => {
+///             // The user hasn't responded yet.
+///         }
+///         _ => {
+///             let user = msg::source();
+///             SENT = Some((user, msg::send(user, b"PING", 0).expect("Unable to send")));
+///         }
+///     }
+/// }
+/// ```
+pub fn mailbox_contains(user: ActorId, message_id: MessageId) -> bool {
+    unsafe {
+        sys::gr_mailbox_contains(user.as_slice().as_ptr(), message_id.as_slice().as_ptr()) != 0
+    }
+}
+
+/// Copy metadata of the message being processed into the `buffer`,
+/// returning its length.
+///
+/// Metadata is empty unless the sender set it with [`send_with_meta`].
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     let mut buffer = [0u8; msg::MAX_METADATA_SIZE];
+///     let len = msg::message_meta(&mut buffer);
+///     let high_priority = buffer[..len].first() == Some(&1);
+/// }
+/// ```
+pub fn message_meta(buffer: &mut [u8; MAX_METADATA_SIZE]) -> usize {
+    unsafe { sys::gr_message_meta(buffer.as_mut_ptr()) as _ }
+}
+
 /// Get the identifier of the message source (256-bit address).
 ///
 /// This function is used to obtain [`ActorId`] of the account that sends
@@ -819,6 +1548,29 @@ pub fn source() -> ActorId {
     program_id
 }
 
+/// Get the code hash of the program that sent the message being processed.
+///
+/// Returns an error if the message source has no code, e.g. when the
+/// message was sent by a user.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     if let Ok(code_hash) = msg::source_code_id() {
+///         // The message came from a program.
+///     }
+/// }
+/// ```
+pub fn source_code_id() -> Result<CodeHash> {
+    let mut code_hash = CodeHash::default();
+    unsafe { sys::gr_source_code_id(code_hash.0.as_mut_ptr()).into_result()? }
+    Ok(code_hash)
+}
+
 /// Get the value associated with the message being processed.
 ///
 /// This function is used to obtain the value that has been sent along with
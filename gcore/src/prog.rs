@@ -18,7 +18,8 @@
 
 //! Program creation API for Gear programs.
 
-use crate::{error::Result, ActorId, CodeHash};
+use crate::{error::Result, utils, ActorId, CodeHash, MessageId};
+use core::num::NonZeroU32;
 
 mod sys {
     use crate::error::SyscallError;
@@ -46,6 +47,31 @@ mod sys {
             program_id_ptr: *mut u8,
             delay: *const u8,
         ) -> SyscallError;
+
+        pub fn gr_create_program_for_reply(
+            code_hash: *const u8,
+            salt_ptr: *const u8,
+            salt_len: u32,
+            data_ptr: *const u8,
+            data_len: u32,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            program_id_ptr: *mut u8,
+            delay: *const u8,
+        ) -> SyscallError;
+
+        pub fn gr_create_program_wgas_for_reply(
+            code_hash: *const u8,
+            salt_ptr: *const u8,
+            salt_len: u32,
+            data_ptr: *const u8,
+            data_len: u32,
+            gas_limit: u64,
+            value_ptr: *const u8,
+            message_id_ptr: *mut u8,
+            program_id_ptr: *mut u8,
+            delay: *const u8,
+        ) -> SyscallError;
     }
 }
 
@@ -79,7 +105,7 @@ pub fn create_program_delayed(
     salt: &[u8],
     payload: &[u8],
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<ActorId> {
     unsafe {
         let mut program_id = ActorId::default();
@@ -91,7 +117,7 @@ pub fn create_program_delayed(
             payload.len() as _,
             value.to_le_bytes().as_ptr(),
             program_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(program_id)
@@ -109,6 +135,11 @@ pub fn create_program_delayed(
 /// Code of newly creating program must be represented as blake2b hash
 /// (`code_hash` parameter).
 ///
+/// Non-zero `value` must be at least existential deposit. Zero `value` is
+/// handled according to the existential deposit policy of the runtime: either
+/// existential deposit is sent instead, so the program should be able to
+/// afford it, or the new program's account is kept without it.
+///
 /// # Examples
 ///
 /// In order to generate an address for a new program `salt` must be provided.
@@ -197,7 +228,7 @@ pub fn create_program_with_gas_delayed(
     payload: &[u8],
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: Option<NonZeroU32>,
 ) -> Result<ActorId> {
     unsafe {
         let mut program_id = ActorId::default();
@@ -210,9 +241,137 @@ pub fn create_program_with_gas_delayed(
             gas_limit,
             value.to_le_bytes().as_ptr(),
             program_id.as_mut_slice().as_mut_ptr(),
-            delay.to_le_bytes().as_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
         )
         .into_result()?;
         Ok(program_id)
     }
 }
+
+/// Same as [`create_program`], but also returns id of the init message.
+///
+/// The reply the new program sends from its `init` function is a reply to
+/// this message, so the creator may wait for it to get the payload.
+///
+/// # Examples
+///
+/// ```
+/// use gcore::{prog, CodeHash};
+///
+/// unsafe extern "C" fn handle() {
+///     # let submitted_code: CodeHash = hex_literal::hex!("abf3746e72a6e8740bd9e12b879fbdd59e052cb390f116454e9116c22021ae4a").into();
+///     let (init_message_id, new_program_id) =
+///         prog::create_program_for_reply(submitted_code, b"salt", b"", 0).unwrap();
+/// }
+/// ```
+pub fn create_program_for_reply(
+    code_hash: CodeHash,
+    salt: &[u8],
+    payload: &[u8],
+    value: u128,
+) -> Result<(MessageId, ActorId)> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        let mut program_id = ActorId::default();
+        sys::gr_create_program_for_reply(
+            code_hash.as_slice().as_ptr(),
+            salt.as_ptr(),
+            salt.len() as _,
+            payload.as_ptr(),
+            payload.len() as _,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            program_id.as_mut_slice().as_mut_ptr(),
+            0u32.to_le_bytes().as_ptr(),
+        )
+        .into_result()?;
+        Ok((message_id, program_id))
+    }
+}
+
+/// Same as [`create_program_with_gas`], but also returns id of the init
+/// message.
+///
+/// See [`create_program_for_reply`] for details.
+pub fn create_program_with_gas_for_reply(
+    code_hash: CodeHash,
+    salt: &[u8],
+    payload: &[u8],
+    gas_limit: u64,
+    value: u128,
+) -> Result<(MessageId, ActorId)> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        let mut program_id = ActorId::default();
+        sys::gr_create_program_wgas_for_reply(
+            code_hash.as_slice().as_ptr(),
+            salt.as_ptr(),
+            salt.len() as _,
+            payload.as_ptr(),
+            payload.len() as _,
+            gas_limit,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            program_id.as_mut_slice().as_mut_ptr(),
+            0u32.to_le_bytes().as_ptr(),
+        )
+        .into_result()?;
+        Ok((message_id, program_id))
+    }
+}
+
+/// Same as [`create_program_for_reply`], but sends delayed.
+pub fn create_program_for_reply_delayed(
+    code_hash: CodeHash,
+    salt: &[u8],
+    payload: &[u8],
+    value: u128,
+    delay: Option<NonZeroU32>,
+) -> Result<(MessageId, ActorId)> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        let mut program_id = ActorId::default();
+        sys::gr_create_program_for_reply(
+            code_hash.as_slice().as_ptr(),
+            salt.as_ptr(),
+            salt.len() as _,
+            payload.as_ptr(),
+            payload.len() as _,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            program_id.as_mut_slice().as_mut_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
+        )
+        .into_result()?;
+        Ok((message_id, program_id))
+    }
+}
+
+/// Same as [`create_program_with_gas_for_reply`], but sends delayed.
+pub fn create_program_with_gas_for_reply_delayed(
+    code_hash: CodeHash,
+    salt: &[u8],
+    payload: &[u8],
+    gas_limit: u64,
+    value: u128,
+    delay: Option<NonZeroU32>,
+) -> Result<(MessageId, ActorId)> {
+    unsafe {
+        let mut message_id = MessageId::default();
+        let mut program_id = ActorId::default();
+        sys::gr_create_program_wgas_for_reply(
+            code_hash.as_slice().as_ptr(),
+            salt.as_ptr(),
+            salt.len() as _,
+            payload.as_ptr(),
+            payload.len() as _,
+            gas_limit,
+            value.to_le_bytes().as_ptr(),
+            message_id.as_mut_slice().as_mut_ptr(),
+            program_id.as_mut_slice().as_mut_ptr(),
+            utils::delay_bytes(delay).as_ptr(),
+        )
+        .into_result()?;
+        Ok((message_id, program_id))
+    }
+}
@@ -16,6 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use core::num::NonZeroU32;
+
+/// Encode the delay in blocks the way syscalls take it, zero for no delay.
+pub(crate) fn delay_bytes(delay: Option<NonZeroU32>) -> [u8; 4] {
+    delay.map_or(0, NonZeroU32::get).to_le_bytes()
+}
+
 #[cfg(feature = "debug")]
 pub mod ext {
     mod sys {
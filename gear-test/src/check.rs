@@ -234,7 +234,7 @@ pub fn check_messages(
                     },
                 );
 
-                if msg.exit_code().unwrap_or(0) == 0
+                if !msg.is_error_reply()
                     && exp
                         .payload
                         .as_mut()
@@ -20,7 +20,8 @@ use crate::check::ExecutionContext;
 use core_processor::common::*;
 use gear_core::{
     code::{Code, CodeAndId, InstrumentedCodeAndId},
-    ids::{CodeId, MessageId, ProgramId},
+    counters::CounterKey,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
     message::{Dispatch, DispatchKind, GasLimit, StoredDispatch, StoredMessage},
     program::Program,
@@ -28,6 +29,7 @@ use gear_core::{
 use std::{
     collections::{BTreeMap, BTreeSet, VecDeque},
     fmt,
+    num::NonZeroU32,
 };
 use wasm_instrument::gas_metering::ConstantCostRules;
 
@@ -149,6 +151,8 @@ impl ExecutionContext for InMemoryExtManager {
                 executable_data: Some(ExecutableActorData {
                     program: program.clone(),
                     pages_with_data: Default::default(),
+                    value_reservations: Default::default(),
+                    children_count: Default::default(),
                 }),
                 memory_pages: Default::default(),
             },
@@ -224,7 +228,21 @@ impl JournalHandler for InMemoryExtManager {
     fn gas_burned(&mut self, _message_id: MessageId, _amount: u64) {}
 
     fn exit_dispatch(&mut self, id_exited: ProgramId, _value_destination: ProgramId) {
-        self.actors.remove(&id_exited);
+        let exited_in_init = self
+            .actors
+            .get(&id_exited)
+            .and_then(|actor| actor.executable_data.as_ref())
+            .map_or(false, |data| !data.program.is_initialized());
+
+        if exited_in_init {
+            self.move_waiting_msgs_to_queue(id_exited);
+            if let Some(actor) = self.actors.get_mut(&id_exited) {
+                // Program exited in init is kept, so its id can't be used again.
+                actor.executable_data = None;
+            }
+        } else {
+            self.actors.remove(&id_exited);
+        }
     }
 
     fn message_consumed(&mut self, message_id: MessageId) {
@@ -236,7 +254,12 @@ impl JournalHandler for InMemoryExtManager {
             self.dispatch_queue.remove(index);
         }
     }
-    fn send_dispatch(&mut self, _message_id: MessageId, dispatch: Dispatch, _delay: u32) {
+    fn send_dispatch(
+        &mut self,
+        _message_id: MessageId,
+        dispatch: Dispatch,
+        _delay: Option<NonZeroU32>,
+    ) {
         let destination = dispatch.destination();
         if self.actors.contains_key(&destination) || self.marked_destinations.contains(&destination)
         {
@@ -265,6 +288,15 @@ impl JournalHandler for InMemoryExtManager {
             self.log.push(dispatch.into_parts().1.into_stored());
         }
     }
+    fn send_dispatch_charge_recipient(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+    ) {
+        // Gas isn't bought here, so it doesn't matter who pays for it.
+        self.send_dispatch(message_id, dispatch, delay);
+    }
     fn wait_dispatch(&mut self, dispatch: StoredDispatch, _duration: Option<u32>) {
         self.message_consumed(dispatch.id());
         self.wait_list
@@ -275,7 +307,7 @@ impl JournalHandler for InMemoryExtManager {
         _message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
-        _delay: u32,
+        _delay: Option<NonZeroU32>,
     ) {
         if let Some(dispatch) = self.wait_list.remove(&(program_id, awakening_id)) {
             self.dispatch_queue.push_back(dispatch);
@@ -327,6 +359,66 @@ impl JournalHandler for InMemoryExtManager {
         }
     }
 
+    fn update_value_reservations(
+        &mut self,
+        program_id: ProgramId,
+        reservations: BTreeMap<ReservationId, u128>,
+    ) {
+        if let Some(TestActor {
+            executable_data: Some(data),
+            ..
+        }) = self.actors.get_mut(&program_id)
+        {
+            data.value_reservations = reservations;
+        }
+    }
+
+    fn update_children_count(&mut self, program_id: ProgramId, children_count: u64) {
+        if let Some(TestActor {
+            executable_data: Some(data),
+            ..
+        }) = self.actors.get_mut(&program_id)
+        {
+            data.children_count = children_count;
+        }
+    }
+
+    fn child_gas_burned(&mut self, _program_id: ProgramId, _amount: u64) {
+        // Gas burned by children is read lazily by the processor, which
+        // can't reach the state of the fixtures, so it isn't kept.
+    }
+
+    fn update_counters(&mut self, _program_id: ProgramId, _counters: BTreeMap<CounterKey, u64>) {
+        // Counters are loaded lazily by the processor, which can't reach
+        // the state of the fixtures, so they aren't kept between executions.
+    }
+
+    fn update_entry_budgets(
+        &mut self,
+        _program_id: ProgramId,
+        _entry_budgets: BTreeMap<DispatchKind, u64>,
+    ) {
+        // Entry budgets are loaded lazily by the processor, which can't reach
+        // the state of the fixtures, so they aren't kept between executions.
+    }
+
+    fn update_charge_allowance(&mut self, _program_id: ProgramId, _allowance: u64) {
+        // Gas isn't bought by the fixtures, so recipients never pay for it.
+    }
+
+    fn update_reply_waker(
+        &mut self,
+        _program_id: ProgramId,
+        _reply_to: MessageId,
+        _waiting: Option<MessageId>,
+    ) {
+        // Fixtures wake the waiting messages explicitly.
+    }
+
+    fn schedule_exit(&mut self, _program_id: ProgramId, _block: u32, _inheritor: ProgramId) {
+        // Blocks aren't spent by the fixtures, so the scheduled exit never comes.
+    }
+
     fn send_value(&mut self, from: ProgramId, to: Option<ProgramId>, value: u128) {
         if let Some(to) = to {
             if let Some(actor) = self.actors.get_mut(&from) {
@@ -348,7 +440,8 @@ impl JournalHandler for InMemoryExtManager {
             for (candidate_id, init_message_id) in candidates {
                 if !self.actors.contains_key(&candidate_id) {
                     let code =
-                        Code::try_new(code.clone(), 1, |_| ConstantCostRules::default()).unwrap();
+                        Code::try_new(code.clone(), 1, |_| ConstantCostRules::default(), None)
+                            .unwrap();
 
                     self.store_program(candidate_id, code, init_message_id);
                 } else {
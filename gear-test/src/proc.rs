@@ -43,6 +43,7 @@ pub const OUTGOING_LIMIT: u32 = 1024;
 pub const MAILBOX_THRESHOLD: u64 = 3000;
 pub const WAITLIST_COST: u64 = 100;
 pub const RESERVE_FOR: u32 = 1;
+pub const BLOCK_DURATION: u64 = 1000;
 
 pub fn parse_payload(payload: String) -> String {
     let program_id_regex = Regex::new(r"\{(?P<id>[0-9]+)\}").unwrap();
@@ -91,8 +92,13 @@ where
     E: Environment<Ext>,
     JH: JournalHandler + CollectState + ExecutionContext,
 {
-    let code = Code::try_new(message.code.clone(), 1, |_| ConstantCostRules::default())
-        .map_err(|e| anyhow::anyhow!("Error initialization: {:?}", &e))?;
+    let code = Code::try_new(
+        message.code.clone(),
+        1,
+        |_| ConstantCostRules::default(),
+        None,
+    )
+    .map_err(|e| anyhow::anyhow!("Error initialization: {:?}", &e))?;
 
     let program = journal_handler.store_program(message.id, code, message.message.id());
     let program_id = program.id();
@@ -107,10 +113,14 @@ where
             executable_data: Some(ExecutableActorData {
                 program,
                 pages_with_data: Default::default(),
+                value_reservations: Default::default(),
+                children_count: Default::default(),
             }),
         },
         dispatch: message.into(),
         origin: Default::default(),
+        tx_hash: None,
+        execution_id: 0,
         gas_allowance: u64::MAX,
         subsequent_execution: false,
     };
@@ -142,8 +152,13 @@ where
         for code in codes {
             let code_bytes = std::fs::read(&code.path)
                 .map_err(|e| IoError::new(IoErrorKind::Other, format!("`{}': {}", code.path, e)))?;
-            let code = Code::try_new(code_bytes.clone(), 1, |_| ConstantCostRules::default())
-                .map_err(|e| anyhow::anyhow!("Error initialization: {:?}", &e))?;
+            let code = Code::try_new(
+                code_bytes.clone(),
+                1,
+                |_| ConstantCostRules::default(),
+                None,
+            )
+            .map_err(|e| anyhow::anyhow!("Error initialization: {:?}", &e))?;
 
             let (code, code_id) = CodeAndId::new(code).into_parts();
 
@@ -258,7 +273,7 @@ where
         );
         let dispatch = Dispatch::new(DispatchKind::Handle, message);
 
-        journal_handler.send_dispatch(Default::default(), dispatch, 0);
+        journal_handler.send_dispatch(Default::default(), dispatch, None);
 
         nonce += 1;
     }
@@ -300,6 +315,8 @@ where
                     actor,
                     dispatch: dispatch.into_incoming(gas_limit),
                     origin: Default::default(),
+                    tx_hash: None,
+                    execution_id: 0,
                     gas_allowance: u64::MAX,
                     subsequent_execution: false,
                 };
@@ -346,6 +363,8 @@ where
                 actor,
                 dispatch: dispatch.into_incoming(gas_limit),
                 origin: Default::default(),
+                tx_hash: None,
+                execution_id: 0,
                 gas_allowance: u64::MAX,
                 subsequent_execution: false,
             };
@@ -374,13 +393,28 @@ where
 fn test_block_config(block_info: BlockInfo) -> BlockConfig {
     BlockConfig {
         block_info,
+        block_duration: BLOCK_DURATION,
         allocations_config: Default::default(),
         existential_deposit: EXISTENTIAL_DEPOSIT,
+        existential_deposit_policy: Default::default(),
         outgoing_limit: OUTGOING_LIMIT,
         host_fn_weights: Default::default(),
         forbidden_funcs: Default::default(),
         mailbox_threshold: MAILBOX_THRESHOLD,
         waitlist_cost: WAITLIST_COST,
+        reply_waker_cost: 0,
         reserve_for: RESERVE_FOR,
+        block_hash_depth: 0,
+        block_hash: |_| Default::default(),
+        program_exists: |_| false,
+        mailbox_contains: |_, _, _| false,
+        codes_count: || 0,
+        delayed_dispatch: |_, _| None,
+        program_counters: |_| Default::default(),
+        children_gas_burned: |_| 0,
+        code_id_of: |_| None,
+        reply_waker: |_, _| None,
+        entry_budget: |_, _| None,
+        entry_budget_read_cost: 0,
     }
 }
@@ -155,6 +155,39 @@ pub fn async_init(_attr: TokenStream, item: TokenStream) -> TokenStream {
     generate_handle_reply_if_required(code)
 }
 
+/// Restrict the function to messages sent by the owner of the program.
+///
+/// Attribute argument is an expression evaluating to the owner's `ActorId`.
+/// Messages from anyone else are replied with `gstd::ownership::AccessDenied`
+/// sent as an error reply, and the function body isn't executed.
+///
+/// ## Usage
+///
+/// ```ignore
+/// static mut OWNER: ActorId = ActorId::zero();
+///
+/// #[gstd::only_owner(unsafe { OWNER })]
+/// fn withdraw() {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn only_owner(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let owner = syn::parse_macro_input!(attr as syn::Expr);
+    let mut function = syn::parse_macro_input!(item as syn::ItemFn);
+
+    let body = &function.block;
+    function.block = syn::parse_quote!({
+        if gstd::ownership::only(&(#owner)).is_err() {
+            gstd::ownership::deny();
+        }
+
+        #body
+    });
+
+    quote!(#function).into()
+}
+
 /// Extends async methods `for_reply` and `for_reply_as` for sending
 /// methods.
 ///
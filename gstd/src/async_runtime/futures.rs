@@ -66,6 +66,8 @@ where
 
     if Pin::new(&mut task.future).poll(&mut cx).is_ready() {
         super::futures().remove(&crate::msg::id());
+    } else if let Some(blocks) = crate::timer::blocks_until_fire(crate::msg::id()) {
+        crate::exec::wait_up_to(blocks)
     } else {
         // TODO: make this call configurable (#1380)
         crate::exec::wait_up_to(100)
@@ -28,6 +28,13 @@ use core::task::{Context, Waker};
 pub type Payload = Vec<u8>;
 pub type ExitCode = i32;
 
+/// Amount of blocks the reply to a message, which awaiting was cancelled,
+/// is still passed to the late reply hook for.
+///
+/// Later replies are handled as unexpected ones, so the cancelled messages
+/// don't pile up in the program state.
+pub(crate) const CANCELLED_REPLY_TTL: u32 = 10_000;
+
 #[derive(Debug)]
 pub(crate) enum ReplyPoll {
     None,
@@ -43,12 +50,17 @@ struct WakeSignal {
 
 pub(crate) struct WakeSignals {
     signals: BTreeMap<MessageId, WakeSignal>,
+    /// Cancelled messages along with the blocks their entries expire at.
+    cancelled: BTreeMap<MessageId, u32>,
+    late_reply_hook: Option<fn(MessageId)>,
 }
 
 impl WakeSignals {
     pub fn new() -> Self {
         Self {
             signals: BTreeMap::new(),
+            cancelled: BTreeMap::new(),
+            late_reply_hook: None,
         }
     }
 
@@ -64,17 +76,63 @@ impl WakeSignals {
     }
 
     pub fn record_reply(&mut self) {
+        self.expire_cancelled();
+
         if let Some(signal) = self.signals.get_mut(&crate::msg::reply_to()) {
             signal.payload = Some((crate::msg::load_bytes(), crate::msg::exit_code()));
             if let Some(waker) = &signal.waker {
                 waker.wake_by_ref();
             }
             crate::exec::wake(signal.message_id);
+        } else if self.cancelled.remove(&crate::msg::reply_to()).is_some() {
+            if let Some(hook) = self.late_reply_hook {
+                hook(crate::msg::reply_to());
+            }
         } else {
             crate::debug!("Received reply for the message we don't expect reply to or already processed before");
         }
     }
 
+    /// Stops awaiting the reply to `reply_to`, returning `false` if it isn't
+    /// awaited.
+    ///
+    /// If the reply isn't received yet, it's passed to the late reply hook
+    /// once received. The message the reply was awaited by is woken up unless
+    /// it awaits other replies.
+    pub fn cancel(&mut self, reply_to: MessageId) -> bool {
+        let signal = match self.signals.remove(&reply_to) {
+            Some(signal) => signal,
+            None => return false,
+        };
+
+        if signal.payload.is_none() {
+            self.expire_cancelled();
+            self.cancelled.insert(
+                reply_to,
+                crate::exec::block_height().saturating_add(CANCELLED_REPLY_TTL),
+            );
+
+            let awaits_others = self
+                .signals
+                .values()
+                .any(|other| other.message_id == signal.message_id);
+            if signal.message_id != crate::msg::id() && !awaits_others {
+                crate::exec::wake(signal.message_id);
+            }
+        }
+
+        true
+    }
+
+    fn expire_cancelled(&mut self) {
+        let now = crate::exec::block_height();
+        self.cancelled.retain(|_, expires_at| *expires_at > now);
+    }
+
+    pub fn set_late_reply_hook(&mut self, hook: Option<fn(MessageId)>) {
+        self.late_reply_hook = hook;
+    }
+
     pub fn waits_for(&self, reply_to: MessageId) -> bool {
         self.signals.contains_key(&reply_to)
     }
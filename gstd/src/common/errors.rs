@@ -21,6 +21,7 @@
 //! Errors related to conversion, decoding, message exit code, other internal
 //! errors.
 
+use crate::{funds::FundsError, msg::ReplyCode, prelude::Box, ActorId, MessageId};
 use core::fmt;
 
 pub use gcore::error::*;
@@ -29,23 +30,87 @@ pub type Result<T> = core::result::Result<T, ContractError>;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ContractError {
+    AccessDenied,
+    BatchEntry(usize, Box<ContractError>),
     Convert(&'static str),
     Decode(codec::Error),
     ExitCode(i32),
     Ext(ExtError),
+    Funds(FundsError),
+    ProgramAlreadyExists(ActorId),
+    ReplyBudgetExceeded,
+    ReplyNotAwaited(MessageId),
+    UnexpectedReply(MessageId),
+}
+
+impl ContractError {
+    /// Error of the reply with the given non-zero exit code.
+    pub(crate) fn from_exit_code(exit_code: i32) -> Self {
+        if ReplyCode::from_raw(exit_code) == ReplyCode::ENTRY_BUDGET_EXCEEDED {
+            Self::ReplyBudgetExceeded
+        } else {
+            Self::ExitCode(exit_code)
+        }
+    }
 }
 
 impl fmt::Display for ContractError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            ContractError::AccessDenied => write!(f, "Access denied"),
+            ContractError::BatchEntry(index, e) => {
+                write!(f, "Batch entry #{} failed: {}", index, e)
+            }
             ContractError::Convert(e) => write!(f, "Conversion error: {:?}", e),
             ContractError::Decode(e) => write!(f, "Decoding codec bytes error: {}", e),
             ContractError::ExitCode(e) => write!(f, "Reply returned exit code {}", e),
             ContractError::Ext(e) => write!(f, "API error: {}", e),
+            ContractError::Funds(e) => write!(f, "Funds error: {}", e),
+            ContractError::ProgramAlreadyExists(id) => {
+                write!(f, "Program {:?} already exists", id)
+            }
+            ContractError::ReplyBudgetExceeded => {
+                write!(f, "Reply exceeded the gas budget of handle_reply")
+            }
+            ContractError::ReplyNotAwaited(id) => {
+                write!(f, "Reply to message {:?} isn't awaited", id)
+            }
+            ContractError::UnexpectedReply(id) => {
+                write!(f, "Reply to unexpected message {:?}", id)
+            }
+        }
+    }
+}
+
+/// Error of a call to another program, which replies with errors of type `E`.
+///
+/// Returned by [`TypedMessageFuture`](crate::msg::TypedMessageFuture).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProgramError<E> {
+    /// The program replied with its error.
+    Program(E),
+    /// The reply was generated by the system, e.g. the message trapped.
+    System(ReplyCode),
+    /// The reply couldn't be processed, e.g. its payload isn't decoded.
+    Contract(ContractError),
+}
+
+impl<E: fmt::Debug> fmt::Display for ProgramError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProgramError::Program(e) => write!(f, "Program replied with error: {:?}", e),
+            ProgramError::System(code) => write!(f, "System replied with code {}", code),
+            ProgramError::Contract(e) => write!(f, "{}", e),
         }
     }
 }
 
+impl<E> From<ContractError> for ProgramError<E> {
+    fn from(err: ContractError) -> Self {
+        Self::Contract(err)
+    }
+}
+
 impl From<ExtError> for ContractError {
     fn from(err: ExtError) -> Self {
         Self::Ext(err)
@@ -192,6 +192,49 @@ impl From<gcore::MessageId> for MessageId {
     }
 }
 
+/// Value reservation identifier.
+///
+/// Identifies a part of the program's balance reserved by
+/// [`exec::reserve_value`](crate::exec::reserve_value) for future messages.
+#[derive(
+    Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq, TypeInfo, Decode, Encode,
+)]
+pub struct ReservationId([u8; 32]);
+
+impl ReservationId {
+    /// Remove the reservation, returning the value left in it.
+    ///
+    /// See [`exec::unreserve_value`](crate::exec::unreserve_value).
+    pub fn unreserve(self) -> Result<u128> {
+        crate::exec::unreserve_value(self)
+    }
+
+    /// Move `value` from the reservation into the new one.
+    ///
+    /// See [`exec::split_value_reservation`](crate::exec::split_value_reservation).
+    pub fn split(self, value: u128) -> Result<Self> {
+        crate::exec::split_value_reservation(self, value)
+    }
+}
+
+impl AsRef<[u8]> for ReservationId {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl From<ReservationId> for gcore::ReservationId {
+    fn from(other: ReservationId) -> Self {
+        Self(other.0)
+    }
+}
+
+impl From<gcore::ReservationId> for ReservationId {
+    fn from(other: gcore::ReservationId) -> Self {
+        Self(other.0)
+    }
+}
+
 #[derive(
     Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq, TypeInfo, Decode, Encode,
 )]
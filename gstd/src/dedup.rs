@@ -0,0 +1,250 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for deduplicating requests handled more than once.
+//!
+//! A sender resending its request, e.g. on a reply timeout, sends a new
+//! message with a new id, so message ids can't tell repeated requests apart.
+//! [`Deduplicator`] remembers the latest processed keys chosen by the
+//! program, e.g. request ids carried in the payload, providing exactly-once
+//! handling within its capacity.
+//!
+//! A message woken from the waitlist is executed again from the start,
+//! so it checks its key once more. The deduplicator remembers which
+//! message recorded the key, so the woken message isn't treated as
+//! a repeated one.
+//!
+//! # Examples
+//!
+//! ```
+//! use gstd::{dedup::Deduplicator, msg};
+//!
+//! static mut DEDUP: Option<Deduplicator<u64>> = None;
+//!
+//! unsafe extern "C" fn init() {
+//!     DEDUP = Some(Deduplicator::new(128));
+//! }
+//!
+//! unsafe extern "C" fn handle() {
+//!     let (request_id, amount): (u64, u128) = msg::load().expect("Unable to decode request");
+//!
+//!     let dedup = DEDUP.as_mut().expect("Initialized in init");
+//!     if !dedup.is_new(request_id) {
+//!         return;
+//!     }
+//!
+//!     // Side effects happen once per request.
+//! }
+//! ```
+
+use crate::{
+    msg,
+    prelude::{
+        codec::{Error, Input},
+        BTreeMap, Decode, Encode, VecDeque,
+    },
+    MessageId,
+};
+
+/// Bounded set of processed request keys.
+///
+/// Once the capacity is reached, the earliest recorded key is forgotten,
+/// so the request with it is treated as new again. The capacity should
+/// cover all the requests which may be repeated.
+#[derive(Clone, Debug, PartialEq, Eq, Encode)]
+pub struct Deduplicator<K: Ord = MessageId> {
+    capacity: u32,
+    order: VecDeque<(K, MessageId)>,
+    #[codec(skip)]
+    seen: BTreeMap<K, MessageId>,
+}
+
+impl<K: Clone + Ord> Deduplicator<K> {
+    /// Create the deduplicator remembering up to `capacity` keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: u32) -> Self {
+        assert!(capacity > 0, "Deduplicator capacity must be non-zero");
+
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            seen: BTreeMap::new(),
+        }
+    }
+
+    /// Record the key for the message being processed, returning `true`
+    /// if it wasn't recorded yet or was recorded by the same message.
+    pub fn is_new(&mut self, key: K) -> bool {
+        self.record(key, msg::id())
+    }
+
+    /// Record the key for the message with given id, returning `true`
+    /// if it wasn't recorded yet or was recorded by the same message.
+    pub fn record(&mut self, key: K, message_id: MessageId) -> bool {
+        if let Some(recorded_by) = self.seen.get(&key) {
+            return *recorded_by == message_id;
+        }
+
+        self.seen.insert(key.clone(), message_id);
+        self.order.push_back((key, message_id));
+
+        if self.order.len() > self.capacity as usize {
+            if let Some((earliest, _)) = self.order.pop_front() {
+                self.seen.remove(&earliest);
+            }
+        }
+
+        true
+    }
+
+    /// Tell whether the key is recorded, without recording it.
+    pub fn contains(&self, key: &K) -> bool {
+        self.seen.contains_key(key)
+    }
+
+    /// Maximal amount of the recorded keys.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Amount of the recorded keys.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Tell whether no keys are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+// Only the capacity and the recorded keys in their order are encoded:
+// the lookup map is rebuilt, so it can't get out of sync with the order.
+impl<K: Clone + Decode + Ord> Decode for Deduplicator<K> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let capacity = u32::decode(input)?;
+        let order = VecDeque::<(K, MessageId)>::decode(input)?;
+
+        if capacity == 0 {
+            return Err("Deduplicator capacity must be non-zero".into());
+        }
+
+        if order.len() > capacity as usize {
+            return Err("Deduplicator keys exceed its capacity".into());
+        }
+
+        let seen: BTreeMap<K, MessageId> = order.iter().cloned().collect();
+
+        if seen.len() != order.len() {
+            return Err("Deduplicator keys are recorded more than once".into());
+        }
+
+        Ok(Self {
+            capacity,
+            order,
+            seen,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{vec, Vec};
+
+    fn id(n: u8) -> MessageId {
+        MessageId::new([n; 32])
+    }
+
+    #[test]
+    fn repeated_keys_not_new() {
+        let mut dedup = Deduplicator::new(4);
+
+        assert!(dedup.record(1u64, id(1)));
+        assert!(dedup.record(2, id(2)));
+        assert!(!dedup.record(1, id(3)));
+        assert!(!dedup.record(2, id(4)));
+        assert_eq!(dedup.len(), 2);
+    }
+
+    #[test]
+    fn woken_message_not_repeated() {
+        let mut dedup = Deduplicator::new(4);
+
+        assert!(dedup.record(1u64, id(1)));
+
+        // The same message checks its key again after the wake.
+        assert!(dedup.record(1, id(1)));
+        assert_eq!(dedup.len(), 1);
+
+        assert!(!dedup.record(1, id(2)));
+    }
+
+    #[test]
+    fn earliest_keys_forgotten_beyond_capacity() {
+        let mut dedup = Deduplicator::new(2);
+
+        assert!(dedup.record(1u64, id(1)));
+        assert!(dedup.record(2, id(2)));
+        assert!(dedup.record(3, id(3)));
+        assert_eq!(dedup.len(), 2);
+
+        assert!(!dedup.contains(&1));
+        assert!(dedup.contains(&2));
+        assert!(dedup.contains(&3));
+
+        // Repeats don't refresh the key.
+        assert!(!dedup.record(2, id(5)));
+        assert!(dedup.record(4, id(4)));
+        assert!(!dedup.contains(&2));
+    }
+
+    #[test]
+    fn state_survives_encoding() {
+        let mut dedup = Deduplicator::new(2);
+        dedup.record(1u64, id(1));
+        dedup.record(2, id(2));
+
+        let mut decoded = Deduplicator::decode(&mut &dedup.encode()[..]).unwrap();
+        assert_eq!(decoded, dedup);
+        assert!(!decoded.record(2, id(5)));
+        assert!(decoded.record(3, id(3)));
+        assert!(!decoded.contains(&1));
+    }
+
+    #[test]
+    fn invalid_state_not_decoded() {
+        let decode = |capacity: u32, order: Vec<(u64, MessageId)>| {
+            Deduplicator::<u64>::decode(&mut &(capacity, order).encode()[..])
+        };
+
+        assert!(decode(0, vec![]).is_err());
+        assert!(decode(1, vec![(1, id(1)), (2, id(2))]).is_err());
+        assert!(decode(2, vec![(1, id(1)), (1, id(2))]).is_err());
+        assert!(decode(2, vec![(1, id(1)), (2, id(2))]).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be non-zero")]
+    fn zero_capacity_rejected() {
+        Deduplicator::<u64>::new(0);
+    }
+}
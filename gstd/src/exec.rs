@@ -77,10 +77,15 @@
 //!     let _my_balance = exec::value_available();
 //! }
 //! ```
-use crate::{ActorId, MessageId};
-pub use gcore::exec::{
-    block_height, block_timestamp, gas_available, leave, value_available, wait, wait_for,
-    wait_up_to,
+use crate::{errors::Result, time::Blocks, ActorId, MessageId, ReservationId};
+pub use gcore::{
+    exec::{
+        block_duration_millis, block_height, block_timestamp, children_count, children_gas_burned,
+        codes_count, context_limits, delayed_eta, execution_id, gas_allowance, gas_available,
+        is_init, leave, new_handle, resumed_from_wait, spendable_value, stack_limit, trace,
+        value_available, wait, wait_budget_remaining, wait_if,
+    },
+    ContextLimits, EntryPoint, TraceLevel,
 };
 
 /// Terminate the execution of a program.
@@ -106,6 +111,65 @@ pub fn exit(value_destination: ActorId) -> ! {
     gcore::exec::exit(value_destination.into())
 }
 
+/// Schedule termination of the program at the given future `block`,
+/// transferring all its value to the `inheritor` then.
+///
+/// The exit can't be cancelled, but scheduling it again replaces the
+/// previous one, see [`gcore::exec::schedule_exit`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{exec, msg};
+///
+/// unsafe extern "C" fn init() {
+///     exec::schedule_exit(exec::block_height() + 1000, msg::source())
+///         .expect("Unable to schedule exit");
+/// }
+/// ```
+pub fn schedule_exit(block: u32, inheritor: ActorId) -> Result<()> {
+    gcore::exec::schedule_exit(block, inheritor.into()).map_err(Into::into)
+}
+
+/// Limit gas available to the executions of the given `entry` point.
+///
+/// The budget applies to the messages executed after the current one,
+/// see [`gcore::exec::set_entry_budget`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::exec::{self, EntryPoint};
+///
+/// unsafe extern "C" fn init() {
+///     exec::set_entry_budget(EntryPoint::Signal, 1_000_000_000)
+///         .expect("Unable to set budget");
+/// }
+/// ```
+pub fn set_entry_budget(entry: EntryPoint, gas: u64) -> Result<()> {
+    gcore::exec::set_entry_budget(entry, gas).map_err(Into::into)
+}
+
+/// Limit gas available to the executions of `handle_reply`.
+///
+/// Replies exceeding the budget don't spend the rest of the gas attached
+/// to the request. The flow awaiting such reply gets
+/// [`ContractError::ReplyBudgetExceeded`](crate::errors::ContractError::ReplyBudgetExceeded)
+/// instead. The budget is typically set in `init`.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::exec;
+///
+/// unsafe extern "C" fn init() {
+///     exec::set_reply_budget(1_000_000_000).expect("Unable to set budget");
+/// }
+/// ```
+pub fn set_reply_budget(gas: u64) -> Result<()> {
+    set_entry_budget(EntryPoint::Reply, gas)
+}
+
 /// Resume previously paused message handling.
 ///
 /// If a message has been paused using the [`wait`] function, then it is
@@ -129,12 +193,125 @@ pub fn wake(waker_id: MessageId) {
 }
 
 /// Same as [`wake`], but wakes delayed.
-pub fn wake_delayed(waker_id: MessageId, delay: u32) {
-    gcore::exec::wake_delayed(waker_id.into(), delay)
+pub fn wake_delayed(waker_id: MessageId, delay: impl Into<Blocks>) {
+    gcore::exec::wake_delayed(waker_id.into(), delay.into().into_delay())
+}
+
+/// Same as [`wait`], but delays handling for given specific duration.
+///
+/// Durations given in [`Millis`](crate::time::Millis) are rounded up
+/// to whole blocks.
+///
+/// NOTE: It panics, if given duration couldn't be totally payed.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{exec, time::Millis};
+///
+/// unsafe extern "C" fn handle() {
+///     if exec::is_init() {
+///         // Same as `exec::wait_for(Blocks(10))`
+///         exec::wait_for(10);
+///     } else {
+///         // Waits for one block at least
+///         exec::wait_for(Millis(1));
+///     }
+/// }
+/// ```
+pub fn wait_for(duration: impl Into<Blocks>) -> ! {
+    gcore::exec::wait_for(duration.into().0)
+}
+
+/// Same as [`wait`], but delays handling for maximal amount of blocks
+/// that could be payed, that doesn't exceed given duration.
+///
+/// Durations given in [`Millis`](crate::time::Millis) are rounded up
+/// to whole blocks.
+pub fn wait_up_to(duration: impl Into<Blocks>) -> ! {
+    gcore::exec::wait_up_to(duration.into().0)
+}
+
+/// Reserve `value` of the program's balance for future messages.
+///
+/// Reserved value is only spent by
+/// [`msg::reservation_send_bytes`](crate::msg::reservation_send_bytes) or
+/// returned to the available balance by [`unreserve_value`].
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let reservation_id = exec::reserve_value(1000).expect("Unable to reserve");
+///     // ...
+///     msg::reservation_send_bytes(reservation_id, msg::source(), b"PAYOUT", 1000)
+///         .expect("Unable to send");
+/// }
+/// ```
+pub fn reserve_value(value: u128) -> Result<ReservationId> {
+    gcore::exec::reserve_value(value)
+        .map(Into::into)
+        .map_err(Into::into)
+}
+
+/// Get hash of the block at the given `height`.
+///
+/// Only recent blocks preceding the current one are available.
+pub fn block_hash(height: u32) -> Result<[u8; 32]> {
+    gcore::exec::block_hash(height).map_err(Into::into)
+}
+
+/// Get hash of the external transaction the current message chain
+/// originates from.
+///
+/// Isn't available if the chain wasn't initiated by a transaction,
+/// or the message was sent with delay by any message of the chain.
+pub fn tx_hash() -> Result<[u8; 32]> {
+    gcore::exec::tx_hash().map_err(Into::into)
+}
+
+/// Remove the value reservation, returning the value left in it.
+pub fn unreserve_value(reservation_id: ReservationId) -> Result<u128> {
+    gcore::exec::unreserve_value(reservation_id.into()).map_err(Into::into)
+}
+
+/// Move `value` from the value reservation into the new one,
+/// returning id of the latter.
+pub fn split_value_reservation(
+    reservation_id: ReservationId,
+    value: u128,
+) -> Result<ReservationId> {
+    gcore::exec::split_value_reservation(reservation_id.into(), value)
+        .map(Into::into)
+        .map_err(Into::into)
+}
+
+/// Get the next id from the program's persistent counter with the given `key`.
+///
+/// Ids start from one and never repeat within the program unless the
+/// execution taking them fails, so they may identify program's own records
+/// without keeping a counter in the state.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{exec, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let ticket = exec::next_id(*b"tickets\0");
+///     msg::reply(ticket, 0).unwrap();
+/// }
+/// ```
+pub fn next_id(key: [u8; 8]) -> u64 {
+    gcore::exec::bump_counter(key)
 }
 
 /// Return ID of the current program.
 ///
+/// The id is requested once and cached, since it never changes.
+///
 /// # Examples
 ///
 /// ```
@@ -146,7 +323,9 @@ pub fn wake_delayed(waker_id: MessageId, delay: u32) {
 /// }
 /// ```
 pub fn program_id() -> ActorId {
-    gcore::exec::program_id().into()
+    static mut PROGRAM_ID: Option<ActorId> = None;
+
+    unsafe { *PROGRAM_ID.get_or_insert_with(|| gcore::exec::program_id().into()) }
 }
 
 /// Return the id of original user who initiated communication with blockchain,
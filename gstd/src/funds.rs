@@ -0,0 +1,208 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for holding value received with messages until it's released
+//! to someone or refunded back to the sender.
+//!
+//! Holds are stored in program's memory and keyed by the id of the message
+//! which value is held, so calling [`hold`] again in the execution resumed
+//! after [`wait`](crate::exec::wait) returns the same hold instead of
+//! holding value twice.
+//!
+//! If execution traps in the same run where [`hold`] was called, nothing
+//! should be done: the program's memory is reverted and the message value
+//! is returned to the sender by the runtime. Value is transferred to the
+//! program once the message starts waiting though, so the hold made before
+//! [`wait`](crate::exec::wait) remains after a trap in the resumed
+//! execution. The runtime signals the program about such a trap, and
+//! [`handle_signal`] called in the program's `handle_signal()` refunds
+//! the hold automatically.
+
+use crate::{
+    errors::{ContractError, Result},
+    msg::{self, SignalCode},
+    prelude::{BTreeMap, Decode, Encode, TypeInfo},
+    ActorId, MessageId,
+};
+use core::fmt;
+
+/// Identifier of the held value.
+///
+/// It's the same as the id of the message which value is held.
+#[derive(
+    Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq, TypeInfo, Decode, Encode,
+)]
+pub struct HoldId(MessageId);
+
+impl HoldId {
+    /// Id of the message which value is held.
+    pub fn message_id(&self) -> MessageId {
+        self.0
+    }
+}
+
+impl From<MessageId> for HoldId {
+    fn from(message_id: MessageId) -> Self {
+        Self(message_id)
+    }
+}
+
+/// Errors of the value holding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TypeInfo, Decode, Encode)]
+pub enum FundsError {
+    /// Requested value is greater than the value of the current message.
+    ValueExceeded {
+        /// Value requested to be held.
+        requested: u128,
+        /// Value of the current message.
+        available: u128,
+    },
+    /// Hold doesn't exist or was already released or refunded.
+    HoldNotFound(HoldId),
+}
+
+impl fmt::Display for FundsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FundsError::ValueExceeded {
+                requested,
+                available,
+            } => write!(
+                f,
+                "Requested to hold {} but message has only {}",
+                requested, available
+            ),
+            FundsError::HoldNotFound(id) => write!(f, "Hold {:?} not found", id),
+        }
+    }
+}
+
+struct Hold {
+    source: ActorId,
+    value: u128,
+}
+
+static mut HOLDS: Option<BTreeMap<HoldId, Hold>> = None;
+
+fn holds() -> &'static mut BTreeMap<HoldId, Hold> {
+    unsafe { HOLDS.get_or_insert_with(BTreeMap::new) }
+}
+
+/// Hold `value` received with the current message.
+///
+/// Returns the id of the existing hold if the current message has
+/// already held value before.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{funds, msg};
+///
+/// unsafe extern "C" fn handle() {
+///     let hold = funds::hold(msg::value()).expect("Unable to hold value");
+///     // ...
+///     funds::release(hold, msg::source()).expect("Unable to release value");
+/// }
+/// ```
+pub fn hold(value: u128) -> Result<HoldId> {
+    let hold_id = HoldId(msg::id());
+
+    if holds().contains_key(&hold_id) {
+        return Ok(hold_id);
+    }
+
+    let available = msg::value();
+
+    if value > available {
+        return Err(FundsError::ValueExceeded {
+            requested: value,
+            available,
+        }
+        .into());
+    }
+
+    holds().insert(
+        hold_id,
+        Hold {
+            source: msg::source(),
+            value,
+        },
+    );
+
+    Ok(hold_id)
+}
+
+/// Value kept by the hold, if it still exists.
+pub fn held(hold_id: HoldId) -> Option<u128> {
+    holds().get(&hold_id).map(|hold| hold.value)
+}
+
+/// Send held value to `to` and remove the hold.
+///
+/// Hold remains if sending fails.
+pub fn release(hold_id: HoldId, to: ActorId) -> Result<MessageId> {
+    let value = held(hold_id).ok_or(FundsError::HoldNotFound(hold_id))?;
+    let message_id = msg::send_bytes(to, b"", value)?;
+
+    holds().remove(&hold_id);
+
+    Ok(message_id)
+}
+
+/// Send held value back to the source of the message it was received with
+/// and remove the hold.
+pub fn refund(hold_id: HoldId) -> Result<MessageId> {
+    let source = holds()
+        .get(&hold_id)
+        .map(|hold| hold.source)
+        .ok_or(FundsError::HoldNotFound(hold_id))?;
+
+    release(hold_id, source)
+}
+
+/// Refund the value held for the message, which execution failed after
+/// waiting, back to its source.
+///
+/// Call it in `handle_signal()`: other signals are ignored. Returns the id
+/// of the message sending the value back, if there was a hold to refund.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::funds;
+///
+/// unsafe extern "C" fn handle_signal() {
+///     funds::handle_signal().expect("Unable to refund held value");
+/// }
+/// ```
+pub fn handle_signal() -> Result<Option<MessageId>> {
+    let (message_id, code) = msg::signal_details()?;
+    let hold_id = HoldId(message_id);
+
+    if code != SignalCode::ExecutionFailed || !holds().contains_key(&hold_id) {
+        return Ok(None);
+    }
+
+    refund(hold_id).map(Some)
+}
+
+impl From<FundsError> for ContractError {
+    fn from(err: FundsError) -> Self {
+        Self::Funds(err)
+    }
+}
@@ -33,19 +33,29 @@ extern crate galloc;
 
 mod async_runtime;
 mod common;
+pub mod dedup;
 pub mod exec;
+pub mod funds;
 pub mod lock;
 pub mod macros;
 pub mod msg;
+pub mod ownership;
 pub mod prelude;
+#[cfg(feature = "profiler")]
+pub mod profiler;
 pub mod prog;
+pub mod time;
+pub mod timer;
 
 pub use async_runtime::{message_loop, record_reply};
 pub use common::{errors, handlers::*, primitives::*};
-pub use gstd_codegen::{async_init, async_main};
-pub use macros::util;
+pub use gstd_codegen::{async_init, async_main, only_owner};
+pub use macros::{metadata_hash, util};
 
 pub use prelude::*;
 
 #[cfg(feature = "debug")]
 pub use gcore::ext;
+
+#[cfg(feature = "profiler")]
+pub use profiler::alloc_tag;
@@ -0,0 +1,76 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Gear `dispatch!` macro generates the `handle` entry point dispatching
+//! messages of the given type to their handlers.
+
+/// **The `dispatch!` macro**
+///
+/// Generates the `handle` function, which decodes the payload of the
+/// incoming message into the given type with [`msg::load`](crate::msg::load),
+/// matches it against the patterns and calls the corresponding handler.
+///
+/// The value returned by the handler is encoded and sent as the reply with
+/// [`msg::reply`](crate::msg::reply), so handlers returning `()` reply with
+/// empty payload.
+///
+/// The execution panics if the payload can't be decoded into the given type
+/// or the reply can't be sent.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{dispatch, prelude::*};
+///
+/// #[derive(Encode, Decode)]
+/// enum Request {
+///     Ping,
+///     Add(u32, u32),
+///     Echo(String),
+/// }
+///
+/// fn add(a: u32, b: u32) -> u32 {
+///     a.saturating_add(b)
+/// }
+///
+/// dispatch! {
+///     Request {
+///         Request::Ping => "PONG",
+///         Request::Add(a, b) => add(a, b),
+///         Request::Echo(text) => text,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! dispatch {
+    ($msg:ty { $($pat:pat => $handler:expr),+ $(,)? }) => {
+        #[no_mangle]
+        extern "C" fn handle() {
+            let msg: $msg = $crate::msg::load()
+                .expect(concat!("Failed to decode `", stringify!($msg), "`"));
+
+            match msg {
+                $(
+                    $pat => {
+                        $crate::msg::reply($handler, 0).expect("Failed to reply");
+                    }
+                )+
+            }
+        }
+    };
+}
@@ -0,0 +1,138 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Gear `ensure!`, `ensure_rollback!` and `fail!` macros. Provide early
+//! returns replying with an error code instead of trapping.
+
+/// **The `fail!` macro**
+///
+/// Replies to the current message with the user `code` and the encoded
+/// `msg`, then leaves the execution via [`exec::leave`](crate::exec::leave),
+/// so the state changes made so far persist.
+///
+/// A trap would burn the gas and discard the reply, while this way the
+/// caller gets a cheap typed error.
+///
+/// The reply can't be sent if one was already committed. The error is then
+/// returned by the enclosing function, which must return
+/// `Result<T, E: From<ContractError>>`, so the case can't go unnoticed.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{fail, msg::ReplyCode, errors::Result};
+///
+/// const UNKNOWN_COMMAND: ReplyCode = ReplyCode::from_raw(400);
+///
+/// fn process(command: &[u8]) -> Result<()> {
+///     match command {
+///         b"ping" => {
+///             // ...
+///         }
+///         _ => fail!(UNKNOWN_COMMAND, "Unknown command"),
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! fail {
+    ($code:expr, $msg:expr) => {
+        match $crate::msg::reply_with_code($code, $msg, 0) {
+            Ok(_) => $crate::exec::leave(),
+            Err(err) => return Err(err.into()),
+        }
+    };
+}
+
+/// **The `ensure!` macro**
+///
+/// Checks the condition and, if it doesn't hold, replies with the user
+/// `code` and the optional encoded `msg`, then leaves the execution keeping
+/// the state changes made so far.
+///
+/// Same as [`fail!`], the enclosing function must return
+/// `Result<T, E: From<ContractError>>` for the case the reply was already
+/// committed.
+///
+/// - `ensure!(cond: bool, code: ReplyCode)`
+///
+/// Replies with empty payload.
+///
+/// - `ensure!(cond: bool, code: ReplyCode, msg: impl Encode)`
+///
+/// Replies with encoded `msg`.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{ensure, msg::{self, ReplyCode}, errors::Result};
+///
+/// const NOT_ENOUGH_VALUE: ReplyCode = ReplyCode::from_raw(402);
+///
+/// fn buy(price: u128) -> Result<()> {
+///     ensure!(msg::value() >= price, NOT_ENOUGH_VALUE, "Not enough value");
+///     // ...
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $code:expr) => {
+        if !$cond {
+            match $crate::msg::reply_bytes_with_code($code, b"", 0) {
+                Ok(_) => $crate::exec::leave(),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    };
+    ($cond:expr, $code:expr, $msg:expr) => {
+        if !$cond {
+            $crate::fail!($code, $msg)
+        }
+    };
+}
+
+/// **The `ensure_rollback!` macro**
+///
+/// Checks the condition and, if it doesn't hold, traps deliberately with
+/// `msg`, so all the state changes made by the execution are rolled back.
+///
+/// Replies sent before the trap are discarded as well, so the caller gets
+/// the error reply generated by the system instead of a user code.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{ensure_rollback, msg};
+///
+/// static mut BALANCE: u128 = 0;
+///
+/// unsafe extern "C" fn handle() {
+///     BALANCE += msg::value();
+///     ensure_rollback!(BALANCE <= 1_000_000, "Balance cap exceeded");
+/// }
+/// ```
+#[macro_export]
+macro_rules! ensure_rollback {
+    ($cond:expr, $msg:literal) => {
+        if !$cond {
+            $crate::prelude::panic!($msg)
+        }
+    };
+}
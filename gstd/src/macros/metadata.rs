@@ -25,6 +25,9 @@
 //! tools and applications that interact with gear programs in the network.
 //! For example, it is used in `idea.gear-tech.io` in order to correctly
 //! form a message payload from json on the JS application side.
+//! Macro also exports `meta_hash`, identifying program's ABI, which is
+//! available for the program itself via [`metadata_hash`](crate::metadata_hash)
+//! and for clients via the `metahash` custom section of the program code.
 
 #[macro_export]
 macro_rules! metadata {
@@ -55,6 +58,43 @@ macro_rules! metadata {
         $crate::export!(meta_registry -> $crate::util::to_hex_registry(
             $crate::prelude::vec![$($crate::util::MetaType::new::<$t>()), *]
         ));
+        $crate::export!(meta_hash -> $crate::util::to_hex_metahash(
+            &[
+                $init_input,
+                $init_output,
+                $async_init_input,
+                $async_init_output,
+                $handle_input,
+                $handle_output,
+                $async_handle_input,
+                $async_handle_output,
+                $state_input,
+                $state_output,
+            ],
+            $crate::prelude::vec![$($crate::util::MetaType::new::<$t>()), *]
+        ));
+
+        // `gear-wasm-builder` replaces the body with storing the hash
+        // computed at build time.
+        #[doc(hidden)]
+        #[no_mangle]
+        unsafe extern "C" fn __gstd_metadata_hash(hash_ptr: *mut [u8; 32]) {
+            *hash_ptr = $crate::util::to_metahash(
+                &[
+                    $init_input,
+                    $init_output,
+                    $async_init_input,
+                    $async_init_output,
+                    $handle_input,
+                    $handle_output,
+                    $async_handle_input,
+                    $async_handle_output,
+                    $state_input,
+                    $state_output,
+                ],
+                $crate::prelude::vec![$($crate::util::MetaType::new::<$t>()), *]
+            );
+        }
     };
 
     (
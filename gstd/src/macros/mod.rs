@@ -20,8 +20,56 @@
 
 mod bail;
 mod debug;
+mod dispatch;
+mod ensure;
 mod export;
 mod metadata;
+mod trace;
+
+mod sys {
+    extern "C" {
+        pub fn __gstd_metadata_hash(hash_ptr: *mut [u8; 32]);
+    }
+}
+
+/// Get the metadata hash of the current program.
+///
+/// Metadata hash identifies program's ABI: types of messages and state,
+/// declared in the [`metadata!`](crate::metadata) macro. Hash depends only
+/// on shapes of declared types, not on their names or paths, so programs
+/// with identical ABIs have identical hashes.
+///
+/// The hash is computed at build time: `gear-wasm-builder` reads it from
+/// `*.meta.wasm`, stores it in the `metahash` custom section of
+/// `*.opt.wasm` and makes this function return it as a constant, so the
+/// type registry isn't built on-chain.
+///
+/// The program must declare its metadata using the
+/// [`metadata!`](crate::metadata) macro, otherwise it won't be linked.
+///
+/// # Examples
+///
+/// ```ignore
+/// use gstd::{metadata_hash, msg};
+///
+/// gstd::metadata! {
+///     title: "Example",
+///     handle:
+///         input: u32,
+/// }
+///
+/// #[no_mangle]
+/// extern "C" fn handle() {
+///     msg::reply(metadata_hash(), 0).expect("Failed to reply");
+/// }
+/// ```
+pub fn metadata_hash() -> [u8; 32] {
+    let mut hash = [0u8; 32];
+
+    unsafe { sys::__gstd_metadata_hash(hash.as_mut_ptr() as _) }
+
+    hash
+}
 
 /// Utility functions.
 pub mod util {
@@ -39,6 +87,44 @@ pub mod util {
         hex::encode(registry.encode())
     }
 
+    /// Generate a metadata hash from given IO slots and meta types.
+    ///
+    /// `slots` contains type names of all IO slots in order of the
+    /// [`metadata!`](crate::metadata) macro arguments (empty for undeclared
+    /// ones), while `meta_types` contains types of declared slots only.
+    ///
+    /// Type paths and names are not hashed, so identical types declared
+    /// in different crates produce identical hashes.
+    pub fn to_metahash(slots: &[&str], meta_types: Vec<MetaType>) -> [u8; 32] {
+        let mut registry = Registry::new();
+
+        let declared: Vec<bool> = slots.iter().map(|slot| !slot.is_empty()).collect();
+        let ids: Vec<u32> = meta_types
+            .iter()
+            .map(|meta_type| registry.register_type(meta_type).id())
+            .collect();
+
+        let registry: PortableRegistry = registry.into();
+        let types: Vec<_> = registry
+            .types()
+            .iter()
+            .map(|ty| (ty.id(), ty.ty().type_params(), ty.ty().type_def()))
+            .collect();
+
+        let encoded = (declared, ids, types).encode();
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(blake2_rfc::blake2b::blake2b(32, &[], &encoded).as_bytes());
+
+        hash
+    }
+
+    /// Generate a metadata hash from given IO slots and meta types
+    /// and encode it to hex.
+    pub fn to_hex_metahash(slots: &[&str], meta_types: Vec<MetaType>) -> String {
+        hex::encode(to_metahash(slots, meta_types))
+    }
+
     /// Convert a given reference to a raw pointer.
     pub fn to_wasm_ptr<T: AsRef<[u8]>>(bytes: T) -> *mut [i32; 2] {
         Box::into_raw(Box::new([
@@ -58,3 +144,67 @@ pub mod util {
         ptr
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::util::{to_metahash, MetaType};
+    use crate::prelude::vec;
+    use scale_info::TypeInfo;
+
+    mod first {
+        use super::*;
+
+        #[derive(TypeInfo)]
+        pub struct Action {
+            pub amount: u32,
+            pub memo: Option<u8>,
+        }
+    }
+
+    mod second {
+        use super::*;
+
+        #[derive(TypeInfo)]
+        pub struct Command {
+            pub amount: u32,
+            pub memo: Option<u8>,
+        }
+    }
+
+    mod changed {
+        use super::*;
+
+        #[derive(TypeInfo)]
+        pub struct Action {
+            pub amount: u64,
+            pub memo: Option<u8>,
+        }
+    }
+
+    const HANDLE_INPUT: [&str; 10] = ["", "", "", "", "Action", "", "", "", "", ""];
+    const HANDLE_OUTPUT: [&str; 10] = ["", "", "", "", "", "Action", "", "", "", ""];
+
+    #[test]
+    fn metahash_ignores_type_paths() {
+        assert_eq!(
+            to_metahash(&HANDLE_INPUT, vec![MetaType::new::<first::Action>()]),
+            to_metahash(&HANDLE_INPUT, vec![MetaType::new::<second::Command>()]),
+        );
+    }
+
+    #[test]
+    fn metahash_depends_on_field_types() {
+        assert_ne!(
+            to_metahash(&HANDLE_INPUT, vec![MetaType::new::<first::Action>()]),
+            to_metahash(&HANDLE_INPUT, vec![MetaType::new::<changed::Action>()]),
+        );
+    }
+
+    #[test]
+    fn metahash_depends_on_slots() {
+        assert_ne!(
+            to_metahash(&HANDLE_INPUT, vec![MetaType::new::<first::Action>()]),
+            to_metahash(&HANDLE_OUTPUT, vec![MetaType::new::<first::Action>()]),
+        );
+    }
+}
@@ -0,0 +1,40 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Gear `trace!` macro.
+//! Enables output of the program traces, which levels are logged by the
+//! node, regardless of the `debug` feature. Traces are formatted and
+//! charged whether the node logs their level or not.
+//!
+//! # Examples
+//!
+//! ```
+//! use gstd::{exec::TraceLevel, msg};
+//!
+//! #[no_mangle]
+//! extern "C" fn handle() {
+//!     gstd::trace!(TraceLevel::Info, "Handling message from {:?}", msg::source());
+//! }
+//! ```
+
+#[macro_export]
+macro_rules! trace {
+    ($level:expr, $fmt:literal $(, $args:expr)* $(,)?) => {
+        $crate::exec::trace($level, &$crate::prelude::format!($fmt $(, $args)*))
+    };
+}
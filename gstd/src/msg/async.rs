@@ -20,9 +20,10 @@
 
 use crate::{
     async_runtime::{signals, ReplyPoll},
-    errors::{ContractError, Result},
+    errors::{ContractError, ProgramError, Result},
+    msg::ReplyCode,
     prelude::{convert::AsRef, Vec},
-    MessageId,
+    ActorId, MessageId,
 };
 use codec::Decode;
 use core::{
@@ -33,6 +34,44 @@ use core::{
 };
 use futures::future::FusedFuture;
 
+/// Stop awaiting the reply to `waiting_reply_to`.
+fn cancel_reply(waiting_reply_to: MessageId) -> Result<()> {
+    if signals().cancel(waiting_reply_to) {
+        Ok(())
+    } else {
+        Err(ContractError::ReplyNotAwaited(waiting_reply_to))
+    }
+}
+
+/// Set the `hook` called on replies to messages, which awaiting was cancelled
+/// with [`MessageFuture::cancel`], or ignore such replies if `None`.
+///
+/// The hook is called from [`record_reply`](crate::record_reply) with the id
+/// of the cancelled message, so the reply itself is available with
+/// [`load_bytes`](crate::msg::load_bytes) and
+/// [`exit_code`](crate::msg::exit_code). Replies are ignored by default.
+///
+/// Cancelled messages are remembered for 10 000 blocks, replies arriving
+/// later are handled as unexpected ones.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, MessageId};
+///
+/// fn log_late_reply(reply_to: MessageId) {
+///     gstd::debug!("Late reply to {:?}: {:?}", reply_to, msg::load_bytes());
+/// }
+///
+/// #[no_mangle]
+/// unsafe extern "C" fn init() {
+///     msg::set_late_reply_hook(Some(log_late_reply));
+/// }
+/// ```
+pub fn set_late_reply_hook(hook: Option<fn(MessageId)>) {
+    signals().set_late_reply_hook(hook);
+}
+
 /// To interrupt a program execution waiting for a reply on a previous message,
 /// one needs to call an `.await` expression.
 /// The initial message that requires a reply is sent instantly.
@@ -64,7 +103,7 @@ impl<D: Decode> Future for CodecMessageFuture<D> {
             ReplyPoll::Pending => Poll::Pending,
             ReplyPoll::Some((actual_reply, exit_code)) => {
                 if exit_code != 0 {
-                    return Poll::Ready(Err(ContractError::ExitCode(exit_code)));
+                    return Poll::Ready(Err(ContractError::from_exit_code(exit_code)));
                 }
 
                 Poll::Ready(D::decode(&mut actual_reply.as_ref()).map_err(ContractError::Decode))
@@ -79,6 +118,87 @@ impl<D: Decode> FusedFuture for CodecMessageFuture<D> {
     }
 }
 
+impl<D: Decode> CodecMessageFuture<D> {
+    /// Decode error replies sent with [`reply_err`](crate::msg::reply_err)
+    /// as `E`, rather than failing with the bare exit code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gstd::{errors::ProgramError, msg, ActorId, String};
+    ///
+    /// async fn divide(calculator: ActorId, a: u32, b: u32) -> Option<u32> {
+    ///     let res = msg::send_for_reply_as::<_, u32>(calculator, (a, b), 0)
+    ///         .expect("Failed to send")
+    ///         .with_errors::<String>()
+    ///         .await;
+    ///
+    ///     match res {
+    ///         Ok(quotient) => Some(quotient),
+    ///         Err(ProgramError::Program(_division_by_zero)) => None,
+    ///         Err(err) => panic!("Calculator failed: {:?}", err),
+    ///     }
+    /// }
+    /// ```
+    pub fn with_errors<E: Decode>(self) -> TypedMessageFuture<D, E> {
+        TypedMessageFuture {
+            waiting_reply_to: self.waiting_reply_to,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Same as [`MessageFuture::cancel`].
+    pub fn cancel(self) -> Result<()> {
+        cancel_reply(self.waiting_reply_to)
+    }
+}
+
+/// Same as [`CodecMessageFuture`], but decodes error replies as `E`.
+///
+/// Created by [`CodecMessageFuture::with_errors`].
+pub struct TypedMessageFuture<D, E> {
+    /// Waiting reply to this the message id
+    pub waiting_reply_to: MessageId,
+    _marker: PhantomData<(D, E)>,
+}
+
+impl<D: Decode, E: Decode> Future for TypedMessageFuture<D, E> {
+    type Output = core::result::Result<D, ProgramError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fut = &mut self;
+        match signals().poll(fut.waiting_reply_to, cx) {
+            ReplyPoll::None => panic!("Somebody created TypedMessageFuture with the MessageId that never ended in static replies!"),
+            ReplyPoll::Pending => Poll::Pending,
+            ReplyPoll::Some((actual_reply, exit_code)) => {
+                let code = ReplyCode::from_raw(exit_code);
+                let decode_err = |err| ProgramError::Contract(ContractError::Decode(err));
+
+                Poll::Ready(if code.is_success() {
+                    D::decode(&mut actual_reply.as_ref()).map_err(decode_err)
+                } else if code.is_user_error() {
+                    Err(E::decode(&mut actual_reply.as_ref()).map_or_else(decode_err, ProgramError::Program))
+                } else {
+                    Err(ProgramError::System(code))
+                })
+            },
+        }
+    }
+}
+
+impl<D: Decode, E: Decode> FusedFuture for TypedMessageFuture<D, E> {
+    fn is_terminated(&self) -> bool {
+        !signals().waits_for(self.waiting_reply_to)
+    }
+}
+
+impl<D: Decode, E: Decode> TypedMessageFuture<D, E> {
+    /// Same as [`MessageFuture::cancel`].
+    pub fn cancel(self) -> Result<()> {
+        cancel_reply(self.waiting_reply_to)
+    }
+}
+
 /// To interrupt a program execution waiting for a reply on a previous message,
 /// one needs to call an `.await` expression.
 /// The initial message that requires a reply is sent instantly.
@@ -103,7 +223,7 @@ impl Future for MessageFuture {
             ReplyPoll::Pending => Poll::Pending,
             ReplyPoll::Some((actual_reply, exit_code)) => {
                 if exit_code != 0 {
-                    return Poll::Ready(Err(ContractError::ExitCode(exit_code)));
+                    return Poll::Ready(Err(ContractError::from_exit_code(exit_code)));
                 }
 
                 Poll::Ready(Ok(actual_reply))
@@ -117,3 +237,87 @@ impl FusedFuture for MessageFuture {
         !signals().waits_for(self.waiting_reply_to)
     }
 }
+
+impl MessageFuture {
+    /// Stop awaiting the reply.
+    ///
+    /// The reply received, but not polled yet, is dropped. The reply received
+    /// later is passed to the hook set with [`set_late_reply_hook`] or
+    /// ignored. The message which sent the request is woken up, unless it's
+    /// the current one or it awaits other replies.
+    ///
+    /// Fails with [`ContractError::ReplyNotAwaited`] if the future has
+    /// already resolved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gstd::{msg, ActorId};
+    ///
+    /// fn fire_and_forget(destination: ActorId) {
+    ///     msg::send_bytes_for_reply(destination, b"PING", 0)
+    ///         .expect("Failed to send")
+    ///         .cancel()
+    ///         .expect("Reply is awaited");
+    /// }
+    /// ```
+    pub fn cancel(self) -> Result<()> {
+        cancel_reply(self.waiting_reply_to)
+    }
+}
+
+/// Future returned by
+/// [`prog::create_program_for_reply`](crate::prog::create_program_for_reply).
+///
+/// Resolves as soon as the new program replies from its `init` function,
+/// returning the new program's address along with the reply payload, or
+/// `Err()` if the reply carries an error code, e.g. because the
+/// initialization failed after replying.
+///
+/// The reply to the init message is generated only if `init` replies
+/// explicitly or fails, so the future never resolves for a program, which
+/// is initialized successfully without replying. The awaiting message is
+/// removed from the waitlist once its waiting expires, so programs created
+/// with this function should always reply from `init`, or the awaiting
+/// should be stopped with [`CreateProgramFuture::cancel`].
+pub struct CreateProgramFuture {
+    /// Waiting reply to this the message id
+    pub waiting_reply_to: MessageId,
+    /// Address of the program being created
+    pub program_id: ActorId,
+}
+
+impl Future for CreateProgramFuture {
+    type Output = Result<(ActorId, Vec<u8>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fut = &mut *self;
+        match signals().poll(fut.waiting_reply_to, cx) {
+            ReplyPoll::None => panic!("Somebody created CreateProgramFuture with the MessageId that never ended in static replies!"),
+            ReplyPoll::Pending => Poll::Pending,
+            ReplyPoll::Some((actual_reply, exit_code)) => {
+                if !ReplyCode::from_raw(exit_code).is_success() {
+                    return Poll::Ready(Err(ContractError::from_exit_code(exit_code)));
+                }
+
+                Poll::Ready(Ok((fut.program_id, actual_reply)))
+            },
+        }
+    }
+}
+
+impl FusedFuture for CreateProgramFuture {
+    fn is_terminated(&self) -> bool {
+        !signals().waits_for(self.waiting_reply_to)
+    }
+}
+
+impl CreateProgramFuture {
+    /// Same as [`MessageFuture::cancel`].
+    ///
+    /// The program is created anyway, only the reply to its init message
+    /// isn't awaited anymore.
+    pub fn cancel(self) -> Result<()> {
+        cancel_reply(self.waiting_reply_to)
+    }
+}
@@ -23,9 +23,11 @@ use crate::{
     errors::{ContractError, Result},
     msg::r#async::{CodecMessageFuture, MessageFuture},
     prelude::{convert::AsRef, vec, Vec},
-    ActorId, MessageId,
+    time::Blocks,
+    ActorId, CodeHash, MessageId, ReservationId,
 };
 use codec::{Decode, Output};
+pub use gcore::msg::{ReplyCode, SignalCode, MAX_METADATA_SIZE};
 use gstd_codegen::wait_for_reply;
 
 trait IntoContractResult<T> {
@@ -95,7 +97,12 @@ impl MessageHandle {
         send_commit(self, program, value)
     }
 
-    pub fn commit_delayed(self, program: ActorId, value: u128, delay: u32) -> Result<MessageId> {
+    pub fn commit_delayed(
+        self,
+        program: ActorId,
+        value: u128,
+        delay: impl Into<Blocks>,
+    ) -> Result<MessageId> {
         send_commit_delayed(self, program, value, delay)
     }
 
@@ -113,7 +120,7 @@ impl MessageHandle {
         program: ActorId,
         gas_limit: u64,
         value: u128,
-        delay: u32,
+        delay: impl Into<Blocks>,
     ) -> Result<MessageId> {
         send_commit_with_gas_delayed(self, program, gas_limit, value, delay)
     }
@@ -162,6 +169,100 @@ pub fn exit_code() -> i32 {
     gcore::msg::exit_code()
 }
 
+/// Get the exit code of the message being processed as a [`ReplyCode`].
+///
+/// Tells the replies generated by the system on errors, e.g. once the
+/// replied message trapped, from the ones sent by the replying program
+/// with its own code via [`reply_with_code`](crate::msg::reply_with_code).
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle_reply() {
+///     let code = msg::status_code();
+///     if code.is_system_error() {
+///         // The replied message failed to be processed
+///     } else if code.is_user_error() {
+///         // The replying program reported an error with its own code
+///     }
+/// }
+/// ```
+pub fn status_code() -> ReplyCode {
+    gcore::msg::status_code()
+}
+
+/// Set the gas limit of messages sent without a specified one.
+///
+/// The limit applies to [`send`](crate::msg::send),
+/// [`reply`](crate::msg::reply) and other functions which don't take an
+/// explicit gas limit. It only affects the messages sent after the call
+/// within the current execution.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     msg::set_default_gas_limit(10_000_000);
+///
+///     // Both are sent with 10_000_000 of gas
+///     msg::send(msg::source(), "PING", 0).expect("Unable to send");
+///     msg::reply("PONG", 0).expect("Unable to reply");
+/// }
+/// ```
+pub fn set_default_gas_limit(gas_limit: u64) {
+    gcore::msg::set_default_gas_limit(gas_limit)
+}
+
+/// Set the total amount of gas the program agrees to pay for messages sent
+/// to it with [`send_bytes_charge_recipient`].
+///
+/// The allowance replaces the previous one and is decreased by the gas limit
+/// of every message paid. Zero allowance refuses such messages.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn init() {
+///     // Pay for up to 100 messages of 10_000_000 gas
+///     msg::set_charge_allowance(1_000_000_000);
+/// }
+/// ```
+pub fn set_charge_allowance(allowance: u64) {
+    gcore::msg::set_charge_allowance(allowance)
+}
+
+/// Get the id of the message the signal is sent about and the reason
+/// of the signal.
+///
+/// This function is used in signal handler to clean up after the message
+/// which failed to be processed.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle_signal() {
+///     let (message_id, code) = msg::signal_details().expect("Not in signal handler");
+///     // ...
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ContractError::Ext`] if called in a context other than
+/// `handle_signal()`.
+pub fn signal_details() -> Result<(MessageId, SignalCode)> {
+    let (message_id, code) = gcore::msg::signal_details()?;
+    Ok((message_id.into(), code))
+}
+
 /// Obtain an identifier of the message currently being processed.
 ///
 /// Message identifiers can be obtained for the currently processed message,
@@ -200,19 +301,72 @@ pub fn load_bytes() -> Vec<u8> {
     result
 }
 
+/// Get the record with given `index` of the payload.
+///
+/// See [`gcore::msg::read_record`] for the format of the payload.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     let first = msg::load_record(0).expect("No records");
+/// }
+/// ```
+pub fn load_record(index: u32) -> Result<Vec<u8>> {
+    let mut record = vec![0u8; size()];
+    let len = gcore::msg::read_record(index, record.as_mut())?;
+    record.truncate(len);
+    Ok(record)
+}
+
+/// Copy `len` bytes of the payload starting at `at` into the `ring` buffer.
+///
+/// Bytes wrap around to the start of the `ring` when reaching its end.
+/// Returns the offset to pass to the next call.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// static mut RING: [u8; 1024] = [0; 1024];
+/// static mut OFFSET: usize = 0;
+///
+/// unsafe extern "C" fn handle() {
+///     let len = msg::size().min(RING.len());
+///     OFFSET = msg::read_ring(0, len, &mut RING, OFFSET);
+/// }
+/// ```
+pub fn read_ring(at: usize, len: usize, ring: &mut [u8], offset: usize) -> usize {
+    gcore::msg::read_ring(at, len, ring, offset)
+}
+
 /// Same as [`reply`](crate::msg::reply), without encoding payload.
 #[wait_for_reply]
 pub fn reply_bytes(payload: impl AsRef<[u8]>, value: u128) -> Result<MessageId> {
     gcore::msg::reply(payload.as_ref(), value).into_contract_result()
 }
 
+/// Same as [`reply_with_code`](crate::msg::reply_with_code), without encoding
+/// payload.
+pub fn reply_bytes_with_code(
+    code: ReplyCode,
+    payload: impl AsRef<[u8]>,
+    value: u128,
+) -> Result<MessageId> {
+    gcore::msg::reply_with_code(code, payload.as_ref(), value).into_contract_result()
+}
+
 /// Same as [`reply_bytes`], but sends delayed.
 pub fn reply_bytes_delayed(
     payload: impl AsRef<[u8]>,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
-    gcore::msg::reply_delayed(payload.as_ref(), value, delay).into_contract_result()
+    gcore::msg::reply_delayed(payload.as_ref(), value, delay.into().into_delay())
+        .into_contract_result()
 }
 
 /// Same as [`reply_bytes`], with gas limit.
@@ -230,10 +384,15 @@ pub fn reply_bytes_with_gas_delayed(
     payload: impl AsRef<[u8]>,
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
-    gcore::msg::reply_with_gas_delayed(payload.as_ref(), gas_limit, value, delay)
-        .into_contract_result()
+    gcore::msg::reply_with_gas_delayed(
+        payload.as_ref(),
+        gas_limit,
+        value,
+        delay.into().into_delay(),
+    )
+    .into_contract_result()
 }
 
 /// Finalize and send a current reply message.
@@ -272,8 +431,8 @@ pub fn reply_commit(value: u128) -> Result<MessageId> {
 }
 
 /// Same as [`reply_commit`], but sends delayed.
-pub fn reply_commit_delayed(value: u128, delay: u32) -> Result<MessageId> {
-    gcore::msg::reply_commit_delayed(value, delay).into_contract_result()
+pub fn reply_commit_delayed(value: u128, delay: impl Into<Blocks>) -> Result<MessageId> {
+    gcore::msg::reply_commit_delayed(value, delay.into().into_delay()).into_contract_result()
 }
 
 /// Same as [`reply_commit`], but with explicit gas limit.
@@ -302,8 +461,13 @@ pub fn reply_commit_with_gas(gas_limit: u64, value: u128) -> Result<MessageId> {
 }
 
 /// Same as [`reply_commit_with_gas`], but sends delayed.
-pub fn reply_commit_with_gas_delayed(gas_limit: u64, value: u128, delay: u32) -> Result<MessageId> {
-    gcore::msg::reply_commit_with_gas_delayed(gas_limit, value, delay).into_contract_result()
+pub fn reply_commit_with_gas_delayed(
+    gas_limit: u64,
+    value: u128,
+    delay: impl Into<Blocks>,
+) -> Result<MessageId> {
+    gcore::msg::reply_commit_with_gas_delayed(gas_limit, value, delay.into().into_delay())
+        .into_contract_result()
 }
 
 /// Push a payload part to the current reply message.
@@ -357,6 +521,54 @@ pub fn reply_to() -> MessageId {
     gcore::msg::reply_to().into()
 }
 
+/// Get a payload of the reply to the `original` message.
+///
+/// Only genuine replies are processed by `handle_reply()`, as the runtime
+/// sets the replied message id itself, so a program can't fake a reply by
+/// sending a plain message. However, a reply may answer another message
+/// than expected, so programs matching replies only by the source
+/// can be fooled. This function checks the replied message id as well.
+///
+/// Futures returned by `*_for_reply` functions perform the same check:
+/// they're resolved only by replies to the messages they wait for.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, MessageId};
+///
+/// static mut REQUEST: Option<MessageId> = None;
+///
+/// unsafe extern "C" fn handle_reply() {
+///     let request = REQUEST.expect("No request sent");
+///     let response = msg::expect_reply_to(request).expect("Unexpected reply");
+///     // ...
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ContractError::UnexpectedReply`] with the actual replied
+/// message id if it differs from `original`,
+/// [`ContractError::ReplyBudgetExceeded`] if the reply exceeded the budget
+/// of `handle_reply` and [`ContractError::ExitCode`] if the reply is
+/// another error one.
+///
+/// # Panics
+///
+/// Panics if called in a context other than `handle_reply()`.
+pub fn expect_reply_to(original: MessageId) -> Result<Vec<u8>> {
+    let reply_to = reply_to();
+    if reply_to != original {
+        return Err(ContractError::UnexpectedReply(reply_to));
+    }
+
+    match exit_code() {
+        0 => Ok(load_bytes()),
+        exit_code => Err(ContractError::from_exit_code(exit_code)),
+    }
+}
+
 /// Send a new message to the program or user.
 ///
 /// Gear allows programs to communicate to each other and users via messages.
@@ -392,14 +604,155 @@ pub fn send_bytes<T: AsRef<[u8]>>(program: ActorId, payload: T, value: u128) ->
     gcore::msg::send(program.into(), payload.as_ref(), value).into_contract_result()
 }
 
+/// Same as [`send_bytes`], but attaches `metadata` to the message.
+///
+/// Metadata is an opaque header kept apart from the payload, e.g. routing
+/// or priority tags. The recipient reads it with [`message_meta`].
+///
+/// Execution traps if `metadata` exceeds [`MAX_METADATA_SIZE`].
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     msg::send_bytes_with_meta(msg::source(), b"HELLO", b"priority:high", 0)
+///         .expect("Unable to send");
+/// }
+/// ```
+pub fn send_bytes_with_meta<T: AsRef<[u8]>, M: AsRef<[u8]>>(
+    program: ActorId,
+    payload: T,
+    metadata: M,
+    value: u128,
+) -> Result<MessageId> {
+    gcore::msg::send_with_meta(program.into(), payload.as_ref(), metadata.as_ref(), value)
+        .into_contract_result()
+}
+
+/// Same as [`send_bytes`], but the reply on the message is delivered to the
+/// `reply_to` program instead of the current one.
+///
+/// The current program never gets the reply, so there is no way to wait
+/// for it. The `reply_to` program gets a reply on a message it never sent
+/// and should check the [`source`] of the reply before trusting it.
+///
+/// See [`gcore::msg::send_with_reply_to`] for the details.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, ActorId};
+///
+/// static mut SERVICE: ActorId = ActorId::zero();
+///
+/// unsafe extern "C" fn handle() {
+///     // Let the service reply to the one who asked.
+///     msg::send_bytes_with_reply_to(SERVICE, msg::load_bytes(), msg::source(), 0)
+///         .expect("Unable to send");
+/// }
+/// ```
+pub fn send_bytes_with_reply_to<T: AsRef<[u8]>>(
+    program: ActorId,
+    payload: T,
+    reply_to: ActorId,
+    value: u128,
+) -> Result<MessageId> {
+    gcore::msg::send_with_reply_to(program.into(), payload.as_ref(), reply_to.into(), value)
+        .into_contract_result()
+}
+
+/// Same as [`send_bytes`], but the message value is drawn from the value
+/// reservation made by [`exec::reserve_value`](crate::exec::reserve_value).
+pub fn reservation_send_bytes<T: AsRef<[u8]>>(
+    reservation_id: ReservationId,
+    program: ActorId,
+    payload: T,
+    value: u128,
+) -> Result<MessageId> {
+    gcore::msg::reservation_send(
+        reservation_id.into(),
+        program.into(),
+        payload.as_ref(),
+        value,
+    )
+    .into_contract_result()
+}
+
+/// Reserve `amount` of value and send a delayed message drawing its value
+/// from the reservation, returning ids of both.
+///
+/// Either both the reservation and the message are made, or neither is.
+pub fn reserve_and_send_bytes<T: AsRef<[u8]>>(
+    amount: u128,
+    program: ActorId,
+    payload: T,
+    value: u128,
+    delay: impl Into<Blocks>,
+) -> Result<(ReservationId, MessageId)> {
+    gcore::msg::reserve_and_send(
+        amount,
+        program.into(),
+        payload.as_ref(),
+        value,
+        delay.into().into_delay(),
+    )
+    .map(|(reservation_id, message_id)| (reservation_id.into(), message_id.into()))
+    .map_err(Into::into)
+}
+
 /// Same as [`send_bytes`], but sends delayed.
 pub fn send_bytes_delayed<T: AsRef<[u8]>>(
     program: ActorId,
     payload: T,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
+) -> Result<MessageId> {
+    gcore::msg::send_delayed(
+        program.into(),
+        payload.as_ref(),
+        value,
+        delay.into().into_delay(),
+    )
+    .into_contract_result()
+}
+
+/// Send the payload of the current message to the program or user as is.
+///
+/// The payload isn't read into the program memory, which makes forwarding
+/// cheaper than sending the result of [`load_bytes`].
+///
+/// The forwarded message is sent by the current program, so the reply to it
+/// comes to the current program rather than to [`source`] of the current
+/// message.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, ActorId};
+///
+/// async fn proxy(target: ActorId) {
+///     let reply = msg::forward_for_reply(target, msg::value())
+///         .expect("Unable to forward")
+///         .await
+///         .expect("Error in reply");
+///     msg::reply_bytes(reply, 0).expect("Unable to reply");
+/// }
+/// ```
+#[wait_for_reply]
+pub fn forward(program: ActorId, value: u128) -> Result<MessageId> {
+    gcore::msg::forward(program.into(), value).into_contract_result()
+}
+
+/// Same as [`forward`], but sends delayed.
+pub fn forward_delayed(
+    program: ActorId,
+    value: u128,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
-    gcore::msg::send_delayed(program.into(), payload.as_ref(), value, delay).into_contract_result()
+    gcore::msg::forward_delayed(program.into(), value, delay.into().into_delay())
+        .into_contract_result()
 }
 
 /// Same as [`send_bytes`], but with explicit gas limit.
@@ -438,9 +791,46 @@ pub fn send_bytes_with_gas_delayed<T: AsRef<[u8]>>(
     payload: T,
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
+) -> Result<MessageId> {
+    gcore::msg::send_with_gas_delayed(
+        program.into(),
+        payload.as_ref(),
+        gas_limit,
+        value,
+        delay.into().into_delay(),
+    )
+    .into_contract_result()
+}
+
+/// Same as [`send_bytes_with_gas`], but the gas is paid by the recipient
+/// program from its balance.
+///
+/// The recipient has to agree to pay with [`set_charge_allowance`]. If it
+/// didn't allow paying for `gas_limit` or can't afford it, the message is not
+/// delivered and an error reply is received instead.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, ActorId};
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let id = msg::source();
+///
+///     msg::send_bytes_charge_recipient(id, b"HELLO", 10_000_000, 0)
+///         .expect("Failed to send message");
+/// }
+/// ```
+#[wait_for_reply]
+pub fn send_bytes_charge_recipient<T: AsRef<[u8]>>(
+    program: ActorId,
+    payload: T,
+    gas_limit: u64,
+    value: u128,
 ) -> Result<MessageId> {
-    gcore::msg::send_with_gas_delayed(program.into(), payload.as_ref(), gas_limit, value, delay)
+    gcore::msg::send_charge_recipient(program.into(), payload.as_ref(), gas_limit, value)
         .into_contract_result()
 }
 
@@ -486,10 +876,15 @@ pub fn send_commit_delayed(
     handle: MessageHandle,
     program: ActorId,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
-    gcore::msg::send_commit_delayed(handle.into(), program.into(), value, delay)
-        .into_contract_result()
+    gcore::msg::send_commit_delayed(
+        handle.into(),
+        program.into(),
+        value,
+        delay.into().into_delay(),
+    )
+    .into_contract_result()
 }
 
 /// Same as [`send_commit`], but with explicit gas limit.
@@ -530,10 +925,16 @@ pub fn send_commit_with_gas_delayed(
     program: ActorId,
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
-    gcore::msg::send_commit_with_gas_delayed(handle.into(), program.into(), gas_limit, value, delay)
-        .into_contract_result()
+    gcore::msg::send_commit_with_gas_delayed(
+        handle.into(),
+        program.into(),
+        gas_limit,
+        value,
+        delay.into().into_delay(),
+    )
+    .into_contract_result()
 }
 
 /// Initialize a message to send, formed in parts.
@@ -612,6 +1013,80 @@ pub fn size() -> usize {
     gcore::msg::size()
 }
 
+/// Get the maximum size of a message payload.
+///
+/// The limit may change across runtime upgrades, so it should be
+/// queried instead of being hardcoded, e.g. to split large data
+/// into messages.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     let data = [0u8; 1024];
+///
+///     for chunk in data.chunks(msg::max_payload_size()) {
+///         msg::send_bytes(msg::source(), chunk, 0).expect("Unable to send");
+///     }
+/// }
+/// ```
+pub fn max_payload_size() -> usize {
+    gcore::msg::max_payload_size()
+}
+
+/// Tell whether the message sent to the `user` is still in the user's
+/// mailbox, i.e. it wasn't claimed, replied or removed on timeout yet.
+///
+/// Only messages sent by this program can be looked up: messages sent by
+/// other actors are reported as absent.
+///
+/// Useful to decide on the messages sent earlier without acting on the
+/// ones already consumed.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, ActorId, MessageId};
+///
+/// static mut SENT: Option<(ActorId, MessageId)> = None;
+///
+/// unsafe extern "C" fn handle() {
+///     if let Some((user, message_id)) = SENT {
+///         if !msg::mailbox_contains(user, message_id) {
+///             // The user has already responded.
+///         }
+///     }
+/// }
+/// ```
+pub fn mailbox_contains(user: ActorId, message_id: MessageId) -> bool {
+    gcore::msg::mailbox_contains(user.into(), message_id.into())
+}
+
+/// Get metadata of the message being processed.
+///
+/// Metadata is empty unless the sender set it, e.g. with
+/// [`send_bytes_with_meta`].
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     if msg::message_meta() == b"priority:high" {
+///         // ...
+///     }
+/// }
+/// ```
+pub fn message_meta() -> Vec<u8> {
+    let mut buffer = [0; MAX_METADATA_SIZE];
+    let len = gcore::msg::message_meta(&mut buffer);
+    buffer[..len].to_vec()
+}
+
 /// Get the identifier of the message source (256-bit address).
 ///
 /// This function is used to obtain [`ActorId`] of the account that sends
@@ -631,6 +1106,27 @@ pub fn source() -> ActorId {
     gcore::msg::source().into()
 }
 
+/// Get the code hash of the program that sent the message being processed.
+///
+/// Returns an error if the message source has no code, e.g. when the
+/// message was sent by a user.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     if let Ok(code_hash) = msg::source_code_id() {
+///         // The message came from a program.
+///     }
+/// }
+/// ```
+pub fn source_code_id() -> Result<CodeHash> {
+    gcore::msg::source_code_id().into_contract_result()
+}
+
 /// Get the value associated with the message being processed.
 ///
 /// This function is used to obtain the value that has been sent along with
@@ -23,8 +23,12 @@
 use crate::{
     async_runtime::signals,
     errors::{ContractError, Result},
-    msg::r#async::{CodecMessageFuture, MessageFuture},
+    msg::{
+        r#async::{CodecMessageFuture, MessageFuture},
+        ReplyCode,
+    },
     prelude::convert::AsRef,
+    time::Blocks,
     ActorId, MessageId,
 };
 use codec::{Decode, Encode};
@@ -51,8 +55,62 @@ pub fn reply<E: Encode>(payload: E, value: u128) -> Result<MessageId> {
     super::reply_bytes(payload.encode(), value)
 }
 
+/// Same as [`reply`], but with the exit code chosen by the program.
+///
+/// Codes reserved for the replies generated by the system, i.e. `1..=255`,
+/// can't be used.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg::{self, ReplyCode};
+///
+/// const NOT_FOUND: ReplyCode = ReplyCode::from_raw(404);
+///
+/// unsafe extern "C" fn handle() {
+///     // ...
+///     msg::reply_with_code(NOT_FOUND, "Unknown key", 0).expect("Failed to reply");
+/// }
+/// ```
+pub fn reply_with_code<E: Encode>(code: ReplyCode, payload: E, value: u128) -> Result<MessageId> {
+    super::reply_bytes_with_code(code, payload.encode(), value)
+}
+
+/// Code of the replies carrying an error sent by [`reply_err`].
+pub const ERROR_REPLY_CODE: ReplyCode = ReplyCode::from_raw(256);
+
+/// Reply to the current message with an error, which the caller decodes
+/// with [`CodecMessageFuture::with_errors`](crate::msg::CodecMessageFuture::with_errors).
+///
+/// The error is sent encoded with [`ERROR_REPLY_CODE`], so the caller
+/// tells it from both the successful replies and the replies generated by
+/// the system.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     let (a, b): (u32, u32) = msg::load().expect("Failed to decode");
+///
+///     if b == 0 {
+///         msg::reply_err("Division by zero", 0).expect("Failed to reply");
+///     } else {
+///         msg::reply(a / b, 0).expect("Failed to reply");
+///     }
+/// }
+/// ```
+pub fn reply_err<E: Encode>(err: E, value: u128) -> Result<MessageId> {
+    reply_with_code(ERROR_REPLY_CODE, err, value)
+}
+
 /// Same as [`reply`], but sends delayed.
-pub fn reply_delayed<E: Encode>(payload: E, value: u128, delay: u32) -> Result<MessageId> {
+pub fn reply_delayed<E: Encode>(
+    payload: E,
+    value: u128,
+    delay: impl Into<Blocks>,
+) -> Result<MessageId> {
     super::reply_bytes_delayed(payload.encode(), value, delay)
 }
 
@@ -99,7 +157,7 @@ pub fn reply_with_gas_delayed<E: Encode>(
     payload: E,
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
     super::reply_bytes_with_gas_delayed(payload.encode(), gas_limit, value, delay)
 }
@@ -115,11 +173,22 @@ pub fn send_delayed<E: Encode>(
     program: ActorId,
     payload: E,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
     super::send_bytes_delayed(program, payload.encode(), value, delay)
 }
 
+/// Same as [`send`], but the reply on the message is delivered to the
+/// `reply_to` program instead of the current one.
+pub fn send_with_reply_to<E: Encode>(
+    program: ActorId,
+    payload: E,
+    reply_to: ActorId,
+    value: u128,
+) -> Result<MessageId> {
+    super::send_bytes_with_reply_to(program, payload.encode(), reply_to, value)
+}
+
 /// Same as [`send`], but with explicit gas limit.
 #[wait_for_reply]
 pub fn send_with_gas<E: Encode>(
@@ -137,7 +206,18 @@ pub fn send_with_gas_delayed<E: Encode>(
     payload: E,
     gas_limit: u64,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<MessageId> {
     super::send_bytes_with_gas_delayed(program, payload.encode(), gas_limit, value, delay)
 }
+
+/// Same as [`send_with_gas`], but the gas is paid by the recipient.
+#[wait_for_reply]
+pub fn send_charge_recipient<E: Encode>(
+    program: ActorId,
+    payload: E,
+    gas_limit: u64,
+    value: u128,
+) -> Result<MessageId> {
+    super::send_bytes_charge_recipient(program, payload.encode(), gas_limit, value)
+}
@@ -0,0 +1,111 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module with the scope guard replying with the outcome of processing.
+//!
+//! Replies are encoded as `Result<T, E>`, so the first byte of the reply
+//! payload is the status code: `0` on success and `1` on failure.
+
+use crate::{errors::Result, MessageId};
+use codec::Encode;
+
+/// Scope guard replying with an error unless processing succeeded.
+///
+/// Once created, the guard replies with `Err(error)` when it's dropped,
+/// e.g. on early return from the handler, unless [`ReplyGuard::reply`]
+/// was called. Traps aren't covered: the runtime replies on them itself.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg::{self, ReplyGuard};
+///
+/// unsafe extern "C" fn handle() {
+///     let guard = ReplyGuard::new("Invalid payload");
+///     let amount: u128 = match msg::load() {
+///         Ok(amount) => amount,
+///         Err(_) => return,
+///     };
+///     // ...
+///     guard.reply(amount, 0).expect("Unable to reply");
+/// }
+/// ```
+#[must_use = "The guard replies with an error once it's dropped"]
+pub struct ReplyGuard<E: Encode> {
+    error: Option<E>,
+}
+
+impl<E: Encode> ReplyGuard<E> {
+    /// Create the guard replying with `Err(error)` if it's dropped
+    /// before replying.
+    pub fn new(error: E) -> Self {
+        Self { error: Some(error) }
+    }
+
+    /// Replace the error the guard replies with.
+    pub fn set_error(&mut self, error: E) {
+        self.error = Some(error);
+    }
+
+    /// Reply with `Ok(payload)`, disarming the guard.
+    pub fn reply<T: Encode>(mut self, payload: T, value: u128) -> Result<MessageId> {
+        self.error = None;
+        super::reply(Ok::<T, E>(payload), value)
+    }
+}
+
+impl<E: Encode> Drop for ReplyGuard<E> {
+    fn drop(&mut self) {
+        if let Some(error) = self.error.take() {
+            // Reply may be already sent, so the error is ignored.
+            let _ = super::reply(Err::<(), E>(error), 0);
+        }
+    }
+}
+
+/// Process the message by `handler`, replying with its result.
+///
+/// Replies with `Ok(payload)` attaching `value` if `handler` succeeds,
+/// and with `Err(error)` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// fn transfer() -> Result<u128, &'static str> {
+///     let amount: u128 = msg::load().map_err(|_| "Invalid payload")?;
+///     // ...
+///     Ok(amount)
+/// }
+///
+/// unsafe extern "C" fn handle() {
+///     msg::reply_result(transfer, 0).expect("Unable to reply");
+/// }
+/// ```
+pub fn reply_result<T, E, F>(handler: F, value: u128) -> Result<MessageId>
+where
+    T: Encode,
+    E: Encode,
+    F: FnOnce() -> core::result::Result<T, E>,
+{
+    match handler() {
+        Ok(payload) => super::reply(Ok::<T, E>(payload), value),
+        Err(error) => super::reply(Err::<T, E>(error), 0),
+    }
+}
@@ -0,0 +1,111 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Leading bytes of the payload, read once per message.
+//!
+//! Routers dispatch on the first bytes of the payload, e.g. an enum
+//! discriminant or a method selector, and don't need the whole payload
+//! loaded for that. The bytes are read on the first request and cached
+//! until a message with another id is processed, so the cache is reset for
+//! every new message and doesn't interfere with [`load_bytes`](super::load_bytes).
+
+use crate::MessageId;
+
+/// Max amount of the leading bytes of the payload available with [`peek`].
+pub const PEEK_LIMIT: usize = 32;
+
+struct Head {
+    message_id: MessageId,
+    len: usize,
+    bytes: [u8; PEEK_LIMIT],
+}
+
+static mut HEAD: Option<Head> = None;
+
+fn head() -> &'static Head {
+    let message_id = super::id();
+
+    unsafe {
+        if !matches!(&HEAD, Some(head) if head.message_id == message_id) {
+            let len = super::size().min(PEEK_LIMIT);
+            let mut bytes = [0; PEEK_LIMIT];
+            gcore::msg::read(0, &mut bytes[..len]);
+
+            HEAD = Some(Head {
+                message_id,
+                len,
+                bytes,
+            });
+        }
+
+        HEAD.as_ref().expect("set above")
+    }
+}
+
+/// Get the first `N` bytes of the payload without loading the whole one.
+///
+/// Returns `None` if the payload is shorter than `N` bytes.
+///
+/// # Panics
+///
+/// Panics if `N` exceeds [`PEEK_LIMIT`].
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     let selector: [u8; 4] = msg::peek().expect("No selector");
+/// }
+/// ```
+pub fn peek<const N: usize>() -> Option<[u8; N]> {
+    assert!(N <= PEEK_LIMIT, "Can't peek more than {} bytes", PEEK_LIMIT);
+
+    let head = head();
+    (N <= head.len).then(|| {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&head.bytes[..N]);
+        bytes
+    })
+}
+
+/// Get the first byte of the payload, e.g. the discriminant of the
+/// SCALE-encoded enum.
+///
+/// Returns `None` if the payload is empty.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::msg;
+///
+/// unsafe extern "C" fn handle() {
+///     match msg::discriminant() {
+///         Some(0) => {
+///             // ...
+///         }
+///         _ => {
+///             // ...
+///         }
+///     }
+/// }
+/// ```
+pub fn discriminant() -> Option<u8> {
+    peek::<1>().map(|[discriminant]| discriminant)
+}
@@ -26,3 +26,9 @@ pub use basic::*;
 
 mod encoded;
 pub use encoded::*;
+
+mod guard;
+pub use guard::*;
+
+mod head;
+pub use head::*;
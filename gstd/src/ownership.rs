@@ -0,0 +1,70 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for access checks of the message source.
+//!
+//! Handlers restricted to the owner of the program may use [`only`] directly
+//! or be marked with the [`only_owner`](crate::only_owner) attribute, which
+//! replies to messages from anyone else with [`AccessDenied`] sent as an
+//! error reply (see [`msg::reply_err`]), so callers detect it by
+//! [`ERROR_REPLY_CODE`](msg::ERROR_REPLY_CODE).
+
+use crate::{
+    errors::{ContractError, Result},
+    exec, msg,
+    prelude::{Decode, Encode, TypeInfo},
+    ActorId,
+};
+
+/// Error replied to the message which source isn't allowed to call
+/// the handler.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TypeInfo, Decode, Encode)]
+pub struct AccessDenied;
+
+/// Check the current message is sent by `owner`.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, ownership, ActorId};
+///
+/// static mut OWNER: ActorId = ActorId::zero();
+///
+/// unsafe extern "C" fn handle() {
+///     ownership::only(&OWNER).expect("Access denied");
+///     // ...
+/// }
+/// ```
+pub fn only(owner: &ActorId) -> Result<()> {
+    if msg::source() == *owner {
+        Ok(())
+    } else {
+        Err(ContractError::AccessDenied)
+    }
+}
+
+/// Check the current message is sent by the program itself.
+pub fn only_self() -> Result<()> {
+    only(&exec::program_id())
+}
+
+/// Reply with [`AccessDenied`] error and stop the execution.
+pub fn deny() -> ! {
+    msg::reply_err(AccessDenied, 0).expect("Unable to reply");
+    exec::leave()
+}
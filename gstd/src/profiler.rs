@@ -0,0 +1,156 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for profiling allocations of the program.
+//!
+//! Enabled with the `profiler` feature, which makes the global allocator
+//! record sizes of all the allocations, so the sites taking the most memory
+//! (and so the most gas for its growth) can be found. Allocations are
+//! attributed to the scope opened with [`alloc_tag`], so hot paths of the
+//! program should be tagged to be told apart.
+//!
+//! Recorded statistics could be obtained as a [`Report`], e.g. to be returned
+//! from `meta_state`, or printed with [`dump`].
+//!
+//! Without the feature neither the allocator nor the program are affected.
+//!
+//! # Examples
+//!
+//! ```
+//! use gstd::{prelude::*, profiler};
+//!
+//! fn parse(input: &str) -> Vec<String> {
+//!     let _tag = gstd::alloc_tag("parse");
+//!
+//!     input.split(',').map(String::from).collect()
+//! }
+//!
+//! #[no_mangle]
+//! extern "C" fn handle() {
+//!     let _items = parse("a,b,c");
+//!
+//!     profiler::dump();
+//! }
+//! ```
+
+use crate::prelude::{Decode, Encode, String, ToString, TypeInfo, Vec};
+
+/// Amount of allocations made in a scope with the given tag.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct AllocSite {
+    /// Tag of the scope.
+    pub tag: String,
+    /// Size of the allocation in bytes.
+    pub size: u32,
+}
+
+/// Statistics of allocations made since the last [`reset`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct Report {
+    /// Total amount of allocations.
+    pub count: u64,
+    /// Total amount of allocated bytes.
+    pub bytes: u64,
+    /// Amounts of allocations by sizes, where `i`-th element
+    /// counts sizes in `[2^i, 2^(i + 1))`, while the last one
+    /// also counts all the larger ones.
+    pub histogram: Vec<u32>,
+    /// The largest allocations, the largest first.
+    pub largest: Vec<AllocSite>,
+}
+
+/// Guard of the allocation scope, restoring the previous one once dropped.
+#[must_use = "scope is closed as soon as the guard is dropped"]
+pub struct AllocTag {
+    #[allow(unused)]
+    previous: &'static str,
+}
+
+impl Drop for AllocTag {
+    fn drop(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        galloc::profiler::set_tag(self.previous);
+    }
+}
+
+/// Open the allocation scope with the given tag.
+///
+/// Allocations are attributed to the tag until the returned guard is dropped.
+pub fn alloc_tag(tag: &'static str) -> AllocTag {
+    #[cfg(target_arch = "wasm32")]
+    let previous = galloc::profiler::set_tag(tag);
+    #[cfg(not(target_arch = "wasm32"))]
+    let previous = {
+        let _ = tag;
+        ""
+    };
+
+    AllocTag { previous }
+}
+
+/// Get statistics of allocations made so far.
+///
+/// Returns an empty report outside of Wasm, where allocations aren't profiled.
+pub fn report() -> Report {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let stats = galloc::profiler::stats();
+
+        let mut largest: Vec<_> = stats.largest.iter().flatten().copied().collect();
+        largest.sort_by(|a, b| b.size.cmp(&a.size));
+
+        Report {
+            count: stats.count,
+            bytes: stats.bytes,
+            histogram: stats.histogram.to_vec(),
+            largest: largest
+                .into_iter()
+                .map(|allocation| AllocSite {
+                    tag: allocation.tag.to_string(),
+                    size: allocation.size as u32,
+                })
+                .collect(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    Report::default()
+}
+
+/// Forget allocations made so far.
+pub fn reset() {
+    #[cfg(target_arch = "wasm32")]
+    galloc::profiler::reset();
+}
+
+/// Print statistics of allocations made so far with [`debug!`](crate::debug).
+pub fn dump() {
+    let report = report();
+
+    crate::debug!("allocations: {}, bytes: {}", report.count, report.bytes);
+
+    for (i, &amount) in report.histogram.iter().enumerate() {
+        if amount != 0 {
+            crate::debug!("sizes from {}: {}", 1u64 << i, amount);
+        }
+    }
+
+    for site in report.largest {
+        crate::debug!("{}: {} bytes", site.tag, site.size);
+    }
+}
@@ -0,0 +1,223 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Program creation builder.
+
+use super::{creation_error, CreatedProgram};
+use crate::{common::errors::Result, prelude::convert::AsRef, time::Blocks, ActorId, CodeHash};
+use core::num::NonZeroU32;
+
+/// Syscall the creation is dispatched to, depending on the options set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Creation {
+    Plain,
+    Delayed(NonZeroU32),
+    WithGas(u64),
+    WithGasDelayed(u64, NonZeroU32),
+}
+
+/// Builder of a new program.
+///
+/// Salt and payload are empty and value is zero unless set. Without
+/// [`with_gas`](Self::with_gas) the init message gets gas limit as described
+/// in [`gcore::prog::create_program`], and without [`delayed`](Self::delayed)
+/// it's sent immediately.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{prog::ProgramBuilder, CodeHash};
+///
+/// fn deploy(code_hash: CodeHash) {
+///     let program_id = ProgramBuilder::new()
+///         .salt(b"salt")
+///         .payload(b"init")
+///         .with_gas(10_000_000)
+///         .delayed(5)
+///         .create(code_hash)
+///         .expect("Failed to create program");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProgramBuilder<'a> {
+    salt: &'a [u8],
+    payload: &'a [u8],
+    gas_limit: Option<u64>,
+    value: u128,
+    delay: Option<NonZeroU32>,
+}
+
+impl<'a> ProgramBuilder<'a> {
+    /// Create the builder with no options set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Salt of the new program's address.
+    pub fn salt<S: AsRef<[u8]> + ?Sized>(self, salt: &'a S) -> Self {
+        Self {
+            salt: salt.as_ref(),
+            ..self
+        }
+    }
+
+    /// Payload of the init message.
+    pub fn payload<P: AsRef<[u8]> + ?Sized>(self, payload: &'a P) -> Self {
+        Self {
+            payload: payload.as_ref(),
+            ..self
+        }
+    }
+
+    /// Gas limit of the init message.
+    pub fn with_gas(self, gas_limit: u64) -> Self {
+        Self {
+            gas_limit: Some(gas_limit),
+            ..self
+        }
+    }
+
+    /// Value sent with the init message.
+    pub fn value(self, value: u128) -> Self {
+        Self { value, ..self }
+    }
+
+    /// Delay of the init message. Zero delay sends it immediately.
+    pub fn delayed(self, delay: impl Into<Blocks>) -> Self {
+        Self {
+            delay: delay.into().into_delay(),
+            ..self
+        }
+    }
+
+    fn creation(&self) -> Creation {
+        match (self.gas_limit, self.delay) {
+            (None, None) => Creation::Plain,
+            (None, Some(delay)) => Creation::Delayed(delay),
+            (Some(gas_limit), None) => Creation::WithGas(gas_limit),
+            (Some(gas_limit), Some(delay)) => Creation::WithGasDelayed(gas_limit, delay),
+        }
+    }
+
+    /// Create the program with the `code_hash` and return its address.
+    ///
+    /// See [`create_program`](super::create_program) for the errors.
+    pub fn create(self, code_hash: CodeHash) -> Result<ActorId> {
+        self.create_with_init_id(code_hash)
+            .map(|created| created.program_id)
+    }
+
+    /// Same as [`create`](Self::create), but also returns id of the init
+    /// message, which the reply from the program's `init` function refers to.
+    pub fn create_with_init_id(self, code_hash: CodeHash) -> Result<CreatedProgram> {
+        let code_hash = code_hash.into();
+        let Self {
+            salt,
+            payload,
+            value,
+            ..
+        } = self;
+
+        let (init_message_id, program_id) = match self.creation() {
+            Creation::Plain => {
+                gcore::prog::create_program_for_reply(code_hash, salt, payload, value)
+            }
+            Creation::Delayed(delay) => gcore::prog::create_program_for_reply_delayed(
+                code_hash,
+                salt,
+                payload,
+                value,
+                Some(delay),
+            ),
+            Creation::WithGas(gas_limit) => gcore::prog::create_program_with_gas_for_reply(
+                code_hash, salt, payload, gas_limit, value,
+            ),
+            Creation::WithGasDelayed(gas_limit, delay) => {
+                gcore::prog::create_program_with_gas_for_reply_delayed(
+                    code_hash,
+                    salt,
+                    payload,
+                    gas_limit,
+                    value,
+                    Some(delay),
+                )
+            }
+        }
+        .map_err(creation_error)?;
+
+        Ok(CreatedProgram {
+            program_id: program_id.into(),
+            init_message_id: init_message_id.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delay(blocks: u32) -> NonZeroU32 {
+        NonZeroU32::new(blocks).expect("non-zero delay")
+    }
+
+    #[test]
+    fn options_pick_syscall() {
+        let builder = ProgramBuilder::new().payload(b"init").value(100);
+        assert_eq!(builder.creation(), Creation::Plain);
+        assert_eq!(builder.delayed(3).creation(), Creation::Delayed(delay(3)));
+        assert_eq!(builder.with_gas(1_000).creation(), Creation::WithGas(1_000));
+        assert_eq!(
+            builder.delayed(3).with_gas(1_000).creation(),
+            Creation::WithGasDelayed(1_000, delay(3))
+        );
+
+        // Zero delay sends the init message immediately.
+        assert_eq!(builder.delayed(0).creation(), Creation::Plain);
+        assert_eq!(
+            builder.with_gas(1_000).delayed(0).creation(),
+            Creation::WithGas(1_000)
+        );
+    }
+
+    #[test]
+    fn options_kept_until_creation() {
+        let builder = ProgramBuilder::new()
+            .salt("salt")
+            .payload(b"\x01\x02\x03")
+            .value(100);
+
+        assert_eq!(builder.salt, b"salt");
+        assert_eq!(builder.payload, &[1, 2, 3]);
+        assert_eq!(builder.value, 100);
+        assert_eq!(builder.gas_limit, None);
+    }
+
+    #[test]
+    fn salt_empty_unless_set() {
+        let builder = ProgramBuilder::new().payload(b"init").with_gas(1_000);
+
+        assert!(builder.salt.is_empty());
+        assert_eq!(
+            builder,
+            ProgramBuilder::new()
+                .salt(b"")
+                .payload(b"init")
+                .with_gas(1_000)
+        );
+    }
+}
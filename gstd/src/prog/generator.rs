@@ -18,32 +18,114 @@
 
 //! Program generation module
 
-use crate::{common::errors::Result, prog, ActorId, CodeHash};
+use crate::{common::errors::Result, exec, prog, time::Blocks, ActorId, CodeHash};
 use codec::alloc::vec::Vec;
 
 /// `ProgramGenerator` allows you to create programs
 /// without need to set the salt manually.
 pub struct ProgramGenerator(u64);
 
+/// Seed of the salts derived from explicit nonces.
+///
+/// Program id is the hash of the code hash and the salt, so the program
+/// created by [`ProgramGenerator::create_program_with_nonce`] gets
+/// `hash(code_hash ++ NONCE_SEED ++ creator ++ nonce.to_be_bytes())` as its
+/// id on any chain, regardless of the message creating it. The creator's id
+/// is mixed in, so other programs can't take the id in advance.
+pub const NONCE_SEED: &[u8] = b"nonce_salt_generator";
+
 // The only existing instance since there is no public ways to construct it.
 static mut PROGRAM_GENERATOR: ProgramGenerator = ProgramGenerator(0);
 
 impl ProgramGenerator {
     pub fn get_salt() -> Vec<u8> {
+        let salt = Self::peek_salt();
+
+        unsafe {
+            PROGRAM_GENERATOR.0 = PROGRAM_GENERATOR.0.saturating_add(1);
+        }
+
+        salt
+    }
+
+    /// Salt the next [`get_salt`](Self::get_salt) call returns, which isn't
+    /// consumed.
+    pub fn peek_salt() -> Vec<u8> {
         // Prefix for not crossing with the user salt.
         let unique_key = b"salt_generator";
         // Provide salt uniqueness across all programs from other messages.
         let message_id = crate::msg::id();
 
-        let creator_nonce;
-        unsafe {
-            creator_nonce = PROGRAM_GENERATOR.0.to_be_bytes();
-            PROGRAM_GENERATOR.0 = PROGRAM_GENERATOR.0.saturating_add(1);
-        }
+        let creator_nonce = unsafe { PROGRAM_GENERATOR.0.to_be_bytes() };
 
         [unique_key, message_id.as_ref(), &creator_nonce].concat()
     }
 
+    /// Address of the program with the `code_hash`, which the next creation
+    /// within the current message gets.
+    ///
+    /// The address is computed the same way the runtime does, as the hash
+    /// of the code hash and the [next salt](Self::peek_salt), so it may be
+    /// used before the creation, e.g. to register the program.
+    pub fn calculate_program_id(code_hash: CodeHash) -> ActorId {
+        let argument = [code_hash.as_ref(), &Self::peek_salt()].concat();
+
+        let mut id = [0; 32];
+        id.copy_from_slice(blake2_rfc::blake2b::blake2b(32, &[], &argument).as_bytes());
+        id.into()
+    }
+
+    /// Salt derived from the explicit `nonce` of the `creator` program.
+    ///
+    /// Unlike [`get_salt`](Self::get_salt), the salt depends on nothing but
+    /// the creator and the nonce, so the same nonce always leads to the same
+    /// program id for the same code and creator. Reusing the nonce fails,
+    /// as creation of the program with existing id does.
+    pub fn salt_from_nonce(creator: ActorId, nonce: u64) -> Vec<u8> {
+        [NONCE_SEED, creator.as_ref(), &nonce.to_be_bytes()].concat()
+    }
+
+    /// Create the program with the salt [derived](Self::salt_from_nonce)
+    /// from the explicit `nonce` and the id of the current program.
+    ///
+    /// Id of the created program is the same across runs and chains, as long
+    /// as the code, the creator and the nonce are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gstd::{prog::ProgramGenerator, CodeHash};
+    ///
+    /// unsafe extern "C" fn handle() {
+    ///     let code_hash = CodeHash::from([0; 32]);
+    ///     // Every deployment creates the registry with the same id.
+    ///     let registry = ProgramGenerator::create_program_with_nonce(code_hash, 1, b"INIT", 0)
+    ///         .expect("Unable to create the registry");
+    /// }
+    /// ```
+    pub fn create_program_with_nonce(
+        code_hash: CodeHash,
+        nonce: u64,
+        payload: impl AsRef<[u8]>,
+        value: u128,
+    ) -> Result<ActorId> {
+        let salt = Self::salt_from_nonce(exec::program_id(), nonce);
+        prog::create_program(code_hash, salt, payload, value)
+    }
+
+    /// Same as [`create_program_with_nonce`](Self::create_program_with_nonce),
+    /// but with explicit gas limit.
+    pub fn create_program_with_gas_and_nonce(
+        code_hash: CodeHash,
+        nonce: u64,
+        payload: impl AsRef<[u8]>,
+        gas_limit: u64,
+        value: u128,
+    ) -> Result<ActorId> {
+        let salt = Self::salt_from_nonce(exec::program_id(), nonce);
+        prog::create_program_with_gas(code_hash, salt, payload, gas_limit, value)
+    }
+
     pub fn create_program_with_gas(
         code_hash: CodeHash,
         payload: impl AsRef<[u8]>,
@@ -58,7 +140,7 @@ impl ProgramGenerator {
         payload: impl AsRef<[u8]>,
         gas_limit: u64,
         value: u128,
-        delay: u32,
+        delay: impl Into<Blocks>,
     ) -> Result<ActorId> {
         prog::create_program_with_gas_delayed(
             code_hash,
@@ -82,7 +164,7 @@ impl ProgramGenerator {
         code_hash: CodeHash,
         payload: impl AsRef<[u8]>,
         value: u128,
-        delay: u32,
+        delay: impl Into<Blocks>,
     ) -> Result<ActorId> {
         prog::create_program_delayed(code_hash, Self::get_salt(), payload, value, delay)
     }
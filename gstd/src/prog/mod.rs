@@ -18,20 +18,74 @@
 
 //! Program creation module.
 
+mod builder;
 mod generator;
 
-pub use generator::ProgramGenerator;
+pub use builder::ProgramBuilder;
+pub use generator::{ProgramGenerator, NONCE_SEED};
 
-use crate::{common::errors::Result, prelude::convert::AsRef, ActorId, CodeHash};
+use crate::{
+    async_runtime::signals,
+    common::errors::{ContractError, ExtError, MessageError, Result},
+    msg::CreateProgramFuture,
+    prelude::{convert::AsRef, Box, Vec},
+    time::Blocks,
+    ActorId, CodeHash, MessageId,
+};
 
+/// Collision with an existing program is reported with its own variant,
+/// so callers are able to handle it without matching the API error.
+fn creation_error(err: ExtError) -> ContractError {
+    match err {
+        ExtError::Message(MessageError::ProgramAlreadyExists { id }) => {
+            ContractError::ProgramAlreadyExists(id.into())
+        }
+        err => err.into(),
+    }
+}
+
+/// Program created by [`ProgramBuilder::create_with_init_id`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CreatedProgram {
+    /// Address of the new program.
+    pub program_id: ActorId,
+    /// Id of the init message, which the reply from the program's `init`
+    /// function refers to.
+    pub init_message_id: MessageId,
+}
+
+/// Create a new program and return its address.
+///
+/// The new program is initialized with `payload` and `value` sent by the
+/// init message. Non-zero `value` less than existential deposit results in
+/// [`MessageError::InsufficientValue`](crate::errors::MessageError::InsufficientValue).
+///
+/// Zero `value` is handled according to the existential deposit policy of
+/// the runtime. With the default `Exempt` policy the new program's account
+/// is kept without existential deposit. With the `TopUp` policy the init
+/// message carries existential deposit taken from the program's balance
+/// instead, failing with
+/// [`MessageError::NotEnoughValueForExistentialDeposit`](crate::errors::MessageError::NotEnoughValueForExistentialDeposit)
+/// if the program can't afford it.
+///
+/// Creating a program with the code and salt of an existing one fails with
+/// [`ContractError::ProgramAlreadyExists`] before the init message is sent.
+///
+/// Id of the init message is returned by
+/// [`ProgramBuilder::create_with_init_id`].
+///
+/// See [`gcore::prog::create_program_with_gas`] for examples.
 pub fn create_program(
     code_hash: CodeHash,
     salt: impl AsRef<[u8]>,
     payload: impl AsRef<[u8]>,
     value: u128,
 ) -> Result<ActorId> {
-    let id = gcore::prog::create_program(code_hash.into(), salt.as_ref(), payload.as_ref(), value)?;
-    Ok(id.into())
+    ProgramBuilder::new()
+        .salt(&salt)
+        .payload(&payload)
+        .value(value)
+        .create(code_hash)
 }
 
 /// Same as [`create_program`], but sends delayed.
@@ -40,16 +94,14 @@ pub fn create_program_delayed(
     salt: impl AsRef<[u8]>,
     payload: impl AsRef<[u8]>,
     value: u128,
-    delay: u32,
+    delay: impl Into<Blocks>,
 ) -> Result<ActorId> {
-    let id = gcore::prog::create_program_delayed(
-        code_hash.into(),
-        salt.as_ref(),
-        payload.as_ref(),
-        value,
-        delay,
-    )?;
-    Ok(id.into())
+    ProgramBuilder::new()
+        .salt(&salt)
+        .payload(&payload)
+        .value(value)
+        .delayed(delay)
+        .create(code_hash)
 }
 
 pub fn create_program_with_gas(
@@ -59,32 +111,122 @@ pub fn create_program_with_gas(
     gas_limit: u64,
     value: u128,
 ) -> Result<ActorId> {
-    let id = gcore::prog::create_program_with_gas(
+    ProgramBuilder::new()
+        .salt(&salt)
+        .payload(&payload)
+        .with_gas(gas_limit)
+        .value(value)
+        .create(code_hash)
+}
+
+/// Same as [`create_program_with_gas`], but sends delayed.
+pub fn create_program_with_gas_delayed(
+    code_hash: CodeHash,
+    salt: impl AsRef<[u8]>,
+    payload: impl AsRef<[u8]>,
+    gas_limit: u64,
+    value: u128,
+    delay: impl Into<Blocks>,
+) -> Result<ActorId> {
+    ProgramBuilder::new()
+        .salt(&salt)
+        .payload(&payload)
+        .with_gas(gas_limit)
+        .value(value)
+        .delayed(delay)
+        .create(code_hash)
+}
+
+/// Create programs with the same `code_hash` from the `entries` of salt,
+/// payload and value, returning their addresses in the order of entries.
+///
+/// Creation stops at the first failed entry with
+/// [`ContractError::BatchEntry`] carrying its index and error. Programs of
+/// the preceding entries are created anyway, so only the failed entry and
+/// the following ones need to be retried.
+pub fn create_program_batch<S, P>(
+    code_hash: CodeHash,
+    entries: impl IntoIterator<Item = (S, P, u128)>,
+) -> Result<Vec<ActorId>>
+where
+    S: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+{
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, (salt, payload, value))| {
+            create_program(code_hash, salt, payload, value)
+                .map_err(|err| ContractError::BatchEntry(index, Box::new(err)))
+        })
+        .collect()
+}
+
+/// Create a new program and wait for the reply to its init message.
+///
+/// The returned future resolves with the new program's address and the
+/// payload the program replied with from its `init` function. If the
+/// initialization fails, the future resolves with the error reply's exit code
+/// even when the program managed to reply before failing.
+///
+/// The program must reply from `init`, otherwise the future never resolves,
+/// see [`CreateProgramFuture`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{prog, CodeHash};
+///
+/// async fn deploy(code_hash: CodeHash) {
+///     let (program_id, config) = prog::create_program_for_reply(code_hash, b"salt", b"", 0)
+///         .expect("Failed to create program")
+///         .await
+///         .expect("Program initialization failed");
+/// }
+/// ```
+pub fn create_program_for_reply(
+    code_hash: CodeHash,
+    salt: impl AsRef<[u8]>,
+    payload: impl AsRef<[u8]>,
+    value: u128,
+) -> Result<CreateProgramFuture> {
+    let (waiting_reply_to, program_id) = gcore::prog::create_program_for_reply(
         code_hash.into(),
         salt.as_ref(),
         payload.as_ref(),
-        gas_limit,
         value,
-    )?;
-    Ok(id.into())
+    )
+    .map_err(creation_error)?;
+    let waiting_reply_to = waiting_reply_to.into();
+    signals().register_signal(waiting_reply_to);
+
+    Ok(CreateProgramFuture {
+        waiting_reply_to,
+        program_id: program_id.into(),
+    })
 }
 
-/// Same as [`create_program_with_gas`], but sends delayed.
-pub fn create_program_with_gas_delayed(
+/// Same as [`create_program_for_reply`], with gas limit.
+pub fn create_program_with_gas_for_reply(
     code_hash: CodeHash,
     salt: impl AsRef<[u8]>,
     payload: impl AsRef<[u8]>,
     gas_limit: u64,
     value: u128,
-    delay: u32,
-) -> Result<ActorId> {
-    let id = gcore::prog::create_program_with_gas_delayed(
+) -> Result<CreateProgramFuture> {
+    let (waiting_reply_to, program_id) = gcore::prog::create_program_with_gas_for_reply(
         code_hash.into(),
         salt.as_ref(),
         payload.as_ref(),
         gas_limit,
         value,
-        delay,
-    )?;
-    Ok(id.into())
+    )
+    .map_err(creation_error)?;
+    let waiting_reply_to = waiting_reply_to.into();
+    signals().register_signal(waiting_reply_to);
+
+    Ok(CreateProgramFuture {
+        waiting_reply_to,
+        program_id: program_id.into(),
+    })
 }
@@ -0,0 +1,184 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for durations counted in blocks and in milliseconds.
+//!
+//! Delays and wait durations are counted in blocks, while programs often
+//! reason about them in time. [`Blocks`] and [`Millis`] keep the units apart
+//! and convert between them using the block duration of the runtime, see
+//! [`exec::block_duration_millis`].
+//!
+//! Milliseconds are rounded up when converted into blocks by `From`, so a wait
+//! or delay is never shorter than requested. Budgets, which mustn't be exceeded,
+//! should be converted by [`Blocks::within`] rounding down instead.
+//!
+//! APIs accepting `impl Into<Blocks>` accept plain `u32` amounts of blocks too.
+//!
+//! # Examples
+//!
+//! ```
+//! use gstd::{exec, time::Millis};
+//!
+//! unsafe extern "C" fn handle() {
+//!     // Resume handling in a minute at the earliest.
+//!     exec::wait_for(Millis(60_000));
+//! }
+//! ```
+
+use crate::{
+    exec,
+    prelude::{Decode, Encode, TypeInfo},
+};
+use core::num::NonZeroU32;
+
+/// Duration in blocks.
+#[derive(
+    Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq, TypeInfo, Decode, Encode,
+)]
+pub struct Blocks(pub u32);
+
+/// Duration in milliseconds.
+#[derive(
+    Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq, TypeInfo, Decode, Encode,
+)]
+pub struct Millis(pub u64);
+
+impl Blocks {
+    /// Amount of blocks lasting at least `millis`, given the duration of a block.
+    ///
+    /// Saturates at `u32::MAX` blocks.
+    pub fn from_millis_ceil(millis: Millis, block_duration: u64) -> Self {
+        let block_duration = block_duration.max(1);
+        let blocks = millis.0 / block_duration + u64::from(millis.0 % block_duration != 0);
+
+        Self(blocks.try_into().unwrap_or(u32::MAX))
+    }
+
+    /// Amount of blocks lasting no longer than `millis`, given the duration of a block.
+    ///
+    /// Saturates at `u32::MAX` blocks.
+    pub fn from_millis_floor(millis: Millis, block_duration: u64) -> Self {
+        let blocks = millis.0 / block_duration.max(1);
+
+        Self(blocks.try_into().unwrap_or(u32::MAX))
+    }
+
+    /// Amount of blocks fitting into the `millis` budget in the current runtime.
+    pub fn within(millis: Millis) -> Self {
+        Self::from_millis_floor(millis, exec::block_duration_millis())
+    }
+
+    /// Delay of a message or wake by this amount of blocks.
+    ///
+    /// Zero blocks delay nothing, so the message is enqueued at once.
+    pub fn into_delay(self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.0)
+    }
+}
+
+impl Millis {
+    /// Duration of the `blocks`, given the duration of a block.
+    pub fn from_blocks(blocks: Blocks, block_duration: u64) -> Self {
+        Self(u64::from(blocks.0).saturating_mul(block_duration))
+    }
+}
+
+impl From<u32> for Blocks {
+    fn from(blocks: u32) -> Self {
+        Self(blocks)
+    }
+}
+
+impl From<Blocks> for u32 {
+    fn from(blocks: Blocks) -> Self {
+        blocks.0
+    }
+}
+
+impl From<u64> for Millis {
+    fn from(millis: u64) -> Self {
+        Self(millis)
+    }
+}
+
+impl From<Millis> for u64 {
+    fn from(millis: Millis) -> Self {
+        millis.0
+    }
+}
+
+/// Rounds up to whole blocks in the current runtime.
+impl From<Millis> for Blocks {
+    fn from(millis: Millis) -> Self {
+        Self::from_millis_ceil(millis, exec::block_duration_millis())
+    }
+}
+
+impl From<Blocks> for Millis {
+    fn from(blocks: Blocks) -> Self {
+        Self::from_blocks(blocks, exec::block_duration_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK_DURATION: u64 = 3_000;
+
+    #[test]
+    fn waits_rounded_up() {
+        let ceil = |millis| Blocks::from_millis_ceil(Millis(millis), BLOCK_DURATION);
+
+        assert_eq!(ceil(0), Blocks(0));
+        assert_eq!(ceil(1), Blocks(1));
+        assert_eq!(ceil(3_000), Blocks(1));
+        assert_eq!(ceil(3_001), Blocks(2));
+        assert_eq!(ceil(u64::MAX), Blocks(u32::MAX));
+    }
+
+    #[test]
+    fn budgets_rounded_down() {
+        let floor = |millis| Blocks::from_millis_floor(Millis(millis), BLOCK_DURATION);
+
+        assert_eq!(floor(2_999), Blocks(0));
+        assert_eq!(floor(3_000), Blocks(1));
+        assert_eq!(floor(5_999), Blocks(1));
+        assert_eq!(floor(u64::MAX), Blocks(u32::MAX));
+    }
+
+    #[test]
+    fn blocks_converted_back_exactly() {
+        for blocks in [0, 1, 7, u32::MAX] {
+            let millis = Millis::from_blocks(Blocks(blocks), BLOCK_DURATION);
+
+            assert_eq!(millis, Millis(u64::from(blocks) * BLOCK_DURATION));
+            assert_eq!(
+                Blocks::from_millis_ceil(millis, BLOCK_DURATION),
+                Blocks::from_millis_floor(millis, BLOCK_DURATION)
+            );
+        }
+    }
+
+    #[test]
+    fn zero_blocks_delay_nothing() {
+        assert_eq!(Blocks(0).into_delay(), None);
+        assert_eq!(Blocks(1).into_delay(), NonZeroU32::new(1));
+        assert_eq!(Blocks(u32::MAX).into_delay(), NonZeroU32::new(u32::MAX));
+    }
+}
@@ -0,0 +1,186 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Module for timers resuming async message handling after some blocks.
+//!
+//! [`Timer`] is a future resolving with the state it was created with once
+//! the given amount of blocks has passed. While the message waits for
+//! the timer, [`message_loop`](crate::message_loop) puts it to the waitlist
+//! no longer than until the nearest timer fires.
+//!
+//! Timer can be cancelled by [`cancel`] from any message of the program,
+//! in which case it resolves immediately without the state.
+
+use crate::{
+    exec, msg,
+    prelude::{BTreeMap, Decode, Encode, TypeInfo},
+    time::Blocks,
+    MessageId,
+};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Timer identifier.
+#[derive(
+    Clone, Copy, Debug, Default, Hash, Ord, PartialEq, PartialOrd, Eq, TypeInfo, Decode, Encode,
+)]
+pub struct TimerId(u64);
+
+struct Entry {
+    message_id: MessageId,
+    fire_at: u32,
+    cancelled: bool,
+}
+
+static mut NONCE: u64 = 0;
+
+static mut TIMERS: Option<BTreeMap<TimerId, Entry>> = None;
+
+fn timers() -> &'static mut BTreeMap<TimerId, Entry> {
+    unsafe { TIMERS.get_or_insert_with(BTreeMap::new) }
+}
+
+/// Future resolving with `state` after the given amount of blocks.
+///
+/// Resolves to `None` if the timer was cancelled.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, timer::Timer};
+///
+/// async fn heartbeat() {
+///     let timer = Timer::new(100, msg::source());
+///     // Timer id may be stored to cancel the timer from another message.
+///     let _timer_id = timer.id();
+///
+///     if let Some(source) = timer.await {
+///         msg::send_bytes(source, b"Heartbeat", 0).unwrap();
+///     }
+/// }
+/// ```
+pub struct Timer<T> {
+    id: TimerId,
+    state: Option<T>,
+}
+
+impl<T> Timer<T> {
+    /// Start the timer firing after `duration` within the current message.
+    ///
+    /// Durations given in [`Millis`](crate::time::Millis) are rounded up
+    /// to whole blocks.
+    pub fn new(duration: impl Into<Blocks>, state: T) -> Self {
+        let id = unsafe {
+            NONCE = NONCE.wrapping_add(1);
+            TimerId(NONCE)
+        };
+
+        timers().insert(
+            id,
+            Entry {
+                message_id: msg::id(),
+                fire_at: exec::block_height().saturating_add(duration.into().0),
+                cancelled: false,
+            },
+        );
+
+        Self {
+            id,
+            state: Some(state),
+        }
+    }
+
+    /// Identifier of the timer.
+    pub fn id(&self) -> TimerId {
+        self.id
+    }
+}
+
+impl<T: Unpin> Future for Timer<T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let entry = timers()
+            .get(&self.id)
+            .expect("Timer is removed only after resolving");
+
+        if entry.cancelled {
+            timers().remove(&self.id);
+            Poll::Ready(None)
+        } else if exec::block_height() >= entry.fire_at {
+            timers().remove(&self.id);
+            Poll::Ready(self.state.take())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for Timer<T> {
+    fn drop(&mut self) {
+        timers().remove(&self.id);
+    }
+}
+
+/// Future resolving after the given `duration`.
+///
+/// # Examples
+///
+/// ```
+/// use gstd::{msg, time::Millis, timer};
+///
+/// async fn delayed_ping() {
+///     timer::sleep_for(Millis(30_000)).await;
+///     msg::send_bytes(msg::source(), b"PING", 0).unwrap();
+/// }
+/// ```
+pub fn sleep_for(duration: impl Into<Blocks>) -> Timer<()> {
+    Timer::new(duration, ())
+}
+
+/// Cancel the timer, waking the message waiting for it.
+///
+/// Returns `false` if the timer has already fired or been cancelled.
+pub fn cancel(timer_id: TimerId) -> bool {
+    match timers().get_mut(&timer_id) {
+        Some(entry) if !entry.cancelled => {
+            entry.cancelled = true;
+
+            if entry.message_id != msg::id() {
+                exec::wake(entry.message_id);
+            }
+
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Amount of blocks until the nearest timer of the message fires.
+pub(crate) fn blocks_until_fire(message_id: MessageId) -> Option<u32> {
+    let now = exec::block_height();
+
+    timers()
+        .values()
+        .filter(|entry| entry.message_id == message_id && !entry.cancelled)
+        .map(|entry| entry.fire_at.saturating_sub(now).max(1))
+        .min()
+}
@@ -25,11 +25,14 @@ mod system;
 mod wasm_executor;
 
 pub use error::{Result, TestError};
-pub use log::{CoreLog, Log, RunResult};
+pub use log::{CoreLog, JournalEntry, Log, RunResult, SimulationResult};
 pub use program::{calculate_program_id, Gas, Program, WasmProgram};
-pub use system::System;
+pub use system::{Backend, RawDispatch, Snapshot, System};
 
 pub const EXISTENTIAL_DEPOSIT: u128 = 500;
 pub const MAILBOX_THRESHOLD: u64 = 3000;
 pub const WAITLIST_COST: u64 = 100;
 pub const RESERVE_FOR: u32 = 1;
+pub const BLOCK_DURATION: u64 = 1000;
+pub const BLOCK_HASH_DEPTH: u32 = 64;
+pub const STACK_HEIGHT: u32 = 512;
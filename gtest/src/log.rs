@@ -16,13 +16,19 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::program::{Gas, ProgramIdWrapper};
+use crate::{
+    program::{Gas, ProgramIdWrapper},
+    system::Backend,
+};
+use blake2_rfc::blake2b;
 use codec::{Codec, Encode};
+use core_processor::common::{DispatchOutcome, JournalNote};
 use gear_core::{
     ids::{MessageId, ProgramId},
-    message::{ExitCode, Payload, StoredMessage},
+    memory::{PageBuf, PageNumber},
+    message::{Dispatch, ExitCode, Payload, ReplyCode, StoredMessage},
 };
-use std::{convert::TryInto, fmt::Debug};
+use std::{collections::BTreeMap, convert::TryInto, fmt::Debug};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CoreLog {
@@ -227,7 +233,8 @@ impl PartialEq<CoreLog> for Log {
             }
         }
 
-        if self.exit_code == 0 {
+        // Payloads of system error replies aren't compared.
+        if !ReplyCode::from(self.exit_code).is_system_error() {
             if let Some(payload) = &self.payload {
                 if payload.get() != other.payload.get() {
                     return false;
@@ -245,6 +252,123 @@ impl PartialEq<Log> for CoreLog {
     }
 }
 
+/// Entry of the execution journal, recorded in order of handling.
+///
+/// Payloads and pages are recorded by hashes to keep the journal small.
+#[derive(Clone, Debug, PartialEq, Eq, Encode)]
+pub enum JournalEntry {
+    Dispatched {
+        message_id: MessageId,
+        failed: bool,
+    },
+    GasBurned {
+        message_id: MessageId,
+        amount: u64,
+    },
+    Sent {
+        message_id: MessageId,
+        source: ProgramId,
+        destination: ProgramId,
+        payload_hash: [u8; 32],
+        value: u128,
+    },
+    PagesUpdated {
+        program_id: ProgramId,
+        pages: Vec<(u32, [u8; 32])>,
+    },
+}
+
+impl JournalEntry {
+    pub(crate) fn dispatched(message_id: MessageId, outcome: &DispatchOutcome) -> Self {
+        Self::Dispatched {
+            message_id,
+            failed: matches!(
+                outcome,
+                DispatchOutcome::MessageTrap { .. } | DispatchOutcome::InitFailure { .. }
+            ),
+        }
+    }
+
+    pub(crate) fn sent(dispatch: &Dispatch) -> Self {
+        Self::Sent {
+            message_id: dispatch.id(),
+            source: dispatch.source(),
+            destination: dispatch.destination(),
+            payload_hash: hash(dispatch.payload()),
+            value: dispatch.value(),
+        }
+    }
+
+    pub(crate) fn pages_updated(
+        program_id: ProgramId,
+        pages_data: &BTreeMap<PageNumber, PageBuf>,
+    ) -> Self {
+        Self::PagesUpdated {
+            program_id,
+            pages: pages_data
+                .iter()
+                .map(|(page, data)| (page.0, hash(data.as_slice())))
+                .collect(),
+        }
+    }
+
+    /// Entries of the `journal`, which isn't handled.
+    ///
+    /// Pages are grouped by programs after the other entries, the same
+    /// way [`core_processor::handle_journal`] passes them to the handler.
+    pub(crate) fn from_notes(journal: &[JournalNote]) -> Vec<Self> {
+        let mut entries = Vec::new();
+        let mut page_updates: BTreeMap<ProgramId, BTreeMap<PageNumber, PageBuf>> = BTreeMap::new();
+
+        for note in journal {
+            match note {
+                JournalNote::MessageDispatched {
+                    message_id,
+                    outcome,
+                    ..
+                } => entries.push(Self::dispatched(*message_id, outcome)),
+                JournalNote::GasBurned { message_id, amount } => entries.push(Self::GasBurned {
+                    message_id: *message_id,
+                    amount: *amount,
+                }),
+                JournalNote::SendDispatch { dispatch, .. }
+                | JournalNote::SendDispatchChargeRecipient { dispatch, .. } => {
+                    entries.push(Self::sent(dispatch))
+                }
+                JournalNote::UpdatePage {
+                    program_id,
+                    page_number,
+                    data,
+                } => {
+                    page_updates
+                        .entry(*program_id)
+                        .or_default()
+                        .insert(*page_number, data.clone());
+                }
+                _ => {}
+            }
+        }
+
+        entries.extend(
+            page_updates
+                .iter()
+                .map(|(program_id, pages_data)| Self::pages_updated(*program_id, pages_data)),
+        );
+
+        entries
+    }
+}
+
+pub(crate) fn hash(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(blake2b::blake2b(32, &[], data).as_bytes());
+    hash
+}
+
+pub(crate) fn journal_hash(journal: &[JournalEntry]) -> [u8; 32] {
+    hash(&journal.encode())
+}
+
 #[derive(Debug)]
 pub struct RunResult {
     pub(crate) log: Vec<CoreLog>,
@@ -254,6 +378,10 @@ pub struct RunResult {
     pub(crate) total_processed: u32,
     pub(crate) main_gas_burned: Gas,
     pub(crate) others_gas_burned: Gas,
+    pub(crate) corpus_written: usize,
+    pub(crate) pages_persisted: usize,
+    pub(crate) journal: Vec<JournalEntry>,
+    pub(crate) backend: Backend,
 }
 
 impl RunResult {
@@ -291,6 +419,44 @@ impl RunResult {
         self.others_gas_burned
     }
 
+    /// Amount of new corpus entries written during the run.
+    ///
+    /// Always zero unless [`System::record_corpus`](crate::System::record_corpus) is called.
+    pub fn corpus_written(&self) -> usize {
+        self.corpus_written
+    }
+
+    /// Amount of changed memory pages persisted during the run.
+    ///
+    /// Execution changing more pages than allowed by
+    /// [`AllocationsConfig::max_pages_persisted`](core_processor::configs::AllocationsConfig::max_pages_persisted)
+    /// fails, persisting none of them.
+    pub fn pages_persisted(&self) -> usize {
+        self.pages_persisted
+    }
+
+    /// Journal of the run: dispatch outcomes, burned gas, sent messages
+    /// and updated pages in order they were handled.
+    ///
+    /// Always empty unless [`System::record_journal`](crate::System::record_journal)
+    /// is called.
+    pub fn journal(&self) -> &[JournalEntry] {
+        &self.journal
+    }
+
+    /// Stable hash of the [`journal`](Self::journal).
+    ///
+    /// Equal for runs executed the same way, so it's comparable
+    /// across test runs and machines.
+    pub fn journal_hash(&self) -> [u8; 32] {
+        journal_hash(&self.journal)
+    }
+
+    /// Backend the run was executed with.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
     pub fn decoded_log<T: Codec + Debug>(&self) -> Vec<DecodedCoreLog<T>> {
         self.log
             .clone()
@@ -300,6 +466,42 @@ impl RunResult {
     }
 }
 
+/// Result of the message execution, which wasn't committed.
+///
+/// See [`Program::calculate_gas_for`](crate::Program::calculate_gas_for).
+#[derive(Debug)]
+pub struct SimulationResult {
+    pub(crate) gas_burned: Gas,
+    pub(crate) pages_persisted: usize,
+    pub(crate) journal: Vec<JournalEntry>,
+}
+
+impl SimulationResult {
+    /// Gas burned by the message.
+    pub fn gas_burned(&self) -> Gas {
+        self.gas_burned
+    }
+
+    /// Amount of changed memory pages, which would be persisted.
+    pub fn pages_persisted(&self) -> usize {
+        self.pages_persisted
+    }
+
+    /// Journal of the execution.
+    ///
+    /// Contains entries of the message only, so it equals to the
+    /// [`RunResult::journal`] of sending the message unless other programs
+    /// execute messages sent by it.
+    pub fn journal(&self) -> &[JournalEntry] {
+        &self.journal
+    }
+
+    /// Stable hash of the [`journal`](Self::journal).
+    pub fn journal_hash(&self) -> [u8; 32] {
+        journal_hash(&self.journal)
+    }
+}
+
 #[test]
 fn soft_into() {
     let log: Log = (1, "payload").into();
@@ -17,29 +17,44 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    log::{CoreLog, RunResult},
+    log::{CoreLog, JournalEntry, RunResult, SimulationResult},
     program::{Gas, WasmProgram},
+    system::{Backend, RawDispatch},
     wasm_executor::WasmExecutor,
-    Result, TestError, EXISTENTIAL_DEPOSIT, MAILBOX_THRESHOLD, RESERVE_FOR, WAITLIST_COST,
+    Result, TestError, BLOCK_DURATION, BLOCK_HASH_DEPTH, EXISTENTIAL_DEPOSIT, MAILBOX_THRESHOLD,
+    RESERVE_FOR, STACK_HEIGHT, WAITLIST_COST,
 };
+use blake2_rfc::blake2b;
+use codec::Encode;
 use core_processor::{
     common::*,
-    configs::{BlockConfig, BlockInfo, MessageExecutionContext},
+    configs::{BlockConfig, BlockInfo, ExistentialDepositPolicy, MessageExecutionContext},
     Ext, PrepareResult,
 };
+use gear_backend_sandbox::SandboxEnvironment;
 use gear_backend_wasmi::WasmiEnvironment;
 use gear_core::{
     code::{Code, CodeAndId, InstrumentedCodeAndId},
-    ids::{CodeId, MessageId, ProgramId},
+    counters::CounterKey,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber, WasmPageNumber},
     message::{
-        Dispatch, DispatchKind, Payload, ReplyMessage, ReplyPacket, StoredDispatch, StoredMessage,
+        Dispatch, DispatchKind, Message, Payload, ReplyMessage, ReplyPacket, SignalCode,
+        SignalMessage, StoredDispatch, StoredMessage,
     },
     program::Program as CoreProgram,
 };
+use gtest_corpus::{Entry, EntryKind};
+use sp_io::TestExternalities;
 use std::{
+    cell::{Cell, RefCell},
     collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     convert::TryInto,
+    fs,
+    marker::PhantomData,
+    num::NonZeroU32,
+    path::PathBuf,
+    ptr,
     time::{SystemTime, UNIX_EPOCH},
 };
 use wasm_instrument::gas_metering::ConstantCostRules;
@@ -48,7 +63,7 @@ const OUTGOING_LIMIT: u32 = 1024;
 
 pub(crate) type Balance = u128;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) enum TestActor {
     Initialized(Program),
     // Contract: program is always `Some`, option is used to take ownership
@@ -140,6 +155,8 @@ impl TestActor {
             ExecutableActorData {
                 program,
                 pages_with_data: pages_data.keys().copied().collect(),
+                value_reservations: Default::default(),
+                children_count: Default::default(),
             },
             pages_data,
         ))
@@ -175,14 +192,34 @@ impl Program {
     }
 }
 
-#[derive(Default, Debug)]
+// Mock programs keep their state to themselves, so there's no way to copy it.
+impl Clone for Program {
+    fn clone(&self) -> Self {
+        match self {
+            Program::Genuine {
+                program,
+                code_id,
+                pages_data,
+            } => Program::Genuine {
+                program: program.clone(),
+                code_id: *code_id,
+                pages_data: pages_data.clone(),
+            },
+            Program::Mock(_) => panic!("Mock programs can't be copied into the snapshot"),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
 pub(crate) struct ExtManager {
     // State metadata
     pub(crate) block_info: BlockInfo,
+    pub(crate) backend: Backend,
 
     // Messaging and programs meta
     pub(crate) msg_nonce: u64,
     pub(crate) id_nonce: u64,
+    pub(crate) execution_nonce: u64,
 
     // State
     pub(crate) actors: BTreeMap<ProgramId, (TestActor, Balance)>,
@@ -190,9 +227,34 @@ pub(crate) struct ExtManager {
     pub(crate) meta_binaries: BTreeMap<CodeId, Vec<u8>>,
     pub(crate) dispatches: VecDeque<StoredDispatch>,
     pub(crate) mailbox: HashMap<ProgramId, Vec<StoredMessage>>,
-    pub(crate) wait_list: BTreeMap<(ProgramId, MessageId), StoredDispatch>,
+    /// Waiting dispatches along with blocks they were put in the waitlist
+    /// at and expected to be removed at.
+    pub(crate) wait_list: BTreeMap<(ProgramId, MessageId), (StoredDispatch, u32, u32)>,
     pub(crate) wait_init_list: BTreeMap<ProgramId, Vec<MessageId>>,
     pub(crate) gas_limits: BTreeMap<MessageId, Option<u64>>,
+    pub(crate) value_reservations: BTreeMap<ProgramId, BTreeMap<ReservationId, u128>>,
+    pub(crate) children_count: BTreeMap<ProgramId, u64>,
+    pub(crate) children_gas_burned: BTreeMap<ProgramId, u64>,
+    pub(crate) counters: BTreeMap<ProgramId, BTreeMap<CounterKey, u64>>,
+    pub(crate) entry_budgets: BTreeMap<ProgramId, BTreeMap<DispatchKind, u64>>,
+    /// Gas the programs agree to pay for recipient charged messages.
+    pub(crate) charge_allowances: BTreeMap<ProgramId, u64>,
+    /// Messages waiting for replies to the messages sent by the programs.
+    pub(crate) reply_wakers: BTreeMap<(ProgramId, MessageId), MessageId>,
+    /// Blocks the programs scheduled their exit at along with inheritors.
+    pub(crate) scheduled_exits: BTreeMap<ProgramId, (u32, ProgramId)>,
+
+    // Corpus
+    pub(crate) corpus_dir: Option<PathBuf>,
+
+    /// Whether messages may be sent with the origin other than their source.
+    pub(crate) origin_override_allowed: bool,
+
+    // Journal of the runs, recorded only if enabled
+    pub(crate) journal: Option<Vec<JournalEntry>>,
+    // Notes of the executions, in canonical form compared across nodes,
+    // collected only while the determinism is checked
+    pub(crate) notes: Option<Journal>,
 
     // Last run info
     pub(crate) origin: ProgramId,
@@ -202,6 +264,136 @@ pub(crate) struct ExtManager {
     pub(crate) others_failed: bool,
     pub(crate) main_gas_burned: Gas,
     pub(crate) others_gas_burned: Gas,
+    pub(crate) corpus_written: usize,
+    pub(crate) pages_persisted: usize,
+}
+
+thread_local! {
+    /// Manager processing the dispatch.
+    ///
+    /// The processor looks programs up through plain functions, so the
+    /// manager is published here for [`program_exists`], [`code_id_of`],
+    /// [`mailbox_contains`] and [`entry_budget`] by [`PublishedManager`] for
+    /// the time the dispatch is processed.
+    static MANAGER: Cell<*const ExtManager> = Cell::new(ptr::null());
+
+    /// Number of known codes, published for [`codes_count`] the same way.
+    static CODES_COUNT: Cell<u64> = Cell::new(0);
+
+    /// Message waiting for the processed reply, published for
+    /// [`reply_waker`] along with the program and the replied message.
+    static REPLY_WAKER: Cell<Option<(ProgramId, MessageId, MessageId)>> = Cell::new(None);
+
+    /// Counters of the destination program, published for
+    /// [`program_counters`] the same way.
+    static PROGRAM_COUNTERS: RefCell<(ProgramId, BTreeMap<CounterKey, u64>)> =
+        RefCell::new(Default::default());
+
+    /// Gas burned by children of the destination program, published for
+    /// [`children_gas_burned`] the same way.
+    static CHILDREN_GAS_BURNED: Cell<(ProgramId, u64)> = Cell::new(Default::default());
+
+    /// Externalities the sandbox is provided within, created once and
+    /// reused by all the dispatches executed with [`Backend::SandboxCompiled`].
+    static SANDBOX_EXTERNALITIES: RefCell<TestExternalities> =
+        RefCell::new(TestExternalities::default());
+}
+
+/// Publishes the manager in [`MANAGER`] until dropped, borrowing it
+/// for the time.
+struct PublishedManager<'a>(PhantomData<&'a ExtManager>);
+
+impl<'a> PublishedManager<'a> {
+    fn new(manager: &'a ExtManager) -> Self {
+        MANAGER.with(|published| published.set(manager));
+        Self(PhantomData)
+    }
+}
+
+impl Drop for PublishedManager<'_> {
+    fn drop(&mut self) {
+        MANAGER.with(|published| published.set(ptr::null()));
+    }
+}
+
+fn with_manager<R>(f: impl FnOnce(&ExtManager) -> R) -> R {
+    let manager = MANAGER.with(Cell::get);
+    assert!(!manager.is_null(), "Lookup outside of dispatch processing");
+
+    // Safety: the manager is published only while it's borrowed for
+    // processing the dispatch and unpublished before the borrow ends.
+    f(unsafe { &*manager })
+}
+
+fn program_exists(id: ProgramId) -> bool {
+    with_manager(|manager| manager.actors.contains_key(&id))
+}
+
+fn code_id_of(id: ProgramId) -> Option<CodeId> {
+    with_manager(|manager| {
+        manager
+            .actors
+            .get(&id)
+            .and_then(|(actor, _)| actor.code_id())
+    })
+}
+
+fn entry_budget(program_id: ProgramId, kind: DispatchKind) -> Option<u64> {
+    with_manager(|manager| {
+        manager
+            .entry_budgets
+            .get(&program_id)
+            .and_then(|budgets| budgets.get(&kind))
+            .copied()
+    })
+}
+
+fn mailbox_contains(program_id: ProgramId, user_id: ProgramId, message_id: MessageId) -> bool {
+    with_manager(|manager| {
+        manager.mailbox.get(&user_id).map_or(false, |messages| {
+            messages
+                .iter()
+                .any(|message| message.id() == message_id && message.source() == program_id)
+        })
+    })
+}
+
+fn codes_count() -> u64 {
+    CODES_COUNT.with(Cell::get)
+}
+
+fn program_counters(program_id: ProgramId) -> BTreeMap<CounterKey, u64> {
+    PROGRAM_COUNTERS.with(|counters| {
+        let (id, counters) = &*counters.borrow();
+        if *id == program_id {
+            counters.clone()
+        } else {
+            Default::default()
+        }
+    })
+}
+
+fn children_gas_burned(program_id: ProgramId) -> u64 {
+    let (id, gas_burned) = CHILDREN_GAS_BURNED.with(Cell::get);
+    if id == program_id {
+        gas_burned
+    } else {
+        0
+    }
+}
+
+fn reply_waker(program_id: ProgramId, reply_to: MessageId) -> Option<MessageId> {
+    REPLY_WAKER
+        .with(Cell::get)
+        .filter(|&(id, replied, _)| id == program_id && replied == reply_to)
+        .map(|(_, _, waiting)| waiting)
+}
+
+/// Fake hash of the block at `height`, derived from the height.
+pub(crate) fn block_hash(height: u32) -> [u8; 32] {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(blake2b::blake2b(32, &[], &height.to_le_bytes()).as_bytes());
+    hash
 }
 
 impl ExtManager {
@@ -245,6 +437,12 @@ impl ExtManager {
         nonce
     }
 
+    pub(crate) fn fetch_inc_execution_nonce(&mut self) -> u64 {
+        let nonce = self.execution_nonce;
+        self.execution_nonce += 1;
+        nonce
+    }
+
     pub(crate) fn free_id_nonce(&mut self) -> u64 {
         while self.actors.contains_key(&self.id_nonce.into())
             || self.mailbox.contains_key(&self.id_nonce.into())
@@ -289,8 +487,27 @@ impl ExtManager {
     }
 
     pub(crate) fn run_dispatch(&mut self, dispatch: Dispatch) -> RunResult {
+        let origin = dispatch.source();
+        self.run_dispatch_as(dispatch, origin)
+    }
+
+    /// Run the dispatch on behalf of the `origin` user.
+    ///
+    /// The origin is kept for every message sent while the dispatch is
+    /// handled, as the pallet does.
+    pub(crate) fn run_dispatch_as(&mut self, dispatch: Dispatch, origin: ProgramId) -> RunResult {
+        if origin != dispatch.source() && !self.origin_override_allowed {
+            panic!(
+                "Origin ({}) differs from source ({}), while origin override isn't allowed",
+                origin,
+                dispatch.source()
+            );
+        }
+
         self.validate_dispatch(&dispatch);
-        self.prepare_for(&dispatch);
+        self.prepare_for(dispatch.id(), origin);
+
+        let journal_start = self.journal_len();
 
         self.gas_limits.insert(dispatch.id(), dispatch.gas_limit());
 
@@ -307,6 +524,146 @@ impl ExtManager {
             self.log.push(message)
         }
 
+        self.run_queue(journal_start)
+    }
+
+    /// Run the raw dispatch without validating it, see [`RawDispatch`].
+    pub(crate) fn inject_raw(&mut self, raw: RawDispatch) -> RunResult {
+        let RawDispatch {
+            kind,
+            source,
+            destination,
+            payload,
+            value,
+            details,
+        } = raw;
+
+        if self.is_user(&destination) {
+            panic!("Raw dispatches can be injected only to programs, not to {destination}");
+        }
+
+        // Programs pay the value from their balance, so it's minted to them first.
+        if value != 0 && !self.is_user(&source) {
+            self.mint_to(&source, value);
+        }
+
+        let nonce = self.fetch_inc_message_nonce();
+        let message = Message::new(
+            MessageId::generate_from_user(self.block_info.height, source, nonce as u128),
+            source,
+            destination,
+            payload.try_into().expect("Payload is too large"),
+            Some(u64::MAX),
+            value,
+            details,
+        );
+
+        self.prepare_for(message.id(), source);
+        let journal_start = self.journal_len();
+
+        self.gas_limits.insert(message.id(), message.gas_limit());
+        self.dispatches
+            .push_back(Dispatch::new(kind, message).into_stored());
+
+        self.run_queue(journal_start)
+    }
+
+    /// Exit programs, which scheduled their exit no later than the current block.
+    pub(crate) fn exit_scheduled(&mut self) {
+        let height = self.block_info.height;
+
+        let due: Vec<_> = self
+            .scheduled_exits
+            .iter()
+            .filter(|(_, (block, _))| *block <= height)
+            .map(|(program_id, (_, inheritor))| (*program_id, *inheritor))
+            .collect();
+
+        for (program_id, inheritor) in due {
+            self.exit_dispatch(program_id, inheritor);
+        }
+    }
+
+    /// Remove messages, whose wait budget is exhausted, from the waitlist.
+    ///
+    /// Mirrors the on-chain scheduler: the rent is charged till the expected
+    /// block and the program gets a signal about removal, while the source
+    /// of the message gets an error reply.
+    pub(crate) fn remove_expired_from_waitlist(&mut self) -> Vec<RunResult> {
+        let height = self.block_info.height;
+
+        let mut expired: Vec<_> = self
+            .wait_list
+            .iter()
+            .filter(|(_, (_, _, expected))| *expected <= height)
+            .map(|(key, (_, _, expected))| (*expected, *key))
+            .collect();
+        expired.sort();
+
+        expired
+            .into_iter()
+            .map(|(_, (program_id, message_id))| self.remove_from_waitlist(program_id, message_id))
+            .collect()
+    }
+
+    /// Forget the reply the message leaving the waitlist waited for.
+    fn forget_reply_waker(&mut self, program_id: ProgramId, message_id: MessageId) {
+        self.reply_wakers
+            .retain(|(id, _), waiting| *id != program_id || *waiting != message_id);
+    }
+
+    fn remove_from_waitlist(&mut self, program_id: ProgramId, message_id: MessageId) -> RunResult {
+        let (dispatch, start, expected) = self
+            .wait_list
+            .remove(&(program_id, message_id))
+            .expect("Can't fail");
+        self.forget_reply_waker(program_id, message_id);
+
+        self.prepare_for(message_id, dispatch.source());
+        let journal_start = self.journal_len();
+
+        self.charge_for_wait(message_id, start, expected);
+
+        if self.exports_signal(&program_id) {
+            let code = if self.wait_budget(message_id) == 0 {
+                SignalCode::OutOfGasInWaitlist
+            } else {
+                SignalCode::RemovedFromWaitlist
+            };
+
+            // Signal is paid with the gas left.
+            let signal = SignalMessage::with_code(message_id, code)
+                .into_dispatch(program_id)
+                .into_stored();
+            let gas_limit = self.gas_limits.get(&message_id).copied().flatten();
+            self.gas_limits.insert(signal.id(), gas_limit);
+            self.dispatches.push_back(signal);
+        }
+
+        // Converted to the string one, if the source is a user.
+        let trap = ExecutionErrorReason::OutOfRent
+            .encode()
+            .try_into()
+            .expect("Error message is too large");
+        let trap_reply = ReplyMessage::system(message_id, trap, core_processor::ERR_EXIT_CODE)
+            .into_dispatch(program_id, dispatch.reply_destination(), message_id);
+        self.send_dispatch(message_id, trap_reply, None);
+
+        self.run_queue(journal_start)
+    }
+
+    fn journal_len(&self) -> usize {
+        self.journal.as_ref().map_or(0, Vec::len)
+    }
+
+    // Entries are built only if the journal is recorded.
+    fn record(&mut self, entry: impl FnOnce() -> JournalEntry) {
+        if let Some(journal) = &mut self.journal {
+            journal.push(entry());
+        }
+    }
+
+    fn run_queue(&mut self, journal_start: usize) -> RunResult {
         let mut total_processed = 0;
         while let Some(dispatch) = self.dispatches.pop_front() {
             let message_id = dispatch.id();
@@ -322,15 +679,21 @@ impl ExtManager {
                 continue;
             }
 
-            let (actor, balance) = self
-                .actors
-                .get_mut(&dest)
-                .expect("Somehow message queue contains message for user");
+            if !self.actors.contains_key(&dest) {
+                // The program has exited after the message was queued.
+                self.process_dormant(0, dispatch);
+                total_processed += 1;
+                continue;
+            }
+
+            let (actor, balance) = self.actors.get_mut(&dest).expect("Can't fail");
             let balance = *balance;
 
             if actor.is_dormant() {
                 self.process_dormant(balance, dispatch);
-            } else if let Some((data, memory_pages)) = actor.get_executable_actor_data() {
+            } else if let Some((mut data, memory_pages)) = actor.get_executable_actor_data() {
+                self.fill_actor_state(dest, &mut data);
+                self.record_corpus_entry(&dispatch);
                 self.process_normal(balance, data, memory_pages, dispatch);
             } else if let Some(mock) = actor.take_mock() {
                 self.process_mock(mock, dispatch);
@@ -351,6 +714,102 @@ impl ExtManager {
             total_processed,
             main_gas_burned: self.main_gas_burned,
             others_gas_burned: self.others_gas_burned,
+            corpus_written: self.corpus_written,
+            pages_persisted: self.pages_persisted,
+            journal: self
+                .journal
+                .as_ref()
+                .map(|journal| journal[journal_start..].to_vec())
+                .unwrap_or_default(),
+            backend: self.backend,
+        }
+    }
+
+    // Fills the state of the program kept by the manager apart from the actor.
+    fn fill_actor_state(&self, program_id: ProgramId, data: &mut ExecutableActorData) {
+        data.value_reservations = self
+            .value_reservations
+            .get(&program_id)
+            .cloned()
+            .unwrap_or_default();
+        data.children_count = self
+            .children_count
+            .get(&program_id)
+            .copied()
+            .unwrap_or_default();
+    }
+
+    /// Execute the dispatch against the current state without committing
+    /// its results.
+    ///
+    /// Only the dispatch itself is executed, messages sent by it aren't.
+    pub(crate) fn calculate_gas_for(&self, dispatch: Dispatch) -> Result<SimulationResult> {
+        let dest = dispatch.destination();
+        let (actor, balance) = self
+            .actors
+            .get(&dest)
+            .ok_or(TestError::ActorNotFound(dest))?;
+
+        // The message would wait for initialization of the program.
+        if matches!(actor, TestActor::Uninitialized(Some(init_id), _) if *init_id != dispatch.id())
+        {
+            return Err(TestError::ActorIsNotExecutable(dest));
+        }
+
+        let (mut data, memory_pages) = actor
+            .get_executable_actor_data()
+            .ok_or(TestError::ActorIsNotExecutable(dest))?;
+        self.fill_actor_state(dest, &mut data);
+
+        let origin = dispatch.source();
+        let gas_limit = dispatch.gas_limit().unwrap_or(u64::MAX);
+        let outcome = self.simulate_dispatch(
+            *balance,
+            Some(data),
+            memory_pages,
+            dispatch.into_stored(),
+            gas_limit,
+            origin,
+        );
+
+        Ok(SimulationResult {
+            gas_burned: Gas(outcome.gas),
+            pages_persisted: outcome.pages.len(),
+            journal: JournalEntry::from_notes(&outcome.journal),
+        })
+    }
+
+    /// Write the payload executed by the dispatch into the corpus directory.
+    ///
+    /// The file is named by the hash of its content, so the same payload
+    /// executed by the same entry point of the program is written once.
+    pub(crate) fn record_corpus_entry(&mut self, dispatch: &StoredDispatch) {
+        let dir = match &self.corpus_dir {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        let kind = match dispatch.kind() {
+            DispatchKind::Init => EntryKind::Init,
+            DispatchKind::Handle => EntryKind::Handle,
+            DispatchKind::Reply => EntryKind::Reply,
+            DispatchKind::Signal => EntryKind::Signal,
+        };
+
+        let bytes = Entry {
+            kind,
+            program_id: dispatch.destination().into(),
+            payload: dispatch.payload().to_vec(),
+        }
+        .encode();
+
+        let hash = blake2b::blake2b(32, &[], &bytes);
+        let path = dir.join(hex::encode(hash.as_bytes()));
+
+        if !path.exists() {
+            fs::write(&path, bytes)
+                .unwrap_or_else(|e| panic!("Failed to write corpus entry {:?}: {}", path, e));
+            self.corpus_written += 1;
         }
     }
 
@@ -402,14 +861,16 @@ impl ExtManager {
         }
     }
 
-    fn prepare_for(&mut self, dispatch: &Dispatch) {
-        self.msg_id = dispatch.id();
-        self.origin = dispatch.source();
+    fn prepare_for(&mut self, message_id: MessageId, origin: ProgramId) {
+        self.msg_id = message_id;
+        self.origin = origin;
         self.log.clear();
         self.main_failed = false;
         self.others_failed = false;
         self.main_gas_burned = Gas::zero();
         self.others_gas_burned = Gas::zero();
+        self.corpus_written = 0;
+        self.pages_persisted = 0;
 
         // TODO: Remove this check after #349.
         if !self.dispatches.is_empty() {
@@ -436,7 +897,7 @@ impl ExtManager {
         self.move_waiting_msgs_to_queue(message_id, program_id);
     }
 
-    fn init_failure(&mut self, message_id: MessageId, program_id: ProgramId) {
+    fn init_failure(&mut self, message_id: MessageId, program_id: ProgramId, origin: ProgramId) {
         let (actor, _) = self
             .actors
             .get_mut(&program_id)
@@ -446,23 +907,29 @@ impl ExtManager {
 
         self.move_waiting_msgs_to_queue(message_id, program_id);
         self.mark_failed(message_id);
+
+        // Program created by another program is terminated and its state
+        // is cleared. Value is returned to the creator.
+        if !self.is_user(&origin) {
+            self.exit_dispatch(program_id, origin);
+            self.actors.insert(program_id, (TestActor::Dormant, 0));
+        }
     }
 
     fn move_waiting_msgs_to_queue(&mut self, message_id: MessageId, program_id: ProgramId) {
         if let Some(ids) = self.wait_init_list.remove(&program_id) {
             for id in ids {
-                self.wake_message(message_id, program_id, id, 0);
+                self.wake_message(message_id, program_id, id, None);
             }
         }
     }
 
     // When called for the `dispatch`, it must be in queue.
     fn check_is_for_wait_list(&self, dispatch: &StoredDispatch) -> bool {
-        let (actor, _) = self
-            .actors
-            .get(&dispatch.destination())
-            .expect("method called for unknown destination");
-        if let TestActor::Uninitialized(maybe_message_id, _) = actor {
+        // Destination is unknown if the program has exited.
+        if let Some((TestActor::Uninitialized(maybe_message_id, _), _)) =
+            self.actors.get(&dispatch.destination())
+        {
             let id = maybe_message_id.expect("message in dispatch queue has id");
             dispatch.reply().is_none() && id != dispatch.id()
         } else {
@@ -470,6 +937,42 @@ impl ExtManager {
         }
     }
 
+    fn exports_signal(&self, program_id: &ProgramId) -> bool {
+        match self.actors.get(program_id) {
+            Some((TestActor::Initialized(Program::Genuine { program, .. }), _)) => {
+                program.code().exports().contains(&DispatchKind::Signal)
+            }
+            Some((TestActor::Initialized(Program::Mock(_)), _)) => true,
+            _ => false,
+        }
+    }
+
+    /// Number of blocks the message can afford to wait for with the gas left.
+    fn wait_budget(&self, message_id: MessageId) -> u32 {
+        let gas_left = self
+            .gas_limits
+            .get(&message_id)
+            .copied()
+            .flatten()
+            .unwrap_or(u64::MAX);
+
+        (gas_left / WAITLIST_COST)
+            .saturating_sub(RESERVE_FOR.into())
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Charge the rent for holding the message in the waitlist since
+    /// `start` till `till` block.
+    fn charge_for_wait(&mut self, message_id: MessageId, start: u32, till: u32) {
+        let duration = till.saturating_sub(start);
+        let amount = u64::from(duration).saturating_mul(WAITLIST_COST);
+
+        if let Some(Some(gas_left)) = self.gas_limits.get_mut(&message_id) {
+            *gas_left = gas_left.saturating_sub(amount);
+        }
+    }
+
     fn process_mock(&mut self, mut mock: Box<dyn WasmProgram>, dispatch: StoredDispatch) {
         enum Mocked {
             Reply(Option<Vec<u8>>),
@@ -506,7 +1009,7 @@ impl ExtManager {
                     self.send_dispatch(
                         message_id,
                         reply_message.into_dispatch(program_id, dispatch.source(), message_id),
-                        0,
+                        None,
                     );
                 }
             }
@@ -536,14 +1039,18 @@ impl ExtManager {
                 }
 
                 if !dispatch.kind().is_signal() {
-                    let id = MessageId::generate_reply(message_id, 1);
-                    let packet = ReplyPacket::new(Default::default(), 1);
+                    let exit_code = match dispatch.kind() {
+                        DispatchKind::Init => core_processor::INIT_FAILURE_EXIT_CODE,
+                        _ => core_processor::ERR_EXIT_CODE,
+                    };
+                    let id = MessageId::generate_reply(message_id, exit_code);
+                    let packet = ReplyPacket::new(Default::default(), exit_code);
                     let reply_message = ReplyMessage::from_packet(id, packet);
 
                     self.send_dispatch(
                         message_id,
                         reply_message.into_dispatch(program_id, dispatch.source(), message_id),
-                        0,
+                        None,
                     );
                 }
             }
@@ -579,22 +1086,70 @@ impl ExtManager {
         memory_pages: BTreeMap<PageNumber, PageBuf>,
         dispatch: StoredDispatch,
     ) {
-        let dest = dispatch.destination();
         let gas_limit = self
             .gas_limits
             .get(&dispatch.id())
             .expect("Unable to find gas limit for message")
             .unwrap_or(u64::MAX);
+        let outcome = self.simulate_dispatch(
+            balance,
+            data,
+            memory_pages,
+            dispatch,
+            gas_limit,
+            self.origin,
+        );
+
+        if let Some(notes) = &mut self.notes {
+            notes.extend(outcome.journal.iter().cloned());
+        }
+        core_processor::handle_journal(outcome.journal, self);
+    }
+
+    fn simulate_dispatch(
+        &self,
+        balance: u128,
+        data: Option<ExecutableActorData>,
+        memory_pages: BTreeMap<PageNumber, PageBuf>,
+        dispatch: StoredDispatch,
+        gas_limit: u64,
+        origin: ProgramId,
+    ) -> SimulationOutcome {
+        let dest = dispatch.destination();
+        let message_id = dispatch.id();
+        REPLY_WAKER.with(|waker| {
+            waker.set(dispatch.reply().and_then(|reply| {
+                let reply_to = reply.reply_to();
+                self.reply_wakers
+                    .get(&(dest, reply_to))
+                    .map(|&waiting| (dest, reply_to, waiting))
+            }))
+        });
         let block_config = BlockConfig {
             block_info: self.block_info,
+            block_duration: BLOCK_DURATION,
             allocations_config: Default::default(),
             existential_deposit: EXISTENTIAL_DEPOSIT,
+            existential_deposit_policy: ExistentialDepositPolicy::Exempt,
             outgoing_limit: OUTGOING_LIMIT,
             host_fn_weights: Default::default(),
             forbidden_funcs: Default::default(),
             mailbox_threshold: MAILBOX_THRESHOLD,
             waitlist_cost: WAITLIST_COST,
+            reply_waker_cost: 0,
             reserve_for: RESERVE_FOR,
+            block_hash_depth: BLOCK_HASH_DEPTH,
+            block_hash,
+            program_exists,
+            mailbox_contains,
+            codes_count,
+            delayed_dispatch: |_, _| None,
+            program_counters,
+            children_gas_burned,
+            code_id_of,
+            reply_waker,
+            entry_budget,
+            entry_budget_read_cost: 0,
         };
         let message_execution_context = MessageExecutionContext {
             actor: Actor {
@@ -603,21 +1158,51 @@ impl ExtManager {
                 executable_data: data,
             },
             dispatch: dispatch.into_incoming(gas_limit),
-            origin: self.origin,
+            origin,
+            tx_hash: None,
+            execution_id: self.fetch_inc_execution_nonce(),
             gas_allowance: u64::MAX,
             subsequent_execution: false,
         };
 
-        let journal = match core_processor::prepare(&block_config, message_execution_context) {
-            PrepareResult::WontExecute(journal) | PrepareResult::Error(journal) => journal,
-            PrepareResult::Ok { context, .. } => core_processor::process::<Ext, WasmiEnvironment>(
-                &block_config,
-                context,
-                memory_pages,
-            ),
-        };
+        let _published = PublishedManager::new(self);
+        CODES_COUNT.with(|count| count.set(self.opt_binaries.len() as u64));
+        PROGRAM_COUNTERS.with(|counters| {
+            *counters.borrow_mut() = (dest, self.counters.get(&dest).cloned().unwrap_or_default())
+        });
+        CHILDREN_GAS_BURNED.with(|gas_burned| {
+            gas_burned.set((
+                dest,
+                self.children_gas_burned
+                    .get(&dest)
+                    .copied()
+                    .unwrap_or_default(),
+            ))
+        });
 
-        core_processor::handle_journal(journal, self);
+        match core_processor::prepare(&block_config, message_execution_context) {
+            PrepareResult::WontExecute(journal) | PrepareResult::Error(journal) => {
+                SimulationOutcome::new(message_id, dest, memory_pages, journal)
+            }
+            PrepareResult::Ok { context, .. } => match self.backend {
+                Backend::Wasmi => core_processor::simulate_dispatch::<Ext, WasmiEnvironment>(
+                    &block_config,
+                    context,
+                    memory_pages,
+                ),
+                // Sandbox is provided by the host, so it's only available
+                // within externalities.
+                Backend::SandboxCompiled => SANDBOX_EXTERNALITIES.with(|externalities| {
+                    externalities.borrow_mut().execute_with(|| {
+                        core_processor::simulate_dispatch::<Ext, SandboxEnvironment>(
+                            &block_config,
+                            context,
+                            memory_pages,
+                        )
+                    })
+                }),
+            },
+        }
     }
 
     fn execute(
@@ -665,14 +1250,16 @@ impl JournalHandler for ExtManager {
         _source: ProgramId,
         outcome: DispatchOutcome,
     ) {
+        self.record(|| JournalEntry::dispatched(message_id, &outcome));
+
         match outcome {
             DispatchOutcome::MessageTrap { .. } => self.mark_failed(message_id),
             DispatchOutcome::Success
             | DispatchOutcome::NoExecution
             | DispatchOutcome::Exit { .. } => {}
-            DispatchOutcome::InitFailure { program_id, .. } => {
-                self.init_failure(message_id, program_id)
-            }
+            DispatchOutcome::InitFailure {
+                program_id, origin, ..
+            } => self.init_failure(message_id, program_id, origin),
             DispatchOutcome::InitSuccess { program_id, .. } => {
                 self.init_success(message_id, program_id)
             }
@@ -680,6 +1267,12 @@ impl JournalHandler for ExtManager {
     }
 
     fn gas_burned(&mut self, message_id: MessageId, amount: u64) {
+        self.record(|| JournalEntry::GasBurned { message_id, amount });
+
+        if let Some(Some(gas_left)) = self.gas_limits.get_mut(&message_id) {
+            *gas_left = gas_left.saturating_sub(amount);
+        }
+
         if self.msg_id == message_id {
             self.main_gas_burned = self.main_gas_burned.saturating_add(Gas(amount));
         } else {
@@ -689,8 +1282,21 @@ impl JournalHandler for ExtManager {
 
     fn exit_dispatch(&mut self, id_exited: ProgramId, value_destination: ProgramId) {
         if let Some((_, balance)) = self.actors.remove(&id_exited) {
-            self.mint_to(&value_destination, balance);
+            if balance != 0 {
+                self.mint_to(&value_destination, balance);
+            }
         }
+
+        self.value_reservations.remove(&id_exited);
+        self.children_count.remove(&id_exited);
+        self.children_gas_burned.remove(&id_exited);
+        self.counters.remove(&id_exited);
+        self.entry_budgets.remove(&id_exited);
+        self.charge_allowances.remove(&id_exited);
+        self.reply_wakers
+            .retain(|(program_id, _), _| *program_id != id_exited);
+        self.scheduled_exits.remove(&id_exited);
+        self.wait_init_list.remove(&id_exited);
     }
 
     fn message_consumed(&mut self, message_id: MessageId) {
@@ -699,19 +1305,34 @@ impl JournalHandler for ExtManager {
         }
     }
 
-    fn send_dispatch(&mut self, _message_id: MessageId, dispatch: Dispatch, _delay: u32) {
+    fn send_dispatch(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        _delay: Option<NonZeroU32>,
+    ) {
+        self.record(|| JournalEntry::sent(&dispatch));
+
         self.gas_limits.insert(dispatch.id(), dispatch.gas_limit());
 
+        // Explicitly provided gas is taken from the sender's one.
+        if let (Some(gas_limit), Some(Some(gas_left))) =
+            (dispatch.gas_limit(), self.gas_limits.get_mut(&message_id))
+        {
+            *gas_left = gas_left.saturating_sub(gas_limit);
+        }
+
         if !self.is_user(&dispatch.destination()) {
             self.dispatches.push_back(dispatch.into_stored());
         } else {
             let message = dispatch.into_stored().into_parts().1;
 
-            let message = match message.exit_code() {
-                Some(0) | None => message,
-                _ => message
+            let message = if message.is_error_reply() {
+                message
                     .with_string_payload::<ExecutionErrorReason>()
-                    .unwrap_or_else(|e| e),
+                    .unwrap_or_else(|e| e)
+            } else {
+                message
             };
 
             self.mailbox
@@ -723,10 +1344,49 @@ impl JournalHandler for ExtManager {
         }
     }
 
+    fn send_dispatch_charge_recipient(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+    ) {
+        let destination = dispatch.destination();
+        let gas_limit = dispatch.gas_limit().unwrap_or_default();
+
+        // Gas isn't bought here, but the recipient still has to agree to pay.
+        let allowance = self
+            .charge_allowances
+            .get(&destination)
+            .copied()
+            .unwrap_or_default();
+
+        if !self.is_user(&destination) && allowance >= gas_limit {
+            self.charge_allowances
+                .insert(destination, allowance - gas_limit);
+            self.send_dispatch(message_id, dispatch, delay);
+        } else {
+            let err = ExecutionErrorReason::RecipientCannotPayGas
+                .encode()
+                .try_into()
+                .expect("Error message is too large");
+            let err_reply = ReplyMessage::system(dispatch.id(), err, core_processor::ERR_EXIT_CODE)
+                .into_dispatch(destination, dispatch.source(), dispatch.id());
+            self.send_dispatch(message_id, err_reply, None);
+        }
+    }
+
     fn wait_dispatch(&mut self, dispatch: StoredDispatch, _duration: Option<u32>) {
         self.message_consumed(dispatch.id());
-        self.wait_list
-            .insert((dispatch.destination(), dispatch.id()), dispatch);
+
+        // Requested duration doesn't bound waiting here, so messages stay
+        // in the waitlist until woken or until they run out of budget.
+        let start = self.block_info.height;
+        let expected = start.saturating_add(self.wait_budget(dispatch.id()));
+
+        self.wait_list.insert(
+            (dispatch.destination(), dispatch.id()),
+            (dispatch, start, expected),
+        );
     }
 
     fn wake_message(
@@ -734,9 +1394,17 @@ impl JournalHandler for ExtManager {
         _message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
-        _delay: u32,
+        _delay: Option<NonZeroU32>,
     ) {
-        if let Some(msg) = self.wait_list.remove(&(program_id, awakening_id)) {
+        if let Some((msg, start, expected)) = self.wait_list.remove(&(program_id, awakening_id)) {
+            self.forget_reply_waker(program_id, awakening_id);
+
+            let till = self
+                .block_info
+                .height
+                .min(expected.saturating_add(RESERVE_FOR));
+            self.charge_for_wait(awakening_id, start, till);
+
             self.dispatches.push_back(msg);
         }
     }
@@ -746,6 +1414,9 @@ impl JournalHandler for ExtManager {
         program_id: ProgramId,
         mut pages_data: BTreeMap<PageNumber, PageBuf>,
     ) {
+        self.record(|| JournalEntry::pages_updated(program_id, &pages_data));
+        self.pages_persisted += pages_data.len();
+
         let (actor, _) = self
             .actors
             .get_mut(&program_id)
@@ -791,6 +1462,79 @@ impl JournalHandler for ExtManager {
         }
     }
 
+    fn update_value_reservations(
+        &mut self,
+        program_id: ProgramId,
+        reservations: BTreeMap<ReservationId, u128>,
+    ) {
+        if reservations.is_empty() {
+            self.value_reservations.remove(&program_id);
+        } else {
+            self.value_reservations.insert(program_id, reservations);
+        }
+    }
+
+    fn update_children_count(&mut self, program_id: ProgramId, children_count: u64) {
+        self.children_count.insert(program_id, children_count);
+    }
+
+    fn child_gas_burned(&mut self, program_id: ProgramId, amount: u64) {
+        // Creators of the programs may be users or exited programs.
+        if !matches!(
+            self.actors.get(&program_id),
+            Some((TestActor::Initialized(_) | TestActor::Uninitialized(..), _))
+        ) {
+            return;
+        }
+
+        let gas_burned = self.children_gas_burned.entry(program_id).or_default();
+        *gas_burned = gas_burned.saturating_add(amount);
+    }
+
+    fn update_counters(&mut self, program_id: ProgramId, counters: BTreeMap<CounterKey, u64>) {
+        if counters.is_empty() {
+            self.counters.remove(&program_id);
+        } else {
+            self.counters.insert(program_id, counters);
+        }
+    }
+
+    fn update_entry_budgets(
+        &mut self,
+        program_id: ProgramId,
+        entry_budgets: BTreeMap<DispatchKind, u64>,
+    ) {
+        self.entry_budgets
+            .entry(program_id)
+            .or_default()
+            .extend(entry_budgets);
+    }
+
+    fn update_charge_allowance(&mut self, program_id: ProgramId, allowance: u64) {
+        if allowance == 0 {
+            self.charge_allowances.remove(&program_id);
+        } else {
+            self.charge_allowances.insert(program_id, allowance);
+        }
+    }
+
+    fn update_reply_waker(
+        &mut self,
+        program_id: ProgramId,
+        reply_to: MessageId,
+        waiting: Option<MessageId>,
+    ) {
+        match waiting {
+            Some(waiting) => self.reply_wakers.insert((program_id, reply_to), waiting),
+            None => self.reply_wakers.remove(&(program_id, reply_to)),
+        };
+    }
+
+    fn schedule_exit(&mut self, program_id: ProgramId, block: u32, inheritor: ProgramId) {
+        // The exit scheduled before is replaced.
+        self.scheduled_exits.insert(program_id, (block, inheritor));
+    }
+
     fn send_value(&mut self, from: ProgramId, to: Option<ProgramId>, value: Balance) {
         if value == 0 {
             // Nothing to do
@@ -821,8 +1565,13 @@ impl JournalHandler for ExtManager {
         if let Some(code) = self.opt_binaries.get(&code_hash).cloned() {
             for (candidate_id, init_message_id) in candidates {
                 if !self.actors.contains_key(&candidate_id) {
-                    let code = Code::try_new(code.clone(), 1, |_| ConstantCostRules::default())
-                        .expect("Program can't be constructed with provided code");
+                    let code = Code::try_new(
+                        code.clone(),
+                        1,
+                        |_| ConstantCostRules::default(),
+                        Some(STACK_HEIGHT),
+                    )
+                    .expect("Program can't be constructed with provided code");
 
                     let code_and_id: InstrumentedCodeAndId =
                         CodeAndId::from_parts_unchecked(code, code_hash).into();
@@ -17,10 +17,10 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    log::RunResult,
+    log::{RunResult, SimulationResult},
     manager::{Balance, ExtManager, Program as InnerProgram, TestActor},
     system::System,
-    Result,
+    Result, STACK_HEIGHT,
 };
 use codec::{Codec, Decode, Encode};
 use gear_core::{
@@ -329,8 +329,13 @@ impl<'a> Program<'a> {
         optimized: Vec<u8>,
         metadata: Option<Vec<u8>>,
     ) -> Self {
-        let code = Code::try_new(optimized, 1, |_| ConstantCostRules::default())
-            .expect("Failed to create Program from code");
+        let code = Code::try_new(
+            optimized,
+            1,
+            |_| ConstantCostRules::default(),
+            Some(STACK_HEIGHT),
+        )
+        .expect("Failed to create Program from code");
 
         let code_and_id: InstrumentedCodeAndId = CodeAndId::new(code).into();
         let (code, code_id) = code_and_id.into_parts();
@@ -379,35 +384,109 @@ impl<'a> Program<'a> {
         from: ID,
         payload: T,
         value: u128,
+    ) -> RunResult {
+        let source = from.into().0;
+        self.send_bytes_as(source, source, payload, value)
+    }
+
+    /// Send the message from `source` on behalf of the `origin` user, e.g.
+    /// by a relayer acting for the user.
+    ///
+    /// Programs get `origin` from `exec::origin` while handling the message
+    /// and all the messages sent in its course.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `origin` differs from `source`, unless it's allowed with
+    /// [`System::allow_origin_override`](crate::System::allow_origin_override).
+    pub fn send_as<S: Into<ProgramIdWrapper>, O: Into<ProgramIdWrapper>, C: Codec>(
+        &self,
+        source: S,
+        origin: O,
+        payload: C,
+        value: u128,
+    ) -> RunResult {
+        self.send_bytes_as(source, origin, payload.encode(), value)
+    }
+
+    /// Same as [`send_as`](Self::send_as), but with the raw `payload`.
+    pub fn send_bytes_as<S: Into<ProgramIdWrapper>, O: Into<ProgramIdWrapper>, T: AsRef<[u8]>>(
+        &self,
+        source: S,
+        origin: O,
+        payload: T,
+        value: u128,
     ) -> RunResult {
         let mut system = self.manager.borrow_mut();
 
-        let source = from.into().0;
+        let nonce = system.fetch_inc_message_nonce();
+        let dispatch = self.dispatch(&system, source.into().0, payload.as_ref(), value, nonce);
+
+        let (actor, _) = system.actors.get_mut(&self.id).expect("Can't fail");
+
+        if let TestActor::Uninitialized(id @ None, _) = actor {
+            *id = Some(dispatch.id());
+        }
+
+        system.run_dispatch_as(dispatch, origin.into().0)
+    }
+
+    /// Execute the message the same way as [`send`](Self::send) does,
+    /// but without committing its results.
+    ///
+    /// Sending the same message afterwards gets the same gas burned and
+    /// the same [`journal`](RunResult::journal), unless other programs
+    /// execute messages sent by this one.
+    pub fn calculate_gas_for<ID: Into<ProgramIdWrapper>, C: Codec>(
+        &self,
+        from: ID,
+        payload: C,
+    ) -> Result<SimulationResult> {
+        self.calculate_gas_for_bytes(from, payload.encode())
+    }
+
+    /// Execute the message the same way as [`send_bytes`](Self::send_bytes)
+    /// does, but without committing its results.
+    ///
+    /// See [`calculate_gas_for`](Self::calculate_gas_for) for details.
+    pub fn calculate_gas_for_bytes<ID: Into<ProgramIdWrapper>, T: AsRef<[u8]>>(
+        &self,
+        from: ID,
+        payload: T,
+    ) -> Result<SimulationResult> {
+        let system = self.manager.borrow();
+
+        // The nonce isn't fetched, so the message sent next gets the same id.
+        let nonce = system.msg_nonce;
+        let dispatch = self.dispatch(&system, from.into().0, payload.as_ref(), 0, nonce);
 
+        system.calculate_gas_for(dispatch)
+    }
+
+    fn dispatch(
+        &self,
+        system: &ExtManager,
+        source: ProgramId,
+        payload: &[u8],
+        value: u128,
+        nonce: u64,
+    ) -> Dispatch {
         let message = Message::new(
-            MessageId::generate_from_user(
-                system.block_info.height,
-                source,
-                system.fetch_inc_message_nonce() as u128,
-            ),
+            MessageId::generate_from_user(system.block_info.height, source, nonce as u128),
             source,
             self.id,
-            payload.as_ref().to_vec().try_into().unwrap(),
+            payload.to_vec().try_into().unwrap(),
             Some(u64::MAX),
             value,
             None,
         );
 
-        let (actor, _) = system.actors.get_mut(&self.id).expect("Can't fail");
-
-        let kind = if let TestActor::Uninitialized(id @ None, _) = actor {
-            *id = Some(message.id());
-            DispatchKind::Init
-        } else {
-            DispatchKind::Handle
+        let kind = match system.actors.get(&self.id) {
+            Some((TestActor::Uninitialized(None, _), _)) => DispatchKind::Init,
+            _ => DispatchKind::Handle,
         };
 
-        system.run_dispatch(Dispatch::new(kind, message))
+        Dispatch::new(kind, message)
     }
 
     pub fn send_signal<ID: Into<ProgramIdWrapper>>(
@@ -517,7 +596,9 @@ mod tests {
 
         let run_result = prog.send(user_id, String::from("should_be_skipped"));
 
-        let expected_log = Log::error_builder(2).source(prog.id()).dest(user_id);
+        let expected_log = Log::error_builder(core_processor::UNAVAILABLE_DEST_EXIT_CODE)
+            .source(prog.id())
+            .dest(user_id);
 
         assert!(!run_result.main_failed());
         assert!(run_result.contains(&expected_log));
@@ -592,6 +673,61 @@ mod tests {
         assert_eq!(prog.balance(), 0);
     }
 
+    #[test]
+    fn exit_in_init() {
+        let sys = System::new();
+        sys.init_logger();
+
+        let user_id = 42;
+        sys.mint_to(user_id, 5000);
+
+        let prog = Program::from_file(
+            &sys,
+            "../target/wasm32-unknown-unknown/release/demo_exit_init.wasm",
+        );
+
+        let run_result = prog.send_bytes_with_value(user_id, [0], 1000);
+        assert!(!run_result.main_failed());
+
+        // Creator is notified with error reply and inherits the value.
+        let expected_log = Log::error_builder(core_processor::INIT_EXITED_EXIT_CODE)
+            .source(prog.id())
+            .dest(user_id);
+        assert!(run_result.contains(&expected_log));
+        assert!(run_result.log()[0]
+            .payload()
+            .starts_with(b"Program exited in init"));
+
+        assert_eq!(prog.balance(), 0);
+        assert_eq!(sys.balance_of(user_id), 5000);
+    }
+
+    #[test]
+    fn exit_in_handle() {
+        let sys = System::new();
+        sys.init_logger();
+
+        let user_id = 42;
+        sys.mint_to(user_id, 5000);
+
+        let prog = Program::from_file(
+            &sys,
+            "../target/wasm32-unknown-unknown/release/demo_exit_handle.wasm",
+        );
+
+        let run_result = prog.send_bytes_with_value(user_id, b"init", 1000);
+        assert!(!run_result.main_failed());
+        assert_eq!(prog.balance(), 1000);
+        assert_eq!(sys.balance_of(user_id), 4000);
+
+        let run_result = prog.send_bytes(user_id, b"exit");
+        assert!(!run_result.main_failed());
+        assert!(run_result.log().is_empty());
+
+        assert_eq!(prog.balance(), 0);
+        assert_eq!(sys.balance_of(user_id), 5000);
+    }
+
     #[test]
     #[should_panic(expected = "An attempt to mint value (1) less than existential deposit (500)")]
     fn mint_less_than_deposit() {
@@ -647,4 +783,78 @@ mod tests {
         sys.claim_value_from_mailbox(receiver);
         assert_eq!(sys.balance_of(receiver), 2 * crate::EXISTENTIAL_DEPOSIT);
     }
+
+    #[test]
+    fn backends_agree() {
+        System::new().assert_backends_agree(|sys| {
+            let user_id = 42;
+
+            let prog = Program::from_file(
+                sys,
+                "../target/wasm32-unknown-unknown/release/demo_ping.wasm",
+            );
+
+            let run_result = prog.send(user_id, "init".to_string());
+            assert_eq!(run_result.backend(), sys.backend());
+
+            let run_result = prog.send(user_id, "PING".to_string());
+            assert!(run_result.contains(&Log::builder().dest(user_id).payload_bytes("PONG")));
+        });
+    }
+
+    #[test]
+    fn restore_from_snapshot() {
+        let sys = System::new();
+        sys.init_logger();
+
+        let user_id = 42;
+
+        let prog = Program::from_file(
+            &sys,
+            "../target/wasm32-unknown-unknown/release/demo_ping.wasm",
+        );
+        prog.send(user_id, "init".to_string());
+
+        let snapshot = sys.snapshot();
+
+        let run_result = prog.send(user_id, "PING".to_string());
+        assert!(run_result.journal().is_empty());
+
+        sys.restore(&snapshot);
+        sys.record_journal(true);
+
+        let replayed = prog.send(user_id, "PING".to_string());
+        assert_eq!(replayed.sent_message_id(), run_result.sent_message_id());
+        assert_eq!(replayed.main_gas_burned(), run_result.main_gas_burned());
+        assert!(!replayed.journal().is_empty());
+    }
+
+    #[test]
+    fn simulation_matches_execution() {
+        let sys = System::new();
+        sys.init_logger();
+        sys.record_journal(true);
+
+        let user_id = 42;
+
+        let prog = Program::from_file(
+            &sys,
+            "../target/wasm32-unknown-unknown/release/demo_ping.wasm",
+        );
+
+        let simulation = prog
+            .calculate_gas_for(user_id, "init".to_string())
+            .expect("Program isn't executable");
+        let run_result = prog.send(user_id, "init".to_string());
+        assert_eq!(simulation.journal_hash(), run_result.journal_hash());
+
+        let simulation = prog
+            .calculate_gas_for(user_id, "PING".to_string())
+            .expect("Program isn't executable");
+        let run_result = prog.send(user_id, "PING".to_string());
+        assert!(run_result.contains(&Log::builder().dest(user_id).payload_bytes("PONG")));
+
+        assert_eq!(simulation.gas_burned(), run_result.main_gas_burned());
+        assert_eq!(simulation.journal_hash(), run_result.journal_hash());
+    }
 }
@@ -17,16 +17,74 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-    log::RunResult,
+    log::{self, JournalEntry, RunResult},
     mailbox::Mailbox,
-    manager::{Balance, ExtManager},
+    manager::{self, Balance, ExtManager},
     program::{Program, ProgramIdWrapper},
+    BLOCK_DURATION,
 };
+use codec::Encode;
 use colored::Colorize;
+use core_processor::common::Journal;
 use env_logger::{Builder, Env};
-use gear_core::{ids::CodeId, message::Dispatch};
+use gear_core::{
+    ids::{CodeId, ProgramId},
+    message::{Dispatch, DispatchKind, ReplyDetails},
+};
 use path_clean::PathClean;
-use std::{cell::RefCell, env, fs, io::Write, path::Path, thread};
+use std::{cell::RefCell, env, fs, io::Write, mem, path::Path, thread};
+
+/// Engine executing programs' code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Interpreting `wasmi` backend.
+    Wasmi,
+    /// Sandbox backend used by the node to execute messages on-chain.
+    SandboxCompiled,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::Wasmi
+    }
+}
+
+/// State of the [`System`] taken by [`System::snapshot`].
+#[derive(Debug, Clone)]
+pub struct Snapshot(ExtManager);
+
+/// Message fed to the program by [`System::inject_raw`] as is.
+///
+/// Nothing prevents the dispatch from being one the chain would never
+/// deliver, so tests can check the program survives hostile peers anyway.
+/// The chain forbids:
+///
+/// - `Init` to an initialized program: it's answered with an error reply
+///   without executing the program.
+/// - `Handle` and `Init` with `details`.
+/// - `Reply` without `details`, or replying to a message the program
+///   never sent, or from an actor the message wasn't sent to.
+/// - `Signal` from anyone but the system, or without `details` of a
+///   message of the program with a [`SignalCode`](gear_core::message::SignalCode).
+/// - Messages with the program itself as the `source`, besides replies
+///   and messages it sends to itself.
+/// - Any `value` the `source` can't afford.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawDispatch {
+    /// Entry point of the program to execute.
+    pub kind: DispatchKind,
+    /// Claimed sender of the message.
+    pub source: ProgramId,
+    /// Program to feed the message to.
+    pub destination: ProgramId,
+    /// Message payload.
+    pub payload: Vec<u8>,
+    /// Value of the message, minted rather than taken from the `source`.
+    pub value: u128,
+    /// Message replied to along with the exit code, or the message the
+    /// signal is about along with the signal code.
+    pub details: Option<ReplyDetails>,
+}
 
 pub struct System(pub(crate) RefCell<ExtManager>);
 
@@ -41,6 +99,18 @@ impl System {
         Default::default()
     }
 
+    /// Create the system executing programs with the given `backend`.
+    pub fn new_with_backend(backend: Backend) -> Self {
+        let system = Self::new();
+        system.0.borrow_mut().backend = backend;
+        system
+    }
+
+    /// Return the backend executing programs.
+    pub fn backend(&self) -> Backend {
+        self.0.borrow().backend
+    }
+
     pub fn init_logger(&self) {
         let _ = Builder::from_env(Env::default().default_filter_or("gwasm=debug"))
             .format(|buf, record| {
@@ -73,14 +143,219 @@ impl System {
             .try_init();
     }
 
+    /// Record payloads executed by programs into `dir` to seed fuzzing.
+    ///
+    /// Each payload is written into its own file along with the entry point
+    /// and the id of the executing program, in the format decoded by
+    /// the `gtest-corpus` crate. Payloads of all entry points, signals
+    /// included, are recorded, identical entries are written once.
+    pub fn record_corpus<P: AsRef<Path>>(&self, dir: P) {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)
+            .unwrap_or_else(|e| panic!("Failed to create corpus directory {:?}: {}", dir, e));
+        self.0.borrow_mut().corpus_dir = Some(dir.to_path_buf());
+    }
+
+    /// Allow sending messages on behalf of the user other than their source
+    /// with [`Program::send_as`](crate::Program::send_as).
+    ///
+    /// Not allowed by default, so the origin of every message is its source.
+    pub fn allow_origin_override(&self, allow: bool) {
+        self.0.borrow_mut().origin_override_allowed = allow;
+    }
+
+    /// Take the snapshot of the system state, which it may be
+    /// [`restore`](Self::restore)d to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system has mock programs, as their state
+    /// can't be copied.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.0.borrow().clone())
+    }
+
+    /// Restore the system state from the `snapshot`.
+    ///
+    /// The state is replaced entirely, including programs created and
+    /// messages sent after the snapshot was taken.
+    pub fn restore(&self, snapshot: &Snapshot) {
+        *self.0.borrow_mut() = snapshot.0.clone();
+    }
+
+    /// Record the journal of the runs, see [`RunResult::journal`].
+    ///
+    /// Not recorded by default. The journal grows with every run while
+    /// it's recorded, so it's meant for tests checking it.
+    pub fn record_journal(&self, record: bool) {
+        let mut manager = self.0.borrow_mut();
+        match (record, &manager.journal) {
+            (true, None) => manager.journal = Some(Vec::new()),
+            (false, _) => manager.journal = None,
+            _ => {}
+        }
+    }
+
+    // Runs the `scenario` from the `snapshot`, returning its journal and notes.
+    //
+    // The system is left in the state after the run with the journal
+    // recorded as before it.
+    fn replay<F: Fn(&System)>(
+        &self,
+        snapshot: &Snapshot,
+        scenario: &F,
+    ) -> (Vec<JournalEntry>, Journal) {
+        self.restore(snapshot);
+        {
+            let mut manager = self.0.borrow_mut();
+            manager.journal = Some(Vec::new());
+            manager.notes = Some(Default::default());
+        }
+
+        scenario(self);
+
+        let mut manager = self.0.borrow_mut();
+        let journal = mem::replace(&mut manager.journal, snapshot.0.journal.clone());
+        let notes = manager.notes.take().unwrap_or_default();
+        (journal.unwrap_or_default(), notes)
+    }
+
+    /// Run the `scenario` `runs` times from the current state of the system,
+    /// checking that it's executed the same way every time.
+    ///
+    /// The state is restored from the snapshot before every run, so
+    /// journals of the runs may differ only if programs are nondeterministic,
+    /// e.g. read uninitialized memory or depend on the hash map iteration order.
+    /// The system is left in the state after the last run.
+    ///
+    /// Executions are also compared by the hash of their canonical journal,
+    /// which nodes log to be compared, see
+    /// [`Journal::canonical_hash`](core_processor::common::Journal::canonical_hash).
+    ///
+    /// # Panics
+    ///
+    /// Panics naming the first divergent journal entry or note, if journals
+    /// of the runs differ. Panics if the system has mock programs before
+    /// the check, see [`snapshot`](Self::snapshot).
+    pub fn determinism_check<F: Fn(&System)>(&self, runs: u32, scenario: F) {
+        let snapshot = self.snapshot();
+
+        let (expected, expected_notes) = self.replay(&snapshot, &scenario);
+        let expected_hash = log::journal_hash(&expected);
+        let expected_notes_hash = expected_notes.canonical_hash();
+
+        for run in 1..runs {
+            let (journal, notes) = self.replay(&snapshot, &scenario);
+            if log::journal_hash(&journal) == expected_hash {
+                if notes.canonical_hash() == expected_notes_hash {
+                    continue;
+                }
+
+                let encoded =
+                    |notes: &Journal| -> Vec<_> { notes.0.iter().map(Encode::encode).collect() };
+                let (expected, actual) = (encoded(&expected_notes), encoded(&notes));
+                let index = expected
+                    .iter()
+                    .zip(&actual)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(expected.len().min(actual.len()));
+
+                panic!(
+                    "Run {} diverged from the first one at canonical journal note {}",
+                    run, index
+                );
+            }
+
+            let index = expected
+                .iter()
+                .zip(&journal)
+                .position(|(a, b)| a != b)
+                .unwrap_or(expected.len().min(journal.len()));
+
+            panic!(
+                "Run {} diverged from the first one at journal entry {}: expected {:?}, got {:?}",
+                run,
+                index,
+                expected.get(index),
+                journal.get(index)
+            );
+        }
+    }
+
+    /// Run the `scenario` with each of the backends from the current state
+    /// of the system, checking that it's executed the same way by all of them.
+    ///
+    /// The state is restored from the snapshot before every run. The system
+    /// is left in the state after the last run with its own backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics naming the first divergent journal entry, if journals of
+    /// the backends differ. Panics if the system has mock programs before
+    /// the check, see [`snapshot`](Self::snapshot).
+    pub fn assert_backends_agree<F: Fn(&System)>(&self, scenario: F) {
+        let snapshot = self.snapshot();
+        let run_scenario = |backend| {
+            let mut snapshot = snapshot.clone();
+            snapshot.0.backend = backend;
+            self.replay(&snapshot, &scenario).0
+        };
+
+        let expected = run_scenario(Backend::Wasmi);
+        let journal = run_scenario(Backend::SandboxCompiled);
+        self.0.borrow_mut().backend = snapshot.0.backend;
+
+        if log::journal_hash(&journal) == log::journal_hash(&expected) {
+            return;
+        }
+
+        let index = expected
+            .iter()
+            .zip(&journal)
+            .position(|(a, b)| a != b)
+            .unwrap_or(expected.len().min(journal.len()));
+
+        panic!(
+            "{:?} backend diverged from {:?} one at journal entry {}: expected {:?}, got {:?}",
+            Backend::SandboxCompiled,
+            Backend::Wasmi,
+            index,
+            expected.get(index),
+            journal.get(index)
+        );
+    }
+
     pub fn send_dispatch(&self, dispatch: Dispatch) -> RunResult {
         self.0.borrow_mut().run_dispatch(dispatch)
     }
 
-    pub fn spend_blocks(&self, amount: u32) {
+    /// Feed the `raw` dispatch to its destination program, bypassing the
+    /// checks of the normal send path.
+    ///
+    /// See [`RawDispatch`] for the dispatches the chain never delivers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the destination isn't a program or the value is less than
+    /// [`EXISTENTIAL_DEPOSIT`](crate::EXISTENTIAL_DEPOSIT), but not zero.
+    pub fn inject_raw(&self, raw: RawDispatch) -> RunResult {
+        self.0.borrow_mut().inject_raw(raw)
+    }
+
+    /// Spend `amount` blocks, charging rent from the waiting messages.
+    ///
+    /// Programs, which scheduled their exit within these blocks, exit.
+    ///
+    /// Messages, which can't afford waiting any longer, are removed from
+    /// the waitlist in order of their expiration. Returns results of
+    /// handling each removal: the signal to the program and the error
+    /// reply to the source of the message.
+    pub fn spend_blocks(&self, amount: u32) -> Vec<RunResult> {
         let mut manager = self.0.borrow_mut();
         manager.block_info.height += amount;
-        manager.block_info.timestamp += 1000 * amount as u64;
+        manager.block_info.timestamp += BLOCK_DURATION * amount as u64;
+        manager.exit_scheduled();
+        manager.remove_expired_from_waitlist()
     }
 
     /// Return the current block height.
@@ -88,6 +363,17 @@ impl System {
         self.0.borrow().block_info.height
     }
 
+    /// Return hash of the block at the given `height`.
+    ///
+    /// Hashes are derived from the height, so they're the same in every run.
+    /// Programs are able to get hashes of [`BLOCK_HASH_DEPTH`] blocks
+    /// preceding the current one.
+    ///
+    /// [`BLOCK_HASH_DEPTH`]: crate::BLOCK_HASH_DEPTH
+    pub fn block_hash(&self, height: u32) -> [u8; 32] {
+        manager::block_hash(height)
+    }
+
     /// Return the current block timestamp.
     pub fn block_timestamp(&self) -> u64 {
         self.0.borrow().block_info.timestamp
@@ -171,3 +457,239 @@ impl System {
         self.0.borrow_mut().claim_value_from_mailbox(&actor_id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{manager::ExtManager, Program, RawDispatch, System, WasmProgram};
+    use core_processor::common::{DispatchOutcome, Journal, JournalHasher, JournalNote};
+    use gear_core::{
+        ids::{MessageId, ProgramId},
+        message::{DispatchKind, ReplyDetails, StoredDispatch, StoredMessage},
+    };
+    use gtest_corpus::{Entry, EntryKind};
+    use std::fs;
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    const USER: u64 = 100;
+
+    #[derive(Debug)]
+    struct Echo;
+
+    impl WasmProgram for Echo {
+        fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(None)
+        }
+
+        fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(Some(payload))
+        }
+
+        fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(None)
+        }
+
+        fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn meta_state(&mut self, _payload: Option<Vec<u8>>) -> Result<Vec<u8>, &'static str> {
+            Ok(vec![])
+        }
+    }
+
+    // Replies with the state left by the previous runs, like a program
+    // reading uninitialized memory.
+    #[derive(Debug)]
+    struct Leaky;
+
+    static LEAKED: AtomicU8 = AtomicU8::new(0);
+
+    impl WasmProgram for Leaky {
+        fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(None)
+        }
+
+        fn handle(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(Some(vec![LEAKED.fetch_add(1, Ordering::Relaxed)]))
+        }
+
+        fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+            Ok(None)
+        }
+
+        fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn meta_state(&mut self, _payload: Option<Vec<u8>>) -> Result<Vec<u8>, &'static str> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn determinism_check_passes_deterministic_program() {
+        System::new().determinism_check(3, |system| {
+            let program = Program::mock(system, Echo);
+            program.send_bytes(USER, b"init");
+
+            let res = program.send_bytes(USER, b"PING");
+            assert_eq!(res.journal().len(), 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Run 1 diverged from the first one at journal entry 1")]
+    fn determinism_check_catches_nondeterministic_program() {
+        System::new().determinism_check(3, |system| {
+            let program = Program::mock(system, Leaky);
+            program.send_bytes(USER, b"init");
+            program.send_bytes(USER, b"PING");
+        });
+    }
+
+    #[test]
+    fn canonical_journal_is_reproducible() {
+        let block_info = ExtManager::new().block_info;
+        let run = || {
+            let system = System::new();
+            system.0.borrow_mut().block_info = block_info;
+
+            let snapshot = system.snapshot();
+            let (_, notes) = system.replay(&snapshot, &|system: &System| {
+                let program = Program::from_file(
+                    system,
+                    "../target/wasm32-unknown-unknown/release/demo_ping.wasm",
+                );
+                program.send_bytes(USER, b"init");
+                program.send_bytes(USER, b"PING");
+            });
+
+            notes
+        };
+
+        let (first, second) = (run(), run());
+        assert!(!first.0.is_empty());
+        assert_eq!(first.canonical_encode(), second.canonical_encode());
+        assert_eq!(first.canonical_hash(), second.canonical_hash());
+    }
+
+    #[test]
+    fn canonical_journal_skips_failure_explanations() {
+        let trapped = |trap: &str| {
+            Journal::from(vec![JournalNote::MessageDispatched {
+                message_id: MessageId::from(1),
+                source: ProgramId::from(2),
+                outcome: DispatchOutcome::MessageTrap {
+                    program_id: ProgramId::from(3),
+                    trap: trap.into(),
+                },
+            }])
+        };
+
+        assert_eq!(
+            trapped("Unreachable").canonical_hash(),
+            trapped("wasm `unreachable` instruction executed").canonical_hash()
+        );
+        assert_ne!(
+            trapped("Unreachable").canonical_hash(),
+            Journal::new().canonical_hash()
+        );
+    }
+
+    #[test]
+    fn journal_hashed_note_by_note() {
+        let notes = Journal::from(vec![
+            JournalNote::GasBurned {
+                message_id: MessageId::from(1),
+                amount: 42,
+            },
+            JournalNote::MessageConsumed(MessageId::from(1)),
+        ]);
+
+        let mut hasher = JournalHasher::new();
+        notes.0.iter().for_each(|note| hasher.update(note));
+
+        let hash = hasher.finalize();
+        assert_eq!(hash, notes.canonical_hash());
+        assert_eq!(
+            hash,
+            blake2_rfc::blake2b::blake2b(32, &[], &notes.canonical_encode()).as_bytes()
+        );
+    }
+
+    #[test]
+    fn corpus_records_signals() {
+        let dir = std::env::temp_dir().join("gtest_corpus_records_signals");
+        let _ = fs::remove_dir_all(&dir);
+
+        let system = System::new();
+        system.record_corpus(&dir);
+
+        let program_id = ProgramId::from(2);
+        let signal = StoredDispatch::new(
+            DispatchKind::Signal,
+            StoredMessage::new(
+                MessageId::from(1),
+                ProgramId::SYSTEM,
+                program_id,
+                b"SIGNAL".to_vec().try_into().unwrap(),
+                0,
+                Some(ReplyDetails::new(MessageId::from(3), 1)),
+            ),
+            None,
+        );
+
+        // The same signal is written once.
+        system.0.borrow_mut().record_corpus_entry(&signal);
+        system.0.borrow_mut().record_corpus_entry(&signal);
+
+        let entries: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|file| Entry::decode(&fs::read(file.unwrap().path()).unwrap()).unwrap())
+            .collect();
+        assert_eq!(
+            entries,
+            vec![Entry {
+                kind: EntryKind::Signal,
+                program_id: program_id.into(),
+                payload: b"SIGNAL".to_vec(),
+            }]
+        );
+        assert_eq!(system.0.borrow().corpus_written, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn program_survives_raw_dispatches() {
+        let system = System::new();
+        let program = Program::mock(&system, Echo);
+        program.send_bytes(USER, b"init");
+
+        let raw = |kind, source, details| RawDispatch {
+            kind,
+            source,
+            destination: program.id(),
+            payload: b"PING".to_vec(),
+            value: 0,
+            details,
+        };
+        let bogus = |code| Some(ReplyDetails::new(MessageId::from(42), code));
+
+        // Reply to the message the program never sent.
+        let res = system.inject_raw(raw(DispatchKind::Reply, USER.into(), bogus(0)));
+        assert!(!res.main_failed());
+
+        // Message claiming to be from the program, echoed back to it.
+        let res = system.inject_raw(raw(DispatchKind::Handle, program.id(), None));
+        assert!(!res.main_failed());
+        assert!(!res.others_failed());
+        assert_eq!(res.total_processed(), 2);
+
+        // Signal from the user about the unknown message.
+        let res = system.inject_raw(raw(DispatchKind::Signal, USER.into(), bogus(42)));
+        assert!(!res.main_failed());
+
+        assert!(system.is_active_program(program.id()));
+    }
+}
@@ -28,14 +28,16 @@ use gear_core::{
     env::Ext as ExtTrait,
     gas::{GasAllowanceCounter, GasCounter, ValueCounter},
     memory::{AllocationsContext, PageBuf, PageNumber, WasmPageNumber},
-    message::{IncomingMessage, MessageContext, Payload},
+    message::{DispatchKind, IncomingMessage, MessageContext, Payload},
     program::Program,
 };
 use std::{collections::BTreeMap, mem};
 use wasmi::{memory_units::Pages, MemoryInstance, MemoryRef, ModuleInstance, RuntimeValue};
 
 use crate::{
-    manager::ExtManager, Result, TestError, MAILBOX_THRESHOLD, RESERVE_FOR, WAITLIST_COST,
+    manager::{self, ExtManager},
+    Result, TestError, BLOCK_DURATION, BLOCK_HASH_DEPTH, MAILBOX_THRESHOLD, RESERVE_FOR,
+    WAITLIST_COST,
 };
 
 /// Binary meta-functions executor for testing purposes
@@ -107,6 +109,7 @@ impl WasmExecutor {
             err: FuncError::Terminated(TerminationReason::Success),
             memory: &mem,
             memory_wrap: &mut memory_wrap,
+            canaries: Default::default(),
         };
 
         let defined_host_functions = builder.defined_host_functions.clone();
@@ -164,17 +167,41 @@ impl WasmExecutor {
                 program.id(),
                 None,
             ),
+            dispatch_kind: DispatchKind::Handle,
             block_info: Default::default(),
+            block_duration: BLOCK_DURATION,
             config: Default::default(),
             existential_deposit: 0,
+            existential_deposit_policy: Default::default(),
             origin: Default::default(),
+            tx_hash: None,
+            execution_id: 0,
             program_id: Default::default(),
             program_candidates_data: Default::default(),
             host_fn_weights: Default::default(),
             forbidden_funcs: Default::default(),
             mailbox_threshold: MAILBOX_THRESHOLD,
             waitlist_cost: WAITLIST_COST,
+            reply_waker_cost: 0,
             reserve_for: RESERVE_FOR,
+            block_hash_depth: BLOCK_HASH_DEPTH,
+            block_hash: manager::block_hash,
+            stack_height: program.code().stack_height(),
+            program_exists: |_| false,
+            mailbox_contains: |_, _, _| false,
+            codes_count: || 0,
+            delayed_dispatch: |_, _| None,
+            delayed_dispatches: Default::default(),
+            value_reservations: Default::default(),
+            children_count: Default::default(),
+            children_gas_burned: |_| 0,
+            code_id_of: |_| None,
+            counters: None,
+            program_counters: |_| Default::default(),
+            scheduled_exit: None,
+            entry_budgets: Default::default(),
+            charge_allowance: None,
+            reply_waker: None,
         })
     }
 
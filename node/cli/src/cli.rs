@@ -28,6 +28,29 @@ pub struct RunCmd {
     /// Force using Vara native runtime.
     #[clap(long = "force-vara")]
     pub force_vara: bool,
+
+    /// Minimum level of logged program traces (`gr_trace`).
+    ///
+    /// Given either globally as `<level>` or for the program as
+    /// `<program_id>=<level>`, where `program_id` is a lowercase hex id
+    /// prefixed with `0x`. Can be specified multiple times.
+    ///
+    /// Levels are the ones of `--log`: error, warn, info, debug, trace.
+    #[clap(long = "program-trace", value_name = "[PROGRAM_ID=]LEVEL")]
+    pub program_trace: Vec<String>,
+}
+
+impl RunCmd {
+    /// Log directives enabling program traces configured with `--program-trace`.
+    pub fn program_trace_directives(&self) -> Vec<String> {
+        self.program_trace
+            .iter()
+            .map(|trace| match trace.split_once('=') {
+                Some((program_id, level)) => format!("gwasm::trace::{program_id}={level}"),
+                None => format!("gwasm::trace={trace}"),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Parser)]
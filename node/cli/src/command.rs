@@ -137,6 +137,14 @@ pub fn run() -> sc_cli::Result<()> {
         .execution
         .get_or_insert(ExecutionStrategy::Wasm);
 
+    // Program traces are logged under their own targets.
+    let program_trace_directives = cli.run.program_trace_directives();
+    cli.run
+        .base
+        .shared_params
+        .log
+        .extend(program_trace_directives);
+
     match &cli.subcommand {
         Some(Subcommand::Key(cmd)) => cmd.run(&cli),
         Some(Subcommand::BuildSpec(cmd)) => {
@@ -190,7 +190,8 @@ pub mod pallet {
             (*msg.payload()).to_vec().try_into().unwrap(),
             msg.value(),
             msg.reply(),
-        );
+        )
+        .with_metadata(msg.metadata().to_vec().try_into().unwrap());
 
         StoredDispatch::new(kind, message, context)
     }
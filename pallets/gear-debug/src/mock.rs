@@ -19,14 +19,14 @@
 use crate as pallet_gear_debug;
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{FindAuthor, OnFinalize, OnIdle, OnInitialize},
+    traits::{FindAuthor, GetDefault, OnFinalize, OnIdle, OnInitialize},
 };
 use frame_system as system;
 use pallet_gear_messenger::Weight;
 use primitive_types::H256;
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, ConstU64, IdentityLookup},
+    traits::{BlakeTwo256, ConstU128, ConstU32, ConstU64, IdentityLookup},
 };
 use sp_std::convert::{TryFrom, TryInto};
 
@@ -140,6 +140,12 @@ impl pallet_gear::Config for Test {
     type Schedule = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = ConstU64<3000>;
+    type ProgramExistentialDeposit = GetDefault;
+    type CodeStatsEraLength = ConstU64<600>;
+    type CodeStatsHistoryDepth = ConstU32<2>;
+    type MaxCodeStats = ConstU32<1024>;
+    type CodeStatsDigestLength = ConstU32<10>;
+    type CodeDepositPerByte = ConstU128<0>;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
@@ -148,6 +154,7 @@ impl pallet_gear::Config for Test {
 
 impl pallet_gear_messenger::Config for Test {
     type BlockLimiter = GearGas;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_scheduler::Config for Test {
@@ -155,6 +162,7 @@ impl pallet_gear_scheduler::Config for Test {
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
     type MailboxCost = ConstU64<100>;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_gas::Config for Test {
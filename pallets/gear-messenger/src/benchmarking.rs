@@ -0,0 +1,173 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+#[allow(unused)]
+use crate::Pallet as GearMessenger;
+use common::{
+    migration::v1,
+    storage::{Counted, DoubleMapStorage, Interval, LinkedNode, MapStorage, Messenger, Queue},
+    Origin,
+};
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_support::{storage_alias, Identity};
+use gear_core::{
+    ids::{MessageId, ProgramId},
+    message::{DispatchKind, StoredDispatch, StoredMessage},
+};
+use sp_std::{convert::TryInto, vec};
+
+// Payload length limit of the Gear pallet's default schedule.
+const MAX_PAYLOAD_LEN: u32 = 64 * 1024;
+
+// Length of the queue built for the import benchmark.
+const IMPORT_QUEUE_LEN: u32 = 10_000;
+
+// Amount of dispatches processed per block in the import benchmark.
+const IMPORT_DISPATCHES_PER_BLOCK: u32 = 100;
+
+// Payload length of the dispatches queued for the import benchmark.
+//
+// Only a fraction of the limit, so the whole queue fits in memory
+// of the benchmarking node.
+const IMPORT_PAYLOAD_LEN: u32 = MAX_PAYLOAD_LEN / 4;
+
+type QueueOf<T> = <Pallet<T> as Messenger>::Queue;
+
+// Waitlist in the layout of the version 1.
+#[storage_alias]
+type OldWaitlist<T: Config> = StorageDoubleMap<
+    Pallet<T>,
+    Identity,
+    ProgramId,
+    Identity,
+    MessageId,
+    (
+        v1::StoredDispatch,
+        Interval<<T as frame_system::Config>::BlockNumber>,
+    ),
+>;
+
+benchmarks! {
+    where_clause { where
+        T::AccountId: Origin,
+    }
+
+    // Block import dequeues a small part of a long queue of large
+    // messages. Payloads of the dispatches left in the queue aren't
+    // read, so the weight mustn't depend on the queue length.
+    //
+    // `n`: Amount of dispatches dequeued.
+    dequeue_dispatches {
+        let n in 0 .. IMPORT_DISPATCHES_PER_BLOCK;
+
+        for i in 0..IMPORT_QUEUE_LEN {
+            let message = StoredMessage::new(
+                MessageId::from(i as u64),
+                Default::default(),
+                Default::default(),
+                vec![0; IMPORT_PAYLOAD_LEN as usize].try_into().expect("Payload is within limit"),
+                0,
+                None,
+            );
+            QueueOf::<T>::queue(StoredDispatch::new(DispatchKind::Handle, message, None))
+                .expect("Failed to queue dispatch");
+        }
+    }: {
+        for _ in 0..n {
+            QueueOf::<T>::dequeue()
+                .expect("Queue is consistent")
+                .expect("Queue is long enough");
+        }
+    }
+    verify {
+        assert_eq!(QueueOf::<T>::len(), IMPORT_QUEUE_LEN - n);
+    }
+
+    move_dispatch_payloads {
+        let n in 0 .. migration::v2::DISPATCHES_PER_BLOCK;
+
+        for i in 0..n {
+            let id = MessageId::from(i as u64);
+            let message = StoredMessage::new(
+                id,
+                Default::default(),
+                Default::default(),
+                vec![0; MAX_PAYLOAD_LEN as usize].try_into().expect("Payload is within limit"),
+                0,
+                None,
+            );
+            let value = StoredDispatch::new(DispatchKind::Handle, message, None);
+            DispatchesWrap::<T>::insert(id, LinkedNode { next: None, value });
+        }
+
+        migration::v2::migrate::<T>();
+    }: {
+        migration::v2::step::<T>();
+    }
+    verify {
+        for i in 0..n {
+            assert!(DispatchPayloadsWrap::<T>::contains_key(&MessageId::from(i as u64)));
+        }
+    }
+
+    translate_messages {
+        let n in 0 .. migration::v3::MESSAGES_PER_BLOCK;
+
+        let program_id = ProgramId::from(1);
+
+        // Waitlisted dispatches are the largest messages to translate.
+        for i in 0..n {
+            let id = MessageId::from(i as u64);
+            let dispatch = v1::StoredDispatch {
+                kind: DispatchKind::Handle,
+                message: v1::StoredMessage {
+                    id,
+                    source: Default::default(),
+                    destination: program_id,
+                    payload: vec![0; MAX_PAYLOAD_LEN as usize]
+                        .try_into()
+                        .expect("Payload is within limit"),
+                    value: 0,
+                    reply: None,
+                },
+                context: None,
+            };
+            let interval = Interval {
+                start: 0u32.into(),
+                finish: 0u32.into(),
+            };
+            OldWaitlist::<T>::insert(program_id, id, (dispatch, interval));
+        }
+
+        migration::v3::migrate::<T>();
+    }: {
+        migration::v3::step::<T>();
+    }
+    verify {
+        for i in 0..n {
+            assert!(WaitlistWrap::<T>::get(&program_id, &MessageId::from(i as u64)).is_some());
+        }
+    }
+}
+
+impl_benchmark_test_suite!(
+    GearMessenger,
+    crate::mock::new_test_ext(),
+    crate::mock::Test
+);
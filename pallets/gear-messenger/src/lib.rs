@@ -137,6 +137,13 @@
 // Database migration module.
 pub mod migration;
 
+// Weights of the pallet's operations.
+pub mod weights;
+
+// Benchmarks of the pallet's operations.
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 // Runtime mock for running tests.
 #[cfg(test)]
 mod mock;
@@ -147,6 +154,7 @@ mod tests;
 
 // Public exports from pallet.
 pub use pallet::*;
+pub use weights::WeightInfo;
 
 // Gear Messenger Pallet module.
 #[frame_support::pallet]
@@ -160,18 +168,28 @@ pub mod pallet {
     use frame_system::{pallet_prelude::*, Pallet as SystemPallet};
     use gear_core::{
         ids::{MessageId, ProgramId},
-        message::{StoredDispatch, StoredMessage},
+        message::{Payload, StoredDispatch, StoredMessage},
     };
-    use sp_std::{convert::TryInto, marker::PhantomData};
+    use sp_std::{convert::TryInto, iter::Map, marker::PhantomData};
 
     /// The current storage version.
-    const MESSENGER_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+    ///
+    /// Version 2 stores queued dispatches' payloads apart from
+    /// the queue's elements (see `DispatchPayloads`). Payloads of
+    /// the elements queued before are moved within next blocks.
+    ///
+    /// Version 3 stores messages with metadata and reply destination.
+    /// Messages stored before are translated within next blocks.
+    const MESSENGER_STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
     // Gear Messenger Pallet's `Config`.
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// Block limits.
         type BlockLimiter: BlockLimiter<Balance = u64>;
+
+        /// Weight information for operations in this pallet.
+        type WeightInfo: crate::WeightInfo;
     }
 
     // Gear Messenger Pallet itself.
@@ -305,6 +323,9 @@ pub mod pallet {
     // ----
 
     // Private storage for queue's elements.
+    //
+    // Since storage version 2 elements are stored without payloads,
+    // so decoding them doesn't depend on the payload size.
     #[pallet::storage]
     type Dispatches<T> =
         CountedStorageMap<_, Identity, MessageId, LinkedNode<MessageId, StoredDispatch>>;
@@ -320,6 +341,23 @@ pub mod pallet {
 
     // ----
 
+    // Private storage for payloads of queue's elements.
+    //
+    // Contains only non-empty payloads, loaded when
+    // the element is dequeued.
+    #[pallet::storage]
+    type DispatchPayloads<T> = StorageMap<_, Identity, MessageId, Payload>;
+
+    // Public wrap of the payloads of queue's elements.
+    common::wrap_storage_map!(
+        storage: DispatchPayloads,
+        name: DispatchPayloadsWrap,
+        key: MessageId,
+        value: Payload
+    );
+
+    // ----
+
     // Private storage for queue's head key.
     #[pallet::storage]
     type Head<T> = StorageValue<_, MessageId>;
@@ -350,6 +388,32 @@ pub mod pallet {
         length: usize
     );
 
+    // `IterableByKeyFromMap` implementation for the mailbox elements.
+    //
+    // Message ids are stored with `Identity` hasher,
+    // so elements are iterated in the order of ids.
+    impl<T: crate::Config> IterableByKeyFromMap<(StoredMessage, Interval<T::BlockNumber>)>
+        for MailboxWrap<T>
+    {
+        type Key1 = T::AccountId;
+        type Key2 = MessageId;
+        type Iter = IteratorWrap<
+            PrefixIterator<(MessageId, (StoredMessage, Interval<T::BlockNumber>))>,
+            (StoredMessage, Interval<T::BlockNumber>),
+            GetSecondPos,
+        >;
+
+        fn iter_key_from(key1: Self::Key1, key2: Option<Self::Key2>) -> Self::Iter {
+            match key2 {
+                Some(key2) => {
+                    let starting_raw_key = Mailbox::<T>::hashed_key_for(&key1, key2);
+                    Mailbox::<T>::iter_prefix_from(key1, starting_raw_key).into()
+                }
+                None => Mailbox::<T>::iter_prefix(key1).into(),
+            }
+        }
+    }
+
     // ----
 
     // Private storage for queue processing flag.
@@ -476,6 +540,184 @@ pub mod pallet {
 
     // ----
 
+    // Below goes queue implementation, storing payloads
+    // of queued dispatches separately from the dequeue's nodes.
+
+    // ----
+
+    /// Queue of dispatches' headers, based on `Dequeue`.
+    type HeadersQueue<T> = QueueImpl<
+        DequeueImpl<
+            MessageId,
+            StoredDispatch,
+            Error<T>,
+            HeadWrap<T>,
+            TailWrap<T>,
+            DispatchesWrap<T>,
+            QueueCallbacks<T>,
+        >,
+        DispatchError,
+        QueueKeyGen,
+    >;
+
+    /// Queue item joining function type.
+    type JoinFn =
+        fn(Result<StoredDispatch, DispatchError>) -> Result<StoredDispatch, DispatchError>;
+
+    /// Message queue, which keeps dispatches' payloads apart
+    /// from the rest of dispatches' data.
+    ///
+    /// Only non-empty payloads are stored separately. Dispatch without
+    /// separately stored payload keeps its own one, which makes elements
+    /// stored in storage version 1 still valid.
+    pub struct PayloadSplitQueue<T: crate::Config>(PhantomData<T>)
+    where
+        T::AccountId: Origin;
+
+    impl<T: crate::Config> PayloadSplitQueue<T>
+    where
+        T::AccountId: Origin,
+    {
+        /// Moves payload out of the dispatch into separate storage.
+        fn split(mut dispatch: StoredDispatch) -> StoredDispatch {
+            let payload = dispatch.take_payload();
+
+            if !payload.get().is_empty() {
+                DispatchPayloadsWrap::<T>::insert(dispatch.id(), payload);
+            }
+
+            dispatch
+        }
+
+        /// Restores payload of the dispatch from separate storage, removing it.
+        fn join(mut dispatch: StoredDispatch) -> StoredDispatch {
+            if let Some(payload) = DispatchPayloadsWrap::<T>::take(dispatch.id()) {
+                dispatch.set_payload(payload);
+            }
+
+            dispatch
+        }
+
+        /// Restores payload of the dispatch from separate storage, keeping it.
+        fn join_copy(mut dispatch: StoredDispatch) -> StoredDispatch {
+            if let Some(payload) = DispatchPayloadsWrap::<T>::get(&dispatch.id()) {
+                dispatch.set_payload(payload);
+            }
+
+            dispatch
+        }
+
+        /// Inserts dispatch with given inserting function,
+        /// storing its payload only on success.
+        fn insert_with(
+            mut dispatch: StoredDispatch,
+            insert: fn(StoredDispatch) -> Result<(), DispatchError>,
+        ) -> Result<(), DispatchError> {
+            let payload = dispatch.take_payload();
+            let id = dispatch.id();
+
+            insert(dispatch)?;
+
+            if !payload.get().is_empty() {
+                DispatchPayloadsWrap::<T>::insert(id, payload);
+            }
+
+            Ok(())
+        }
+    }
+
+    // Implementation of `Queue` for `PayloadSplitQueue`.
+    impl<T: crate::Config> Queue for PayloadSplitQueue<T>
+    where
+        T::AccountId: Origin,
+    {
+        type Value = StoredDispatch;
+        type Error = Error<T>;
+        type OutputError = DispatchError;
+
+        fn dequeue() -> Result<Option<Self::Value>, Self::OutputError> {
+            HeadersQueue::<T>::dequeue().map(|maybe_dispatch| maybe_dispatch.map(Self::join))
+        }
+
+        fn mutate_values<F: FnMut(Self::Value) -> Self::Value>(mut f: F) {
+            HeadersQueue::<T>::mutate_values(|dispatch| Self::split(f(Self::join(dispatch))))
+        }
+
+        fn queue(value: Self::Value) -> Result<(), Self::OutputError> {
+            Self::insert_with(value, HeadersQueue::<T>::queue)
+        }
+
+        fn clear() {
+            HeadersQueue::<T>::clear();
+            DispatchPayloadsWrap::<T>::clear();
+        }
+
+        fn requeue(value: Self::Value) -> Result<(), Self::OutputError> {
+            Self::insert_with(value, HeadersQueue::<T>::requeue)
+        }
+    }
+
+    // Implementation of `Counted` for `PayloadSplitQueue`.
+    impl<T: crate::Config> Counted for PayloadSplitQueue<T>
+    where
+        T::AccountId: Origin,
+    {
+        type Length = Capacity;
+
+        fn len() -> Self::Length {
+            HeadersQueue::<T>::len()
+        }
+    }
+
+    // `IterableMap` implementation for `PayloadSplitQueue`.
+    //
+    // Draining removes separately stored payloads as well,
+    // while common iteration only reads them.
+    impl<T: crate::Config> IterableMap<Result<StoredDispatch, DispatchError>> for PayloadSplitQueue<T>
+    where
+        T::AccountId: Origin,
+    {
+        type DrainIter = Map<
+            <HeadersQueue<T> as IterableMap<Result<StoredDispatch, DispatchError>>>::DrainIter,
+            JoinFn,
+        >;
+        type Iter = Map<
+            <HeadersQueue<T> as IterableMap<Result<StoredDispatch, DispatchError>>>::Iter,
+            JoinFn,
+        >;
+
+        fn drain() -> Self::DrainIter {
+            let join: JoinFn = |res| res.map(Self::join);
+            HeadersQueue::<T>::drain().map(join)
+        }
+
+        fn iter() -> Self::Iter {
+            let join: JoinFn = |res| res.map(Self::join_copy);
+            HeadersQueue::<T>::iter().map(join)
+        }
+    }
+
+    // `IterableFromMap` implementation for `PayloadSplitQueue`,
+    // reading separately stored payloads as common iteration does.
+    impl<T: crate::Config> IterableFromMap<Result<StoredDispatch, DispatchError>>
+        for PayloadSplitQueue<T>
+    where
+        T::AccountId: Origin,
+    {
+        type Key = MessageId;
+        type Iter = Map<
+            <HeadersQueue<T> as IterableFromMap<Result<StoredDispatch, DispatchError>>>::Iter,
+            JoinFn,
+        >;
+
+        fn iter_from(key: Option<Self::Key>) -> Self::Iter {
+            let join: JoinFn = |res| res.map(Self::join_copy);
+            HeadersQueue::<T>::iter_from(key).map(join)
+        }
+    }
+
+    // ----
+
     // Below goes callbacks, used for mailbox algorithm.
     //
     // Note, that they are public like storage wrappers
@@ -571,6 +813,7 @@ pub mod pallet {
         type MailboxFirstKey = T::AccountId;
         type MailboxSecondKey = MessageId;
         type MailboxedMessage = StoredMessage;
+        type QueueKey = MessageId;
         type QueuedDispatch = StoredDispatch;
         type WaitlistFirstKey = ProgramId;
         type WaitlistSecondKey = MessageId;
@@ -582,19 +825,7 @@ pub mod pallet {
 
         type QueueProcessing = TogglerImpl<QueueProcessingWrap<T>>;
 
-        type Queue = QueueImpl<
-            DequeueImpl<
-                MessageId,
-                Self::QueuedDispatch,
-                Self::Error,
-                HeadWrap<T>,
-                TailWrap<T>,
-                DispatchesWrap<T>,
-                QueueCallbacks<T>,
-            >,
-            DispatchError,
-            QueueKeyGen,
-        >;
+        type Queue = PayloadSplitQueue<T>;
 
         type Mailbox = MailboxImpl<
             MailboxWrap<T>,
@@ -615,6 +846,10 @@ pub mod pallet {
             WaitListCallbacks<T>,
             WaitlistKeyGen,
         >;
+
+        fn migrating() -> bool {
+            crate::migration::v3::in_progress::<T>()
+        }
     }
 
     // Gear Messenger Pallet hooks.
@@ -627,6 +862,11 @@ pub mod pallet {
     where
         T::AccountId: Origin,
     {
+        /// Runtime upgrade.
+        fn on_runtime_upgrade() -> Weight {
+            crate::migration::migrate::<T>()
+        }
+
         /// Block initialization.
         fn on_initialize(_bn: BlockNumberFor<T>) -> Weight {
             // Amount of weight used for initialization.
@@ -650,6 +890,10 @@ pub mod pallet {
             <Self as Messenger>::QueueProcessing::allow();
             weight += T::DbWeight::get().writes(1);
 
+            // Continue migrations of the messages stored before
+            // the upgrade, if any are left.
+            weight += crate::migration::step::<T>();
+
             weight
         }
     }
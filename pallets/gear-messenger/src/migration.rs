@@ -18,14 +18,343 @@
 
 //! Database migration module.
 
-use crate::{Config, Pallet, Weight};
+use crate::{Config, DispatchPayloadsWrap, Pallet, Weight};
+use common::storage::{Interval, LinkedNode, MapStorage};
+use frame_support::{
+    storage_alias,
+    traits::{Get, StorageVersion},
+    Identity,
+};
+use gear_core::ids::{MessageId, ProgramId};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
 pub fn migrate<T: Config>() -> Weight {
-    use frame_support::traits::StorageVersion;
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = T::DbWeight::get().reads(1);
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = Weight::zero();
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
+
+    if version < 3 {
+        weight = weight.saturating_add(v3::migrate::<T>());
+    }
+
+    weight
+}
+
+/// Continues migrations started by the upgrade, returning consumed weight.
+///
+/// Payloads are moved out of the queue's elements once
+/// they are translated into the current layout.
+pub fn step<T: Config>() -> Weight {
+    let mut weight: Weight = T::DbWeight::get().reads(1);
+
+    if v3::in_progress::<T>() {
+        weight = weight.saturating_add(v3::step::<T>());
+    } else {
+        weight = weight.saturating_add(v2::step::<T>());
+    }
 
     weight
 }
+
+/// Migration to the storage version 2.
+///
+/// Moves non-empty payloads of queued dispatches out of
+/// the queue's elements into separate storage.
+///
+/// The queue reads elements with inline payloads as well, so payloads
+/// are moved over several blocks: the upgrade only starts the migration,
+/// and each block initialization moves payloads of up to
+/// [`DISPATCHES_PER_BLOCK`](v2::DISPATCHES_PER_BLOCK) elements.
+pub mod v2 {
+    use super::*;
+    use crate::WeightInfo;
+    use codec::Decode;
+    use frame_support::storage::{unhashed, StoragePrefixedMap};
+    use gear_core::message::StoredDispatch;
+    use sp_std::vec::Vec;
+
+    /// Maximal amount of the queue's elements visited within the block.
+    pub const DISPATCHES_PER_BLOCK: u32 = 64;
+
+    // Queue's elements.
+    //
+    // Payloads are moved once the version 3 migration is over, so elements
+    // are visited in the current layout, with payloads still inline.
+    #[storage_alias]
+    type Dispatches<T: Config> =
+        StorageMap<Pallet<T>, Identity, MessageId, LinkedNode<MessageId, StoredDispatch>>;
+
+    // Raw key of the last visited element, present
+    // while the migration is in progress.
+    #[storage_alias]
+    type DispatchPayloadsCursor<T: Config> = StorageValue<Pallet<T>, Vec<u8>>;
+
+    /// Starts migration to the version 2, returning consumed weight.
+    pub fn migrate<T: Config>() -> Weight {
+        DispatchPayloadsCursor::<T>::put(Dispatches::<T>::final_prefix().to_vec());
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!(
+            target: "runtime::gear",
+            "Gear Messenger migrated to storage version 2: payloads of queued dispatches are moved within next blocks"
+        );
+
+        T::DbWeight::get().writes(2)
+    }
+
+    /// Tell whether payloads of queued dispatches are still being moved.
+    pub fn in_progress<T: Config>() -> bool {
+        DispatchPayloadsCursor::<T>::exists()
+    }
+
+    /// Moves payloads of the next queue's elements, returning consumed weight.
+    ///
+    /// Elements, which can't be decoded, are logged and left untouched.
+    pub fn step<T: Config>() -> Weight {
+        let mut cursor = match DispatchPayloadsCursor::<T>::get() {
+            Some(cursor) => cursor,
+            None => return T::DbWeight::get().reads(1),
+        };
+
+        let prefix = Dispatches::<T>::final_prefix();
+        let mut visited = 0;
+
+        while visited < DISPATCHES_PER_BLOCK {
+            let key = match sp_io::storage::next_key(&cursor).filter(|key| key.starts_with(&prefix))
+            {
+                Some(key) => key,
+                None => {
+                    DispatchPayloadsCursor::<T>::kill();
+
+                    log::info!(
+                        target: "runtime::gear",
+                        "Gear Messenger moved payloads of all queued dispatches"
+                    );
+
+                    return T::WeightInfo::move_dispatch_payloads(visited);
+                }
+            };
+
+            visited += 1;
+
+            let node = unhashed::get_raw(&key)
+                .map(|raw| LinkedNode::<MessageId, StoredDispatch>::decode(&mut raw.as_ref()));
+
+            match node {
+                Some(Ok(mut node)) => {
+                    let payload = node.value.take_payload();
+
+                    if !payload.get().is_empty() {
+                        DispatchPayloadsWrap::<T>::insert(node.value.id(), payload);
+                        unhashed::put(&key, &node);
+                    }
+                }
+                _ => log::error!(
+                    target: "runtime::gear",
+                    "Queued dispatch at {:?} can't be decoded: its payload is left in place",
+                    key
+                ),
+            }
+
+            cursor = key;
+        }
+
+        DispatchPayloadsCursor::<T>::put(cursor);
+
+        T::WeightInfo::move_dispatch_payloads(visited)
+    }
+}
+
+/// Migration to the storage version 3.
+///
+/// Translates queued, mailboxed and waitlisted messages into
+/// the layout with metadata and reply destination. Messages stored
+/// before get empty metadata and reply to their source.
+///
+/// Messages of the old layout can't be read, so they are translated over
+/// several blocks, while the messenger reports [`migrating`](v3::in_progress)
+/// and no messages or tasks are processed: the upgrade only starts the
+/// migration, and each block initialization translates up to
+/// [`MESSAGES_PER_BLOCK`](v3::MESSAGES_PER_BLOCK) messages.
+pub mod v3 {
+    use super::*;
+    use crate::WeightInfo;
+    use codec::{Decode, DecodeAll, Encode};
+    use common::migration::v1;
+    use frame_support::storage::{unhashed, StoragePrefixedMap};
+    use gear_core::message::{StoredDispatch, StoredMessage};
+    use sp_std::vec::Vec;
+
+    /// Maximal amount of messages visited within the block.
+    pub const MESSAGES_PER_BLOCK: u32 = 64;
+
+    // Storages of the pallet in the current layout.
+    //
+    // Queue is declared as plain map: the counter of the counted map
+    // isn't affected by the migration.
+    #[storage_alias]
+    type Dispatches<T: Config> =
+        StorageMap<Pallet<T>, Identity, MessageId, LinkedNode<MessageId, StoredDispatch>>;
+
+    #[storage_alias]
+    type Mailbox<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Identity,
+        T::AccountId,
+        Identity,
+        MessageId,
+        (StoredMessage, Interval<T::BlockNumber>),
+    >;
+
+    #[storage_alias]
+    type Waitlist<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Identity,
+        ProgramId,
+        Identity,
+        MessageId,
+        (StoredDispatch, Interval<T::BlockNumber>),
+    >;
+
+    // Raw key of the last visited message, present
+    // while the migration is in progress.
+    #[storage_alias]
+    type MessagesCursor<T: Config> = StorageValue<Pallet<T>, Vec<u8>>;
+
+    // Prefixes of the storages in the order they are migrated.
+    fn prefixes<T: Config>() -> [[u8; 32]; 3] {
+        [
+            Dispatches::<T>::final_prefix(),
+            Mailbox::<T>::final_prefix(),
+            Waitlist::<T>::final_prefix(),
+        ]
+    }
+
+    /// Starts migration to the version 3, returning consumed weight.
+    pub fn migrate<T: Config>() -> Weight {
+        MessagesCursor::<T>::put(Dispatches::<T>::final_prefix().to_vec());
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        log::info!(
+            target: "runtime::gear",
+            "Gear Messenger migrated to storage version 3: messages are translated within next blocks"
+        );
+
+        T::DbWeight::get().writes(2)
+    }
+
+    /// Tell whether messages are still being translated.
+    pub fn in_progress<T: Config>() -> bool {
+        MessagesCursor::<T>::exists()
+    }
+
+    /// Translates the next messages, returning consumed weight.
+    ///
+    /// Messages, which can't be decoded in the old layout, are logged
+    /// and left untouched. Messages, which are decoded in the current
+    /// layout, were stored after the upgrade and are skipped.
+    pub fn step<T: Config>() -> Weight {
+        let mut cursor = match MessagesCursor::<T>::get() {
+            Some(cursor) => cursor,
+            None => return T::DbWeight::get().reads(1),
+        };
+
+        let prefixes = prefixes::<T>();
+        let mut stage = prefixes
+            .iter()
+            .position(|prefix| cursor.starts_with(prefix))
+            .unwrap_or(prefixes.len());
+        let mut visited = 0;
+
+        while visited < MESSAGES_PER_BLOCK && stage < prefixes.len() {
+            let prefix = &prefixes[stage];
+
+            let key = match sp_io::storage::next_key(&cursor).filter(|key| key.starts_with(prefix))
+            {
+                Some(key) => key,
+                None => {
+                    // Next storage is visited from its beginning.
+                    stage += 1;
+
+                    if let Some(prefix) = prefixes.get(stage) {
+                        cursor = prefix.to_vec();
+                    }
+
+                    continue;
+                }
+            };
+
+            visited += 1;
+
+            let translated = match stage {
+                0 => translate::<LinkedNode<MessageId, v1::StoredDispatch>, _>(&key, |node| {
+                    LinkedNode::<MessageId, StoredDispatch> {
+                        next: node.next,
+                        value: node.value.into(),
+                    }
+                }),
+                1 => translate::<(v1::StoredMessage, Interval<T::BlockNumber>), _>(
+                    &key,
+                    |(message, interval)| -> (StoredMessage, _) { (message.into(), interval) },
+                ),
+                _ => translate::<(v1::StoredDispatch, Interval<T::BlockNumber>), _>(
+                    &key,
+                    |(dispatch, interval)| -> (StoredDispatch, _) { (dispatch.into(), interval) },
+                ),
+            };
+
+            if !translated {
+                log::error!(
+                    target: "runtime::gear",
+                    "Message at {:?} can't be decoded: it's left untouched",
+                    key
+                );
+            }
+
+            cursor = key;
+        }
+
+        if stage < prefixes.len() {
+            MessagesCursor::<T>::put(cursor);
+        } else {
+            MessagesCursor::<T>::kill();
+
+            log::info!(
+                target: "runtime::gear",
+                "Gear Messenger translated all messages into the current layout"
+            );
+        }
+
+        T::WeightInfo::translate_messages(visited)
+    }
+
+    // Translates the entry at `key` from the old layout, returning
+    // whether it's in the current layout now.
+    fn translate<Old, New>(key: &[u8], f: impl FnOnce(Old) -> New) -> bool
+    where
+        Old: Decode,
+        New: Decode + Encode,
+    {
+        let raw = match unhashed::get_raw(key) {
+            Some(raw) => raw,
+            None => return false,
+        };
+
+        if New::decode_all(&mut raw.as_ref()).is_ok() {
+            return true;
+        }
+
+        match Old::decode_all(&mut raw.as_ref()) {
+            Ok(old) => {
+                unhashed::put(key, &f(old));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
@@ -100,6 +100,7 @@ impl system::Config for Test {
 
 impl pallet_gear_messenger::Config for Test {
     type BlockLimiter = GearGas;
+    type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
@@ -20,7 +20,7 @@
 
 use super::*;
 use crate::mock::*;
-use common::storage::*;
+use common::{migration::v1, storage::*};
 use gear_core::{
     ids::MessageId,
     message::{DispatchKind, StoredDispatch, StoredMessage},
@@ -299,3 +299,401 @@ fn queue_works() {
         assert_eq!(QueueOf::len(), 1);
     });
 }
+
+// Dispatch constructor with given payload.
+fn dispatch_with_payload(id: MessageId, payload: Vec<u8>) -> StoredDispatch {
+    StoredDispatch::new(
+        DispatchKind::Handle,
+        StoredMessage::new(
+            id,
+            Default::default(),
+            Default::default(),
+            payload.try_into().expect("Payload is too large"),
+            Default::default(),
+            Default::default(),
+        ),
+        None,
+    )
+}
+
+#[test]
+fn queue_stores_payloads_separately() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let id_1: MessageId = 1.into();
+        let id_2: MessageId = 2.into();
+        let id_3: MessageId = 3.into();
+
+        QueueOf::queue(dispatch_with_payload(id_1, vec![1; 1024])).expect("Algorithmic error");
+        QueueOf::queue(dispatch_with_payload(id_2, vec![])).expect("Algorithmic error");
+        QueueOf::queue(dispatch_with_payload(id_3, vec![3, 3, 3])).expect("Algorithmic error");
+
+        assert_eq!(QueueOf::len(), 3);
+
+        // Queue's elements don't contain payloads.
+        for id in [id_1, id_2, id_3] {
+            let node = DispatchesWrap::<Test>::get(&id).expect("Element not found");
+            assert!(node.value.payload().is_empty());
+        }
+
+        // Only non-empty payloads are stored.
+        assert!(DispatchPayloadsWrap::<Test>::contains_key(&id_1));
+        assert!(!DispatchPayloadsWrap::<Test>::contains_key(&id_2));
+        assert!(DispatchPayloadsWrap::<Test>::contains_key(&id_3));
+
+        // Iteration doesn't remove payloads.
+        let payloads: Vec<Vec<u8>> = QueueOf::iter()
+            .map(|res| res.expect("Algorithmic error").payload().to_vec())
+            .collect();
+
+        assert_eq!(payloads, vec![vec![1; 1024], vec![], vec![3, 3, 3]]);
+        assert!(DispatchPayloadsWrap::<Test>::contains_key(&id_1));
+
+        // Dequeue joins payload back and removes it from storage.
+        let dispatch_1 = QueueOf::dequeue()
+            .expect("Algorithmic error")
+            .expect("No dispatches found");
+
+        assert_eq!(dispatch_1.id(), id_1);
+        assert_eq!(dispatch_1.payload(), &[1; 1024][..]);
+        assert!(!DispatchPayloadsWrap::<Test>::contains_key(&id_1));
+
+        // Requeue splits payload again.
+        QueueOf::requeue(dispatch_1).expect("Algorithmic error");
+
+        assert!(DispatchPayloadsWrap::<Test>::contains_key(&id_1));
+
+        // Failed insertion doesn't override stored payload.
+        assert!(QueueOf::queue(dispatch_with_payload(id_3, vec![4])).is_err());
+
+        assert_eq!(
+            DispatchPayloadsWrap::<Test>::get(&id_3).map(|p| p.get().to_vec()),
+            Some(vec![3, 3, 3])
+        );
+
+        // Mutation keeps payloads apart.
+        QueueOf::mutate_values(|mut dispatch| {
+            let mut payload = dispatch.payload().to_vec();
+            payload.push(0);
+            dispatch.set_payload(payload.try_into().expect("Payload is too large"));
+            dispatch
+        });
+
+        let node = DispatchesWrap::<Test>::get(&id_2).expect("Element not found");
+        assert!(node.value.payload().is_empty());
+
+        // Draining removes all payloads.
+        let payloads: Vec<Vec<u8>> = QueueOf::drain()
+            .map(|res| res.expect("Algorithmic error").payload().to_vec())
+            .collect();
+
+        let mut expected_1 = vec![1; 1024];
+        expected_1.push(0);
+
+        assert_eq!(payloads, vec![expected_1, vec![0], vec![3, 3, 3, 0]]);
+        assert!(QueueOf::is_empty());
+        assert!(!DispatchPayloadsWrap::<Test>::contains_key(&id_1));
+        assert!(!DispatchPayloadsWrap::<Test>::contains_key(&id_2));
+        assert!(!DispatchPayloadsWrap::<Test>::contains_key(&id_3));
+    });
+}
+
+// Storages of the pallet in the layout of the version 1.
+mod v1_storage {
+    use super::*;
+    use common::migration::v1::{StoredDispatch, StoredMessage};
+    use frame_support::{storage_alias, Identity};
+    use gear_core::ids::ProgramId;
+
+    #[storage_alias]
+    pub type Dispatches<T: Config> =
+        StorageMap<Pallet<T>, Identity, MessageId, LinkedNode<MessageId, StoredDispatch>>;
+
+    #[storage_alias]
+    pub type Mailbox<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Identity,
+        T::AccountId,
+        Identity,
+        MessageId,
+        (StoredMessage, Interval<T::BlockNumber>),
+    >;
+
+    #[storage_alias]
+    pub type Waitlist<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Identity,
+        ProgramId,
+        Identity,
+        MessageId,
+        (StoredDispatch, Interval<T::BlockNumber>),
+    >;
+}
+
+fn v1_message_with_payload(id: MessageId, payload: Vec<u8>) -> v1::StoredMessage {
+    v1::StoredMessage {
+        id,
+        source: 42.into(),
+        destination: Default::default(),
+        payload: payload.try_into().expect("Payload is too large"),
+        value: Default::default(),
+        reply: None,
+    }
+}
+
+fn v1_dispatch_with_payload(id: MessageId, payload: Vec<u8>) -> v1::StoredDispatch {
+    v1::StoredDispatch {
+        kind: DispatchKind::Handle,
+        message: v1_message_with_payload(id, payload),
+        context: None,
+    }
+}
+
+#[test]
+fn queue_migrates_to_separate_payloads() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let id_1: MessageId = 1.into();
+        let id_2: MessageId = 2.into();
+
+        // Elements stored in version 1 format, with inline payloads.
+        StorageVersion::new(1).put::<Pallet<Test>>();
+
+        v1_storage::Dispatches::<Test>::insert(
+            id_1,
+            LinkedNode {
+                next: Some(id_2),
+                value: v1_dispatch_with_payload(id_1, vec![1, 2, 3]),
+            },
+        );
+        v1_storage::Dispatches::<Test>::insert(
+            id_2,
+            LinkedNode {
+                next: None,
+                value: v1_dispatch_with_payload(id_2, vec![4, 5, 6]),
+            },
+        );
+        HeadWrap::<Test>::put(id_1);
+        TailWrap::<Test>::put(id_2);
+
+        crate::migration::migrate::<Test>();
+
+        assert_eq!(Pallet::<Test>::on_chain_storage_version(), 3);
+        assert!(<Pallet<Test> as Messenger>::migrating());
+
+        // Next block translates elements into the current layout,
+        // keeping payloads inline.
+        crate::migration::step::<Test>();
+
+        assert!(!<Pallet<Test> as Messenger>::migrating());
+        assert!(migration::v2::in_progress::<Test>());
+
+        let node = DispatchesWrap::<Test>::get(&id_2).expect("Element not found");
+        assert_eq!(node.value.payload(), &[4, 5, 6][..]);
+
+        // Not moved payloads are still readable.
+        let payloads: Vec<Vec<u8>> = QueueOf::iter()
+            .map(|res| res.expect("Algorithmic error").payload().to_vec())
+            .collect();
+
+        assert_eq!(payloads, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        // Next block moves payloads out of the elements.
+        crate::migration::step::<Test>();
+
+        assert!(!migration::v2::in_progress::<Test>());
+
+        let node = DispatchesWrap::<Test>::get(&id_2).expect("Element not found");
+        assert!(node.value.payload().is_empty());
+        assert!(DispatchPayloadsWrap::<Test>::contains_key(&id_2));
+
+        let dispatch_1 = QueueOf::dequeue()
+            .expect("Algorithmic error")
+            .expect("No dispatches found");
+
+        assert_eq!(dispatch_1.payload(), &[1, 2, 3][..]);
+        assert!(dispatch_1.metadata().is_empty());
+        assert_eq!(dispatch_1.reply_destination(), 42.into());
+
+        let dispatch_2 = QueueOf::dequeue()
+            .expect("Algorithmic error")
+            .expect("No dispatches found");
+
+        assert_eq!(dispatch_2.payload(), &[4, 5, 6][..]);
+        assert!(!DispatchPayloadsWrap::<Test>::contains_key(&id_2));
+        assert!(QueueOf::is_empty());
+    });
+}
+
+#[test]
+fn queue_payloads_moved_in_bounded_steps() {
+    use frame_support::storage::{unhashed, StoragePrefixedMap};
+    use migration::v2::DISPATCHES_PER_BLOCK;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let ids: Vec<MessageId> = (1..=DISPATCHES_PER_BLOCK as u64 + 1)
+            .map(Into::into)
+            .collect();
+
+        for &id in &ids {
+            DispatchesWrap::<Test>::insert(
+                id,
+                LinkedNode {
+                    next: None,
+                    value: dispatch_with_payload(id, vec![1, 2, 3]),
+                },
+            );
+        }
+
+        // Element, which can't be decoded, is left untouched.
+        let corrupted_key = [
+            v1_storage::Dispatches::<Test>::final_prefix().as_ref(),
+            MessageId::from(0).as_ref(),
+        ]
+        .concat();
+        unhashed::put_raw(&corrupted_key, &[0xff]);
+
+        migration::v2::migrate::<Test>();
+
+        migration::v2::step::<Test>();
+
+        assert!(migration::v2::in_progress::<Test>());
+        assert_eq!(
+            ids.iter()
+                .filter(|id| DispatchPayloadsWrap::<Test>::contains_key(id))
+                .count(),
+            DISPATCHES_PER_BLOCK as usize - 1
+        );
+
+        migration::v2::step::<Test>();
+
+        assert!(!migration::v2::in_progress::<Test>());
+        assert!(ids
+            .iter()
+            .all(|id| DispatchPayloadsWrap::<Test>::contains_key(id)));
+        assert_eq!(unhashed::get_raw(&corrupted_key), Some(vec![0xff]));
+    });
+}
+
+#[test]
+fn mailbox_and_waitlist_migrate_to_layout_with_metadata() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let user_id = 1;
+        let program_id = 2.into();
+        let id_1: MessageId = 1.into();
+        let id_2: MessageId = 2.into();
+        let interval = Interval {
+            start: 1,
+            finish: 10,
+        };
+
+        StorageVersion::new(2).put::<Pallet<Test>>();
+
+        v1_storage::Mailbox::<Test>::insert(
+            user_id,
+            id_1,
+            (
+                v1_message_with_payload(id_1, vec![1, 2, 3]),
+                interval.clone(),
+            ),
+        );
+        v1_storage::Waitlist::<Test>::insert(
+            program_id,
+            id_2,
+            (
+                v1_dispatch_with_payload(id_2, vec![4, 5, 6]),
+                interval.clone(),
+            ),
+        );
+
+        crate::migration::migrate::<Test>();
+
+        assert_eq!(Pallet::<Test>::on_chain_storage_version(), 3);
+
+        crate::migration::step::<Test>();
+
+        assert!(!<Pallet<Test> as Messenger>::migrating());
+
+        let (message, mailboxed_interval) =
+            MailboxWrap::<Test>::get(&user_id, &id_1).expect("Message not found");
+
+        assert_eq!(message.payload(), &[1, 2, 3][..]);
+        assert!(message.metadata().is_empty());
+        assert_eq!(message.reply_destination(), 42.into());
+        assert_eq!(mailboxed_interval, interval);
+
+        let (dispatch, waitlisted_interval) =
+            WaitlistWrap::<Test>::get(&program_id, &id_2).expect("Dispatch not found");
+
+        assert_eq!(dispatch.payload(), &[4, 5, 6][..]);
+        assert!(dispatch.metadata().is_empty());
+        assert_eq!(dispatch.reply_destination(), 42.into());
+        assert_eq!(waitlisted_interval, interval);
+    });
+}
+
+#[test]
+fn messages_translated_in_bounded_steps() {
+    use migration::v3::MESSAGES_PER_BLOCK;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let program_id = 2.into();
+        let interval = Interval {
+            start: 1,
+            finish: 10,
+        };
+
+        let ids: Vec<MessageId> = (1..=MESSAGES_PER_BLOCK as u64 + 1)
+            .map(Into::into)
+            .collect();
+
+        for &id in &ids {
+            v1_storage::Waitlist::<Test>::insert(
+                program_id,
+                id,
+                (
+                    v1_dispatch_with_payload(id, vec![1, 2, 3]),
+                    interval.clone(),
+                ),
+            );
+        }
+
+        migration::v3::migrate::<Test>();
+
+        // Message stored after the upgrade is already in the current layout.
+        let id: MessageId = 0.into();
+        WaitlistWrap::<Test>::insert(
+            program_id,
+            id,
+            (dispatch_with_payload(id, vec![4, 5, 6]), interval.clone()),
+        );
+
+        migration::v3::step::<Test>();
+
+        assert!(migration::v3::in_progress::<Test>());
+        assert_eq!(
+            ids.iter()
+                .filter(|id| WaitlistWrap::<Test>::get(&program_id, id).is_some())
+                .count(),
+            MESSAGES_PER_BLOCK as usize - 1
+        );
+
+        migration::v3::step::<Test>();
+
+        assert!(!migration::v3::in_progress::<Test>());
+        assert!(ids
+            .iter()
+            .all(|id| WaitlistWrap::<Test>::get(&program_id, id).is_some()));
+
+        let (dispatch, _) =
+            WaitlistWrap::<Test>::get(&program_id, &id).expect("Dispatch not found");
+        assert_eq!(dispatch.payload(), &[4, 5, 6][..]);
+    });
+}
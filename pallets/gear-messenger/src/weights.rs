@@ -0,0 +1,91 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Weights for pallet_gear_messenger
+//!
+//! NOT GENERATED YET: the weights below count storage accesses only.
+//! They have to be replaced with the output of the `pallet_gear_messenger`
+//! benchmarks, see scripts/benchmarking/run_all_benchmarks.sh.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(clippy::unnecessary_cast)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_gear_messenger.
+pub trait WeightInfo {
+    fn dequeue_dispatches(n: u32, ) -> Weight;
+    fn move_dispatch_payloads(n: u32, ) -> Weight;
+    fn translate_messages(n: u32, ) -> Weight;
+}
+
+/// Weights for pallet_gear_messenger using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// The range of component `n` is `[0, 100]`.
+    fn dequeue_dispatches(n: u32, ) -> Weight {
+        Weight::from_ref_time(0 as u64)
+            .saturating_add(T::DbWeight::get().reads((4 as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes((4 as u64).saturating_mul(n as u64)))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn move_dispatch_payloads(n: u32, ) -> Weight {
+        Weight::from_ref_time(0 as u64)
+            .saturating_add(T::DbWeight::get().reads(1 as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes(1 as u64))
+            .saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn translate_messages(n: u32, ) -> Weight {
+        Weight::from_ref_time(0 as u64)
+            .saturating_add(T::DbWeight::get().reads(1 as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes(1 as u64))
+            .saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(n as u64)))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    /// The range of component `n` is `[0, 100]`.
+    fn dequeue_dispatches(n: u32, ) -> Weight {
+        Weight::from_ref_time(0 as u64)
+            .saturating_add(RocksDbWeight::get().reads((4 as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes((4 as u64).saturating_mul(n as u64)))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn move_dispatch_payloads(n: u32, ) -> Weight {
+        Weight::from_ref_time(0 as u64)
+            .saturating_add(RocksDbWeight::get().reads(1 as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes(1 as u64))
+            .saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn translate_messages(n: u32, ) -> Weight {
+        Weight::from_ref_time(0 as u64)
+            .saturating_add(RocksDbWeight::get().reads(1 as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes(1 as u64))
+            .saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(n as u64)))
+    }
+}
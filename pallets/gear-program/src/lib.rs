@@ -66,7 +66,10 @@ pub mod pallet {
     const LOCK_ID: LockIdentifier = *b"resume_p";
 
     /// The current storage version.
-    const PROGRAM_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+    ///
+    /// Version 2 counts codes in `CodeStorage`.
+    /// Version 3 records stack height of instrumented codes.
+    const PROGRAM_STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
     #[pallet::config]
     pub trait Config: frame_system::Config {
@@ -116,7 +119,8 @@ pub mod pallet {
 
     #[pallet::storage]
     #[pallet::unbounded]
-    pub(crate) type CodeStorage<T: Config> = StorageMap<_, Identity, CodeId, InstrumentedCode>;
+    pub(crate) type CodeStorage<T: Config> =
+        CountedStorageMap<_, Identity, CodeId, InstrumentedCode>;
 
     common::wrap_storage_map!(
         storage: CodeStorage,
@@ -153,7 +157,11 @@ pub mod pallet {
         StorageMap<_, Identity, ProgramId, pause::PausedProgram>;
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            crate::migration::migrate::<T>()
+        }
+    }
 
     impl<T: Config> common::CodeStorage for pallet::Pallet<T> {
         type InstrumentedCodeStorage = CodeStorageWrap<T>;
@@ -16,19 +16,113 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{Config, Pallet};
-use common::Origin;
-use frame_support::weights::Weight;
+use crate::{pallet::CodeStorage, Config, Pallet};
+use frame_support::{
+    traits::{Get, StorageVersion},
+    weights::Weight,
+};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
-pub fn migrate<T: Config>() -> Weight
-where
-    <T as frame_system::Config>::AccountId: Origin,
-{
-    use frame_support::traits::StorageVersion;
+pub fn migrate<T: Config>() -> Weight {
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = T::DbWeight::get().reads(1);
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = Weight::zero();
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
+
+    if version < 3 {
+        weight = weight.saturating_add(v3::migrate::<T>());
+    }
 
     weight
 }
+
+/// Migration to the storage version 2.
+///
+/// Initializes the counter of codes in `CodeStorage`.
+pub mod v2 {
+    use super::*;
+
+    /// Migrates storage to the version 2, returning consumed weight.
+    pub fn migrate<T: Config>() -> Weight {
+        let count = CodeStorage::<T>::initialize_counter();
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!(
+            target: "runtime::gear",
+            "Gear Program migrated to storage version 2: {} codes counted",
+            count
+        );
+
+        T::DbWeight::get().reads_writes(u64::from(count), 2)
+    }
+}
+
+/// Migration to the storage version 3.
+///
+/// Instrumented codes in `CodeStorage` get the stack height they are
+/// instrumented with, which is `None` for the codes uploaded before.
+pub mod v3 {
+    use super::*;
+    use codec::{Decode, Encode};
+    use frame_support::storage::unhashed;
+    use gear_core::{code::InstrumentedCode, memory::WasmPageNumber, message::DispatchKind};
+    use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+    /// Layout of `InstrumentedCode` before the version 3.
+    #[derive(Decode, Encode)]
+    struct InstrumentedCodeV2 {
+        code: Vec<u8>,
+        exports: BTreeSet<DispatchKind>,
+        static_pages: WasmPageNumber,
+        version: u32,
+    }
+
+    /// Migrates storage to the version 3, returning consumed weight.
+    ///
+    /// Unlike `translate_values`, codes failing to decode are kept as they are
+    /// rather than removed, so the corrupted entries are only reported.
+    pub fn migrate<T: Config>() -> Weight {
+        let mut count = 0u64;
+        let mut migrated = 0u64;
+
+        for code_id in CodeStorage::<T>::iter_keys() {
+            count += 1;
+
+            let key = CodeStorage::<T>::hashed_key_for(code_id);
+            let raw = unhashed::get_raw(&key).unwrap_or_default();
+
+            // The new field is appended, so the code is re-encoded with
+            // `None` stack height after the old layout is checked.
+            let code = InstrumentedCodeV2::decode(&mut &raw[..])
+                .map(|old| (old, None::<u32>).encode())
+                .ok()
+                .and_then(|raw| InstrumentedCode::decode(&mut &raw[..]).ok());
+
+            match code {
+                Some(code) => {
+                    CodeStorage::<T>::insert(code_id, code);
+                    migrated += 1;
+                }
+                None => log::error!(
+                    target: "runtime::gear",
+                    "Failed to migrate instrumented code {:?}",
+                    code_id
+                ),
+            }
+        }
+
+        StorageVersion::new(3).put::<Pallet<T>>();
+
+        log::info!(
+            target: "runtime::gear",
+            "Gear Program migrated to storage version 3: {} of {} codes",
+            migrated,
+            count
+        );
+
+        T::DbWeight::get().reads_writes(count, migrated.saturating_add(1))
+    }
+}
@@ -100,6 +100,7 @@ impl pallet_balances::Config for Test {
 
 impl pallet_gear_messenger::Config for Test {
     type BlockLimiter = GearGas;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_program::Config for Test {
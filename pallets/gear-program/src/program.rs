@@ -25,6 +25,11 @@ impl<T: Config> pallet::Pallet<T> {
         common::program_exists(program_id.into_origin()) | Self::program_paused(program_id)
     }
 
+    /// Number of distinct codes uploaded to the chain.
+    pub fn codes_count() -> u64 {
+        CodeStorage::<T>::count() as u64
+    }
+
     pub fn reset_storage() {
         let _ = PausedPrograms::<T>::clear(u32::MAX, None);
     }
@@ -38,7 +38,7 @@ const CODE: &[u8] = &hex!("0061736d01000000010401600000020f0103656e76066d656d6f7
 #[test]
 fn pause_program_works() {
     new_test_ext().execute_with(|| {
-        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default())
+        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default(), None)
             .expect("Error creating Code");
 
         let code_and_id = CodeAndId::new(code);
@@ -131,7 +131,7 @@ fn pause_program_works() {
 #[test]
 fn pause_program_twice_fails() {
     new_test_ext().execute_with(|| {
-        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default())
+        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default(), None)
             .expect("Error creating Code");
 
         let code_and_id = CodeAndId::new(code);
@@ -163,7 +163,7 @@ fn pause_program_twice_fails() {
 #[test]
 fn pause_terminated_program_fails() {
     new_test_ext().execute_with(|| {
-        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default())
+        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default(), None)
             .expect("Error creating Code");
 
         let code_and_id = CodeAndId::new(code);
@@ -423,6 +423,37 @@ fn resume_program_wrong_list_fails() {
     });
 }
 
+#[test]
+fn codes_migrate_with_stack_height() {
+    use codec::Encode;
+    use frame_support::{
+        storage::unhashed,
+        traits::{GetStorageVersion, StorageVersion},
+    };
+
+    new_test_ext().execute_with(|| {
+        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default(), None)
+            .expect("Error creating Code");
+        let (code, code_id) = CodeAndId::new(code).into_parts();
+        let (code, _) = code.into_parts();
+
+        // Code stored in version 2 format, without the trailing stack height.
+        StorageVersion::new(2).put::<Pallet<Test>>();
+
+        let mut raw = code.encode();
+        assert_eq!(raw.pop(), Some(0));
+        unhashed::put_raw(&pallet::CodeStorage::<Test>::hashed_key_for(code_id), &raw);
+
+        crate::migration::migrate::<Test>();
+
+        assert_eq!(Pallet::<Test>::on_chain_storage_version(), 3);
+
+        let migrated = GearProgram::get_code(code_id).expect("Code must be migrated");
+        assert_eq!(migrated.stack_height(), None);
+        assert_eq!(migrated.encode(), code.encode());
+    });
+}
+
 mod utils {
     use gear_core::memory::PageBuf;
 
@@ -440,7 +471,7 @@ mod utils {
     pub fn create_uninitialized_program_messages(
         wasm_static_pages: WasmPageNumber,
     ) -> CreateProgramResult {
-        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default())
+        let code = Code::try_new(CODE.to_vec(), 1, |_| ConstantCostRules::default(), None)
             .expect("Error creating Code");
 
         let code_and_id = CodeAndId::new(code);
@@ -0,0 +1,86 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+#[allow(unused)]
+use crate::Pallet as GearScheduler;
+use common::{migration::v1, scheduler::ScheduledTask, storage::KeyIterableByKeyMap};
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_support::{storage_alias, Identity};
+use frame_system::pallet_prelude::BlockNumberFor;
+use gear_core::{ids::MessageId, message::DispatchKind};
+use migration::v2::OldScheduledTask;
+use sp_std::{convert::TryInto, vec};
+
+// Payload length limit of the Gear pallet's default schedule.
+const MAX_PAYLOAD_LEN: u32 = 64 * 1024;
+
+// Task pool in the layout of the version 1.
+#[storage_alias]
+type OldTaskPool<T: Config> = StorageDoubleMap<
+    Pallet<T>,
+    Identity,
+    BlockNumberFor<T>,
+    Identity,
+    OldScheduledTask<<T as frame_system::Config>::AccountId>,
+    (),
+>;
+
+benchmarks! {
+    translate_tasks {
+        let n in 0 .. migration::v2::TASKS_PER_BLOCK;
+
+        let bn: BlockNumberFor<T> = 1u32.into();
+
+        for i in 0..n {
+            let dispatch = v1::StoredDispatch {
+                kind: DispatchKind::Handle,
+                message: v1::StoredMessage {
+                    id: MessageId::from(i as u64),
+                    source: Default::default(),
+                    destination: Default::default(),
+                    payload: vec![0; MAX_PAYLOAD_LEN as usize]
+                        .try_into()
+                        .expect("Payload is within limit"),
+                    value: 0,
+                    reply: None,
+                },
+                context: None,
+            };
+            OldTaskPool::<T>::insert(bn, OldScheduledTask::SendDispatch(dispatch), ());
+        }
+
+        migration::v2::migrate::<T>();
+    }: {
+        migration::v2::step::<T>();
+    }
+    verify {
+        assert_eq!(
+            TaskPoolWrap::<T>::iter_prefix_keys(bn)
+                .filter(|task| matches!(task, ScheduledTask::SendDispatch(_)))
+                .count(),
+            n as usize
+        );
+    }
+}
+
+impl_benchmark_test_suite!(
+    GearScheduler,
+    crate::mock::new_test_ext(),
+    crate::mock::Test
+);
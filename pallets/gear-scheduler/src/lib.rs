@@ -23,6 +23,13 @@
 // Database migration module.
 pub mod migration;
 
+// Weights of the pallet's operations.
+pub mod weights;
+
+// Benchmarks of the pallet's operations.
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 // Runtime mock for running tests.
 #[cfg(test)]
 mod mock;
@@ -33,6 +40,7 @@ mod tests;
 
 // Public exports from pallet.
 pub use pallet::*;
+pub use weights::WeightInfo;
 
 // Gear Scheduler Pallet module.
 #[frame_support::pallet]
@@ -58,7 +66,10 @@ pub mod pallet {
     pub(crate) type GasAllowanceOf<T> = <<T as Config>::BlockLimiter as BlockLimiter>::GasAllowance;
 
     /// The current storage version.
-    const SCHEDULER_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+    ///
+    /// Version 2 stores delayed dispatches with metadata and reply destination.
+    /// Delayed dispatches scheduled before are translated within next blocks.
+    const SCHEDULER_STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
     // Gear Scheduler Pallet's `Config`.
     #[pallet::config]
@@ -77,6 +88,9 @@ pub mod pallet {
         /// Cost for storing in mailbox per block.
         #[pallet::constant]
         type MailboxCost: Get<Cost>;
+
+        /// Weight information for operations in this pallet.
+        type WeightInfo: crate::WeightInfo;
     }
 
     // Gear Scheduler Pallet itself.
@@ -90,6 +104,22 @@ pub mod pallet {
     #[pallet::storage_version(SCHEDULER_STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
+    // Gear Scheduler Pallet hooks.
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        /// Runtime upgrade.
+        fn on_runtime_upgrade() -> Weight {
+            crate::migration::migrate::<T>()
+        }
+
+        /// Block initialization.
+        fn on_initialize(_bn: BlockNumberFor<T>) -> Weight {
+            // Continue migration of the tasks stored
+            // before the upgrade, if any are left.
+            crate::migration::step::<T>()
+        }
+    }
+
     // Gear Scheduler Pallet error type.
     //
     // Used as inner error type for `Scheduler` implementation.
@@ -268,5 +298,9 @@ pub mod pallet {
             DispatchError,
             TaskPoolCallbacksImpl<T>,
         >;
+
+        fn migrating() -> bool {
+            crate::migration::v2::in_progress::<T>()
+        }
     }
 }
@@ -19,13 +19,156 @@
 //! Database migration module.
 
 use crate::{Config, Pallet, Weight};
+use frame_support::traits::{Get, StorageVersion};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
 pub fn migrate<T: Config>() -> Weight {
-    use frame_support::traits::StorageVersion;
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = T::DbWeight::get().reads(1);
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = Weight::zero();
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
 
     weight
 }
+
+/// Continues the migration started by the upgrade, returning consumed weight.
+pub fn step<T: Config>() -> Weight {
+    v2::step::<T>()
+}
+
+/// Migration to the storage version 2.
+///
+/// Translates delayed dispatches, which are the keys of the task pool,
+/// into the layout with metadata and reply destination.
+///
+/// Tasks of the old layout can't be read, so they are translated over
+/// several blocks, while the scheduler reports [`migrating`](v2::in_progress)
+/// and no tasks are processed: the upgrade only starts the migration,
+/// and each block initialization visits up to
+/// [`TASKS_PER_BLOCK`](v2::TASKS_PER_BLOCK) tasks.
+pub mod v2 {
+    use super::*;
+    use crate::WeightInfo;
+    use codec::{Decode, DecodeAll, Encode};
+    use common::{migration::v1::StoredDispatch, scheduler::ScheduledTask};
+    use frame_support::{
+        storage::{unhashed, StoragePrefixedMap},
+        storage_alias, Identity,
+    };
+    use frame_system::pallet_prelude::BlockNumberFor;
+    use gear_core::ids::{CodeId, MessageId, ProgramId};
+    use sp_std::vec::Vec;
+
+    /// Maximal amount of tasks visited within the block.
+    pub const TASKS_PER_BLOCK: u32 = 64;
+
+    /// Scheduled task in the layout of the version 1.
+    #[derive(Clone, Debug, Decode, Encode)]
+    pub enum OldScheduledTask<AccountId> {
+        PauseProgram(ProgramId),
+        RemoveCode(CodeId),
+        RemoveFromMailbox(AccountId, MessageId),
+        RemoveFromWaitlist(ProgramId, MessageId),
+        RemovePausedProgram(ProgramId),
+        WakeMessage(ProgramId, MessageId),
+        SendDispatch(StoredDispatch),
+    }
+
+    type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
+
+    #[storage_alias]
+    type TaskPool<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Identity,
+        BlockNumberFor<T>,
+        Identity,
+        ScheduledTask<AccountIdOf<T>>,
+        (),
+    >;
+
+    // Raw key of the last visited task, present
+    // while the migration is in progress.
+    #[storage_alias]
+    type TasksCursor<T: Config> = StorageValue<Pallet<T>, Vec<u8>>;
+
+    /// Starts migration to the version 2, returning consumed weight.
+    pub fn migrate<T: Config>() -> Weight {
+        TasksCursor::<T>::put(TaskPool::<T>::final_prefix().to_vec());
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!(
+            target: "runtime::gear",
+            "Gear Scheduler migrated to storage version 2: delayed dispatches are translated within next blocks"
+        );
+
+        T::DbWeight::get().writes(2)
+    }
+
+    /// Tell whether delayed dispatches are still being translated.
+    pub fn in_progress<T: Config>() -> bool {
+        TasksCursor::<T>::exists()
+    }
+
+    /// Translates delayed dispatches of the next tasks, returning consumed weight.
+    ///
+    /// Other tasks are encoded the same way in both layouts, as well as
+    /// tasks stored after the upgrade, so they are skipped. Tasks, which
+    /// can't be decoded in either layout, are logged and left untouched.
+    pub fn step<T: Config>() -> Weight {
+        let mut cursor = match TasksCursor::<T>::get() {
+            Some(cursor) => cursor,
+            None => return T::DbWeight::get().reads(1),
+        };
+
+        let prefix = TaskPool::<T>::final_prefix();
+        let mut visited = 0;
+
+        while visited < TASKS_PER_BLOCK {
+            let key = match sp_io::storage::next_key(&cursor).filter(|key| key.starts_with(&prefix))
+            {
+                Some(key) => key,
+                None => {
+                    TasksCursor::<T>::kill();
+
+                    log::info!(
+                        target: "runtime::gear",
+                        "Gear Scheduler translated all delayed dispatches"
+                    );
+
+                    return T::WeightInfo::translate_tasks(visited);
+                }
+            };
+
+            visited += 1;
+
+            let raw_key = &key[prefix.len()..];
+
+            if <(BlockNumberFor<T>, ScheduledTask<AccountIdOf<T>>)>::decode_all(&mut &raw_key[..])
+                .is_err()
+            {
+                match <(BlockNumberFor<T>, OldScheduledTask<AccountIdOf<T>>)>::decode_all(
+                    &mut &raw_key[..],
+                ) {
+                    Ok((bn, OldScheduledTask::SendDispatch(dispatch))) => {
+                        unhashed::kill(&key);
+                        TaskPool::<T>::insert(bn, ScheduledTask::SendDispatch(dispatch.into()), ());
+                    }
+                    _ => log::error!(
+                        target: "runtime::gear",
+                        "Task at {:?} can't be decoded: it's left untouched",
+                        key
+                    ),
+                }
+            }
+
+            cursor = key;
+        }
+
+        TasksCursor::<T>::put(cursor);
+
+        T::WeightInfo::translate_tasks(visited)
+    }
+}
@@ -21,7 +21,7 @@ use frame_support::{
     construct_runtime,
     pallet_prelude::*,
     parameter_types,
-    traits::{ConstU64, FindAuthor},
+    traits::{ConstU128, ConstU32, ConstU64, FindAuthor, GetDefault},
     weights::constants::RocksDbWeight,
 };
 use frame_system as system;
@@ -136,6 +136,12 @@ impl pallet_gear::Config for Test {
     type DebugInfo = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = ConstU64<3000>;
+    type ProgramExistentialDeposit = GetDefault;
+    type CodeStatsEraLength = ConstU64<600>;
+    type CodeStatsHistoryDepth = ConstU32<2>;
+    type MaxCodeStats = ConstU32<1024>;
+    type CodeStatsDigestLength = ConstU32<10>;
+    type CodeDepositPerByte = ConstU128<0>;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
@@ -147,6 +153,7 @@ impl pallet_gear_scheduler::Config for Test {
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
     type MailboxCost = ConstU64<100>;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_gas::Config for Test {
@@ -155,6 +162,7 @@ impl pallet_gear_gas::Config for Test {
 
 impl pallet_gear_messenger::Config for Test {
     type BlockLimiter = GearGas;
+    type WeightInfo = ();
 }
 
 pub struct FixedBlockAuthor;
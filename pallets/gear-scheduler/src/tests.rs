@@ -112,7 +112,7 @@ fn out_of_rent_reply_exists(
         {
             msg.destination() == src
                 && msg.source() == pid
-                && msg.reply() == Some(ReplyDetails::new(mid, 1))
+                && msg.reply() == Some(ReplyDetails::new(mid, core_processor::ERR_EXIT_CODE))
                 && msg.payload() == ExecutionErrorReason::OutOfRent.to_string().as_bytes()
         } else {
             false
@@ -339,3 +339,69 @@ fn gear_handles_outdated_tasks() {
         assert_eq!(Balances::reserved_balance(USER_2), 0);
     });
 }
+
+// Task pool in the layout of the version 1.
+#[frame_support::storage_alias]
+type OldTaskPool<T: Config> = StorageDoubleMap<
+    Pallet<T>,
+    frame_support::Identity,
+    <T as frame_system::Config>::BlockNumber,
+    frame_support::Identity,
+    migration::v2::OldScheduledTask<<T as frame_system::Config>::AccountId>,
+    (),
+>;
+
+#[test]
+fn delayed_dispatches_migrate_to_layout_with_metadata() {
+    use common::migration::v1;
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+    use migration::v2::OldScheduledTask;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let bn = 5;
+        let source: ProgramId = 1.into();
+
+        let dispatch = v1::StoredDispatch {
+            kind: DispatchKind::Handle,
+            message: v1::StoredMessage {
+                id: 1.into(),
+                source,
+                destination: 2.into(),
+                payload: vec![1, 2, 3].try_into().expect("Payload is too large"),
+                value: 0,
+                reply: None,
+            },
+            context: None,
+        };
+
+        // Tasks stored in version 1 format.
+        StorageVersion::new(1).put::<Pallet<Test>>();
+
+        OldTaskPool::<Test>::insert(bn, OldScheduledTask::SendDispatch(dispatch.clone()), ());
+        OldTaskPool::<Test>::insert(bn, OldScheduledTask::WakeMessage(2.into(), 3.into()), ());
+
+        migration::migrate::<Test>();
+
+        assert_eq!(Pallet::<Test>::on_chain_storage_version(), 2);
+        assert!(<Pallet<Test> as Scheduler>::migrating());
+
+        // Next block translates delayed dispatches.
+        migration::step::<Test>();
+
+        assert!(!<Pallet<Test> as Scheduler>::migrating());
+
+        let dispatch: StoredDispatch = dispatch.into();
+
+        assert!(dispatch.metadata().is_empty());
+        assert_eq!(dispatch.reply_destination(), source);
+        assert!(TaskPoolOf::<Test>::contains(
+            &bn,
+            &ScheduledTask::SendDispatch(dispatch)
+        ));
+        assert!(TaskPoolOf::<Test>::contains(
+            &bn,
+            &ScheduledTask::WakeMessage(2.into(), 3.into())
+        ));
+    });
+}
@@ -18,13 +18,29 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-pub use pallet_gear::{manager::HandleKind, GasInfo};
+pub use pallet_gear::{manager::HandleKind, CodeStats, GasInfo, MessagesPage};
 use sp_core::H256;
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
+    /// Version 2 pages the message queue in the order of processing.
+    /// Version 3 adds `code_stats`.
+    /// Version 4 adds `code_refcount`.
+    #[api_version(4)]
     pub trait GearApi {
         #[allow(clippy::too_many_arguments)]
         fn calculate_gas_info(source: H256, kind: HandleKind, payload: Vec<u8>, value: u128, allow_other_panics: bool, initial_gas: Option<u64>,) -> Result<GasInfo, Vec<u8>>;
+
+        /// Page of messages in the mailbox of the `account`, following the `cursor`.
+        fn mailbox_of(account: H256, cursor: Option<H256>, limit: u32) -> MessagesPage;
+
+        /// Page of messages in the message queue, following the `cursor`.
+        fn queue_snapshot(cursor: Option<H256>, limit: u32) -> MessagesPage;
+
+        /// Stats of the codes, which burned the most gas within the `era`, or the current one.
+        fn code_stats(era: Option<u32>, limit: u32) -> Vec<(H256, CodeStats)>;
+
+        /// Amount of active programs running the code.
+        fn code_refcount(code_id: H256) -> u32;
     }
 }
@@ -28,7 +28,7 @@ use jsonrpsee::{
     types::error::{CallError, ErrorObject},
 };
 pub use pallet_gear_rpc_runtime_api::GearApi as GearRuntimeApi;
-use pallet_gear_rpc_runtime_api::{GasInfo, HandleKind};
+use pallet_gear_rpc_runtime_api::{CodeStats, GasInfo, HandleKind, MessagesPage};
 use sp_api::{ApiError, ApiRef, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_core::{Bytes, H256};
@@ -91,6 +91,34 @@ pub trait GearApi<BlockHash, ResponseType> {
         allow_other_panics: bool,
         at: Option<BlockHash>,
     ) -> RpcResult<GasInfo>;
+
+    #[method(name = "gear_mailboxOf")]
+    fn mailbox_of(
+        &self,
+        account: H256,
+        cursor: Option<H256>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<MessagesPage>;
+
+    #[method(name = "gear_queueSnapshot")]
+    fn queue_snapshot(
+        &self,
+        cursor: Option<H256>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<MessagesPage>;
+
+    #[method(name = "gear_codeStats")]
+    fn code_stats(
+        &self,
+        era: Option<u32>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(H256, CodeStats)>>;
+
+    #[method(name = "gear_codeRefcount")]
+    fn code_refcount(&self, code_id: H256, at: Option<BlockHash>) -> RpcResult<u32>;
 }
 
 /// A struct that implements the [`GearApi`].
@@ -303,4 +331,64 @@ where
             )
         })
     }
+
+    fn mailbox_of(
+        &self,
+        account: H256,
+        cursor: Option<H256>,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<MessagesPage> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .mailbox_of(&at, account, cursor, limit)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn queue_snapshot(
+        &self,
+        cursor: Option<H256>,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<MessagesPage> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .queue_snapshot(&at, cursor, limit)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn code_stats(
+        &self,
+        era: Option<u32>,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<(H256, CodeStats)>> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .code_stats(&at, era, limit)
+            .map_err(runtime_error_into_rpc_error)
+    }
+
+    fn code_refcount(&self, code_id: H256, at: Option<<Block as BlockT>::Hash>) -> RpcResult<u32> {
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+
+        self.client
+            .runtime_api()
+            .code_refcount(&at, code_id)
+            .map_err(runtime_error_into_rpc_error)
+    }
 }
@@ -38,14 +38,17 @@ use crate::{
     schedule::{API_BENCHMARK_BATCH_SIZE, INSTR_BENCHMARK_BATCH_SIZE},
     BTreeMap, BalanceOf, BlockGasLimitOf, Call, Config, CostsPerBlockOf, CurrencyOf,
     ExecutionEnvironment, Ext as Externalities, GasHandlerOf, MailboxOf, Pallet as Gear, Pallet,
-    QueueOf, Schedule, WaitlistOf,
+    QueueOf, Schedule, TxHashOf, WaitlistOf,
 };
 use codec::Encode;
 use common::{
-    benchmarking, scheduler::SchedulingCostsPerBlock, storage::*, CodeMetadata, CodeStorage,
-    GasPrice, GasTree, Origin,
+    benchmarking,
+    scheduler::{ScheduledTask, SchedulingCostsPerBlock},
+    storage::*,
+    CodeMetadata, CodeStorage, GasPrice, GasTree, Origin,
 };
 use core_processor::{
+    common::JournalHandler,
     configs::{AllocationsConfig, BlockConfig, BlockInfo, MessageExecutionContext},
     PrepareResult, PreparedMessageExecutionContext,
 };
@@ -211,6 +214,7 @@ where
                 code.clone(),
                 schedule.instruction_weights.version,
                 |module| schedule.rules(module),
+                Some(schedule.limits.stack_height),
             )
             .map_err(|_| "Code failed to load")?;
 
@@ -310,20 +314,37 @@ where
 
     let block_config = BlockConfig {
         block_info,
+        block_duration: Gear::<T>::block_duration(),
         allocations_config: AllocationsConfig {
             max_pages: gear_core::memory::WasmPageNumber(T::Schedule::get().limits.memory_pages),
             init_cost: T::Schedule::get().memory_weights.initial_cost,
             alloc_cost: T::Schedule::get().memory_weights.allocation_cost,
             mem_grow_cost: T::Schedule::get().memory_weights.grow_cost,
             load_page_cost: T::Schedule::get().memory_weights.load_cost,
+            write_page_cost: T::Schedule::get().memory_weights.write_cost,
+            max_pages_persisted: T::Schedule::get().limits.max_pages_persisted_per_message,
         },
         existential_deposit,
+        existential_deposit_policy: T::ProgramExistentialDeposit::get(),
         outgoing_limit: 2048,
         host_fn_weights: Default::default(),
         forbidden_funcs: Default::default(),
         mailbox_threshold,
         waitlist_cost,
+        reply_waker_cost: T::DbWeight::get().writes(4).ref_time(),
         reserve_for,
+        block_hash_depth: T::Schedule::get().limits.block_hash_depth,
+        block_hash: Gear::<T>::block_hash_at,
+        program_exists: pallet_gear_program::Pallet::<T>::program_exists,
+        mailbox_contains: Gear::<T>::mailbox_contains,
+        codes_count: pallet_gear_program::Pallet::<T>::codes_count,
+        delayed_dispatch: common::get_delayed_dispatch,
+        program_counters: common::get_counters,
+        children_gas_burned: common::get_children_gas_burned,
+        code_id_of: Gear::<T>::code_id_of,
+        reply_waker: common::get_reply_waker,
+        entry_budget: common::get_entry_budget,
+        entry_budget_read_cost: T::DbWeight::get().reads(1).ref_time(),
     };
 
     if let Some(queued_dispatch) = QueueOf::<T>::dequeue().map_err(|_| "MQ storage corrupted")? {
@@ -336,6 +357,8 @@ where
             actor,
             dispatch: queued_dispatch.into_incoming(initial_gas),
             origin: ProgramId::from_origin(source),
+            tx_hash: None,
+            execution_id: Gear::<T>::next_execution_id(),
             gas_allowance: u64::MAX,
             subsequent_execution: false,
         };
@@ -391,6 +414,25 @@ benchmarks! {
         assert!(MailboxOf::<T>::is_empty(&caller));
     }
 
+    tasks_exit_program {
+        let program_id = benchmarking::account::<T::AccountId>("program", 0, 100);
+        <T as pallet::Config>::Currency::deposit_creating(&program_id, 100_000_000_000_000_u128.unique_saturated_into());
+        let code = benchmarking::generate_wasm2(16.into()).unwrap();
+        benchmarking::set_program(program_id.clone().into_origin(), code, 1.into());
+        let program_id = ProgramId::from_origin(program_id.into_origin());
+        let inheritor = ProgramId::from_origin(benchmarking::account::<T::AccountId>("inheritor", 0, 100).into_origin());
+
+        let mut ext_manager = ExtManager::<T>::default();
+        ext_manager.schedule_exit(program_id, 2, inheritor);
+
+        init_block::<T>();
+    }: {
+        ScheduledTask::ExitProgram(program_id, inheritor).process_with(&mut ext_manager);
+    }
+    verify {
+        assert!(Gear::<T>::is_exited(program_id));
+    }
+
     // This constructs a program that is maximal expensive to instrument.
     // It creates a maximum number of metering blocks per byte.
     //
@@ -409,6 +451,19 @@ benchmarks! {
         assert!(<T as pallet::Config>::CodeStorage::exists(code_id));
     }
 
+    remove_code {
+        let caller = whitelisted_caller();
+        <T as pallet::Config>::Currency::make_free_balance_be(&caller, caller_funding::<T>());
+        let WasmModule { code, hash: code_id, .. } = WasmModule::<T>::dummy();
+        Gear::<T>::upload_code(RawOrigin::Signed(caller.clone()).into(), code)
+            .expect("Failed to upload code");
+
+        init_block::<T>();
+    }: _(RawOrigin::Signed(caller), code_id)
+    verify {
+        assert!(!<T as pallet::Config>::CodeStorage::exists(code_id));
+    }
+
     // The size of the salt influences the runtime because is is hashed in order to
     // determine the program address.
     //
@@ -509,6 +564,20 @@ benchmarks! {
         assert!(MailboxOf::<T>::is_empty(&caller))
     }
 
+    note_tx_hash {
+        let l in 0 .. T::Schedule::get().limits.code_len;
+        let message_id = MessageId::from_origin(benchmarking::account::<T::AccountId>("message", 0, 100).into_origin());
+
+        // Emulating application of the extrinsic of the given length.
+        sp_io::storage::set(sp_core::storage::well_known_keys::EXTRINSIC_INDEX, &0u32.encode());
+        SystemPallet::<T>::note_extrinsic(vec![0_u8; l as usize]);
+    }: {
+        Gear::<T>::note_tx_hash(message_id);
+    }
+    verify {
+        assert!(TxHashOf::<T>::contains_key(message_id));
+    }
+
     initial_allocation {
         let q in 1 .. MAX_PAGES;
         let caller: T::AccountId = benchmarking::account("caller", 0, 0);
@@ -686,29 +755,23 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_msg_id {
-        let r in 0 .. API_BENCHMARK_BATCHES;
-        let instance = Program::<T>::new(WasmModule::getter(
-            "env", "gr_msg_id", r * API_BENCHMARK_BATCH_SIZE
-        ), vec![])?;
-        let Exec {
-            ext_manager,
-            block_config,
-            context,
-            memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
-    }: {
-        core_processor::process::<
-            Externalities,
-            ExecutionEnvironment,
-        >(&block_config, context, memory_pages);
-    }
-
-    gr_origin {
+    gr_gas_allowance {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let instance = Program::<T>::new(WasmModule::getter(
-            "env", "gr_origin", r * API_BENCHMARK_BATCH_SIZE
-        ), vec![])?;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_gas_allowance",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
         let Exec {
             ext_manager,
             block_config,
@@ -722,11 +785,23 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_program_id {
+    gr_is_init {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let instance = Program::<T>::new(WasmModule::getter(
-            "env", "gr_program_id", r * API_BENCHMARK_BATCH_SIZE
-        ), vec![])?;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_is_init",
+                params: vec![],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
         let Exec {
             ext_manager,
             block_config,
@@ -740,12 +815,23 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_source {
+    gr_resumed_from_wait {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let instance = Program::<T>::new(WasmModule::getter(
-            "env", "gr_source", r * API_BENCHMARK_BATCH_SIZE
-        ), vec![])?;
-
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_resumed_from_wait",
+                params: vec![],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
         let Exec {
             ext_manager,
             block_config,
@@ -759,10 +845,10 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_value {
+    gr_msg_id {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let instance = Program::<T>::new(WasmModule::getter(
-            "env", "gr_value", r * API_BENCHMARK_BATCH_SIZE
+            "env", "gr_msg_id", r * API_BENCHMARK_BATCH_SIZE
         ), vec![])?;
         let Exec {
             ext_manager,
@@ -777,10 +863,10 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_value_available {
+    gr_origin {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let instance = Program::<T>::new(WasmModule::getter(
-            "env", "gr_value_available", r * API_BENCHMARK_BATCH_SIZE
+            "env", "gr_origin", r * API_BENCHMARK_BATCH_SIZE
         ), vec![])?;
         let Exec {
             ext_manager,
@@ -795,70 +881,55 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_size {
-        let r in 0 .. API_BENCHMARK_BATCHES;
-        let code = WasmModule::<T>::from(ModuleDefinition {
-            memory: Some(ImportedMemory::max::<T>()),
-            imported_functions: vec![ImportedFunction {
-                module: "env",
-                name: "gr_size",
-                params: vec![],
-                return_type: Some(ValueType::I32),
-            }],
-            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::Call(0),
-                Instruction::Drop,
-            ])),
-            .. Default::default()
-        });
-        let instance = Program::<T>::new(code, vec![])?;
-        let Exec {
-            ext_manager,
-            block_config,
-            context,
-            memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
-    }: {
-        core_processor::process::<
-            Externalities,
-            ExecutionEnvironment,
-        >(&block_config, context, memory_pages);
-    }
-
-    gr_read {
+    gr_forward {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let pages = 1u32;
-        let buffer_size = pages * 64 * 1024 - 4;
         let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
+        let pid_bytes = instance.addr.encode();
+        let pid_len = pid_bytes.len();
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let delay_bytes = 0_u32.to_le_bytes().to_vec();
+        let delay_len = delay_bytes.len();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_read",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
-                return_type: None,
+                name: "gr_forward",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
             data_segments: vec![
                 DataSegment {
-                    offset: 0,
-                    value: buffer_size.to_le_bytes().to_vec(),
+                    offset: 0_u32,
+                    value: pid_bytes,
+                },
+                DataSegment {
+                    offset: pid_len as u32,
+                    value: value_bytes,
+                },
+                DataSegment {
+                    offset: (pid_len + value_len) as u32,
+                    value: delay_bytes,
                 },
             ],
             handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // at
-                Instruction::I32Const(0), // len
-                Instruction::I32Const(0), // output ptr
+                Instruction::I32Const(0), // program_id_ptr
+                Instruction::I32Const(pid_len as i32), // value_ptr
+                Instruction::I32Const((pid_len + value_len + delay_len) as i32), // message_id_ptr
+                Instruction::I32Const((pid_len + value_len) as i32), // delay_ptr
                 Instruction::Call(0),
+                Instruction::Drop,
                 ])),
                 .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
+
         let Exec {
             ext_manager,
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -866,44 +937,57 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_read_per_kb {
+    // Benchmark the `gr_forward` call.
+    // `n`: Size of the forwarded payload in kb
+    gr_forward_per_kb {
         let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
-        let pages = 16u32;
-        let buffer_size = pages * 64 * 1024 - 4;
         let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
         let pid_bytes = instance.addr.encode();
         let pid_len = pid_bytes.len();
         let value_bytes = 0_u128.encode();
         let value_len = value_bytes.len();
+        let delay_bytes = 0_u32.to_le_bytes().to_vec();
+        let delay_len = delay_bytes.len();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_read",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
-                return_type: None,
+                name: "gr_forward",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
             data_segments: vec![
                 DataSegment {
-                    offset: 0,
-                    value: buffer_size.to_le_bytes().to_vec(),
+                    offset: 0_u32,
+                    value: pid_bytes,
+                },
+                DataSegment {
+                    offset: pid_len as u32,
+                    value: value_bytes,
+                },
+                DataSegment {
+                    offset: (pid_len + value_len) as u32,
+                    value: delay_bytes,
                 },
             ],
             handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // at
-                Instruction::I32Const((n * 1024) as i32), // len
-                Instruction::I32Const(0), // output ptr
+                Instruction::I32Const(0), // program_id_ptr
+                Instruction::I32Const(pid_len as i32), // value_ptr
+                Instruction::I32Const((pid_len + value_len + delay_len) as i32), // message_id_ptr
+                Instruction::I32Const((pid_len + value_len) as i32), // delay_ptr
                 Instruction::Call(0),
+                Instruction::Drop,
                 ])),
                 .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
+
         let Exec {
             ext_manager,
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![0xff; (n * 1024) as usize], 0u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![0; (n * 1024) as usize], 10000000u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -911,17 +995,19 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_block_height {
+    gr_signal_details {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_block_height",
-                params: vec![],
+                name: "gr_signal_details",
+                params: vec![ValueType::I32, ValueType::I32],
                 return_type: Some(ValueType::I32),
             }],
             handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // message_id_ptr
+                Instruction::I32Const(32), // code_ptr
                 Instruction::Call(0),
                 Instruction::Drop,
             ])),
@@ -941,17 +1027,18 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_block_timestamp {
+    gr_bump_counter {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_block_timestamp",
-                params: vec![],
+                name: "gr_bump_counter",
+                params: vec![ValueType::I32],
                 return_type: Some(ValueType::I64),
             }],
             handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // key_ptr
                 Instruction::Call(0),
                 Instruction::Drop,
             ])),
@@ -971,24 +1058,11 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_send_init {
+    gr_program_id {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let code = WasmModule::<T>::from(ModuleDefinition {
-            memory: Some(ImportedMemory::max::<T>()),
-            imported_functions: vec![ImportedFunction {
-                module: "env",
-                name: "gr_send_init",
-                params: vec![ValueType::I32],
-                return_type: Some(ValueType::I32),
-            }],
-            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // handle ptr
-                Instruction::Call(0),
-                Instruction::Drop,
-            ])),
-            .. Default::default()
-        });
-        let instance = Program::<T>::new(code, vec![])?;
+        let instance = Program::<T>::new(WasmModule::getter(
+            "env", "gr_program_id", r * API_BENCHMARK_BATCH_SIZE
+        ), vec![])?;
         let Exec {
             ext_manager,
             block_config,
@@ -1002,30 +1076,38 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_send_push {
+    gr_source {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::getter(
+            "env", "gr_source", r * API_BENCHMARK_BATCH_SIZE
+        ), vec![])?;
+
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_source_code_id {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_send_init",
+                name: "gr_source_code_id",
                 params: vec![ValueType::I32],
                 return_type: Some(ValueType::I32),
-            },
-            ImportedFunction {
-                module: "env",
-                name: "gr_send_push",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
-                return_type: Some(ValueType::I32),
             }],
             handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // handle ptr
-                Instruction::Call(0), // get handle
-                Instruction::Drop,
-                Instruction::I32Const(0), // handle ptr
-                Instruction::I32Const(0), // payload ptr
-                Instruction::I32Const(0), // payload len
-                Instruction::Call(1), // send_push
+                Instruction::I32Const(0), // code_id_ptr
+                Instruction::Call(0),
                 Instruction::Drop,
             ])),
             .. Default::default()
@@ -1044,30 +1126,133 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_send_push_per_kb {
-        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+    gr_value {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::getter(
+            "env", "gr_value", r * API_BENCHMARK_BATCH_SIZE
+        ), vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_value_available {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::getter(
+            "env", "gr_value_available", r * API_BENCHMARK_BATCH_SIZE
+        ), vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_spendable_value {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::getter(
+            "env", "gr_spendable_value", r * API_BENCHMARK_BATCH_SIZE
+        ), vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_size {
+        let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_send_init",
-                params: vec![ValueType::I32],
+                name: "gr_size",
+                params: vec![],
                 return_type: Some(ValueType::I32),
-            },
-            ImportedFunction {
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_message_meta {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_send_push",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                name: "gr_message_meta",
+                params: vec![ValueType::I32],
                 return_type: Some(ValueType::I32),
             }],
-            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // handle ptr
-                Instruction::Call(0), // get handle
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // metadata_ptr
+                Instruction::Call(0),
                 Instruction::Drop,
-                Instruction::I32Const(0), // handle ptr
-                Instruction::I32Const(0), // payload ptr
-                Instruction::I32Const((n * 1024) as i32), // payload_len
-                Instruction::Call(1), // send_push
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_max_payload_size {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_max_payload_size",
+                params: vec![],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
                 Instruction::Drop,
             ])),
             .. Default::default()
@@ -1086,52 +1271,40 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // Benchmark the `gr_send_commit` call.
-    // `gr_send` call is shortcut for `gr_send_init` + `gr_send_commit`
-    gr_send_commit {
+    gr_read {
         let r in 0 .. API_BENCHMARK_BATCHES;
+        let pages = 1u32;
+        let buffer_size = pages * 64 * 1024 - 4;
         let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
-        let pid_bytes = instance.addr.encode();
-        let pid_len = pid_bytes.len();
-        let value_bytes = 0_u128.encode();
-        let value_len = value_bytes.len();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_send",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
-                return_type: Some(ValueType::I32),
+                name: "gr_read",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: None,
             }],
             data_segments: vec![
                 DataSegment {
-                    offset: 0_u32,
-                    value: pid_bytes,
-                },
-                DataSegment {
-                    offset: pid_len as u32,
-                    value: value_bytes,
+                    offset: 0,
+                    value: buffer_size.to_le_bytes().to_vec(),
                 },
             ],
-            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCHES, &[
-                Instruction::I32Const(0), // program_id_ptr
-                Instruction::I32Const(0), // payload_ptr
-                Instruction::I32Const(0), // payload_len
-                Instruction::I32Const(pid_len as i32), // value_ptr
-                Instruction::I32Const((pid_len + value_len) as i32), // message_id_ptr
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // at
+                Instruction::I32Const(0), // len
+                Instruction::I32Const(0), // output ptr
                 Instruction::Call(0),
-                Instruction::Drop,
                 ])),
                 .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
-
         let Exec {
             ext_manager,
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1139,10 +1312,10 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // Benchmark the `gr_send_commit` call.
-    // `n`: Size of message payload in kb
-    gr_send_commit_per_kb {
+    gr_read_per_kb {
         let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let pages = 16u32;
+        let buffer_size = pages * 64 * 1024 - 4;
         let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
         let pid_bytes = instance.addr.encode();
         let pid_len = pid_bytes.len();
@@ -1152,29 +1325,51 @@ benchmarks! {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_send",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32],
-                return_type: Some(ValueType::I32),
+                name: "gr_read",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: None,
             }],
             data_segments: vec![
                 DataSegment {
-                    offset: 0_u32,
-                    value: pid_bytes,
-                },
-                DataSegment {
-                    offset: pid_len as u32,
-                    value: value_bytes,
+                    offset: 0,
+                    value: buffer_size.to_le_bytes().to_vec(),
                 },
             ],
-            handle_body: Some(body::plain(vec![
-                Instruction::I32Const(0), // program_id_ptr
-                Instruction::I32Const(0), // payload_ptr
-                Instruction::I32Const((n * 1024) as i32), // payload_len
-                Instruction::I32Const(pid_len as i32), // value_ptr
-                Instruction::I32Const((pid_len + value_len) as i32), // message_id_ptr
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // at
+                Instruction::I32Const((n * 1024) as i32), // len
+                Instruction::I32Const(0), // output ptr
+                Instruction::Call(0),
+                ])),
+                .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![0xff; (n * 1024) as usize], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_block_height {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_block_height",
+                params: vec![],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
                 Instruction::Call(0),
                 Instruction::Drop,
-                Instruction::End,
             ])),
             .. Default::default()
         });
@@ -1184,7 +1379,7 @@ benchmarks! {
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1192,34 +1387,21 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // Benchmark the `gr_reply_commit` call.
-    gr_reply_commit {
+    gr_stack_limit {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
-        let value_bytes = 0_u128.encode();
-        let value_len = value_bytes.len();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_reply_commit",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                name: "gr_stack_limit",
+                params: vec![],
                 return_type: Some(ValueType::I32),
             }],
-            data_segments: vec![
-                DataSegment {
-                    offset: 0u32,
-                    value: value_bytes,
-                },
-            ],
             handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // payload_ptr
-                Instruction::I32Const(0), // payload_len
-                Instruction::I32Const(0), // value_ptr
                 Instruction::Call(0),
                 Instruction::Drop,
-                ])),
-                .. Default::default()
+            ])),
+            .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
         let Exec {
@@ -1227,7 +1409,7 @@ benchmarks! {
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1235,32 +1417,998 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_reply_commit_per_kb {
-        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
-        let value_bytes = 0_u128.encode();
-        let value_len = value_bytes.len();
+    gr_block_timestamp {
+        let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_reply_commit",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
-                return_type: Some(ValueType::I32),
+                name: "gr_block_timestamp",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_block_duration {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_block_duration",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_block_hash {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_block_hash",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // height
+                Instruction::I32Const(0), // hash_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_tx_hash {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_tx_hash",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // hash_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_set_default_gas_limit {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_set_default_gas_limit",
+                params: vec![ValueType::I64],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I64Const(1_000_000), // gas_limit
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_set_charge_allowance {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_set_charge_allowance",
+                params: vec![ValueType::I64],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I64Const(1_000_000), // gas_limit
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_children_count {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_children_count",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_children_gas_burned {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_children_gas_burned",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_codes_count {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_codes_count",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_execution_id {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_execution_id",
+                params: vec![],
+                return_type: Some(ValueType::I64),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_context_limits {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::getter(
+            "env", "gr_context_limits", r * API_BENCHMARK_BATCH_SIZE
+        ), vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_send_init {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_init",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // handle ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_send_push {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_init",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "gr_send_push",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // handle ptr
+                Instruction::Call(0), // get handle
+                Instruction::Drop,
+                Instruction::I32Const(0), // handle ptr
+                Instruction::I32Const(0), // payload ptr
+                Instruction::I32Const(0), // payload len
+                Instruction::Call(1), // send_push
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_send_push_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send_init",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
+            },
+            ImportedFunction {
+                module: "env",
+                name: "gr_send_push",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // handle ptr
+                Instruction::Call(0), // get handle
+                Instruction::Drop,
+                Instruction::I32Const(0), // handle ptr
+                Instruction::I32Const(0), // payload ptr
+                Instruction::I32Const((n * 1024) as i32), // payload_len
+                Instruction::Call(1), // send_push
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    // Benchmark the `gr_send_commit` call.
+    // `gr_send` call is shortcut for `gr_send_init` + `gr_send_commit`
+    gr_send_commit {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
+        let pid_bytes = instance.addr.encode();
+        let pid_len = pid_bytes.len();
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send",
+                params: vec![ValueType::I32; 6],
+                return_type: Some(ValueType::I32),
+            }],
+            data_segments: vec![
+                DataSegment {
+                    offset: 0_u32,
+                    value: pid_bytes,
+                },
+                DataSegment {
+                    offset: pid_len as u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCHES, &[
+                Instruction::I32Const(0), // program_id_ptr
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const(0), // payload_len
+                Instruction::I32Const(pid_len as i32), // value_ptr
+                Instruction::I32Const((pid_len + value_len) as i32), // message_id_ptr
+                Instruction::I32Const((pid_len + value_len + 32) as i32), // delay_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+                ])),
+                .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    // Benchmark the `gr_send_commit` call.
+    // `n`: Size of message payload in kb
+    gr_send_commit_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
+        let pid_bytes = instance.addr.encode();
+        let pid_len = pid_bytes.len();
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_send",
+                params: vec![ValueType::I32; 6],
+                return_type: Some(ValueType::I32),
+            }],
+            data_segments: vec![
+                DataSegment {
+                    offset: 0_u32,
+                    value: pid_bytes,
+                },
+                DataSegment {
+                    offset: pid_len as u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::plain(vec![
+                Instruction::I32Const(0), // program_id_ptr
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const((n * 1024) as i32), // payload_len
+                Instruction::I32Const(pid_len as i32), // value_ptr
+                Instruction::I32Const((pid_len + value_len) as i32), // message_id_ptr
+                Instruction::I32Const((pid_len + value_len + 32) as i32), // delay_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+                Instruction::End,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    // Benchmark the `gr_reply_commit` call.
+    gr_reply_commit {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_commit",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            data_segments: vec![
+                DataSegment {
+                    offset: 0u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const(0), // payload_len
+                Instruction::I32Const(0), // value_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+                ])),
+                .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_reply_commit_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_commit",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            data_segments: vec![
+                DataSegment {
+                    offset: 0u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const((n * 1024) as i32), // payload_len
+                Instruction::I32Const(0), // value_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+                ])),
+                .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    // Benchmark the `gr_reply_push` call.
+    gr_reply_push {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_push",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            data_segments: vec![
+                DataSegment {
+                    offset: 0u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const(0), // payload_len
+                Instruction::Call(0),
+                Instruction::Drop,
+                ])),
+                .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_reply_push_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let value_bytes = 0_u128.encode();
+        let value_len = value_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_push",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            data_segments: vec![
+                DataSegment {
+                    offset: 0u32,
+                    value: value_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // payload_ptr
+                Instruction::I32Const((n * 1024) as i32), // payload_len
+                Instruction::Call(0),
+                Instruction::Drop,
+                ])),
+                .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_reply_to {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_reply_to",
+                params: vec![ValueType::I32],
+                return_type: None,
+            }],
+            reply_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                // dest_ptr
+                Instruction::I32Const(0),
+                Instruction::Call(0),
+                ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let msg_id = MessageId::from(10);
+        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), Default::default(), Some(1_000_000), 0, None).into_stored();
+        MailboxOf::<T>::insert(msg, u32::MAX.unique_saturated_into()).expect("Error during mailbox insertion");
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Reply(msg_id, 0), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_debug {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_debug",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0),
+                Instruction::I32Const(0),
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_trace {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_trace",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(1), // level
+                Instruction::I32Const(0), // data_ptr
+                Instruction::I32Const(0), // data_len
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_trace_per_kb {
+        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_trace",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(1), // level
+                Instruction::I32Const(0), // data_ptr
+                Instruction::I32Const((n * 1024) as i32), // data_len
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_exit_code {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_exit_code",
+                params: vec![],
+                return_type: Some(ValueType::I32),
+            }],
+            reply_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let msg_id = MessageId::from(10);
+        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), Default::default(), Some(1_000_000), 0, None).into_stored();
+        MailboxOf::<T>::insert(msg, u32::MAX.unique_saturated_into()).expect("Error during mailbox insertion");
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Reply(msg_id, 0), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    // We cannot call `gr_exit` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_exit {
+        let r in 0 .. 1;
+        let pid_bytes = ProgramId::from(1).encode();
+        let pid_len = pid_bytes.len();
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_exit",
+                params: vec![ValueType::I32],
+                return_type: None,
             }],
             data_segments: vec![
                 DataSegment {
-                    offset: 0u32,
-                    value: value_bytes,
+                    offset: 0_u32,
+                    value: pid_bytes,
                 },
             ],
-            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // payload_ptr
-                Instruction::I32Const((n * 1024) as i32), // payload_len
-                Instruction::I32Const(0), // value_ptr
+            handle_body: Some(body::repeated(r, &[
+                Instruction::I32Const(0),
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_schedule_exit {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_schedule_exit",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(-1), // block
+                Instruction::I32Const(0), // inheritor_ptr
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_set_entry_budget {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_set_entry_budget",
+                params: vec![ValueType::I32, ValueType::I64],
+                return_type: Some(ValueType::I32),
+            }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(2), // entry
+                Instruction::I64Const(1_000_000_000), // gas
+                Instruction::Call(0),
+                Instruction::Drop,
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    // We cannot call `gr_leave` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_leave {
+        let r in 0 .. 1;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_leave",
+                params: vec![],
+                return_type: None,
+            }],
+            handle_body: Some(body::repeated(r, &[
                 Instruction::Call(0),
-                Instruction::Drop,
-                ])),
-                .. Default::default()
+            ])),
+            .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
         let Exec {
@@ -1268,7 +2416,7 @@ benchmarks! {
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1276,41 +2424,56 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // Benchmark the `gr_reply_push` call.
-    gr_reply_push {
+    gr_mailbox_contains {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let instance = Program::<T>::new(WasmModule::<T>::dummy(), vec![])?;
-        let value_bytes = 0_u128.encode();
-        let value_len = value_bytes.len();
+        let user_id = ProgramId::from_origin(benchmarking::account::<T::AccountId>("user", 0, 100).into_origin());
+        let message_id = MessageId::from_origin(benchmarking::account::<T::AccountId>("message", 0, 100).into_origin());
+        let user_id_bytes = user_id.encode();
+        let user_id_len = user_id_bytes.len();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_reply_push",
+                name: "gr_mailbox_contains",
                 params: vec![ValueType::I32, ValueType::I32],
                 return_type: Some(ValueType::I32),
             }],
             data_segments: vec![
                 DataSegment {
-                    offset: 0u32,
-                    value: value_bytes,
+                    offset: 0_u32,
+                    value: user_id_bytes,
+                },
+                DataSegment {
+                    offset: user_id_len as u32,
+                    value: message_id.encode(),
                 },
             ],
             handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // payload_ptr
-                Instruction::I32Const(0), // payload_len
+                Instruction::I32Const(0), // user_id_ptr
+                Instruction::I32Const(user_id_len as i32), // message_id_ptr
                 Instruction::Call(0),
                 Instruction::Drop,
-                ])),
-                .. Default::default()
+            ])),
+            .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
+        // The lookup decodes the mailboxed message, so the worst case
+        // is the message sent by the program with the largest payload.
+        let payload = vec![0; T::Schedule::get().limits.payload_len as usize];
+        MailboxOf::<T>::insert(gear_core::message::StoredMessage::new(
+            message_id,
+            ProgramId::from_origin(instance.addr),
+            user_id,
+            payload.try_into().expect("Payload is within limit"),
+            0,
+            None,
+        ), u32::MAX.unique_saturated_into()).expect("Error during mailbox insertion");
         let Exec {
             ext_manager,
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1318,31 +2481,22 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_reply_push_per_kb {
-        let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
-        let value_bytes = 0_u128.encode();
-        let value_len = value_bytes.len();
+    // We cannot call `gr_wait` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_wait {
+        let r in 0 .. 1;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_reply_push",
-                params: vec![ValueType::I32, ValueType::I32],
-                return_type: Some(ValueType::I32),
+                name: "gr_wait",
+                params: vec![],
+                return_type: None,
             }],
-            data_segments: vec![
-                DataSegment {
-                    offset: 0u32,
-                    value: value_bytes,
-                },
-            ],
-            handle_body: Some(body::repeated(API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0), // payload_ptr
-                Instruction::I32Const((n * 1024) as i32), // payload_len
+            handle_body: Some(body::repeated(r, &[
                 Instruction::Call(0),
-                Instruction::Drop,
-                ])),
-                .. Default::default()
+            ])),
+            .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
         let Exec {
@@ -1350,7 +2504,7 @@ benchmarks! {
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 10000000u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1358,33 +2512,31 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_reply_to {
-        let r in 0 .. API_BENCHMARK_BATCHES;
+    // We cannot call `gr_wait_for` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_wait_for {
+        let r in 0 .. 1;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_reply_to",
+                name: "gr_wait_for",
                 params: vec![ValueType::I32],
                 return_type: None,
             }],
-            reply_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                // dest_ptr
-                Instruction::I32Const(0),
+            handle_body: Some(body::repeated(r, &[
+                Instruction::I32Const(100),
                 Instruction::Call(0),
-                ])),
+            ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
-        let msg_id = MessageId::from(10);
-        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), Default::default(), Some(1_000_000), 0, None).into_stored();
-        MailboxOf::<T>::insert(msg, u32::MAX.unique_saturated_into()).expect("Error during mailbox insertion");
         let Exec {
             ext_manager,
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Reply(msg_id, 0), vec![], 0u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1392,19 +2544,20 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_debug {
-        let r in 0 .. API_BENCHMARK_BATCHES;
+    // We cannot call `gr_wait_up_to` multiple times. Therefore our weight determination is not
+    // as precise as with other APIs.
+    gr_wait_up_to {
+        let r in 0 .. 1;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_debug",
-                params: vec![ValueType::I32, ValueType::I32],
+                name: "gr_wait_up_to",
+                params: vec![ValueType::I32],
                 return_type: None,
             }],
-            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-                Instruction::I32Const(0),
-                Instruction::I32Const(0),
+            handle_body: Some(body::repeated(r, &[
+                Instruction::I32Const(100),
                 Instruction::Call(0),
             ])),
             .. Default::default()
@@ -1423,32 +2576,29 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_exit_code {
+    gr_wait_budget_remaining {
         let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_exit_code",
+                name: "gr_wait_budget_remaining",
                 params: vec![],
                 return_type: Some(ValueType::I32),
             }],
-            reply_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
                 Instruction::Call(0),
                 Instruction::Drop,
             ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
-        let msg_id = MessageId::from(10);
-        let msg = gear_core::message::Message::new(msg_id, instance.addr.as_bytes().into(), ProgramId::from(instance.caller.clone().into_origin().as_bytes()), Default::default(), Some(1_000_000), 0, None).into_stored();
-        MailboxOf::<T>::insert(msg, u32::MAX.unique_saturated_into()).expect("Error during mailbox insertion");
         let Exec {
             ext_manager,
             block_config,
             context,
             memory_pages,
-        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Reply(msg_id, 0), vec![], 0u32.into())?;
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
     }: {
         core_processor::process::<
             Externalities,
@@ -1456,33 +2606,74 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // We cannot call `gr_exit` multiple times. Therefore our weight determination is not
-    // as precise as with other APIs.
-    gr_exit {
-        let r in 0 .. 1;
-        let pid_bytes = ProgramId::from(1).encode();
-        let pid_len = pid_bytes.len();
+    // Benchmarks the case when condition is false, so `gr_wait_if` doesn't
+    // interrupt execution and can be called multiple times.
+    gr_wait_if {
+        let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_exit",
+                name: "gr_wait_if",
                 params: vec![ValueType::I32],
                 return_type: None,
             }],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                // condition
+                Instruction::I32Const(0),
+                Instruction::Call(0),
+            ])),
+            .. Default::default()
+        });
+        let instance = Program::<T>::new(code, vec![])?;
+        let Exec {
+            ext_manager,
+            block_config,
+            context,
+            memory_pages,
+        } = prepare::<T>(instance.caller.into_origin(), HandleKind::Handle(ProgramId::from_origin(instance.addr)), vec![], 0u32.into())?;
+    }: {
+        core_processor::process::<
+            Externalities,
+            ExecutionEnvironment,
+        >(&block_config, context, memory_pages);
+    }
+
+    gr_wake {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let message_ids = (0..r * API_BENCHMARK_BATCH_SIZE)
+            .map(|i| gear_core::ids::MessageId::from(i as u64))
+            .collect::<Vec<_>>();
+        let message_id_len = message_ids.get(0).map(|i| i.encode().len()).unwrap_or(0);
+        let message_id_bytes = message_ids.iter().flat_map(|x| x.encode()).collect();
+        let delay_offset = message_ids.len() * message_id_len;
+        let code = WasmModule::<T>::from(ModuleDefinition {
+            memory: Some(ImportedMemory::max::<T>()),
+            imported_functions: vec![ImportedFunction {
+                module: "env",
+                name: "gr_wake",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: None,
+            }],
             data_segments: vec![
                 DataSegment {
                     offset: 0_u32,
-                    value: pid_bytes,
+                    value: message_id_bytes,
                 },
             ],
-            handle_body: Some(body::repeated(r, &[
-                Instruction::I32Const(0),
-                Instruction::Call(0),
+            handle_body: Some(body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
+                Counter(0_u32, message_id_len as u32), // message_id_ptr
+                Regular(Instruction::I32Const(delay_offset as i32)), // delay_ptr
+                Regular(Instruction::Call(0)),
             ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
+        for message_id in message_ids {
+            let message = gear_core::message::Message::new(message_id, 1.into(), ProgramId::from(instance.addr.as_bytes()), Default::default(), Some(1_000_000), 0, None);
+            let dispatch = gear_core::message::Dispatch::new(gear_core::message::DispatchKind::Handle, message).into_stored();
+            WaitlistOf::<T>::insert(dispatch.clone(), u32::MAX.unique_saturated_into()).expect("Duplicate wl message");
+        }
         let Exec {
             ext_manager,
             block_config,
@@ -1496,20 +2687,19 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // We cannot call `gr_leave` multiple times. Therefore our weight determination is not
-    // as precise as with other APIs.
-    gr_leave {
-        let r in 0 .. 1;
+    gr_new_handle {
+        let r in 0 .. API_BENCHMARK_BATCHES;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_leave",
+                name: "gr_new_handle",
                 params: vec![],
-                return_type: None,
+                return_type: Some(ValueType::I32),
             }],
-            handle_body: Some(body::repeated(r, &[
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
                 Instruction::Call(0),
+                Instruction::Drop,
             ])),
             .. Default::default()
         });
@@ -1527,24 +2717,33 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // We cannot call `gr_wait` multiple times. Therefore our weight determination is not
-    // as precise as with other APIs.
-    gr_wait {
-        let r in 0 .. 1;
+    gr_delayed_eta {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let message_id = MessageId::from(10);
+        let message_id_bytes = message_id.encode();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_wait",
-                params: vec![],
-                return_type: None,
+                name: "gr_delayed_eta",
+                params: vec![ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
-            handle_body: Some(body::repeated(r, &[
+            data_segments: vec![
+                DataSegment {
+                    offset: 0_u32,
+                    value: message_id_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(0), // message_id_ptr
                 Instruction::Call(0),
+                Instruction::Drop,
             ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
+        common::delayed_dispatches_insert(ProgramId::from_origin(instance.addr), message_id, u32::MAX);
         let Exec {
             ext_manager,
             block_config,
@@ -1558,21 +2757,23 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // We cannot call `gr_wait_for` multiple times. Therefore our weight determination is not
-    // as precise as with other APIs.
-    gr_wait_for {
-        let r in 0 .. 1;
+    gr_reserve_value {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let value_offset = 0_u32;
+        let reservation_id_offset = value_offset + 0_u128.encode().len() as u32;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_wait_for",
-                params: vec![ValueType::I32],
-                return_type: None,
+                name: "gr_reserve_value",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
-            handle_body: Some(body::repeated(r, &[
-                Instruction::I32Const(100),
+            handle_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+                Instruction::I32Const(value_offset as i32), // value_ptr
+                Instruction::I32Const(reservation_id_offset as i32), // reservation_id_ptr
                 Instruction::Call(0),
+                Instruction::Drop,
             ])),
             .. Default::default()
         });
@@ -1590,25 +2791,41 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    // We cannot call `gr_wait_up_to` multiple times. Therefore our weight determination is not
-    // as precise as with other APIs.
-    gr_wait_up_to {
-        let r in 0 .. 1;
+    gr_unreserve_value {
+        let r in 0 .. API_BENCHMARK_BATCHES;
+        let reservation_ids = (0..r * API_BENCHMARK_BATCH_SIZE)
+            .map(|i| gear_core::ids::ReservationId::from(i as u64))
+            .collect::<Vec<_>>();
+        let reservation_id_len = reservation_ids.get(0).map(|i| i.encode().len()).unwrap_or(0);
+        let reservation_id_bytes = reservation_ids.iter().flat_map(|x| x.encode()).collect();
+        let value_offset = reservation_ids.len() * reservation_id_len;
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_wait_up_to",
-                params: vec![ValueType::I32],
-                return_type: None,
+                name: "gr_unreserve_value",
+                params: vec![ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
-            handle_body: Some(body::repeated(r, &[
-                Instruction::I32Const(100),
-                Instruction::Call(0),
+            data_segments: vec![
+                DataSegment {
+                    offset: 0_u32,
+                    value: reservation_id_bytes,
+                },
+            ],
+            handle_body: Some(body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
+                Counter(0_u32, reservation_id_len as u32), // reservation_id_ptr
+                Regular(Instruction::I32Const(value_offset as i32)), // value_ptr
+                Regular(Instruction::Call(0)),
+                Regular(Instruction::Drop),
             ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
+        common::set_value_reservations(
+            ProgramId::from_origin(instance.addr),
+            reservation_ids.into_iter().map(|id| (id, 0)).collect(),
+        );
         let Exec {
             ext_manager,
             block_config,
@@ -1622,39 +2839,47 @@ benchmarks! {
         >(&block_config, context, memory_pages);
     }
 
-    gr_wake {
+    gr_split_value_reservation {
         let r in 0 .. API_BENCHMARK_BATCHES;
-        let message_ids = (0..r * API_BENCHMARK_BATCH_SIZE)
-            .map(|i| gear_core::ids::MessageId::from(i as u64))
+        let reservation_ids = (0..r * API_BENCHMARK_BATCH_SIZE)
+            .map(|i| gear_core::ids::ReservationId::from(i as u64))
             .collect::<Vec<_>>();
-        let message_id_len = message_ids.get(0).map(|i| i.encode().len()).unwrap_or(0);
-        let message_id_bytes = message_ids.iter().flat_map(|x| x.encode()).collect();
+        let reservation_id_len = reservation_ids.get(0).map(|i| i.encode().len()).unwrap_or(0);
+        let reservation_id_bytes = reservation_ids.iter().flat_map(|x| x.encode()).collect();
+        let value_offset = reservation_ids.len() * reservation_id_len;
+        let split_id_offset = value_offset + 1_u128.encode().len();
         let code = WasmModule::<T>::from(ModuleDefinition {
             memory: Some(ImportedMemory::max::<T>()),
             imported_functions: vec![ImportedFunction {
                 module: "env",
-                name: "gr_wake",
-                params: vec![ValueType::I32],
-                return_type: None,
+                name: "gr_split_value_reservation",
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
             data_segments: vec![
                 DataSegment {
                     offset: 0_u32,
-                    value: message_id_bytes,
+                    value: reservation_id_bytes,
+                },
+                DataSegment {
+                    offset: value_offset as u32,
+                    value: 1_u128.encode(),
                 },
             ],
             handle_body: Some(body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
-                Counter(0_u32, message_id_len as u32), // message_id_ptr
+                Counter(0_u32, reservation_id_len as u32), // reservation_id_ptr
+                Regular(Instruction::I32Const(value_offset as i32)), // value_ptr
+                Regular(Instruction::I32Const(split_id_offset as i32)), // split_id_ptr
                 Regular(Instruction::Call(0)),
+                Regular(Instruction::Drop),
             ])),
             .. Default::default()
         });
         let instance = Program::<T>::new(code, vec![])?;
-        for message_id in message_ids {
-            let message = gear_core::message::Message::new(message_id, 1.into(), ProgramId::from(instance.addr.as_bytes()), Default::default(), Some(1_000_000), 0, None);
-            let dispatch = gear_core::message::Dispatch::new(gear_core::message::DispatchKind::Handle, message).into_stored();
-            WaitlistOf::<T>::insert(dispatch.clone(), u32::MAX.unique_saturated_into()).expect("Duplicate wl message");
-        }
+        common::set_value_reservations(
+            ProgramId::from_origin(instance.addr),
+            reservation_ids.into_iter().map(|id| (id, 1)).collect(),
+        );
         let Exec {
             ext_manager,
             block_config,
@@ -1684,8 +2909,8 @@ benchmarks! {
             imported_functions: vec![ImportedFunction {
                 module: "env",
                 name: "gr_create_program_wgas",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32],
-                return_type: None,
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
             data_segments: vec![
                 DataSegment {
@@ -1714,7 +2939,9 @@ benchmarks! {
                 Regular(Instruction::I64Const(100000000)),
                 Regular(Instruction::I32Const((salt_bytes_len + code_hash_len) as i32)),
                 Regular(Instruction::I32Const((value_bytes_len + salt_bytes_len + code_hash_len) as i32)),
+                Regular(Instruction::I32Const((value_bytes_len + salt_bytes_len + code_hash_len + 32) as i32)), // delay_ptr
                 Regular(Instruction::Call(0)),
+                Regular(Instruction::Drop),
             ])),
             .. Default::default()
         });
@@ -1748,8 +2975,8 @@ benchmarks! {
             imported_functions: vec![ImportedFunction {
                 module: "env",
                 name: "gr_create_program_wgas",
-                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32],
-                return_type: None,
+                params: vec![ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I32, ValueType::I64, ValueType::I32, ValueType::I32, ValueType::I32],
+                return_type: Some(ValueType::I32),
             }],
             data_segments: vec![
                 DataSegment {
@@ -1778,7 +3005,9 @@ benchmarks! {
                 Regular(Instruction::I64Const(100000000)),
                 Regular(Instruction::I32Const((salt_bytes_len + code_hash_len) as i32)),
                 Regular(Instruction::I32Const((value_bytes_len + salt_bytes_len + code_hash_len) as i32)),
+                Regular(Instruction::I32Const((value_bytes_len + salt_bytes_len + code_hash_len + 32) as i32)), // delay_ptr
                 Regular(Instruction::Call(0)),
+                Regular(Instruction::Drop),
             ])),
             .. Default::default()
         });
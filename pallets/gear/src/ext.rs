@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use alloc::collections::BTreeSet;
+use core::num::NonZeroU32;
 use core_processor::{Ext, ProcessorContext, ProcessorError, ProcessorExt};
 use gear_backend_common::{
     error_processor::IntoExtError, AsTerminationReason, ExtInfo, IntoExtInfo, TerminationReason,
@@ -24,11 +25,15 @@ use gear_backend_common::{
 };
 use gear_core::{
     costs::RuntimeCosts,
+    counters::{CounterKey, ProgramCounters},
     env::Ext as EnvExt,
     gas::GasAmount,
-    ids::{MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{Memory, PageBuf, WasmPageNumber},
-    message::{ExitCode, HandlePacket, InitPacket, ReplyPacket},
+    message::{
+        ContextStoreLimits, DispatchKind, ExitCode, HandlePacket, InitPacket, ReplyPacket,
+        SignalCode,
+    },
 };
 use gear_core_errors::{CoreError, ExtError, MemoryError};
 use gear_lazy_pages_common as lazy_pages;
@@ -80,6 +85,12 @@ impl IntoExtInfo for LazyPagesExt {
             message_context,
             gas_counter,
             program_candidates_data,
+            value_reservations,
+            counters,
+            scheduled_exit,
+            entry_budgets,
+            charge_allowance,
+            reply_waker,
             ..
         } = self.inner.context;
 
@@ -104,7 +115,7 @@ impl IntoExtInfo for LazyPagesExt {
         }
 
         let (outcome, context_store) = message_context.drain();
-        let (generated_dispatches, awakening) = outcome.drain();
+        let (generated_dispatches, awakening, recipient_charged) = outcome.drain();
 
         let info = ExtInfo {
             gas_amount: gas_counter.into(),
@@ -112,8 +123,15 @@ impl IntoExtInfo for LazyPagesExt {
             pages_data: accessed_pages_data,
             generated_dispatches,
             awakening,
+            recipient_charged,
             context_store,
             program_candidates_data,
+            value_reservations: value_reservations.into_changed(),
+            counters: counters.and_then(ProgramCounters::into_changed),
+            scheduled_exit,
+            entry_budgets,
+            charge_allowance,
+            reply_waker,
         };
         Ok(info)
     }
@@ -238,6 +256,38 @@ impl EnvExt for LazyPagesExt {
         self.inner.block_timestamp().map_err(Error::Processor)
     }
 
+    fn block_duration(&mut self) -> Result<u64, Self::Error> {
+        self.inner.block_duration().map_err(Error::Processor)
+    }
+
+    fn block_hash(&mut self, height: u32) -> Result<[u8; 32], Self::Error> {
+        self.inner.block_hash(height).map_err(Error::Processor)
+    }
+
+    fn tx_hash(&mut self) -> Result<[u8; 32], Self::Error> {
+        self.inner.tx_hash().map_err(Error::Processor)
+    }
+
+    fn stack_limit(&mut self) -> Result<Option<u32>, Self::Error> {
+        self.inner.stack_limit().map_err(Error::Processor)
+    }
+
+    fn children_count(&mut self) -> Result<u64, Self::Error> {
+        self.inner.children_count().map_err(Error::Processor)
+    }
+
+    fn children_gas_burned(&mut self) -> Result<u64, Self::Error> {
+        self.inner.children_gas_burned().map_err(Error::Processor)
+    }
+
+    fn codes_count(&mut self) -> Result<u64, Self::Error> {
+        self.inner.codes_count().map_err(Error::Processor)
+    }
+
+    fn context_limits(&mut self) -> Result<ContextStoreLimits, Self::Error> {
+        self.inner.context_limits().map_err(Error::Processor)
+    }
+
     fn origin(&mut self) -> Result<ProgramId, Self::Error> {
         self.inner.origin().map_err(Error::Processor)
     }
@@ -260,14 +310,74 @@ impl EnvExt for LazyPagesExt {
         &mut self,
         handle: usize,
         msg: HandlePacket,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     ) -> Result<MessageId, Self::Error> {
         self.inner
             .send_commit(handle, msg, delay)
             .map_err(Error::Processor)
     }
 
-    fn reply_commit(&mut self, msg: ReplyPacket, delay: u32) -> Result<MessageId, Self::Error> {
+    fn send_commit_charge_recipient(
+        &mut self,
+        handle: usize,
+        msg: HandlePacket,
+    ) -> Result<MessageId, Self::Error> {
+        self.inner
+            .send_commit_charge_recipient(handle, msg)
+            .map_err(Error::Processor)
+    }
+
+    fn reservation_send_commit(
+        &mut self,
+        id: ReservationId,
+        handle: usize,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        self.inner
+            .reservation_send_commit(id, handle, msg, delay)
+            .map_err(Error::Processor)
+    }
+
+    fn reserve_and_send(
+        &mut self,
+        amount: u128,
+        msg: HandlePacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<(ReservationId, MessageId), Self::Error> {
+        self.inner
+            .reserve_and_send(amount, msg, delay)
+            .map_err(Error::Processor)
+    }
+
+    fn forward(
+        &mut self,
+        destination: ProgramId,
+        value: u128,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
+        self.inner
+            .forward(destination, value, delay)
+            .map_err(Error::Processor)
+    }
+
+    fn set_default_gas_limit(&mut self, gas_limit: u64) -> Result<(), Self::Error> {
+        self.inner
+            .set_default_gas_limit(gas_limit)
+            .map_err(Error::Processor)
+    }
+
+    fn set_charge_allowance(&mut self, allowance: u64) -> Result<(), Self::Error> {
+        self.inner
+            .set_charge_allowance(allowance)
+            .map_err(Error::Processor)
+    }
+
+    fn reply_commit(
+        &mut self,
+        msg: ReplyPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<MessageId, Self::Error> {
         self.inner
             .reply_commit(msg, delay)
             .map_err(Error::Processor)
@@ -281,14 +391,38 @@ impl EnvExt for LazyPagesExt {
         self.inner.source().map_err(Error::Processor)
     }
 
+    fn source_code_id(&mut self) -> Result<CodeId, Self::Error> {
+        self.inner.source_code_id().map_err(Error::Processor)
+    }
+
     fn exit(&mut self) -> Result<(), Self::Error> {
         self.inner.exit().map_err(Error::Processor)
     }
 
+    fn schedule_exit(&mut self, block: u32, inheritor: ProgramId) -> Result<(), Self::Error> {
+        self.inner
+            .schedule_exit(block, inheritor)
+            .map_err(Error::Processor)
+    }
+
+    fn set_entry_budget(&mut self, entry: u32, gas: u64) -> Result<(), Self::Error> {
+        self.inner
+            .set_entry_budget(entry, gas)
+            .map_err(Error::Processor)
+    }
+
     fn exit_code(&mut self) -> Result<Option<ExitCode>, Self::Error> {
         self.inner.exit_code().map_err(Error::Processor)
     }
 
+    fn signal_details(&mut self) -> Result<(MessageId, SignalCode), Self::Error> {
+        self.inner.signal_details().map_err(Error::Processor)
+    }
+
+    fn execution_id(&mut self) -> Result<u64, Self::Error> {
+        self.inner.execution_id().map_err(Error::Processor)
+    }
+
     fn message_id(&mut self) -> Result<MessageId, Self::Error> {
         self.inner.message_id().map_err(Error::Processor)
     }
@@ -305,14 +439,46 @@ impl EnvExt for LazyPagesExt {
         self.inner.debug(data).map_err(Error::Processor)
     }
 
+    fn trace(&mut self, level: u32, data: &str) -> Result<(), Self::Error> {
+        self.inner.trace(level, data).map_err(Error::Processor)
+    }
+
     fn read(&mut self) -> Result<&[u8], Self::Error> {
         self.inner.read().map_err(Error::Processor)
     }
 
+    fn read_part(&mut self, len: usize) -> Result<&[u8], Self::Error> {
+        self.inner.read_part(len).map_err(Error::Processor)
+    }
+
     fn size(&mut self) -> Result<usize, Self::Error> {
         self.inner.size().map_err(Error::Processor)
     }
 
+    fn message_meta(&mut self) -> Result<&[u8], Self::Error> {
+        self.inner.message_meta().map_err(Error::Processor)
+    }
+
+    fn mailbox_contains(
+        &mut self,
+        user_id: ProgramId,
+        message_id: MessageId,
+    ) -> Result<bool, Self::Error> {
+        self.inner
+            .mailbox_contains(user_id, message_id)
+            .map_err(Error::Processor)
+    }
+
+    fn record_not_found(&mut self, index: u32, count: u32) -> Result<(), Self::Error> {
+        self.inner
+            .record_not_found(index, count)
+            .map_err(Error::Processor)
+    }
+
+    fn max_payload_size(&mut self) -> Result<u32, Self::Error> {
+        self.inner.max_payload_size().map_err(Error::Processor)
+    }
+
     fn charge_gas(&mut self, val: u64) -> Result<(), Self::Error> {
         self.inner.charge_gas(val).map_err(Error::Processor)
     }
@@ -329,6 +495,18 @@ impl EnvExt for LazyPagesExt {
         self.inner.gas_available().map_err(Error::Processor)
     }
 
+    fn gas_allowance(&mut self) -> Result<u64, Self::Error> {
+        self.inner.gas_allowance().map_err(Error::Processor)
+    }
+
+    fn is_init(&mut self) -> Result<bool, Self::Error> {
+        self.inner.is_init().map_err(Error::Processor)
+    }
+
+    fn resumed_from_wait(&mut self) -> Result<bool, Self::Error> {
+        self.inner.resumed_from_wait().map_err(Error::Processor)
+    }
+
     fn value(&mut self) -> Result<u128, Self::Error> {
         self.inner.value().map_err(Error::Processor)
     }
@@ -341,6 +519,12 @@ impl EnvExt for LazyPagesExt {
         self.inner.wait().map_err(Error::Processor)
     }
 
+    fn wait_for_reply(&mut self, message_id: MessageId) -> Result<(), Self::Error> {
+        self.inner
+            .wait_for_reply(message_id)
+            .map_err(Error::Processor)
+    }
+
     fn wait_for(&mut self, duration: u32) -> Result<(), Self::Error> {
         self.inner.wait_for(duration).map_err(Error::Processor)
     }
@@ -349,7 +533,15 @@ impl EnvExt for LazyPagesExt {
         self.inner.wait_up_to(duration).map_err(Error::Processor)
     }
 
-    fn wake(&mut self, waker_id: MessageId, delay: u32) -> Result<(), Self::Error> {
+    fn wait_budget_remaining(&mut self) -> Result<u32, Self::Error> {
+        self.inner.wait_budget_remaining().map_err(Error::Processor)
+    }
+
+    fn wait_if(&mut self, condition: bool) -> Result<(), Self::Error> {
+        self.inner.wait_if(condition).map_err(Error::Processor)
+    }
+
+    fn wake(&mut self, waker_id: MessageId, delay: Option<NonZeroU32>) -> Result<(), Self::Error> {
         self.inner.wake(waker_id, delay).map_err(Error::Processor)
     }
 
@@ -357,7 +549,15 @@ impl EnvExt for LazyPagesExt {
         self.inner.value_available().map_err(Error::Processor)
     }
 
-    fn create_program(&mut self, packet: InitPacket, delay: u32) -> Result<ProgramId, Self::Error> {
+    fn spendable_value(&mut self) -> Result<u128, Self::Error> {
+        self.inner.spendable_value().map_err(Error::Processor)
+    }
+
+    fn create_program(
+        &mut self,
+        packet: InitPacket,
+        delay: Option<NonZeroU32>,
+    ) -> Result<(MessageId, ProgramId), Self::Error> {
         self.inner
             .create_program(packet, delay)
             .map_err(Error::Processor)
@@ -369,7 +569,41 @@ impl EnvExt for LazyPagesExt {
             .map_err(Error::Processor)
     }
 
+    fn new_handle(&mut self) -> Result<u32, Self::Error> {
+        self.inner.new_handle().map_err(Error::Processor)
+    }
+
+    fn delayed_eta(&mut self, message_id: MessageId) -> Result<u32, Self::Error> {
+        self.inner.delayed_eta(message_id).map_err(Error::Processor)
+    }
+
+    fn reserve_value(&mut self, value: u128) -> Result<ReservationId, Self::Error> {
+        self.inner.reserve_value(value).map_err(Error::Processor)
+    }
+
+    fn unreserve_value(&mut self, id: ReservationId) -> Result<u128, Self::Error> {
+        self.inner.unreserve_value(id).map_err(Error::Processor)
+    }
+
+    fn split_value_reservation(
+        &mut self,
+        id: ReservationId,
+        value: u128,
+    ) -> Result<ReservationId, Self::Error> {
+        self.inner
+            .split_value_reservation(id, value)
+            .map_err(Error::Processor)
+    }
+
+    fn bump_counter(&mut self, key: CounterKey) -> Result<u64, Self::Error> {
+        self.inner.bump_counter(key).map_err(Error::Processor)
+    }
+
     fn forbidden_funcs(&self) -> &BTreeSet<&'static str> {
         &self.inner.context.forbidden_funcs
     }
+
+    fn dispatch_kind(&self) -> DispatchKind {
+        self.inner.context.dispatch_kind
+    }
 }
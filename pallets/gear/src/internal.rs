@@ -19,10 +19,12 @@
 //! Internal details of Gear Pallet implementation.
 
 use crate::{
-    Authorship, BalanceOf, Config, CostsPerBlockOf, CurrencyOf, Event, GasBalanceOf, GasHandlerOf,
-    MailboxOf, Pallet, SchedulingCostOf, SystemPallet, TaskPoolOf, WaitlistOf,
+    Authorship, BalanceOf, CodeDepositOf, CodeRefcount, CodeStats, CodeStatsCount, CodeStatsOf,
+    Config, CostsPerBlockOf, CurrencyOf, Error, Event, GasAllowanceOf, GasBalanceOf, GasHandlerOf,
+    MailboxOf, Pallet, ScheduledExitOf, SchedulingCostOf, SystemPallet, TaskPoolOf, TxHashOf,
+    TxHashWithheldOf, WaitlistOf,
 };
-use alloc::collections::BTreeSet;
+use alloc::{collections::BTreeSet, vec::Vec};
 use codec::{Decode, Encode};
 use common::{
     event::{
@@ -32,16 +34,20 @@ use common::{
     gas_provider::GasNodeId,
     scheduler::*,
     storage::*,
-    GasPrice, GasTree, Origin,
+    GasPrice, GasTree, Origin, Program,
 };
-use core::cmp::{Ord, Ordering};
-use core_processor::common::ExecutionErrorReason;
-use frame_support::traits::{
-    BalanceStatus, Currency, ExistenceRequirement, Imbalance, ReservableCurrency,
+use core::{
+    cmp::{Ord, Ordering},
+    num::NonZeroU32,
+};
+use core_processor::common::{DispatchOutcome, ExecutionErrorReason, JournalNote};
+use frame_support::{
+    traits::{BalanceStatus, Currency, ExistenceRequirement, Imbalance, ReservableCurrency},
+    weights::Weight,
 };
 use frame_system::pallet_prelude::BlockNumberFor;
 use gear_core::{
-    ids::{MessageId, ProgramId},
+    ids::{CodeId, MessageId, ProgramId},
     message::{Dispatch, Message, StoredDispatch, StoredMessage},
 };
 use sp_runtime::traits::{Get, One, SaturatedConversion, Saturating, UniqueSaturatedInto, Zero};
@@ -258,10 +264,20 @@ where
     ///
     /// Updates currency and balances data on imbalance creation.
     pub(crate) fn consume_message(message_id: MessageId) {
+        // Querying origin message id before the node is gone.
+        let origin_msg = GasHandlerOf::<T>::get_origin_key(message_id)
+            .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+
         // Consuming `GasNode`, returning optional outcome with imbalance.
         let outcome = GasHandlerOf::<T>::consume(message_id)
             .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
 
+        // Removing hash of the originating transaction once the whole tree is gone.
+        if !GasHandlerOf::<T>::exists(origin_msg) {
+            TxHashOf::<T>::remove(origin_msg);
+        }
+        TxHashWithheldOf::<T>::remove(message_id);
+
         // Unreserving funds, if imbalance returned.
         if let Some((imbalance, external)) = outcome {
             // Peeking numeric value from negative imbalance.
@@ -407,6 +423,10 @@ where
         GasHandlerOf::<T>::unlock_all(waitlisted.id())
             .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
 
+        // Forgetting the reply the message waited for, so it
+        // can't wake the message once it waits again.
+        common::clear_reply_waker_of(waitlisted.destination(), waitlisted.id());
+
         // Charging for holding.
         Self::charge_for_hold(
             waitlisted.id(),
@@ -520,14 +540,10 @@ where
     pub(crate) fn send_delayed_dispatch(
         origin_msg: MessageId,
         dispatch: Dispatch,
-        delay: u32,
+        delay: NonZeroU32,
         to_user: bool,
+        payer: Option<T::AccountId>,
     ) {
-        // Validating delay
-        if delay.is_zero() {
-            unreachable!("Delayed sending with zero delay appeared");
-        }
-
         // Taking data for funds manipulations.
         let from = <T::AccountId as Origin>::from_origin(dispatch.source().into_origin());
         let value = dispatch.value().unique_saturated_into();
@@ -568,24 +584,13 @@ where
                 GasHandlerOf::<T>::cut(origin_msg, dispatch.id(), gas_limit)
                     .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
             }
-        } else if let Some(gas_limit) = dispatch.gas_limit() {
-            // # Safety
-            //
-            // 1. There is no logic splitting value from the reserved nodes.
-            // 2. The `gas_limit` has been checked inside message queue processing.
-            // 3. The `value` of the value node has been checked before.
-            // 4. The `dispatch.id()` is new generated by system from a checked
-            //    ( inside message queue processing ) `message_id`.
-            GasHandlerOf::<T>::split_with_value(origin_msg, dispatch.id(), gas_limit)
-                .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
         } else {
-            // # Safety
-            //
-            // 1. There is no logic splitting value from the reserved nodes.
-            // 2. The `dispatch.id()` is new generated by system from a checked
-            //    ( inside message queue processing ) `message_id`.
-            GasHandlerOf::<T>::split(origin_msg, dispatch.id())
-                .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+            Self::create_dispatch_gas_node(origin_msg, dispatch.id(), dispatch.gas_limit(), payer);
+        }
+
+        // Delayed messages are executed apart from the originating transaction.
+        if !to_user {
+            Pallet::<T>::withhold_tx_hash(origin_msg, dispatch.id(), true);
         }
 
         if !dispatch.value().is_zero() {
@@ -594,14 +599,66 @@ where
                 .unwrap_or_else(|e| unreachable!("Unable to reserve requested value {:?}", e));
         }
 
+        let expected_bn =
+            SystemPallet::<T>::block_number().saturating_add(delay.get().unique_saturated_into());
+
+        // Remembering block of dispatching for the source program queries.
+        common::delayed_dispatches_insert(
+            dispatch.source(),
+            dispatch.id(),
+            expected_bn.unique_saturated_into(),
+        );
+
         // Adding removal request in task pool.
         TaskPoolOf::<T>::add(
-            SystemPallet::<T>::block_number().saturating_add(delay.unique_saturated_into()),
+            expected_bn,
             ScheduledTask::SendDispatch(dispatch.into_stored()),
         )
         .unwrap_or_else(|e| unreachable!("Scheduling logic invalidated! {:?}", e));
     }
 
+    /// Creates gas node of the dispatch sent to program by `origin_msg`.
+    ///
+    /// Gas is split from the `origin_msg` node, unless the `payer` buys it.
+    pub(crate) fn create_dispatch_gas_node(
+        origin_msg: MessageId,
+        dispatch_id: MessageId,
+        gas_limit: Option<u64>,
+        payer: Option<T::AccountId>,
+    ) {
+        match (gas_limit, payer) {
+            (Some(gas_limit), Some(payer)) => {
+                // # Safety
+                //
+                // This is unreachable since the `dispatch_id` is new generated
+                // by system from a checked ( inside message queue processing ) `origin_msg`.
+                GasHandlerOf::<T>::create(payer, dispatch_id, gas_limit)
+                    .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+            }
+            (Some(gas_limit), None) => {
+                // # Safety
+                //
+                // 1. There is no logic splitting value from the reserved nodes.
+                // 2. The `gas_limit` has been checked inside message queue processing.
+                // 3. The `value` of the value node has been checked before.
+                // 4. The `dispatch_id` is new generated by system from a checked
+                //    ( inside message queue processing ) `origin_msg`.
+                GasHandlerOf::<T>::split_with_value(origin_msg, dispatch_id, gas_limit)
+                    .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+            }
+            (None, None) => {
+                // # Safety
+                //
+                // 1. There is no logic splitting value from the reserved nodes.
+                // 2. The `dispatch_id` is new generated by system from a checked
+                //    ( inside message queue processing ) `origin_msg`.
+                GasHandlerOf::<T>::split(origin_msg, dispatch_id)
+                    .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+            }
+            (None, Some(_)) => unreachable!("Recipient charged dispatch always has gas limit"),
+        }
+    }
+
     /// Sends message to user.
     ///
     /// It may be added to mailbox, if apply requirements.
@@ -631,14 +688,15 @@ where
         //
         // Note: for users, trap replies always contain
         // string explanation of the error.
-        let message = match message.exit_code() {
-            Some(0) | None => message,
-            _ => message
+        let message = if message.is_error_reply() {
+            message
                 .with_string_payload::<ExecutionErrorReason>()
                 .unwrap_or_else(|e| {
                     log::debug!("Failed to decode error to string");
                     e
-                }),
+                })
+        } else {
+            message
         };
 
         // Converting message into stored one.
@@ -706,14 +764,15 @@ where
         //
         // We don't plan to send delayed error replies yet,
         // but this logic appears here for future purposes.
-        let message = match message.exit_code() {
-            Some(0) | None => message,
-            _ => message
+        let message = if message.is_error_reply() {
+            message
                 .with_string_payload::<ExecutionErrorReason>()
                 .unwrap_or_else(|e| {
                     log::debug!("Failed to decode error to string");
                     e
-                }),
+                })
+        } else {
+            message
         };
 
         // Taking data for funds manipulations.
@@ -781,4 +840,158 @@ where
 
         inheritor
     }
+
+    /// Accounts execution of the message by the program
+    /// with the `code_id` in the current code stats era.
+    ///
+    /// Storage accesses are charged from the gas allowance of the block,
+    /// as a part of the dispatch.
+    pub(crate) fn note_code_stats(code_id: CodeId, journal: &[JournalNote]) {
+        let mut executed = false;
+        let mut trapped = false;
+        let mut gas_burned = 0u64;
+
+        for note in journal {
+            match note {
+                JournalNote::GasBurned { amount, .. } => {
+                    gas_burned = gas_burned.saturating_add(*amount);
+                }
+                JournalNote::MessageDispatched { outcome, .. } => {
+                    executed = !matches!(outcome, DispatchOutcome::NoExecution);
+                    trapped = matches!(
+                        outcome,
+                        DispatchOutcome::MessageTrap { .. } | DispatchOutcome::InitFailure { .. }
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        if !executed && gas_burned.is_zero() {
+            return;
+        }
+
+        // Charging the worst case: reading and updating both
+        // the stats of the code and amount of codes tracked within the era.
+        GasAllowanceOf::<T>::decrease(Self::code_stats_weight());
+
+        let era = Self::code_stats_era(SystemPallet::<T>::block_number());
+
+        if !CodeStatsOf::<T>::contains_key(era, code_id) {
+            let tracked = CodeStatsCount::<T>::get(era);
+
+            if tracked >= T::MaxCodeStats::get() {
+                log::debug!("Code stats limit reached in era {era}, skipping {code_id:?}");
+                return;
+            }
+
+            CodeStatsCount::<T>::insert(era, tracked.saturating_add(1));
+        }
+
+        CodeStatsOf::<T>::mutate(era, code_id, |stats| {
+            let stats = stats.get_or_insert_with(Default::default);
+
+            stats.executions = stats.executions.saturating_add(executed as u32);
+            stats.gas_burned = stats.gas_burned.saturating_add(gas_burned);
+            stats.traps = stats.traps.saturating_add(trapped as u32);
+        });
+    }
+
+    /// Weight of the storage accesses of [`Self::note_code_stats`].
+    pub(crate) fn code_stats_weight() -> u64 {
+        T::DbWeight::get().reads_writes(2, 2).ref_time()
+    }
+
+    /// Stats of the codes, which burned the most gas within the `era`.
+    pub(crate) fn top_code_stats(era: u32, limit: u32) -> Vec<(CodeId, CodeStats)> {
+        let mut top: Vec<_> = CodeStatsOf::<T>::iter_prefix(era).collect();
+
+        top.sort_by(|(id_a, a), (id_b, b)| {
+            b.gas_burned
+                .cmp(&a.gas_burned)
+                .then_with(|| id_a.as_ref().cmp(id_b.as_ref()))
+        });
+        top.truncate(limit as usize);
+
+        top
+    }
+
+    /// Emits digest of the finished code stats era, if any code was executed
+    /// within it, and removes the era falling out of the history,
+    /// if the new era starts at the block `bn`.
+    pub(crate) fn rotate_code_stats(bn: BlockNumberFor<T>) -> Weight {
+        let era_length = T::CodeStatsEraLength::get().max(One::one());
+
+        if bn.is_zero() || !(bn % era_length).is_zero() {
+            return Weight::zero();
+        }
+
+        let era = Self::code_stats_era(bn);
+        let finished = era.saturating_sub(1);
+        let tracked = CodeStatsCount::<T>::get(finished);
+
+        if tracked > 0 {
+            Self::deposit_event(Event::CodeStatsDigest {
+                era: finished,
+                top: Self::top_code_stats(finished, T::CodeStatsDigestLength::get()),
+            });
+        }
+
+        let mut removed = 0;
+
+        if let Some(outdated) = era.checked_sub(T::CodeStatsHistoryDepth::get().max(1)) {
+            removed = CodeStatsOf::<T>::clear_prefix(outdated, T::MaxCodeStats::get(), None).unique;
+            CodeStatsCount::<T>::remove(outdated);
+        }
+
+        T::DbWeight::get().reads_writes(
+            u64::from(tracked).saturating_add(1),
+            u64::from(removed).saturating_add(2),
+        )
+    }
+
+    /// Reserves storage deposit for the new code of `code_len` bytes
+    /// from its uploader `who`.
+    pub(crate) fn reserve_code_deposit(
+        who: &T::AccountId,
+        code_id: CodeId,
+        code_len: usize,
+    ) -> Result<(), Error<T>> {
+        let code_len: BalanceOf<T> = (code_len as u64).unique_saturated_into();
+        let deposit = T::CodeDepositPerByte::get().saturating_mul(code_len);
+
+        CurrencyOf::<T>::reserve(who, deposit)
+            .map_err(|_| Error::<T>::NotEnoughBalanceForReserve)?;
+
+        CodeDepositOf::<T>::insert(code_id, (who.clone(), deposit));
+
+        Ok(())
+    }
+
+    /// Counts new active program running the code.
+    pub(crate) fn acquire_code(code_id: CodeId) {
+        CodeRefcount::<T>::mutate(code_id, |count| *count = count.saturating_add(1));
+    }
+
+    /// Stops counting the program as running its code,
+    /// if the program is still active.
+    pub(crate) fn release_code_of(program_id: ProgramId) {
+        if let Some(Program::Active(program)) = common::get_program(program_id.into_origin()) {
+            CodeRefcount::<T>::mutate_exists(CodeId::from_origin(program.code_hash), |count| {
+                *count = count
+                    .and_then(|count| count.checked_sub(1))
+                    .filter(|count| !count.is_zero());
+            });
+        }
+    }
+
+    /// Removes the exit scheduled by the program from the task pool.
+    pub(crate) fn cancel_scheduled_exit(program_id: ProgramId) {
+        if let Some((bn, inheritor)) = ScheduledExitOf::<T>::take(program_id) {
+            let task = ScheduledTask::ExitProgram(program_id, inheritor);
+
+            TaskPoolOf::<T>::delete(bn, task)
+                .unwrap_or_else(|e| unreachable!("Scheduling logic invalidated! {:?}", e));
+        }
+    }
 }
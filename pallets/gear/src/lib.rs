@@ -47,9 +47,10 @@ pub use crate::{
     pallet::*,
     schedule::{HostFnWeights, InstructionWeights, Limits, Schedule},
 };
+pub use core_processor::configs::ExistentialDepositPolicy;
 pub use weights::WeightInfo;
 
-use common::{scheduler::*, storage::*, BlockLimiter, CodeStorage, GasProvider};
+use common::{scheduler::*, storage::*, BlockLimiter, CodeStorage, GasProvider, Origin};
 use frame_support::{
     traits::{Currency, StorageVersion},
     weights::Weight,
@@ -66,7 +67,7 @@ use gear_core::{
 };
 use pallet_gear_program::Pallet as GearProgramPallet;
 use primitive_types::H256;
-use sp_runtime::traits::{Saturating, UniqueSaturatedInto, Zero};
+use sp_runtime::traits::{One, Saturating, UniqueSaturatedInto, Zero};
 use sp_std::{
     collections::{btree_map::BTreeMap, btree_set::BTreeSet},
     convert::TryInto,
@@ -96,7 +97,7 @@ pub type GasHandlerOf<T> = <<T as Config>::GasProvider as GasProvider>::GasTree;
 pub type BlockGasLimitOf<T> = <<T as Config>::BlockLimiter as BlockLimiter>::BlockGasLimit;
 
 /// The current storage version.
-const GEAR_STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+const GEAR_STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
 pub trait DebugInfo {
     fn is_remap_id_enabled() -> bool;
@@ -137,6 +138,68 @@ pub struct GasInfo {
     pub waited: bool,
 }
 
+/// Maximum amount of messages in a page of stored messages.
+pub const MAX_PAGE_LEN: u32 = 100;
+
+/// Maximum length of the payload included into [`MessagePreview`].
+pub const MAX_PREVIEW_PAYLOAD_LEN: u32 = 1024;
+
+/// Stored message, as it's shown to explorers.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct MessagePreview {
+    /// Message id.
+    pub id: H256,
+    /// Message source.
+    pub source: H256,
+    /// Message destination.
+    pub destination: H256,
+    /// Message value.
+    pub value: u128,
+    /// Length of the message payload.
+    pub payload_len: u32,
+    /// Message payload, if it's not longer than [`MAX_PREVIEW_PAYLOAD_LEN`].
+    pub payload: Option<Vec<u8>>,
+}
+
+impl From<StoredMessage> for MessagePreview {
+    fn from(message: StoredMessage) -> Self {
+        let payload_len = message.payload().len() as u32;
+
+        Self {
+            id: message.id().into_origin(),
+            source: message.source().into_origin(),
+            destination: message.destination().into_origin(),
+            value: message.value(),
+            payload_len,
+            payload: (payload_len <= MAX_PREVIEW_PAYLOAD_LEN).then(|| message.payload().to_vec()),
+        }
+    }
+}
+
+/// Page of stored messages: mailbox pages are sorted by ids, queue pages
+/// follow the order of processing.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct MessagesPage {
+    /// Messages of the page.
+    pub messages: Vec<MessagePreview>,
+    /// Cursor of the next page, if there may be more messages.
+    pub next_cursor: Option<H256>,
+}
+
+/// Execution statistics of a code within a stats era.
+#[derive(Clone, Copy, Debug, Default, Decode, Encode, PartialEq, Eq, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct CodeStats {
+    /// Amount of messages executed by programs with the code.
+    pub executions: u32,
+    /// Amount of gas burned by programs with the code.
+    pub gas_burned: u64,
+    /// Amount of executions, which ended up with a trap.
+    pub traps: u32,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
@@ -156,7 +219,10 @@ pub mod pallet {
         Program, ProgramState,
     };
     use core_processor::{
-        common::{Actor, DispatchOutcome as CoreDispatchOutcome, ExecutableActorData, JournalNote},
+        common::{
+            Actor, DispatchOutcome as CoreDispatchOutcome, ExecutableActorData, JournalHasher,
+            JournalNote,
+        },
         configs::{AllocationsConfig, BlockConfig, BlockInfo, MessageExecutionContext},
         PrepareResult,
     };
@@ -212,6 +278,42 @@ pub mod pallet {
         #[pallet::constant]
         type MailboxThreshold: Get<u64>;
 
+        /// Policy of providing existential deposit for accounts of the programs
+        /// created by other programs with zero value.
+        ///
+        /// Shouldn't be changed while programs exist, since `Exempt` policy
+        /// holds provider references for accounts of the programs.
+        #[pallet::constant]
+        type ProgramExistentialDeposit: Get<ExistentialDepositPolicy>;
+
+        /// Amount of blocks in a code stats era.
+        ///
+        /// Digest of the most expensive codes is emitted once an era ends.
+        #[pallet::constant]
+        type CodeStatsEraLength: Get<Self::BlockNumber>;
+
+        /// Amount of the last code stats eras kept in storage.
+        #[pallet::constant]
+        type CodeStatsHistoryDepth: Get<u32>;
+
+        /// Maximal amount of codes tracked within a code stats era.
+        ///
+        /// Codes executed after the limit is reached aren't tracked
+        /// till the next era.
+        #[pallet::constant]
+        type MaxCodeStats: Get<u32>;
+
+        /// Amount of the most expensive codes included into the era digest.
+        #[pallet::constant]
+        type CodeStatsDigestLength: Get<u32>;
+
+        /// Storage deposit reserved per byte of the uploaded code
+        /// from its original uploader.
+        ///
+        /// Deposit is returned, once the code is removed.
+        #[pallet::constant]
+        type CodeDepositPerByte: Get<BalanceOf<Self>>;
+
         /// Messenger.
         type Messenger: Messenger<
             BlockNumber = Self::BlockNumber,
@@ -220,6 +322,7 @@ pub mod pallet {
             MailboxFirstKey = Self::AccountId,
             MailboxSecondKey = MessageId,
             MailboxedMessage = StoredMessage,
+            QueueKey = MessageId,
             QueuedDispatch = StoredDispatch,
             WaitlistFirstKey = ProgramId,
             WaitlistSecondKey = MessageId,
@@ -253,6 +356,68 @@ pub mod pallet {
     #[pallet::generate_store(pub(super) trait Store)]
     pub struct Pallet<T>(PhantomData<T>);
 
+    /// Hashes of external transactions, mapped by ids of the messages
+    /// they have initiated.
+    ///
+    /// Entry is kept while the gas tree of the message exists,
+    /// so every message of the chain is able to query the hash.
+    #[pallet::storage]
+    pub(crate) type TxHashOf<T> = StorageMap<_, Identity, MessageId, [u8; 32]>;
+
+    /// Messages of the transaction chains, which don't get the hash,
+    /// since they or the messages they were sent by were delayed.
+    ///
+    /// Entry is kept till the message is consumed.
+    #[pallet::storage]
+    pub(crate) type TxHashWithheldOf<T> = StorageMap<_, Identity, MessageId, ()>;
+
+    /// Identifier of the next execution of a message.
+    ///
+    /// Every execution gets its own identifier, including executions of
+    /// the same message after being woken.
+    #[pallet::storage]
+    pub(crate) type ExecutionNonce<T> = StorageValue<_, u64, ValueQuery>;
+
+    /// Execution statistics of codes, mapped by code stats eras.
+    ///
+    /// Only the last `CodeStatsHistoryDepth` eras are kept:
+    /// the oldest one is removed once a new era starts.
+    #[pallet::storage]
+    pub type CodeStatsOf<T> = StorageDoubleMap<_, Identity, u32, Identity, CodeId, CodeStats>;
+
+    /// Amount of codes tracked within code stats eras.
+    #[pallet::storage]
+    pub(crate) type CodeStatsCount<T> = StorageMap<_, Identity, u32, u32, ValueQuery>;
+
+    /// Storage deposits of codes along with the accounts,
+    /// which uploaded the codes and paid the deposits.
+    #[pallet::storage]
+    pub(crate) type CodeDepositOf<T: Config> =
+        StorageMap<_, Identity, CodeId, (T::AccountId, BalanceOf<T>)>;
+
+    /// Amount of active programs running a code.
+    ///
+    /// Code can't be removed while any program runs it.
+    #[pallet::storage]
+    pub(crate) type CodeRefcount<T> = StorageMap<_, Identity, CodeId, u32, ValueQuery>;
+
+    /// Programs, which accounts are kept by a provider reference
+    /// instead of existential deposit.
+    ///
+    /// The reference is released on exit only for the programs listed here,
+    /// so changing `ProgramExistentialDeposit` doesn't affect the programs
+    /// created before.
+    #[pallet::storage]
+    pub(crate) type ProgramProviders<T> = StorageMap<_, Identity, ProgramId, ()>;
+
+    /// Exits scheduled by programs along with the inheritors of their value.
+    ///
+    /// A program has at most one exit in the task pool: scheduling it again
+    /// replaces the task.
+    #[pallet::storage]
+    pub(crate) type ScheduledExitOf<T: Config> =
+        StorageMap<_, Identity, ProgramId, (BlockNumberFor<T>, ProgramId)>;
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -366,6 +531,16 @@ pub mod pallet {
             /// NOTE: See more docs about change kinds at `gear_common::event`.
             change: ProgramChangeKind<T::BlockNumber>,
         },
+
+        /// Digest of the most expensive codes within the finished
+        /// code stats era.
+        CodeStatsDigest {
+            /// Index of the finished era.
+            era: u32,
+            /// Codes, which burned the most gas within the era,
+            /// sorted by the amount of gas burned.
+            top: Vec<(CodeId, CodeStats)>,
+        },
     }
 
     // Gear pallet error.
@@ -410,6 +585,20 @@ pub mod pallet {
         ValueLessThanMinimal,
         /// Messages storage corrupted.
         MessagesStorageCorrupted,
+        /// Code has no storage deposit paid by the caller.
+        ///
+        /// Occurs when trying to remove a code, which was uploaded by another account,
+        /// or before storage deposits were introduced.
+        NoCodeDeposit,
+        /// Code is in use.
+        ///
+        /// Occurs when trying to remove a code, which is run by active programs.
+        CodeInUse,
+        /// Failed to re-instrument the code with the current schedule.
+        ///
+        /// Occurs when the code uploaded before the schedule update doesn't pass
+        /// the current instrumentation, e.g. the stack limiter.
+        ReinstrumentationFailed,
     }
 
     #[pallet::hooks]
@@ -420,6 +609,10 @@ pub mod pallet {
         fn on_runtime_upgrade() -> Weight {
             log::debug!(target: "runtime::gear", "⚙️ Runtime upgrade");
 
+            // The upgrade block is filled anyway, so the weight
+            // of the migrations isn't added.
+            migration::migrate::<T>();
+
             Weight::MAX
         }
 
@@ -427,7 +620,7 @@ pub mod pallet {
         fn on_initialize(bn: BlockNumberFor<T>) -> Weight {
             log::debug!(target: "runtime::gear", "⚙️  Initialization of block #{:?}", bn);
 
-            Weight::zero()
+            Self::rotate_code_stats(bn)
         }
 
         /// Finalization
@@ -452,6 +645,25 @@ pub mod pallet {
             // so we don't need to include that db write.
             GasAllowanceOf::<T>::put(remaining_weight.ref_time());
 
+            // Stored messages and tasks can't be read while they are migrated,
+            // so tasks of the block are left for next blocks.
+            if <T::Messenger as Messenger>::migrating() || <T::Scheduler as Scheduler>::migrating()
+            {
+                log::debug!(
+                    target: "runtime::gear",
+                    "⚙️  Queue and tasks processing of block #{:?} postponed by migration",
+                    bn,
+                );
+
+                MissedBlocksOf::<T>::mutate(|missed_blocks| {
+                    missed_blocks
+                        .get_or_insert_with(Default::default)
+                        .insert(bn);
+                });
+
+                return T::DbWeight::get().reads_writes(3, 1);
+            }
+
             // Ext manager creation.
             // It will be processing messages execution results following its `JournalHandler` trait implementation.
             // It also will handle delayed tasks following `TasksHandler`.
@@ -569,6 +781,8 @@ pub mod pallet {
             )
             .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
 
+            Self::note_tx_hash(message_id);
+
             let message = InitMessage::from_packet(message_id, packet);
             let dispatch = message
                 .into_dispatch(ProgramId::from_origin(origin))
@@ -696,6 +910,79 @@ pub mod pallet {
             res
         }
 
+        /// Page of messages in the mailbox of `account`.
+        ///
+        /// Returns at most `limit` messages with ids greater than `cursor`.
+        pub fn mailbox_of(account: T::AccountId, cursor: Option<H256>, limit: u32) -> MessagesPage {
+            let cursor = cursor.map(MessageId::from_origin);
+
+            Self::messages_page(
+                MailboxOf::<T>::iter_key_from(account, cursor).map(|(message, _)| message),
+                limit,
+            )
+        }
+
+        /// Page of messages in the message queue.
+        ///
+        /// Returns at most `limit` messages following `cursor` in the queue.
+        pub fn queue_snapshot(cursor: Option<H256>, limit: u32) -> MessagesPage {
+            let cursor = cursor.map(MessageId::from_origin);
+
+            Self::messages_page(
+                QueueOf::<T>::iter_from(cursor)
+                    .filter_map(Result::ok)
+                    .map(|dispatch| dispatch.into_parts().1),
+                limit,
+            )
+        }
+
+        // Messages are read starting right after the cursor, so only the page
+        // is loaded from the storage. The mailbox is iterated in the order of
+        // message ids and the queue in the order of processing, so messages
+        // added in between requests of pages don't shift them.
+        fn messages_page(
+            messages: impl Iterator<Item = StoredMessage>,
+            limit: u32,
+        ) -> MessagesPage {
+            let limit = limit.min(MAX_PAGE_LEN) as usize;
+            let page: Vec<StoredMessage> = messages.take(limit).collect();
+
+            let next_cursor = (page.len() == limit)
+                .then(|| page.last().map(|message| message.id().into_origin()))
+                .flatten();
+
+            MessagesPage {
+                messages: page.into_iter().map(Into::into).collect(),
+                next_cursor,
+            }
+        }
+
+        /// Index of the code stats era, which is current at the block `bn`.
+        pub fn code_stats_era(bn: T::BlockNumber) -> u32 {
+            let era_length = T::CodeStatsEraLength::get().max(One::one());
+
+            (bn / era_length).unique_saturated_into()
+        }
+
+        /// Stats of the codes, which burned the most gas within the `era`,
+        /// or the current era if it isn't specified.
+        ///
+        /// Returns at most `limit` entries, sorted by the amount of gas burned.
+        pub fn code_stats(era: Option<u32>, limit: u32) -> Vec<(H256, CodeStats)> {
+            let era = era
+                .unwrap_or_else(|| Self::code_stats_era(<frame_system::Pallet<T>>::block_number()));
+
+            Self::top_code_stats(era, limit)
+                .into_iter()
+                .map(|(code_id, stats)| (code_id.into_origin(), stats))
+                .collect()
+        }
+
+        /// Amount of active programs running the code.
+        pub fn code_refcount(code_id: H256) -> u32 {
+            CodeRefcount::<T>::get(CodeId::from_origin(code_id))
+        }
+
         pub fn run_with_ext_copy<R, F: FnOnce() -> R>(f: F) -> R {
             sp_externalities::with_externalities(|ext| {
                 ext.storage_start_transaction();
@@ -789,18 +1076,35 @@ pub mod pallet {
                 alloc_cost: schedule.memory_weights.allocation_cost,
                 mem_grow_cost: schedule.memory_weights.grow_cost,
                 load_page_cost: schedule.memory_weights.load_cost,
+                write_page_cost: schedule.memory_weights.write_cost,
+                max_pages_persisted: schedule.limits.max_pages_persisted_per_message,
             };
 
             let block_config = BlockConfig {
                 block_info,
+                block_duration: Self::block_duration(),
                 allocations_config,
                 existential_deposit,
+                existential_deposit_policy: T::ProgramExistentialDeposit::get(),
                 outgoing_limit: T::OutgoingLimit::get(),
                 host_fn_weights: schedule.host_fn_weights.into_core(),
                 forbidden_funcs: ["gr_gas_available"].into(),
                 mailbox_threshold: T::MailboxThreshold::get(),
                 waitlist_cost: CostsPerBlockOf::<T>::waitlist(),
+                reply_waker_cost: T::DbWeight::get().writes(4).ref_time(),
                 reserve_for: CostsPerBlockOf::<T>::reserve_for().unique_saturated_into(),
+                block_hash_depth: schedule.limits.block_hash_depth,
+                block_hash: Self::block_hash_at,
+                program_exists: GearProgramPallet::<T>::program_exists,
+                mailbox_contains: Self::mailbox_contains,
+                codes_count: GearProgramPallet::<T>::codes_count,
+                delayed_dispatch: common::get_delayed_dispatch,
+                program_counters: common::get_counters,
+                children_gas_burned: common::get_children_gas_burned,
+                code_id_of: Self::code_id_of,
+                reply_waker: common::get_reply_waker,
+                entry_budget: common::get_entry_budget,
+                entry_budget_read_cost: T::DbWeight::get().reads(1).ref_time(),
             };
 
             let mut min_limit = 0;
@@ -828,6 +1132,8 @@ pub mod pallet {
                     actor,
                     dispatch: queued_dispatch.into_incoming(gas_limit),
                     origin: ProgramId::from_origin(source),
+                    tx_hash: None,
+                    execution_id: Self::next_execution_id(),
                     gas_allowance: u64::MAX,
                     subsequent_execution,
                 };
@@ -881,11 +1187,14 @@ pub mod pallet {
                                 })
                                 .unwrap_or(0);
 
-                            core_processor::process::<Ext, ExecutionEnvironment>(
+                            // The journal is handled below against the storage,
+                            // which is never committed by the runtime API.
+                            core_processor::simulate_dispatch::<Ext, ExecutionEnvironment>(
                                 &block_config,
                                 context,
                                 memory_pages,
                             )
+                            .journal
                         }
                         PrepareResult::WontExecute(journal) | PrepareResult::Error(journal) => {
                             journal
@@ -1014,6 +1323,17 @@ pub mod pallet {
                 .unwrap_or_default()
         }
 
+        /// Returns code id of the program if it's active.
+        pub fn code_id_of(program_id: ProgramId) -> Option<CodeId> {
+            common::get_program(program_id.into_origin()).and_then(|p| {
+                if let Program::Active(prog) = p {
+                    Some(CodeId::from_origin(prog.code_hash))
+                } else {
+                    None
+                }
+            })
+        }
+
         /// Returns MessageId for newly created user message.
         pub fn next_message_id(user_id: H256) -> MessageId {
             let nonce = SentOf::<T>::get();
@@ -1024,6 +1344,92 @@ pub mod pallet {
             MessageId::generate_from_user(block_number, user_id, nonce.into())
         }
 
+        /// Returns the expected duration of a block in milliseconds.
+        pub(crate) fn block_duration() -> u64 {
+            <T as pallet_timestamp::Config>::MinimumPeriod::get()
+                .saturating_mul(2u32.into())
+                .unique_saturated_into()
+        }
+
+        /// Returns hash of the block at the given height.
+        pub(crate) fn block_hash_at(height: u32) -> [u8; 32] {
+            let hash = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::from(height));
+
+            let mut bytes = [0; 32];
+            bytes.copy_from_slice(hash.as_ref());
+            bytes
+        }
+
+        /// Whether the message sent by the program is in the mailbox of
+        /// the user, i.e. it wasn't claimed, replied or removed on timeout yet.
+        pub(crate) fn mailbox_contains(
+            program_id: ProgramId,
+            user_id: ProgramId,
+            message_id: MessageId,
+        ) -> bool {
+            let user_id = <T::AccountId as Origin>::from_origin(user_id.into_origin());
+
+            MailboxOf::<T>::peek(&user_id, &message_id)
+                .map_or(false, |message| message.source() == program_id)
+        }
+
+        /// Stores hash of the currently applied extrinsic for the message
+        /// initiated by it.
+        ///
+        /// Does nothing outside of extrinsic application.
+        pub(crate) fn note_tx_hash(message_id: MessageId) {
+            if let Some(index) = <frame_system::Pallet<T>>::extrinsic_index() {
+                let data = <frame_system::Pallet<T>>::extrinsic_data(index);
+                TxHashOf::<T>::insert(message_id, sp_io::hashing::blake2_256(&data));
+            }
+        }
+
+        /// Returns identifier for the next execution of a message,
+        /// increasing the execution nonce.
+        pub(crate) fn next_execution_id() -> u64 {
+            ExecutionNonce::<T>::mutate(|nonce| {
+                let id = *nonce;
+                *nonce = nonce.wrapping_add(1);
+                id
+            })
+        }
+
+        /// Weight of the storage accesses of [`Self::next_execution_id`].
+        pub(crate) fn execution_nonce_weight() -> u64 {
+            T::DbWeight::get().reads_writes(1, 1).ref_time()
+        }
+
+        /// Returns hash of the external transaction the message chain
+        /// of the given message originates from.
+        ///
+        /// Delayed messages and messages sent by them don't get the hash.
+        pub(crate) fn tx_hash_of(message_id: MessageId) -> Option<[u8; 32]> {
+            if TxHashWithheldOf::<T>::contains_key(message_id) {
+                return None;
+            }
+
+            GasHandlerOf::<T>::get_origin_key(message_id)
+                .ok()
+                .and_then(TxHashOf::<T>::get)
+        }
+
+        /// Withholds hash of the originating transaction from the message
+        /// sent by the `parent` one, if the message is delayed or the hash
+        /// is withheld from the `parent`.
+        pub(crate) fn withhold_tx_hash(parent: MessageId, message_id: MessageId, delayed: bool) {
+            let withheld = if delayed {
+                GasHandlerOf::<T>::get_origin_key(parent)
+                    .map(TxHashOf::<T>::contains_key)
+                    .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e))
+            } else {
+                TxHashWithheldOf::<T>::contains_key(parent)
+            };
+
+            if withheld {
+                TxHashWithheldOf::<T>::insert(message_id, ());
+            }
+        }
+
         /// Delayed tasks processing.
         pub fn process_tasks(ext_manager: &mut ExtManager<T>) {
             // Current block number.
@@ -1138,24 +1544,47 @@ pub mod pallet {
                 alloc_cost: schedule.memory_weights.allocation_cost,
                 mem_grow_cost: schedule.memory_weights.grow_cost,
                 load_page_cost: schedule.memory_weights.load_cost,
+                write_page_cost: schedule.memory_weights.write_cost,
+                max_pages_persisted: schedule.limits.max_pages_persisted_per_message,
             };
 
             let block_config = BlockConfig {
                 block_info,
+                block_duration: Self::block_duration(),
                 allocations_config,
                 existential_deposit,
+                existential_deposit_policy: T::ProgramExistentialDeposit::get(),
                 outgoing_limit: T::OutgoingLimit::get(),
                 host_fn_weights: schedule.host_fn_weights.into_core(),
                 forbidden_funcs: Default::default(),
                 mailbox_threshold: T::MailboxThreshold::get(),
                 waitlist_cost: CostsPerBlockOf::<T>::waitlist(),
+                reply_waker_cost: T::DbWeight::get().writes(4).ref_time(),
                 reserve_for: CostsPerBlockOf::<T>::reserve_for().unique_saturated_into(),
+                block_hash_depth: schedule.limits.block_hash_depth,
+                block_hash: Self::block_hash_at,
+                program_exists: GearProgramPallet::<T>::program_exists,
+                mailbox_contains: Self::mailbox_contains,
+                codes_count: GearProgramPallet::<T>::codes_count,
+                delayed_dispatch: common::get_delayed_dispatch,
+                program_counters: common::get_counters,
+                children_gas_burned: common::get_children_gas_burned,
+                code_id_of: Self::code_id_of,
+                reply_waker: common::get_reply_waker,
+                entry_budget: common::get_entry_budget,
+                entry_budget_read_cost: T::DbWeight::get().reads(1).ref_time(),
             };
 
             if T::DebugInfo::is_remap_id_enabled() {
                 T::DebugInfo::remap_id();
             }
 
+            // Journal of the block is hashed only to be compared with other
+            // nodes, when enabled with `--log gear::journal=debug`. Notes are
+            // hashed as they're handled, so they aren't kept for the block.
+            let mut journal_hasher = log::log_enabled!(target: "gear::journal", log::Level::Debug)
+                .then(JournalHasher::new);
+
             while QueueProcessingOf::<T>::allowed() {
                 if let Some(dispatch) = QueueOf::<T>::dequeue()
                     .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e))
@@ -1176,6 +1605,9 @@ pub mod pallet {
                         GasAllowanceOf::<T>::get(),
                     );
 
+                    // Code of the destination, which execution is accounted in stats.
+                    let mut stats_code_id = None;
+
                     let active_actor_data = if let Some(maybe_active_program) =
                         common::get_program(dispatch.destination().into_origin())
                     {
@@ -1187,13 +1619,21 @@ pub mod pallet {
                                 if code.instruction_weights_version()
                                     == schedule.instruction_weights.version
                                 {
-                                    code
+                                    Some(code)
                                 } else {
                                     // todo: charge for code instrumenting
-                                    // If instrumented code exists, re-instrumentation can't fail
-                                    Self::reinstrument_code(code_id, &schedule).unwrap_or_else(
-                                        |e| unreachable!("Code storage corrupted {:?}", e),
-                                    )
+                                    Self::reinstrument_code(code_id, &schedule)
+                                        .map_err(|e| {
+                                            // The code uploaded before the stack limiter was
+                                            // introduced may fail its injection, so the program
+                                            // can't be executed anymore.
+                                            log::debug!(
+                                                "Program '{:?}' can't be executed: {:?}",
+                                                dispatch.destination(),
+                                                e
+                                            )
+                                        })
+                                        .ok()
                                 }
                             } else {
                                 // This branch is considered unreachable,
@@ -1211,35 +1651,47 @@ pub mod pallet {
                                 continue;
                             };
 
-                            if matches!(prog.state, ProgramState::Uninitialized {message_id} if message_id != dispatch.id())
-                                && dispatch.reply().is_none()
-                            {
-                                // Adding id in on-init wake list.
-                                common::waiting_init_append_message_id(
+                            if let Some(code) = code {
+                                if matches!(prog.state, ProgramState::Uninitialized {message_id} if message_id != dispatch.id())
+                                    && dispatch.reply().is_none()
+                                {
+                                    // Adding id in on-init wake list.
+                                    common::waiting_init_append_message_id(
+                                        dispatch.destination(),
+                                        dispatch.id(),
+                                    );
+
+                                    Self::wait_dispatch(
+                                        dispatch,
+                                        None,
+                                        MessageWaitedSystemReason::ProgramIsNotInitialized
+                                            .into_reason(),
+                                    );
+                                    continue;
+                                }
+
+                                stats_code_id = Some(code_id);
+
+                                let program = NativeProgram::from_parts(
                                     dispatch.destination(),
-                                    dispatch.id(),
+                                    code,
+                                    prog.allocations,
+                                    matches!(prog.state, ProgramState::Initialized),
                                 );
 
-                                Self::wait_dispatch(
-                                    dispatch,
-                                    None,
-                                    MessageWaitedSystemReason::ProgramIsNotInitialized
-                                        .into_reason(),
-                                );
-                                continue;
+                                Some(ExecutableActorData {
+                                    program,
+                                    pages_with_data: prog.pages_with_data,
+                                    value_reservations: common::get_value_reservations(
+                                        dispatch.destination(),
+                                    ),
+                                    children_count: common::get_children_count(
+                                        dispatch.destination(),
+                                    ),
+                                })
+                            } else {
+                                None
                             }
-
-                            let program = NativeProgram::from_parts(
-                                dispatch.destination(),
-                                code,
-                                prog.allocations,
-                                matches!(prog.state, ProgramState::Initialized),
-                            );
-
-                            Some(ExecutableActorData {
-                                program,
-                                pages_with_data: prog.pages_with_data,
-                            })
                         } else {
                             // Reaching this branch is possible when init message was processed with failure, while other kind of messages
                             // were already in the queue/were added to the queue (for example. moved from wait list in case of async init)
@@ -1263,6 +1715,12 @@ pub mod pallet {
                         .unique_saturated_into();
 
                     let program_id = dispatch.destination();
+                    let tx_hash = Self::tx_hash_of(dispatch.id());
+
+                    // Charging the update of the execution nonce
+                    // as a part of the dispatch.
+                    GasAllowanceOf::<T>::decrease(Self::execution_nonce_weight());
+                    let execution_id = Self::next_execution_id();
                     let message_execution_context = MessageExecutionContext {
                         actor: Actor {
                             balance,
@@ -1271,6 +1729,8 @@ pub mod pallet {
                         },
                         dispatch: dispatch.into_incoming(gas_limit),
                         origin: ProgramId::from_origin(external.into_origin()),
+                        tx_hash,
+                        execution_id,
                         gas_allowance: GasAllowanceOf::<T>::get(),
                         subsequent_execution: ext_manager.program_pages_loaded(&program_id),
                     };
@@ -1312,6 +1772,14 @@ pub mod pallet {
                             }
                         };
 
+                    if let Some(code_id) = stats_code_id {
+                        Self::note_code_stats(code_id, &journal);
+                    }
+
+                    if let Some(journal_hasher) = &mut journal_hasher {
+                        journal.iter().for_each(|note| journal_hasher.update(note));
+                    }
+
                     core_processor::handle_journal(journal, &mut ext_manager);
 
                     if T::DebugInfo::is_enabled() {
@@ -1326,6 +1794,15 @@ pub mod pallet {
                 }
             }
 
+            if let Some(journal_hasher) = journal_hasher {
+                log::debug!(
+                    target: "gear::journal",
+                    "Journal hash of block #{}: {:?}",
+                    block_info.height,
+                    H256::from(journal_hasher.finalize()),
+                );
+            }
+
             let post_data: QueuePostProcessingData = ext_manager.into();
             let total_handled = DequeuedOf::<T>::get();
 
@@ -1372,6 +1849,9 @@ pub mod pallet {
         /// One detail should be mentioned here. The injection can actually fail, if cost for some wasm instruction
         /// is removed. But this case is prevented by the Gear node protocol and checked in backwards compatibility
         /// test (`schedule::tests::instructions_backward_compatibility`)
+        ///
+        /// The stack limiter isn't covered by the reasons above though: the code uploaded
+        /// before it was introduced may fail its injection, so the error is returned.
         pub(crate) fn reinstrument_code(
             code_id: CodeId,
             schedule: &Schedule<T>,
@@ -1391,8 +1871,12 @@ pub mod pallet {
                 original_code,
                 schedule.instruction_weights.version,
                 |module| schedule.rules(module),
+                Some(schedule.limits.stack_height),
             )
-            .unwrap_or_else(|e| unreachable!("Unexpected re-instrumentation failure: {:?}", e));
+            .map_err(|e| {
+                log::debug!("Code '{:?}' failed to re-instrument: {:?}", code_id, e);
+                Error::<T>::ReinstrumentationFailed
+            })?;
 
             let code_and_id = CodeAndId::from_parts_unchecked(code, code_id);
             let code_and_id = InstrumentedCodeAndId::from(code_and_id);
@@ -1409,9 +1893,12 @@ pub mod pallet {
                 Error::<T>::CodeTooLarge
             );
 
-            let code = Code::try_new(code, schedule.instruction_weights.version, |module| {
-                schedule.rules(module)
-            })
+            let code = Code::try_new(
+                code,
+                schedule.instruction_weights.version,
+                |module| schedule.rules(module),
+                Some(schedule.limits.stack_height),
+            )
             .map_err(|e| {
                 log::debug!("Code failed to load: {:?}", e);
                 Error::<T>::FailedToConstructProgram
@@ -1504,6 +1991,8 @@ pub mod pallet {
                 packet.gas_limit().expect("Can't fail"),
             );
 
+            Self::note_tx_hash(message_id);
+
             let message = InitMessage::from_packet(message_id, packet);
             let dispatch = message
                 .into_dispatch(ProgramId::from_origin(origin))
@@ -1536,10 +2025,13 @@ pub mod pallet {
         /// program in storage. So the code for the child will be initialized by program initialization request only if it exists in storage.
         ///
         /// More precisely, the code and its metadata are actually saved in the storage under the hash of the `code`. The code hash is computed
-        /// as Blake256 hash. At the time of the call the `code` hash should not be in the storage. If it was stored previously, call will end up
-        /// with an `CodeAlreadyExists` error. In this case user can be sure, that he can actually use the hash of his program's code bytes to define
+        /// as Blake256 hash. If the `code` was stored previously, call succeeds without any changes, charging only for the
+        /// lookup of the hash. In both cases user can be sure, that he can actually use the hash of his program's code bytes to define
         /// "program factory" logic in his program.
         ///
+        /// The uploader of the new code pays storage deposit of `CodeDepositPerByte` per byte of the `code`,
+        /// which is returned once the code is removed with `remove_code`.
+        ///
         /// Parameters
         /// - `code`: wasm code of a program as a byte vector.
         ///
@@ -1551,7 +2043,15 @@ pub mod pallet {
         pub fn upload_code(origin: OriginFor<T>, code: Vec<u8>) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
 
-            let code_id = Self::set_code_with_metadata(Self::check_code(code)?, who.into_origin())?;
+            if T::CodeStorage::exists(CodeId::generate(&code)) {
+                return Ok(Some(<T as Config>::WeightInfo::upload_code(0)).into());
+            }
+
+            let code_len = code.len();
+            let code_id =
+                Self::set_code_with_metadata(Self::check_code(code)?, who.clone().into_origin())?;
+
+            Self::reserve_code_deposit(&who, code_id, code_len)?;
 
             // TODO: replace this temporary (`None`) value
             // for expiration block number with properly
@@ -1604,6 +2104,9 @@ pub mod pallet {
         /// has been removed.
         #[pallet::weight(
             <T as Config>::WeightInfo::upload_program(code.len() as u32, salt.len() as u32)
+                .saturating_add(<T as Config>::WeightInfo::note_tx_hash(
+                    (code.len() + salt.len() + init_payload.len()) as u32
+                ))
         )]
         pub fn upload_program(
             origin: OriginFor<T>,
@@ -1617,31 +2120,41 @@ pub mod pallet {
 
             Self::check_gas_limit_and_value(gas_limit, value)?;
 
-            let code_and_id = Self::check_code(code)?;
-            let packet = Self::init_packet(
-                who.clone(),
-                code_and_id.code_id(),
-                salt,
-                init_payload,
-                gas_limit,
-                value,
-            )?;
+            let code_id = CodeId::generate(&code);
 
-            if !T::CodeStorage::exists(code_and_id.code_id()) {
-                // By that call we follow the guarantee that we have in `Self::upload_code` -
-                // if there's code in storage, there's also metadata for it.
-                let code_hash =
-                    Self::set_code_with_metadata(code_and_id, who.clone().into_origin())?;
+            // Stored code was checked once uploaded, so only creation of
+            // the program and hashing of the whole transaction are charged.
+            if T::CodeStorage::exists(code_id) {
+                let weight = <T as Config>::WeightInfo::create_program(salt.len() as u32)
+                    .saturating_add(<T as Config>::WeightInfo::note_tx_hash(
+                        (code.len() + salt.len() + init_payload.len()) as u32,
+                    ));
+                let packet =
+                    Self::init_packet(who.clone(), code_id, salt, init_payload, gas_limit, value)?;
 
-                // TODO: replace this temporary (`None`) value
-                // for expiration block number with properly
-                // calculated one (issues #646 and #969).
-                Self::deposit_event(Event::CodeChanged {
-                    id: code_hash,
-                    change: CodeChangeKind::Active { expiration: None },
-                });
+                Self::do_create_program(who, packet)?;
+
+                return Ok(Some(weight).into());
             }
 
+            let code_len = code.len();
+            let code_and_id = Self::check_code(code)?;
+            let packet =
+                Self::init_packet(who.clone(), code_id, salt, init_payload, gas_limit, value)?;
+
+            // By that call we follow the guarantee that we have in `Self::upload_code` -
+            // if there's code in storage, there's also metadata for it.
+            Self::set_code_with_metadata(code_and_id, who.clone().into_origin())?;
+            Self::reserve_code_deposit(&who, code_id, code_len)?;
+
+            // TODO: replace this temporary (`None`) value
+            // for expiration block number with properly
+            // calculated one (issues #646 and #969).
+            Self::deposit_event(Event::CodeChanged {
+                id: code_id,
+                change: CodeChangeKind::Active { expiration: None },
+            });
+
             Self::do_create_program(who, packet)?;
 
             Ok(().into())
@@ -1663,7 +2176,12 @@ pub mod pallet {
         /// # NOTE
         ///
         /// For the details of this extrinsic, see `upload_code`.
-        #[pallet::weight(<T as Config>::WeightInfo::create_program(salt.len() as u32))]
+        #[pallet::weight(
+            <T as Config>::WeightInfo::create_program(salt.len() as u32)
+                .saturating_add(<T as Config>::WeightInfo::note_tx_hash(
+                    (salt.len() + init_payload.len()) as u32
+                ))
+        )]
         pub fn create_program(
             origin: OriginFor<T>,
             code_id: CodeId,
@@ -1707,7 +2225,10 @@ pub mod pallet {
         ///
         /// Emits the following events:
         /// - `DispatchMessageEnqueued(MessageInfo)` when dispatch message is placed in the queue.
-        #[pallet::weight(<T as Config>::WeightInfo::send_message(payload.len() as u32))]
+        #[pallet::weight(
+            <T as Config>::WeightInfo::send_message(payload.len() as u32)
+                .saturating_add(<T as Config>::WeightInfo::note_tx_hash(payload.len() as u32))
+        )]
         pub fn send_message(
             origin: OriginFor<T>,
             destination: ProgramId,
@@ -1755,6 +2276,8 @@ pub mod pallet {
                 GasHandlerOf::<T>::create(who.clone(), message.id(), gas_limit)
                     .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
 
+                Self::note_tx_hash(message.id());
+
                 let message = message.into_stored_dispatch(ProgramId::from_origin(origin));
 
                 Self::deposit_event(Event::MessageEnqueued {
@@ -1800,7 +2323,10 @@ pub mod pallet {
         ///
         /// NOTE: only user who is destination of the message, can claim value
         /// or reply on the message from mailbox.
-        #[pallet::weight(<T as Config>::WeightInfo::send_reply(payload.len() as u32))]
+        #[pallet::weight(
+            <T as Config>::WeightInfo::send_reply(payload.len() as u32)
+                .saturating_add(<T as Config>::WeightInfo::note_tx_hash(payload.len() as u32))
+        )]
         pub fn send_reply(
             origin: OriginFor<T>,
             reply_to_id: MessageId,
@@ -1826,7 +2352,7 @@ pub mod pallet {
 
             // Checking that program, origin replies to, is not terminated.
             ensure!(
-                Self::is_active(mailboxed.source()),
+                Self::is_active(mailboxed.reply_destination()),
                 Error::<T>::InactiveProgram
             );
 
@@ -1855,10 +2381,12 @@ pub mod pallet {
             GasHandlerOf::<T>::create(origin.clone(), message.id(), gas_limit)
                 .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
 
+            Self::note_tx_hash(message.id());
+
             // Converting reply message into appropriate type for queueing.
             let dispatch = message.into_stored_dispatch(
                 ProgramId::from_origin(origin.clone().into_origin()),
-                mailboxed.source(),
+                mailboxed.reply_destination(),
                 mailboxed.id(),
             );
 
@@ -1912,11 +2440,49 @@ pub mod pallet {
             <T as Config>::Messenger::reset();
             GearProgramPallet::<T>::reset_storage();
             common::reset_storage();
+            let _ = CodeRefcount::<T>::clear(u32::MAX, None);
+            for (depositor, deposit) in CodeDepositOf::<T>::drain().map(|(_, entry)| entry) {
+                CurrencyOf::<T>::unreserve(&depositor, deposit);
+            }
+            let _ = ProgramProviders::<T>::clear(u32::MAX, None);
+            let _ = ScheduledExitOf::<T>::clear(u32::MAX, None);
 
             Self::deposit_event(Event::DatabaseWiped);
 
             Ok(())
         }
+
+        /// Removes code, which isn't run by any active program, from storage.
+        ///
+        /// Storage deposit paid for the code is returned to the caller,
+        /// so only the original uploader of the code is able to remove it.
+        ///
+        /// Parameters:
+        /// - `code_id`: wasm code id in the code storage.
+        ///
+        /// Emits the following events:
+        /// - `CodeChanged { change: CodeChangeKind::Inactive, .. }` when the code is removed.
+        #[pallet::weight(<T as Config>::WeightInfo::remove_code())]
+        pub fn remove_code(origin: OriginFor<T>, code_id: CodeId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let deposit = CodeDepositOf::<T>::get(code_id)
+                .and_then(|(depositor, deposit)| (depositor == who).then_some(deposit))
+                .ok_or(Error::<T>::NoCodeDeposit)?;
+
+            ensure!(CodeRefcount::<T>::get(code_id) == 0, Error::<T>::CodeInUse);
+
+            T::CodeStorage::remove_code(code_id);
+            CodeDepositOf::<T>::remove(code_id);
+            CurrencyOf::<T>::unreserve(&who, deposit);
+
+            Self::deposit_event(Event::CodeChanged {
+                id: code_id,
+                change: CodeChangeKind::Inactive,
+            });
+
+            Ok(().into())
+        }
     }
 
     impl<T: Config> common::PaymentProvider<T::AccountId> for Pallet<T>
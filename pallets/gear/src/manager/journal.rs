@@ -16,27 +16,35 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use core::{convert::TryInto, num::NonZeroU32};
+
 use crate::{
     manager::ExtManager, Config, CurrencyOf, Event, GasAllowanceOf, GasHandlerOf,
-    GearProgramPallet, Pallet, QueueOf, SentOf, TaskPoolOf, WaitlistOf,
+    GearProgramPallet, Pallet, ProgramProviders, QueueOf, ScheduledExitOf, SentOf, TaskPoolOf,
+    WaitlistOf,
 };
+use codec::Encode;
 use common::{
     event::*,
     scheduler::{ScheduledTask, TaskPool},
     storage::*,
-    CodeStorage, GasTree, Origin, Program,
+    CodeStorage, CommonError, GasPrice, GasTree, Origin, Program,
+};
+use core_processor::common::{
+    DispatchOutcome as CoreDispatchOutcome, ExecutionErrorReason, JournalHandler,
 };
-use core_processor::common::{DispatchOutcome as CoreDispatchOutcome, JournalHandler};
 use frame_support::{
     sp_runtime::Saturating,
     traits::{Currency, ExistenceRequirement, ReservableCurrency},
 };
 use frame_system::Pallet as SystemPallet;
 use gear_core::{
-    ids::{CodeId, MessageId, ProgramId},
+    counters::CounterKey,
+    ids::{CodeId, MessageId, ProgramId, ReservationId},
     memory::{PageBuf, PageNumber},
-    message::{Dispatch, StoredDispatch},
+    message::{Dispatch, DispatchKind, ReplyMessage, StoredDispatch},
 };
+use primitive_types::H256;
 use sp_runtime::traits::{UniqueSaturatedInto, Zero};
 
 use sp_std::{
@@ -137,23 +145,34 @@ where
                 // dequeued. The other case is async init.
                 wake_waiting_init_msgs(program_id);
 
-                common::set_program_terminated_status(program_id.into_origin(), origin)
-                    .expect("Only active program can cause init failure");
-
-                let program_id = <T::AccountId as Origin>::from_origin(program_id.into_origin());
-
-                let balance = CurrencyOf::<T>::free_balance(&program_id);
-                let destination = Pallet::<T>::inheritor_for(origin);
-                let destination = <T::AccountId as Origin>::from_origin(destination.into_origin());
-
-                if !balance.is_zero() {
-                    CurrencyOf::<T>::transfer(
-                        &program_id,
-                        &destination,
-                        balance,
-                        ExistenceRequirement::AllowDeath,
-                    )
-                    .unwrap_or_else(|e| unreachable!("Failed to transfer value: {:?}", e));
+                if GearProgramPallet::<T>::program_exists(origin) {
+                    // Program created by another program is terminated and
+                    // its state is cleared. Value is returned to the creator.
+                    self.release_exited(program_id, origin, |id| {
+                        common::set_program_terminated_status(id, origin)
+                    });
+                } else {
+                    Pallet::<T>::release_code_of(program_id);
+                    common::set_program_terminated_status(program_id.into_origin(), origin)
+                        .expect("Only active program can cause init failure");
+
+                    let program_id =
+                        <T::AccountId as Origin>::from_origin(program_id.into_origin());
+
+                    let balance = CurrencyOf::<T>::free_balance(&program_id);
+                    let destination = Pallet::<T>::inheritor_for(origin);
+                    let destination =
+                        <T::AccountId as Origin>::from_origin(destination.into_origin());
+
+                    if !balance.is_zero() {
+                        CurrencyOf::<T>::transfer(
+                            &program_id,
+                            &destination,
+                            balance,
+                            ExistenceRequirement::AllowDeath,
+                        )
+                        .unwrap_or_else(|e| unreachable!("Failed to transfer value: {:?}", e));
+                    }
                 }
 
                 DispatchStatus::Failed
@@ -179,97 +198,85 @@ where
     }
 
     fn exit_dispatch(&mut self, id_exited: ProgramId, value_destination: ProgramId) {
-        let reason = MessageWokenSystemReason::ProgramGotInitialized.into_reason();
-
-        WaitlistOf::<T>::drain_key(id_exited).for_each(|entry| {
-            let message = Pallet::<T>::wake_dispatch_requirements(entry, reason.clone());
-
-            QueueOf::<T>::queue(message)
-                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+        self.release_exited(id_exited, value_destination, |id| {
+            common::set_program_exited_status(id, value_destination)
         });
-
-        let _ = common::waiting_init_take_messages(id_exited);
-
-        let id_exited = id_exited.into_origin();
-
-        common::set_program_exited_status(id_exited, value_destination)
-            .expect("`exit` can be called only from active program; qed");
-
-        let program_account = &<T::AccountId as Origin>::from_origin(id_exited);
-        let balance = CurrencyOf::<T>::free_balance(program_account);
-
-        let destination = Pallet::<T>::inheritor_for(value_destination);
-        let destination = <T::AccountId as Origin>::from_origin(destination.into_origin());
-
-        if !balance.is_zero() {
-            CurrencyOf::<T>::transfer(
-                program_account,
-                &destination,
-                balance,
-                ExistenceRequirement::AllowDeath,
-            )
-            .unwrap_or_else(|e| unreachable!("Failed to transfer value: {:?}", e));
-        }
     }
 
     fn message_consumed(&mut self, message_id: MessageId) {
         Pallet::<T>::consume_message(message_id)
     }
 
-    fn send_dispatch(&mut self, message_id: MessageId, dispatch: Dispatch, delay: u32) {
-        let to_user = self.check_user_id(&dispatch.destination());
-
-        if !delay.is_zero() {
-            log::debug!("Sending delayed for {delay} blocks dispatch");
-            Pallet::<T>::send_delayed_dispatch(message_id, dispatch, delay, to_user)
-        } else if !to_user {
-            let gas_limit = dispatch.gas_limit();
-            let dispatch = dispatch.into_stored();
+    fn send_dispatch(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+    ) {
+        self.send_dispatch_paid_by(message_id, dispatch, delay, None)
+    }
 
+    fn send_dispatch_charge_recipient(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+    ) {
+        let destination = dispatch.destination();
+        let gas_limit = dispatch
+            .gas_limit()
+            .unwrap_or_else(|| unreachable!("Recipient charged dispatch always has gas limit"));
+
+        // Only active programs, which allowed paying for at least `gas_limit`
+        // with `gr_set_charge_allowance`, pay for the messages sent to them.
+        let payer = <T::AccountId as Origin>::from_origin(destination.into_origin());
+        let allowance = common::get_charge_allowance(destination);
+        let paid = self.check_program_id(&destination)
+            && Pallet::<T>::is_active(destination)
+            && allowance >= gas_limit
+            && CurrencyOf::<T>::reserve(&payer, T::GasPrice::gas_price(gas_limit)).is_ok();
+
+        if paid {
+            common::set_charge_allowance(destination, allowance - gas_limit);
+
+            log::debug!("Gas of dispatch {:?} is paid by recipient", dispatch.id());
+
+            self.send_dispatch_paid_by(message_id, dispatch, delay, Some(payer));
+        } else {
             log::debug!(
-                "Sending message {:?} from {:?} with gas limit {:?}",
-                dispatch.message(),
+                "Recipient {:?} can't pay for message {:?} from {:?}",
+                destination,
+                dispatch.id(),
                 message_id,
-                gas_limit,
             );
 
-            if dispatch.value() != 0 {
-                CurrencyOf::<T>::reserve(
-                    &<T::AccountId as Origin>::from_origin(dispatch.source().into_origin()),
-                    dispatch.value().unique_saturated_into(),
-                ).unwrap_or_else(|_| unreachable!("Value reservation can't fail due to value sending rules. For more info, see module docs."));
-            }
-
-            if let Some(gas_limit) = gas_limit {
-                // # Safety
-                //
-                // 1. There is no logic splitting value from the reserved nodes.
-                // 2. The `gas_limit` has been checked inside message queue processing.
-                // 3. The `value` of the value node has been checked before.
-                // 4. The `dispatch.id()` is new generated by system from a checked
-                //    ( inside message queue processing ) `message_id`.
-                GasHandlerOf::<T>::split_with_value(message_id, dispatch.id(), gas_limit)
-                    .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
-            } else {
-                // # Safety
-                //
-                // 1. There is no logic splitting value from the reserved nodes.
-                // 2. The `dispatch.id()` is new generated by system from a checked
-                //    ( inside message queue processing ) `message_id`.
-                GasHandlerOf::<T>::split(message_id, dispatch.id())
-                    .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
-            }
-
-            QueueOf::<T>::queue(dispatch)
+            // Expect cannot panic unless error message is too large.
+            let err = ExecutionErrorReason::RecipientCannotPayGas
+                .encode()
+                .try_into()
+                .expect("Error message is too large");
+
+            // Message isn't sent, so its sender gets error reply instead.
+            //
+            // # Safety
+            //
+            // 1. The dispatch.id() is new generated by system.
+            // 2. This reply message is generated by our system.
+            //
+            // So, the message id of this reply message will not be duplicated.
+            let err_reply = ReplyMessage::system(dispatch.id(), err, core_processor::ERR_EXIT_CODE)
+                .into_stored_dispatch(destination, dispatch.source(), dispatch.id());
+
+            // # Safety
+            //
+            // There is no logic splitting value from the reserved nodes.
+            GasHandlerOf::<T>::split(message_id, err_reply.id())
+                .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+
+            Pallet::<T>::withhold_tx_hash(message_id, err_reply.id(), false);
+
+            QueueOf::<T>::queue(err_reply)
                 .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
-        } else {
-            log::debug!(
-                "Sending user message {:?} from {:?} with gas limit {:?}",
-                dispatch.message(),
-                message_id,
-                dispatch.gas_limit(),
-            );
-            Pallet::<T>::send_user_message(message_id, dispatch.into_parts().1);
         }
     }
 
@@ -286,29 +293,29 @@ where
         message_id: MessageId,
         program_id: ProgramId,
         awakening_id: MessageId,
-        delay: u32,
+        delay: Option<NonZeroU32>,
     ) {
-        if delay.is_zero() {
-            if let Some(dispatch) = Pallet::<T>::wake_dispatch(
-                program_id,
-                awakening_id,
-                MessageWokenRuntimeReason::WakeCalled.into_reason(),
-            ) {
-                QueueOf::<T>::queue(dispatch)
-                    .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+        if let Some(delay) = delay {
+            if WaitlistOf::<T>::contains(&program_id, &awakening_id) {
+                let expected_bn = SystemPallet::<T>::block_number()
+                    .saturating_add(delay.get().unique_saturated_into());
+                let task = ScheduledTask::WakeMessage(program_id, awakening_id);
+
+                // This validation helps us to avoid returning error on insertion into `TaskPool` in case of duplicate wake.
+                if !TaskPoolOf::<T>::contains(&expected_bn, &task) {
+                    TaskPoolOf::<T>::add(expected_bn, task)
+                        .unwrap_or_else(|e| unreachable!("Scheduling logic invalidated! {:?}", e));
+                }
 
                 return;
             }
-        } else if WaitlistOf::<T>::contains(&program_id, &awakening_id) {
-            let expected_bn =
-                SystemPallet::<T>::block_number().saturating_add(delay.unique_saturated_into());
-            let task = ScheduledTask::WakeMessage(program_id, awakening_id);
-
-            // This validation helps us to avoid returning error on insertion into `TaskPool` in case of duplicate wake.
-            if !TaskPoolOf::<T>::contains(&expected_bn, &task) {
-                TaskPoolOf::<T>::add(expected_bn, task)
-                    .unwrap_or_else(|e| unreachable!("Scheduling logic invalidated! {:?}", e));
-            }
+        } else if let Some(dispatch) = Pallet::<T>::wake_dispatch(
+            program_id,
+            awakening_id,
+            MessageWokenRuntimeReason::WakeCalled.into_reason(),
+        ) {
+            QueueOf::<T>::queue(dispatch)
+                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
 
             return;
         }
@@ -358,6 +365,68 @@ where
         }
     }
 
+    fn update_value_reservations(
+        &mut self,
+        program_id: ProgramId,
+        reservations: BTreeMap<ReservationId, u128>,
+    ) {
+        common::set_value_reservations(program_id, reservations);
+    }
+
+    fn update_children_count(&mut self, program_id: ProgramId, children_count: u64) {
+        common::set_children_count(program_id, children_count);
+    }
+
+    fn child_gas_burned(&mut self, program_id: ProgramId, amount: u64) {
+        // Creators of the programs may be users or exited programs.
+        if !Pallet::<T>::is_active(program_id) {
+            return;
+        }
+
+        let gas_burned = common::get_children_gas_burned(program_id).saturating_add(amount);
+        common::set_children_gas_burned(program_id, gas_burned);
+    }
+
+    fn update_counters(&mut self, program_id: ProgramId, counters: BTreeMap<CounterKey, u64>) {
+        common::set_counters(program_id, counters);
+    }
+
+    fn update_entry_budgets(
+        &mut self,
+        program_id: ProgramId,
+        entry_budgets: BTreeMap<DispatchKind, u64>,
+    ) {
+        let mut budgets = common::get_entry_budgets(program_id);
+        budgets.extend(entry_budgets);
+        common::set_entry_budgets(program_id, budgets);
+    }
+
+    fn update_charge_allowance(&mut self, program_id: ProgramId, allowance: u64) {
+        common::set_charge_allowance(program_id, allowance);
+    }
+
+    fn update_reply_waker(
+        &mut self,
+        program_id: ProgramId,
+        reply_to: MessageId,
+        waiting: Option<MessageId>,
+    ) {
+        common::set_reply_waker(program_id, reply_to, waiting);
+    }
+
+    fn schedule_exit(&mut self, program_id: ProgramId, block: u32, inheritor: ProgramId) {
+        // The exit scheduled before is replaced.
+        Pallet::<T>::cancel_scheduled_exit(program_id);
+
+        let task = ScheduledTask::ExitProgram(program_id, inheritor);
+        let block = block.unique_saturated_into();
+
+        TaskPoolOf::<T>::add(block, task)
+            .unwrap_or_else(|e| unreachable!("Scheduling logic invalidated! {:?}", e));
+
+        ScheduledExitOf::<T>::insert(program_id, (block, inheritor));
+    }
+
     fn send_value(&mut self, from: ProgramId, to: Option<ProgramId>, value: u128) {
         let to = Pallet::<T>::inheritor_for(to.unwrap_or(from));
         let to = <T::AccountId as Origin>::from_origin(to.into_origin());
@@ -401,3 +470,119 @@ where
             .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
     }
 }
+
+impl<T: Config> ExtManager<T>
+where
+    T::AccountId: Origin,
+{
+    /// Sends the dispatch generated by `message_id`.
+    ///
+    /// Gas of the dispatch is bought by the `payer`, if provided.
+    fn send_dispatch_paid_by(
+        &mut self,
+        message_id: MessageId,
+        dispatch: Dispatch,
+        delay: Option<NonZeroU32>,
+        payer: Option<T::AccountId>,
+    ) {
+        let to_user = self.check_user_id(&dispatch.destination());
+
+        if let Some(delay) = delay {
+            log::debug!("Sending delayed for {delay} blocks dispatch");
+            Pallet::<T>::send_delayed_dispatch(message_id, dispatch, delay, to_user, payer)
+        } else if !to_user {
+            let gas_limit = dispatch.gas_limit();
+            let dispatch = dispatch.into_stored();
+
+            log::debug!(
+                "Sending message {:?} from {:?} with gas limit {:?}",
+                dispatch.message(),
+                message_id,
+                gas_limit,
+            );
+
+            if dispatch.value() != 0 {
+                CurrencyOf::<T>::reserve(
+                    &<T::AccountId as Origin>::from_origin(dispatch.source().into_origin()),
+                    dispatch.value().unique_saturated_into(),
+                ).unwrap_or_else(|_| unreachable!("Value reservation can't fail due to value sending rules. For more info, see module docs."));
+            }
+
+            Pallet::<T>::create_dispatch_gas_node(message_id, dispatch.id(), gas_limit, payer);
+
+            Pallet::<T>::withhold_tx_hash(message_id, dispatch.id(), false);
+
+            QueueOf::<T>::queue(dispatch)
+                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+        } else {
+            log::debug!(
+                "Sending user message {:?} from {:?} with gas limit {:?}",
+                dispatch.message(),
+                message_id,
+                dispatch.gas_limit(),
+            );
+            Pallet::<T>::send_user_message(message_id, dispatch.into_parts().1);
+        }
+    }
+
+    /// Wake messages of the exited program, clear its state and transfer
+    /// its balance to the inheritor of `value_destination`.
+    ///
+    /// `set_status` updates the program record.
+    fn release_exited(
+        &mut self,
+        id_exited: ProgramId,
+        value_destination: ProgramId,
+        set_status: impl FnOnce(H256) -> Result<(), CommonError>,
+    ) {
+        let reason = MessageWokenSystemReason::ProgramGotInitialized.into_reason();
+
+        WaitlistOf::<T>::drain_key(id_exited).for_each(|entry| {
+            let message = Pallet::<T>::wake_dispatch_requirements(entry, reason.clone());
+
+            QueueOf::<T>::queue(message)
+                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+        });
+
+        let _ = common::waiting_init_take_messages(id_exited);
+
+        common::clear_delayed_dispatches(id_exited);
+        common::set_value_reservations(id_exited, Default::default());
+        common::set_children_count(id_exited, 0);
+        common::set_children_gas_burned(id_exited, 0);
+        common::set_counters(id_exited, Default::default());
+        common::set_entry_budgets(id_exited, Default::default());
+        common::set_charge_allowance(id_exited, 0);
+        common::clear_reply_wakers(id_exited);
+
+        Pallet::<T>::cancel_scheduled_exit(id_exited);
+        Pallet::<T>::release_code_of(id_exited);
+
+        let kept_by_provider = ProgramProviders::<T>::take(id_exited).is_some();
+
+        let id_exited = id_exited.into_origin();
+
+        set_status(id_exited).expect("`exit` can be called only from active program; qed");
+
+        let program_account = &<T::AccountId as Origin>::from_origin(id_exited);
+        let balance = CurrencyOf::<T>::free_balance(program_account);
+
+        let destination = Pallet::<T>::inheritor_for(value_destination);
+        let destination = <T::AccountId as Origin>::from_origin(destination.into_origin());
+
+        if !balance.is_zero() {
+            CurrencyOf::<T>::transfer(
+                program_account,
+                &destination,
+                balance,
+                ExistenceRequirement::AllowDeath,
+            )
+            .unwrap_or_else(|e| unreachable!("Failed to transfer value: {:?}", e));
+        }
+
+        // Releasing provider reference the account was kept by.
+        if kept_by_provider {
+            let _ = SystemPallet::<T>::dec_providers(program_account);
+        }
+    }
+}
@@ -49,14 +49,16 @@ mod task;
 pub use journal::*;
 pub use task::*;
 
-use crate::{Config, CurrencyOf, GearProgramPallet};
+use crate::{
+    Config, CurrencyOf, ExistentialDepositPolicy, GearProgramPallet, Pallet, ProgramProviders,
+};
 use codec::{Decode, Encode};
 use common::{event::*, ActiveProgram, CodeStorage, Origin, ProgramState};
 use core_processor::common::{Actor, ExecutableActorData};
 use frame_support::traits::Currency;
 use gear_core::{
     ids::{CodeId, MessageId, ProgramId},
-    message::ExitCode,
+    message::{DispatchKind, ExitCode},
     program::Program as NativeProgram,
 };
 use sp_runtime::traits::UniqueSaturatedInto;
@@ -162,6 +164,17 @@ where
         self.program_loaded_pages.insert(id);
     }
 
+    /// Check if the program exports the signal entry point.
+    pub fn exports_signal(&self, id: ProgramId) -> bool {
+        let exports = || {
+            let active: ActiveProgram = common::get_program(id.into_origin())?.try_into().ok()?;
+            let code = T::CodeStorage::get_code(CodeId::from_origin(active.code_hash))?;
+            Some(code.exports().contains(&DispatchKind::Signal))
+        };
+
+        exports().unwrap_or(false)
+    }
+
     /// NOTE: By calling this function we can't differ whether `None` returned, because
     /// program with `id` doesn't exist or it's terminated
     pub fn get_actor(&self, id: ProgramId) -> Option<Actor> {
@@ -187,6 +200,8 @@ where
             executable_data: Some(ExecutableActorData {
                 program,
                 pages_with_data: active.pages_with_data,
+                value_reservations: common::get_value_reservations(id),
+                children_count: common::get_children_count(id),
             }),
         })
     }
@@ -210,5 +225,13 @@ where
         };
 
         common::set_program(program_id.into_origin(), program);
+        Pallet::<T>::acquire_code(code_id);
+
+        // Program's account is kept without existential deposit.
+        if T::ProgramExistentialDeposit::get() == ExistentialDepositPolicy::Exempt {
+            let account = <T::AccountId as Origin>::from_origin(program_id.into_origin());
+            frame_system::Pallet::<T>::inc_providers(&account);
+            ProgramProviders::<T>::insert(program_id, ());
+        }
     }
 }
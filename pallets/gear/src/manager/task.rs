@@ -18,23 +18,27 @@
 
 use core::convert::TryInto;
 
-use crate::{manager::ExtManager, Config, Event, GasHandlerOf, Pallet, QueueOf};
+use crate::{
+    internal::HoldBound, manager::ExtManager, Config, CostsPerBlockOf, Event, GasAllowanceOf,
+    GasHandlerOf, Pallet, QueueOf, ScheduledExitOf, WeightInfo,
+};
 use alloc::string::ToString;
 use codec::Encode;
 use common::{
     event::{
-        MessageWokenRuntimeReason, MessageWokenSystemReason, RuntimeReason, SystemReason,
-        UserMessageReadSystemReason,
+        MessageWokenRuntimeReason, MessageWokenSystemReason, ProgramChangeKind, RuntimeReason,
+        SystemReason, UserMessageReadSystemReason,
     },
     scheduler::*,
     storage::*,
     GasTree, Origin,
 };
-use core_processor::common::ExecutionErrorReason;
+use core_processor::common::{ExecutionErrorReason, JournalHandler};
 use gear_core::{
     ids::{CodeId, MessageId, ProgramId},
-    message::{ReplyMessage, StoredDispatch},
+    message::{ReplyMessage, SignalCode, SignalMessage, StoredDispatch},
 };
+use sp_runtime::traits::Zero;
 
 impl<T: Config> TaskHandler<T::AccountId> for ExtManager<T>
 where
@@ -80,7 +84,6 @@ where
         Pallet::<T>::consume_message(mailboxed.id());
     }
 
-    // TODO: Generate system signal (instead of reply?) (issue #647).
     fn remove_from_waitlist(&mut self, program_id: ProgramId, message_id: MessageId) {
         // Wake reason.
         let reason = MessageWokenSystemReason::OutOfRent.into_reason();
@@ -89,11 +92,47 @@ where
         let waitlisted = Pallet::<T>::wake_dispatch(program_id, message_id, reason)
             .unwrap_or_else(|| unreachable!("Scheduling logic invalidated!"));
 
+        // Sending signal to the program, so it could clean up after the message.
+        if self.exports_signal(program_id) {
+            // Gas left after charging for holding in waitlist.
+            let gas_left = GasHandlerOf::<T>::get_limit(message_id)
+                .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+
+            // Budget is exhausted if the gas left can't pay for another
+            // block of waiting on top of the reserve.
+            let exhausted = HoldBound::<T>::by(CostsPerBlockOf::<T>::waitlist())
+                .maximum_for(gas_left)
+                .expected_duration()
+                .is_zero();
+
+            let code = if exhausted {
+                SignalCode::OutOfGasInWaitlist
+            } else {
+                SignalCode::RemovedFromWaitlist
+            };
+
+            // Signal id is derived from the waitlisted message id,
+            // so it's unique as well.
+            let signal = SignalMessage::with_code(message_id, code)
+                .into_dispatch(program_id)
+                .into_stored();
+
+            // Splitting gas for signal, so it's paid with the gas left.
+            GasHandlerOf::<T>::split(message_id, signal.id())
+                .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
+
+            Pallet::<T>::withhold_tx_hash(message_id, signal.id(), false);
+
+            // Enqueueing signal into message queue.
+            QueueOf::<T>::queue(signal)
+                .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
+        }
+
         // Trap explanation.
         let trap = ExecutionErrorReason::OutOfRent;
 
         // Generate trap reply.
-        if self.check_program_id(&waitlisted.source()) {
+        if self.check_program_id(&waitlisted.reply_destination()) {
             // Sending trap reply to program, by enqueuing it to message queue.
             // Expect cannot panic unless error message is too large.
             let trap = trap
@@ -110,7 +149,7 @@ where
             //
             // So, the message id of this reply message will not be duplicated.
             let trap_reply = ReplyMessage::system(message_id, trap, core_processor::ERR_EXIT_CODE)
-                .into_stored_dispatch(program_id, waitlisted.source(), message_id);
+                .into_stored_dispatch(program_id, waitlisted.reply_destination(), message_id);
 
             // Splitting gas for newly created reply message.
             //
@@ -120,6 +159,8 @@ where
             GasHandlerOf::<T>::split(message_id, trap_reply.id())
                 .unwrap_or_else(|e| unreachable!("GasTree corrupted! {:?}", e));
 
+            Pallet::<T>::withhold_tx_hash(message_id, trap_reply.id(), false);
+
             // Enqueueing dispatch into message queue.
             QueueOf::<T>::queue(trap_reply)
                 .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
@@ -147,7 +188,7 @@ where
             //
             // So, the message id of this reply message will not be duplicated.
             let trap_reply = ReplyMessage::system(message_id, trap, core_processor::ERR_EXIT_CODE)
-                .into_stored(program_id, waitlisted.source(), message_id);
+                .into_stored(program_id, waitlisted.reply_destination(), message_id);
 
             // Depositing appropriate event.
             Pallet::<T>::deposit_event(Event::UserMessageSent {
@@ -176,6 +217,8 @@ where
     }
 
     fn send_dispatch(&mut self, dispatch: StoredDispatch) {
+        common::delayed_dispatches_remove(dispatch.source(), dispatch.id());
+
         if self.check_program_id(&dispatch.destination()) {
             QueueOf::<T>::queue(dispatch)
                 .unwrap_or_else(|e| unreachable!("Message queue corrupted! {:?}", e));
@@ -183,4 +226,24 @@ where
             Pallet::<T>::send_user_message_after_delay(dispatch.into_parts().1);
         }
     }
+
+    fn exit_program(&mut self, program_id: ProgramId, inheritor: ProgramId) {
+        // Decreasing gas allowance due to the program's exit.
+        GasAllowanceOf::<T>::decrease(<T as Config>::WeightInfo::tasks_exit_program().ref_time());
+
+        // The task has been taken from the pool already.
+        ScheduledExitOf::<T>::remove(program_id);
+
+        // Program may have already exited or been terminated.
+        if !Pallet::<T>::is_active(program_id) {
+            return;
+        }
+
+        self.exit_dispatch(program_id, inheritor);
+
+        Pallet::<T>::deposit_event(Event::ProgramChanged {
+            id: program_id,
+            change: ProgramChangeKind::Inactive,
+        });
+    }
 }
@@ -17,13 +17,57 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{Config, Pallet, Weight};
+use frame_support::traits::{Get, StorageVersion};
 
 /// Wrapper for all migrations of this pallet, based on `StorageVersion`.
 pub fn migrate<T: Config>() -> Weight {
-    use frame_support::traits::StorageVersion;
+    let version = StorageVersion::get::<Pallet<T>>();
+    let mut weight: Weight = T::DbWeight::get().reads(1);
 
-    let _version = StorageVersion::get::<Pallet<T>>();
-    let weight: Weight = Weight::zero();
+    if version < 2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
 
     weight
 }
+
+/// Migration to the storage version 2.
+///
+/// Counts active programs running each code into `CodeRefcount`.
+///
+/// Storage deposits can't be backfilled, because the uploaders of the codes
+/// stored before aren't known, so these codes can't be removed.
+pub mod v2 {
+    use super::*;
+    use crate::CodeRefcount;
+    use common::{Origin, Program};
+    use gear_core::ids::CodeId;
+
+    /// Migrates storage to the version 2, returning consumed weight.
+    pub fn migrate<T: Config>() -> Weight {
+        let mut count = 0u64;
+        let mut active = 0u64;
+
+        for id in common::program_ids() {
+            count += 1;
+
+            if let Some(Program::Active(program)) = common::get_program(id) {
+                CodeRefcount::<T>::mutate(CodeId::from_origin(program.code_hash), |refcount| {
+                    *refcount = refcount.saturating_add(1)
+                });
+                active += 1;
+            }
+        }
+
+        StorageVersion::new(2).put::<Pallet<T>>();
+
+        log::info!(
+            target: "runtime::gear",
+            "Gear migrated to storage version 2: {} of {} programs counted",
+            active,
+            count
+        );
+
+        T::DbWeight::get().reads_writes(count.saturating_add(active), active.saturating_add(1))
+    }
+}
@@ -22,7 +22,7 @@ use frame_support::{
     construct_runtime,
     pallet_prelude::*,
     parameter_types,
-    traits::{ConstU64, FindAuthor},
+    traits::{ConstU32, ConstU64, FindAuthor},
     weights::RuntimeDbWeight,
 };
 use frame_system as system;
@@ -124,6 +124,8 @@ parameter_types! {
     pub const BlockGasLimit: u64 = 100_000_000_000;
     pub const OutgoingLimit: u32 = 1024;
     pub GearSchedule: pallet_gear::Schedule<Test> = <pallet_gear::Schedule<Test>>::default();
+    pub static ProgramExistentialDeposit: pallet_gear::ExistentialDepositPolicy = Default::default();
+    pub static CodeDepositPerByte: u128 = 0;
 }
 
 impl pallet_gear::Config for Test {
@@ -136,6 +138,12 @@ impl pallet_gear::Config for Test {
     type DebugInfo = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = ConstU64<3000>;
+    type ProgramExistentialDeposit = ProgramExistentialDeposit;
+    type CodeStatsEraLength = ConstU64<100>;
+    type CodeStatsHistoryDepth = ConstU32<2>;
+    type MaxCodeStats = ConstU32<4>;
+    type CodeStatsDigestLength = ConstU32<2>;
+    type CodeDepositPerByte = CodeDepositPerByte;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
@@ -147,6 +155,7 @@ impl pallet_gear_scheduler::Config for Test {
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
     type MailboxCost = ConstU64<100>;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_gas::Config for Test {
@@ -155,6 +164,7 @@ impl pallet_gear_gas::Config for Test {
 
 impl pallet_gear_messenger::Config for Test {
     type BlockLimiter = GearGas;
+    type WeightInfo = ();
 }
 
 pub struct FixedBlockAuthor;
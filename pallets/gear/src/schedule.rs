@@ -110,6 +110,9 @@ pub struct Limits {
     /// See <https://wiki.parity.io/WebAssembly-StackHeight> to find out
     /// how the stack frame cost is calculated. Each element can be of one of the
     /// wasm value types. This means the maximum size per element is 64bit.
+    ///
+    /// Programs are instrumented with the limit, so [`InstructionWeights::version`]
+    /// should be incremented whenever it's changed to re-instrument deployed codes.
     pub stack_height: u32,
 
     /// Maximum number of globals a module is allowed to declare.
@@ -152,6 +155,17 @@ pub struct Limits {
     /// version of the code. Therefore `instantiate_with_code` can fail even when supplying
     /// a wasm binary below this maximum size.
     pub code_len: u32,
+
+    /// The maximum amount of blocks preceding the current one which hashes
+    /// are available to programs.
+    ///
+    /// Must not exceed `BlockHashCount` of `frame_system`, which hashes of
+    /// older blocks are pruned from the storage after.
+    pub block_hash_depth: u32,
+
+    /// The maximum amount of changed memory pages persisted after a single
+    /// execution. Execution changing more of them fails, persisting none.
+    pub max_pages_persisted_per_message: u32,
 }
 
 impl Limits {
@@ -357,6 +371,108 @@ pub struct HostFnWeights<T: Config> {
     /// Weight per payload byte by `create_program_wgas`.
     pub gr_create_program_wgas_per_byte: u64,
 
+    /// Weight of calling `gr_new_handle`.
+    pub gr_new_handle: u64,
+
+    /// Weight of calling `gr_delayed_eta`.
+    pub gr_delayed_eta: u64,
+
+    /// Weight of calling `gr_reserve_value`.
+    pub gr_reserve_value: u64,
+
+    /// Weight of calling `gr_unreserve_value`.
+    pub gr_unreserve_value: u64,
+
+    /// Weight of calling `gr_context_limits`.
+    pub gr_context_limits: u64,
+
+    /// Weight of calling `gr_children_count`.
+    pub gr_children_count: u64,
+
+    /// Weight of calling `gr_bump_counter`.
+    pub gr_bump_counter: u64,
+
+    /// Weight of calling `gr_is_init`.
+    pub gr_is_init: u64,
+
+    /// Weight of calling `gr_signal_details`.
+    pub gr_signal_details: u64,
+
+    /// Weight of calling `gr_forward`.
+    pub gr_forward: u64,
+
+    /// Weight per payload byte by `gr_forward`.
+    pub gr_forward_per_byte: u64,
+
+    /// Weight of calling `gr_block_hash`.
+    pub gr_block_hash: u64,
+
+    /// Weight of calling `gr_stack_limit`.
+    pub gr_stack_limit: u64,
+
+    /// Weight of calling `gr_split_value_reservation`.
+    pub gr_split_value_reservation: u64,
+
+    /// Weight of calling `gr_tx_hash`.
+    pub gr_tx_hash: u64,
+
+    /// Weight of calling `gr_set_default_gas_limit`.
+    pub gr_set_default_gas_limit: u64,
+
+    /// Weight of calling `gr_max_payload_size`.
+    pub gr_max_payload_size: u64,
+
+    /// Weight of calling `gr_resumed_from_wait`.
+    pub gr_resumed_from_wait: u64,
+
+    /// Weight of calling `gr_children_gas_burned`.
+    pub gr_children_gas_burned: u64,
+
+    /// Weight of calling `gr_wait_if`.
+    pub gr_wait_if: u64,
+
+    /// Weight of calling `gr_source_code_id`.
+    pub gr_source_code_id: u64,
+
+    /// Weight of calling `gr_wait_budget_remaining`.
+    pub gr_wait_budget_remaining: u64,
+
+    /// Weight of calling `gr_spendable_value`.
+    pub gr_spendable_value: u64,
+
+    /// Weight of calling `gr_block_duration`.
+    pub gr_block_duration: u64,
+
+    /// Weight of calling `gr_schedule_exit`.
+    pub gr_schedule_exit: u64,
+
+    /// Weight of calling `gr_message_meta`.
+    pub gr_message_meta: u64,
+
+    /// Weight of calling `gr_gas_allowance`.
+    pub gr_gas_allowance: u64,
+
+    /// Weight of calling `gr_mailbox_contains`.
+    pub gr_mailbox_contains: u64,
+
+    /// Weight of calling `gr_codes_count`.
+    pub gr_codes_count: u64,
+
+    /// Weight of calling `gr_trace`.
+    pub gr_trace: u64,
+
+    /// Weight per data byte by `gr_trace`.
+    pub gr_trace_per_byte: u64,
+
+    /// Weight of calling `gr_execution_id`.
+    pub gr_execution_id: u64,
+
+    /// Weight of calling `gr_set_entry_budget`.
+    pub gr_set_entry_budget: u64,
+
+    /// Weight of calling `gr_set_charge_allowance`.
+    pub gr_set_charge_allowance: u64,
+
     /// Weight of calling `gas`.
     pub gas: u64,
 
@@ -382,6 +498,9 @@ pub struct MemoryWeights<T: Config> {
     /// Weight of loading page.
     pub load_cost: u64,
 
+    /// Weight of persisting changed page.
+    pub write_cost: u64,
+
     /// The type parameter is used in the default implementation.
     #[codec(skip)]
     pub _phantom: PhantomData<T>,
@@ -484,6 +603,9 @@ impl Default for Limits {
             call_depth: 32,
             payload_len: 64 * 1024,
             code_len: 512 * 1024,
+            block_hash_depth: 64,
+            // 16 MiB of gear pages.
+            max_pages_persisted_per_message: 4096,
         }
     }
 }
@@ -491,7 +613,7 @@ impl Default for Limits {
 impl<T: Config> Default for InstructionWeights<T> {
     fn default() -> Self {
         Self {
-            version: 4,
+            version: 5,
             i64const: cost_instr!(instr_i64const, 1),
             i64load: cost_instr!(instr_i64load, 2),
             i64store: cost_instr!(instr_i64store, 2),
@@ -583,6 +705,40 @@ impl<T: Config> HostFnWeights<T> {
             gr_wake: self.gr_wake,
             gr_create_program_wgas: self.gr_create_program_wgas,
             gr_create_program_wgas_per_byte: self.gr_create_program_wgas_per_byte,
+            gr_new_handle: self.gr_new_handle,
+            gr_delayed_eta: self.gr_delayed_eta,
+            gr_reserve_value: self.gr_reserve_value,
+            gr_unreserve_value: self.gr_unreserve_value,
+            gr_context_limits: self.gr_context_limits,
+            gr_children_count: self.gr_children_count,
+            gr_bump_counter: self.gr_bump_counter,
+            gr_is_init: self.gr_is_init,
+            gr_signal_details: self.gr_signal_details,
+            gr_forward: self.gr_forward,
+            gr_forward_per_byte: self.gr_forward_per_byte,
+            gr_block_hash: self.gr_block_hash,
+            gr_stack_limit: self.gr_stack_limit,
+            gr_split_value_reservation: self.gr_split_value_reservation,
+            gr_tx_hash: self.gr_tx_hash,
+            gr_set_default_gas_limit: self.gr_set_default_gas_limit,
+            gr_max_payload_size: self.gr_max_payload_size,
+            gr_resumed_from_wait: self.gr_resumed_from_wait,
+            gr_children_gas_burned: self.gr_children_gas_burned,
+            gr_wait_if: self.gr_wait_if,
+            gr_source_code_id: self.gr_source_code_id,
+            gr_wait_budget_remaining: self.gr_wait_budget_remaining,
+            gr_spendable_value: self.gr_spendable_value,
+            gr_block_duration: self.gr_block_duration,
+            gr_schedule_exit: self.gr_schedule_exit,
+            gr_message_meta: self.gr_message_meta,
+            gr_gas_allowance: self.gr_gas_allowance,
+            gr_mailbox_contains: self.gr_mailbox_contains,
+            gr_codes_count: self.gr_codes_count,
+            gr_trace: self.gr_trace,
+            gr_trace_per_byte: self.gr_trace_per_byte,
+            gr_execution_id: self.gr_execution_id,
+            gr_set_entry_budget: self.gr_set_entry_budget,
+            gr_set_charge_allowance: self.gr_set_charge_allowance,
             gas: self.gas,
         }
     }
@@ -624,6 +780,40 @@ impl<T: Config> Default for HostFnWeights<T> {
             gr_wake: cost_batched!(gr_wake),
             gr_create_program_wgas: cost!(gr_create_program_wgas),
             gr_create_program_wgas_per_byte: cost_byte_batched!(gr_create_program_wgas_per_kb),
+            gr_new_handle: cost_batched!(gr_new_handle),
+            gr_delayed_eta: cost_batched!(gr_delayed_eta),
+            gr_reserve_value: cost_batched!(gr_reserve_value),
+            gr_unreserve_value: cost_batched!(gr_unreserve_value),
+            gr_context_limits: cost_batched!(gr_context_limits),
+            gr_children_count: cost_batched!(gr_children_count),
+            gr_bump_counter: cost_batched!(gr_bump_counter),
+            gr_is_init: cost_batched!(gr_is_init),
+            gr_signal_details: cost_batched!(gr_signal_details),
+            gr_forward: cost_batched!(gr_forward),
+            gr_forward_per_byte: cost_byte_batched!(gr_forward_per_kb),
+            gr_block_hash: cost_batched!(gr_block_hash),
+            gr_stack_limit: cost_batched!(gr_stack_limit),
+            gr_split_value_reservation: cost_batched!(gr_split_value_reservation),
+            gr_tx_hash: cost_batched!(gr_tx_hash),
+            gr_set_default_gas_limit: cost_batched!(gr_set_default_gas_limit),
+            gr_max_payload_size: cost_batched!(gr_max_payload_size),
+            gr_resumed_from_wait: cost_batched!(gr_resumed_from_wait),
+            gr_children_gas_burned: cost_batched!(gr_children_gas_burned),
+            gr_wait_if: cost_batched!(gr_wait_if),
+            gr_source_code_id: cost_batched!(gr_source_code_id),
+            gr_wait_budget_remaining: cost_batched!(gr_wait_budget_remaining),
+            gr_spendable_value: cost_batched!(gr_spendable_value),
+            gr_block_duration: cost_batched!(gr_block_duration),
+            gr_schedule_exit: cost_batched!(gr_schedule_exit),
+            gr_message_meta: cost_batched!(gr_message_meta),
+            gr_gas_allowance: cost_batched!(gr_gas_allowance),
+            gr_mailbox_contains: cost_batched!(gr_mailbox_contains),
+            gr_codes_count: cost_batched!(gr_codes_count),
+            gr_trace: cost_batched!(gr_trace),
+            gr_trace_per_byte: cost_byte_batched!(gr_trace_per_kb),
+            gr_execution_id: cost_batched!(gr_execution_id),
+            gr_set_entry_budget: cost_batched!(gr_set_entry_budget),
+            gr_set_charge_allowance: cost_batched!(gr_set_charge_allowance),
             gas: cost_batched!(gas),
             _phantom: PhantomData,
         }
@@ -637,6 +827,7 @@ impl<T: Config> Default for MemoryWeights<T> {
             allocation_cost: <T as Config>::WeightInfo::allocation_cost().ref_time(),
             grow_cost: <T as Config>::WeightInfo::grow_cost().ref_time(),
             load_cost: <T as Config>::WeightInfo::load_cost().ref_time(),
+            write_cost: <T as Config>::WeightInfo::write_cost().ref_time(),
             _phantom: PhantomData,
         }
     }
@@ -24,8 +24,9 @@ use crate::{
         RuntimeEvent as MockRuntimeEvent, RuntimeOrigin, System, Test, BLOCK_AUTHOR,
         LOW_BALANCE_USER, USER_1, USER_2, USER_3,
     },
-    pallet, BlockGasLimitOf, Config, CostsPerBlockOf, Error, Event, GasAllowanceOf, GasHandlerOf,
-    GasInfo, MailboxOf, WaitlistOf,
+    pallet, BlockGasLimitOf, CodeDepositOf, CodeRefcount, CodeStatsCount, CodeStatsOf, Config,
+    CostsPerBlockOf, Error, Event, GasAllowanceOf, GasHandlerOf, GasInfo, MailboxOf,
+    ScheduledExitOf, TxHashOf, TxHashWithheldOf, WaitlistOf, WeightInfo,
 };
 use codec::{Decode, Encode};
 use common::{
@@ -45,11 +46,16 @@ use frame_support::{
     traits::Currency,
 };
 use frame_system::{pallet_prelude::BlockNumberFor, Pallet as SystemPallet};
-use gear_backend_common::{StackEndError, TrapExplanation};
+use gear_backend_common::{
+    read::{ReadContext, ReadOrigin},
+    ring::RingBufferError,
+    syscall_signature, StackEndError, SysCallSignatureError, TrapExplanation,
+};
 use gear_backend_sandbox::funcs::FuncError;
 use gear_core::{
     code::{self, Code},
     ids::{CodeId, MessageId, ProgramId},
+    message::MetadataSizeError,
 };
 use gear_core_errors::*;
 use sp_runtime::{traits::UniqueSaturatedInto, SaturatedConversion};
@@ -106,7 +112,18 @@ fn unstoppable_block_execution_works() {
 
         assert!(balance_for_each_execution * executions_amount > real_gas_to_burn);
 
-        run_to_block(3, Some(minimal_weight.ref_time() + real_gas_to_burn));
+        let code_stats_weight = executions_amount * Gear::code_stats_weight();
+        let execution_nonce_weight = executions_amount * Gear::execution_nonce_weight();
+
+        run_to_block(
+            3,
+            Some(
+                minimal_weight.ref_time()
+                    + real_gas_to_burn
+                    + code_stats_weight
+                    + execution_nonce_weight,
+            ),
+        );
 
         assert_last_dequeued(executions_amount as u32);
 
@@ -751,7 +768,9 @@ fn spent_gas_to_reward_block_author_works() {
         let gas_spent = GasPrice::gas_price(
             BlockGasLimitOf::<Test>::get()
                 - GasAllowanceOf::<Test>::get()
-                - minimal_weight.ref_time(),
+                - minimal_weight.ref_time()
+                - Gear::code_stats_weight()
+                - Gear::execution_nonce_weight(),
         );
         assert_eq!(
             Balances::free_balance(BLOCK_AUTHOR),
@@ -805,10 +824,14 @@ fn unused_gas_released_back_works() {
 
         run_to_block(2, None);
 
+        // Both init and handle messages were accounted in code stats
+        // and got execution ids.
         let user1_actual_msgs_spends = GasPrice::gas_price(
             BlockGasLimitOf::<Test>::get()
                 - GasAllowanceOf::<Test>::get()
-                - minimal_weight.ref_time(),
+                - minimal_weight.ref_time()
+                - 2 * Gear::code_stats_weight()
+                - 2 * Gear::execution_nonce_weight(),
         );
 
         assert!(user1_potential_msgs_spends > user1_actual_msgs_spends);
@@ -1539,8 +1562,14 @@ fn block_gas_limit_works() {
 
         // program1 sends message to a user and it goes to the TaskPool
         let weight = minimal_weight + tasks_add_weight;
+        // Each processed message is also accounted in code stats.
+        let stats = Gear::code_stats_weight();
+        // Each message, which execution was tried, got an execution id.
+        let nonce = Gear::execution_nonce_weight();
         // both processed if gas allowance equals only burned count
-        run_to_next_block(Some(weight.ref_time() + gas1.burned + gas2.burned));
+        run_to_next_block(Some(
+            weight.ref_time() + gas1.burned + stats + nonce + gas2.burned + stats + nonce,
+        ));
         assert_last_dequeued(2);
 
         send_with_min_limit_to(pid1, &gas1);
@@ -1548,7 +1577,9 @@ fn block_gas_limit_works() {
         send_with_min_limit_to(pid1, &gas1);
 
         // Try to process 3 messages
-        run_to_next_block(Some(weight.ref_time() + gas1.burned + gas2.burned - 1));
+        run_to_next_block(Some(
+            weight.ref_time() + gas1.burned + stats + nonce + nonce + gas2.burned - 1,
+        ));
 
         // Message #1 is dequeued and processed.
         // Message #2 tried to execute, but exceed gas_allowance is re-queued at the top.
@@ -1565,7 +1596,14 @@ fn block_gas_limit_works() {
         // Try to process 2 messages.
         let additional_weight = 12;
         run_to_next_block(Some(
-            weight.ref_time() + gas2.burned + gas1.burned + additional_weight,
+            weight.ref_time()
+                + gas2.burned
+                + stats
+                + nonce
+                + gas1.burned
+                + stats
+                + nonce
+                + additional_weight,
         ));
 
         // Both messages got processed.
@@ -2190,9 +2228,12 @@ fn test_code_submission_pass() {
         let saved_code = <Test as Config>::CodeStorage::get_code(code_id);
 
         let schedule = <Test as Config>::Schedule::get();
-        let code = Code::try_new(code, schedule.instruction_weights.version, |module| {
-            schedule.rules(module)
-        })
+        let code = Code::try_new(
+            code,
+            schedule.instruction_weights.version,
+            |module| schedule.rules(module),
+            Some(schedule.limits.stack_height),
+        )
         .expect("Error creating Code");
         assert_eq!(saved_code.unwrap().code(), code.code());
 
@@ -2214,25 +2255,32 @@ fn test_code_submission_pass() {
 }
 
 #[test]
-fn test_same_code_submission_fails() {
+fn test_same_code_submission_is_noop() {
     init_logger();
     new_test_ext().execute_with(|| {
         let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code);
 
         assert_ok!(Gear::upload_code(
             RuntimeOrigin::signed(USER_1),
             code.clone()
         ),);
+        let expected_meta = <Test as Config>::CodeStorage::get_metadata(code_id);
+        let events = System::events().len();
+
         // Trying to set the same code twice.
-        assert_noop!(
-            Gear::upload_code(RuntimeOrigin::signed(USER_1), code.clone()),
-            Error::<Test>::CodeAlreadyExists,
-        );
+        assert_ok!(Gear::upload_code(
+            RuntimeOrigin::signed(USER_1),
+            code.clone()
+        ));
         // Trying the same from another origin
-        assert_noop!(
-            Gear::upload_code(RuntimeOrigin::signed(USER_2), code),
-            Error::<Test>::CodeAlreadyExists,
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_2), code));
+
+        assert_eq!(
+            <Test as Config>::CodeStorage::get_metadata(code_id),
+            expected_meta
         );
+        assert_eq!(System::events().len(), events);
     })
 }
 
@@ -2266,9 +2314,12 @@ fn test_code_is_not_submitted_twice_after_program_submission() {
         assert!(<Test as Config>::CodeStorage::exists(code_id));
 
         // Trying to set the same code twice.
-        assert_noop!(
-            Gear::upload_code(RuntimeOrigin::signed(USER_2), code),
-            Error::<Test>::CodeAlreadyExists,
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_2), code));
+        assert_eq!(
+            <Test as Config>::CodeStorage::get_metadata(code_id)
+                .expect("code is stored")
+                .author,
+            USER_1.into_origin()
         );
     })
 }
@@ -3082,16 +3133,34 @@ fn exit_init() {
             vec![],
             [0].to_vec(),
             50_000_000_000u64,
-            0u128
+            10_000u128
         ));
 
         let program_id = utils::get_last_program_id();
+        let init_message_id = get_last_message_id();
 
         run_to_block(2, None);
 
         assert!(!Gear::is_active(program_id));
         assert!(!Gear::is_initialized(program_id));
-        assert!(MailboxOf::<Test>::is_empty(&USER_1));
+        assert!(program_exists(program_id.into_origin()));
+
+        // Value is inherited by the creator.
+        assert_balance(program_id, 0u128, 0u128);
+
+        // Creator is notified with error reply.
+        let mail = get_last_mail(USER_1);
+        let reply = mail.reply().expect("Must be reply");
+        assert_eq!(reply.reply_to(), init_message_id);
+        assert_eq!(reply.exit_code(), core_processor::INIT_EXITED_EXIT_CODE);
+        assert_eq!(
+            mail.payload(),
+            ExecutionErrorReason::InitExited {
+                inheritor: USER_1.into_origin().into()
+            }
+            .to_string()
+            .as_bytes()
+        );
 
         // Program is not removed and can't be submitted again
         assert_noop!(
@@ -3108,6 +3177,324 @@ fn exit_init() {
     })
 }
 
+#[test]
+fn failed_child_init_terminates_program() {
+    // Creates the child with empty salt from the code id in the payload,
+    // forwards exit code of the reply to the sender of the last request.
+    let factory = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_create_program"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_exit_code" (func $exit_code (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_reply" (func $handle_reply))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x100))
+                (call $source (i32.const 0x700))
+                (if (call $create (i32.const 0x100) (i32.const 0x200) (i32.const 0)
+                        (i32.const 0) (i32.const 0) (i32.const 0x300) (i32.const 0x400)
+                        (i32.const 0x500))
+                    (then unreachable))
+            )
+            (func $handle_reply
+                (i32.store (i32.const 0x600) (call $exit_code))
+                (if (call $send (i32.const 0x700) (i32.const 0x600) (i32.const 4)
+                        (i32.const 0x300) (i32.const 0x800) (i32.const 0x500))
+                    (then unreachable))
+            )
+        )"#;
+
+    let child = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (export "init" (func $init))
+            (func $init unreachable)
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let child_code = ProgramCodeKind::Custom(child).to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        let child_id = ProgramId::generate(child_code_id, &[]);
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(factory)
+        ));
+        let factory_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(factory_id));
+
+        System::reset_events();
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            factory_id,
+            child_code_id.encode(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+        run_to_next_block(None);
+
+        // Creator is notified that the child id is dead with a distinct code.
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.source(), factory_id);
+        assert_eq!(
+            message.payload(),
+            core_processor::INIT_FAILURE_EXIT_CODE.to_le_bytes()
+        );
+
+        // The child is terminated with the creator as inheritor.
+        assert!(Gear::is_terminated(child_id));
+        assert_eq!(
+            common::get_program(child_id.into_origin()),
+            Some(common::Program::Terminated(factory_id))
+        );
+    });
+}
+
+// Creates the child with empty salt from the code id in the payload, then
+// forwards the init message id, the id of the message replied to, exit
+// code and payload of the reply to the sender of the last request.
+const FORWARDING_FACTORY_WAT: &str = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_create_program_for_reply"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply_to" (func $reply_to (param i32)))
+            (import "env" "gr_exit_code" (func $exit_code (result i32)))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_reply" (func $handle_reply))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x100))
+                (call $source (i32.const 0x700))
+                (if (call $create (i32.const 0x100) (i32.const 0x200) (i32.const 0)
+                        (i32.const 0) (i32.const 0) (i32.const 0x300) (i32.const 0x1000)
+                        (i32.const 0x400) (i32.const 0x500))
+                    (then unreachable))
+            )
+            (func $handle_reply
+                (call $reply_to (i32.const 0x1020))
+                (i32.store (i32.const 0x1040) (call $exit_code))
+                (call $read (i32.const 0) (call $size) (i32.const 0x1044))
+                (if (call $send (i32.const 0x700) (i32.const 0x1000)
+                        (i32.add (call $size) (i32.const 0x44))
+                        (i32.const 0x300) (i32.const 0x800) (i32.const 0x500))
+                    (then unreachable))
+            )
+        )"#;
+
+#[test]
+fn child_init_reply_forwarded_to_creator() {
+    let child = |trap| {
+        format!(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "gr_reply"
+                    (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+                (export "init" (func $init))
+                (func $init
+                    (if (call $reply (i32.const 0) (i32.const 6) (i32.const 0x100)
+                            (i32.const 0x200) (i32.const 0x300))
+                        (then unreachable))
+                    {}
+                )
+                (data (i32.const 0) "digest")
+            )"#,
+            if trap { "unreachable" } else { "" }
+        )
+    };
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(FORWARDING_FACTORY_WAT)
+        ));
+        let factory_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(factory_id));
+
+        for trap in [false, true] {
+            let child_code = ProgramCodeKind::Custom(&child(trap)).to_bytes();
+            let child_code_id = CodeId::generate(&child_code);
+            let child_id = ProgramId::generate(child_code_id, &[]);
+            assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+            System::reset_events();
+
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                factory_id,
+                child_code_id.encode(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+            run_to_next_block(None);
+
+            // Creator is replied to its init message exactly once.
+            let messages: Vec<_> = System::events()
+                .into_iter()
+                .filter_map(|e| match e.event {
+                    MockRuntimeEvent::Gear(Event::UserMessageSent { message, .. })
+                        if message.destination() == USER_1.into() =>
+                    {
+                        Some(message)
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(messages.len(), 1);
+
+            let payload = messages[0].payload();
+            assert_eq!(&payload[..32], &payload[32..64]);
+
+            let exit_code = i32::from_le_bytes(payload[64..68].try_into().unwrap());
+            if trap {
+                // Error reply wins over the reply sent before the trap.
+                assert_eq!(exit_code, core_processor::INIT_FAILURE_EXIT_CODE);
+                assert_ne!(&payload[68..], b"digest");
+                assert!(Gear::is_terminated(child_id));
+            } else {
+                assert_eq!(exit_code, 0);
+                assert_eq!(&payload[68..], b"digest");
+                assert!(Gear::is_initialized(child_id));
+            }
+        }
+    });
+}
+
+#[test]
+fn child_init_exit_reply_forwarded_to_creator() {
+    // The child exits in init, leaving its value to the creator.
+    let child = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_exit" (func $exit (param i32)))
+            (export "init" (func $init))
+            (func $init
+                (call $source (i32.const 0))
+                (call $exit (i32.const 0))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(FORWARDING_FACTORY_WAT)
+        ));
+        let factory_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(factory_id));
+
+        let child_code = ProgramCodeKind::Custom(child).to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        let child_id = ProgramId::generate(child_code_id, &[]);
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+        System::reset_events();
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            factory_id,
+            child_code_id.encode(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+        run_to_next_block(None);
+
+        let message = maybe_last_message(USER_1).expect("message expected");
+        let payload = message.payload();
+        assert_eq!(&payload[..32], &payload[32..64]);
+
+        // Creator gets the dedicated exit code along with the inheritor.
+        let exit_code = i32::from_le_bytes(payload[64..68].try_into().unwrap());
+        assert_eq!(exit_code, core_processor::INIT_EXITED_EXIT_CODE);
+        let reason =
+            ExecutionErrorReason::decode(&mut &payload[68..]).expect("init exit reason expected");
+        assert_eq!(
+            reason,
+            ExecutionErrorReason::InitExited {
+                inheritor: factory_id
+            }
+        );
+
+        assert!(Gear::is_exited(child_id));
+        assert_balance(child_id, 0u128, 0u128);
+    });
+}
+
+#[test]
+fn exit_handle_keeps_program() {
+    use demo_exit_handle::WASM_BINARY;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code_id = CodeId::generate(WASM_BINARY);
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            vec![],
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000u64,
+            10_000u128
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert!(Gear::is_initialized(program_id));
+        assert_balance(program_id, 10_000u128, 0u128);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000u64,
+            0u128
+        ));
+
+        run_to_block(3, None);
+
+        // Value is inherited, but program is kept exited.
+        assert!(!Gear::is_active(program_id));
+        assert!(program_exists(program_id.into_origin()));
+        assert_balance(program_id, 0u128, 0u128);
+
+        assert_noop!(
+            Gear::create_program(
+                RuntimeOrigin::signed(USER_1),
+                code_id,
+                vec![],
+                Vec::new(),
+                2_000_000_000,
+                0u128
+            ),
+            Error::<Test>::ProgramAlreadyExists,
+        );
+    })
+}
+
 #[test]
 fn test_create_program_works() {
     use demo_init_wait::WASM_BINARY;
@@ -3125,9 +3512,12 @@ fn test_create_program_works() {
 
         // Parse wasm code.
         let schedule = <Test as Config>::Schedule::get();
-        let code = Code::try_new(code, schedule.instruction_weights.version, |module| {
-            schedule.rules(module)
-        })
+        let code = Code::try_new(
+            code,
+            schedule.instruction_weights.version,
+            |module| schedule.rules(module),
+            Some(schedule.limits.stack_height),
+        )
         .expect("Code failed to load");
 
         let code_id = CodeId::generate(code.raw_code());
@@ -3402,10 +3792,10 @@ fn test_create_program_duplicate() {
         ));
         run_to_block(4, None);
 
-        // When duplicate try happens, init is not executed, a reply is generated and executed (+2 dequeued, +1 dispatched)
-        // Concerning dispatch message, it is executed, because destination exists (+1 dispatched, +1 dequeued)
-        assert_eq!(MailboxOf::<Test>::len(&USER_2), 1);
-        assert_total_dequeued(3 + 3); // +3 from extrinsics (2 upload_program, 1 send_message)
+        // Duplicate is detected by the syscall, so neither init nor dispatch message is sent
+        // and the factory fails on unwrapping the error
+        assert_eq!(MailboxOf::<Test>::len(&USER_2), 0);
+        assert_total_dequeued(3); // 3 from extrinsics (2 upload_program, 1 send_message)
         assert_init_success(2); // +2 from extrinsics (2 upload_program)
 
         System::reset_events();
@@ -3434,10 +3824,9 @@ fn test_create_program_duplicate() {
         run_to_block(6, None);
 
         // First call successfully creates a program and sends a messages to it (+2 dequeued, +1 dispatched)
-        // Second call will not cause init message execution, but a reply will be generated (+2 dequeued, +1 dispatched)
-        // Handle message from the second call will be executed (addressed for existing destination) (+1 dequeued, +1 dispatched)
-        assert_eq!(MailboxOf::<Test>::len(&USER_2), 1);
-        assert_total_dequeued(5 + 2); // +2 from extrinsics (send_message)
+        // Second call fails on the duplicate without sending any messages
+        assert_eq!(MailboxOf::<Test>::len(&USER_2), 0);
+        assert_total_dequeued(2 + 2); // +2 from extrinsics (send_message)
         assert_init_success(1);
 
         assert_noop!(
@@ -4863,37 +5252,192 @@ fn delayed_wake() {
 }
 
 #[test]
-fn cascading_messages_with_value_do_not_overcharge() {
+fn delays_pin_execution_block() {
+    // Sends, replies or creates the child from the code id in the payload
+    // with the delay from it, using the delay as the salt of the child.
+    let scheduler = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_create_program"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (call $size) (i32.const 0x100))
+                (call $source (i32.const 0x200))
+                (if (i32.eqz (i32.load (i32.const 0x100)))
+                    (then
+                        (if (call $send (i32.const 0x200) (i32.const 0) (i32.const 0)
+                                (i32.const 0x300) (i32.const 0x400) (i32.const 0x104))
+                            (then unreachable))
+                    )
+                    (else
+                        (if (i32.eq (i32.load (i32.const 0x100)) (i32.const 1))
+                            (then
+                                (if (call $reply (i32.const 0) (i32.const 0) (i32.const 0x300)
+                                        (i32.const 0x400) (i32.const 0x104))
+                                    (then unreachable))
+                            )
+                            (else
+                                (if (call $create (i32.const 0x108) (i32.const 0x104)
+                                        (i32.const 4) (i32.const 0) (i32.const 0)
+                                        (i32.const 0x300) (i32.const 0x400) (i32.const 0x104))
+                                    (then unreachable))
+                            )
+                        )
+                    )
+                )
+            )
+        )"#;
+
+    let child = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (export "init" (func $init))
+            (func $init)
+        )"#;
+
+    // Runs blocks until `executed` holds, returning the block it held in.
+    fn executed_in(executed: impl Fn() -> bool) -> u64 {
+        for _ in 0..10 {
+            System::reset_events();
+            run_to_next_block(None);
+
+            if executed() {
+                return System::block_number();
+            }
+        }
+
+        unreachable!("Not executed in 10 blocks");
+    }
+
     init_logger();
     new_test_ext().execute_with(|| {
-        let contract_id = generate_program_id(MUL_CONST_WASM_BINARY, b"contract");
-        let wrapper_id = generate_program_id(WAITING_PROXY_WASM_BINARY, b"salt");
+        let child_code = ProgramCodeKind::Custom(child).to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
 
-        assert_ok!(Gear::upload_program(
-            RuntimeOrigin::signed(USER_1),
-            MUL_CONST_WASM_BINARY.to_vec(),
-            b"contract".to_vec(),
-            50_u64.encode(),
-            5_000_000_000,
-            0,
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(scheduler)
         ));
+        let scheduler_id = get_last_program_id();
 
-        assert_ok!(Gear::upload_program(
-            RuntimeOrigin::signed(USER_1),
-            WAITING_PROXY_WASM_BINARY.to_vec(),
-            b"salt".to_vec(),
-            <[u8; 32]>::from(contract_id).encode(),
-            5_000_000_000,
-            0,
-        ));
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(scheduler_id));
+
+        for delay in [0u32, 1, 3] {
+            // Sends and replies.
+            for mode in [0u32, 1] {
+                assert_ok!(Gear::send_message(
+                    RuntimeOrigin::signed(USER_1),
+                    scheduler_id,
+                    (mode, delay).encode(),
+                    DEFAULT_GAS_LIMIT * 100,
+                    0,
+                ));
+                let handled_in = System::block_number() + 1;
+
+                assert_eq!(
+                    executed_in(|| maybe_last_message(USER_1).is_some()),
+                    handled_in + u64::from(delay)
+                );
+            }
 
-        run_to_block(2, None);
+            // Program creation.
+            let child_id = ProgramId::generate(child_code_id, &delay.to_le_bytes());
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                scheduler_id,
+                (2u32, delay, child_code_id).encode(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+            let handled_in = System::block_number() + 1;
 
-        let payload = 100_u64.to_le_bytes().to_vec();
+            assert_eq!(
+                executed_in(|| Gear::is_initialized(child_id)),
+                handled_in + u64::from(delay)
+            );
 
-        let user_balance_before_calculating = Balances::free_balance(USER_1);
+            // Wakes.
+            assert_ok!(Gear::upload_program(
+                RuntimeOrigin::signed(USER_1),
+                demo_delayed_sender::WASM_BINARY.to_vec(),
+                delay.to_le_bytes().to_vec(),
+                0u32.to_le_bytes().to_vec(),
+                BlockGasLimitOf::<Test>::get(),
+                0
+            ));
+            let waker_id = get_last_program_id();
 
-        run_to_block(3, None);
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                waker_id,
+                vec![],
+                DEFAULT_GAS_LIMIT * 100,
+                0
+            ));
+            let waiting_id = get_last_message_id();
+
+            run_to_next_block(None);
+            assert!(WaitlistOf::<Test>::contains(&waker_id, &waiting_id));
+
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                waker_id,
+                delay.to_le_bytes().to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0
+            ));
+            let handled_in = System::block_number() + 1;
+
+            assert_eq!(
+                executed_in(|| !WaitlistOf::<Test>::contains(&waker_id, &waiting_id)),
+                handled_in + u64::from(delay)
+            );
+        }
+    });
+}
+
+#[test]
+fn cascading_messages_with_value_do_not_overcharge() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let contract_id = generate_program_id(MUL_CONST_WASM_BINARY, b"contract");
+        let wrapper_id = generate_program_id(WAITING_PROXY_WASM_BINARY, b"salt");
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            MUL_CONST_WASM_BINARY.to_vec(),
+            b"contract".to_vec(),
+            50_u64.encode(),
+            5_000_000_000,
+            0,
+        ));
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            WAITING_PROXY_WASM_BINARY.to_vec(),
+            b"salt".to_vec(),
+            <[u8; 32]>::from(contract_id).encode(),
+            5_000_000_000,
+            0,
+        ));
+
+        run_to_block(2, None);
+
+        let payload = 100_u64.to_le_bytes().to_vec();
+
+        let user_balance_before_calculating = Balances::free_balance(USER_1);
+
+        run_to_block(3, None);
 
         // The constant added for checks.
         let value = 10_000_000;
@@ -5404,6 +5948,65 @@ fn test_async_messages() {
     })
 }
 
+#[test]
+fn plain_message_does_not_resolve_reply_future() {
+    use demo_async_tester::{Kind, WASM_BINARY};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            WASM_BINARY.to_vec(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000u64,
+            0,
+        ));
+
+        let pid = get_last_program_id();
+        run_to_next_block(None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            pid,
+            Kind::Send.encode(),
+            10_000_000_000u64,
+            0,
+        ));
+
+        run_to_next_block(None);
+        let request = get_last_mail(USER_1);
+        assert_eq!(Kind::decode(&mut request.payload()), Ok(Kind::Send));
+
+        // Plain message looking like the expected reply is processed
+        // by `handle` as a new request, rather than resolving the future.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            pid,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000u64,
+            0,
+        ));
+        let spoofed_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert_eq!(dispatch_status(spoofed_id), Some(DispatchStatus::Failed));
+        assert!(maybe_last_message(USER_1).map_or(true, |m| m.payload() != b"PONG"));
+
+        assert_ok!(Gear::send_reply(
+            RuntimeOrigin::signed(USER_1),
+            request.id(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000u64,
+            0,
+        ));
+
+        run_to_next_block(None);
+        let pong = maybe_last_message(USER_1).expect("Program must send PONG");
+        assert_eq!(pong.payload(), b"PONG");
+    })
+}
+
 #[test]
 fn missing_functions_are_not_executed() {
     // handle is copied from ProgramCodeKind::OutgoingWithValueInHandle
@@ -5603,6 +6206,7 @@ fn test_mad_big_prog_instrumentation() {
             code_bytes,
             schedule.instruction_weights.version,
             |module| schedule.rules(module),
+            Some(schedule.limits.stack_height),
         );
         // In any case of the defined weights on the platform, instrumentation of the valid
         // huge wasm mustn't fail
@@ -6334,66 +6938,4474 @@ fn check_gr_read_error_works() {
         assert_failed(
             message_id,
             ExecutionErrorReason::Ext(TrapExplanation::Other(
-                FuncError::<<crate::Ext as ProcessorExt>::Error>::ReadWrongRange(0..10, 0)
-                    .to_string()
-                    .into(),
+                FuncError::<<crate::Ext as ProcessorExt>::Error>::ReadWrongRange(
+                    0..10,
+                    0,
+                    ReadContext::new("gr_read", ReadOrigin::Requested),
+                )
+                .to_string()
+                .into(),
             )),
         );
     });
 }
 
-/// Check that too large message, which is constructed by `gr_reply_push`,
-/// leads to program execution error.
+/// Test that `gr_read_records` reads the record by its index and reports
+/// the index out of records with the error length.
 #[test]
-fn check_reply_push_payload_exceed() {
+fn gr_read_records_works() {
     let wat = r#"
         (module
-            (import "env" "memory" (memory 0x100))
-            (import "env" "gr_reply_push" (func $gr (param i32 i32) (result i32)))
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read_records" (func $read_records (param i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
             (export "init" (func $init))
-            (func $init
-                ;; first reply push must be ok
-                (block
-                    i32.const 0
-                    i32.const 0x1000000
-                    call $gr
-                    i32.eqz
-                    br_if 0
-                    unreachable
-                )
-                ;; second must lead to overflow
-                (block
-                    i32.const 0
-                    i32.const 0x1000000
-                    call $gr
-                    unreachable
-                )
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (if (call $read_records (i32.const 1) (i32.const 0x100) (i32.const 8)
+                        (i32.const 0x200))
+                    (then
+                        (if (call $reply (i32.const 0x700) (i32.const 4)
+                                (i32.const 0x400) (i32.const 0x500) (i32.const 0x600))
+                            (then unreachable)))
+                    (else
+                        (if (call $reply (i32.const 0x100) (i32.load (i32.const 0x200))
+                                (i32.const 0x400) (i32.const 0x500) (i32.const 0x600))
+                            (then unreachable))))
             )
+            (data (i32.const 0x700) "none")
         )"#;
 
+    let records = |records: &[&[u8]]| {
+        let mut payload = Vec::new();
+        for record in records {
+            payload.extend_from_slice(&(record.len() as u32).to_le_bytes());
+            payload.extend_from_slice(record);
+        }
+        payload
+    };
+
     init_logger();
     new_test_ext().execute_with(|| {
-        Gear::upload_program(
+        assert_ok!(Gear::upload_program(
             RuntimeOrigin::signed(USER_1),
             ProgramCodeKind::Custom(wat).to_bytes(),
             DEFAULT_SALT.to_vec(),
             EMPTY_PAYLOAD.to_vec(),
-            50_000_000_000,
+            DEFAULT_GAS_LIMIT * 100,
             0,
-        )
-        .expect("Failed to upload program");
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        // The second record is read.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            records(&[b"ab", b"cde"]),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+        assert_last_dequeued(1);
+
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        assert_eq!(reply.payload(), b"cde");
+
+        // The only record is there, so the index is out of records.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            records(&[b"ab"]),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+        assert_last_dequeued(1);
+
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        assert_eq!(reply.payload(), b"none");
+
+        // The second record's prefix points out of the payload.
+        let mut payload = records(&[b"ab"]);
+        payload.extend_from_slice(&9u32.to_le_bytes());
+        payload.push(b'x');
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            payload,
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
 
         let message_id = get_last_message_id();
 
+        run_to_next_block(None);
+        assert_last_dequeued(1);
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Other(
+                FuncError::<<crate::Ext as ProcessorExt>::Error>::ReadWrongRange(
+                    10..19,
+                    11,
+                    ReadContext::new("gr_read_records", ReadOrigin::Sliced),
+                )
+                .to_string()
+                .into(),
+            )),
+        );
+    });
+}
+
+/// Test that `gr_read_ring` wraps payload around the end of the ring buffer.
+#[test]
+fn gr_read_ring_wraps_around() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read_ring" (func $read_ring (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init
+                ;; 4 bytes of the payload starting at 1 into the ring
+                ;; of capacity 4 at 0x100 with write offset 2
+                (if (i32.ne
+                        (call $read_ring (i32.const 1) (i32.const 4)
+                            (i32.const 0x100) (i32.const 4) (i32.const 2))
+                        (i32.const 2))
+                    (then unreachable))
+                (if (call $reply (i32.const 0x100) (i32.const 4)
+                        (i32.const 0) (i32.const 0x200) (i32.const 0x300))
+                    (then unreachable))
+            )
+            (func $handle
+                ;; write offset is out of the ring
+                (drop (call $read_ring (i32.const 0) (i32.const 1)
+                    (i32.const 0x100) (i32.const 4) (i32.const 4)))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            b"abcdef".to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
         run_to_block(2, None);
         assert_last_dequeued(1);
+        assert!(Gear::is_initialized(program_id));
+
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        assert_eq!(reply.payload(), b"debc");
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+        assert_last_dequeued(1);
         assert_failed(
             message_id,
             ExecutionErrorReason::Ext(TrapExplanation::Other(
-                ExtError::Message(MessageError::MaxMessageSizeExceed)
-                    .to_string()
-                    .into(),
+                FuncError::<<crate::Ext as ProcessorExt>::Error>::RingBuffer(
+                    RingBufferError::WrongOffset(4, 4),
+                )
+                .to_string()
+                .into(),
             )),
         );
     });
 }
+
+/// Test that exit codes chosen by programs for their replies are told apart
+/// from ones of the replies generated by the system.
+#[test]
+fn reply_with_code_told_from_system_error() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_reply_with_code" (func $reply_with_code (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                ;; exit code of the reply is taken from the payload
+                (call $read (i32.const 0) (i32.const 4) (i32.const 0x100))
+                (if (call $reply_with_code (i32.const 0x200) (i32.const 4)
+                        (i32.load (i32.const 0x100)) (i32.const 0x300)
+                        (i32.const 0x400) (i32.const 0x500))
+                    (then unreachable))
+            )
+            (data (i32.const 0x200) "PONG")
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        let reply_code_to = |exit_code: i32| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                exit_code.to_le_bytes().to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            let message_id = get_last_message_id();
+
+            run_to_next_block(None);
+
+            let reply = maybe_last_message(USER_1).expect("reply expected");
+            assert_eq!(reply.reply_to(), Some(message_id));
+
+            reply
+        };
+
+        let reply = reply_code_to(404);
+        let code = ReplyCode::from(reply.exit_code().expect("Must be reply"));
+        assert_eq!(code, ReplyCode::from_raw(404));
+        assert!(code.is_user_error());
+        assert!(!reply.is_error_reply());
+        assert_eq!(reply.payload(), b"PONG");
+
+        // Codes of the system are reserved, so the reply is rejected
+        // and the program traps.
+        let reply = reply_code_to(core_processor::ERR_EXIT_CODE);
+        let code = ReplyCode::from(reply.exit_code().expect("Must be reply"));
+        assert_eq!(code, ReplyCode::EXECUTION_ERROR);
+        assert!(code.is_system_error());
+        assert!(reply.is_error_reply());
+        assert_ne!(reply.payload(), b"PONG");
+    });
+}
+
+/// Test that the default gas limit applies only to messages sent after it's set.
+#[test]
+fn default_gas_limit_applies_to_later_sends() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let gas_limit = <Test as Config>::MailboxThreshold::get();
+
+        let wat = format!(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "gr_source" (func $source (param i32)))
+                (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+                (import "env" "gr_set_default_gas_limit" (func $set_default_gas_limit (param i64)))
+                (export "init" (func $init))
+                (export "handle" (func $handle))
+                (func $init)
+                (func $handle
+                    (call $source (i32.const 0x100))
+                    (if (call $send (i32.const 0x100) (i32.const 0x200) (i32.const 1)
+                            (i32.const 0x300) (i32.const 0x400) (i32.const 0x500))
+                        (then unreachable))
+                    (call $set_default_gas_limit (i64.const {gas_limit}))
+                    (if (call $send (i32.const 0x100) (i32.const 0x201) (i32.const 1)
+                            (i32.const 0x300) (i32.const 0x400) (i32.const 0x500))
+                        (then unreachable))
+                )
+                (data (i32.const 0x200) "AB")
+            )"#
+        );
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(&wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+
+        let sent = |payload: &[u8]| {
+            System::events()
+                .into_iter()
+                .find_map(|e| match e.event {
+                    MockRuntimeEvent::Gear(Event::UserMessageSent { message, .. })
+                        if message.payload() == payload =>
+                    {
+                        Some(message.id())
+                    }
+                    _ => None,
+                })
+                .expect("Message must be sent")
+        };
+
+        let mailbox_key = AccountId::from_origin(USER_1.into_origin());
+
+        // Sent before the default is set, so without gas.
+        let message_id = sent(b"A");
+        assert!(!MailboxOf::<Test>::contains(&mailbox_key, &message_id));
+
+        let message_id = sent(b"B");
+        assert!(MailboxOf::<Test>::contains(&mailbox_key, &message_id));
+        assert_ok!(GasHandlerOf::<Test>::get_limit(message_id), gas_limit);
+    });
+}
+
+/// Test that the default gas limit applies to messages sent from value reservations.
+#[test]
+fn default_gas_limit_applies_to_reservation_send() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let gas_limit = <Test as Config>::MailboxThreshold::get();
+
+        let wat = format!(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "gr_reserve_value" (func $reserve (param i32 i32) (result i32)))
+                (import "env" "gr_reservation_send"
+                    (func $reservation_send (param i32 i32 i32 i32 i32 i32 i32) (result i32)))
+                (import "env" "gr_set_default_gas_limit" (func $set_default_gas_limit (param i64)))
+                (export "init" (func $init))
+                ;; destination is USER_1
+                (data (i32.const 0x0) "\01")
+                ;; 10_000 value
+                (data (i32.const 0x20) "\10\27")
+                (func $init
+                    (if (call $reserve (i32.const 0x20) (i32.const 0x100))
+                        (then unreachable))
+                    (call $set_default_gas_limit (i64.const {gas_limit}))
+                    (if (call $reservation_send (i32.const 0x100) (i32.const 0) (i32.const 0)
+                            (i32.const 0) (i32.const 0x20) (i32.const 0x200) (i32.const 0x40))
+                        (then unreachable))
+                )
+            )"#
+        );
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(&wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            10_000,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.value(), 10_000);
+
+        let mailbox_key = AccountId::from_origin(USER_1.into_origin());
+        assert!(MailboxOf::<Test>::contains(&mailbox_key, &message.id()));
+        assert_ok!(GasHandlerOf::<Test>::get_limit(message.id()), gas_limit);
+    });
+}
+
+/// Test that the default gas limit doesn't override the gas limit
+/// of recipient charged messages.
+#[test]
+fn default_gas_limit_kept_by_send_charge_recipient() {
+    // Agrees to pay for messages with 15_000_000 of gas in total.
+    let receiver_wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_set_charge_allowance" (func $set_charge_allowance (param i64)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init
+                (call $set_charge_allowance (i64.const 15000000))
+            )
+            (func $handle)
+        )"#;
+
+    // Sets the default gas limit exceeding the allowance and sends
+    // recipient charged message to the destination from the payload.
+    let sender_wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_set_default_gas_limit" (func $set_default_gas_limit (param i64)))
+            (import "env" "gr_send_charge_recipient" (func $send (param i32 i32 i32 i64 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0))
+                (call $set_default_gas_limit (i64.const 20000000))
+                (if (call $send (i32.const 0) (i32.const 0) (i32.const 0) (i64.const 10000000)
+                        (i32.const 0x40) (i32.const 0x80))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(receiver_wat)
+        ));
+        let receiver = get_last_program_id();
+
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(sender_wat)
+        ));
+        let sender = get_last_program_id();
+
+        run_to_block(2, None);
+
+        // Funding receiver, so it's able to pay for messages.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            receiver,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            1_000_000_000,
+        ));
+
+        run_to_block(3, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            sender,
+            receiver.encode(),
+            10_000_000_000,
+            0,
+        ));
+
+        run_to_block(4, None);
+
+        // Sender's message and the recipient charged one, which was
+        // paid with the explicit gas limit.
+        assert_last_dequeued(2);
+        assert_eq!(common::get_charge_allowance(receiver), 5_000_000);
+    });
+}
+
+/// Test that programs are told the max payload size.
+#[test]
+fn max_payload_size_told() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let max_payload_size = gear_core::message::MAX_PAYLOAD_SIZE;
+
+        let wat = format!(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "gr_max_payload_size" (func $max_payload_size (result i32)))
+                (export "init" (func $init))
+                (func $init
+                    (if (i32.ne (call $max_payload_size) (i32.const {max_payload_size}))
+                        (then unreachable))
+                )
+            )"#
+        );
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(&wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+    });
+}
+
+#[test]
+fn resumed_from_wait_told() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_msg_id" (func $msg_id (param i32)))
+            (import "env" "gr_resumed_from_wait" (func $resumed_from_wait (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_wait" (func $wait))
+            (import "env" "gr_wake" (func $wake (param i32 i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (data (i32.const 0x100) "resumed")
+            (func $init
+                ;; init isn't resumed
+                (if (call $resumed_from_wait)
+                    (then unreachable))
+            )
+            (func $handle
+                (if (call $resumed_from_wait)
+                    (then
+                        (if (call $send (i32.const 0x0) (i32.const 0x100) (i32.const 7)
+                                (i32.const 0x20) (i32.const 0x400) (i32.const 0x20))
+                            (then unreachable))
+                        return))
+                ;; non-empty message wakes the waiting one
+                (if (call $size)
+                    (then
+                        (call $wake (i32.const 0x300) (i32.const 0x20))
+                        return))
+                (call $msg_id (i32.const 0x300))
+                (call $wait)
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &message_id));
+        assert!(maybe_last_message(USER_1).is_none());
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            b"wake".to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &message_id));
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.payload(), b"resumed");
+    });
+}
+
+#[test]
+fn wait_if_waits_only_on_true() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_wait_if" (func $wait_if (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (data (i32.const 0x100) "complete")
+            (func $init)
+            (func $handle
+                ;; empty message waits for more input
+                (call $wait_if (i32.eqz (call $size)))
+                (if (call $send (i32.const 0x0) (i32.const 0x100) (i32.const 8)
+                        (i32.const 0x20) (i32.const 0x400) (i32.const 0x20))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+        let waiting_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &waiting_id));
+        assert!(maybe_last_message(USER_1).is_none());
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            b"input".to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &message_id));
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.payload(), b"complete");
+    });
+}
+
+/// Test that execution changing more pages than allowed persists none of them.
+#[test]
+fn gas_allowance_shared_within_block() {
+    // Replies with the block gas allowance and the message gas left.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_gas_allowance" (func $gas_allowance (result i64)))
+            (import "env" "gr_gas_available" (func $gas_available (result i64)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (i64.store (i32.const 0x20) (call $gas_allowance))
+                (i64.store (i32.const 0x28) (call $gas_available))
+                (if (call $reply (i32.const 0x20) (i32.const 16)
+                        (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let gas_limit = 10_000_000_000;
+        let block_gas_limit = BlockGasLimitOf::<Test>::get();
+        assert!(gas_limit < block_gas_limit);
+
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        for _ in 0..2 {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                EMPTY_PAYLOAD.to_vec(),
+                gas_limit,
+                0,
+            ));
+        }
+        run_to_next_block(None);
+
+        let replies: Vec<_> = System::events()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                MockRuntimeEvent::Gear(Event::UserMessageSent { message, .. })
+                    if message.destination() == USER_1.into() =>
+                {
+                    let allowance = u64::from_le_bytes(message.payload()[..8].try_into().unwrap());
+                    let available = u64::from_le_bytes(message.payload()[8..].try_into().unwrap());
+                    Some((allowance, available))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(replies.len(), 2);
+
+        for &(allowance, available) in &replies {
+            assert!(allowance < block_gas_limit);
+            assert!(available < gas_limit);
+            assert!(allowance > available);
+        }
+
+        // The second message sees what's left after the first one.
+        assert!(replies[1].0 < replies[0].0);
+    });
+}
+
+#[test]
+fn codes_count_tracks_uploaded_codes() {
+    // Replies with the number of codes known to the chain.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_codes_count" (func $codes_count (result i64)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (i64.store (i32.const 0x20) (call $codes_count))
+                (if (call $reply (i32.const 0x20) (i32.const 8)
+                        (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    let codes_count = |program_id| {
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        run_to_next_block(None);
+
+        let reply = maybe_last_message(USER_1).expect("Reply should be sent");
+        u64::from_le_bytes(reply.payload().try_into().unwrap())
+    };
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_eq!(codes_count(program_id), 1);
+
+        let code = ProgramCodeKind::Default.to_bytes();
+        assert_ok!(Gear::upload_code(
+            RuntimeOrigin::signed(USER_1),
+            code.clone()
+        ));
+
+        assert_eq!(codes_count(program_id), 2);
+
+        // Creating a program from the known code doesn't add a new one.
+        assert_ok!(Gear::create_program(
+            RuntimeOrigin::signed(USER_1),
+            CodeId::generate(&code),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+
+        assert_eq!(codes_count(program_id), 2);
+    });
+}
+
+#[test]
+fn execution_id_changes_on_wake() {
+    // Mails its execution id to USER_1, waiting for a block once.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_execution_id" (func $execution_id (result i64)))
+            (import "env" "gr_resumed_from_wait" (func $resumed_from_wait (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_wait_for" (func $wait_for (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            ;; wait for 1 block
+            (data (i32.const 0x40) "\01")
+            (func $init)
+            (func $handle
+                (i64.store (i32.const 0x100) (call $execution_id))
+                (if (call $send (i32.const 0x0) (i32.const 0x100) (i32.const 8)
+                        (i32.const 0x20) (i32.const 0x400) (i32.const 0x20))
+                    (then unreachable))
+                (if (i32.eqz (call $resumed_from_wait))
+                    (then (call $wait_for (i32.const 0x40))))
+            )
+        )"#;
+
+    let last_execution_id = || {
+        let mail = maybe_last_message(USER_1).expect("Mail should be sent");
+        u64::from_le_bytes(mail.payload().try_into().unwrap())
+    };
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+        let first = last_execution_id();
+
+        run_to_next_block(None);
+        run_to_next_block(None);
+        let second = last_execution_id();
+
+        assert_succeed(message_id);
+        // The message is the same, while its executions are told apart.
+        assert!(second > first);
+    });
+}
+
+#[test]
+fn trace_charged_per_byte() {
+    // Traces as many bytes of data as given in payload.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_trace" (func $trace (param i32 i32 i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (i32.const 4) (i32.const 0x10))
+                (call $trace (i32.const 5) (i32.const 0x100) (i32.load (i32.const 0x10)))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let burned_with = |len: u32| {
+            Gear::calculate_gas_info(
+                USER_1.into_origin(),
+                HandleKind::Handle(program_id),
+                len.to_le_bytes().to_vec(),
+                0,
+                true,
+            )
+            .expect("calculate_gas_info failed")
+            .burned
+        };
+
+        let per_byte = <Test as Config>::Schedule::get()
+            .host_fn_weights
+            .gr_trace_per_byte;
+        assert!(per_byte > 0);
+        assert!(burned_with(1024) - burned_with(0) >= per_byte * 1024);
+    });
+}
+
+#[test]
+fn trace_charged_regardless_of_log_level() {
+    // Traces 5 bytes of data at the address given in payload.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_trace" (func $trace (param i32 i32 i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (i32.const 4) (i32.const 0x10))
+                (call $trace (i32.const 4) (i32.load (i32.const 0x10)) (i32.const 5))
+            )
+            (data (i32.const 0x20) "trace")
+        )"#;
+
+    init_logger();
+
+    let max_level = log::max_level();
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let burned_with = |level: log::LevelFilter| {
+            log::set_max_level(level);
+
+            Gear::calculate_gas_info(
+                USER_1.into_origin(),
+                HandleKind::Handle(program_id),
+                0x20u32.to_le_bytes().to_vec(),
+                0,
+                true,
+            )
+            .expect("calculate_gas_info failed")
+            .burned
+        };
+
+        // Gas doesn't depend on whether the node logs the trace.
+        assert_eq!(
+            burned_with(log::LevelFilter::Off),
+            burned_with(log::LevelFilter::Trace)
+        );
+
+        // The data is read even if the trace isn't logged.
+        log::set_max_level(log::LevelFilter::Off);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            0xfffcu32.to_le_bytes().to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+
+        assert_eq!(dispatch_status(message_id), Some(DispatchStatus::Failed));
+    });
+
+    log::set_max_level(max_level);
+}
+
+#[test]
+fn pages_persisted_per_message_limited() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let max = <Test as Config>::Schedule::get()
+            .limits
+            .max_pages_persisted_per_message;
+
+        // Writes a byte into each of the given amount of gear pages,
+        // allocated right after the only static wasm page.
+        let upload = |pages: u32| {
+            let wasm_pages = (pages + 15) / 16;
+
+            let wat = format!(
+                r#"
+                (module
+                    (import "env" "memory" (memory 1))
+                    (import "env" "alloc" (func $alloc (param i32) (result i32)))
+                    (export "init" (func $init))
+                    (export "handle" (func $handle))
+                    (func $init)
+                    (func $handle
+                        (local $addr i32)
+                        (drop (call $alloc (i32.const {wasm_pages})))
+                        (local.set $addr (i32.const 0x10000))
+                        (loop
+                            (i32.store8 (local.get $addr) (i32.const 1))
+                            (local.set $addr (i32.add (local.get $addr) (i32.const 0x1000)))
+                            (br_if 0 (i32.lt_u
+                                (local.get $addr)
+                                (i32.add (i32.const 0x10000) (i32.mul (i32.const {pages}) (i32.const 0x1000)))))
+                        )
+                    )
+                )"#
+            );
+
+            assert_ok!(Gear::upload_program(
+                RuntimeOrigin::signed(USER_1),
+                ProgramCodeKind::Custom(&wat).to_bytes(),
+                pages.to_le_bytes().to_vec(),
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            let program_id = utils::get_last_program_id();
+
+            run_to_next_block(None);
+            assert!(Gear::is_initialized(program_id));
+
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            let message_id = get_last_message_id();
+
+            run_to_next_block(None);
+
+            let program = match common::get_program(program_id.into_origin())
+                .expect("program exists")
+            {
+                common::Program::Active(p) => p,
+                _ => unreachable!(),
+            };
+
+            let pages_data = common::get_program_pages_data(program_id.into_origin(), &program)
+                .expect("pages data exists");
+
+            (message_id, pages_data.len())
+        };
+
+        let (message_id, persisted) = upload(max);
+        assert_succeed(message_id);
+        assert_eq!(persisted, max as usize);
+
+        let (message_id, persisted) = upload(max + 1);
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::TooManyPageWrites {
+                dirtied: max + 1,
+                max,
+            },
+        );
+        assert_eq!(persisted, 0);
+    });
+}
+
+/// Check that too large message, which is constructed by `gr_reply_push`,
+/// leads to program execution error.
+#[test]
+fn check_reply_push_payload_exceed() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 0x100))
+            (import "env" "gr_reply_push" (func $gr (param i32 i32) (result i32)))
+            (export "init" (func $init))
+            (func $init
+                ;; first reply push must be ok
+                (block
+                    i32.const 0
+                    i32.const 0x1000000
+                    call $gr
+                    i32.eqz
+                    br_if 0
+                    unreachable
+                )
+                ;; second must lead to overflow
+                (block
+                    i32.const 0
+                    i32.const 0x1000000
+                    call $gr
+                    unreachable
+                )
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        )
+        .expect("Failed to upload program");
+
+        let message_id = get_last_message_id();
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Other(
+                ExtError::Message(MessageError::MaxMessageSizeExceed)
+                    .to_string()
+                    .into(),
+            )),
+        );
+    });
+}
+
+/// Check that a syscall failing after it has already charged part of the
+/// resources reports nonzero error length and stores the error for `gr_error`.
+#[test]
+fn error_len_reported_after_partial_charge() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_reply_wgas" (func $reply_wgas (param i32 i32 i64 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            ;; u128::MAX value which the program can't afford
+            (data (i32.const 0x10) "\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff")
+            (func $init
+                (local $len i32)
+                ;; gas limit is charged first, then value check fails
+                (local.set $len
+                    (call $reply_wgas (i32.const 0) (i32.const 0) (i64.const 5000)
+                        (i32.const 0x10) (i32.const 0x100) (i32.const 0x200)))
+                (if (i32.eqz (local.get $len))
+                    (then unreachable))
+                ;; reply with the stored error as payload
+                (call $error (i32.const 0x300))
+                (if (call $reply (i32.const 0x300) (local.get $len)
+                        (i32.const 0x40) (i32.const 0x100) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        let reply = maybe_last_message(USER_1).expect("reply with error expected");
+        let expected = ExtError::Message(MessageError::NotEnoughValue {
+            message_value: u128::MAX,
+            value_left: 0,
+        });
+        assert_eq!(reply.payload().len(), expected.encoded_size());
+        assert_eq!(
+            ExtError::decode(&mut reply.payload()).expect("valid encoded error"),
+            expected
+        );
+    });
+}
+
+#[test]
+fn send_charge_recipient_works() {
+    // Agrees to pay for a single message with 10_000_000 of gas.
+    let receiver_wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_set_charge_allowance" (func $set_charge_allowance (param i64)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init
+                (call $set_charge_allowance (i64.const 15000000))
+            )
+            (func $handle)
+        )"#;
+
+    // Sends recipient charged message to the destination from the payload.
+    let sender_wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_send_charge_recipient" (func $send (param i32 i32 i32 i64 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0))
+                (if (call $send (i32.const 0) (i32.const 0) (i32.const 0) (i64.const 10000000)
+                        (i32.const 0x40) (i32.const 0x80))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(receiver_wat)
+        ));
+        let receiver = get_last_program_id();
+
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(sender_wat)
+        ));
+        let sender = get_last_program_id();
+
+        run_to_block(2, None);
+
+        // Funding receiver, so it's able to pay for messages.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            receiver,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            1_000_000_000,
+        ));
+
+        run_to_block(3, None);
+
+        let receiver_account = AccountId::from_origin(receiver.into_origin());
+        let receiver_balance = Balances::free_balance(receiver_account);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            sender,
+            receiver.encode(),
+            10_000_000_000,
+            0,
+        ));
+
+        run_to_block(4, None);
+
+        // Sender's message and the recipient charged one.
+        assert_last_dequeued(2);
+        assert!(Balances::free_balance(receiver_account) < receiver_balance);
+        assert!(Balances::reserved_balance(receiver_account).is_zero());
+        assert_eq!(common::get_charge_allowance(receiver), 5_000_000);
+
+        let receiver_balance = Balances::free_balance(receiver_account);
+
+        // Allowance left doesn't cover the gas limit, so the recipient
+        // doesn't pay and the message isn't delivered.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            sender,
+            receiver.encode(),
+            10_000_000_000,
+            0,
+        ));
+
+        run_to_block(5, None);
+
+        // Sender's message and error reply to the sender.
+        assert_last_dequeued(2);
+        assert_eq!(Balances::free_balance(receiver_account), receiver_balance);
+        assert_eq!(common::get_charge_allowance(receiver), 5_000_000);
+
+        // User can't pay for the message, so it isn't delivered.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            sender,
+            ProgramId::from(USER_2).encode(),
+            10_000_000_000,
+            0,
+        ));
+
+        run_to_block(6, None);
+
+        // Sender's message and error reply to the sender.
+        assert_last_dequeued(2);
+        assert!(maybe_last_message(USER_2).is_none());
+    });
+}
+
+#[test]
+fn send_charge_recipient_sent_as_ordinary_dispatch() {
+    use crate::manager::ExtManager;
+    use core_processor::common::JournalHandler;
+    use gear_core::message::{Dispatch, DispatchKind, Message};
+
+    // Agrees to pay for messages with 15_000_000 of gas in total.
+    let receiver_wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_set_charge_allowance" (func $set_charge_allowance (param i64)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init
+                (call $set_charge_allowance (i64.const 15000000))
+            )
+            (func $handle)
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(receiver_wat)
+        ));
+        let receiver = get_last_program_id();
+
+        run_to_block(2, None);
+
+        // Funding receiver, so it's able to pay for messages.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            receiver,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            1_000_000_000,
+        ));
+
+        run_to_block(3, None);
+
+        let receiver_account = AccountId::from_origin(receiver.into_origin());
+        let source = ProgramId::from(USER_2);
+        let dispatch = |id, gas_limit| {
+            Dispatch::new(
+                DispatchKind::Handle,
+                Message::new(
+                    id,
+                    source,
+                    receiver,
+                    Default::default(),
+                    Some(gas_limit),
+                    0,
+                    None,
+                ),
+            )
+        };
+
+        // Hash of the transaction is withheld from the sender.
+        let parent = MessageId::generate_outgoing(get_last_message_id(), 0);
+        let user_account = AccountId::from_origin(USER_1.into_origin());
+        assert_ok!(GasHandlerOf::<Test>::create(
+            user_account,
+            parent,
+            1_000_000
+        ));
+        TxHashWithheldOf::<Test>::insert(parent, ());
+
+        let mut ext_manager = ExtManager::<Test>::default();
+
+        let sent = dispatch(MessageId::generate_outgoing(parent, 0), 10_000_000);
+        let sent_id = sent.id();
+        ext_manager.send_dispatch_charge_recipient(parent, sent, None);
+
+        // Gas is bought by the recipient, but otherwise the message
+        // is sent the same way as ordinary ones.
+        assert_eq!(common::get_charge_allowance(receiver), 5_000_000);
+        assert_ok!(GasHandlerOf::<Test>::get_limit(sent_id), 10_000_000);
+        assert_ok!(
+            GasHandlerOf::<Test>::get_external(sent_id),
+            receiver_account
+        );
+        assert!(TxHashWithheldOf::<Test>::contains_key(sent_id));
+
+        let delayed = dispatch(MessageId::generate_outgoing(parent, 1), 5_000_000);
+        let delayed_id = delayed.id();
+        ext_manager.send_dispatch_charge_recipient(parent, delayed, 1.try_into().ok());
+
+        // Delayed message is scheduled and remembered for the source.
+        assert_eq!(common::get_charge_allowance(receiver), 0);
+        assert_ok!(GasHandlerOf::<Test>::get_limit(delayed_id), 5_000_000);
+        assert_eq!(common::get_delayed_dispatch(source, delayed_id), Some(4));
+    });
+}
+
+#[test]
+fn delayed_eta_works() {
+    // Sends delayed message to the source if payload is empty,
+    // otherwise reads its id and expected eta from the payload.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_delayed_eta" (func $delayed_eta (param i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (if (call $size)
+                    (then
+                        (call $read (i32.const 0) (i32.const 36) (i32.const 0x40))
+                    )
+                    (else
+                        (call $source (i32.const 0))
+                        (i32.store (i32.const 0x60) (i32.const 10))
+                        (if (call $send (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0x20)
+                                (i32.const 0x40) (i32.const 0x60))
+                            (then unreachable))
+                    )
+                )
+                (if (i32.ne (call $delayed_eta (i32.const 0x40)) (i32.load (i32.const 0x60)))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+
+        assert_succeed(message_id);
+
+        let delayed_id = MessageId::generate_outgoing(message_id, 0);
+        assert_eq!(
+            common::get_delayed_dispatch(program_id, delayed_id),
+            Some(13)
+        );
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            (delayed_id, 9u32).encode(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(4, None);
+
+        assert_succeed(message_id);
+
+        run_to_block(13, None);
+
+        // Delayed message was dispatched, so it's unknown for the program.
+        assert!(common::get_delayed_dispatch(program_id, delayed_id).is_none());
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            (delayed_id, 0u32).encode(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Message(
+                MessageError::DelayedNotFound,
+            ))),
+        );
+    });
+}
+
+#[test]
+fn exit_forgets_delayed_dispatches() {
+    // Sends delayed message to the source if payload is empty,
+    // otherwise exits.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_exit" (func $exit (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $source (i32.const 0))
+                (if (call $size)
+                    (then
+                        (call $exit (i32.const 0))
+                    )
+                )
+                (i32.store (i32.const 0x60) (i32.const 10))
+                (if (call $send (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 0x20)
+                        (i32.const 0x40) (i32.const 0x60))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            10_000_000_000,
+            0,
+        ));
+        let delayed_id = MessageId::generate_outgoing(get_last_message_id(), 0);
+
+        run_to_block(3, None);
+
+        assert!(common::get_delayed_dispatch(program_id, delayed_id).is_some());
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            b"exit".to_vec(),
+            10_000_000_000,
+            0,
+        ));
+
+        run_to_block(4, None);
+
+        assert!(!Gear::is_active(program_id));
+        assert!(common::get_delayed_dispatch(program_id, delayed_id).is_none());
+    });
+}
+
+/// Check numeric syscall outputs are little-endian bytes, which is the layout
+/// `gcore` decodes them with.
+#[test]
+fn numeric_outputs_are_little_endian() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_value" (func $value (param i32)))
+            (import "env" "gr_value_available" (func $value_available (param i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (func $init
+                (call $value (i32.const 0x10))
+                (call $value_available (i32.const 0x20))
+                ;; reply with both values as payload
+                (if (call $reply (i32.const 0x10) (i32.const 32)
+                        (i32.const 0x40) (i32.const 0x100) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    let value: u128 = 0x0102_0304;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            value,
+        ));
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        // Program has no balance before init, so all available value is the message one.
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        assert_eq!(
+            reply.payload(),
+            [value.to_le_bytes(), value.to_le_bytes()].concat()
+        );
+    });
+}
+
+/// Check that `gr_children_count` counts programs created by the program
+/// over all its executions, including ones failed to be initialized.
+#[test]
+fn children_count_accumulated() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_msg_id" (func $msg_id (param i32)))
+            (import "env" "gr_create_program"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_children_count" (func $children_count (result i64)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                ;; code id of the child is the payload
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x100))
+                ;; message id is the salt
+                (call $msg_id (i32.const 0x200))
+                (if (call $create (i32.const 0x100) (i32.const 0x200) (i32.const 32)
+                        (i32.const 0) (i32.const 0) (i32.const 0x300) (i32.const 0x400)
+                        (i32.const 0x500))
+                    (then unreachable))
+                (i64.store (i32.const 0x600) (call $children_count))
+                (if (call $reply (i32.const 0x600) (i32.const 8)
+                        (i32.const 0x300) (i32.const 0x700) (i32.const 0x500))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let child_code = ProgramCodeKind::Default.to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+        assert_eq!(common::get_children_count(program_id), 0);
+
+        // The last child's code doesn't exist, so it's never initialized.
+        let code_ids = [child_code_id, child_code_id, CodeId::from(u64::MAX)];
+
+        for (expected, code_id) in (1u64..).zip(code_ids) {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                code_id.encode(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            run_to_next_block(None);
+
+            let reply = maybe_last_message(USER_1).expect("reply expected");
+            assert_eq!(reply.payload(), expected.to_le_bytes());
+            assert_eq!(common::get_children_count(program_id), expected);
+        }
+    });
+}
+
+/// Check that `gr_children_gas_burned` accumulates gas burned by
+/// initializations of the programs created by the program.
+#[test]
+fn children_gas_burned_accumulated() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_msg_id" (func $msg_id (param i32)))
+            (import "env" "gr_create_program"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_children_gas_burned" (func $children_gas_burned (result i64)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                ;; empty payload requests gas burned by children
+                (if (i32.eqz (call $size))
+                    (then
+                        (i64.store (i32.const 0x600) (call $children_gas_burned))
+                        (if (call $reply (i32.const 0x600) (i32.const 8)
+                                (i32.const 0x300) (i32.const 0x700) (i32.const 0x500))
+                            (then unreachable))
+                        return))
+                ;; otherwise code id of the child is the payload
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x100))
+                ;; message id is the salt
+                (call $msg_id (i32.const 0x200))
+                (if (call $create (i32.const 0x100) (i32.const 0x200) (i32.const 32)
+                        (i32.const 0) (i32.const 0) (i32.const 0x300) (i32.const 0x400)
+                        (i32.const 0x500))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let child_code = ProgramCodeKind::Default.to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        let children_gas_burned = || {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            run_to_next_block(None);
+
+            let reply = maybe_last_message(USER_1).expect("reply expected");
+            let gas_burned = u64::from_le_bytes(reply.payload().try_into().unwrap());
+            assert_eq!(common::get_children_gas_burned(program_id), gas_burned);
+
+            gas_burned
+        };
+
+        assert_eq!(children_gas_burned(), 0);
+
+        let mut previous = 0;
+
+        for _ in 0..2 {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                child_code_id.encode(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            // The child is initialized within the same block.
+            run_to_next_block(None);
+
+            let gas_burned = children_gas_burned();
+            assert!(gas_burned > previous);
+            previous = gas_burned;
+        }
+
+        // Gas burned by initializations of programs created by users
+        // isn't accounted anywhere.
+        assert_eq!(common::get_children_gas_burned(USER_1.into()), 0);
+    });
+}
+
+/// Check that zero-value program creation follows the existential deposit
+/// policy and the child is able to manage its value afterwards.
+#[test]
+fn zero_value_creation_follows_existential_deposit_policy() {
+    use crate::{mock::ProgramExistentialDeposit, ExistentialDepositPolicy, ProgramProviders};
+
+    // Creates the child with zero value from the code id in the payload,
+    // replies with its id or with the error.
+    let factory = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_msg_id" (func $msg_id (param i32)))
+            (import "env" "gr_create_program"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (local $len i32)
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x100))
+                (call $msg_id (i32.const 0x200))
+                (local.set $len
+                    (call $create (i32.const 0x100) (i32.const 0x200) (i32.const 32)
+                        (i32.const 0) (i32.const 0) (i32.const 0x300) (i32.const 0x400)
+                        (i32.const 0x500)))
+                (if (i32.eqz (local.get $len))
+                    (then
+                        (if (call $reply (i32.const 0x400) (i32.const 32)
+                                (i32.const 0x300) (i32.const 0x600) (i32.const 0x500))
+                            (then unreachable)))
+                    (else
+                        (call $error (i32.const 0x700))
+                        (if (call $reply (i32.const 0x700) (local.get $len)
+                                (i32.const 0x300) (i32.const 0x600) (i32.const 0x500))
+                            (then unreachable))))
+            )
+        )"#;
+
+    // Sends value from the payload to the source, replies with the error if any.
+    let child = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_source" (func $source (param i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (local $len i32)
+                (call $read (i32.const 0) (i32.const 16) (i32.const 0x100))
+                (call $source (i32.const 0x200))
+                (local.set $len
+                    (call $send (i32.const 0x200) (i32.const 0) (i32.const 0)
+                        (i32.const 0x100) (i32.const 0x300) (i32.const 0x400)))
+                (if (local.get $len)
+                    (then (call $error (i32.const 0x500))))
+                (if (call $reply (i32.const 0x500) (local.get $len)
+                        (i32.const 0x400) (i32.const 0x600) (i32.const 0x400))
+                    (then unreachable))
+            )
+        )"#;
+
+    let existential_deposit = || -> u128 {
+        <Test as pallet::Config>::Currency::minimum_balance().unique_saturated_into()
+    };
+    let balance_of =
+        |id: ProgramId| Balances::free_balance(AccountId::from_origin(id.into_origin()));
+
+    // Uploads the child code and the factory, returns their ids.
+    let prepare = || {
+        let child_code = ProgramCodeKind::Custom(child).to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(factory)
+        ));
+        let factory_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(factory_id));
+
+        (factory_id, child_code_id)
+    };
+
+    let send = |destination: ProgramId, payload: Vec<u8>, value: u128| {
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            destination,
+            payload,
+            DEFAULT_GAS_LIMIT * 100,
+            value,
+        ));
+        run_to_next_block(None);
+
+        maybe_last_message(USER_1)
+            .expect("reply expected")
+            .payload()
+            .to_vec()
+    };
+    let error =
+        |payload: Vec<u8>| ExtError::decode(&mut payload.as_ref()).expect("valid encoded error");
+
+    init_logger();
+
+    // The child is kept by the provider reference without any value.
+    ProgramExistentialDeposit::set(ExistentialDepositPolicy::Exempt);
+    new_test_ext().execute_with(|| {
+        let (factory_id, child_code_id) = prepare();
+
+        let child_id = ProgramId::decode(&mut send(factory_id, child_code_id.encode(), 0).as_ref())
+            .expect("child id expected");
+        assert!(Gear::is_initialized(child_id));
+        assert_eq!(balance_of(child_id), 0);
+        assert_eq!(
+            System::providers(&AccountId::from_origin(child_id.into_origin())),
+            1
+        );
+        assert!(ProgramProviders::<Test>::contains_key(child_id));
+    });
+
+    // The child has to be topped up by the factory.
+    ProgramExistentialDeposit::set(ExistentialDepositPolicy::TopUp);
+    new_test_ext().execute_with(|| {
+        let existential_deposit = existential_deposit();
+        let (factory_id, child_code_id) = prepare();
+
+        // The factory has no value.
+        assert_eq!(
+            error(send(factory_id, child_code_id.encode(), 0)),
+            ExtError::Message(MessageError::NotEnoughValueForExistentialDeposit {
+                existential_deposit,
+                value_left: 0,
+            })
+        );
+
+        // Value of the message is enough to top up the child.
+        let child_id = ProgramId::decode(
+            &mut send(factory_id, child_code_id.encode(), existential_deposit).as_ref(),
+        )
+        .expect("child id expected");
+        assert!(Gear::is_initialized(child_id));
+        assert_eq!(balance_of(child_id), existential_deposit as u64);
+        assert_eq!(balance_of(factory_id), 0);
+        assert!(!ProgramProviders::<Test>::contains_key(child_id));
+
+        // Sending value below existential deposit fails explicitly,
+        // the child keeps its value.
+        assert_eq!(
+            error(send(child_id, 1u128.encode(), 0)),
+            ExtError::Message(MessageError::InsufficientValue {
+                message_value: 1,
+                existential_deposit,
+            })
+        );
+        assert_eq!(balance_of(child_id), existential_deposit as u64);
+
+        // The whole value is sent out.
+        assert!(send(child_id, existential_deposit.encode(), 0).is_empty());
+        assert_eq!(balance_of(child_id), 0);
+    });
+    ProgramExistentialDeposit::set(Default::default());
+}
+
+/// Check that creating a program with the code and salt of an existing one
+/// fails in the syscall with the id of the existing program, so no init
+/// message is sent.
+#[test]
+fn create_program_collision_reports_existing_id() {
+    // Creates the child from the code id in the payload with the constant salt,
+    // replies with its id or with the error.
+    let factory = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_create_program"
+                (func $create (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (local $len i32)
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x100))
+                (local.set $len
+                    (call $create (i32.const 0x100) (i32.const 0x800) (i32.const 4)
+                        (i32.const 0) (i32.const 0) (i32.const 0x300) (i32.const 0x400)
+                        (i32.const 0x500)))
+                (if (i32.eqz (local.get $len))
+                    (then
+                        (if (call $reply (i32.const 0x400) (i32.const 32)
+                                (i32.const 0x300) (i32.const 0x600) (i32.const 0x500))
+                            (then unreachable)))
+                    (else
+                        (call $error (i32.const 0x700))
+                        (if (call $reply (i32.const 0x700) (local.get $len)
+                                (i32.const 0x300) (i32.const 0x600) (i32.const 0x500))
+                            (then unreachable))))
+            )
+            (data (i32.const 0x800) "salt")
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let child_code = ProgramCodeKind::Default.to_bytes();
+        let child_code_id = CodeId::generate(&child_code);
+        let child_id = ProgramId::generate(child_code_id, b"salt");
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), child_code));
+
+        assert_ok!(upload_program_default(
+            USER_1,
+            ProgramCodeKind::Custom(factory)
+        ));
+        let factory_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(factory_id));
+
+        let send = || {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                factory_id,
+                child_code_id.encode(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+            run_to_next_block(None);
+
+            maybe_last_message(USER_1)
+                .expect("reply expected")
+                .payload()
+                .to_vec()
+        };
+
+        // The child is created and initialized.
+        assert_eq!(send(), child_id.encode());
+        assert_last_dequeued(2);
+        assert!(Gear::is_initialized(child_id));
+
+        // The collision is reported without sending the init message.
+        let error = ExtError::decode(&mut send().as_ref()).expect("valid encoded error");
+        assert_eq!(
+            error,
+            ExtError::Message(MessageError::ProgramAlreadyExists {
+                id: child_id.into()
+            })
+        );
+        assert_last_dequeued(1);
+    });
+}
+
+/// Check that waiting fails if the stored context exceeds its limits,
+/// while the context fitting them is kept intact until the message is woken.
+#[test]
+fn wait_context_limits() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 2))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_send_init" (func $send_init (param i32) (result i32)))
+            (import "env" "gr_send_push" (func $send_push (param i32 i32 i32) (result i32)))
+            (import "env" "gr_send_commit" (func $send_commit (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_wait_for" (func $wait_for (param i32)))
+            (export "init" (func $init))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            ;; wait for 1 block
+            (data (i32.const 0x40) "\01")
+            (data (i32.const 0x100) "ab")
+            (func $init
+                (local $i i32)
+                ;; woken: the stored payload is sent
+                (if (i32.load (i32.const 0x200))
+                    (then
+                        (if (call $send_commit (i32.load (i32.const 0x300)) (i32.const 0x400)
+                                (i32.const 0) (i32.const 0x20) (i32.const 0x30))
+                            (then unreachable))
+                        return))
+                (i32.store (i32.const 0x200) (i32.const 1))
+                (if (call $send_init (i32.const 0x300))
+                    (then unreachable))
+                (if (call $send_push (i32.load (i32.const 0x300)) (i32.const 0x100) (i32.const 2))
+                    (then unreachable))
+                ;; payload of size 1: exceed limit of stored handles
+                (if (i32.eq (call $size) (i32.const 1))
+                    (then
+                        (loop $handles
+                            (if (call $send_init (i32.const 0x310))
+                                (then unreachable))
+                            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                            (br_if $handles (i32.lt_u (local.get $i) (i32.const 256))))))
+                ;; payload of size 2: exceed limit of stored payload bytes
+                (if (i32.eq (call $size) (i32.const 2))
+                    (then
+                        (if (call $send_push (i32.load (i32.const 0x300)) (i32.const 0)
+                                (i32.const 0x10000))
+                            (then unreachable))))
+                (call $wait_for (i32.const 0x40))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        for payload in [vec![1], vec![1, 2]] {
+            assert_ok!(Gear::upload_program(
+                RuntimeOrigin::signed(USER_1),
+                ProgramCodeKind::Custom(wat).to_bytes(),
+                payload.clone(),
+                payload,
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            let message_id = get_last_message_id();
+
+            run_to_next_block(None);
+            assert_failed(
+                message_id,
+                ExecutionErrorReason::Ext(TrapExplanation::Core(ExtError::Wait(
+                    WaitError::ContextTooLarge,
+                ))),
+            );
+        }
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(!Gear::is_initialized(program_id));
+
+        run_to_next_block(None);
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.payload(), b"ab");
+    });
+}
+
+// Sends "ping" to USER_1 waiting for the reply, and sends "done" once woken.
+const SEND_AND_WAIT_WAT: &str = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_send_and_wait" (func $send_and_wait (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (data (i32.const 0x100) "ping")
+            (data (i32.const 0x110) "done")
+            (func $init)
+            (func $handle
+                ;; woken by the reply
+                (if (i32.load (i32.const 0x200))
+                    (then
+                        (if (call $send (i32.const 0x0) (i32.const 0x110) (i32.const 4)
+                                (i32.const 0x20) (i32.const 0x400) (i32.const 0x20))
+                            (then unreachable))
+                        return))
+                (i32.store (i32.const 0x200) (i32.const 1))
+                (drop (call $send_and_wait (i32.const 0x0) (i32.const 0x100) (i32.const 4)
+                    (i32.const 0x20) (i32.const 0x340)))
+                unreachable
+            )
+        )"#;
+
+#[test]
+fn send_and_wait_woken_by_reply() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(SEND_AND_WAIT_WAT).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let handle_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &handle_id));
+
+        let sent = get_last_mail(USER_1);
+        assert_eq!(sent.payload(), b"ping");
+        assert_eq!(
+            common::get_reply_waker(program_id, sent.id()),
+            Some(handle_id)
+        );
+
+        assert_ok!(Gear::send_reply(
+            RuntimeOrigin::signed(USER_1),
+            sent.id(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+        run_to_next_block(None);
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &handle_id));
+        assert_eq!(common::get_reply_waker(program_id, sent.id()), None);
+        assert_eq!(get_last_mail(USER_1).payload(), b"done");
+    });
+}
+
+#[test]
+fn reply_waker_removed_with_waitlisted_message() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(SEND_AND_WAIT_WAT).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let handle_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &handle_id));
+
+        let sent = get_last_mail(USER_1);
+        assert_eq!(
+            common::get_reply_waker(program_id, sent.id()),
+            Some(handle_id)
+        );
+
+        // The message is removed from the waitlist once its rent is over.
+        System::set_block_number(get_waitlist_expiration(handle_id) - 1);
+        run_to_next_block(None);
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &handle_id));
+        assert_eq!(common::get_reply_waker(program_id, sent.id()), None);
+
+        // The late reply wakes nothing.
+        assert_ok!(Gear::send_reply(
+            RuntimeOrigin::signed(USER_1),
+            sent.id(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+        run_to_next_block(None);
+        assert_ne!(
+            maybe_last_message(USER_1).map(|message| message.payload().to_vec()),
+            Some(b"done".to_vec())
+        );
+    });
+}
+
+#[test]
+fn reply_waker_read_charged() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(SEND_AND_WAIT_WAT).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let handle_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &handle_id));
+
+        let sent = get_last_mail(USER_1);
+
+        // The reply can't pay for reading the message waiting for it.
+        let reply_waker_cost = mock::DbWeight::get().writes(4).ref_time();
+        assert_ok!(Gear::send_reply(
+            RuntimeOrigin::signed(USER_1),
+            sent.id(),
+            EMPTY_PAYLOAD.to_vec(),
+            reply_waker_cost - 1,
+            0,
+        ));
+
+        let reply_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert_failed(reply_id, ExecutionErrorReason::ReplyWakerReadGasExceeded);
+
+        // The waiting message is left untouched.
+        assert!(WaitlistOf::<Test>::contains(&program_id, &handle_id));
+        assert_eq!(
+            common::get_reply_waker(program_id, sent.id()),
+            Some(handle_id)
+        );
+    });
+}
+
+#[test]
+fn reply_budget_exceeded_replaced_with_error() {
+    // Sets the budget of `handle_reply` in init, so the reply burning all
+    // its gas is dropped and the flow waiting for it is woken with the error.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_exit_code" (func $exit_code (result i32)))
+            (import "env" "gr_msg_id" (func $msg_id (param i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_send_and_wait" (func $send_and_wait (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_set_entry_budget" (func $set_entry_budget (param i32 i64) (result i32)))
+            (import "env" "gr_wake" (func $wake (param i32 i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_reply" (func $handle_reply))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (data (i32.const 0x100) "ping")
+            (func $init
+                (if (call $set_entry_budget (i32.const 2) (i64.const 1000000000))
+                    (then unreachable))
+            )
+            (func $handle
+                ;; woken by the reply: its exit code is sent
+                (if (i32.load (i32.const 0x200))
+                    (then
+                        (if (call $send (i32.const 0x0) (i32.const 0x120) (i32.const 4)
+                                (i32.const 0x20) (i32.const 0x400) (i32.const 0x20))
+                            (then unreachable))
+                        return))
+                (i32.store (i32.const 0x200) (i32.const 1))
+                (call $msg_id (i32.const 0x300))
+                (drop (call $send_and_wait (i32.const 0x0) (i32.const 0x100) (i32.const 4)
+                    (i32.const 0x20) (i32.const 0x340)))
+                unreachable
+            )
+            (func $handle_reply
+                ;; handling of a successful reply never ends
+                (if (i32.eqz (call $exit_code))
+                    (then (loop $burn (br $burn))))
+                (i32.store (i32.const 0x120) (call $exit_code))
+                (call $wake (i32.const 0x300) (i32.const 0x20))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let handle_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &handle_id));
+
+        let sent = get_last_mail(USER_1);
+        assert_eq!(sent.payload(), b"ping");
+
+        // The gas of the reply is far beyond the budget of `handle_reply`.
+        assert_ok!(Gear::send_reply(
+            RuntimeOrigin::signed(USER_1),
+            sent.id(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 500,
+            0,
+        ));
+
+        let reply_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert_eq!(dispatch_status(reply_id), Some(DispatchStatus::Failed));
+
+        run_to_next_block(None);
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &handle_id));
+        assert!(Gear::is_active(program_id));
+
+        let code = get_last_mail(USER_1).payload().to_vec();
+        assert_eq!(
+            code,
+            core_processor::ENTRY_BUDGET_EXCEEDED_EXIT_CODE.to_le_bytes()
+        );
+    });
+}
+
+#[test]
+fn counters_bumped_transactionally() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_bump_counter" (func $bump_counter (param i32) (result i64)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (data (i32.const 0x40) "orders\00\00")
+            (func $init)
+            (func $handle
+                (i64.store (i32.const 0x100) (call $bump_counter (i32.const 0x40)))
+                ;; non-empty payload: bumped counter is rolled back
+                (if (call $size)
+                    (then unreachable))
+                (if (call $send (i32.const 0x0) (i32.const 0x100) (i32.const 8)
+                        (i32.const 0x20) (i32.const 0x200) (i32.const 0x20))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        let send = |payload: Vec<u8>| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                payload,
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            let message_id = get_last_message_id();
+            run_to_next_block(None);
+            message_id
+        };
+
+        send(EMPTY_PAYLOAD.to_vec());
+        assert_eq!(get_last_mail(USER_1).payload(), 1u64.to_le_bytes());
+
+        let message_id = send(vec![1]);
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Unknown),
+        );
+        assert_eq!(
+            common::get_counters(program_id).get(b"orders\0\0"),
+            Some(&1)
+        );
+
+        send(EMPTY_PAYLOAD.to_vec());
+        assert_eq!(get_last_mail(USER_1).payload(), 2u64.to_le_bytes());
+        assert_eq!(
+            common::get_counters(program_id).get(b"orders\0\0"),
+            Some(&2)
+        );
+    });
+}
+
+#[test]
+fn is_init_in_init_only() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_is_init" (func $is_init (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $dispatch))
+            (export "handle" (func $dispatch))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (func $dispatch
+                (i32.store (i32.const 0x100) (call $is_init))
+                (if (call $send (i32.const 0x0) (i32.const 0x100) (i32.const 4)
+                        (i32.const 0x20) (i32.const 0x200) (i32.const 0x20))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+        assert_eq!(get_last_mail(USER_1).payload(), 1u32.to_le_bytes());
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        run_to_next_block(None);
+        assert_eq!(get_last_mail(USER_1).payload(), 0u32.to_le_bytes());
+    });
+}
+
+#[test]
+fn signal_details_after_removal_from_waitlist() {
+    use gear_core::message::SignalCode;
+
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_signal_details" (func $signal_details (param i32 i32) (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_wait_for" (func $wait_for (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_signal" (func $handle_signal))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            ;; wait for 5 blocks
+            (data (i32.const 0x40) "\05")
+            (func $init)
+            (func $handle
+                ;; details are available in signal entry point only
+                (if (i32.eqz (call $signal_details (i32.const 0x100) (i32.const 0x120)))
+                    (then unreachable))
+                (call $wait_for (i32.const 0x40))
+            )
+            (func $handle_signal
+                (if (call $signal_details (i32.const 0x100) (i32.const 0x120))
+                    (then unreachable))
+                ;; send message id and code
+                (if (call $send (i32.const 0x0) (i32.const 0x100) (i32.const 36)
+                        (i32.const 0x20) (i32.const 0x200) (i32.const 0x20))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &message_id));
+
+        // Message stays in waitlist until the waiting duration expires.
+        let expiration = get_waitlist_expiration(message_id);
+        System::set_block_number(expiration - 2);
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &message_id));
+
+        run_to_next_block(None);
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &message_id));
+
+        let details = [
+            message_id.as_ref(),
+            &SignalCode::RemovedFromWaitlist.to_u32().to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(get_last_mail(USER_1).payload(), details);
+    });
+}
+
+#[test]
+fn signal_after_execution_failed_after_waiting() {
+    use gear_core::message::SignalCode;
+
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_signal_details" (func $signal_details (param i32 i32) (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_send_and_wait" (func $send_and_wait (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_signal" (func $handle_signal))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (data (i32.const 0x100) "ping")
+            (func $init)
+            (func $handle
+                ;; traps once woken by the reply
+                (if (i32.load (i32.const 0x200))
+                    (then unreachable))
+                (i32.store (i32.const 0x200) (i32.const 1))
+                (drop (call $send_and_wait (i32.const 0x0) (i32.const 0x100) (i32.const 4)
+                    (i32.const 0x20) (i32.const 0x340)))
+                unreachable
+            )
+            (func $handle_signal
+                (if (call $signal_details (i32.const 0x300) (i32.const 0x320))
+                    (then unreachable))
+                ;; send message id and code
+                (if (call $send (i32.const 0x0) (i32.const 0x300) (i32.const 36)
+                        (i32.const 0x20) (i32.const 0x400) (i32.const 0x20))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            1_000,
+        ));
+
+        let handle_id = get_last_message_id();
+
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &handle_id));
+
+        // Value is transferred to the program once the message starts waiting.
+        assert_balance(program_id, 1_000u128, 0u128);
+
+        let sent = get_last_mail(USER_1);
+        assert_ok!(Gear::send_reply(
+            RuntimeOrigin::signed(USER_1),
+            sent.id(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            0,
+        ));
+
+        System::reset_events();
+
+        run_to_next_block(None);
+        run_to_next_block(None);
+        run_to_next_block(None);
+
+        // Resumed execution traps, so the program is signaled about it.
+        assert_failed(
+            handle_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Unknown),
+        );
+
+        let details = [
+            handle_id.as_ref(),
+            &SignalCode::ExecutionFailed.to_u32().to_le_bytes(),
+        ]
+        .concat();
+        assert!(System::events().into_iter().any(|e| matches!(
+            e.event,
+            MockRuntimeEvent::Gear(Event::UserMessageSent { message, .. })
+                if message.payload() == details
+        )));
+    });
+}
+
+#[test]
+fn wait_budget_exhausted_at_block_boundary() {
+    use crate::{manager::ExtManager, QueueOf};
+    use gear_core::message::{SignalCode, SignalMessage};
+
+    // Replies with the wait budget for non-empty payload,
+    // waits for as long as the budget allows otherwise.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_wait_budget_remaining" (func $wait_budget_remaining (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_wait" (func $wait))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_signal" (func $handle_signal))
+            (func $init)
+            (func $handle
+                (if (call $size)
+                    (then
+                        (i32.store (i32.const 0x20) (call $wait_budget_remaining))
+                        (if (call $reply (i32.const 0x20) (i32.const 4)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable)))
+                    (else (call $wait)))
+            )
+            (func $handle_signal)
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let cost = CostsPerBlockOf::<Test>::waitlist();
+        let reserve_for = CostsPerBlockOf::<Test>::reserve_for();
+
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        let send = |payload: Vec<u8>, gas_limit: u64| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                payload,
+                gas_limit,
+                0,
+            ));
+            get_last_message_id()
+        };
+
+        // Every extra `cost` of gas buys one more block of waiting.
+        let budget = |gas_limit: u64| {
+            send(vec![1], gas_limit);
+            run_to_next_block(None);
+
+            let reply = maybe_last_message(USER_1).expect("reply expected");
+            u32::from_le_bytes(reply.payload().try_into().expect("4 bytes expected"))
+        };
+        let gas_limit = 10_000_000_000;
+        // Warming up, so the same memory pages are loaded in both runs.
+        budget(gas_limit);
+        assert_eq!(budget(gas_limit + 1_000 * cost) - budget(gas_limit), 1_000);
+
+        let message_id = send(EMPTY_PAYLOAD.to_vec(), gas_limit);
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &message_id));
+
+        // The message waits exactly as long as the gas left pays for,
+        // keeping `reserve_for` blocks as a safety margin.
+        let waited_at = System::block_number();
+        let gas_left = GasHandlerOf::<Test>::get_limit(message_id).unwrap();
+        let expiration = get_waitlist_expiration(message_id);
+        assert_eq!(expiration, waited_at + gas_left / cost - reserve_for);
+
+        System::set_block_number(expiration - 2);
+        run_to_next_block(None);
+        assert!(WaitlistOf::<Test>::contains(&program_id, &message_id));
+
+        // Processing the removal task only to catch the signal in the queue.
+        System::set_block_number(expiration);
+        GasAllowanceOf::<Test>::put(BlockGasLimitOf::<Test>::get());
+        Gear::process_tasks(&mut ExtManager::<Test>::default());
+        assert!(!WaitlistOf::<Test>::contains(&program_id, &message_id));
+
+        let signal_id = SignalMessage::with_code(message_id, SignalCode::OutOfGasInWaitlist).id();
+        assert!(QueueOf::<Test>::iter()
+            .filter_map(Result::ok)
+            .any(|dispatch| dispatch.id() == signal_id));
+
+        let trap_reply = maybe_last_message(USER_1).expect("trap reply expected");
+        assert_eq!(trap_reply.reply_to(), Some(message_id));
+        assert_eq!(
+            trap_reply.payload(),
+            ExecutionErrorReason::OutOfRent.to_string().as_bytes()
+        );
+    });
+}
+
+#[test]
+fn forward_sends_current_payload() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_forward" (func $forward (param i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            (func $init)
+            (func $handle
+                (if (call $forward (i32.const 0x0) (i32.const 0x20) (i32.const 0x40) (i32.const 0x60))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let payload = b"forwarded payload".to_vec();
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            payload.clone(),
+            10_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_block(3, None);
+
+        assert_succeed(message_id);
+
+        // Forwarded message is sent by the program and isn't a reply.
+        let mail = get_last_mail(USER_1);
+        assert_eq!(mail.source(), program_id);
+        assert_eq!(mail.payload(), payload);
+        assert!(mail.reply().is_none());
+    });
+}
+
+#[test]
+fn block_hash_of_recent_blocks() {
+    use sp_core::H256;
+
+    // Replies with hash of the block at height from the payload,
+    // or with the error if the hash is unavailable.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_block_hash" (func $block_hash (param i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (local $len i32)
+                (call $read (i32.const 0) (i32.const 4) (i32.const 0))
+                (local.set $len
+                    (call $block_hash (i32.load (i32.const 0)) (i32.const 0x20)))
+                (if (i32.eqz (local.get $len))
+                    (then
+                        (if (call $reply (i32.const 0x20) (i32.const 32)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable)))
+                    (else
+                        (call $error (i32.const 0x300))
+                        (if (call $reply (i32.const 0x300) (local.get $len)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable))))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let depth = <Test as Config>::Schedule::get().limits.block_hash_depth;
+
+        // Block hashes aren't stored by the mock, so filling them manually.
+        let hash_of = |height: u32| H256::from_low_u64_be(height as u64 + 1);
+        for height in 0..depth * 2 {
+            frame_system::BlockHash::<Test>::insert(height as u64, hash_of(height));
+        }
+
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        System::set_block_number(depth as u64 + 10);
+
+        // Queries the hash in the next block.
+        let query = |height: u32| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                height.encode(),
+                50_000_000_000,
+                0,
+            ));
+            run_to_next_block(None);
+
+            maybe_last_message(USER_1)
+                .expect("reply expected")
+                .payload()
+                .to_vec()
+        };
+        let next_block = || System::block_number() as u32 + 1;
+        let error = |payload: Vec<u8>| {
+            ExtError::decode(&mut payload.as_ref()).expect("valid encoded error")
+        };
+
+        let height = next_block() - 1;
+        assert_eq!(query(height), hash_of(height).as_bytes());
+
+        let height = next_block() - depth;
+        assert_eq!(query(height), hash_of(height).as_bytes());
+
+        assert_eq!(
+            error(query(next_block() - depth - 1)),
+            ExtError::Execution(ExecutionError::BlockHashTooOld)
+        );
+
+        assert_eq!(
+            error(query(next_block())),
+            ExtError::Execution(ExecutionError::BlockHashUnknown)
+        );
+
+        assert_eq!(
+            error(query(next_block() + 10)),
+            ExtError::Execution(ExecutionError::BlockHashUnknown)
+        );
+    });
+}
+
+#[test]
+fn tx_hash_of_originating_transaction() {
+    use sp_core::storage::well_known_keys;
+
+    // Replies with hash of the originating transaction,
+    // or with the error if the hash is unavailable.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_tx_hash" (func $tx_hash (param i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (local $len i32)
+                (local.set $len (call $tx_hash (i32.const 0x20)))
+                (if (i32.eqz (local.get $len))
+                    (then
+                        (if (call $reply (i32.const 0x20) (i32.const 32)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable)))
+                    (else
+                        (call $error (i32.const 0x300))
+                        (if (call $reply (i32.const 0x300) (local.get $len)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable))))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        let send = || {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                EMPTY_PAYLOAD.to_vec(),
+                50_000_000_000,
+                0,
+            ));
+            get_last_message_id()
+        };
+        let reply = || {
+            run_to_next_block(None);
+
+            maybe_last_message(USER_1)
+                .expect("reply expected")
+                .payload()
+                .to_vec()
+        };
+
+        // The mock doesn't apply extrinsics, so there is no transaction.
+        send();
+        assert_eq!(
+            ExtError::decode(&mut reply().as_ref()).expect("valid encoded error"),
+            ExtError::Execution(ExecutionError::NoTxHash)
+        );
+
+        // Emulating application of the extrinsic.
+        let extrinsic = b"extrinsic".to_vec();
+        sp_io::storage::set(well_known_keys::EXTRINSIC_INDEX, &0u32.encode());
+        System::note_extrinsic(extrinsic.clone());
+        let message_id = send();
+        sp_io::storage::clear(well_known_keys::EXTRINSIC_INDEX);
+
+        assert_eq!(
+            TxHashOf::<Test>::get(message_id),
+            Some(sp_io::hashing::blake2_256(&extrinsic))
+        );
+        assert_eq!(reply(), sp_io::hashing::blake2_256(&extrinsic));
+
+        // Hash is removed along with the gas tree of the message.
+        assert!(TxHashOf::<Test>::get(message_id).is_none());
+    });
+}
+
+#[test]
+fn tx_hash_withheld_from_delayed_messages() {
+    use gear_core::message::{Dispatch, DispatchKind, Message};
+    use sp_core::storage::well_known_keys;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Default));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        // Emulating application of the extrinsic.
+        let extrinsic = b"extrinsic".to_vec();
+        sp_io::storage::set(well_known_keys::EXTRINSIC_INDEX, &0u32.encode());
+        System::note_extrinsic(extrinsic.clone());
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        sp_io::storage::clear(well_known_keys::EXTRINSIC_INDEX);
+
+        let message_id = get_last_message_id();
+        let hash = sp_io::hashing::blake2_256(&extrinsic);
+        assert_eq!(Gear::tx_hash_of(message_id), Some(hash));
+
+        // Message sent by the program with delay.
+        let delayed = Dispatch::new(
+            DispatchKind::Handle,
+            Message::new(
+                MessageId::generate_outgoing(message_id, 0),
+                program_id,
+                program_id,
+                Default::default(),
+                Some(1_000_000),
+                0,
+                None,
+            ),
+        );
+        let delayed_id = delayed.id();
+        Gear::send_delayed_dispatch(message_id, delayed, 1.try_into().unwrap(), false, None);
+        assert!(Gear::tx_hash_of(delayed_id).is_none());
+
+        // Messages sent by the delayed one don't get the hash either,
+        // unlike ones sent by the original message.
+        let sent_by_delayed = MessageId::generate_outgoing(delayed_id, 0);
+        Gear::withhold_tx_hash(delayed_id, sent_by_delayed, false);
+        assert!(Gear::tx_hash_of(sent_by_delayed).is_none());
+
+        let sent_by_original = MessageId::generate_outgoing(message_id, 1);
+        Gear::withhold_tx_hash(message_id, sent_by_original, false);
+        assert!(!TxHashWithheldOf::<Test>::contains_key(sent_by_original));
+    });
+}
+
+#[test]
+fn source_code_id_of_program_source() {
+    // Forwards the payload to itself when sent by a user, replying
+    // with the error. Sends its code id to the account from
+    // the payload when sent by itself.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_program_id" (func $program_id (param i32)))
+            (import "env" "gr_source_code_id" (func $source_code_id (param i32) (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_error" (func $error (param i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (local $len i32)
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0))
+                (local.set $len (call $source_code_id (i32.const 0x20)))
+                (if (i32.eqz (local.get $len))
+                    (then
+                        (if (call $send (i32.const 0) (i32.const 0x20) (i32.const 32)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x80))
+                            (then unreachable)))
+                    (else
+                        (call $program_id (i32.const 0x40))
+                        (if (call $send (i32.const 0x40) (i32.const 0) (i32.const 32)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x80))
+                            (then unreachable))
+                        (call $error (i32.const 0x300))
+                        (if (call $reply (i32.const 0x300) (local.get $len)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable))))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+        assert!(Gear::is_initialized(program_id));
+
+        let user: ProgramId = USER_1.into();
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            user.as_ref().to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+
+        run_to_next_block(None);
+
+        let messages: Vec<_> = System::events()
+            .into_iter()
+            .filter_map(|e| match e.event {
+                MockRuntimeEvent::Gear(Event::UserMessageSent { message, .. })
+                    if message.destination() == user =>
+                {
+                    Some(message)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(messages.len(), 2);
+
+        let reply = messages
+            .iter()
+            .find(|m| m.reply_to() == Some(message_id))
+            .expect("reply expected");
+        assert_eq!(
+            ExtError::decode(&mut reply.payload()).expect("valid encoded error"),
+            ExtError::Execution(ExecutionError::NoSourceCode)
+        );
+
+        let code_id = Gear::code_id_of(program_id).expect("program is active");
+        let sent = messages
+            .iter()
+            .find(|m| m.reply_to().is_none())
+            .expect("message with code id expected");
+        assert_eq!(sent.payload(), code_id.as_ref());
+    });
+}
+
+#[test]
+fn block_duration_reported() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_block_duration" (func $block_duration (result i64)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (i64.store (i32.const 0x20) (call $block_duration))
+                (if (call $reply (i32.const 0x20) (i32.const 8)
+                        (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+        run_to_next_block(None);
+
+        assert_succeed(message_id);
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        let block_duration = 2 * <Test as pallet_timestamp::Config>::MinimumPeriod::get();
+        assert_eq!(reply.payload(), block_duration.to_le_bytes());
+    });
+}
+
+#[test]
+fn scheduled_exit_terminates_program() {
+    // Schedules exit at the current block (must fail), then 3 blocks later.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_block_height" (func $block_height (result i32)))
+            (import "env" "gr_schedule_exit" (func $schedule_exit (param i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init
+                (if (i32.eqz (call $schedule_exit (call $block_height) (i32.const 0)))
+                    (then unreachable))
+                (if (call $schedule_exit
+                        (i32.add (call $block_height) (i32.const 3)) (i32.const 0))
+                    (then unreachable))
+            )
+            (func $handle)
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        // Init is processed in block 2, exit is due at block 5.
+        run_to_block(4, None);
+        assert!(Gear::is_active(program_id));
+
+        run_to_block(5, None);
+        assert!(Gear::is_exited(program_id));
+    });
+}
+
+#[test]
+fn scheduled_exit_replaced_on_reschedule() {
+    // Init schedules exit 3 blocks later, handle reschedules it 6 blocks later.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_block_height" (func $block_height (result i32)))
+            (import "env" "gr_schedule_exit" (func $schedule_exit (param i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init
+                (if (call $schedule_exit
+                        (i32.add (call $block_height) (i32.const 3)) (i32.const 0))
+                    (then unreachable))
+            )
+            (func $handle
+                (if (call $schedule_exit
+                        (i32.add (call $block_height) (i32.const 6)) (i32.const 0))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        // Init is processed in block 2, exit is due at block 5.
+        run_to_block(2, None);
+        assert!(ScheduledExitOf::<Test>::contains_key(program_id));
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+
+        // Handle is processed in block 3, exit is due at block 9 now.
+        run_to_block(5, None);
+        assert!(Gear::is_active(program_id));
+        assert_eq!(
+            ScheduledExitOf::<Test>::get(program_id),
+            Some((9, ProgramId::default()))
+        );
+
+        run_to_block(9, None);
+        assert!(Gear::is_exited(program_id));
+        assert!(!ScheduledExitOf::<Test>::contains_key(program_id));
+    });
+}
+
+#[test]
+fn message_meta_set_by_sender() {
+    // Empty payload: sends itself a message with metadata.
+    // One byte payload: mails the received metadata to the origin.
+    // Longer payload: sends itself a message with oversized metadata.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_origin" (func $origin (param i32)))
+            (import "env" "gr_program_id" (func $program_id (param i32)))
+            (import "env" "gr_message_meta" (func $message_meta (param i32) (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_send_with_meta" (func $send_with_meta (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (data (i32.const 0x100) "meta")
+            (func $init)
+            (func $handle
+                (call $program_id (i32.const 0x00))
+                (call $origin (i32.const 0x20))
+                (if (i32.eqz (call $size))
+                    (then
+                        (if (call $send_with_meta (i32.const 0x00) (i32.const 0x100) (i32.const 1)
+                                (i32.const 0x100) (i32.const 4)
+                                (i32.const 0x40) (i32.const 0x60) (i32.const 0x80))
+                            (then unreachable))
+                        (return)))
+                (if (i32.eq (call $size) (i32.const 1))
+                    (then
+                        (if (call $send (i32.const 0x20) (i32.const 0x200)
+                                (call $message_meta (i32.const 0x200))
+                                (i32.const 0x40) (i32.const 0x60) (i32.const 0x80))
+                            (then unreachable))
+                        (return)))
+                (drop (call $send_with_meta (i32.const 0x00) (i32.const 0x100) (i32.const 1)
+                        (i32.const 0x100) (i32.const 65)
+                        (i32.const 0x40) (i32.const 0x60) (i32.const 0x80)))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        run_to_next_block(None);
+
+        let mail = maybe_last_message(USER_1).expect("mail expected");
+        assert_eq!(mail.payload(), b"meta");
+        assert!(mail.metadata().is_empty());
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            program_id,
+            vec![0, 0],
+            50_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+        run_to_next_block(None);
+
+        assert_failed(
+            message_id,
+            ExecutionErrorReason::Ext(TrapExplanation::Other(MetadataSizeError.to_string().into())),
+        );
+    });
+}
+
+#[test]
+fn send_with_reply_to_redirects_reply() {
+    // 32 bytes payload: sends "ping" to the given program, redirecting
+    // the reply to it as well.
+    // 4 bytes payload: replies "pong".
+    // Empty payload: tries redirecting the reply to the origin and
+    // replies with the error length.
+    // On reply: mails the reply payload to the origin.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_origin" (func $origin (param i32)))
+            (import "env" "gr_program_id" (func $program_id (param i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_send_with_reply_to" (func $send_with_reply_to (param i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (export "handle_reply" (func $handle_reply))
+            (data (i32.const 0x100) "pingpong")
+            (func $init)
+            (func $handle
+                (call $program_id (i32.const 0x20))
+                (call $origin (i32.const 0x40))
+                (if (i32.eq (call $size) (i32.const 32))
+                    (then
+                        (call $read (i32.const 0) (i32.const 32) (i32.const 0x00))
+                        (if (call $send_with_reply_to (i32.const 0x00) (i32.const 0x100) (i32.const 4)
+                                (i32.const 0x00) (i32.const 0x60) (i32.const 0x80) (i32.const 0xA0))
+                            (then unreachable))
+                        (return)))
+                (if (i32.eq (call $size) (i32.const 4))
+                    (then
+                        (if (call $reply (i32.const 0x104) (i32.const 4)
+                                (i32.const 0x60) (i32.const 0x80) (i32.const 0xA0))
+                            (then unreachable))
+                        (return)))
+                (i32.store (i32.const 0x200)
+                    (call $send_with_reply_to (i32.const 0x20) (i32.const 0x100) (i32.const 4)
+                        (i32.const 0x40) (i32.const 0x60) (i32.const 0x80) (i32.const 0xA0)))
+                (if (call $reply (i32.const 0x200) (i32.const 4)
+                        (i32.const 0x60) (i32.const 0x80) (i32.const 0xA0))
+                    (then unreachable))
+            )
+            (func $handle_reply
+                (call $origin (i32.const 0x40))
+                (call $read (i32.const 0) (call $size) (i32.const 0x200))
+                (if (call $send (i32.const 0x40) (i32.const 0x200) (call $size)
+                        (i32.const 0x60) (i32.const 0x80) (i32.const 0xA0))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Custom(wat).to_bytes();
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            code.clone(),
+            b"proxy".to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        let proxy = get_last_program_id();
+
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            code,
+            b"service".to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        let service = get_last_program_id();
+
+        run_to_block(2, None);
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            proxy,
+            service.as_ref().to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        run_to_block(5, None);
+
+        // The reply on the proxy's message went to the service.
+        let mail = maybe_last_message(USER_1).expect("mail expected");
+        assert_eq!(mail.source(), service);
+        assert_eq!(mail.payload(), b"pong");
+
+        // Replies can't be redirected to users.
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_1),
+            proxy,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000,
+            0,
+        ));
+        let message_id = get_last_message_id();
+        run_to_next_block(None);
+
+        assert_succeed(message_id);
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        assert_eq!(reply.reply_to(), Some(message_id));
+        assert_ne!(reply.payload(), 0i32.to_le_bytes());
+    });
+}
+
+#[test]
+fn mailbox_contains_until_claimed() {
+    // Empty payload: mails the origin, keeping the message id.
+    // Otherwise replies whether the mailed message is in the mailbox.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_size" (func $size (result i32)))
+            (import "env" "gr_origin" (func $origin (param i32)))
+            (import "env" "gr_mailbox_contains" (func $mailbox_contains (param i32 i32) (result i32)))
+            (import "env" "gr_send_wgas" (func $send_wgas (param i32 i32 i32 i64 i32 i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $origin (i32.const 0x20))
+                (if (i32.eqz (call $size))
+                    (then
+                        (if (call $send_wgas (i32.const 0x20) (i32.const 0) (i32.const 0)
+                                (i64.const 10000000) (i32.const 0x40) (i32.const 0x60) (i32.const 0x80))
+                            (then unreachable))
+                        (return)))
+                (i32.store (i32.const 0x100)
+                    (call $mailbox_contains (i32.const 0x20) (i32.const 0x60)))
+                (if (call $reply (i32.const 0x100) (i32.const 4)
+                        (i32.const 0x40) (i32.const 0x80) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let send = |payload: Vec<u8>| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                payload,
+                50_000_000_000,
+                0,
+            ));
+            let message_id = get_last_message_id();
+            run_to_next_block(None);
+            assert_succeed(message_id);
+        };
+        let contains = || {
+            send(vec![1]);
+            let reply = maybe_last_message(USER_1).expect("reply expected");
+            u32::from_le_bytes(reply.payload().try_into().unwrap())
+        };
+
+        send(EMPTY_PAYLOAD.to_vec());
+        let mail_id = get_last_mail(USER_1).id();
+
+        assert_eq!(contains(), 1);
+
+        assert_ok!(Gear::claim_value(RuntimeOrigin::signed(USER_1), mail_id));
+        assert!(!MailboxOf::<Test>::contains(&USER_1, &mail_id));
+
+        assert_eq!(contains(), 0);
+    });
+}
+
+#[test]
+fn mailbox_contains_only_messages_sent_by_program() {
+    use gear_core::message::StoredMessage;
+
+    // Replies whether the message with the id from the payload,
+    // mailed to the origin, is in the mailbox.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_origin" (func $origin (param i32)))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_mailbox_contains" (func $mailbox_contains (param i32 i32) (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                (call $origin (i32.const 0x20))
+                (call $read (i32.const 0) (i32.const 32) (i32.const 0x60))
+                (i32.store (i32.const 0x100)
+                    (call $mailbox_contains (i32.const 0x20) (i32.const 0x60)))
+                (if (call $reply (i32.const 0x100) (i32.const 4)
+                        (i32.const 0x40) (i32.const 0x80) (i32.const 0x200))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let user = ProgramId::from_origin(USER_1.into_origin());
+        let other = ProgramId::from_origin(USER_2.into_origin());
+        let mail = |id: u64, source: ProgramId| {
+            let message = StoredMessage::new(id.into(), source, user, Default::default(), 0, None);
+            MailboxOf::<Test>::insert(message, 100).expect("Failed to insert");
+
+            MessageId::from(id)
+        };
+
+        let own_mail_id = mail(1, program_id);
+        let other_mail_id = mail(2, other);
+
+        let contains = |mail_id: MessageId| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                mail_id.encode(),
+                50_000_000_000,
+                0,
+            ));
+            let message_id = get_last_message_id();
+            run_to_next_block(None);
+            assert_succeed(message_id);
+
+            let reply = maybe_last_message(USER_1).expect("reply expected");
+            u32::from_le_bytes(reply.payload().try_into().unwrap())
+        };
+
+        assert_eq!(contains(own_mail_id), 1);
+
+        // Mail of other actors can't be probed.
+        assert!(MailboxOf::<Test>::contains(&USER_1, &other_mail_id));
+        assert_eq!(contains(other_mail_id), 0);
+    });
+}
+
+#[test]
+fn stack_limit_reported_and_enforced() {
+    // Replies with the stack limit for zero depth from the payload,
+    // recurses to the depth otherwise.
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_read" (func $read (param i32 i32 i32)))
+            (import "env" "gr_stack_limit" (func $stack_limit (result i32)))
+            (import "env" "gr_reply" (func $reply (param i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $recurse (param $depth i32)
+                (if (local.get $depth)
+                    (then (call $recurse (i32.sub (local.get $depth) (i32.const 1)))))
+            )
+            (func $handle
+                (call $read (i32.const 0) (i32.const 4) (i32.const 0))
+                (if (i32.load (i32.const 0))
+                    (then (call $recurse (i32.load (i32.const 0))))
+                    (else
+                        (i32.store (i32.const 0x20) (call $stack_limit))
+                        (if (call $reply (i32.const 0x20) (i32.const 4)
+                                (i32.const 0x80) (i32.const 0x100) (i32.const 0x200))
+                            (then unreachable))))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let stack_height = <Test as Config>::Schedule::get().limits.stack_height;
+
+        assert_ok!(upload_program_default(USER_1, ProgramCodeKind::Custom(wat)));
+        let program_id = get_last_program_id();
+
+        run_to_block(2, None);
+
+        let send = |depth: u32| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                depth.encode(),
+                50_000_000_000,
+                0,
+            ));
+            let message_id = get_last_message_id();
+            run_to_next_block(None);
+            message_id
+        };
+
+        let message_id = send(0);
+        assert_succeed(message_id);
+        let reply = maybe_last_message(USER_1).expect("reply expected");
+        assert_eq!(reply.payload(), stack_height.to_le_bytes());
+
+        // Recursion keeping within the limit.
+        assert_succeed(send(stack_height / 16));
+
+        // Every level keeps at least one value on the stack.
+        assert_failed(
+            send(stack_height),
+            ExecutionErrorReason::Ext(TrapExplanation::Unknown),
+        );
+    });
+}
+
+#[test]
+fn syscalls_with_wrong_signatures_fail() {
+    macro_rules! wat_template {
+        () => {
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (import "env" "{}" (func {}))
+                (export "init" (func $init))
+                (func $init)
+            )"#
+        };
+    }
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let cases = [
+            // Missing `delay_ptr`.
+            ("gr_send", "(param i32 i32 i32 i32 i32) (result i32)"),
+            // Missing `payload_len`.
+            ("gr_reply_push", "(param i32) (result i32)"),
+            // Block height is `u32`.
+            ("gr_block_height", "(result i64)"),
+        ];
+
+        let schedule = <Test as Config>::Schedule::get();
+
+        for (name, signature) in cases {
+            let wat = format!(wat_template!(), name, signature);
+            let code = ProgramCodeKind::Custom(wat.as_str()).to_bytes();
+
+            let error = SysCallSignatureError {
+                name: name.into(),
+                expected: syscall_signature(name).expect("Syscall must be known"),
+            };
+            assert!(matches!(
+                Code::try_new(
+                    code.clone(),
+                    schedule.instruction_weights.version,
+                    |module| schedule.rules(module),
+                    Some(schedule.limits.stack_height),
+                ),
+                Err(code::CodeError::SyscallSignature(e)) if e == error
+            ));
+
+            assert_noop!(
+                Gear::upload_program(
+                    RuntimeOrigin::signed(USER_1),
+                    code,
+                    DEFAULT_SALT.to_vec(),
+                    EMPTY_PAYLOAD.to_vec(),
+                    50_000_000_000,
+                    0,
+                ),
+                Error::<Test>::FailedToConstructProgram
+            );
+        }
+    });
+}
+
+#[test]
+fn messages_pages_dont_skip_or_duplicate() {
+    use crate::{MessagesPage, QueueOf, MAX_PAGE_LEN, MAX_PREVIEW_PAYLOAD_LEN};
+    use gear_core::message::{DispatchKind, StoredDispatch, StoredMessage};
+    use sp_core::H256;
+    use std::collections::BTreeSet;
+
+    fn message(id: u64, destination: ProgramId, payload_len: u32) -> StoredMessage {
+        StoredMessage::new(
+            MessageId::from(id),
+            ProgramId::from(1000),
+            destination,
+            vec![0; payload_len as usize]
+                .try_into()
+                .expect("Payload is within limit"),
+            0,
+            None,
+        )
+    }
+
+    // Requests all pages, adding new messages after the first one.
+    fn collect_pages(
+        page: impl Fn(Option<H256>) -> MessagesPage,
+        mut add_messages: impl FnMut(),
+    ) -> Vec<H256> {
+        let mut ids = vec![];
+        let mut cursor = None;
+
+        loop {
+            let MessagesPage {
+                messages,
+                next_cursor,
+            } = page(cursor);
+
+            ids.extend(messages.into_iter().map(|message| message.id));
+
+            if ids.len() == 3 {
+                add_messages();
+            }
+
+            match next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+
+        ids
+    }
+
+    fn assert_pages(ids: &[H256], stored: impl IntoIterator<Item = u64>) {
+        // Nothing is duplicated.
+        let unique: BTreeSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), ids.len());
+
+        for id in stored {
+            assert!(ids.contains(&MessageId::from(id).into_origin()));
+        }
+    }
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let user = ProgramId::from_origin(USER_1.into_origin());
+        let program_id = ProgramId::from(2000);
+        let ids = 1..=10;
+
+        for id in ids.clone() {
+            MailboxOf::<Test>::insert(message(id, user, 0), 1).expect("Failed to insert");
+            QueueOf::<Test>::queue(StoredDispatch::new(
+                DispatchKind::Handle,
+                message(id, program_id, 0),
+                None,
+            ))
+            .expect("Failed to queue");
+        }
+
+        let mut new_id = 100;
+
+        let mailbox_ids = collect_pages(
+            |cursor| Gear::mailbox_of(USER_1, cursor, 3),
+            || {
+                for _ in 0..5 {
+                    new_id += 1;
+                    MailboxOf::<Test>::insert(message(new_id, user, 0), 1)
+                        .expect("Failed to insert");
+                }
+            },
+        );
+        assert_pages(&mailbox_ids, ids.clone());
+
+        // Mailbox pages are sorted by ids.
+        assert!(mailbox_ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let queue_ids = collect_pages(
+            |cursor| Gear::queue_snapshot(cursor, 3),
+            || {
+                for _ in 0..5 {
+                    new_id += 1;
+                    QueueOf::<Test>::queue(StoredDispatch::new(
+                        DispatchKind::Handle,
+                        message(new_id, program_id, 0),
+                        None,
+                    ))
+                    .expect("Failed to queue");
+                }
+            },
+        );
+        assert_pages(&queue_ids, ids.clone());
+
+        // Queue pages follow the queue order.
+        let queued: Vec<H256> = ids
+            .clone()
+            .map(|id| MessageId::from(id).into_origin())
+            .collect();
+        assert_eq!(queue_ids[..queued.len()], queued[..]);
+
+        // Page following the dequeued message starts from the head.
+        let cursor = Some(MessageId::from(1).into_origin());
+        QueueOf::<Test>::dequeue().expect("Failed to dequeue");
+        let page = Gear::queue_snapshot(cursor, 1);
+        assert_eq!(page.messages[0].id, MessageId::from(2).into_origin());
+
+        // Pages and payloads are capped.
+        for id in 0..MAX_PAGE_LEN as u64 {
+            MailboxOf::<Test>::insert(message(1_000 + id, user, 0), 1).expect("Failed to insert");
+        }
+        let page = Gear::mailbox_of(USER_1, None, u32::MAX);
+        assert_eq!(page.messages.len(), MAX_PAGE_LEN as usize);
+        assert!(page.next_cursor.is_some());
+
+        let user = ProgramId::from_origin(USER_2.into_origin());
+        MailboxOf::<Test>::insert(message(1, user, MAX_PREVIEW_PAYLOAD_LEN + 1), 1)
+            .expect("Failed to insert");
+        let page = Gear::mailbox_of(USER_2, None, 1);
+        assert_eq!(page.messages[0].payload_len, MAX_PREVIEW_PAYLOAD_LEN + 1);
+        assert_eq!(page.messages[0].payload, None);
+    });
+}
+
+#[test]
+fn value_reservation_split() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_reserve_value" (func $reserve (param i32 i32) (result i32)))
+            (import "env" "gr_unreserve_value" (func $unreserve (param i32 i32) (result i32)))
+            (import "env" "gr_split_value_reservation"
+                (func $split (param i32 i32 i32) (result i32)))
+            (import "env" "gr_value_available" (func $value_available (param i32)))
+            (export "init" (func $init))
+            ;; 10_000 value
+            (data (i32.const 0x20) "\10\27")
+            ;; 4_000 value
+            (data (i32.const 0x30) "\a0\0f")
+            ;; 7_000 value
+            (data (i32.const 0x40) "\58\1b")
+            (func $init
+                ;; reserve all the value
+                (if (call $reserve (i32.const 0x20) (i32.const 0x100))
+                    (then unreachable))
+                ;; move a part of it into the new reservation
+                (if (call $split (i32.const 0x100) (i32.const 0x30) (i32.const 0x200))
+                    (then unreachable))
+                ;; only 6_000 is left in the source reservation
+                (if (i32.eqz (call $split (i32.const 0x100) (i32.const 0x40) (i32.const 0x300)))
+                    (then unreachable))
+                ;; the split part is returned to the available balance
+                (if (call $unreserve (i32.const 0x200) (i32.const 0x300))
+                    (then unreachable))
+                (if (i64.ne (i64.load (i32.const 0x300)) (i64.const 4000))
+                    (then unreachable))
+                (call $value_available (i32.const 0x300))
+                (if (i64.ne (i64.load (i32.const 0x300)) (i64.const 4000))
+                    (then unreachable))
+                ;; the split reservation is already unreserved
+                (if (i32.eqz (call $unreserve (i32.const 0x200) (i32.const 0x300)))
+                    (then unreachable))
+                (if (i32.eqz (call $split (i32.const 0x200) (i32.const 0x30) (i32.const 0x300)))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            10_000,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        assert!(Gear::is_initialized(program_id));
+
+        let reservations = common::get_value_reservations(program_id);
+        assert_eq!(reservations.into_values().collect::<Vec<_>>(), vec![6_000]);
+    });
+}
+
+#[test]
+fn spendable_value_zero_below_existential_deposit() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_reserve_value" (func $reserve (param i32 i32) (result i32)))
+            (import "env" "gr_value_available" (func $value_available (param i32)))
+            (import "env" "gr_spendable_value" (func $spendable_value (param i32)))
+            (export "init" (func $init))
+            ;; 9_600 value
+            (data (i32.const 0x20) "\80\25")
+            (func $init
+                (call $spendable_value (i32.const 0x100))
+                (if (i64.ne (i64.load (i32.const 0x100)) (i64.const 10000))
+                    (then unreachable))
+                ;; leave less than existential deposit
+                (if (call $reserve (i32.const 0x20) (i32.const 0x200))
+                    (then unreachable))
+                (call $value_available (i32.const 0x100))
+                (if (i64.ne (i64.load (i32.const 0x100)) (i64.const 400))
+                    (then unreachable))
+                (call $spendable_value (i32.const 0x100))
+                (if (i64.ne (i64.load (i32.const 0x100)) (i64.const 0))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            10_000,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        assert!(Gear::is_initialized(program_id));
+    });
+}
+
+#[test]
+fn value_reservations_spent_only_by_reservation_send() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_reserve_value" (func $reserve (param i32 i32) (result i32)))
+            (import "env" "gr_unreserve_value" (func $unreserve (param i32 i32) (result i32)))
+            (import "env" "gr_send" (func $send (param i32 i32 i32 i32 i32 i32) (result i32)))
+            (import "env" "gr_reservation_send"
+                (func $reservation_send (param i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            ;; 10_000 value
+            (data (i32.const 0x20) "\10\27")
+            ;; 5_000 value
+            (data (i32.const 0x30) "\88\13")
+            (func $init
+                ;; reserve all the value
+                (if (call $reserve (i32.const 0x20) (i32.const 0x100))
+                    (then unreachable))
+                ;; reserved value can't be sent by ordinary message
+                (if (i32.eqz (call $send (i32.const 0) (i32.const 0) (i32.const 0)
+                        (i32.const 0x20) (i32.const 0x200) (i32.const 0x40)))
+                    (then unreachable))
+                ;; but can be sent from the reservation
+                (if (call $reservation_send (i32.const 0x100) (i32.const 0) (i32.const 0)
+                        (i32.const 0) (i32.const 0x30) (i32.const 0x200) (i32.const 0x40))
+                    (then unreachable))
+                ;; the rest of the reservation is returned
+                (if (call $unreserve (i32.const 0x100) (i32.const 0x300))
+                    (then unreachable))
+                (if (i64.ne (i64.load (i32.const 0x300)) (i64.const 5000))
+                    (then unreachable))
+                ;; and available for ordinary messages
+                (if (call $send (i32.const 0) (i32.const 0) (i32.const 0)
+                        (i32.const 0x30) (i32.const 0x200) (i32.const 0x40))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            10_000,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        assert!(Gear::is_initialized(program_id));
+        assert!(common::get_value_reservations(program_id).is_empty());
+
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.value(), 5_000);
+    });
+}
+
+#[test]
+fn reserve_and_send_is_atomic() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (import "env" "gr_reserve_and_send"
+                (func $reserve_and_send (param i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+            (export "init" (func $init))
+            ;; destination is USER_1
+            (data (i32.const 0x0) "\01")
+            ;; 10_000 value
+            (data (i32.const 0x20) "\10\27")
+            ;; 5_000 value
+            (data (i32.const 0x30) "\88\13")
+            ;; 20_000 value
+            (data (i32.const 0x50) "\20\4e")
+            (func $init
+                ;; message value exceeds the reservation, so nothing is reserved
+                (if (i32.eqz (call $reserve_and_send (i32.const 0x20) (i32.const 0)
+                        (i32.const 0) (i32.const 0) (i32.const 0x50)
+                        (i32.const 0x100) (i32.const 0x200) (i32.const 0x40)))
+                    (then unreachable))
+                ;; so all the value is still available for the reservation
+                (if (call $reserve_and_send (i32.const 0x20) (i32.const 0)
+                        (i32.const 0) (i32.const 0) (i32.const 0x30)
+                        (i32.const 0x100) (i32.const 0x200) (i32.const 0x40))
+                    (then unreachable))
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        assert_ok!(Gear::upload_program(
+            RuntimeOrigin::signed(USER_1),
+            ProgramCodeKind::Custom(wat).to_bytes(),
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT * 100,
+            10_000,
+        ));
+
+        let program_id = utils::get_last_program_id();
+
+        run_to_block(2, None);
+        assert_last_dequeued(1);
+
+        assert!(Gear::is_initialized(program_id));
+
+        // The rest of the value stays reserved.
+        let reservations = common::get_value_reservations(program_id);
+        assert_eq!(reservations.into_values().collect::<Vec<_>>(), vec![5_000]);
+
+        let message = maybe_last_message(USER_1).expect("message expected");
+        assert_eq!(message.value(), 5_000);
+    });
+}
+
+#[test]
+fn code_stats_tracked_per_era() {
+    let wat_trap = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle
+                unreachable
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_trap = ProgramCodeKind::Custom(wat_trap).to_bytes();
+        let code_id = CodeId::generate(&code);
+        let code_trap_id = CodeId::generate(&code_trap);
+
+        let upload = |code: Vec<u8>| {
+            assert_ok!(Gear::upload_program(
+                RuntimeOrigin::signed(USER_1),
+                code,
+                DEFAULT_SALT.to_vec(),
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+
+            utils::get_last_program_id()
+        };
+
+        let program_id = upload(code);
+        let program_trap_id = upload(code_trap);
+
+        let send = |program_id| {
+            assert_ok!(Gear::send_message(
+                RuntimeOrigin::signed(USER_1),
+                program_id,
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT * 100,
+                0,
+            ));
+        };
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+        assert!(Gear::is_initialized(program_trap_id));
+
+        send(program_id);
+        send(program_id);
+        send(program_trap_id);
+
+        run_to_next_block(None);
+
+        let era = Gear::code_stats_era(System::block_number());
+        assert_eq!(era, 0);
+        assert_eq!(CodeStatsCount::<Test>::get(era), 2);
+
+        let stats = CodeStatsOf::<Test>::get(era, code_id).expect("code is tracked");
+        assert_eq!(stats.executions, 3);
+        assert_eq!(stats.traps, 0);
+        assert!(stats.gas_burned > 0);
+
+        let stats_trap = CodeStatsOf::<Test>::get(era, code_trap_id).expect("code is tracked");
+        assert_eq!(stats_trap.executions, 2);
+        assert_eq!(stats_trap.traps, 1);
+        assert!(stats_trap.gas_burned > 0);
+
+        let mut expected_top = vec![(code_id, stats), (code_trap_id, stats_trap)];
+        expected_top.sort_by(|(id_a, a), (id_b, b)| {
+            b.gas_burned
+                .cmp(&a.gas_burned)
+                .then_with(|| id_a.as_ref().cmp(id_b.as_ref()))
+        });
+
+        let top = Gear::code_stats(None, 1);
+        assert_eq!(
+            top,
+            vec![(expected_top[0].0.into_origin(), expected_top[0].1)]
+        );
+
+        // Digest of the era is emitted once the next one starts.
+        let era_length = <Test as Config>::CodeStatsEraLength::get();
+        run_to_block(era_length, None);
+
+        assert!(System::events().into_iter().any(|e| {
+            e.event
+                == MockRuntimeEvent::Gear(Event::CodeStatsDigest {
+                    era,
+                    top: expected_top.clone(),
+                })
+        }));
+
+        send(program_id);
+        run_to_next_block(None);
+        assert!(CodeStatsOf::<Test>::contains_key(era + 1, code_id));
+
+        // Only the last `CodeStatsHistoryDepth` eras are kept.
+        run_to_block(era_length * 2, None);
+
+        assert_eq!(CodeStatsOf::<Test>::iter_prefix(era).count(), 0);
+        assert_eq!(CodeStatsCount::<Test>::get(era), 0);
+        assert!(CodeStatsOf::<Test>::contains_key(era + 1, code_id));
+        assert!(!CodeStatsOf::<Test>::contains_key(era + 1, code_trap_id));
+    });
+}
+
+#[test]
+fn exit_releases_code_for_removal() {
+    use demo_exit_handle::WASM_BINARY;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        mock::CodeDepositPerByte::set(10);
+
+        let code_id = CodeId::generate(WASM_BINARY);
+        let deposit = 10 * WASM_BINARY.len() as u128;
+
+        assert_ok!(Gear::upload_code(
+            RuntimeOrigin::signed(USER_1),
+            WASM_BINARY.to_vec()
+        ));
+        assert_eq!(Balances::reserved_balance(USER_1), deposit);
+        assert_eq!(CodeDepositOf::<Test>::get(code_id), Some((USER_1, deposit)));
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 0);
+
+        assert_ok!(Gear::create_program(
+            RuntimeOrigin::signed(USER_2),
+            code_id,
+            vec![],
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000u64,
+            0
+        ));
+
+        let program_id = utils::get_last_program_id();
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 1);
+
+        run_to_next_block(None);
+        assert!(Gear::is_initialized(program_id));
+
+        assert_noop!(
+            Gear::remove_code(RuntimeOrigin::signed(USER_1), code_id),
+            Error::<Test>::CodeInUse
+        );
+        // Only the uploader, who paid the deposit, is able to remove the code.
+        assert_noop!(
+            Gear::remove_code(RuntimeOrigin::signed(USER_2), code_id),
+            Error::<Test>::NoCodeDeposit
+        );
+
+        assert_ok!(Gear::send_message(
+            RuntimeOrigin::signed(USER_2),
+            program_id,
+            EMPTY_PAYLOAD.to_vec(),
+            50_000_000_000u64,
+            0
+        ));
+
+        run_to_next_block(None);
+
+        assert!(!Gear::is_active(program_id));
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 0);
+
+        assert_ok!(Gear::remove_code(RuntimeOrigin::signed(USER_1), code_id));
+
+        System::assert_last_event(
+            Event::CodeChanged {
+                id: code_id,
+                change: CodeChangeKind::Inactive,
+            }
+            .into(),
+        );
+        assert!(!<Test as Config>::CodeStorage::exists(code_id));
+        assert!(<Test as Config>::CodeStorage::get_original_code(code_id).is_none());
+        assert!(<Test as Config>::CodeStorage::get_metadata(code_id).is_none());
+        assert!(CodeDepositOf::<Test>::get(code_id).is_none());
+        assert_eq!(Balances::reserved_balance(USER_1), 0);
+    });
+}
+
+#[test]
+fn init_failure_releases_code() {
+    let wat = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (export "init" (func $init))
+            (func $init
+                unreachable
+            )
+        )"#;
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        mock::CodeDepositPerByte::set(10);
+
+        let code = ProgramCodeKind::Custom(wat).to_bytes();
+        let code_id = CodeId::generate(&code);
+        let deposit = 10 * code.len() as u128;
+
+        let program_id = {
+            let res = upload_program_default(USER_1, ProgramCodeKind::Custom(wat));
+            assert_ok!(res);
+            res.expect("submit result was asserted")
+        };
+
+        assert_eq!(CodeDepositOf::<Test>::get(code_id), Some((USER_1, deposit)));
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 1);
+
+        run_to_next_block(None);
+
+        assert!(Gear::is_terminated(program_id));
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 0);
+
+        let reserved = Balances::reserved_balance(USER_1);
+        assert_ok!(Gear::remove_code(RuntimeOrigin::signed(USER_1), code_id));
+        assert_eq!(Balances::reserved_balance(USER_1), reserved - deposit);
+    });
+}
+
+#[test]
+fn duplicate_upload_charges_only_lookup() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        mock::CodeDepositPerByte::set(10);
+
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code);
+        let deposit = 10 * code.len() as u128;
+
+        let info = Gear::upload_code(RuntimeOrigin::signed(USER_1), code.clone())
+            .expect("code is uploaded");
+        assert_eq!(info.actual_weight, None);
+        assert_eq!(Balances::reserved_balance(USER_1), deposit);
+
+        // Duplicate is neither instrumented, nor charged with the deposit.
+        let info = Gear::upload_code(RuntimeOrigin::signed(USER_2), code.clone())
+            .expect("duplicate is accepted");
+        assert_eq!(
+            info.actual_weight,
+            Some(<Test as Config>::WeightInfo::upload_code(0))
+        );
+        assert_eq!(Balances::reserved_balance(USER_2), 0);
+        assert_eq!(CodeDepositOf::<Test>::get(code_id), Some((USER_1, deposit)));
+
+        // Program with the stored code is charged as created from the code id.
+        let tx_len = code.len() + DEFAULT_SALT.len() + EMPTY_PAYLOAD.len();
+        let info = Gear::upload_program(
+            RuntimeOrigin::signed(USER_2),
+            code,
+            DEFAULT_SALT.to_vec(),
+            EMPTY_PAYLOAD.to_vec(),
+            DEFAULT_GAS_LIMIT,
+            0,
+        )
+        .expect("program is uploaded");
+        assert_eq!(
+            info.actual_weight,
+            Some(
+                <Test as Config>::WeightInfo::create_program(DEFAULT_SALT.len() as u32)
+                    .saturating_add(<Test as Config>::WeightInfo::note_tx_hash(tx_len as u32))
+            )
+        );
+        assert_eq!(
+            Balances::reserved_balance(USER_2),
+            <Test as Config>::GasPrice::gas_price(DEFAULT_GAS_LIMIT)
+        );
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 1);
+    });
+}
+
+#[test]
+fn reset_unreserves_code_deposits() {
+    init_logger();
+    new_test_ext().execute_with(|| {
+        mock::CodeDepositPerByte::set(10);
+
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code);
+        let deposit = 10 * code.len() as u128;
+
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), code));
+        assert_eq!(Balances::reserved_balance(USER_1), deposit);
+
+        assert_ok!(Gear::reset(RuntimeOrigin::root()));
+
+        assert!(CodeDepositOf::<Test>::get(code_id).is_none());
+        assert_eq!(Balances::reserved_balance(USER_1), 0);
+    });
+}
+
+#[test]
+fn code_refcount_migrates() {
+    use frame_support::traits::{GetStorageVersion, StorageVersion};
+
+    init_logger();
+    new_test_ext().execute_with(|| {
+        let code = ProgramCodeKind::Default.to_bytes();
+        let code_id = CodeId::generate(&code);
+
+        assert_ok!(Gear::upload_code(RuntimeOrigin::signed(USER_1), code));
+        for salt in [b"salt1", b"salt2"] {
+            assert_ok!(Gear::create_program(
+                RuntimeOrigin::signed(USER_1),
+                code_id,
+                salt.to_vec(),
+                EMPTY_PAYLOAD.to_vec(),
+                DEFAULT_GAS_LIMIT,
+                0
+            ));
+        }
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 2);
+
+        // Programs created before the version 2 aren't counted.
+        StorageVersion::new(1).put::<Gear>();
+        let _ = CodeRefcount::<Test>::clear(u32::MAX, None);
+
+        crate::migration::migrate::<Test>();
+
+        assert_eq!(Gear::on_chain_storage_version(), 2);
+        assert_eq!(Gear::code_refcount(code_id.into_origin()), 2);
+    });
+}
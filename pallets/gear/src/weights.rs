@@ -22,6 +22,27 @@
 //! DATE: 2022-09-17, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
 //! HOSTNAME: `epyc-runners-node.hetzner`, CPU: `AMD EPYC 7502P 32-Core Processor`
 //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("gear-dev"), DB CACHE: 1024
+//!
+//! NOT GENERATED YET: the weights of the syscalls below are conservative
+//! placeholders above any measured syscall, 100_000_000 per call plus
+//! 200_000_000 per repetition. Syscalls reading the storage count the read
+//! per call on top. `gr_forward_per_kb` and `gr_trace_per_kb` take 60_000_000
+//! per kilobyte of the forwarded payload and of the traced data. They have to
+//! be replaced with the output of scripts/benchmarking/run_all_benchmarks.sh:
+//! `gr_gas_allowance`, `gr_is_init`, `gr_resumed_from_wait`, `gr_block_hash`,
+//! `gr_tx_hash`, `gr_set_default_gas_limit`, `gr_set_charge_allowance`,
+//! `gr_forward`, `gr_forward_per_kb`, `gr_signal_details`, `gr_bump_counter`,
+//! `gr_source_code_id`, `gr_spendable_value`, `gr_message_meta`,
+//! `gr_max_payload_size`, `gr_stack_limit`, `gr_block_duration`,
+//! `gr_children_count`, `gr_children_gas_burned`, `gr_codes_count`,
+//! `gr_execution_id`, `gr_context_limits`, `gr_trace`, `gr_trace_per_kb`,
+//! `gr_schedule_exit`, `gr_set_entry_budget`, `gr_mailbox_contains`,
+//! `gr_wait_budget_remaining`, `gr_wait_if`, `gr_new_handle`, `gr_delayed_eta`,
+//! `gr_reserve_value`, `gr_unreserve_value`, `gr_split_value_reservation`.
+//!
+//! The weight of the `tasks_exit_program` task and the base weight of
+//! `remove_code` are conservative placeholders as well, 400_000_000 and
+//! 200_000_000 on top of their storage accesses.
 
 // Executed Command:
 // ./target/production/gear-node benchmark pallet --chain=gear-dev --steps=50 --repeat=20 --pallet=pallet_gear --extrinsic=* --execution=wasm --wasm-execution=compiled --heap-pages=4096 --output=./scripts/benchmarking/weights-output/pallet_gear.rs --template=.maintain/frame-weight-template.hbs
@@ -40,29 +61,55 @@ pub trait WeightInfo {
     fn grow_cost() -> Weight;
     fn initial_cost() -> Weight;
     fn load_cost() -> Weight;
+    fn write_cost() -> Weight;
     fn claim_value() -> Weight;
+    fn tasks_exit_program() -> Weight;
     fn upload_code(c: u32, ) -> Weight;
+    fn remove_code() -> Weight;
     fn create_program(s: u32, ) -> Weight;
     fn upload_program(c: u32, s: u32, ) -> Weight;
     fn send_message(p: u32, ) -> Weight;
     fn send_reply(p: u32, ) -> Weight;
+    fn note_tx_hash(l: u32, ) -> Weight;
     fn initial_allocation(q: u32, ) -> Weight;
     fn alloc_in_handle(q: u32, ) -> Weight;
     fn reinstrument(c: u32, ) -> Weight;
     fn alloc(r: u32, ) -> Weight;
     fn gas(r: u32, ) -> Weight;
     fn gr_gas_available(r: u32, ) -> Weight;
+    fn gr_gas_allowance(r: u32, ) -> Weight;
+    fn gr_is_init(r: u32, ) -> Weight;
+    fn gr_resumed_from_wait(r: u32, ) -> Weight;
     fn gr_msg_id(r: u32, ) -> Weight;
     fn gr_origin(r: u32, ) -> Weight;
+    fn gr_block_hash(r: u32, ) -> Weight;
+    fn gr_tx_hash(r: u32, ) -> Weight;
+    fn gr_set_default_gas_limit(r: u32, ) -> Weight;
+    fn gr_set_charge_allowance(r: u32, ) -> Weight;
+    fn gr_forward(r: u32, ) -> Weight;
+    fn gr_forward_per_kb(n: u32, ) -> Weight;
+    fn gr_signal_details(r: u32, ) -> Weight;
+    fn gr_bump_counter(r: u32, ) -> Weight;
     fn gr_program_id(r: u32, ) -> Weight;
     fn gr_source(r: u32, ) -> Weight;
+    fn gr_source_code_id(r: u32, ) -> Weight;
     fn gr_value(r: u32, ) -> Weight;
     fn gr_value_available(r: u32, ) -> Weight;
+    fn gr_spendable_value(r: u32, ) -> Weight;
     fn gr_size(r: u32, ) -> Weight;
+    fn gr_message_meta(r: u32, ) -> Weight;
+    fn gr_max_payload_size(r: u32, ) -> Weight;
     fn gr_read(r: u32, ) -> Weight;
     fn gr_read_per_kb(n: u32, ) -> Weight;
     fn gr_block_height(r: u32, ) -> Weight;
+    fn gr_stack_limit(r: u32, ) -> Weight;
     fn gr_block_timestamp(r: u32, ) -> Weight;
+    fn gr_block_duration(r: u32, ) -> Weight;
+    fn gr_children_count(r: u32, ) -> Weight;
+    fn gr_children_gas_burned(r: u32, ) -> Weight;
+    fn gr_codes_count(r: u32, ) -> Weight;
+    fn gr_execution_id(r: u32, ) -> Weight;
+    fn gr_context_limits(r: u32, ) -> Weight;
     fn gr_send_init(r: u32, ) -> Weight;
     fn gr_send_push(r: u32, ) -> Weight;
     fn gr_send_push_per_kb(n: u32, ) -> Weight;
@@ -74,13 +121,25 @@ pub trait WeightInfo {
     fn gr_reply_push_per_kb(n: u32, ) -> Weight;
     fn gr_reply_to(r: u32, ) -> Weight;
     fn gr_debug(r: u32, ) -> Weight;
+    fn gr_trace(r: u32, ) -> Weight;
+    fn gr_trace_per_kb(n: u32, ) -> Weight;
     fn gr_exit_code(r: u32, ) -> Weight;
     fn gr_exit(r: u32, ) -> Weight;
+    fn gr_schedule_exit(r: u32, ) -> Weight;
+    fn gr_set_entry_budget(r: u32, ) -> Weight;
     fn gr_leave(r: u32, ) -> Weight;
+    fn gr_mailbox_contains(r: u32, ) -> Weight;
     fn gr_wait(r: u32, ) -> Weight;
     fn gr_wait_for(r: u32, ) -> Weight;
     fn gr_wait_up_to(r: u32, ) -> Weight;
+    fn gr_wait_budget_remaining(r: u32, ) -> Weight;
+    fn gr_wait_if(r: u32, ) -> Weight;
     fn gr_wake(r: u32, ) -> Weight;
+    fn gr_new_handle(r: u32, ) -> Weight;
+    fn gr_delayed_eta(r: u32, ) -> Weight;
+    fn gr_reserve_value(r: u32, ) -> Weight;
+    fn gr_unreserve_value(r: u32, ) -> Weight;
+    fn gr_split_value_reservation(r: u32, ) -> Weight;
     fn gr_create_program_wgas(r: u32, ) -> Weight;
     fn gr_create_program_wgas_per_kb(n: u32, ) -> Weight;
     fn instr_i64const(r: u32, ) -> Weight;
@@ -152,11 +211,19 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
     fn load_cost() -> Weight {
         T::DbWeight::get().reads(1)
     }
+    fn write_cost() -> Weight {
+        T::DbWeight::get().writes(1)
+    }
     fn claim_value() -> Weight {
         Weight::from_ref_time(181_473_000 as u64)
             .saturating_add(T::DbWeight::get().reads(8 as u64))
             .saturating_add(T::DbWeight::get().writes(6 as u64))
     }
+    fn tasks_exit_program() -> Weight {
+        Weight::from_ref_time(400_000_000 as u64)
+            .saturating_add(T::DbWeight::get().reads(11 as u64))
+            .saturating_add(T::DbWeight::get().writes(17 as u64))
+    }
     /// The range of component `c` is `[0, 256902]`.
     fn upload_code(c: u32, ) -> Weight {
         Weight::from_ref_time(86_323_000 as u64)
@@ -165,6 +232,11 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(T::DbWeight::get().reads(1 as u64))
             .saturating_add(T::DbWeight::get().writes(3 as u64))
     }
+    fn remove_code() -> Weight {
+        Weight::from_ref_time(200_000_000 as u64)
+            .saturating_add(T::DbWeight::get().reads(4 as u64))
+            .saturating_add(T::DbWeight::get().writes(5 as u64))
+    }
     /// The range of component `s` is `[0, 4194304]`.
     fn create_program(s: u32, ) -> Weight {
         Weight::from_ref_time(69_840_000 as u64)
@@ -200,6 +272,14 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(T::DbWeight::get().reads(14 as u64))
             .saturating_add(T::DbWeight::get().writes(11 as u64))
     }
+    /// The range of component `l` is `[0, 524288]`.
+    fn note_tx_hash(l: u32, ) -> Weight {
+        Weight::from_ref_time(3_218_000 as u64)
+            // Standard Error: 0
+            .saturating_add(Weight::from_ref_time(1_416 as u64).saturating_mul(l as u64))
+            .saturating_add(T::DbWeight::get().reads(2 as u64))
+            .saturating_add(T::DbWeight::get().writes(1 as u64))
+    }
     /// The range of component `q` is `[1, 512]`.
     fn initial_allocation(q: u32, ) -> Weight {
         Weight::from_ref_time(182_185_000 as u64)
@@ -243,6 +323,21 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(60_502_769 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_gas_allowance(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_is_init(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_resumed_from_wait(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_msg_id(r: u32, ) -> Weight {
         Weight::from_ref_time(93_227_000 as u64)
             // Standard Error: 70_761
@@ -255,6 +350,49 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(74_713_387 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_block_hash(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_tx_hash(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_set_default_gas_limit(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_set_charge_allowance(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_forward(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn gr_forward_per_kb(n: u32, ) -> Weight {
+        Weight::from_ref_time(300_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(60_000_000 as u64).saturating_mul(n as u64))
+            .saturating_add(T::DbWeight::get().reads(4 as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_signal_details(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_bump_counter(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_program_id(r: u32, ) -> Weight {
         Weight::from_ref_time(92_725_000 as u64)
             // Standard Error: 61_090
@@ -267,6 +405,12 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(75_543_794 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_source_code_id(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_value(r: u32, ) -> Weight {
         Weight::from_ref_time(92_395_000 as u64)
             // Standard Error: 70_426
@@ -279,12 +423,27 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(80_668_251 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_spendable_value(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_size(r: u32, ) -> Weight {
         Weight::from_ref_time(77_725_000 as u64)
             // Standard Error: 15_913
             .saturating_add(Weight::from_ref_time(51_528_319 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_message_meta(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_max_payload_size(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_read(r: u32, ) -> Weight {
         Weight::from_ref_time(93_096_000 as u64)
             // Standard Error: 28_352
@@ -303,12 +462,49 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(51_838_746 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_stack_limit(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_block_timestamp(r: u32, ) -> Weight {
         Weight::from_ref_time(77_766_000 as u64)
             // Standard Error: 32_690
             .saturating_add(Weight::from_ref_time(59_819_659 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_block_duration(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_children_count(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_children_gas_burned(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_codes_count(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_execution_id(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_context_limits(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_send_init(r: u32, ) -> Weight {
         Weight::from_ref_time(78_487_000 as u64)
             // Standard Error: 65_020
@@ -332,6 +528,7 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
         Weight::from_ref_time(93_176_000 as u64)
             // Standard Error: 72_233
             .saturating_add(Weight::from_ref_time(51_797_871 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `n` is `[0, 64]`.
     fn gr_send_commit_per_kb(n: u32, ) -> Weight {
@@ -345,6 +542,7 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
         Weight::from_ref_time(93_036_000 as u64)
             // Standard Error: 75_234
             .saturating_add(Weight::from_ref_time(92_337_562 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `n` is `[0, 64]`.
     fn gr_reply_commit_per_kb(n: u32, ) -> Weight {
@@ -378,6 +576,16 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(72_618_542 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_trace(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn gr_trace_per_kb(n: u32, ) -> Weight {
+        Weight::from_ref_time(300_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(60_000_000 as u64).saturating_mul(n as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_exit_code(r: u32, ) -> Weight {
         Weight::from_ref_time(77_345_000 as u64)
             // Standard Error: 21_958
@@ -390,12 +598,28 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(Weight::from_ref_time(29_843_200 as u64).saturating_mul(r as u64))
             .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_schedule_exit(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_set_entry_budget(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
     /// The range of component `r` is `[0, 1]`.
     fn gr_leave(r: u32, ) -> Weight {
         Weight::from_ref_time(76_724_000 as u64)
             // Standard Error: 231_928
             .saturating_add(Weight::from_ref_time(14_789_100 as u64).saturating_mul(r as u64))
     }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_mailbox_contains(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
     /// The range of component `r` is `[0, 1]`.
     fn gr_wait(r: u32, ) -> Weight {
         Weight::from_ref_time(76_724_000 as u64)
@@ -417,18 +641,54 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
             .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_wait_budget_remaining(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_wait_if(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_wake(r: u32, ) -> Weight {
         Weight::from_ref_time(77_385_000 as u64)
             // Standard Error: 118_668
             .saturating_add(Weight::from_ref_time(124_489_000 as u64).saturating_mul(r as u64))
             .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_new_handle(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_delayed_eta(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_reserve_value(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_unreserve_value(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_split_value_reservation(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
     /// The range of component `r` is `[0, 1]`.
     fn gr_create_program_wgas(r: u32, ) -> Weight {
         Weight::from_ref_time(94_328_000 as u64)
             // Standard Error: 231_687
             .saturating_add(Weight::from_ref_time(50_507_800 as u64).saturating_mul(r as u64))
-            .saturating_add(T::DbWeight::get().reads((4 as u64).saturating_mul(r as u64)))
+            .saturating_add(T::DbWeight::get().reads((6 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `n` is `[0, 64]`.
     fn gr_create_program_wgas_per_kb(n: u32, ) -> Weight {
@@ -755,11 +1015,19 @@ impl WeightInfo for () {
     fn load_cost() -> Weight {
         RocksDbWeight::get().reads(1)
     }
+    fn write_cost() -> Weight {
+        RocksDbWeight::get().writes(1)
+    }
     fn claim_value() -> Weight {
         Weight::from_ref_time(181_473_000 as u64)
             .saturating_add(RocksDbWeight::get().reads(8 as u64))
             .saturating_add(RocksDbWeight::get().writes(6 as u64))
     }
+    fn tasks_exit_program() -> Weight {
+        Weight::from_ref_time(400_000_000 as u64)
+            .saturating_add(RocksDbWeight::get().reads(11 as u64))
+            .saturating_add(RocksDbWeight::get().writes(17 as u64))
+    }
     /// The range of component `c` is `[0, 256902]`.
     fn upload_code(c: u32, ) -> Weight {
         Weight::from_ref_time(86_323_000 as u64)
@@ -768,6 +1036,11 @@ impl WeightInfo for () {
             .saturating_add(RocksDbWeight::get().reads(1 as u64))
             .saturating_add(RocksDbWeight::get().writes(3 as u64))
     }
+    fn remove_code() -> Weight {
+        Weight::from_ref_time(200_000_000 as u64)
+            .saturating_add(RocksDbWeight::get().reads(4 as u64))
+            .saturating_add(RocksDbWeight::get().writes(5 as u64))
+    }
     /// The range of component `s` is `[0, 4194304]`.
     fn create_program(s: u32, ) -> Weight {
         Weight::from_ref_time(69_840_000 as u64)
@@ -803,6 +1076,14 @@ impl WeightInfo for () {
             .saturating_add(RocksDbWeight::get().reads(14 as u64))
             .saturating_add(RocksDbWeight::get().writes(11 as u64))
     }
+    /// The range of component `l` is `[0, 524288]`.
+    fn note_tx_hash(l: u32, ) -> Weight {
+        Weight::from_ref_time(3_218_000 as u64)
+            // Standard Error: 0
+            .saturating_add(Weight::from_ref_time(1_416 as u64).saturating_mul(l as u64))
+            .saturating_add(RocksDbWeight::get().reads(2 as u64))
+            .saturating_add(RocksDbWeight::get().writes(1 as u64))
+    }
     /// The range of component `q` is `[1, 512]`.
     fn initial_allocation(q: u32, ) -> Weight {
         Weight::from_ref_time(182_185_000 as u64)
@@ -846,6 +1127,21 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(60_502_769 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_gas_allowance(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_is_init(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_resumed_from_wait(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_msg_id(r: u32, ) -> Weight {
         Weight::from_ref_time(93_227_000 as u64)
             // Standard Error: 70_761
@@ -858,6 +1154,49 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(74_713_387 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_block_hash(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_tx_hash(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_set_default_gas_limit(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_set_charge_allowance(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_forward(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn gr_forward_per_kb(n: u32, ) -> Weight {
+        Weight::from_ref_time(300_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(60_000_000 as u64).saturating_mul(n as u64))
+            .saturating_add(RocksDbWeight::get().reads(4 as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_signal_details(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_bump_counter(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_program_id(r: u32, ) -> Weight {
         Weight::from_ref_time(92_725_000 as u64)
             // Standard Error: 61_090
@@ -870,6 +1209,12 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(75_543_794 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_source_code_id(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_value(r: u32, ) -> Weight {
         Weight::from_ref_time(92_395_000 as u64)
             // Standard Error: 70_426
@@ -882,12 +1227,27 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(80_668_251 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_spendable_value(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_size(r: u32, ) -> Weight {
         Weight::from_ref_time(77_725_000 as u64)
             // Standard Error: 15_913
             .saturating_add(Weight::from_ref_time(51_528_319 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_message_meta(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_max_payload_size(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_read(r: u32, ) -> Weight {
         Weight::from_ref_time(93_096_000 as u64)
             // Standard Error: 28_352
@@ -906,12 +1266,49 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(51_838_746 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_stack_limit(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_block_timestamp(r: u32, ) -> Weight {
         Weight::from_ref_time(77_766_000 as u64)
             // Standard Error: 32_690
             .saturating_add(Weight::from_ref_time(59_819_659 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_block_duration(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_children_count(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_children_gas_burned(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_codes_count(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_execution_id(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_context_limits(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_send_init(r: u32, ) -> Weight {
         Weight::from_ref_time(78_487_000 as u64)
             // Standard Error: 65_020
@@ -935,6 +1332,7 @@ impl WeightInfo for () {
         Weight::from_ref_time(93_176_000 as u64)
             // Standard Error: 72_233
             .saturating_add(Weight::from_ref_time(51_797_871 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `n` is `[0, 64]`.
     fn gr_send_commit_per_kb(n: u32, ) -> Weight {
@@ -948,6 +1346,7 @@ impl WeightInfo for () {
         Weight::from_ref_time(93_036_000 as u64)
             // Standard Error: 75_234
             .saturating_add(Weight::from_ref_time(92_337_562 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `n` is `[0, 64]`.
     fn gr_reply_commit_per_kb(n: u32, ) -> Weight {
@@ -981,6 +1380,16 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(72_618_542 as u64).saturating_mul(r as u64))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_trace(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `n` is `[0, 64]`.
+    fn gr_trace_per_kb(n: u32, ) -> Weight {
+        Weight::from_ref_time(300_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(60_000_000 as u64).saturating_mul(n as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_exit_code(r: u32, ) -> Weight {
         Weight::from_ref_time(77_345_000 as u64)
             // Standard Error: 21_958
@@ -993,12 +1402,28 @@ impl WeightInfo for () {
             .saturating_add(Weight::from_ref_time(29_843_200 as u64).saturating_mul(r as u64))
             .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_schedule_exit(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_set_entry_budget(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
     /// The range of component `r` is `[0, 1]`.
     fn gr_leave(r: u32, ) -> Weight {
         Weight::from_ref_time(76_724_000 as u64)
             // Standard Error: 231_928
             .saturating_add(Weight::from_ref_time(14_789_100 as u64).saturating_mul(r as u64))
     }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_mailbox_contains(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
     /// The range of component `r` is `[0, 1]`.
     fn gr_wait(r: u32, ) -> Weight {
         Weight::from_ref_time(76_724_000 as u64)
@@ -1020,18 +1445,54 @@ impl WeightInfo for () {
             .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `r` is `[0, 20]`.
+    fn gr_wait_budget_remaining(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_wait_if(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
     fn gr_wake(r: u32, ) -> Weight {
         Weight::from_ref_time(77_385_000 as u64)
             // Standard Error: 118_668
             .saturating_add(Weight::from_ref_time(124_489_000 as u64).saturating_mul(r as u64))
             .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
     }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_new_handle(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_delayed_eta(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+            .saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(r as u64)))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_reserve_value(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_unreserve_value(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
+    /// The range of component `r` is `[0, 20]`.
+    fn gr_split_value_reservation(r: u32, ) -> Weight {
+        Weight::from_ref_time(100_000_000 as u64)
+            .saturating_add(Weight::from_ref_time(200_000_000 as u64).saturating_mul(r as u64))
+    }
     /// The range of component `r` is `[0, 1]`.
     fn gr_create_program_wgas(r: u32, ) -> Weight {
         Weight::from_ref_time(94_328_000 as u64)
             // Standard Error: 231_687
             .saturating_add(Weight::from_ref_time(50_507_800 as u64).saturating_mul(r as u64))
-            .saturating_add(RocksDbWeight::get().reads((4 as u64).saturating_mul(r as u64)))
+            .saturating_add(RocksDbWeight::get().reads((6 as u64).saturating_mul(r as u64)))
     }
     /// The range of component `n` is `[0, 64]`.
     fn gr_create_program_wgas_per_kb(n: u32, ) -> Weight {
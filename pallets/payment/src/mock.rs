@@ -19,7 +19,9 @@
 use crate as pallet_gear_payment;
 use frame_support::{
     construct_runtime, parameter_types,
-    traits::{ConstU8, Contains, Currency, FindAuthor, OnFinalize, OnInitialize, OnUnbalanced},
+    traits::{
+        ConstU8, Contains, Currency, FindAuthor, GetDefault, OnFinalize, OnInitialize, OnUnbalanced,
+    },
     weights::{constants::WEIGHT_PER_SECOND, IdentityFee},
 };
 use frame_system as system;
@@ -27,7 +29,7 @@ use pallet_transaction_payment::CurrencyAdapter;
 use primitive_types::H256;
 use sp_runtime::{
     testing::{Header, TestXt},
-    traits::{BlakeTwo256, ConstU64, IdentityLookup},
+    traits::{BlakeTwo256, ConstU128, ConstU32, ConstU64, IdentityLookup},
 };
 use sp_std::{
     convert::{TryFrom, TryInto},
@@ -173,6 +175,12 @@ impl pallet_gear::Config for Test {
     type DebugInfo = ();
     type CodeStorage = GearProgram;
     type MailboxThreshold = ConstU64<3000>;
+    type ProgramExistentialDeposit = GetDefault;
+    type CodeStatsEraLength = ConstU64<600>;
+    type CodeStatsHistoryDepth = ConstU32<2>;
+    type MaxCodeStats = ConstU32<1024>;
+    type CodeStatsDigestLength = ConstU32<10>;
+    type CodeDepositPerByte = ConstU128<0>;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
@@ -195,10 +203,12 @@ impl pallet_gear_scheduler::Config for Test {
     type ReserveThreshold = ConstU64<1>;
     type WaitlistCost = ConstU64<100>;
     type MailboxCost = ConstU64<100>;
+    type WeightInfo = ();
 }
 
 impl pallet_gear_messenger::Config for Test {
     type BlockLimiter = GearGas;
+    type WeightInfo = ();
 }
 
 type NegativeImbalance = <Balances as Currency<u64>>::NegativeImbalance;
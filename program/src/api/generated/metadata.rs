@@ -8896,6 +8896,12 @@ pub mod api {
                         pub reply: ::core::option::Option<
                             runtime_types::gear_core::message::common::ReplyDetails,
                         >,
+                        pub metadata: runtime_types::gear_core::buffer::LimitedVec<
+                            ::core::primitive::u8,
+                            runtime_types::gear_core::message::MetadataSizeError,
+                        >,
+                        pub reply_destination:
+                            ::core::option::Option<runtime_types::gear_core::ids::ProgramId>,
                     }
                 }
                 #[derive(:: subxt :: codec :: Decode, :: subxt :: codec :: Encode, Debug)]
@@ -8910,6 +8916,8 @@ pub mod api {
                     Signal,
                 }
                 #[derive(:: subxt :: codec :: Decode, :: subxt :: codec :: Encode, Debug)]
+                pub struct MetadataSizeError;
+                #[derive(:: subxt :: codec :: Decode, :: subxt :: codec :: Encode, Debug)]
                 pub struct PayloadSizeError;
             }
         }
@@ -66,6 +66,8 @@ impl From<generated_message::stored::StoredMessage> for message::StoredMessage {
             other.value,
             other.reply.map(Into::into),
         )
+        .with_metadata(other.metadata.0.try_into().expect("Infallible"))
+        .with_reply_destination(other.reply_destination.map(Into::into))
     }
 }
 
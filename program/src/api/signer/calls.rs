@@ -2,7 +2,7 @@
 use crate::api::{
     config::GearConfig,
     generated::api::{runtime_types::sp_runtime::DispatchError, Event},
-    signer::Signer,
+    signer::{Signer, Strategy, Verification},
     types::InBlock,
 };
 use anyhow::anyhow;
@@ -182,6 +182,30 @@ impl Signer {
             let status = status?;
             self.log_status(&status);
             match status {
+                InBlock(b) if self.strategy != Strategy::Finalized => {
+                    log::info!(
+                        "Submitted call {}::{} {} is in block {}",
+                        Call::PALLET,
+                        Call::FUNCTION,
+                        b.extrinsic_hash(),
+                        b.block_hash()
+                    );
+
+                    if let Strategy::InBlockThenVerifyFinalized { timeout_blocks } = self.strategy {
+                        self.verifications
+                            .lock()
+                            .expect("Poisoned")
+                            .push(Verification::spawn(
+                                self.api.clone(),
+                                b.block_hash(),
+                                b.extrinsic_hash(),
+                                timeout_blocks,
+                            ));
+                    }
+
+                    self.log_balance_spent(before).await?;
+                    return Ok(b);
+                }
                 Future | Ready | Broadcast(_) | InBlock(_) => (),
                 Dropped | Invalid | Usurped(_) | FinalityTimeout(_) | Retracted(_) => {
                     self.log_balance_spent(before).await?;
@@ -0,0 +1,139 @@
+//! Finality strategies of submitted extrinsics.
+use crate::{
+    api::{config::GearConfig, Api},
+    result::Result,
+};
+use anyhow::anyhow;
+use std::time::Duration;
+use subxt::{rpc::BlockNumber, sp_core::H256, sp_runtime::traits::Header};
+use tokio::task::JoinHandle;
+
+/// Interval of polling the node while verifying finalization.
+const POLL_INTERVAL: Duration = Duration::from_millis(1_000);
+
+/// When extrinsic processing is considered done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Return once the extrinsic is included into a block.
+    ///
+    /// The block may still be retracted by a reorg.
+    InBlock,
+    /// Return once the block including the extrinsic is finalized.
+    Finalized,
+    /// Return once the extrinsic is included into a block, verifying
+    /// finalization of the block in background.
+    ///
+    /// Verification gives up if the block isn't finalized within
+    /// `timeout_blocks` best blocks since inclusion.
+    InBlockThenVerifyFinalized { timeout_blocks: u32 },
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self::Finalized
+    }
+}
+
+/// Outcome of the finalization verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Finality {
+    /// Block including the extrinsic is finalized.
+    Finalized,
+    /// Block including the extrinsic was retracted by a reorg,
+    /// so the extrinsic has to be resubmitted.
+    Dropped {
+        block_hash: H256,
+        extrinsic_hash: H256,
+    },
+    /// Block wasn't finalized in time.
+    TimedOut {
+        block_hash: H256,
+        extrinsic_hash: H256,
+    },
+}
+
+/// Finalization verification running in background.
+#[derive(Debug)]
+pub struct Verification {
+    /// Hash of the block including the extrinsic.
+    pub block_hash: H256,
+    /// Hash of the verified extrinsic.
+    pub extrinsic_hash: H256,
+    handle: JoinHandle<Result<Finality>>,
+}
+
+impl Verification {
+    /// Spawn verification of finalization of `block_hash` including `extrinsic_hash`.
+    pub(crate) fn spawn(
+        api: Api,
+        block_hash: H256,
+        extrinsic_hash: H256,
+        timeout_blocks: u32,
+    ) -> Self {
+        let handle = tokio::spawn(verify(api, block_hash, extrinsic_hash, timeout_blocks));
+
+        Self {
+            block_hash,
+            extrinsic_hash,
+            handle,
+        }
+    }
+
+    /// Wait for the verification outcome.
+    pub async fn wait(self) -> Result<Finality> {
+        self.handle.await.map_err(|e| anyhow!(e))?
+    }
+}
+
+async fn number(api: &Api, hash: Option<H256>) -> Result<u32> {
+    let header = api
+        .client
+        .rpc()
+        .header(hash)
+        .await?
+        .ok_or_else(|| anyhow!("Header of block {hash:?} not found"))?;
+
+    Ok(*header.number())
+}
+
+async fn verify(
+    api: Api,
+    block_hash: H256,
+    extrinsic_hash: H256,
+    timeout_blocks: u32,
+) -> Result<Finality> {
+    let included_at = number(&api, Some(block_hash)).await?;
+    let deadline = number(&api, None).await?.saturating_add(timeout_blocks);
+
+    loop {
+        let finalized_head = api.client.rpc().finalized_head().await?;
+
+        if number(&api, Some(finalized_head)).await? >= included_at {
+            let canonical = api
+                .client
+                .rpc()
+                .block_hash(Some(BlockNumber::from(included_at)))
+                .await?;
+
+            return Ok(if canonical == Some(block_hash) {
+                log::info!("Block {block_hash} with extrinsic {extrinsic_hash} is finalized");
+                Finality::Finalized
+            } else {
+                log::info!("Block {block_hash} with extrinsic {extrinsic_hash} is retracted");
+                Finality::Dropped {
+                    block_hash,
+                    extrinsic_hash,
+                }
+            });
+        }
+
+        if number(&api, None).await? >= deadline {
+            return Ok(Finality::TimedOut {
+                block_hash,
+                extrinsic_hash,
+            });
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
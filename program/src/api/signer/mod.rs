@@ -4,7 +4,11 @@ use crate::{
     keystore,
     result::{Error, Result},
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    mem,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
 use subxt::{
     sp_core::{crypto::Ss58Codec, sr25519::Pair, Pair as PairT},
     sp_runtime::AccountId32,
@@ -12,51 +16,57 @@ use subxt::{
 };
 
 mod calls;
+mod finality;
 mod rpc;
 mod utils;
 
+pub use finality::{Finality, Strategy, Verification};
+
 #[derive(Clone)]
 pub struct Signer {
     api: Api,
     /// Current signer.
     pub signer: PairSigner<GearConfig, Pair>,
+    strategy: Strategy,
+    verifications: Arc<Mutex<Vec<Verification>>>,
 }
 
 impl Signer {
     /// New signer api.
     pub fn new(api: Api, suri: &str, passwd: Option<&str>) -> Result<Self> {
-        Ok(Self {
+        Ok(Self::with_signer(
             api,
-            signer: PairSigner::new(
-                Pair::from_string(suri, passwd).map_err(|_| Error::InvalidSecret)?,
-            ),
-        })
+            PairSigner::new(Pair::from_string(suri, passwd).map_err(|_| Error::InvalidSecret)?),
+        ))
+    }
+
+    fn with_signer(api: Api, signer: PairSigner<GearConfig, Pair>) -> Self {
+        Self {
+            api,
+            signer,
+            strategy: Default::default(),
+            verifications: Default::default(),
+        }
     }
 
     /// Change inner signer.
     pub fn change(self, suri: &str, passwd: Option<&str>) -> Result<Self> {
         Ok(Self {
-            api: self.api,
             signer: PairSigner::new(
                 Pair::from_string(suri, passwd).map_err(|_| Error::InvalidSecret)?,
             ),
+            ..self
         })
     }
 
     /// New signer from cache
     pub fn cache(api: Api, passwd: Option<&str>) -> Result<Self> {
-        Ok(Self {
-            api,
-            signer: keystore::cache(passwd)?,
-        })
+        Ok(Self::with_signer(api, keystore::cache(passwd)?))
     }
 
     /// New signer from keyring
     pub fn keyring(api: Api, passwd: Option<&str>) -> Result<Self> {
-        Ok(Self {
-            api,
-            signer: keystore::keyring(passwd)?,
-        })
+        Ok(Self::with_signer(api, keystore::keyring(passwd)?))
     }
 
     /// Try new signer from keyring or cache.
@@ -68,6 +78,31 @@ impl Signer {
         }
     }
 
+    /// Change finality strategy of submitted extrinsics.
+    ///
+    /// Verifications started by the returned signer aren't shared
+    /// with the current one.
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            verifications: Default::default(),
+            ..self
+        }
+    }
+
+    /// Current finality strategy.
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// Take finalization verifications started since the previous call.
+    ///
+    /// Verifications are started for extrinsics submitted with
+    /// [`Strategy::InBlockThenVerifyFinalized`].
+    pub fn take_verifications(&self) -> Vec<Verification> {
+        mem::take(&mut *self.verifications.lock().expect("Poisoned"))
+    }
+
     /// Get address of the current signer
     pub fn address(&self) -> String {
         self.signer.account_id().to_ss58check()
@@ -1,6 +1,9 @@
 //! gear api rpc methods
 use crate::{
-    api::{signer::Signer, types::GasInfo},
+    api::{
+        signer::Signer,
+        types::{CodeStats, GasInfo, MessagesPage},
+    },
     result::Result,
 };
 use gear_core::ids::{CodeId, MessageId, ProgramId};
@@ -123,4 +126,52 @@ impl Signer {
             .await
             .map_err(Into::into)
     }
+
+    /// gear_mailboxOf
+    pub async fn mailbox_of(
+        &self,
+        account: H256,
+        cursor: Option<H256>,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<MessagesPage> {
+        self.rpc()
+            .request("gear_mailboxOf", rpc_params![account, cursor, limit, at])
+            .await
+            .map_err(Into::into)
+    }
+
+    /// gear_queueSnapshot
+    pub async fn queue_snapshot(
+        &self,
+        cursor: Option<H256>,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<MessagesPage> {
+        self.rpc()
+            .request("gear_queueSnapshot", rpc_params![cursor, limit, at])
+            .await
+            .map_err(Into::into)
+    }
+
+    /// gear_codeStats
+    pub async fn code_stats(
+        &self,
+        era: Option<u32>,
+        limit: u32,
+        at: Option<H256>,
+    ) -> Result<Vec<(H256, CodeStats)>> {
+        self.rpc()
+            .request("gear_codeStats", rpc_params![era, limit, at])
+            .await
+            .map_err(Into::into)
+    }
+
+    /// gear_codeRefcount
+    pub async fn code_refcount(&self, code_id: H256, at: Option<H256>) -> Result<u32> {
+        self.rpc()
+            .request("gear_codeRefcount", rpc_params![code_id, at])
+            .await
+            .map_err(Into::into)
+    }
 }
@@ -67,6 +67,15 @@ mod gear {
                 .await?)
         }
 
+        /// Get original code by `code_hash`
+        pub async fn original_code_storage(&self, code_hash: [u8; 32]) -> Result<Option<Vec<u8>>> {
+            Ok(self
+                .storage()
+                .gear_program()
+                .original_code_storage(&CodeId(code_hash), None)
+                .await?)
+        }
+
         /// Get active program from program id.
         pub async fn gprog(&self, pid: H256) -> Result<ActiveProgram> {
             let bytes = self
@@ -12,7 +12,7 @@ use crate::{
 use parity_scale_codec::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use subxt::TransactionInBlock;
+use subxt::{sp_core::H256, TransactionInBlock};
 
 /// Information of gas
 #[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, Serialize, Deserialize)]
@@ -25,6 +25,44 @@ pub struct GasInfo {
     pub burned: u64,
 }
 
+/// Stored message, as it's shown by `gear_mailboxOf` and `gear_queueSnapshot`.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessagePreview {
+    /// Message id.
+    pub id: H256,
+    /// Message source.
+    pub source: H256,
+    /// Message destination.
+    pub destination: H256,
+    /// Message value.
+    pub value: u128,
+    /// Length of the message payload.
+    pub payload_len: u32,
+    /// Message payload, if it's short enough to be included.
+    pub payload: Option<Vec<u8>>,
+}
+
+/// Page of stored messages: mailbox pages are sorted by ids, queue pages
+/// follow the order of processing.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessagesPage {
+    /// Messages of the page.
+    pub messages: Vec<MessagePreview>,
+    /// Cursor of the next page, if there may be more messages.
+    pub next_cursor: Option<H256>,
+}
+
+/// Execution statistics of a code, as it's shown by `gear_codeStats`.
+#[derive(Clone, Copy, Debug, Default, Decode, Encode, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeStats {
+    /// Amount of messages executed by programs with the code.
+    pub executions: u32,
+    /// Amount of gas burned by programs with the code.
+    pub gas_burned: u64,
+    /// Amount of executions, which ended up with a trap.
+    pub traps: u32,
+}
+
 /// Gear pages.
 pub type GearPages = HashMap<u32, Vec<u8>>;
 
@@ -10,10 +10,13 @@ use std::{fs, path::PathBuf};
 /// program in storage. So the code for the child will be initialized by program initialization request only if it exists in storage.
 ///
 /// More precisely, the code and its metadata are actually saved in the storage under the hash of the `code`. The code hash is computed
-/// as Blake256 hash. At the time of the call the `code` hash should not be in the storage. If it was stored previously, call will end up
-/// with an `CodeAlreadyExists` error. In this case user can be sure, that he can actually use the hash of his program's code bytes to define
+/// as Blake256 hash. If the `code` was stored previously, call succeeds without any changes, charging only for the
+/// lookup of the hash. In both cases user can be sure, that he can actually use the hash of his program's code bytes to define
 /// "program factory" logic in his program.
 ///
+/// The uploader of the new code pays storage deposit of `CodeDepositPerByte` per byte of the `code`,
+/// which is returned once the code is removed with `remove_code`.
+///
 /// Parameters
 /// - `code`: wasm code of a program as a byte vector.
 ///
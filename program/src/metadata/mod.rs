@@ -43,7 +43,7 @@ macro_rules! construct_metadata {
         impl PartialEq for Metadata {
             fn eq(&self, other: &Self) -> bool {
                 $(
-                    if self.$meta != other.$meta && stringify!($meta) != "meta_registry"{
+                    if self.$meta != other.$meta && !is_opaque(stringify!($meta)) {
                         return false;
                     }
                 )+
@@ -77,7 +77,7 @@ macro_rules! construct_metadata {
                         if let Ok(ty) = registry.derive_name(&type_name) {
                             display.field(stringify!($meta), &ty);
                         }
-                        else if stringify!($meta) != "meta_registry" {
+                        else if !is_opaque(stringify!($meta)) {
                             display.field(stringify!($meta), &type_name);
                         }
                     }
@@ -101,9 +101,15 @@ construct_metadata![
     meta_async_handle_output,
     meta_state_input,
     meta_state_output,
-    meta_registry
+    meta_registry,
+    meta_hash
 ];
 
+/// Returns bool, defining if metadata field isn't a type name.
+fn is_opaque(meta: &str) -> bool {
+    meta == "meta_registry" || meta == "meta_hash"
+}
+
 impl Metadata {
     /// Read meta state.
     pub fn read(
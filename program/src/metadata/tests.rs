@@ -59,7 +59,8 @@ fn test_parsing_metadata() {
             meta_async_handle_output: Some("MessageHandleAsyncOut".into()),
             meta_state_input: Some("Option<Id>".into()),
             meta_state_output: Some("Vec<Wallet>".into()),
-            meta_registry: None
+            meta_registry: None,
+            meta_hash: None
         }
     );
 
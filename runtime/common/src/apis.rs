@@ -154,6 +154,26 @@ macro_rules! impl_runtime_apis_plus_common {
 				) -> Result<pallet_gear::GasInfo, Vec<u8>> {
 					Gear::calculate_gas_info(account_id, kind, payload, value, allow_other_panics, initial_gas)
 				}
+
+				fn mailbox_of(
+					account: H256,
+					cursor: Option<H256>,
+					limit: u32,
+				) -> pallet_gear::MessagesPage {
+					Gear::mailbox_of(AccountId::from(account.to_fixed_bytes()), cursor, limit)
+				}
+
+				fn queue_snapshot(cursor: Option<H256>, limit: u32) -> pallet_gear::MessagesPage {
+					Gear::queue_snapshot(cursor, limit)
+				}
+
+				fn code_stats(era: Option<u32>, limit: u32) -> Vec<(H256, pallet_gear::CodeStats)> {
+					Gear::code_stats(era, limit)
+				}
+
+				fn code_refcount(code_id: H256) -> u32 {
+					Gear::code_refcount(code_id)
+				}
 			}
 
 			#[cfg(feature = "runtime-benchmarks")]
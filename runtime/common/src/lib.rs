@@ -62,6 +62,13 @@ parameter_types! {
 
     pub const OutgoingLimit: u32 = 1024;
     pub const MailboxThreshold: u64 = 3000;
+
+    pub const CodeStatsEraLength: BlockNumber = 600;
+    pub const CodeStatsHistoryDepth: u32 = 24;
+    pub const MaxCodeStats: u32 = 1024;
+    pub const CodeStatsDigestLength: u32 = 10;
+
+    pub const CodeDepositPerByte: Balance = 1_000;
 }
 
 pub type NegativeImbalance<T> = <pallet_balances::Pallet<T> as Currency<
@@ -48,8 +48,9 @@ use pallet_grandpa::{
 pub use pallet_timestamp::Call as TimestampCall;
 pub use pallet_transaction_payment::{CurrencyAdapter, Multiplier};
 use runtime_common::{
-    impl_runtime_apis_plus_common, BlockHashCount, DealWithFees, GasLimitMaxPercentage,
-    MailboxCost, MailboxThreshold, OperationalFeeMultiplier, OutgoingLimit, QueueLengthStep,
+    impl_runtime_apis_plus_common, BlockHashCount, CodeDepositPerByte, CodeStatsDigestLength,
+    CodeStatsEraLength, CodeStatsHistoryDepth, DealWithFees, GasLimitMaxPercentage, MailboxCost,
+    MailboxThreshold, MaxCodeStats, OperationalFeeMultiplier, OutgoingLimit, QueueLengthStep,
     ReserveThreshold, RuntimeBlockLength, WaitlistCost, AVERAGE_ON_INITIALIZE_RATIO,
     NORMAL_DISPATCH_RATIO,
 };
@@ -349,6 +350,8 @@ impl pallet_gear_program::Config for Runtime {
 
 parameter_types! {
     pub Schedule: pallet_gear::Schedule<Runtime> = Default::default();
+    pub const ProgramExistentialDeposit: pallet_gear::ExistentialDepositPolicy =
+        pallet_gear::ExistentialDepositPolicy::Exempt;
 }
 
 impl pallet_gear::Config for Runtime {
@@ -361,6 +364,12 @@ impl pallet_gear::Config for Runtime {
     type DebugInfo = DebugInfo;
     type CodeStorage = GearProgram;
     type MailboxThreshold = MailboxThreshold;
+    type ProgramExistentialDeposit = ProgramExistentialDeposit;
+    type CodeStatsEraLength = CodeStatsEraLength;
+    type CodeStatsHistoryDepth = CodeStatsHistoryDepth;
+    type MaxCodeStats = MaxCodeStats;
+    type CodeStatsDigestLength = CodeStatsDigestLength;
+    type CodeDepositPerByte = CodeDepositPerByte;
     type Messenger = GearMessenger;
     type GasProvider = GearGas;
     type BlockLimiter = GearGas;
@@ -380,6 +389,7 @@ impl pallet_gear_scheduler::Config for Runtime {
     type ReserveThreshold = ReserveThreshold;
     type WaitlistCost = WaitlistCost;
     type MailboxCost = MailboxCost;
+    type WeightInfo = pallet_gear_scheduler::weights::SubstrateWeight<Runtime>;
 }
 
 impl pallet_gear_gas::Config for Runtime {
@@ -388,6 +398,7 @@ impl pallet_gear_gas::Config for Runtime {
 
 impl pallet_gear_messenger::Config for Runtime {
     type BlockLimiter = GearGas;
+    type WeightInfo = pallet_gear_messenger::weights::SubstrateWeight<Runtime>;
 }
 
 pub struct ExtraFeeFilter;
@@ -524,6 +535,8 @@ mod benches {
         // Gear pallets
         [pallet_gear, Gear]
         [pallet_gear_program, GearProgram]
+        [pallet_gear_messenger, GearMessenger]
+        [pallet_gear_scheduler, GearScheduler]
     );
 }
 
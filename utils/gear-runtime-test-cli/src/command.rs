@@ -460,6 +460,8 @@ fn run_fixture(test: &'_ sample::Test, fixture: &sample::Fixture) -> ColoredStri
                         ExecutableActorData {
                             program,
                             pages_with_data: memory.keys().cloned().collect(),
+                            value_reservations: Default::default(),
+                            children_count: Default::default(),
                         },
                         memory,
                     ))
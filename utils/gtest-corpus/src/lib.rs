@@ -0,0 +1,191 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Format of the corpus entries recorded by `gtest::System::record_corpus`.
+//!
+//! Every entry is stored in its own file:
+//!
+//! | Offset | Size | Field                                   |
+//! |--------|------|-----------------------------------------|
+//! | 0      | 4    | magic `b"gcrp"`                         |
+//! | 4      | 1    | format version                          |
+//! | 5      | 1    | entry kind: init, handle, reply, signal |
+//! | 6      | 32   | id of the program executing the entry   |
+//! | 38     | ..   | payload                                 |
+//!
+//! The crate has no dependencies, so fuzz harnesses can decode seeds
+//! without pulling `gstd` or the runtime in.
+
+use std::{fmt, fs, io, path::Path};
+
+/// Magic bytes every entry starts with.
+pub const MAGIC: [u8; 4] = *b"gcrp";
+
+/// Current version of the format.
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 2 + 32;
+
+/// Entry point which executed the payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    Init,
+    Handle,
+    Reply,
+    Signal,
+}
+
+impl EntryKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Init => 0,
+            Self::Handle => 1,
+            Self::Reply => 2,
+            Self::Signal => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, Error> {
+        match byte {
+            0 => Ok(Self::Init),
+            1 => Ok(Self::Handle),
+            2 => Ok(Self::Reply),
+            3 => Ok(Self::Signal),
+            _ => Err(Error::UnknownKind(byte)),
+        }
+    }
+}
+
+/// Error of decoding the entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    TooShort(usize),
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownKind(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort(len) => write!(f, "Entry of {} bytes is shorter than header", len),
+            Self::BadMagic => write!(f, "Entry doesn't start with magic bytes"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported entry format version {}", version)
+            }
+            Self::UnknownKind(kind) => write!(f, "Unknown entry kind {}", kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Payload executed by the program.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub program_id: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+impl Entry {
+    /// Encode the entry into the file content.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.kind.to_byte());
+        bytes.extend_from_slice(&self.program_id);
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    /// Decode the entry from the file content.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::TooShort(bytes.len()));
+        }
+
+        let (magic, bytes) = bytes.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Err(Error::BadMagic);
+        }
+
+        if bytes[0] != VERSION {
+            return Err(Error::UnsupportedVersion(bytes[0]));
+        }
+
+        let kind = EntryKind::from_byte(bytes[1])?;
+
+        let mut program_id = [0; 32];
+        program_id.copy_from_slice(&bytes[2..34]);
+
+        Ok(Self {
+            kind,
+            program_id,
+            payload: bytes[34..].to_vec(),
+        })
+    }
+}
+
+/// Read all entries of the corpus directory.
+///
+/// Files which aren't valid entries are skipped.
+pub fn read_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Entry>> {
+    let mut entries = vec![];
+
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        if let Ok(entry) = Entry::decode(&fs::read(path)?) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode() {
+        let entry = Entry {
+            kind: EntryKind::Reply,
+            program_id: [42; 32],
+            payload: b"PONG".to_vec(),
+        };
+
+        let bytes = entry.encode();
+        assert_eq!(Entry::decode(&bytes), Ok(entry));
+
+        assert_eq!(Entry::decode(&bytes[..10]), Err(Error::TooShort(10)));
+
+        let mut corrupted = bytes.clone();
+        corrupted[0] = 0;
+        assert_eq!(Entry::decode(&corrupted), Err(Error::BadMagic));
+
+        let mut corrupted = bytes;
+        corrupted[5] = 4;
+        assert_eq!(Entry::decode(&corrupted), Err(Error::UnknownKind(4)));
+    }
+}
@@ -1,7 +1,8 @@
 //! CLI args for the `gear-node-loader`
 
 use anyhow::Error;
-use std::str::FromStr;
+use codec::{Decode, Encode};
+use std::{num::NonZeroUsize, path::PathBuf, str::FromStr};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -21,6 +22,18 @@ pub struct LoadParams {
     #[structopt(long, default_value = "ws://localhost:9944")]
     pub endpoint: String,
 
+    /// Endpoint of the secondary node, e.g. running another runtime version.
+    ///
+    /// If provided, every batch is submitted to both nodes and divergences
+    /// of their outcomes are reported.
+    #[structopt(long)]
+    pub diff_endpoint: Option<String>,
+
+    /// Seed of the batches generator, so runs can be reproduced.
+    /// If the parameter isn't provided, then timestamp will be used.
+    #[structopt(long)]
+    pub seed: Option<u64>,
+
     // TODO: Add auth to gclient.
     // /// User name
     // #[structopt(long, default_value = "//Alice")]
@@ -36,20 +49,47 @@ pub struct LoadParams {
     #[structopt(long)]
     pub code_seed_type: Option<SeedVariant>,
 
-    /// Desirable amount of workers in task pool.
+    /// Initial amount of workers in task pool.
+    ///
+    /// The amount is adapted to keep p95 latency of batches inclusion
+    /// within `target-latency`.
     #[structopt(long, short, default_value = "8")]
     pub workers: usize,
 
+    /// Maximum amount of workers in task pool.
+    #[structopt(long, default_value = "32")]
+    pub max_workers: usize,
+
+    /// Target p95 latency of batches inclusion in milliseconds.
+    #[structopt(long, default_value = "10000")]
+    pub target_latency: u64,
+
     /// Desirable amount of calls in the sending batch.
     #[structopt(long, short, default_value = "4")]
     pub batch_size: usize,
+
+    /// Maximum amount of programs and codes each, kept for the generation
+    /// of batches. The oldest known ones are forgotten first.
+    #[structopt(long, default_value = "1024")]
+    pub context_capacity: NonZeroUsize,
+
+    /// File the session is saved to once the run is interrupted by SIGINT.
+    #[structopt(long, default_value = ".session", parse(from_os_str))]
+    pub session_file: PathBuf,
+
+    /// Resume the session saved to the given file by an interrupted run.
+    ///
+    /// Programs and codes of the session which don't exist on the node are
+    /// pruned. Seeds of the session take precedence over the provided ones.
+    #[structopt(long, parse(from_os_str))]
+    pub resume_session: Option<PathBuf>,
 }
 
 pub fn parse_cli_params() -> Params {
     Params::from_args()
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 pub enum SeedVariant {
     Dynamic(u64),
     Constant(u64),
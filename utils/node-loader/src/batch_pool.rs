@@ -6,138 +6,456 @@ use anyhow::anyhow;
 use batch::Batch;
 use context::Context;
 use futures::{stream::FuturesUnordered, StreamExt};
-use gclient::{Error, EventProcessor, GearApi, Result};
-use gear_core::ids::MessageId;
-use generators::BatchGenerator;
+use gclient::{Error, EventProcessor, Finality, GearApi, Result, Strategy, Verification};
+use gear_core::ids::{CodeId, MessageId};
+use generators::{BatchGenerator, RngState};
+use preflight::Expectation;
 use report::{BatchReporter, BatchRunReport};
+use session::Session;
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs::{self, File},
     io::Write,
     marker::PhantomData,
+    mem,
+    path::{Path, PathBuf},
 };
+use throttle::Sample;
 
 use self::{batch::BatchWithSeed, report::Report};
+pub use throttle::Throttle;
 
 mod batch;
 mod context;
+mod diff;
 pub mod generators;
+mod preflight;
 mod report;
+mod session;
+mod throttle;
 
 type Seed = u64;
 
+/// Batches are run in-block, while finalization of their blocks is verified
+/// no longer than this amount of blocks.
+const FINALITY_TIMEOUT_BLOCKS: u32 = 20;
+
+/// Stats of the run are reported every this amount of batches.
+const STATS_INTERVAL: usize = 10;
+
 pub struct BatchPool<Rng: LoaderRng> {
     api: GearApi,
-    pool_size: usize,
+    diff_api: Option<GearApi>,
+    throttle: Throttle,
     batch_size: usize,
+    context_capacity: usize,
     tasks_context: Context,
+    session_file: PathBuf,
+    // Generator state and report of the resumed session.
+    resumed: Option<(RngState, String)>,
+    batches_run: usize,
+    pool_full: usize,
+    reorg_dropped: usize,
+    diverged: usize,
     _phantom: PhantomData<Rng>,
 }
 
 impl<Rng: LoaderRng> BatchPool<Rng> {
-    pub fn new(api: GearApi, pool_size: usize, batch_size: usize) -> Self {
+    pub fn new(
+        api: GearApi,
+        throttle: Throttle,
+        batch_size: usize,
+        context_capacity: usize,
+        session_file: PathBuf,
+    ) -> Self {
         Self {
             api,
-            pool_size,
+            diff_api: None,
+            throttle,
             batch_size,
-            tasks_context: Context::new(),
+            context_capacity,
+            tasks_context: Context::with_capacity(context_capacity),
+            session_file,
+            resumed: None,
+            batches_run: 0,
+            pool_full: 0,
+            reorg_dropped: 0,
+            diverged: 0,
             _phantom: PhantomData,
         }
     }
 
-    pub async fn run(&mut self, code_seed_type: Option<SeedVariant>) -> Result<()> {
+    /// Submit every batch to the node of `diff_api` as well,
+    /// reporting divergences of its outcomes from the primary node.
+    pub fn with_diff_api(mut self, diff_api: GearApi) -> Self {
+        self.diff_api = Some(diff_api);
+        self
+    }
+
+    /// Continue the session saved to `path` by an interrupted run.
+    ///
+    /// Programs and codes of the session, which don't exist on the node
+    /// anymore, e.g. because its state was reset, are pruned.
+    pub async fn resume_session(mut self, path: &Path) -> Result<Self> {
+        let Session {
+            programs,
+            codes,
+            rng,
+        } = Session::load(path)?;
+
+        let (programs_len, codes_len) = (programs.len(), codes.len());
+
+        let mut existing_programs = Vec::with_capacity(programs_len);
+        for id in programs {
+            if self.api.program_exists(id).await? {
+                existing_programs.push(id);
+            }
+        }
+
+        let mut existing_codes = Vec::with_capacity(codes_len);
+        for (id, bytes) in codes {
+            if self.api.code_exists(id).await? {
+                existing_codes.push((id, bytes));
+            }
+        }
+
+        let report = format!(
+            "session {}: {} programs ({} pruned), {} codes ({} pruned)",
+            path.display(),
+            existing_programs.len(),
+            programs_len - existing_programs.len(),
+            existing_codes.len(),
+            codes_len - existing_codes.len(),
+        );
+
+        self.tasks_context =
+            Context::restore(self.context_capacity, existing_programs, existing_codes);
+        self.resumed = Some((rng, report));
+
+        Ok(self)
+    }
+
+    /// Runs batches until SIGINT is received.
+    ///
+    /// Once interrupted, no new batches are started. Batches in flight and
+    /// finality of the run ones are awaited, and the session is saved to be
+    /// resumed later.
+    pub async fn run(
+        &mut self,
+        seed: Option<Seed>,
+        code_seed_type: Option<SeedVariant>,
+    ) -> Result<()> {
         let mut batches = FuturesUnordered::new();
+        // Finality is awaited apart from the batches, so included batches
+        // release their slots in the pool without waiting for it.
+        let mut verifications = FuturesUnordered::new();
+
+        let (mut batch_gen, info) = match self.resumed.take() {
+            Some((rng, report)) => (
+                BatchGenerator::<Rng>::from_rng_state(rng, self.batch_size),
+                format!(
+                    "Resuming task pool with seed {} from {report}\n\n",
+                    rng.seed
+                ),
+            ),
+            None => {
+                let seed = seed.unwrap_or_else(utils::now);
+                (
+                    BatchGenerator::<Rng>::new(seed, self.batch_size, code_seed_type),
+                    format!("Running task pool with seed {seed}\n\n"),
+                )
+            }
+        };
 
-        let seed = utils::now();
-        let info = format!("Running task pool with seed {seed}\n\n");
         println!("{info}");
 
         fs::write(".log", info.as_bytes()).expect("Failed to write into file");
 
-        let mut batch_gen = BatchGenerator::<Rng>::new(seed, self.batch_size, code_seed_type);
-
         let mut num = self.api.rpc_nonce().await?;
+        let mut diff_num = match &self.diff_api {
+            Some(api) => api.rpc_nonce().await?,
+            None => 0,
+        };
+
+        let interrupted = tokio::signal::ctrl_c();
+        tokio::pin!(interrupted);
+
+        loop {
+            // Throttle may have lowered the rate, so new batches are started
+            // only once enough of the running ones complete.
+            while batches.len() < self.throttle.rate() {
+                let batch_with_seed = batch_gen.generate(&mut self.tasks_context);
+
+                let mut api = self.batch_api();
+                api.set_nonce(num);
+                num += 1;
+
+                // Finality on the secondary node isn't verified, since only
+                // divergences of outcomes are of interest there.
+                let diff_api = self.diff_api.clone().map(|api| {
+                    let mut api = api.with_strategy(Strategy::InBlock);
+                    api.set_nonce(diff_num);
+                    diff_num += 1;
+                    api
+                });
+
+                batches.push(run_batch(api, diff_api, batch_with_seed));
+            }
 
-        while batches.len() != self.pool_size {
-            let batch_with_seed = batch_gen.generate(self.tasks_context.clone());
-
-            let mut api = self.api.clone();
-            api.set_nonce(num);
-            num += 1;
+            tokio::select! {
+                Some(mut report) = batches.next() => {
+                    let pending = mem::take(&mut report.verifications);
+                    if !pending.is_empty() {
+                        verifications.push(verify_finality(pending));
+                    }
 
-            batches.push(run_batch(api, batch_with_seed));
+                    self.process_run_report(report);
+                }
+                Some(res) = verifications.next() => self.process_finality_report(res),
+                res = &mut interrupted => {
+                    res?;
+                    break;
+                }
+            }
         }
 
-        while let Some(report) = batches.next().await {
-            self.process_run_report(report);
-            let batch_with_seed = batch_gen.generate(self.tasks_context.clone());
+        println!("Interrupted, awaiting {} batches in flight", batches.len());
 
-            let mut api = self.api.clone();
-            api.set_nonce(num);
-            num += 1;
+        while let Some(mut report) = batches.next().await {
+            let pending = mem::take(&mut report.verifications);
+            if !pending.is_empty() {
+                verifications.push(verify_finality(pending));
+            }
 
-            batches.push(run_batch(api, batch_with_seed));
+            self.process_run_report(report);
         }
 
-        unreachable!()
+        while let Some(res) = verifications.next().await {
+            self.process_finality_report(res);
+        }
+
+        Session::new(&self.tasks_context, batch_gen.rng_state()).save(&self.session_file)?;
+
+        println!("Session saved to {}", self.session_file.display());
+
+        Ok(())
+    }
+
+    // Each batch gets its own api, so verifications of its extrinsics
+    // aren't mixed with ones of other batches.
+    fn batch_api(&self) -> GearApi {
+        self.api
+            .clone()
+            .with_strategy(Strategy::InBlockThenVerifyFinalized {
+                timeout_blocks: FINALITY_TIMEOUT_BLOCKS,
+            })
     }
 
     fn process_run_report(&mut self, report: BatchRunReport) {
         let BatchRunReport {
-            reports,
+            mut reports,
             context_update,
             blocks_stopped,
+            diverged,
+            sample,
+            ..
         } = report;
 
+        self.batches_run += 1;
+
+        if let Some(sample) = sample {
+            if sample == Sample::PoolFull {
+                self.pool_full += 1;
+            }
+
+            if let Some(change) = self.throttle.record(sample) {
+                reports.push(format!("RATE: {change}"));
+            }
+        }
+
         self.tasks_context.update(context_update);
 
-        let res = format!("\n{}\n", reports.join("\n"));
-        println!("{res}");
+        if diverged {
+            self.diverged += 1;
+        }
 
-        let mut file = File::options()
-            .write(true)
-            .append(true)
-            .create(true)
-            .open(".log")
-            .expect("Failed to create a file");
+        if self.batches_run % STATS_INTERVAL == 0 {
+            reports.push(self.stats());
+        }
 
-        file.write_all(res.as_bytes())
-            .expect("Failed to write into file");
+        write_reports(&reports);
 
         assert!(!blocks_stopped);
     }
+
+    // Context was already updated by the batch once it was included, so
+    // programs and codes of the retracted batch are left to be missed
+    // on picks rather than removed.
+    fn process_finality_report(&mut self, res: Result<Vec<String>>) {
+        let reports = match res {
+            Ok(dropped) if dropped.is_empty() => return,
+            Ok(dropped) => {
+                self.reorg_dropped += 1;
+
+                let mut reports = vec![String::from("REORG DROPPED:")];
+                reports.extend(dropped);
+                reports.push(format!(
+                    "Batches dropped by reorgs so far: {}",
+                    self.reorg_dropped
+                ));
+                reports
+            }
+            Err(err) => vec![String::from("FINALITY ERROR:"), err.to_string()],
+        };
+
+        write_reports(&reports);
+    }
+
+    fn stats(&self) -> String {
+        let p95 = self
+            .throttle
+            .last_p95()
+            .map(|p95| format!("{p95} ms"))
+            .unwrap_or_else(|| String::from("unknown"));
+
+        let context = &self.tasks_context;
+        let program_picks = context.program_picks();
+        let code_picks = context.code_picks();
+
+        format!(
+            "STATS: {} batches run, {} batches in flight allowed, p95 latency {p95}, \
+            {} pool full errors, {} batches dropped by reorgs, {} batches diverged, \
+            {} programs known ({} picks hit, {} missed), \
+            {} codes known ({} picks hit, {} missed)",
+            self.batches_run,
+            self.throttle.rate(),
+            self.pool_full,
+            self.reorg_dropped,
+            self.diverged,
+            context.programs_len(),
+            program_picks.hits,
+            program_picks.misses,
+            context.codes_len(),
+            code_picks.hits,
+            code_picks.misses,
+        )
+    }
 }
 
-async fn run_batch(api: GearApi, batch: BatchWithSeed) -> BatchRunReport {
+async fn run_batch(
+    api: GearApi,
+    diff_api: Option<GearApi>,
+    batch: BatchWithSeed,
+) -> BatchRunReport {
     let pre_run_report = batch.report();
 
-    match run_batch_impl(api, batch.into()).await {
+    let (res, divergences) = match diff_api {
+        Some(diff_api) => {
+            let (res, diff_res) = futures::join!(
+                run_batch_impl(api.clone(), batch.clone().into()),
+                run_batch_impl(diff_api, batch.into())
+            );
+
+            let divergences = diff::compare(&pre_run_report, &res, &diff_res);
+            (res, divergences)
+        }
+        None => (run_batch_impl(api.clone(), batch.into()).await, vec![]),
+    };
+
+    let mut report = match res {
         Ok(report) => BatchRunReport::new(pre_run_report, report),
         Err(err) => BatchRunReport::from_err(pre_run_report, err),
+    };
+
+    if !divergences.is_empty() {
+        report.reports.push(String::from("DIVERGED:"));
+        report.reports.extend(divergences);
+        report.diverged = true;
     }
+
+    report.verifications = api.take_verifications();
+
+    report
+}
+
+fn write_reports(reports: &[String]) {
+    let res = format!("\n{}\n", reports.join("\n"));
+    println!("{res}");
+
+    let mut file = File::options()
+        .write(true)
+        .append(true)
+        .create(true)
+        .open(".log")
+        .expect("Failed to create a file");
+
+    file.write_all(res.as_bytes())
+        .expect("Failed to write into file");
+}
+
+// Outcomes of a batch which block was retracted by a reorg don't reflect
+// the chain state, so such batches are reported apart from the rest.
+async fn verify_finality(verifications: Vec<Verification>) -> Result<Vec<String>> {
+    let mut dropped = vec![];
+
+    for verification in verifications {
+        match verification.wait().await? {
+            Finality::Finalized => {}
+            Finality::Dropped {
+                block_hash,
+                extrinsic_hash,
+            } => dropped.push(format!(
+                "Extrinsic {extrinsic_hash} was retracted with block {block_hash}, resubmit required"
+            )),
+            Finality::TimedOut { block_hash, .. } => {
+                log::warn!("Block {block_hash} isn't finalized in {FINALITY_TIMEOUT_BLOCKS} blocks")
+            }
+        }
+    }
+
+    Ok(dropped)
 }
 
 async fn run_batch_impl(api: GearApi, batch: Batch) -> Result<Report> {
+    let expectations = preflight::classify(&api, &batch).await?;
+
     let mut logs = vec![];
+    let mut unexpected = vec![];
+    let mut outcomes = BTreeMap::new();
 
     match batch {
         Batch::UploadProgram(args) => {
+            let code_bytes: Vec<_> = args.iter().map(|args| args.0 .0.clone()).collect();
             let args = args.into_iter().map(|v| v.into());
 
+            let started = utils::now();
             let (ex_results, batch_block_hash) = api.upload_program_bytes_batch(args).await?;
+            let latency = utils::now() - started;
 
             let mut init_messages = BTreeMap::new();
+            let mut codes = BTreeMap::new();
 
-            for r in ex_results {
+            for (i, (r, expectation)) in ex_results.into_iter().zip(&expectations).enumerate() {
                 match r {
                     Ok((mid, pid)) => {
-                        init_messages.insert(mid, pid);
+                        init_messages.insert(mid, (i, pid, expectation));
+
+                        // Code is saved even if the initialization fails.
+                        let code = code_bytes[i].clone();
+                        codes.insert(CodeId::generate(&code), code);
+                    }
+                    Err(e) => {
+                        let log = format!("[#{:<2}] Extrinsic failure: '{:?}'", logs.len() + 1, e);
+                        log_outcome(
+                            &mut logs,
+                            &mut unexpected,
+                            &mut outcomes,
+                            i,
+                            expectation,
+                            Some(format!("{e:?}")),
+                            log,
+                        );
                     }
-                    Err(e) => logs.push(format!(
-                        "[#{:<2}] Extrinsic failure: '{:?}'",
-                        logs.len() + 1,
-                        e
-                    )),
                 }
             }
 
@@ -172,47 +490,86 @@ async fn run_batch_impl(api: GearApi, batch: Batch) -> Result<Report> {
             let mut program_ids = BTreeSet::new();
 
             for (mid, maybe_err) in results {
-                let pid = init_messages.remove(&mid).expect("Infallible");
+                let (i, pid, expectation) = init_messages.remove(&mid).expect("Infallible");
 
                 if let Some(expl) = maybe_err {
-                    logs.push(format!("[#{:<2}] Program with {pid:#.2} failed initialization on {mid:#.2} with a trap: '{expl}'", logs.len() + 1))
+                    let log = format!("[#{:<2}] Program with {pid:#.2} failed initialization on {mid:#.2} with a trap: '{expl}'", logs.len() + 1);
+                    log_outcome(
+                        &mut logs,
+                        &mut unexpected,
+                        &mut outcomes,
+                        i,
+                        expectation,
+                        Some(expl),
+                        log,
+                    );
                 } else {
                     // TODO: handle case of "NotExecuted". It's not actual for init messages, but will be useful in future.
-                    logs.push(format!(
+                    let log = format!(
                         "[#{:<2}] {mid:#.2} successfully inited program with '{pid:#.2}'",
                         logs.len() + 1
-                    ));
+                    );
+                    log_outcome(
+                        &mut logs,
+                        &mut unexpected,
+                        &mut outcomes,
+                        i,
+                        expectation,
+                        None,
+                        log,
+                    );
                     program_ids.insert(pid);
                 }
             }
 
             Ok(Report {
                 logs,
+                unexpected,
                 program_ids,
                 blocks_stopped,
-                codes: BTreeSet::new(),
+                codes,
+                latency,
+                outcomes,
             })
         }
         Batch::UploadCode(args) => {
-            let args = args.into_iter().map(Into::<Vec<_>>::into);
-            let (ex_results, _) = api.upload_code_batch(args).await?;
+            let mut code_bytes: Vec<_> = args.into_iter().map(Into::<Vec<_>>::into).collect();
+            let started = utils::now();
+            let (ex_results, _) = api.upload_code_batch(&code_bytes).await?;
+            let latency = utils::now() - started;
 
-            let mut codes = BTreeSet::new();
+            let mut codes = BTreeMap::new();
 
-            for r in ex_results {
+            for (i, (r, expectation)) in ex_results.into_iter().zip(&expectations).enumerate() {
                 match r {
                     Ok(code_id) => {
-                        codes.insert(code_id);
-                        logs.push(format!(
+                        codes.insert(code_id, mem::take(&mut code_bytes[i]));
+                        let log = format!(
                             "[#{:<2}] Successfully deployed code with id '{code_id}'",
                             logs.len() + 1,
-                        ));
+                        );
+                        log_outcome(
+                            &mut logs,
+                            &mut unexpected,
+                            &mut outcomes,
+                            i,
+                            expectation,
+                            None,
+                            log,
+                        );
+                    }
+                    Err(e) => {
+                        let log = format!("[#{:<2}] Extrinsic failure: '{:?}'", logs.len() + 1, e);
+                        log_outcome(
+                            &mut logs,
+                            &mut unexpected,
+                            &mut outcomes,
+                            i,
+                            expectation,
+                            Some(format!("{e:?}")),
+                            log,
+                        );
                     }
-                    Err(e) => logs.push(format!(
-                        "[#{:<2}] Extrinsic failure: '{:?}'",
-                        logs.len() + 1,
-                        e
-                    )),
                 }
             }
 
@@ -221,28 +578,40 @@ async fn run_batch_impl(api: GearApi, batch: Batch) -> Result<Report> {
 
             Ok(Report {
                 logs,
+                unexpected,
                 program_ids: BTreeSet::new(),
                 blocks_stopped,
                 codes,
+                latency,
+                outcomes,
             })
         }
         Batch::SendMessage(args) => {
             let args = args.into_iter().map(|v| v.into());
 
+            let started = utils::now();
             let (ex_results, batch_block_hash) = api.send_message_bytes_batch(args).await?;
+            let latency = utils::now() - started;
 
             let mut handle_messages = BTreeMap::new();
 
-            for r in ex_results {
+            for (i, (r, expectation)) in ex_results.into_iter().zip(&expectations).enumerate() {
                 match r {
                     Ok((mid, pid)) => {
-                        handle_messages.insert(mid, pid);
+                        handle_messages.insert(mid, (i, pid, expectation));
+                    }
+                    Err(e) => {
+                        let log = format!("[#{:<2}] Extrinsic failure: '{:?}'", logs.len() + 1, e);
+                        log_outcome(
+                            &mut logs,
+                            &mut unexpected,
+                            &mut outcomes,
+                            i,
+                            expectation,
+                            Some(format!("{e:?}")),
+                            log,
+                        );
                     }
-                    Err(e) => logs.push(format!(
-                        "[#{:<2}] Extrinsic failure: '{:?}'",
-                        logs.len() + 1,
-                        e
-                    )),
                 }
             }
 
@@ -278,42 +647,72 @@ async fn run_batch_impl(api: GearApi, batch: Batch) -> Result<Report> {
             let blocks_stopped = !listener.blocks_running().await?;
 
             for (mid, maybe_err) in results {
-                let pid = handle_messages.remove(&mid).expect("Infallible");
+                let (i, pid, expectation) = handle_messages.remove(&mid).expect("Infallible");
 
                 if let Some(expl) = maybe_err {
-                    logs.push(format!("[#{:<2}] Message {mid:#.2} sent to program {pid:#.2} failed execution with a trap: '{expl}'", logs.len() + 1))
+                    let log = format!("[#{:<2}] Message {mid:#.2} sent to program {pid:#.2} failed execution with a trap: '{expl}'", logs.len() + 1);
+                    log_outcome(
+                        &mut logs,
+                        &mut unexpected,
+                        &mut outcomes,
+                        i,
+                        expectation,
+                        Some(expl),
+                        log,
+                    );
                 } else {
-                    logs.push(format!(
+                    let log = format!(
                         "[#{:<2}] Successfully executed {mid:#.2} message for program '{pid:#.2}'",
                         logs.len() + 1
-                    ));
+                    );
+                    log_outcome(
+                        &mut logs,
+                        &mut unexpected,
+                        &mut outcomes,
+                        i,
+                        expectation,
+                        None,
+                        log,
+                    );
                 }
             }
 
             Ok(Report {
                 logs,
-                codes: BTreeSet::new(),
+                unexpected,
+                codes: BTreeMap::new(),
                 program_ids: BTreeSet::new(),
                 blocks_stopped,
+                latency,
+                outcomes,
             })
         }
         Batch::CreateProgram(args) => {
             let args = args.into_iter().map(|v| v.into());
 
+            let started = utils::now();
             let (ex_results, batch_block_hash) = api.create_program_bytes_batch(args).await?;
+            let latency = utils::now() - started;
 
             let mut init_messages = BTreeMap::new();
 
-            for r in ex_results {
+            for (i, (r, expectation)) in ex_results.into_iter().zip(&expectations).enumerate() {
                 match r {
                     Ok((mid, pid)) => {
-                        init_messages.insert(mid, pid);
+                        init_messages.insert(mid, (i, pid, expectation));
+                    }
+                    Err(e) => {
+                        let log = format!("[#{:<2}] Extrinsic failure: '{:?}'", logs.len() + 1, e);
+                        log_outcome(
+                            &mut logs,
+                            &mut unexpected,
+                            &mut outcomes,
+                            i,
+                            expectation,
+                            Some(format!("{e:?}")),
+                            log,
+                        );
                     }
-                    Err(e) => logs.push(format!(
-                        "[#{:<2}] Extrinsic failure: '{:?}'",
-                        logs.len() + 1,
-                        e
-                    )),
                 }
             }
 
@@ -348,26 +747,68 @@ async fn run_batch_impl(api: GearApi, batch: Batch) -> Result<Report> {
             let mut program_ids = BTreeSet::new();
 
             for (mid, maybe_err) in results {
-                let pid = init_messages.remove(&mid).expect("Infallible");
+                let (i, pid, expectation) = init_messages.remove(&mid).expect("Infallible");
 
                 if let Some(expl) = maybe_err {
-                    logs.push(format!("[#{:<2}] Program with {pid:#.2} failed initialization on {mid:#.2} with a trap: '{expl}'", logs.len() + 1))
+                    let log = format!("[#{:<2}] Program with {pid:#.2} failed initialization on {mid:#.2} with a trap: '{expl}'", logs.len() + 1);
+                    log_outcome(
+                        &mut logs,
+                        &mut unexpected,
+                        &mut outcomes,
+                        i,
+                        expectation,
+                        Some(expl),
+                        log,
+                    );
                 } else {
                     // TODO: handle case of "NotExecuted". It's not actual for init messages, but will be useful in future.
-                    logs.push(format!(
+                    let log = format!(
                         "[#{:<2}] {mid:#.2} successfully inited program with '{pid:#.2}'",
                         logs.len() + 1
-                    ));
+                    );
+                    log_outcome(
+                        &mut logs,
+                        &mut unexpected,
+                        &mut outcomes,
+                        i,
+                        expectation,
+                        None,
+                        log,
+                    );
                     program_ids.insert(pid);
                 }
             }
 
             Ok(Report {
                 logs,
+                unexpected,
                 program_ids,
                 blocks_stopped,
-                codes: BTreeSet::new(),
+                codes: BTreeMap::new(),
+                latency,
+                outcomes,
             })
         }
     }
 }
+
+// Only outcomes differing from the pre-flight expectation are worth attention,
+// so they're additionally collected apart from the regular logs.
+fn log_outcome(
+    logs: &mut Vec<String>,
+    unexpected: &mut Vec<String>,
+    outcomes: &mut BTreeMap<usize, Option<String>>,
+    index: usize,
+    expectation: &Expectation,
+    error: Option<String>,
+    log: String,
+) {
+    let succeed = error.is_none();
+    outcomes.insert(index, error);
+
+    if expectation.is_success() != succeed {
+        unexpected.push(format!("{log}, expected {expectation}"));
+    }
+
+    logs.push(log);
+}
@@ -9,6 +9,7 @@ mod send_message;
 mod upload_code;
 mod upload_program;
 
+#[derive(Clone)]
 pub enum Batch {
     UploadProgram(Vec<UploadProgramArgs>),
     UploadCode(Vec<UploadCodeArgs>),
@@ -16,6 +17,7 @@ pub enum Batch {
     CreateProgram(Vec<CreateProgramArgs>),
 }
 
+#[derive(Clone)]
 pub struct BatchWithSeed {
     seed: Seed,
     batch: Batch,
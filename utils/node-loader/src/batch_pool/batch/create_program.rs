@@ -2,13 +2,11 @@
 
 use gear_core::ids::CodeId;
 
-use crate::{
-    batch_pool::Seed,
-    utils::{LoaderRng, NonEmptyVec, RingGet},
-};
+use crate::{batch_pool::Seed, utils::LoaderRng};
 
 pub type CreateProgramArgsInner = (CodeId, Vec<u8>, Vec<u8>, u64, u128);
 
+#[derive(Clone)]
 pub struct CreateProgramArgs(pub CreateProgramArgsInner);
 
 impl From<CreateProgramArgs> for CreateProgramArgsInner {
@@ -20,15 +18,9 @@ impl From<CreateProgramArgs> for CreateProgramArgsInner {
 }
 
 impl CreateProgramArgs {
-    pub fn generate<Rng: LoaderRng>(existing_codes: NonEmptyVec<CodeId>, rng_seed: Seed) -> Self {
+    pub fn generate<Rng: LoaderRng>(code: CodeId, rng_seed: Seed) -> Self {
         let mut rng = Rng::seed_from_u64(rng_seed);
 
-        let code_idx = rng.next_u64() as usize;
-        let code = existing_codes
-            .ring_get(code_idx)
-            .copied()
-            .expect("Infallible");
-
         let mut salt = vec![0; rng.gen_range(1..=100)];
         rng.fill_bytes(&mut salt);
 
@@ -1,13 +1,11 @@
 //! Send message task
 
-use crate::{
-    batch_pool::Seed,
-    utils::{LoaderRng, NonEmptyVec, RingGet},
-};
+use crate::{batch_pool::Seed, utils::LoaderRng};
 use gear_core::ids::ProgramId;
 
 pub type SendMessageArgsInner = (ProgramId, Vec<u8>, u64, u128);
 
+#[derive(Clone)]
 pub struct SendMessageArgs(pub SendMessageArgsInner);
 
 impl From<SendMessageArgs> for SendMessageArgsInner {
@@ -17,18 +15,9 @@ impl From<SendMessageArgs> for SendMessageArgsInner {
 }
 
 impl SendMessageArgs {
-    pub fn generate<Rng: LoaderRng>(
-        existing_programs: NonEmptyVec<ProgramId>,
-        rng_seed: Seed,
-    ) -> Self {
+    pub fn generate<Rng: LoaderRng>(destination: ProgramId, rng_seed: Seed) -> Self {
         let mut rng = Rng::seed_from_u64(rng_seed);
 
-        let program_idx = rng.next_u64() as usize;
-        let destination = existing_programs
-            .ring_get(program_idx)
-            .copied()
-            .expect("Infallible");
-
         let mut payload = vec![0; rng.gen_range(1..=100)];
         rng.fill_bytes(&mut payload);
 
@@ -5,6 +5,7 @@ use crate::{
     utils::LoaderRng,
 };
 
+#[derive(Clone)]
 pub struct UploadCodeArgs(pub Vec<u8>);
 
 impl From<UploadCodeArgs> for Vec<u8> {
@@ -7,6 +7,7 @@ use crate::{
 
 pub type UploadProgramArgsInner = (Vec<u8>, Vec<u8>, Vec<u8>, u64, u128);
 
+#[derive(Clone)]
 pub struct UploadProgramArgs(pub UploadProgramArgsInner);
 
 impl From<UploadProgramArgs> for UploadProgramArgsInner {
@@ -17,10 +18,14 @@ impl From<UploadProgramArgs> for UploadProgramArgsInner {
 
 impl UploadProgramArgs {
     pub fn generate<Rng: LoaderRng>(code_seed: Seed, rng_seed: Seed) -> Self {
-        let mut rng = Rng::seed_from_u64(rng_seed);
-
         let code = generators::generate_gear_program::<Rng>(code_seed);
 
+        Self::with_code::<Rng>(code, rng_seed)
+    }
+
+    pub fn with_code<Rng: LoaderRng>(code: Vec<u8>, rng_seed: Seed) -> Self {
+        let mut rng = Rng::seed_from_u64(rng_seed);
+
         let mut salt = vec![0; rng.gen_range(1..=100)];
         rng.fill_bytes(&mut salt);
 
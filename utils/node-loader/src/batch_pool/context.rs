@@ -1,20 +1,102 @@
 use gear_core::ids::{CodeId, ProgramId};
-use std::collections::BTreeSet;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use super::report::Report;
 
-// TODO DN
 #[derive(Default)]
 pub struct ContextUpdate {
     program_ids: BTreeSet<ProgramId>,
-    codes: BTreeSet<CodeId>,
+    codes: BTreeMap<CodeId, Vec<u8>>,
 }
 
-#[derive(Clone, Default)]
+/// Set of ids, which keeps the order of their insertion and holds
+/// no more than `capacity` of them, evicting the oldest inserted ones.
+#[derive(Debug, Clone)]
+pub struct BoundedSet<T> {
+    order: VecDeque<T>,
+    ids: BTreeSet<T>,
+    capacity: usize,
+}
+
+impl<T: Ord + Copy> BoundedSet<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity of the set must be non-zero");
+
+        Self {
+            order: VecDeque::with_capacity(capacity),
+            ids: BTreeSet::new(),
+            capacity,
+        }
+    }
+
+    /// Inserts `id`, evicting the oldest inserted one if the set is full.
+    ///
+    /// Re-insertion of an existing id doesn't change its position.
+    pub fn insert(&mut self, id: T) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+
+        if self.order.len() == self.capacity {
+            let evicted = self.order.pop_front().expect("Infallible");
+            self.ids.remove(&evicted);
+        }
+
+        self.order.push_back(id);
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Ids in the order of their insertion.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.order.iter()
+    }
+
+    pub fn contains(&self, id: &T) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Picks an id uniformly in constant time.
+    pub fn random(&self, rng: &mut impl Rng) -> Option<T> {
+        (!self.is_empty()).then(|| self.order[rng.gen_range(0..self.len())])
+    }
+}
+
+/// Amounts of successful and failed attempts to pick an id from the context.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PickStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl PickStats {
+    fn record<T>(&mut self, picked: &Option<T>) {
+        if picked.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Context {
-    pub programs: BTreeSet<ProgramId>, // for send_message/send_reply
-    pub codes: BTreeSet<CodeId>,
+    programs: BoundedSet<ProgramId>, // for send_message/send_reply
+    codes: BoundedSet<CodeId>,
+    // bytes of the known codes, for mutations
+    code_bytes: BTreeMap<CodeId, Vec<u8>>,
     // pub mailbox: Vec<Mailbox>, // for send_reply and claim_value
+    program_picks: PickStats,
+    code_picks: PickStats,
 }
 
 impl From<Report> for ContextUpdate {
@@ -27,12 +109,179 @@ impl From<Report> for ContextUpdate {
 }
 
 impl Context {
-    pub fn new() -> Self {
-        Default::default()
+    /// Creates context holding no more than `capacity` programs and codes each.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            programs: BoundedSet::new(capacity),
+            codes: BoundedSet::new(capacity),
+            code_bytes: BTreeMap::new(),
+            program_picks: Default::default(),
+            code_picks: Default::default(),
+        }
+    }
+
+    /// Creates context of the given programs and codes, inserting them
+    /// in the given order.
+    pub fn restore(
+        capacity: usize,
+        programs: Vec<ProgramId>,
+        codes: Vec<(CodeId, Vec<u8>)>,
+    ) -> Self {
+        let mut context = Self::with_capacity(capacity);
+
+        for id in programs {
+            context.programs.insert(id);
+        }
+
+        for (id, bytes) in codes {
+            if context.codes.insert(id) {
+                context.code_bytes.insert(id, bytes);
+            }
+        }
+
+        context
+            .code_bytes
+            .retain(|id, _| context.codes.contains(id));
+
+        context
     }
 
-    pub fn update(&mut self, mut update: ContextUpdate) {
-        self.programs.append(&mut update.program_ids);
-        self.codes.append(&mut update.codes);
+    /// Known programs in the order of their insertion.
+    pub fn programs(&self) -> Vec<ProgramId> {
+        self.programs.iter().copied().collect()
+    }
+
+    /// Known codes with their bytes in the order of their insertion.
+    pub fn codes(&self) -> Vec<(CodeId, Vec<u8>)> {
+        self.codes
+            .iter()
+            .filter_map(|id| self.code_bytes.get(id).map(|bytes| (*id, bytes.clone())))
+            .collect()
+    }
+
+    pub fn programs_len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn codes_len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn program_picks(&self) -> PickStats {
+        self.program_picks
+    }
+
+    pub fn code_picks(&self) -> PickStats {
+        self.code_picks
+    }
+
+    pub fn random_program(&mut self, rng: &mut impl Rng) -> Option<ProgramId> {
+        let picked = self.programs.random(rng);
+        self.program_picks.record(&picked);
+
+        picked
+    }
+
+    pub fn random_code(&mut self, rng: &mut impl Rng) -> Option<CodeId> {
+        let picked = self.codes.random(rng);
+        self.code_picks.record(&picked);
+
+        picked
+    }
+
+    /// Picks a known code, returning its bytes.
+    pub fn random_code_bytes(&mut self, rng: &mut impl Rng) -> Option<&[u8]> {
+        let picked = self.random_code(rng)?;
+
+        self.code_bytes.get(&picked).map(Vec::as_slice)
+    }
+
+    // Ids of the update are inserted in their sorted order, so the same
+    // sequence of updates always results in the same context.
+    pub fn update(&mut self, update: ContextUpdate) {
+        for id in update.program_ids {
+            self.programs.insert(id);
+        }
+
+        for (id, bytes) in update.codes {
+            self.codes.insert(id);
+            self.code_bytes.insert(id, bytes);
+        }
+
+        // Bytes of the evicted codes are forgotten as well.
+        self.code_bytes.retain(|id, _| self.codes.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn update(programs: &[u64], codes: &[u64]) -> ContextUpdate {
+        ContextUpdate {
+            program_ids: programs.iter().map(|&id| ProgramId::from(id)).collect(),
+            codes: codes
+                .iter()
+                .map(|&id| (CodeId::from(id), id.to_le_bytes().to_vec()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn oldest_inserted_evicted() {
+        let mut set = BoundedSet::new(3);
+
+        for id in [5u64, 1, 3] {
+            assert!(set.insert(id));
+        }
+
+        // Re-insertion doesn't refresh the id.
+        assert!(!set.insert(5));
+        assert!(set.insert(2));
+
+        assert!(!set.ids.contains(&5));
+        assert_eq!(set.order, [1, 3, 2]);
+    }
+
+    #[test]
+    fn updates_respect_bounds_under_replay() {
+        let updates = || vec![update(&[3, 1, 2], &[7]), update(&[4, 5], &[8, 9])];
+
+        let replay = || {
+            let mut context = Context::with_capacity(2);
+            updates().into_iter().for_each(|u| context.update(u));
+            context
+        };
+
+        let context = replay();
+
+        assert_eq!(context.programs_len(), 2);
+        assert_eq!(context.codes_len(), 2);
+        assert_eq!(
+            context.programs.order,
+            [ProgramId::from(4), ProgramId::from(5)]
+        );
+        assert_eq!(context.codes.order, replay().codes.order);
+        assert_eq!(
+            context.code_bytes.keys().collect::<Vec<_>>(),
+            [&CodeId::from(8), &CodeId::from(9)]
+        );
+    }
+
+    #[test]
+    fn picks_recorded() {
+        let mut rng = SmallRng::seed_from_u64(42);
+        let mut context = Context::with_capacity(4);
+
+        assert_eq!(context.random_program(&mut rng), None);
+
+        context.update(update(&[1], &[]));
+
+        assert_eq!(context.random_program(&mut rng), Some(ProgramId::from(1)));
+        assert_eq!(context.random_code(&mut rng), None);
+
+        assert_eq!(context.program_picks(), PickStats { hits: 1, misses: 1 });
+        assert_eq!(context.code_picks(), PickStats { hits: 0, misses: 1 });
     }
 }
@@ -0,0 +1,131 @@
+//! Differential comparison of batches run on two nodes.
+//!
+//! Identical batches are submitted to the primary and the secondary nodes,
+//! e.g. running the old and the new runtime, and their reports are compared.
+//! Any divergence in outcomes of the tasks or in programs and codes they
+//! produced signals a consensus-breaking change between the runtimes.
+
+use super::report::Report;
+use gclient::Result;
+use std::collections::BTreeSet;
+
+/// Compare reports of the batch run on the primary and the secondary nodes.
+///
+/// `tasks` is the pre-run report of the batch, which describes the task
+/// of index `i` on the line `i + 1`. Returns the found divergences.
+pub fn compare(
+    tasks: &[String],
+    primary: &Result<Report>,
+    secondary: &Result<Report>,
+) -> Vec<String> {
+    let (primary, secondary) = match (primary, secondary) {
+        (Ok(primary), Ok(secondary)) => (primary, secondary),
+        // Errors of the whole batch are caused by the loader or the connection,
+        // rather than by the runtime, so they're compared only for presence.
+        (Err(_), Err(_)) => return vec![],
+        (Ok(_), Err(err)) => return vec![format!("Batch failed on secondary node only: {err}")],
+        (Err(err), Ok(_)) => return vec![format!("Batch failed on primary node only: {err}")],
+    };
+
+    let mut divergences = vec![];
+
+    let indices: BTreeSet<_> = primary
+        .outcomes
+        .keys()
+        .chain(secondary.outcomes.keys())
+        .collect();
+
+    for index in indices {
+        let outcome = primary.outcomes.get(index);
+        let diff_outcome = secondary.outcomes.get(index);
+
+        if outcome != diff_outcome {
+            let task = tasks
+                .get(index + 1)
+                .map(String::as_str)
+                .unwrap_or("Unknown task");
+
+            divergences.push(format!(
+                "{task}: {} on primary node, {} on secondary node",
+                describe(outcome),
+                describe(diff_outcome)
+            ));
+        }
+    }
+
+    for id in primary
+        .program_ids
+        .symmetric_difference(&secondary.program_ids)
+    {
+        let node = node(primary.program_ids.contains(id));
+        divergences.push(format!("Program {id} is initialized on {node} node only"));
+    }
+
+    let codes: BTreeSet<_> = primary.codes.keys().collect();
+    let diff_codes: BTreeSet<_> = secondary.codes.keys().collect();
+
+    for id in codes.symmetric_difference(&diff_codes) {
+        let node = node(codes.contains(id));
+        divergences.push(format!("Code {id} is uploaded on {node} node only"));
+    }
+
+    divergences
+}
+
+fn describe(outcome: Option<&Option<String>>) -> String {
+    match outcome {
+        None => String::from("no outcome"),
+        Some(None) => String::from("succeed"),
+        Some(Some(err)) => format!("failed with '{err}'"),
+    }
+}
+
+fn node(primary: bool) -> &'static str {
+    if primary {
+        "primary"
+    } else {
+        "secondary"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gear_core::ids::CodeId;
+    use std::collections::BTreeMap;
+
+    fn report(outcomes: &[Option<&str>], codes: &[u64]) -> Result<Report> {
+        Ok(Report {
+            logs: vec![],
+            unexpected: vec![],
+            codes: codes.iter().map(|&id| (CodeId::from(id), vec![])).collect(),
+            program_ids: BTreeSet::new(),
+            blocks_stopped: false,
+            latency: 0,
+            outcomes: outcomes
+                .iter()
+                .enumerate()
+                .map(|(i, err)| (i, err.map(String::from)))
+                .collect::<BTreeMap<_, _>>(),
+        })
+    }
+
+    #[test]
+    fn divergences_name_tasks() {
+        let tasks: Vec<_> = ["Batch", "[#1 ] first", "[#2 ] second"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let primary = report(&[None, Some("trap")], &[1]);
+        assert!(compare(&tasks, &primary, &report(&[None, Some("trap")], &[1])).is_empty());
+
+        let divergences = compare(&tasks, &primary, &report(&[None, None], &[1, 2]));
+        assert_eq!(divergences.len(), 2);
+        assert!(divergences[0].starts_with("[#2 ] second: failed with 'trap'"));
+        assert!(divergences[1].ends_with("on secondary node only"));
+
+        let divergences = compare(&tasks, &primary, &Err(gclient::Error::EventNotFound));
+        assert_eq!(divergences.len(), 1);
+    }
+}
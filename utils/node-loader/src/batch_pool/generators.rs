@@ -2,9 +2,10 @@ use crate::{
     args::SeedVariant,
     batch_pool::{batch::Batch, context::Context, Seed},
     generators,
-    utils::{self, LoaderRng, LoaderRngCore, NonEmptyVec},
+    utils::{self, LoaderRng, LoaderRngCore},
 };
 use arbitrary::Unstructured;
+use codec::{Decode, Encode};
 use rand::RngCore;
 
 use super::batch::{
@@ -12,12 +13,11 @@ use super::batch::{
 };
 
 pub fn get_some_seed_generator<Rng: LoaderRng>(
-    code_seed_type: Option<SeedVariant>,
+    code_seed_type: SeedVariant,
 ) -> Box<dyn LoaderRngCore> {
     match code_seed_type {
-        None => Box::new(Rng::seed_from_u64(utils::now())) as _,
-        Some(SeedVariant::Dynamic(v)) => Box::new(Rng::seed_from_u64(v)) as _,
-        Some(SeedVariant::Constant(v)) => Box::new(ConstantGenerator::new(v)) as _,
+        SeedVariant::Dynamic(v) => Box::new(Rng::seed_from_u64(v)) as _,
+        SeedVariant::Constant(v) => Box::new(ConstantGenerator::new(v)) as _,
     }
 }
 
@@ -32,6 +32,20 @@ pub fn generate_gear_program<Rng: LoaderRng>(seed: Seed) -> Vec<u8> {
     gear_wasm_gen::gen_gear_program_code(&mut u, gear_wasm_gen::GearConfig::default())
 }
 
+/// Mutates a single instruction of the `code`, keeping it loadable.
+///
+/// Codes having nothing to mutate are returned intact.
+pub fn mutate_gear_program<Rng: LoaderRng>(code: &[u8], seed: Seed) -> Vec<u8> {
+    let mut rng = Rng::seed_from_u64(seed);
+
+    let mut buf = vec![0; 100];
+    rng.fill_bytes(&mut buf);
+
+    let mut u = Unstructured::new(&buf);
+
+    gear_wasm_gen::mutate_gear_program_code(&mut u, code).unwrap_or_else(|| code.to_vec())
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ConstantGenerator(u64);
 
@@ -59,95 +73,140 @@ impl RngCore for ConstantGenerator {
     }
 }
 
+/// State of the generator's rngs, which they can be restored from.
+///
+/// Rngs are restored by seeding them again and skipping the amount of
+/// values they already produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct RngState {
+    /// Seed of the batches rng.
+    pub seed: Seed,
+    /// Amount of batches generated.
+    pub batches: u64,
+    /// Seed of the codes rng.
+    pub code_seed: SeedVariant,
+    /// Amount of code seeds generated.
+    pub code_seeds: u64,
+}
+
 pub struct BatchGenerator<Rng: LoaderRng> {
     pub batch_gen_rng: Rng,
     pub batch_size: usize,
     code_seed_gen: Box<dyn LoaderRngCore>,
+    state: RngState,
 }
 
 impl<Rng: LoaderRng> BatchGenerator<Rng> {
     pub fn new(seed: Seed, batch_size: usize, code_seed_type: Option<SeedVariant>) -> Self {
+        // Timestamp seed is fixed, so the session can be resumed.
+        let code_seed = code_seed_type.unwrap_or_else(|| SeedVariant::Dynamic(utils::now()));
+
         Self {
             batch_gen_rng: Rng::seed_from_u64(seed),
             batch_size,
-            code_seed_gen: generators::get_some_seed_generator::<Rng>(code_seed_type),
+            code_seed_gen: generators::get_some_seed_generator::<Rng>(code_seed),
+            state: RngState {
+                seed,
+                batches: 0,
+                code_seed,
+                code_seeds: 0,
+            },
+        }
+    }
+
+    /// Creates generator continuing from the given state.
+    pub fn from_rng_state(state: RngState, batch_size: usize) -> Self {
+        let mut generator = Self::new(state.seed, batch_size, Some(state.code_seed));
+
+        for _ in 0..state.batches {
+            generator.next_batch_seed();
         }
+
+        for _ in 0..state.code_seeds {
+            generator.next_code_seed();
+        }
+
+        generator
+    }
+
+    pub fn rng_state(&self) -> RngState {
+        self.state
     }
 
-    pub fn generate(&mut self, context: Context) -> BatchWithSeed {
-        let seed = self.batch_gen_rng.next_u64();
+    pub fn next_batch_seed(&mut self) -> Seed {
+        self.state.batches += 1;
+        self.batch_gen_rng.next_u64()
+    }
+
+    pub fn next_code_seed(&mut self) -> Seed {
+        self.state.code_seeds += 1;
+        self.code_seed_gen.next_u64()
+    }
+
+    pub fn generate(&mut self, context: &mut Context) -> BatchWithSeed {
+        let seed = self.next_batch_seed();
         let mut rng = Rng::seed_from_u64(seed);
 
         let spec = rng.next_u64();
 
-        let batch = match spec % 4 {
-            0 => Batch::UploadProgram(
-                (0..self.batch_size)
-                    .map(|_| {
-                        UploadProgramArgs::generate::<Rng>(
-                            self.code_seed_gen.next_u64(),
-                            rng.next_u64(),
-                        )
-                    })
-                    .collect(),
-            ),
+        let batch = match spec % 5 {
+            0 => self.upload_program_batch(&mut rng),
             1 => Batch::UploadCode(
                 (0..self.batch_size)
-                    .map(|_| UploadCodeArgs::generate::<Rng>(self.code_seed_gen.next_u64()))
+                    .map(|_| UploadCodeArgs::generate::<Rng>(self.next_code_seed()))
                     .collect(),
             ),
             2 => {
-                if let Ok(existing_programs) =
-                    NonEmptyVec::try_from_iter(context.programs.iter().copied())
-                {
-                    Batch::SendMessage(
-                        (0..self.batch_size)
-                            .map(|_| {
-                                SendMessageArgs::generate::<Rng>(
-                                    existing_programs.clone(),
-                                    rng.next_u64(),
-                                )
-                            })
-                            .collect(),
-                    )
-                } else {
-                    Batch::UploadProgram(
-                        (0..self.batch_size)
-                            .map(|_| {
-                                UploadProgramArgs::generate::<Rng>(
-                                    self.code_seed_gen.next_u64(),
-                                    rng.next_u64(),
-                                )
+                let destinations: Option<Vec<_>> = (0..self.batch_size)
+                    .map(|_| context.random_program(&mut rng))
+                    .collect();
+
+                match destinations {
+                    Some(destinations) => Batch::SendMessage(
+                        destinations
+                            .into_iter()
+                            .map(|destination| {
+                                SendMessageArgs::generate::<Rng>(destination, rng.next_u64())
                             })
                             .collect(),
-                    )
+                    ),
+                    None => self.upload_program_batch(&mut rng),
                 }
             }
             3 => {
-                if let Ok(existing_codes) =
-                    NonEmptyVec::try_from_iter(context.codes.iter().copied())
-                {
-                    Batch::CreateProgram(
-                        (0..self.batch_size)
-                            .map(|_| {
-                                CreateProgramArgs::generate::<Rng>(
-                                    existing_codes.clone(),
-                                    rng.next_u64(),
-                                )
-                            })
+                let codes: Option<Vec<_>> = (0..self.batch_size)
+                    .map(|_| context.random_code(&mut rng))
+                    .collect();
+
+                match codes {
+                    Some(codes) => Batch::CreateProgram(
+                        codes
+                            .into_iter()
+                            .map(|code| CreateProgramArgs::generate::<Rng>(code, rng.next_u64()))
                             .collect(),
-                    )
-                } else {
-                    Batch::UploadProgram(
-                        (0..self.batch_size)
-                            .map(|_| {
-                                UploadProgramArgs::generate::<Rng>(
-                                    self.code_seed_gen.next_u64(),
-                                    rng.next_u64(),
-                                )
-                            })
+                    ),
+                    None => self.upload_program_batch(&mut rng),
+                }
+            }
+            // Mutants of the known codes are uploaded as new ones with programs
+            // created from them, which stresses validation and instrumentation.
+            4 => {
+                let codes: Option<Vec<_>> = (0..self.batch_size)
+                    .map(|_| {
+                        context.random_code_bytes(&mut rng).map(|code| {
+                            generators::mutate_gear_program::<Rng>(code, rng.next_u64())
+                        })
+                    })
+                    .collect();
+
+                match codes {
+                    Some(codes) => Batch::UploadProgram(
+                        codes
+                            .into_iter()
+                            .map(|code| UploadProgramArgs::with_code::<Rng>(code, rng.next_u64()))
                             .collect(),
-                    )
+                    ),
+                    None => self.upload_program_batch(&mut rng),
                 }
             }
             _ => unreachable!(),
@@ -155,4 +214,12 @@ impl<Rng: LoaderRng> BatchGenerator<Rng> {
 
         (seed, batch).into()
     }
+
+    fn upload_program_batch(&mut self, rng: &mut Rng) -> Batch {
+        Batch::UploadProgram(
+            (0..self.batch_size)
+                .map(|_| UploadProgramArgs::generate::<Rng>(self.next_code_seed(), rng.next_u64()))
+                .collect(),
+        )
+    }
 }
@@ -0,0 +1,288 @@
+//! Pre-flight classification of generated calls.
+//!
+//! Before a batch is submitted, each call is dry-run against the node's runtime
+//! to estimate whether it's going to succeed. Calls expected to fail are still
+//! submitted to cover failure paths, but only outcomes that differ from the
+//! expectation are considered worth attention.
+
+use super::batch::{Batch, CreateProgramArgs, SendMessageArgs, UploadCodeArgs, UploadProgramArgs};
+use gclient::{GasInfo, GearApi, Result};
+use gear_core::ids::{CodeId, ProgramId};
+use std::fmt;
+
+/// Expected outcome of the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expectation {
+    Success,
+    Failure(String),
+}
+
+impl Expectation {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success)
+    }
+
+    fn from_gas_info(info: Result<GasInfo>, gas_limit: u64) -> Self {
+        match info {
+            Ok(GasInfo { min_limit, .. }) if min_limit > gas_limit => Self::Failure(format!(
+                "gas limit {gas_limit} is less than required {min_limit}"
+            )),
+            Ok(_) => Self::Success,
+            Err(e) => Self::Failure(format!("dry-run failed: {e}")),
+        }
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Success => write!(f, "success"),
+            Self::Failure(reason) => write!(f, "failure ({reason})"),
+        }
+    }
+}
+
+/// Classify each call of the batch, keeping the order of calls.
+pub async fn classify(api: &GearApi, batch: &Batch) -> Result<Vec<Expectation>> {
+    let balance = api.free_balance(api.account_id().clone()).await?;
+    let mut expectations = vec![];
+
+    match batch {
+        Batch::UploadProgram(args) => {
+            for args in args {
+                expectations.push(upload_program(api, balance, args).await?);
+            }
+        }
+        Batch::UploadCode(args) => {
+            for args in args {
+                expectations.push(upload_code(api, args).await?);
+            }
+        }
+        Batch::SendMessage(args) => {
+            for args in args {
+                expectations.push(send_message(api, balance, args).await?);
+            }
+        }
+        Batch::CreateProgram(args) => {
+            for args in args {
+                expectations.push(create_program(api, balance, args).await?);
+            }
+        }
+    }
+
+    Ok(expectations)
+}
+
+async fn check_collision(
+    api: &GearApi,
+    code_id: CodeId,
+    salt: &[u8],
+) -> Result<Option<Expectation>> {
+    let id = ProgramId::generate(code_id, salt);
+
+    Ok(api
+        .program_exists(id)
+        .await?
+        .then(|| Expectation::Failure(format!("program {id} already exists"))))
+}
+
+fn check_balance(balance: u128, value: u128) -> Option<Expectation> {
+    (value > balance)
+        .then(|| Expectation::Failure(format!("value {value} exceeds balance {balance}")))
+}
+
+pub async fn upload_program(
+    api: &GearApi,
+    balance: u128,
+    UploadProgramArgs((code, salt, payload, gas_limit, value)): &UploadProgramArgs,
+) -> Result<Expectation> {
+    if let Some(expectation) = check_collision(api, CodeId::generate(code), salt).await? {
+        return Ok(expectation);
+    }
+
+    if let Some(expectation) = check_balance(balance, *value) {
+        return Ok(expectation);
+    }
+
+    let info = api.calculate_upload_gas(code, payload, *value, false).await;
+
+    Ok(Expectation::from_gas_info(info, *gas_limit))
+}
+
+/// Uploading the code, which is already stored, succeeds without changes.
+pub async fn upload_code(_api: &GearApi, _args: &UploadCodeArgs) -> Result<Expectation> {
+    Ok(Expectation::Success)
+}
+
+pub async fn send_message(
+    api: &GearApi,
+    balance: u128,
+    SendMessageArgs((destination, payload, gas_limit, value)): &SendMessageArgs,
+) -> Result<Expectation> {
+    if !api.program_exists(*destination).await? {
+        return Ok(Expectation::Failure(format!(
+            "program {destination} doesn't exist"
+        )));
+    }
+
+    if let Some(expectation) = check_balance(balance, *value) {
+        return Ok(expectation);
+    }
+
+    let info = api
+        .calculate_handle_gas(*destination, payload, *value, false)
+        .await;
+
+    Ok(Expectation::from_gas_info(info, *gas_limit))
+}
+
+pub async fn create_program(
+    api: &GearApi,
+    balance: u128,
+    CreateProgramArgs((code_id, salt, payload, gas_limit, value)): &CreateProgramArgs,
+) -> Result<Expectation> {
+    if !api.code_exists(*code_id).await? {
+        return Ok(Expectation::Failure(format!(
+            "code {code_id} doesn't exist"
+        )));
+    }
+
+    if let Some(expectation) = check_collision(api, *code_id, salt).await? {
+        return Ok(expectation);
+    }
+
+    if let Some(expectation) = check_balance(balance, *value) {
+        return Ok(expectation);
+    }
+
+    let info = api
+        .calculate_create_gas(*code_id, payload, *value, false)
+        .await;
+
+    Ok(Expectation::from_gas_info(info, *gas_limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAS_LIMIT: u64 = 240_000_000_000;
+
+    const WAT: &str = r#"
+        (module
+            (import "env" "memory" (memory 1))
+            (export "init" (func $init))
+            (export "handle" (func $handle))
+            (func $init)
+            (func $handle)
+        )"#;
+
+    fn code() -> Vec<u8> {
+        gclient::wat2wasm(WAT)
+    }
+
+    #[tokio::test]
+    async fn classify_upload_program() -> Result<()> {
+        let api = GearApi::dev().await?;
+        let balance = api.free_balance(api.account_id().clone()).await?;
+
+        let args = UploadProgramArgs((code(), gclient::bytes_now().to_vec(), vec![], GAS_LIMIT, 0));
+        assert!(upload_program(&api, balance, &args).await?.is_success());
+
+        let args = UploadProgramArgs((code(), gclient::bytes_now().to_vec(), vec![], 0, 0));
+        assert!(!upload_program(&api, balance, &args).await?.is_success());
+
+        let args = UploadProgramArgs((
+            vec![0; 32],
+            gclient::bytes_now().to_vec(),
+            vec![],
+            GAS_LIMIT,
+            0,
+        ));
+        assert!(!upload_program(&api, balance, &args).await?.is_success());
+
+        let args = UploadProgramArgs((
+            code(),
+            gclient::bytes_now().to_vec(),
+            vec![],
+            GAS_LIMIT,
+            u128::MAX,
+        ));
+        assert!(!upload_program(&api, balance, &args).await?.is_success());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn classify_send_message() -> Result<()> {
+        let api = GearApi::dev().await?;
+        let balance = api.free_balance(api.account_id().clone()).await?;
+
+        let args = SendMessageArgs((ProgramId::from(u64::MAX), vec![], GAS_LIMIT, 0));
+        assert!(!send_message(&api, balance, &args).await?.is_success());
+
+        let mut listener = api.subscribe().await?;
+        let (mid, pid, _) = api
+            .upload_program_bytes(code(), gclient::bytes_now(), "", GAS_LIMIT, 0)
+            .await?;
+        assert!(listener.message_processed(mid).await?.succeed());
+
+        let args = SendMessageArgs((pid, vec![], GAS_LIMIT, 0));
+        assert!(send_message(&api, balance, &args).await?.is_success());
+
+        let args = SendMessageArgs((pid, vec![], 0, 0));
+        assert!(!send_message(&api, balance, &args).await?.is_success());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn classify_code() -> Result<()> {
+        let api = GearApi::dev().await?;
+        let balance = api.free_balance(api.account_id().clone()).await?;
+
+        let code = code();
+        let code_id = CodeId::generate(&code);
+
+        let args =
+            CreateProgramArgs((code_id, gclient::bytes_now().to_vec(), vec![], GAS_LIMIT, 0));
+        if !api.code_exists(code_id).await? {
+            assert!(!create_program(&api, balance, &args).await?.is_success());
+            assert!(upload_code(&api, &UploadCodeArgs(code.clone()))
+                .await?
+                .is_success());
+
+            api.upload_code(&code).await?;
+        }
+
+        assert!(upload_code(&api, &UploadCodeArgs(code)).await?.is_success());
+        assert!(create_program(&api, balance, &args).await?.is_success());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn classify_collision() -> Result<()> {
+        let api = GearApi::dev().await?;
+        let balance = api.free_balance(api.account_id().clone()).await?;
+
+        let code = code();
+        let code_id = CodeId::generate(&code);
+        let salt = gclient::bytes_now().to_vec();
+
+        let mut listener = api.subscribe().await?;
+        let (mid, _, _) = api
+            .upload_program_bytes(code.clone(), &salt, "", GAS_LIMIT, 0)
+            .await?;
+        assert!(listener.message_processed(mid).await?.succeed());
+
+        // Intentional collision with the uploaded program.
+        let args = UploadProgramArgs((code, salt.clone(), vec![], GAS_LIMIT, 0));
+        assert!(!upload_program(&api, balance, &args).await?.is_success());
+
+        let args = CreateProgramArgs((code_id, salt, vec![], GAS_LIMIT, 0));
+        assert!(!create_program(&api, balance, &args).await?.is_success());
+
+        Ok(())
+    }
+}
@@ -1,18 +1,25 @@
-use super::context::ContextUpdate;
-use gclient::Error;
+use super::{context::ContextUpdate, throttle::Sample};
+use gclient::{Error, Verification};
 use gear_core::ids::{CodeId, ProgramId};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 // Todo DN maybe queue for guaranteeing the order?
 pub type PreRunReport = Vec<String>;
 
 pub struct Report {
     pub logs: Vec<String>,
-    // todo Option
-    pub codes: BTreeSet<CodeId>,
+    /// Logs of outcomes which differ from the pre-flight expectation.
+    pub unexpected: Vec<String>,
+    /// Uploaded codes with their bytes, so they could be mutated later.
+    pub codes: BTreeMap<CodeId, Vec<u8>>,
     // todo Option
     pub program_ids: BTreeSet<ProgramId>,
     pub blocks_stopped: bool,
+    /// Milliseconds the batch took to be included in a block.
+    pub latency: u64,
+    /// Outcomes of the tasks by their index in the batch,
+    /// with the error of the failed ones.
+    pub outcomes: BTreeMap<usize, Option<String>>,
 }
 
 #[derive(Default)]
@@ -21,6 +28,12 @@ pub struct BatchRunReport {
     pub reports: Vec<String>,
     pub context_update: ContextUpdate,
     pub blocks_stopped: bool,
+    /// Pending finality verifications of the batch extrinsics.
+    pub verifications: Vec<Verification>,
+    /// Outcomes of the batch differ on the secondary node.
+    pub diverged: bool,
+    /// Outcome of the submission the pool is throttled on.
+    pub sample: Option<Sample>,
 }
 
 impl BatchRunReport {
@@ -31,10 +44,18 @@ impl BatchRunReport {
         reports.push(String::from("RESULTS:"));
         reports.append(report.logs.as_mut());
 
+        if !report.unexpected.is_empty() {
+            reports.push(String::from("UNEXPECTED:"));
+            reports.append(report.unexpected.as_mut());
+        }
+
         Self {
             reports,
             blocks_stopped: report.blocks_stopped,
+            sample: Some(Sample::Latency(report.latency)),
             context_update: report.into(),
+            verifications: vec![],
+            diverged: false,
         }
     }
 
@@ -47,6 +68,7 @@ impl BatchRunReport {
 
         Self {
             reports,
+            sample: Sample::from_error(&err),
             ..Default::default()
         }
     }
@@ -0,0 +1,127 @@
+//! Session of the loader run, saved on interruption to be resumed later.
+//!
+//! The session file starts with the version of its format followed by the
+//! SCALE encoded [`Session`], so files of older versions can be recognized
+//! and migrated once the format changes.
+
+use super::{context::Context, generators::RngState};
+use anyhow::anyhow;
+use codec::{Decode, Encode};
+use gclient::Result;
+use gear_core::ids::{CodeId, ProgramId};
+use std::{fs, path::Path};
+
+/// Current version of the session file format.
+pub const SESSION_VERSION: u8 = 1;
+
+/// State of the run needed to continue it without warming up again.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Session {
+    /// Known programs in the order of their insertion.
+    pub programs: Vec<ProgramId>,
+    /// Known codes with their bytes in the order of their insertion.
+    pub codes: Vec<(CodeId, Vec<u8>)>,
+    /// State of the batches generator.
+    pub rng: RngState,
+}
+
+impl Session {
+    pub fn new(context: &Context, rng: RngState) -> Self {
+        Self {
+            programs: context.programs(),
+            codes: context.codes(),
+            rng,
+        }
+    }
+
+    pub fn encode_versioned(&self) -> Vec<u8> {
+        (SESSION_VERSION, self).encode()
+    }
+
+    pub fn decode_versioned(mut bytes: &[u8]) -> Result<Self> {
+        let version = u8::decode(&mut bytes).map_err(|e| anyhow!("Invalid session: {e}"))?;
+
+        match version {
+            SESSION_VERSION => {
+                Session::decode(&mut bytes).map_err(|e| anyhow!("Invalid session: {e}").into())
+            }
+            v => Err(anyhow!("Unsupported session version {v}, expected {SESSION_VERSION}").into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.encode_versioned())?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::decode_versioned(&fs::read(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{args::SeedVariant, batch_pool::generators::BatchGenerator};
+    use rand::rngs::SmallRng;
+
+    fn session() -> Session {
+        Session {
+            programs: vec![ProgramId::from(3), ProgramId::from(1)],
+            codes: vec![(CodeId::from(7), vec![0, 97, 115, 109])],
+            rng: RngState {
+                seed: 42,
+                batches: 5,
+                code_seed: SeedVariant::Dynamic(8),
+                code_seeds: 11,
+            },
+        }
+    }
+
+    #[test]
+    fn session_round_trip() {
+        let session = session();
+        let bytes = session.encode_versioned();
+
+        assert_eq!(bytes[0], SESSION_VERSION);
+        assert_eq!(Session::decode_versioned(&bytes).unwrap(), session);
+    }
+
+    #[test]
+    fn unsupported_version_rejected() {
+        let mut bytes = session().encode_versioned();
+        bytes[0] = SESSION_VERSION + 1;
+
+        assert!(Session::decode_versioned(&bytes).is_err());
+        assert!(Session::decode_versioned(&[]).is_err());
+    }
+
+    #[test]
+    fn context_order_preserved() {
+        let session = session();
+        let context = Context::restore(4, session.programs.clone(), session.codes.clone());
+
+        assert_eq!(Session::new(&context, session.rng), session);
+    }
+
+    #[test]
+    fn generator_resumed() {
+        for code_seed in [SeedVariant::Dynamic(8), SeedVariant::Constant(8)] {
+            let mut generator = BatchGenerator::<SmallRng>::new(42, 4, Some(code_seed));
+
+            for _ in 0..3 {
+                generator.next_batch_seed();
+                generator.next_code_seed();
+            }
+            generator.next_code_seed();
+
+            let state = generator.rng_state();
+            let mut resumed = BatchGenerator::<SmallRng>::from_rng_state(state, 4);
+
+            assert_eq!(resumed.rng_state(), state);
+            assert_eq!(resumed.next_batch_seed(), generator.next_batch_seed());
+            assert_eq!(resumed.next_code_seed(), generator.next_code_seed());
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Adaptive throttling of batches in flight.
+//!
+//! The pool keeps no more batches in flight than [`Throttle::rate`] allows.
+//! The rate is adapted with AIMD: it grows by one while the p95 inclusion
+//! latency of batches stays within the target, and halves once the latency
+//! exceeds the target or the node's transaction pool is full. So the node is
+//! kept saturated, but not wedged by the growing pool of extrinsics.
+
+use gclient::Error;
+use std::fmt;
+
+/// Minimum amount of latency samples the rate is adapted on.
+const MIN_SAMPLES: usize = 5;
+
+/// Outcome of the batch submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sample {
+    /// Extrinsics of the batch were included in a block within the given
+    /// amount of milliseconds.
+    Latency(u64),
+    /// Node rejected the batch, because its transaction pool is full.
+    PoolFull,
+}
+
+impl Sample {
+    /// Sample of the failed submission, if the failure signals the overload.
+    pub fn from_error(err: &Error) -> Option<Self> {
+        let err = err.to_string();
+
+        (err.contains("Immediately Dropped") || err.contains("pool is full"))
+            .then_some(Self::PoolFull)
+    }
+}
+
+/// Reason of the rate change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    WithinTarget { p95: u64 },
+    AboveTarget { p95: u64 },
+    PoolFull,
+}
+
+/// Change of the rate, recorded in the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateChange {
+    pub from: usize,
+    pub to: usize,
+    pub reason: Reason,
+}
+
+impl fmt::Display for RateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { from, to, reason } = self;
+
+        write!(f, "batches in flight {from} -> {to}: ")?;
+
+        match reason {
+            Reason::WithinTarget { p95 } => write!(f, "p95 latency {p95} ms is within target"),
+            Reason::AboveTarget { p95 } => write!(f, "p95 latency {p95} ms exceeds target"),
+            Reason::PoolFull => write!(f, "transaction pool is full"),
+        }
+    }
+}
+
+/// AIMD controller of the amount of batches in flight.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    rate: usize,
+    max_rate: usize,
+    target_p95: u64,
+    samples: Vec<u64>,
+    last_p95: Option<u64>,
+}
+
+impl Throttle {
+    /// Create the controller starting with `rate` batches in flight,
+    /// which is never adapted above `max_rate`.
+    ///
+    /// `target_p95` is the p95 inclusion latency in milliseconds.
+    pub fn new(rate: usize, max_rate: usize, target_p95: u64) -> Self {
+        let max_rate = max_rate.max(1);
+
+        Self {
+            rate: rate.clamp(1, max_rate),
+            max_rate,
+            target_p95,
+            samples: vec![],
+            last_p95: None,
+        }
+    }
+
+    /// Amount of batches allowed to be in flight.
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// The latest p95 latency the rate was adapted on.
+    pub fn last_p95(&self) -> Option<u64> {
+        self.last_p95
+    }
+
+    /// Record outcome of the batch, adapting the rate.
+    ///
+    /// The rate is adapted once enough latency samples are collected since
+    /// the previous adaptation, so every decision relies on fresh samples.
+    pub fn record(&mut self, sample: Sample) -> Option<RateChange> {
+        let latency = match sample {
+            Sample::PoolFull => return self.change(self.rate / 2, Reason::PoolFull),
+            Sample::Latency(latency) => latency,
+        };
+
+        self.samples.push(latency);
+
+        if self.samples.len() < self.rate.max(MIN_SAMPLES) {
+            return None;
+        }
+
+        let p95 = p95(&self.samples);
+        self.last_p95 = Some(p95);
+
+        if p95 > self.target_p95 {
+            self.change(self.rate / 2, Reason::AboveTarget { p95 })
+        } else {
+            self.change(self.rate + 1, Reason::WithinTarget { p95 })
+        }
+    }
+
+    fn change(&mut self, rate: usize, reason: Reason) -> Option<RateChange> {
+        self.samples.clear();
+
+        let from = self.rate;
+        self.rate = rate.clamp(1, self.max_rate);
+
+        (from != self.rate).then_some(RateChange {
+            from,
+            to: self.rate,
+            reason,
+        })
+    }
+}
+
+// Nearest-rank percentile.
+fn p95(samples: &[u64]) -> u64 {
+    let mut samples = samples.to_vec();
+    samples.sort_unstable();
+
+    let rank = (samples.len() * 95 + 99) / 100;
+    samples[rank.max(1) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: u64 = 1_000;
+
+    // Node including extrinsics slower the more batches are in flight.
+    fn simulated_latency(rate: usize, tick: u64) -> u64 {
+        let jitter = tick * 7919 % 100;
+        rate as u64 * 100 + jitter
+    }
+
+    #[test]
+    fn p95_is_nearest_rank() {
+        assert_eq!(p95(&[5]), 5);
+        assert_eq!(p95(&(1..=100).rev().collect::<Vec<_>>()), 95);
+        assert_eq!(p95(&(1..=10).collect::<Vec<_>>()), 10);
+    }
+
+    #[test]
+    fn grows_within_target() {
+        let mut throttle = Throttle::new(1, 4, TARGET);
+
+        let changes: Vec<_> = (0..100)
+            .filter_map(|_| throttle.record(Sample::Latency(TARGET / 2)))
+            .collect();
+
+        assert_eq!(throttle.rate(), 4);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().all(|change| change.to == change.from + 1
+            && change.reason == Reason::WithinTarget { p95: TARGET / 2 }));
+    }
+
+    #[test]
+    fn halves_above_target_and_on_full_pool() {
+        let mut throttle = Throttle::new(16, 16, TARGET);
+
+        for _ in 0..15 {
+            assert_eq!(throttle.record(Sample::Latency(TARGET * 2)), None);
+        }
+        assert_eq!(
+            throttle.record(Sample::Latency(TARGET * 2)),
+            Some(RateChange {
+                from: 16,
+                to: 8,
+                reason: Reason::AboveTarget { p95: TARGET * 2 },
+            })
+        );
+
+        assert_eq!(
+            throttle.record(Sample::PoolFull),
+            Some(RateChange {
+                from: 8,
+                to: 4,
+                reason: Reason::PoolFull,
+            })
+        );
+
+        throttle.record(Sample::PoolFull);
+        throttle.record(Sample::PoolFull);
+        assert_eq!(throttle.rate(), 1);
+        assert_eq!(throttle.record(Sample::PoolFull), None);
+    }
+
+    #[test]
+    fn converges_to_target_latency() {
+        let mut throttle = Throttle::new(1, 64, TARGET);
+
+        for tick in 0..10_000 {
+            throttle.record(Sample::Latency(simulated_latency(throttle.rate(), tick)));
+
+            // The node sustains 9 batches in flight within the target latency,
+            // so after warming up the rate oscillates in between halving
+            // the overload and growing back.
+            if tick > 1_000 {
+                assert!((4..=10).contains(&throttle.rate()));
+            }
+        }
+
+        assert!(throttle.last_p95().is_some());
+    }
+}
@@ -6,7 +6,7 @@
 //! That's why generated data is called semi-random.
 
 use args::{parse_cli_params, LoadParams, Params};
-use batch_pool::{generators, BatchPool};
+use batch_pool::{generators, BatchPool, Throttle};
 use gclient::{GearApi, Result};
 use rand::rngs::SmallRng;
 
@@ -32,9 +32,24 @@ async fn run(params: Params) -> Result<()> {
 async fn load_node(params: LoadParams) -> Result<()> {
     let api = GearApi::init(utils::str_to_wsaddr(params.endpoint)).await?;
 
-    BatchPool::<SmallRng>::new(api, params.workers, params.batch_size)
-        .run(params.code_seed_type)
-        .await?;
+    let throttle = Throttle::new(params.workers, params.max_workers, params.target_latency);
 
-    unreachable!()
+    let mut pool = BatchPool::<SmallRng>::new(
+        api,
+        throttle,
+        params.batch_size,
+        params.context_capacity.get(),
+        params.session_file,
+    );
+
+    if let Some(endpoint) = params.diff_endpoint {
+        let diff_api = GearApi::init(utils::str_to_wsaddr(endpoint)).await?;
+        pool = pool.with_diff_api(diff_api);
+    }
+
+    if let Some(path) = params.resume_session {
+        pool = pool.resume_session(&path).await?;
+    }
+
+    pool.run(params.seed, params.code_seed_type).await
 }
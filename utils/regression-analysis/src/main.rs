@@ -340,6 +340,40 @@ fn weights(kind: WeightsKind, input_file: PathBuf, output_file: PathBuf) {
                     gr_wake,
                     gr_create_program_wgas,
                     gr_create_program_wgas_per_byte,
+                    gr_new_handle,
+                    gr_delayed_eta,
+                    gr_reserve_value,
+                    gr_unreserve_value,
+                    gr_context_limits,
+                    gr_children_count,
+                    gr_bump_counter,
+                    gr_is_init,
+                    gr_signal_details,
+                    gr_forward,
+                    gr_forward_per_byte,
+                    gr_block_hash,
+                    gr_stack_limit,
+                    gr_split_value_reservation,
+                    gr_tx_hash,
+                    gr_set_default_gas_limit,
+                    gr_max_payload_size,
+                    gr_resumed_from_wait,
+                    gr_children_gas_burned,
+                    gr_wait_if,
+                    gr_source_code_id,
+                    gr_wait_budget_remaining,
+                    gr_spendable_value,
+                    gr_block_duration,
+                    gr_schedule_exit,
+                    gr_message_meta,
+                    gr_gas_allowance,
+                    gr_mailbox_contains,
+                    gr_codes_count,
+                    gr_trace,
+                    gr_trace_per_byte,
+                    gr_execution_id,
+                    gr_set_entry_budget,
+                    gr_set_charge_allowance,
                     gas,
                 }
             }
@@ -24,6 +24,7 @@ use crate::{cargo_command::CargoCommand, wasm_project::WasmProject};
 mod builder_error;
 mod cargo_command;
 mod crate_info;
+pub mod metahash;
 pub mod optimize;
 mod stack_end;
 mod wasm_project;
@@ -0,0 +1,194 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, Result};
+use pwasm_utils::parity_wasm::elements::{
+    CustomSection, FuncBody, ImportCountType, Instruction, Instructions, Internal, Module, Section,
+};
+use std::cell::RefCell;
+use wasmi::{
+    memory_units::Pages, Error as WasmiError, Externals, FuncInstance, FuncRef, ImportsBuilder,
+    MemoryDescriptor, MemoryInstance, MemoryRef, ModuleImportResolver, ModuleInstance, RuntimeArgs,
+    RuntimeValue, Signature, Trap, TrapCode,
+};
+
+/// Name of the custom section keeping the metadata hash of a program.
+pub const METAHASH_SECTION: &str = "metahash";
+
+const META_HASH_EXPORT: &str = "meta_hash";
+const METADATA_HASH_FN: &str = "__gstd_metadata_hash";
+
+const ALLOC_INDEX: usize = 0;
+const FREE_INDEX: usize = 1;
+const UNSUPPORTED_INDEX: usize = 2;
+
+/// Read the metadata hash exported by the `*.meta.wasm` binary.
+///
+/// Returns `None` if the program doesn't declare its metadata.
+pub fn read_metahash(meta: &[u8]) -> Result<Option<[u8; 32]>> {
+    let module = wasmi::Module::from_buffer(meta)?;
+
+    let resolver = Resolver::default();
+    let instance = ModuleInstance::new(
+        &module,
+        &ImportsBuilder::new().with_resolver("env", &resolver),
+    )?
+    .assert_no_start();
+
+    if instance.export_by_name(META_HASH_EXPORT).is_none() {
+        return Ok(None);
+    }
+
+    let memory = resolver
+        .memory
+        .borrow()
+        .clone()
+        .ok_or_else(|| anyhow!("`*.meta.wasm` doesn't import memory"))?;
+    let mut externals = MetaExternals {
+        memory: memory.clone(),
+    };
+
+    let at = match instance.invoke_export(META_HASH_EXPORT, &[], &mut externals)? {
+        Some(RuntimeValue::I32(at)) => at as u32,
+        _ => return Err(anyhow!("`{}` returned no pointer", META_HASH_EXPORT)),
+    };
+
+    let ptr_and_len = memory.get(at, 8)?;
+    let ptr = u32::from_le_bytes(ptr_and_len[..4].try_into()?);
+    let len = u32::from_le_bytes(ptr_and_len[4..].try_into()?);
+
+    let hex_hash = memory.get(ptr, len as usize)?;
+    let mut hash = [0; 32];
+    hex::decode_to_slice(hex_hash, &mut hash)?;
+
+    Ok(Some(hash))
+}
+
+/// Embed the metadata hash into the program `module`.
+///
+/// The hash is stored in the [`METAHASH_SECTION`] custom section, and the
+/// body of `__gstd_metadata_hash` is replaced with storing the hash, so the
+/// program doesn't build the type registry to get its own hash.
+pub fn insert_metahash(module: &mut Module, hash: [u8; 32]) -> Result<(), &str> {
+    let imported_funcs = module.import_count(ImportCountType::Function);
+
+    let func_index = module
+        .export_section()
+        .ok_or("Cannot find export section")?
+        .entries()
+        .iter()
+        .find_map(|entry| match entry.internal() {
+            Internal::Function(index) if entry.field() == METADATA_HASH_FN => Some(*index),
+            _ => None,
+        });
+
+    if let Some(func_index) = func_index {
+        let body_index = (func_index as usize)
+            .checked_sub(imported_funcs)
+            .ok_or("`__gstd_metadata_hash` is imported")?;
+
+        let mut instructions = vec![];
+        for (offset, chunk) in hash.chunks(8).enumerate() {
+            let chunk = u64::from_le_bytes(chunk.try_into().expect("Chunks have size of 8; qed"));
+            instructions.extend([
+                Instruction::GetLocal(0),
+                Instruction::I64Const(chunk as i64),
+                Instruction::I64Store(0, offset as u32 * 8),
+            ]);
+        }
+        instructions.push(Instruction::End);
+
+        let body = module
+            .code_section_mut()
+            .ok_or("Cannot find code section")?
+            .bodies_mut()
+            .get_mut(body_index)
+            .ok_or("Cannot find `__gstd_metadata_hash` body")?;
+        *body = FuncBody::new(vec![], Instructions::new(instructions));
+    }
+
+    module
+        .sections_mut()
+        .push(Section::Custom(CustomSection::new(
+            METAHASH_SECTION.to_string(),
+            hash.to_vec(),
+        )));
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Resolver {
+    memory: RefCell<Option<MemoryRef>>,
+}
+
+impl ModuleImportResolver for Resolver {
+    fn resolve_func(&self, field_name: &str, signature: &Signature) -> Result<FuncRef, WasmiError> {
+        let index = match field_name {
+            "alloc" => ALLOC_INDEX,
+            "free" => FREE_INDEX,
+            _ => UNSUPPORTED_INDEX,
+        };
+
+        Ok(FuncInstance::alloc_host(signature.clone(), index))
+    }
+
+    fn resolve_memory(
+        &self,
+        field_name: &str,
+        descriptor: &MemoryDescriptor,
+    ) -> Result<MemoryRef, WasmiError> {
+        if field_name != "memory" {
+            return Err(WasmiError::Instantiation(format!(
+                "Memory export env:{} not found",
+                field_name
+            )));
+        }
+
+        let memory = MemoryInstance::alloc(Pages(descriptor.initial() as usize), None)?;
+        *self.memory.borrow_mut() = Some(memory.clone());
+
+        Ok(memory)
+    }
+}
+
+struct MetaExternals {
+    memory: MemoryRef,
+}
+
+impl Externals for MetaExternals {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        match index {
+            ALLOC_INDEX => {
+                let pages: u32 = args.nth_checked(0)?;
+                let previous = self
+                    .memory
+                    .grow(Pages(pages as usize))
+                    .map_err(|_| Trap::Code(TrapCode::MemoryAccessOutOfBounds))?;
+
+                Ok(Some(RuntimeValue::I32(previous.0 as i32)))
+            }
+            FREE_INDEX => Ok(None),
+            _ => Err(Trap::Code(TrapCode::Unreachable)),
+        }
+    }
+}
@@ -12,7 +12,7 @@ use std::{
     process::Command,
 };
 
-const META_EXPORTS: [&str; 13] = [
+const META_EXPORTS: [&str; 14] = [
     "meta_init_input",
     "meta_init_output",
     "meta_async_init_input",
@@ -22,17 +22,19 @@ const META_EXPORTS: [&str; 13] = [
     "meta_async_handle_input",
     "meta_async_handle_output",
     "meta_registry",
+    "meta_hash",
     "meta_title",
     "meta_state",
     "meta_state_input",
     "meta_state_output",
 ];
-const OPTIMIZED_EXPORTS: [&str; 5] = [
+const OPTIMIZED_EXPORTS: [&str; 6] = [
     "handle",
     "handle_reply",
     "handle_signal",
     "init",
     "__gear_stack_end",
+    "__heap_base",
 ];
 
 /// Type of the output wasm.
@@ -74,13 +76,19 @@ impl Optimizer {
     /// Strips all custom sections.
     ///
     /// Presently all custom sections are not required so they can be stripped safely.
-    /// The name section is already stripped by `wasm-opt`.
+    /// The name section is already stripped by `wasm-opt`. The metadata hash section
+    /// is inserted afterwards by [`Optimizer::insert_metahash`].
     pub fn strip_custom_sections(&mut self) {
         self.module
             .sections_mut()
             .retain(|section| !matches!(section, Section::Reloc(_) | Section::Custom(_)))
     }
 
+    pub fn insert_metahash(&mut self, hash: [u8; 32]) {
+        let _ = crate::metahash::insert_metahash(&mut self.module, hash)
+            .map_err(|s| log::debug!("{}", s));
+    }
+
     /// Process optimization.
     pub fn optimize(&self, ty: OptType) -> Result<Vec<u8>> {
         let mut module = self.module.clone();
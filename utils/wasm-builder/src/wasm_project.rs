@@ -228,14 +228,21 @@ pub const WASM_BINARY_META: &[u8] = include_bytes!("{}");
         optimizer.insert_stack_and_export();
         optimizer.strip_custom_sections();
 
+        // Generate *.meta.wasm.
+        let meta = optimizer.optimize(OptType::Meta)?;
+        fs::write(to_meta, &meta)?;
+
+        // Embed the metadata hash computed by *.meta.wasm.
+        if let Some(hash) =
+            crate::metahash::read_metahash(&meta).context("unable to read the metadata hash")?
+        {
+            optimizer.insert_metahash(hash);
+        }
+
         // Generate *.opt.wasm.
         let opt = optimizer.optimize(OptType::Opt)?;
         fs::write(to_opt, opt)?;
 
-        // Generate *.meta.wasm.
-        let meta = optimizer.optimize(OptType::Meta)?;
-        fs::write(to_meta, meta)?;
-
         Ok(())
     }
 }
@@ -1,11 +1,32 @@
 #![no_std]
 
-use gstd::{debug, msg};
+use gstd::{debug, metadata_hash, msg, prelude::*};
+
+gstd::metadata! {
+    title: "Test program",
+    handle:
+        input: Vec<u8>,
+        output: Vec<u8>,
+}
 
 #[no_mangle]
 unsafe extern "C" fn handle() {
     debug!("handle()");
-    msg::reply_bytes("Hello world!", 0).unwrap();
+    if msg::load_bytes() == b"METAHASH" {
+        msg::reply(metadata_hash(), 0).unwrap();
+    } else {
+        msg::reply_bytes("Hello world!", 0).unwrap();
+    }
+}
+
+#[cfg(test)]
+fn expected_metahash() -> [u8; 32] {
+    use gstd::util::{to_metahash, MetaType};
+
+    to_metahash(
+        &["", "", "", "", "Vec<u8>", "Vec<u8>", "", "", "", ""],
+        vec![MetaType::new::<Vec<u8>>(), MetaType::new::<Vec<u8>>()],
+    )
 }
 
 #[no_mangle]
@@ -36,18 +57,38 @@ mod gtest_tests {
                 .dest(123)
                 .payload_bytes("Hello world!")
         ));
+
+        let res = this_program.send_bytes(123, "METAHASH");
+        assert!(res.contains(
+            &Log::builder()
+                .source(1)
+                .dest(123)
+                .payload(super::expected_metahash())
+        ));
     }
 }
 
 #[cfg(test)]
 mod tests {
     extern crate std;
+    use parity_wasm::elements::Module;
     use std::fs;
 
     mod code {
         include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
     }
 
+    #[test]
+    fn metahash_section() {
+        let module: Module = parity_wasm::deserialize_buffer(code::WASM_BINARY_OPT).unwrap();
+        let section = module
+            .custom_sections()
+            .find(|section| section.name() == "metahash")
+            .expect("Metadata hash section not found");
+
+        assert_eq!(section.payload(), super::expected_metahash());
+    }
+
     #[test]
     #[cfg(debug_assertions)]
     fn debug_wasm() {
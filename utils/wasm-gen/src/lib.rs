@@ -28,6 +28,9 @@ use parity_wasm::{
 };
 use wasm_smith::{InstructionKind::*, InstructionKinds, Module as ModuleSmith, SwarmConfig};
 
+mod mutate;
+pub use mutate::{mutate_gear_program_code, mutate_gear_program_module};
+
 mod syscalls;
 use syscalls::{sys_calls_table, SyscallsConfig};
 
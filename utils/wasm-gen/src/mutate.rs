@@ -0,0 +1,158 @@
+// This file is part of Gear.
+
+// Copyright (C) 2022 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mutations of the generated codes, keeping them loadable.
+//!
+//! Mutation changes a single instruction of a function body: either a value
+//! of a constant or an opcode to another one of the same signature. So the
+//! mutated code stays valid, while its behavior diverges from the original,
+//! which exercises instrumentation and validation with near-valid codes.
+
+use arbitrary::{Result, Unstructured};
+use parity_wasm::elements::{Instruction, Module};
+
+// Groups of interchangeable opcodes, which pop and push the same types.
+const I32_BINARY: &[Instruction] = &[
+    Instruction::I32Add,
+    Instruction::I32Sub,
+    Instruction::I32Mul,
+    Instruction::I32And,
+    Instruction::I32Or,
+    Instruction::I32Xor,
+    Instruction::I32Shl,
+    Instruction::I32ShrS,
+    Instruction::I32ShrU,
+    Instruction::I32Rotl,
+    Instruction::I32Rotr,
+];
+
+const I64_BINARY: &[Instruction] = &[
+    Instruction::I64Add,
+    Instruction::I64Sub,
+    Instruction::I64Mul,
+    Instruction::I64And,
+    Instruction::I64Or,
+    Instruction::I64Xor,
+    Instruction::I64Shl,
+    Instruction::I64ShrS,
+    Instruction::I64ShrU,
+    Instruction::I64Rotl,
+    Instruction::I64Rotr,
+];
+
+const I32_COMPARISON: &[Instruction] = &[
+    Instruction::I32Eq,
+    Instruction::I32Ne,
+    Instruction::I32LtS,
+    Instruction::I32LtU,
+    Instruction::I32GtS,
+    Instruction::I32GtU,
+    Instruction::I32LeS,
+    Instruction::I32LeU,
+    Instruction::I32GeS,
+    Instruction::I32GeU,
+];
+
+const I64_COMPARISON: &[Instruction] = &[
+    Instruction::I64Eq,
+    Instruction::I64Ne,
+    Instruction::I64LtS,
+    Instruction::I64LtU,
+    Instruction::I64GtS,
+    Instruction::I64GtU,
+    Instruction::I64LeS,
+    Instruction::I64LeU,
+    Instruction::I64GeS,
+    Instruction::I64GeU,
+];
+
+const GROUPS: &[&[Instruction]] = &[I32_BINARY, I64_BINARY, I32_COMPARISON, I64_COMPARISON];
+
+fn is_mutable(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::I32Const(_) | Instruction::I64Const(_)
+    ) || GROUPS.iter().any(|group| group.contains(instruction))
+}
+
+fn mutate_instruction(u: &mut Unstructured, instruction: &mut Instruction) -> Result<()> {
+    match instruction {
+        Instruction::I32Const(value) => *value = u.arbitrary()?,
+        Instruction::I64Const(value) => *value = u.arbitrary()?,
+        _ => {
+            let group = GROUPS
+                .iter()
+                .find(|group| group.contains(instruction))
+                .expect("only mutable instructions are passed");
+
+            let others: Vec<_> = group
+                .iter()
+                .filter(|&other| other != &*instruction)
+                .collect();
+
+            *instruction = (*u.choose(&others)?).clone();
+        }
+    }
+
+    Ok(())
+}
+
+/// Mutate a single instruction of the `module`.
+///
+/// Returns `false` if the module has no instructions to mutate
+/// or `u` has not enough data.
+pub fn mutate_gear_program_module(u: &mut Unstructured, module: &mut Module) -> bool {
+    let bodies = match module.code_section_mut() {
+        Some(section) => section.bodies_mut(),
+        None => return false,
+    };
+
+    let positions: Vec<_> = bodies
+        .iter()
+        .enumerate()
+        .flat_map(|(body_idx, body)| {
+            body.code()
+                .elements()
+                .iter()
+                .enumerate()
+                .filter(|(_, instruction)| is_mutable(instruction))
+                .map(move |(instr_idx, _)| (body_idx, instr_idx))
+        })
+        .collect();
+
+    let (body_idx, instr_idx) = match u.choose(&positions) {
+        Ok(&position) => position,
+        Err(_) => return false,
+    };
+
+    let instruction = &mut bodies[body_idx].code_mut().elements_mut()[instr_idx];
+
+    mutate_instruction(u, instruction).is_ok()
+}
+
+/// Mutate a single instruction of the `code`.
+///
+/// Returns `None` if the code can't be decoded, has no instructions
+/// to mutate or `u` has not enough data.
+pub fn mutate_gear_program_code(u: &mut Unstructured, code: &[u8]) -> Option<Vec<u8>> {
+    let mut module: Module = parity_wasm::deserialize_buffer(code).ok()?;
+
+    mutate_gear_program_module(u, &mut module)
+        .then(|| parity_wasm::serialize(module).ok())
+        .flatten()
+}
@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{gen_gear_program_code, GearConfig};
+use crate::{gen_gear_program_code, mutate_gear_program_code, GearConfig};
 use arbitrary::Unstructured;
 use rand::{rngs::SmallRng, RngCore, SeedableRng};
 
@@ -59,3 +59,21 @@ fn gen_wasm_valid() {
         wasmparser::validate(&code).unwrap();
     }
 }
+
+#[test]
+fn mutated_wasm_valid() {
+    let mut rng = SmallRng::seed_from_u64(44444);
+    for _ in 0..100 {
+        let mut buf = vec![0; 1000000];
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let code = gen_gear_program_code(&mut u, GearConfig::new_valid());
+
+        let mut buf = vec![0; 100];
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        if let Some(mutated) = mutate_gear_program_code(&mut u, &code) {
+            wasmparser::validate(&mutated).unwrap();
+        }
+    }
+}